@@ -0,0 +1,371 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use log::error;
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::{config::BatchConfig, read_config};
+use freyja_common::{
+    cloud_adapter::{
+        Acknowledgement, Acknowledger, BackfillRequest, CloudAdapter, CloudAdapterError,
+        CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// The metadata key for the number of messages in a batch
+pub const BATCH_SIZE_METADATA_KEY: &str = "batch_size";
+
+/// The messages waiting to be sent in a batch
+#[derive(Default)]
+struct PendingBatch {
+    /// The messages, in the order in which they were received, with the acknowledgers which report whether they were sent
+    messages: Vec<(CloudMessageRequest, Acknowledger)>,
+
+    /// The time at which the oldest message was received
+    started_at: Option<Instant>,
+}
+
+/// A cloud adapter decorator which combines messages into batches to reduce the number of requests.
+/// Messages with the same metadata are combined into one message whose value is a JSON array of
+/// the original messages, and whose metadata also has a `batch_size` entry.
+/// A batch is sent when it's full, or with the next message once its oldest message is older than
+/// the configured delay.
+/// Batches which fail to send are dropped, so this decorator should wrap a `RetryCloudAdapter`
+/// if failed batches must be retried.
+/// Each message is acknowledged once its batch is sent, so the emitter can tell which messages were dropped.
+pub struct BatchingCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The batch config
+    config: BatchConfig,
+
+    /// The messages waiting to be sent
+    pending: std::sync::Mutex<PendingBatch>,
+}
+
+impl<TInner> BatchingCloudAdapter<TInner> {
+    /// Creates a new BatchingCloudAdapter
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the batch config
+    pub fn new(inner: TInner, config: BatchConfig) -> Self {
+        Self {
+            inner,
+            config,
+            pending: std::sync::Mutex::new(PendingBatch::default()),
+        }
+    }
+
+    /// Adds a message to the pending batch.
+    /// Returns the acknowledgement of the message, and the pending messages if the batch is ready to be sent.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: the message to add
+    fn push(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> (
+        Acknowledgement,
+        Option<Vec<(CloudMessageRequest, Acknowledger)>>,
+    ) {
+        let max_batch_delay = Duration::from_millis(self.config.max_batch_delay_ms);
+        let mut pending = self.pending.lock().unwrap();
+
+        let (acknowledger, acknowledgement) = Acknowledgement::pending();
+        let started_at = *pending.started_at.get_or_insert_with(Instant::now);
+        pending.messages.push((cloud_message, acknowledger));
+
+        if pending.messages.len() >= self.config.batch_size
+            || started_at.elapsed() >= max_batch_delay
+        {
+            (
+                acknowledgement,
+                Some(std::mem::take(&mut *pending).messages),
+            )
+        } else {
+            (acknowledgement, None)
+        }
+    }
+
+    /// Combines messages with the same metadata into batch messages.
+    /// Returns each batch message with the acknowledgers of the messages in it
+    ///
+    /// # Arguments
+    /// - `messages`: the messages to combine
+    fn combine(
+        messages: Vec<(CloudMessageRequest, Acknowledger)>,
+    ) -> Result<Vec<(CloudMessageRequest, Vec<Acknowledger>)>, CloudAdapterError> {
+        let mut groups: Vec<(
+            HashMap<String, String>,
+            Vec<CloudMessageRequest>,
+            Vec<Acknowledger>,
+        )> = Vec::new();
+        for (message, acknowledger) in messages {
+            match groups
+                .iter_mut()
+                .find(|(metadata, _, _)| *metadata == message.metadata)
+            {
+                Some((_, group, acknowledgers)) => {
+                    group.push(message);
+                    acknowledgers.push(acknowledger);
+                }
+                None => groups.push((message.metadata.clone(), vec![message], vec![acknowledger])),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(mut metadata, messages, acknowledgers)| {
+                metadata.insert(
+                    BATCH_SIZE_METADATA_KEY.to_string(),
+                    messages.len().to_string(),
+                );
+
+                // Groups always have at least one message
+                let signal_timestamp = messages[messages.len() - 1].signal_timestamp;
+                let signal_value = serde_json::to_string(&messages)
+                    .map_err(CloudAdapterError::serialize)?
+                    .into();
+
+                Ok((
+                    CloudMessageRequest {
+                        metadata,
+                        signal_value,
+                        numeric_value: None,
+                        signal_timestamp,
+                        // The key of each message is kept in the batch's value
+                        idempotency_key: None,
+                    },
+                    acknowledgers,
+                ))
+            })
+            .collect()
+    }
+
+    /// Reports the outcome of sending a batch to the acknowledgers of its messages.
+    /// If the decorated adapter acknowledges the batch later, the outcome is reported once it does
+    ///
+    /// # Arguments
+    /// - `result`: the result of sending the batch
+    /// - `acknowledgers`: the acknowledgers of the messages in the batch
+    fn acknowledge(
+        result: &Result<CloudMessageResponse, CloudAdapterError>,
+        acknowledgers: Vec<Acknowledger>,
+    ) {
+        match result {
+            Ok(CloudMessageResponse {
+                acknowledgement: Some(acknowledgement),
+                ..
+            }) => {
+                let acknowledgement = acknowledgement.clone();
+                tokio::spawn(async move {
+                    let accepted = acknowledgement.accepted().await;
+                    for acknowledger in acknowledgers {
+                        acknowledger.acknowledge(accepted);
+                    }
+                });
+            }
+            _ => {
+                for acknowledger in acknowledgers {
+                    acknowledger.acknowledge(result.is_ok());
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for BatchingCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_new(selector)?,
+            read_config()?.batch,
+        ))
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.batch,
+        ))
+    }
+
+    /// Adds the signal to the pending batch and sends the batch if it's ready.
+    /// Every batch is attempted even if an earlier one fails, and the first error is returned.
+    /// Otherwise, the response has an acknowledgement which resolves once the signal's batch is sent.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let (acknowledgement, messages) = self.push(cloud_message);
+        let response = CloudMessageResponse {
+            acknowledgement: Some(acknowledgement),
+            ..Default::default()
+        };
+        let messages = match messages {
+            Some(messages) => messages,
+            None => return Ok(response),
+        };
+
+        let mut result = Ok(response);
+        for (batch, acknowledgers) in Self::combine(messages)? {
+            let batch_result = self.inner.send_to_cloud(batch).await;
+            Self::acknowledge(&batch_result, acknowledgers);
+
+            if let Err(e) = batch_result {
+                error!("Failed to send batch: {e}");
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use super::*;
+
+    use freyja_common::cloud_adapter::CloudAdapterErrorKind;
+    use freyja_test_common::mocks::MockCloudAdapter;
+
+    use crate::test_utils::create_message;
+
+    /// Creates a mock adapter which records the messages it receives
+    fn create_recording_adapter() -> (
+        MockCloudAdapter,
+        Arc<std::sync::Mutex<Vec<CloudMessageRequest>>>,
+    ) {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+
+        let mut inner = MockCloudAdapter::new();
+        inner.expect_send_to_cloud().returning(move |message| {
+            received_clone.lock().unwrap().push(message);
+            Ok(CloudMessageResponse::default())
+        });
+
+        (inner, received)
+    }
+
+    fn create_config(batch_size: usize, max_batch_delay_ms: u64) -> BatchConfig {
+        BatchConfig {
+            batch_size,
+            max_batch_delay_ms,
+        }
+    }
+
+    fn with_metadata(value: &str, group: &str) -> CloudMessageRequest {
+        let mut message = create_message(value);
+        message
+            .metadata
+            .insert(String::from("group"), group.to_string());
+        message
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_full_batches() {
+        let (inner, received) = create_recording_adapter();
+        let uut = BatchingCloudAdapter::new(inner, create_config(2, 60000));
+
+        uut.send_to_cloud(create_message("1")).await.unwrap();
+        assert!(received.lock().unwrap().is_empty());
+
+        uut.send_to_cloud(create_message("2")).await.unwrap();
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].metadata[BATCH_SIZE_METADATA_KEY], "2");
+
+        let batch: Vec<CloudMessageRequest> =
+            serde_json::from_str(&received[0].signal_value).unwrap();
+        let values: Vec<&str> = batch.iter().map(|m| &*m.signal_value).collect();
+        assert_eq!(values, vec!["1", "2"]);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_groups_messages_by_metadata() {
+        let (inner, received) = create_recording_adapter();
+        let uut = BatchingCloudAdapter::new(inner, create_config(3, 60000));
+
+        uut.send_to_cloud(with_metadata("1", "a")).await.unwrap();
+        uut.send_to_cloud(with_metadata("2", "b")).await.unwrap();
+        uut.send_to_cloud(with_metadata("3", "a")).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].metadata["group"], "a");
+        assert_eq!(received[0].metadata[BATCH_SIZE_METADATA_KEY], "2");
+        assert_eq!(received[1].metadata["group"], "b");
+        assert_eq!(received[1].metadata[BATCH_SIZE_METADATA_KEY], "1");
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_acknowledges_messages_when_batch_is_sent() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| message.metadata["group"] == "a")
+            .returning(|_| Ok(CloudMessageResponse::default()));
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| message.metadata["group"] == "b")
+            .returning(|_| Err(CloudAdapterErrorKind::Communication.into()));
+        let uut = BatchingCloudAdapter::new(inner, create_config(3, 60000));
+
+        let first = uut.send_to_cloud(with_metadata("1", "a")).await.unwrap();
+        let second = uut.send_to_cloud(with_metadata("2", "b")).await.unwrap();
+        assert!(uut.send_to_cloud(with_metadata("3", "a")).await.is_err());
+
+        assert!(first.acknowledgement.unwrap().accepted().await);
+        assert!(!second.acknowledgement.unwrap().accepted().await);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_expired_batches() {
+        let (inner, received) = create_recording_adapter();
+        let uut = BatchingCloudAdapter::new(inner, create_config(10, 0));
+
+        uut.send_to_cloud(create_message("1")).await.unwrap();
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].metadata[BATCH_SIZE_METADATA_KEY], "1");
+    }
+}
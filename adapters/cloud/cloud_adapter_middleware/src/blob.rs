@@ -0,0 +1,444 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{fs, path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::{config::BlobConfig, delta::VALUE_ENCODING_METADATA_KEY, read_config};
+use freyja_common::{
+    blob_sink::{BlobSink, BlobSinkError},
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// The metadata key for the type of a signal's values.
+/// Mappings can set this to `blob` in a signal's target metadata to upload all of its values
+pub const VALUE_TYPE_METADATA_KEY: &str = "value_type";
+
+/// The value type of signals whose values are uploaded to object storage.
+/// The values of messages with this value type are references to the uploaded blobs
+pub const BLOB_VALUE_TYPE: &str = "blob";
+
+/// The encoding of values which carry binary content as base64, such as images.
+/// Mappings can set the `value_encoding` entry of a signal's target metadata to this value
+/// so that the decoded bytes are uploaded rather than the base64 text
+pub const BASE64_VALUE_ENCODING: &str = "base64";
+
+/// The metadata key for the size in bytes of an uploaded blob
+pub const BLOB_SIZE_METADATA_KEY: &str = "blob_size_bytes";
+
+/// The metadata key for the SHA-256 checksum of an uploaded blob, which receivers can use to verify it
+pub const BLOB_SHA256_METADATA_KEY: &str = "blob_sha256";
+
+/// A cloud adapter decorator which uploads large values, such as images or point clouds, with a `BlobSink`
+/// and sends a reference to each uploaded blob to the decorated adapter instead of the value.
+/// Values are uploaded if their signal's target metadata has a `value_type` of `blob`,
+/// or if they're at least as large as the configured minimum size.
+/// Values with a `value_encoding` of `base64` are decoded before they're uploaded.
+pub struct BlobUploadingCloudAdapter<TInner, TSink> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The sink which uploads the blobs
+    sink: TSink,
+
+    /// The blob config
+    config: BlobConfig,
+}
+
+impl<TInner, TSink: BlobSink> BlobUploadingCloudAdapter<TInner, TSink> {
+    /// Creates a new BlobUploadingCloudAdapter
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `sink`: the sink which uploads the blobs
+    /// - `config`: the blob config
+    pub fn new(inner: TInner, sink: TSink, config: BlobConfig) -> Self {
+        Self {
+            inner,
+            sink,
+            config,
+        }
+    }
+
+    /// Checks whether a message's value should be uploaded
+    ///
+    /// # Arguments
+    /// - `cloud_message`: the message to check
+    fn is_blob(&self, cloud_message: &CloudMessageRequest) -> bool {
+        cloud_message
+            .metadata
+            .get(VALUE_TYPE_METADATA_KEY)
+            .is_some_and(|value_type| value_type == BLOB_VALUE_TYPE)
+            || self
+                .config
+                .min_size_bytes
+                .is_some_and(|min_size_bytes| cloud_message.signal_value.len() >= min_size_bytes)
+    }
+
+    /// Gets the content of a message's value, decoding it if its `value_encoding` is `base64`
+    ///
+    /// # Arguments
+    /// - `cloud_message`: the message whose value should be uploaded
+    fn get_content(cloud_message: &CloudMessageRequest) -> Result<Vec<u8>, CloudAdapterError> {
+        match cloud_message
+            .metadata
+            .get(VALUE_ENCODING_METADATA_KEY)
+            .map(String::as_str)
+        {
+            Some(BASE64_VALUE_ENCODING) => STANDARD
+                .decode(cloud_message.signal_value.as_bytes())
+                .map_err(CloudAdapterError::deserialize),
+            _ => Ok(cloud_message.signal_value.as_bytes().to_vec()),
+        }
+    }
+}
+
+#[async_trait]
+impl<TInner, TSink> CloudAdapter for BlobUploadingCloudAdapter<TInner, TSink>
+where
+    TInner: CloudAdapter + Send + Sync,
+    TSink: BlobSink + Send + Sync,
+{
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter and the blob sink.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_new(selector)?,
+            TSink::create_new().map_err(CloudAdapterError::io)?,
+            read_config()?.blob,
+        ))
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant. The blob sink is created with its default settings.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            TSink::create_new().map_err(CloudAdapterError::io)?,
+            read_config()?.blob,
+        ))
+    }
+
+    /// Sends the signal to the cloud, uploading its value and sending a reference to it instead if it's a blob.
+    /// The message isn't sent if the value can't be decoded or the upload fails.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        mut cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        if self.is_blob(&cloud_message) {
+            let content = Self::get_content(&cloud_message)?;
+
+            // Blobs are keyed by their checksum so that uploading the same value again, such as after a retry,
+            // replaces the earlier upload rather than adding another copy
+            let checksum = format!("{:x}", Sha256::digest(&content));
+            let reference = self
+                .sink
+                .upload(&checksum, &content)
+                .await
+                .map_err(CloudAdapterError::communication)?;
+
+            cloud_message.metadata.insert(
+                VALUE_TYPE_METADATA_KEY.to_string(),
+                BLOB_VALUE_TYPE.to_string(),
+            );
+            cloud_message.metadata.insert(
+                BLOB_SIZE_METADATA_KEY.to_string(),
+                content.len().to_string(),
+            );
+            cloud_message
+                .metadata
+                .insert(BLOB_SHA256_METADATA_KEY.to_string(), checksum);
+            // The reference isn't encoded like the value was
+            cloud_message.metadata.remove(VALUE_ENCODING_METADATA_KEY);
+            cloud_message.signal_value = reference.into();
+            cloud_message.numeric_value = None;
+        }
+
+        self.inner.send_to_cloud(cloud_message).await
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+/// A blob sink which writes blobs to a local directory, such as a directory which is synced to object storage.
+/// References to its blobs are `file://` urls.
+pub struct FileBlobSink {
+    /// The directory into which blobs are written
+    directory: PathBuf,
+}
+
+impl FileBlobSink {
+    /// Creates a new FileBlobSink and creates its directory if it doesn't exist
+    ///
+    /// # Arguments
+    /// - `directory`: the directory into which blobs are written
+    pub fn new(directory: impl Into<PathBuf>) -> Result<Self, BlobSinkError> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory).map_err(BlobSinkError::io)?;
+
+        Ok(Self {
+            directory: fs::canonicalize(directory).map_err(BlobSinkError::io)?,
+        })
+    }
+}
+
+#[async_trait]
+impl BlobSink for FileBlobSink {
+    /// Creates a new instance of a FileBlobSink with the directory from the middleware config
+    fn create_new() -> Result<Self, BlobSinkError> {
+        let config = read_config().map_err(BlobSinkError::io)?;
+        Self::new(config.blob.directory)
+    }
+
+    /// Writes a blob to a file named after its key and returns the file's url.
+    /// The blob is written to a temporary file which is then renamed, so a file with the key's name is always complete.
+    ///
+    /// # Arguments
+    /// - `key`: the key of the blob
+    /// - `content`: the content of the blob
+    async fn upload(&self, key: &str, content: &[u8]) -> Result<String, BlobSinkError> {
+        let path = self.directory.join(key);
+        let temp_path = self.directory.join(format!(".{key}.tmp"));
+        let content = content.to_vec();
+
+        // The file system calls block, so they're run outside of the async runtime's worker threads
+        let target_path = path.clone();
+        tokio::task::spawn_blocking(move || {
+            fs::write(&temp_path, content)?;
+            fs::rename(&temp_path, target_path)
+        })
+        .await
+        .map_err(BlobSinkError::unknown)?
+        .map_err(BlobSinkError::io)?;
+
+        Ok(format!("file://{}", path.to_string_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod blob_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use freyja_common::cloud_adapter::CloudAdapterErrorKind;
+    use freyja_test_common::{
+        fixtures::TempDirFixture,
+        mocks::{MockBlobSink, MockCloudAdapter},
+    };
+
+    use crate::test_utils::create_message;
+
+    const REFERENCE: &str = "s3://blobs/image";
+
+    fn create_config(min_size_bytes: Option<usize>) -> BlobConfig {
+        BlobConfig {
+            min_size_bytes,
+            directory: String::from("blobs"),
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_uploads_blob_values_and_sends_reference() {
+        let value = "a".repeat(1000);
+        let expected_content = value.clone();
+        let expected_checksum = format!("{:x}", Sha256::digest(&value));
+        let expected_metadata = HashMap::from([
+            (
+                VALUE_TYPE_METADATA_KEY.to_string(),
+                BLOB_VALUE_TYPE.to_string(),
+            ),
+            (BLOB_SIZE_METADATA_KEY.to_string(), String::from("1000")),
+            (
+                BLOB_SHA256_METADATA_KEY.to_string(),
+                expected_checksum.clone(),
+            ),
+        ]);
+
+        let mut sink = MockBlobSink::new();
+        sink.expect_upload()
+            .withf(move |key, content| {
+                key == expected_checksum && content == expected_content.as_bytes()
+            })
+            .times(1)
+            .returning(|_, _| Ok(REFERENCE.to_string()));
+
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(move |message| {
+                &*message.signal_value == REFERENCE
+                    && message.numeric_value.is_none()
+                    && message.metadata == expected_metadata
+            })
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut message = create_message(&value);
+        message.metadata.insert(
+            VALUE_TYPE_METADATA_KEY.to_string(),
+            BLOB_VALUE_TYPE.to_string(),
+        );
+
+        let uut = BlobUploadingCloudAdapter::new(inner, sink, create_config(None));
+        assert!(uut.send_to_cloud(message).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_uploads_decoded_base64_values() {
+        let content: Vec<u8> = vec![0x89, 0x50, 0x4e, 0x47, 0x00, 0xff, 0xfe, 0x80];
+        assert!(std::str::from_utf8(&content).is_err());
+
+        let directory = TempDirFixture::new();
+        let sink = FileBlobSink::new(&directory.path).unwrap();
+
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| {
+                message.signal_value.starts_with("file://")
+                    && message.metadata[BLOB_SIZE_METADATA_KEY] == "8"
+                    && !message.metadata.contains_key(VALUE_ENCODING_METADATA_KEY)
+            })
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut message = create_message(&STANDARD.encode(&content));
+        message.metadata.insert(
+            VALUE_TYPE_METADATA_KEY.to_string(),
+            BLOB_VALUE_TYPE.to_string(),
+        );
+        message.metadata.insert(
+            VALUE_ENCODING_METADATA_KEY.to_string(),
+            BASE64_VALUE_ENCODING.to_string(),
+        );
+
+        let uut = BlobUploadingCloudAdapter::new(inner, sink, create_config(None));
+        let result = uut.send_to_cloud(message).await;
+        let uploaded = fs::read(
+            directory
+                .path
+                .join(format!("{:x}", Sha256::digest(&content))),
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(uploaded.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_returns_err_for_invalid_base64_values() {
+        let mut sink = MockBlobSink::new();
+        sink.expect_upload().never();
+
+        let mut inner = MockCloudAdapter::new();
+        inner.expect_send_to_cloud().never();
+
+        let mut message = create_message("not base64!");
+        message.metadata.insert(
+            VALUE_ENCODING_METADATA_KEY.to_string(),
+            BASE64_VALUE_ENCODING.to_string(),
+        );
+
+        let uut = BlobUploadingCloudAdapter::new(inner, sink, create_config(Some(1)));
+        let result = uut.send_to_cloud(message).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Deserialize));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_uploads_values_above_min_size() {
+        let mut sink = MockBlobSink::new();
+        sink.expect_upload()
+            .times(1)
+            .returning(|_, _| Ok(REFERENCE.to_string()));
+
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| {
+                &*message.signal_value == REFERENCE
+                    && message.metadata[VALUE_TYPE_METADATA_KEY] == BLOB_VALUE_TYPE
+            })
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = BlobUploadingCloudAdapter::new(inner, sink, create_config(Some(100)));
+        assert!(uut
+            .send_to_cloud(create_message(&"a".repeat(100)))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_other_values_unchanged() {
+        let mut sink = MockBlobSink::new();
+        sink.expect_upload().never();
+
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| &*message.signal_value == "42" && message.metadata.is_empty())
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = BlobUploadingCloudAdapter::new(inner, sink, create_config(Some(100)));
+        assert!(uut.send_to_cloud(create_message("42")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_does_not_send_message_when_upload_fails() {
+        let mut sink = MockBlobSink::new();
+        sink.expect_upload()
+            .times(1)
+            .returning(|_, _| Err(BlobSinkError::communication("unavailable")));
+
+        let mut inner = MockCloudAdapter::new();
+        inner.expect_send_to_cloud().never();
+
+        let uut = BlobUploadingCloudAdapter::new(inner, sink, create_config(Some(1)));
+        let result = uut.send_to_cloud(create_message("42")).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+    }
+
+    #[tokio::test]
+    async fn file_blob_sink_writes_blob_and_returns_its_url() {
+        let directory = TempDirFixture::new();
+        let uut = FileBlobSink::new(&directory.path).unwrap();
+
+        let reference = uut.upload("key", b"content").await.unwrap();
+
+        assert!(reference.starts_with("file://") && reference.ends_with("key"));
+        assert_eq!(fs::read(directory.path.join("key")).unwrap(), b"content");
+        assert!(!directory.path.join(".key.tmp").exists());
+    }
+}
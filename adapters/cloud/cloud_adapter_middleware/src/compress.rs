@@ -0,0 +1,183 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{io::Write, sync::Arc};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::{write::GzEncoder, Compression};
+use tokio::sync::Mutex;
+
+use crate::{config::CompressConfig, read_config};
+use freyja_common::{
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// The metadata key for the encoding of a compressed value
+pub const CONTENT_ENCODING_METADATA_KEY: &str = "content_encoding";
+
+/// The encoding of compressed values: gzip, followed by base64 so that the value is still a string
+pub const GZIP_BASE64_ENCODING: &str = "gzip+base64";
+
+/// A cloud adapter decorator which compresses large signal values.
+/// Compressed values are gzipped and then base64 encoded,
+/// and their messages have a `content_encoding` metadata entry so that receivers can decode them.
+pub struct CompressingCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The compression config
+    config: CompressConfig,
+}
+
+impl<TInner> CompressingCloudAdapter<TInner> {
+    /// Creates a new CompressingCloudAdapter
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the compression config
+    pub fn new(inner: TInner, config: CompressConfig) -> Self {
+        Self { inner, config }
+    }
+
+    /// Compresses a value with gzip and encodes it with base64
+    ///
+    /// # Arguments
+    /// - `value`: the value to compress
+    fn compress(&self, value: &str) -> Result<String, CloudAdapterError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::new(self.config.level.min(9)));
+        encoder
+            .write_all(value.as_bytes())
+            .map_err(CloudAdapterError::io)?;
+        let compressed = encoder.finish().map_err(CloudAdapterError::io)?;
+
+        Ok(STANDARD.encode(compressed))
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for CompressingCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_new(selector)?,
+            read_config()?.compress,
+        ))
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.compress,
+        ))
+    }
+
+    /// Sends the signal to the cloud, compressing its value if it's large enough
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        mut cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        if cloud_message.signal_value.len() >= self.config.min_size_bytes {
+            cloud_message.signal_value = self.compress(&cloud_message.signal_value)?.into();
+            cloud_message.metadata.insert(
+                CONTENT_ENCODING_METADATA_KEY.to_string(),
+                GZIP_BASE64_ENCODING.to_string(),
+            );
+        }
+
+        self.inner.send_to_cloud(cloud_message).await
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod compress_tests {
+    use super::*;
+
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+    use freyja_test_common::mocks::MockCloudAdapter;
+
+    use crate::test_utils::create_message;
+
+    fn create_config(min_size_bytes: usize) -> CompressConfig {
+        CompressConfig {
+            min_size_bytes,
+            level: 6,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_compresses_large_values() {
+        let value = "a".repeat(1000);
+        let expected_value = value.clone();
+
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(move |message| {
+                let compressed = STANDARD.decode(message.signal_value.as_bytes()).unwrap();
+                let mut decompressed = String::new();
+                GzDecoder::new(compressed.as_slice())
+                    .read_to_string(&mut decompressed)
+                    .unwrap();
+
+                message.signal_value.len() < expected_value.len()
+                    && decompressed == expected_value
+                    && message.metadata[CONTENT_ENCODING_METADATA_KEY] == GZIP_BASE64_ENCODING
+            })
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = CompressingCloudAdapter::new(inner, create_config(100));
+        assert!(uut.send_to_cloud(create_message(&value)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_does_not_compress_small_values() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| {
+                &*message.signal_value == "42"
+                    && !message.metadata.contains_key(CONTENT_ENCODING_METADATA_KEY)
+            })
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = CompressingCloudAdapter::new(inner, create_config(100));
+        assert!(uut.send_to_cloud(create_message("42")).await.is_ok());
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use log::Level;
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the cloud adapter middleware.
+/// Each decorator reads its own section when it's created with `create_new`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The config for `RetryCloudAdapter`
+    pub retry: RetryConfig,
+
+    /// The config for `RateLimitedCloudAdapter`
+    pub rate_limit: RateLimitConfig,
+
+    /// The config for `BatchingCloudAdapter`
+    pub batch: BatchConfig,
+
+    /// The config for `BlobUploadingCloudAdapter` and `FileBlobSink`
+    pub blob: BlobConfig,
+
+    /// The config for `CompressingCloudAdapter`
+    pub compress: CompressConfig,
+
+    /// The config for `DeltaEncodingCloudAdapter`
+    pub delta: DeltaConfig,
+
+    /// The config for `LoggingCloudAdapter`
+    pub log: LogConfig,
+
+    /// The config for `MetricsCloudAdapter`
+    pub metrics: MetricsConfig,
+
+    /// The config for `TargetTransformingCloudAdapter`
+    pub transform: TransformConfig,
+}
+
+/// Configuration for retrying failed messages
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// The maximum number of times to retry a message after the first attempt fails
+    pub max_retries: u32,
+
+    /// The time in milliseconds to wait before the first retry
+    pub retry_interval_ms: u64,
+
+    /// The maximum time in milliseconds to wait between retries.
+    /// The retry interval doubles after each retry until it reaches this value.
+    pub max_retry_interval_ms: u64,
+}
+
+/// Configuration for limiting the rate at which messages are sent
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// The maximum sustained number of messages per second
+    pub max_messages_per_second: f64,
+
+    /// The number of messages that can be sent at once before the rate limit applies
+    pub burst: u32,
+
+    /// What to do with messages that exceed the rate limit
+    pub mode: RateLimitMode,
+}
+
+/// What to do with messages that exceed the rate limit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateLimitMode {
+    /// Wait until the message can be sent
+    Wait,
+
+    /// Drop the message
+    Drop,
+}
+
+/// Configuration for combining messages into batches
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchConfig {
+    /// The number of messages to collect before sending a batch
+    pub batch_size: usize,
+
+    /// The maximum time in milliseconds to hold the oldest message in a batch.
+    /// A batch which is older than this is sent with the next message, even if it isn't full.
+    pub max_batch_delay_ms: u64,
+}
+
+/// Configuration for uploading large values to object storage
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlobConfig {
+    /// The minimum size in bytes of a value to upload, even if its signal isn't marked as a blob.
+    /// If this is `None`, only the values of signals which are marked as blobs are uploaded.
+    #[serde(default)]
+    pub min_size_bytes: Option<usize>,
+
+    /// The directory into which `FileBlobSink` writes blobs
+    pub directory: String,
+}
+
+/// Configuration for compressing message values
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompressConfig {
+    /// The minimum size in bytes of a value to compress. Smaller values are sent unchanged.
+    pub min_size_bytes: usize,
+
+    /// The gzip compression level from 0 (none) to 9 (best)
+    pub level: u32,
+}
+
+/// Configuration for delta encoding numeric values
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeltaConfig {
+    /// The maximum time in milliseconds between keyframes for a signal
+    pub keyframe_interval_ms: u64,
+
+    /// The maximum number of deltas to send for a signal before sending a keyframe
+    pub max_deltas_between_keyframes: u32,
+}
+
+/// Configuration for logging messages
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogConfig {
+    /// The level to log messages at. Failures are always logged as errors.
+    pub level: Level,
+}
+
+/// Configuration for collecting metrics
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// The interval in seconds at which metrics are logged
+    pub report_interval_s: u64,
+}
+
+/// Configuration for transforming message metadata into the conventions of each kind of cloud adapter
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TransformConfig {
+    /// The transformation for each kind of decorated adapter, such as `GRPCCloudAdapter`.
+    /// Messages to adapters of other kinds are sent unchanged
+    #[serde(default)]
+    pub adapters: HashMap<String, TargetTransformation>,
+}
+
+/// A transformation of message metadata
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TargetTransformation {
+    /// The metadata entries to set, with templates for their values such as `{path[-1]}`.
+    /// Each `{key}` placeholder is replaced with the value of the metadata entry with that key,
+    /// and `{key[n]}` is replaced with the `n`th `/`-separated segment of the value, counting from the end if `n` is negative
+    #[serde(default)]
+    pub set: HashMap<String, String>,
+
+    /// The metadata keys to remove, such as the keys that the new entries replace
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
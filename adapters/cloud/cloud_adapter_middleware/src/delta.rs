@@ -0,0 +1,406 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::{config::DeltaConfig, read_config};
+use freyja_common::{
+    clock::{Clock, SystemClock},
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// The metadata key for how a numeric value is encoded
+pub const VALUE_ENCODING_METADATA_KEY: &str = "value_encoding";
+
+/// The encoding of values which are sent as they are
+pub const KEYFRAME_ENCODING: &str = "keyframe";
+
+/// The encoding of values which are sent as the change since the last acknowledged value
+pub const DELTA_ENCODING: &str = "delta";
+
+/// The encoding state of a signal
+struct EncodingState {
+    /// The last value acknowledged by the decorated adapter, as the receiver reconstructs it
+    reference: f64,
+
+    /// The number of deltas sent since the last keyframe
+    deltas_since_keyframe: u32,
+
+    /// The monotonic time of the clock when the last keyframe was acknowledged
+    keyframe_at: Duration,
+}
+
+/// The encoding state of each signal, keyed by message metadata
+type EncodingStates = std::sync::Mutex<HashMap<BTreeMap<String, String>, EncodingState>>;
+
+/// A cloud adapter decorator which reduces uplink bandwidth for smooth numeric signals such as speed and RPM.
+/// Numeric values are sent as the change since the last value that the decorated adapter acknowledged,
+/// with a full value sent periodically as a keyframe so that receivers can recover from lost messages.
+/// Messages have a `value_encoding` metadata entry with the value `keyframe` or `delta`,
+/// and signals are identified by the rest of their metadata.
+/// Values which aren't numeric are sent unchanged without a `value_encoding` entry.
+/// If the decorated adapter returns a deferred acknowledgement, a value only counts as acknowledged once the cloud accepts it.
+pub struct DeltaEncodingCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The delta encoding config
+    config: DeltaConfig,
+
+    /// The encoding state of each signal, keyed by message metadata
+    states: Arc<EncodingStates>,
+
+    /// The clock used to decide when to send keyframes
+    clock: Arc<dyn Clock>,
+}
+
+impl<TInner> DeltaEncodingCloudAdapter<TInner> {
+    /// Creates a new DeltaEncodingCloudAdapter which uses the system clock
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the delta encoding config
+    pub fn new(inner: TInner, config: DeltaConfig) -> Self {
+        Self::with_clock(inner, config, Arc::new(SystemClock))
+    }
+
+    /// Creates a new DeltaEncodingCloudAdapter which uses the provided clock
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the delta encoding config
+    /// - `clock`: the clock used to decide when to send keyframes
+    pub fn with_clock(inner: TInner, config: DeltaConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            inner,
+            config,
+            states: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            clock,
+        }
+    }
+
+    /// Gets the change since the last acknowledged value of a signal.
+    /// Returns `None` if a keyframe should be sent instead.
+    ///
+    /// # Arguments
+    /// - `key`: the signal's key
+    /// - `value`: the new value
+    fn delta(&self, key: &BTreeMap<String, String>, value: f64) -> Option<f64> {
+        let keyframe_interval = Duration::from_millis(self.config.keyframe_interval_ms);
+        let now = self.clock.monotonic_now();
+        let states = self.states.lock().unwrap();
+
+        states
+            .get(key)
+            .filter(|state| {
+                state.deltas_since_keyframe < self.config.max_deltas_between_keyframes
+                    && now.saturating_sub(state.keyframe_at) < keyframe_interval
+            })
+            .map(|state| value - state.reference)
+    }
+}
+
+/// Updates the encoding state of a signal after the decorated adapter acknowledged a value
+///
+/// # Arguments
+/// - `states`: the encoding state of each signal
+/// - `clock`: the clock used to decide when to send keyframes
+/// - `key`: the signal's key
+/// - `value`: the value that was sent
+/// - `delta`: the delta that was sent, or `None` if the value was sent as a keyframe
+fn acknowledge(
+    states: &EncodingStates,
+    clock: &dyn Clock,
+    key: BTreeMap<String, String>,
+    value: f64,
+    delta: Option<f64>,
+) {
+    let mut states = states.lock().unwrap();
+    match (delta, states.get_mut(&key)) {
+        (Some(delta), Some(state)) => {
+            // Receivers add the delta to their last value, so the reference does the same to avoid drifting
+            state.reference += delta;
+            state.deltas_since_keyframe += 1;
+        }
+        _ => {
+            states.insert(
+                key,
+                EncodingState {
+                    reference: value,
+                    deltas_since_keyframe: 0,
+                    keyframe_at: clock.monotonic_now(),
+                },
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for DeltaEncodingCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_new(selector)?,
+            read_config()?.delta,
+        ))
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.delta,
+        ))
+    }
+
+    /// Sends the signal to the cloud as a keyframe or as the change since the last acknowledged value
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        mut cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let value = match cloud_message.numeric_value {
+            Some(value) => value,
+            None => return self.inner.send_to_cloud(cloud_message).await,
+        };
+
+        let key: BTreeMap<String, String> = cloud_message
+            .metadata
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        let delta = self.delta(&key, value);
+        let encoding = match delta {
+            Some(delta) => {
+                cloud_message.signal_value = delta.to_string().into();
+                cloud_message.numeric_value = Some(delta);
+                DELTA_ENCODING
+            }
+            None => KEYFRAME_ENCODING,
+        };
+        cloud_message.metadata.insert(
+            VALUE_ENCODING_METADATA_KEY.to_string(),
+            encoding.to_string(),
+        );
+
+        let response = self.inner.send_to_cloud(cloud_message).await?;
+        match response.acknowledgement.clone() {
+            None => acknowledge(&self.states, self.clock.as_ref(), key, value, delta),
+            Some(acknowledgement) => {
+                // The value is only acknowledged once the cloud accepts it, which can happen after this returns
+                let states = self.states.clone();
+                let clock = self.clock.clone();
+                tokio::spawn(async move {
+                    if acknowledgement.accepted().await {
+                        acknowledge(&states, clock.as_ref(), key, value, delta);
+                    }
+                });
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod delta_tests {
+    use super::*;
+
+    use freyja_common::{
+        clock::ManualClock,
+        cloud_adapter::{Acknowledgement, CloudAdapterErrorKind},
+    };
+    use freyja_test_common::{mockall::Sequence, mocks::MockCloudAdapter};
+
+    use crate::test_utils::create_message;
+
+    fn create_config(max_deltas_between_keyframes: u32) -> DeltaConfig {
+        DeltaConfig {
+            keyframe_interval_ms: 60000,
+            max_deltas_between_keyframes,
+        }
+    }
+
+    /// Expects a message with the provided encoding and value
+    ///
+    /// # Arguments
+    /// - `inner`: the mock adapter
+    /// - `sequence`: the sequence of expected messages
+    /// - `encoding`: the expected encoding
+    /// - `value`: the expected value
+    fn expect_message(
+        inner: &mut MockCloudAdapter,
+        sequence: &mut Sequence,
+        encoding: &'static str,
+        value: &'static str,
+    ) {
+        inner
+            .expect_send_to_cloud()
+            .withf(move |message| {
+                message.metadata[VALUE_ENCODING_METADATA_KEY] == encoding
+                    && &*message.signal_value == value
+            })
+            .once()
+            .in_sequence(sequence)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_deltas_after_keyframe() {
+        let mut inner = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        expect_message(&mut inner, &mut sequence, KEYFRAME_ENCODING, "100");
+        expect_message(&mut inner, &mut sequence, DELTA_ENCODING, "5");
+        expect_message(&mut inner, &mut sequence, DELTA_ENCODING, "-2");
+
+        let uut = DeltaEncodingCloudAdapter::new(inner, create_config(10));
+        for value in ["100", "105", "103"] {
+            uut.send_to_cloud(create_message(value)).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_keyframe_after_max_deltas() {
+        let mut inner = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        expect_message(&mut inner, &mut sequence, KEYFRAME_ENCODING, "1");
+        expect_message(&mut inner, &mut sequence, DELTA_ENCODING, "1");
+        expect_message(&mut inner, &mut sequence, KEYFRAME_ENCODING, "3");
+
+        let uut = DeltaEncodingCloudAdapter::new(inner, create_config(1));
+        for value in ["1", "2", "3"] {
+            uut.send_to_cloud(create_message(value)).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_deltas_relative_to_last_acknowledged_value() {
+        let mut inner = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        expect_message(&mut inner, &mut sequence, KEYFRAME_ENCODING, "10");
+        inner
+            .expect_send_to_cloud()
+            .once()
+            .in_sequence(&mut sequence)
+            .returning(|_| Err(CloudAdapterErrorKind::Communication.into()));
+        expect_message(&mut inner, &mut sequence, DELTA_ENCODING, "2");
+
+        let uut = DeltaEncodingCloudAdapter::new(inner, create_config(10));
+        uut.send_to_cloud(create_message("10")).await.unwrap();
+        assert!(uut.send_to_cloud(create_message("11")).await.is_err());
+        uut.send_to_cloud(create_message("12")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_keyframe_after_interval() {
+        let mut inner = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        expect_message(&mut inner, &mut sequence, KEYFRAME_ENCODING, "1");
+        expect_message(&mut inner, &mut sequence, DELTA_ENCODING, "1");
+        expect_message(&mut inner, &mut sequence, KEYFRAME_ENCODING, "3");
+
+        let clock = Arc::new(ManualClock::default());
+        let uut = DeltaEncodingCloudAdapter::with_clock(inner, create_config(10), clock.clone());
+        uut.send_to_cloud(create_message("1")).await.unwrap();
+        clock.advance(Duration::from_millis(59999));
+        uut.send_to_cloud(create_message("2")).await.unwrap();
+        clock.advance(Duration::from_millis(1));
+        uut.send_to_cloud(create_message("3")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_waits_for_deferred_acknowledgement() {
+        let (accepted_acknowledger, accepted) = Acknowledgement::pending();
+        let (rejected_acknowledger, rejected) = Acknowledgement::pending();
+
+        let mut inner = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        for (value, acknowledgement) in [("10", accepted), ("11", rejected)] {
+            inner
+                .expect_send_to_cloud()
+                .withf(move |message| {
+                    message.metadata[VALUE_ENCODING_METADATA_KEY] == KEYFRAME_ENCODING
+                        && &*message.signal_value == value
+                })
+                .once()
+                .in_sequence(&mut sequence)
+                .return_once(move |_| {
+                    Ok(CloudMessageResponse {
+                        acknowledgement: Some(acknowledgement),
+                        ..Default::default()
+                    })
+                });
+        }
+        expect_message(&mut inner, &mut sequence, DELTA_ENCODING, "2");
+
+        let uut = DeltaEncodingCloudAdapter::new(inner, create_config(10));
+
+        // Neither value is acknowledged until the cloud accepts it, so both are sent as keyframes
+        uut.send_to_cloud(create_message("10")).await.unwrap();
+        uut.send_to_cloud(create_message("11")).await.unwrap();
+
+        accepted_acknowledger.acknowledge(true);
+        rejected_acknowledger.acknowledge(false);
+        for _ in 0..2 {
+            tokio::task::yield_now().await;
+        }
+
+        uut.send_to_cloud(create_message("12")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_does_not_encode_non_numeric_values() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| {
+                &*message.signal_value == "on"
+                    && !message.metadata.contains_key(VALUE_ENCODING_METADATA_KEY)
+            })
+            .times(2)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = DeltaEncodingCloudAdapter::new(inner, create_config(10));
+        for _ in 0..2 {
+            uut.send_to_cloud(create_message("on")).await.unwrap();
+        }
+    }
+}
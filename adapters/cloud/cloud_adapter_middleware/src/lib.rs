@@ -0,0 +1,50 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+pub mod batch;
+pub mod blob;
+pub mod compress;
+pub mod config;
+pub mod delta;
+pub mod logging;
+pub mod metrics;
+pub mod rate_limit;
+pub mod retry;
+pub mod transform;
+
+use freyja_build_common::config_file_stem;
+use freyja_common::{cloud_adapter::CloudAdapterError, config_utils, out_dir};
+
+use config::Config;
+
+/// Reads the middleware config, which is shared by every decorator
+pub(crate) fn read_config() -> Result<Config, CloudAdapterError> {
+    config_utils::read_from_files(
+        config_file_stem!(),
+        config_utils::JSON_EXT,
+        out_dir!(),
+        CloudAdapterError::io,
+        CloudAdapterError::deserialize,
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use freyja_common::cloud_adapter::CloudMessageRequest;
+    use time::OffsetDateTime;
+
+    /// Creates a message with the provided value and no metadata
+    ///
+    /// # Arguments
+    /// - `value`: the signal value
+    pub fn create_message(value: &str) -> CloudMessageRequest {
+        CloudMessageRequest {
+            metadata: Default::default(),
+            signal_value: value.into(),
+            numeric_value: value.parse().ok(),
+            signal_timestamp: OffsetDateTime::UNIX_EPOCH,
+            idempotency_key: None,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::{error, log};
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::{config::LogConfig, read_config};
+use freyja_common::{
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A cloud adapter decorator which logs every message and its result
+pub struct LoggingCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The logging config
+    config: LogConfig,
+}
+
+impl<TInner> LoggingCloudAdapter<TInner> {
+    /// Creates a new LoggingCloudAdapter
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the logging config
+    pub fn new(inner: TInner, config: LogConfig) -> Self {
+        Self { inner, config }
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for LoggingCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(TInner::create_new(selector)?, read_config()?.log))
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.log,
+        ))
+    }
+
+    /// Sends the signal to the cloud and logs the result
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let kind = self.inner.kind();
+        log!(
+            self.config.level,
+            "Sending value {} with metadata {:?} to cloud adapter {kind}",
+            cloud_message.signal_value,
+            cloud_message.metadata
+        );
+
+        let start = Instant::now();
+        let result = self.inner.send_to_cloud(cloud_message).await;
+        match &result {
+            Ok(_) => log!(
+                self.config.level,
+                "Cloud adapter {kind} sent the message in {:?}",
+                start.elapsed()
+            ),
+            Err(e) => error!(
+                "Cloud adapter {kind} failed to send the message after {:?}: {e}",
+                start.elapsed()
+            ),
+        }
+
+        result
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+
+    use log::Level;
+
+    use freyja_common::cloud_adapter::CloudAdapterErrorKind;
+    use freyja_test_common::mocks::MockCloudAdapter;
+
+    use crate::test_utils::create_message;
+
+    #[tokio::test]
+    async fn send_to_cloud_returns_inner_result() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .times(1)
+            .returning(|_| Err(CloudAdapterErrorKind::Communication.into()));
+
+        let uut = LoggingCloudAdapter::new(inner, LogConfig { level: Level::Info });
+        let result = uut.send_to_cloud(create_message("42")).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::info;
+use tokio::sync::Mutex;
+
+use crate::{config::MetricsConfig, read_config};
+use freyja_common::{
+    clock::{Clock, SystemClock},
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// Counters for the messages sent by a cloud adapter
+#[derive(Debug, Default)]
+pub struct CloudAdapterMetrics {
+    /// The number of messages which were sent successfully
+    sent: AtomicU64,
+
+    /// The number of messages which failed to send
+    failed: AtomicU64,
+
+    /// The total time from sending messages until they were acknowledged, in microseconds
+    total_latency_us: AtomicU64,
+}
+
+impl CloudAdapterMetrics {
+    /// Gets the current values of the counters
+    pub fn snapshot(&self) -> CloudAdapterMetricsSnapshot {
+        let sent = self.sent.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        let total_latency_us = self.total_latency_us.load(Ordering::Relaxed);
+
+        let attempts = sent + failed;
+        let average_latency = if attempts == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_micros(total_latency_us / attempts)
+        };
+
+        CloudAdapterMetricsSnapshot {
+            sent,
+            failed,
+            average_latency,
+        }
+    }
+
+    /// Records the result of sending a message
+    ///
+    /// # Arguments
+    /// - `success`: whether the message was sent successfully
+    /// - `latency`: how long it took until the message was acknowledged
+    fn record(&self, success: bool, latency: Duration) {
+        let counter = if success { &self.sent } else { &self.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.total_latency_us.fetch_add(
+            u64::try_from(latency.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+}
+
+/// The values of a cloud adapter's metrics at a point in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CloudAdapterMetricsSnapshot {
+    /// The number of messages which were sent successfully
+    pub sent: u64,
+
+    /// The number of messages which failed to send
+    pub failed: u64,
+
+    /// The average time from sending a message until it was acknowledged
+    pub average_latency: Duration,
+}
+
+/// A cloud adapter decorator which counts sent and failed messages and measures their latency.
+/// If the decorated adapter returns a deferred acknowledgement, the message is counted once the acknowledgement
+/// has an outcome, and its latency includes the wait for it.
+/// The metrics are logged periodically and can also be read with `metrics`.
+pub struct MetricsCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The metrics config
+    config: MetricsConfig,
+
+    /// The collected metrics
+    metrics: Arc<CloudAdapterMetrics>,
+
+    /// The monotonic time of the clock when the metrics were last logged
+    last_report: std::sync::Mutex<Duration>,
+
+    /// The clock used to measure latency
+    clock: Arc<dyn Clock>,
+}
+
+impl<TInner> MetricsCloudAdapter<TInner> {
+    /// Creates a new MetricsCloudAdapter which uses the system clock
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the metrics config
+    pub fn new(inner: TInner, config: MetricsConfig) -> Self {
+        Self::with_clock(inner, config, Arc::new(SystemClock))
+    }
+
+    /// Creates a new MetricsCloudAdapter which uses the provided clock
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the metrics config
+    /// - `clock`: the clock used to measure latency
+    pub fn with_clock(inner: TInner, config: MetricsConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            inner,
+            config,
+            metrics: Arc::new(CloudAdapterMetrics::default()),
+            last_report: std::sync::Mutex::new(clock.monotonic_now()),
+            clock,
+        }
+    }
+
+    /// Gets the collected metrics.
+    /// The returned value is updated as more messages are sent.
+    pub fn metrics(&self) -> Arc<CloudAdapterMetrics> {
+        self.metrics.clone()
+    }
+}
+
+impl<TInner: CloudAdapter> MetricsCloudAdapter<TInner> {
+    /// Logs the metrics if the report interval has passed since they were last logged
+    fn report_if_due(&self) {
+        let report_interval = Duration::from_secs(self.config.report_interval_s);
+        let now = self.clock.monotonic_now();
+        let mut last_report = self.last_report.lock().unwrap();
+        if now.saturating_sub(*last_report) >= report_interval {
+            info!(
+                "Cloud adapter {} metrics: {:?}",
+                self.inner.kind(),
+                self.metrics.snapshot()
+            );
+            *last_report = now;
+        }
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for MetricsCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_new(selector)?,
+            read_config()?.metrics,
+        ))
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.metrics,
+        ))
+    }
+
+    /// Sends the signal to the cloud and records the result once the message is acknowledged
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let start = self.clock.monotonic_now();
+        let result = self.inner.send_to_cloud(cloud_message).await;
+
+        match result
+            .as_ref()
+            .ok()
+            .and_then(|response| response.acknowledgement.clone())
+        {
+            Some(acknowledgement) => {
+                // The outcome is only known once the acknowledgement resolves, which can happen after this returns
+                let metrics = self.metrics.clone();
+                let clock = self.clock.clone();
+                tokio::spawn(async move {
+                    let accepted = acknowledgement.accepted().await;
+                    metrics.record(accepted, clock.monotonic_now().saturating_sub(start));
+                });
+            }
+            None => self.metrics.record(
+                result.is_ok(),
+                self.clock.monotonic_now().saturating_sub(start),
+            ),
+        }
+        self.report_if_due();
+
+        result
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::*;
+
+    use freyja_common::{
+        clock::ManualClock,
+        cloud_adapter::{Acknowledgement, CloudAdapterErrorKind},
+    };
+    use freyja_test_common::{mockall::Sequence, mocks::MockCloudAdapter};
+
+    use crate::test_utils::create_message;
+
+    #[tokio::test]
+    async fn send_to_cloud_counts_sent_and_failed_messages() {
+        let mut inner = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        inner
+            .expect_send_to_cloud()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+        inner
+            .expect_send_to_cloud()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Err(CloudAdapterErrorKind::Communication.into()));
+
+        let uut = MetricsCloudAdapter::new(
+            inner,
+            MetricsConfig {
+                report_interval_s: 60,
+            },
+        );
+        let metrics = uut.metrics();
+        assert_eq!(metrics.snapshot().average_latency, Duration::ZERO);
+
+        for _ in 0..3 {
+            let _ = uut.send_to_cloud(create_message("42")).await;
+        }
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.sent, 2);
+        assert_eq!(snapshot.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_measures_latency_until_deferred_acknowledgement() {
+        let (acknowledger, acknowledgement) = Acknowledgement::pending();
+
+        let mut inner = MockCloudAdapter::new();
+        inner.expect_send_to_cloud().return_once(move |_| {
+            Ok(CloudMessageResponse {
+                acknowledgement: Some(acknowledgement),
+                ..Default::default()
+            })
+        });
+
+        let clock = Arc::new(ManualClock::default());
+        let uut = MetricsCloudAdapter::with_clock(
+            inner,
+            MetricsConfig {
+                report_interval_s: 60,
+            },
+            clock.clone(),
+        );
+        let metrics = uut.metrics();
+
+        assert!(uut.send_to_cloud(create_message("42")).await.is_ok());
+        tokio::task::yield_now().await;
+        assert_eq!(metrics.snapshot().sent + metrics.snapshot().failed, 0);
+
+        clock.advance(Duration::from_millis(500));
+        acknowledger.acknowledge(false);
+        for _ in 0..2 {
+            tokio::task::yield_now().await;
+        }
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.sent, 0);
+        assert_eq!(snapshot.failed, 1);
+        assert_eq!(snapshot.average_latency, Duration::from_millis(500));
+    }
+}
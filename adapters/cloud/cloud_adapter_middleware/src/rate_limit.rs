@@ -0,0 +1,223 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use log::debug;
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::{
+    config::{RateLimitConfig, RateLimitMode},
+    read_config,
+};
+use freyja_common::{
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A token bucket which refills at a constant rate
+struct TokenBucket {
+    /// The number of available tokens. This is negative if messages are waiting for tokens.
+    tokens: f64,
+
+    /// The time at which the bucket was last refilled
+    last_refill: Instant,
+}
+
+/// A cloud adapter decorator which limits the rate at which messages are sent
+pub struct RateLimitedCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The rate limit config
+    config: RateLimitConfig,
+
+    /// The tokens for sending messages
+    bucket: std::sync::Mutex<TokenBucket>,
+}
+
+impl<TInner> RateLimitedCloudAdapter<TInner> {
+    /// Creates a new RateLimitedCloudAdapter.
+    /// Returns an error if the rate limit is not a positive number.
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the rate limit config
+    pub fn new(inner: TInner, config: RateLimitConfig) -> Result<Self, CloudAdapterError> {
+        if !config.max_messages_per_second.is_finite() || config.max_messages_per_second <= 0.0 {
+            return Err(CloudAdapterError::deserialize(format!(
+                "The rate limit must be a positive number, but was {}",
+                config.max_messages_per_second
+            )));
+        }
+
+        let bucket = TokenBucket {
+            tokens: f64::from(config.burst.max(1)),
+            last_refill: Instant::now(),
+        };
+
+        Ok(Self {
+            inner,
+            config,
+            bucket: std::sync::Mutex::new(bucket),
+        })
+    }
+
+    /// Takes a token for sending a message.
+    /// Returns how long to wait before sending the message, or `None` if the message should be dropped.
+    fn acquire(&self) -> Option<Duration> {
+        let rate = self.config.max_messages_per_second;
+        let capacity = f64::from(self.config.burst.max(1));
+        let now = Instant::now();
+
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens < 1.0 && self.config.mode == RateLimitMode::Drop {
+            return None;
+        }
+
+        // Waiting messages take their token in advance so that they are sent in order
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Some(Duration::ZERO)
+        } else {
+            Some(Duration::from_secs_f64(-bucket.tokens / rate))
+        }
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for RateLimitedCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Self::new(TInner::create_new(selector)?, read_config()?.rate_limit)
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.rate_limit,
+        )
+    }
+
+    /// Sends the signal to the cloud once the rate limit allows it.
+    /// Messages which exceed the rate limit are dropped without an error in `Drop` mode.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        match self.acquire() {
+            Some(delay) => {
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+
+                self.inner.send_to_cloud(cloud_message).await
+            }
+            None => {
+                debug!("Dropping message because the rate limit was exceeded");
+                Ok(CloudMessageResponse::default())
+            }
+        }
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod rate_limit_tests {
+    use super::*;
+
+    use freyja_common::cloud_adapter::CloudAdapterErrorKind;
+    use freyja_test_common::mocks::MockCloudAdapter;
+
+    use crate::test_utils::create_message;
+
+    fn create_config(mode: RateLimitMode) -> RateLimitConfig {
+        RateLimitConfig {
+            max_messages_per_second: 1.0,
+            burst: 2,
+            mode,
+        }
+    }
+
+    #[test]
+    fn new_returns_err_for_invalid_rate() {
+        for rate in [0.0, -1.0, f64::NAN] {
+            let config = RateLimitConfig {
+                max_messages_per_second: rate,
+                ..create_config(RateLimitMode::Wait)
+            };
+
+            let result = RateLimitedCloudAdapter::new(MockCloudAdapter::new(), config);
+            assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Deserialize));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn send_to_cloud_waits_when_rate_limit_is_exceeded() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .times(3)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = RateLimitedCloudAdapter::new(inner, create_config(RateLimitMode::Wait)).unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            uut.send_to_cloud(create_message("42")).await.unwrap();
+        }
+
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn send_to_cloud_drops_messages_when_rate_limit_is_exceeded() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .times(2)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = RateLimitedCloudAdapter::new(inner, create_config(RateLimitMode::Drop)).unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            uut.send_to_cloud(create_message("42")).await.unwrap();
+        }
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
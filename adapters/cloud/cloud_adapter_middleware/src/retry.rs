@@ -0,0 +1,166 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::{config::RetryConfig, read_config};
+use freyja_common::{
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    retry_utils::RetryPolicy,
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A cloud adapter decorator which retries failed messages with an exponential backoff.
+/// Errors which aren't retryable, such as serialization failures, are returned immediately.
+pub struct RetryCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The policy for retrying failed messages
+    policy: RetryPolicy<CloudAdapterError>,
+}
+
+impl<TInner> RetryCloudAdapter<TInner> {
+    /// Creates a new RetryCloudAdapter
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the retry config
+    pub fn new(inner: TInner, config: RetryConfig) -> Self {
+        let policy = RetryPolicy::exponential()
+            .max_retries(config.max_retries)
+            .initial_interval(Duration::from_millis(config.retry_interval_ms))
+            .max_interval(Duration::from_millis(config.max_retry_interval_ms))
+            .retry_if_retryable()
+            .context("Sending message to the decorated cloud adapter");
+
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for RetryCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_new(selector)?,
+            read_config()?.retry,
+        ))
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.retry,
+        ))
+    }
+
+    /// Sends the signal to the cloud, retrying if the decorated adapter fails with a retryable error.
+    /// Returns the last error if every attempt fails.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        self.policy
+            .execute(|| self.inner.send_to_cloud(cloud_message.clone()))
+            .await
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use super::*;
+
+    use freyja_common::cloud_adapter::CloudAdapterErrorKind;
+    use freyja_test_common::{mockall::Sequence, mocks::MockCloudAdapter};
+
+    use crate::test_utils::create_message;
+
+    fn create_config(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            retry_interval_ms: 0,
+            max_retry_interval_ms: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_retries_until_success() {
+        let mut inner = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        inner
+            .expect_send_to_cloud()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .returning(|_| Err(CloudAdapterErrorKind::Communication.into()));
+        inner
+            .expect_send_to_cloud()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = RetryCloudAdapter::new(inner, create_config(3));
+        let result = uut.send_to_cloud(create_message("42")).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_returns_err_after_max_retries() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .times(3)
+            .returning(|_| Err(CloudAdapterErrorKind::Communication.into()));
+
+        let uut = RetryCloudAdapter::new(inner, create_config(2));
+        let result = uut.send_to_cloud(create_message("42")).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_does_not_retry_errors_that_are_not_retryable() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .once()
+            .returning(|_| Err(CloudAdapterErrorKind::Serialize.into()));
+
+        let uut = RetryCloudAdapter::new(inner, create_config(2));
+        let result = uut.send_to_cloud(create_message("42")).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Serialize));
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use log::warn;
+use tokio::sync::Mutex;
+
+use crate::{
+    config::{TargetTransformation, TransformConfig},
+    read_config,
+};
+use freyja_common::{
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A cloud adapter decorator which rewrites the metadata of each message into the conventions of the decorated adapter,
+/// such as deriving a digital twin id and property from a signal's path.
+/// The transformation is selected from the config by the kind of the decorated adapter,
+/// so mapping authors don't need to know the metadata keys of each backend.
+pub struct TargetTransformingCloudAdapter<TInner> {
+    /// The decorated adapter
+    inner: TInner,
+
+    /// The metadata entries to set, with the templates for their values
+    set: Vec<(String, Template)>,
+
+    /// The metadata keys to remove
+    remove: Vec<String>,
+}
+
+impl<TInner: CloudAdapter> TargetTransformingCloudAdapter<TInner> {
+    /// Creates a new TargetTransformingCloudAdapter.
+    /// Returns an error if a template of the decorated adapter's transformation is invalid
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `config`: the transformation config
+    pub fn new(inner: TInner, mut config: TransformConfig) -> Result<Self, CloudAdapterError> {
+        let TargetTransformation { set, remove } =
+            config.adapters.remove(&inner.kind()).unwrap_or_default();
+        let set = set
+            .into_iter()
+            .map(|(key, template)| Ok((key, Template::parse(&template)?)))
+            .collect::<Result<_, CloudAdapterError>>()?;
+
+        Ok(Self { inner, set, remove })
+    }
+
+    /// Transforms the metadata of a message.
+    /// The templates are rendered with the original metadata, and the removed keys are removed before the new entries are set,
+    /// so an entry can be replaced. Entries whose templates refer to missing metadata aren't set.
+    ///
+    /// # Arguments
+    /// - `metadata`: the message metadata
+    fn transform(&self, metadata: &mut HashMap<String, String>) {
+        let rendered: Vec<(String, String)> = self
+            .set
+            .iter()
+            .filter_map(|(key, template)| match template.render(metadata) {
+                Some(value) => Some((key.clone(), value)),
+                None => {
+                    warn!("Could not set metadata entry {key} for cloud adapter {} because the message doesn't have the metadata that it refers to", self.inner.kind());
+                    None
+                }
+            })
+            .collect();
+
+        for key in self.remove.iter() {
+            metadata.remove(key);
+        }
+
+        metadata.extend(rendered);
+    }
+}
+
+#[async_trait]
+impl<TInner: CloudAdapter + Send + Sync> CloudAdapter for TargetTransformingCloudAdapter<TInner> {
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the decorated adapter.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Self::new(TInner::create_new(selector)?, read_config()?.transform)
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This also creates the decorated adapter for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Self::new(
+            TInner::create_for_tenant(selector, tenant)?,
+            read_config()?.transform,
+        )
+    }
+
+    /// Sends the signal to the cloud after transforming its metadata
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        mut cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        self.transform(&mut cloud_message.metadata);
+        self.inner.send_to_cloud(cloud_message).await
+    }
+
+    /// Checks the decorated adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.inner.check_for_backfill().await
+    }
+
+    /// Gets the kind of the decorated adapter so that routing is not affected by the decorator
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+/// A part of a metadata template
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TemplatePart {
+    /// Literal text
+    Literal(String),
+
+    /// The value of a metadata entry, or one of its `/`-separated segments if `segment` is set.
+    /// Negative segments count from the end
+    Placeholder { key: String, segment: Option<isize> },
+}
+
+/// A template for a metadata value, such as `{path[0]}.{path[-1]}`.
+/// Placeholders are replaced with the values of the metadata entries with the same keys
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Template(Vec<TemplatePart>);
+
+impl Template {
+    /// Parses a template
+    ///
+    /// # Arguments
+    /// - `template`: the template
+    fn parse(template: &str) -> Result<Self, CloudAdapterError> {
+        let invalid = || CloudAdapterError::deserialize(format!("Invalid template {template:?}"));
+
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(TemplatePart::Literal(rest[..start].to_string()));
+            }
+
+            let end = start + rest[start..].find('}').ok_or_else(invalid)?;
+            let placeholder = &rest[start + 1..end];
+            let (key, segment) = match placeholder
+                .strip_suffix(']')
+                .and_then(|p| p.split_once('['))
+            {
+                Some((key, segment)) => (key, Some(segment.parse().map_err(|_| invalid())?)),
+                None => (placeholder, None),
+            };
+
+            if key.is_empty() || key.contains(['{', '[', ']']) {
+                return Err(invalid());
+            }
+
+            parts.push(TemplatePart::Placeholder {
+                key: key.to_string(),
+                segment,
+            });
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest.to_string()));
+        }
+
+        Ok(Self(parts))
+    }
+
+    /// Renders the template with a message's metadata.
+    /// Returns `None` if a placeholder refers to a missing entry or segment
+    ///
+    /// # Arguments
+    /// - `metadata`: the message metadata
+    fn render(&self, metadata: &HashMap<String, String>) -> Option<String> {
+        let mut rendered = String::new();
+        for part in self.0.iter() {
+            match part {
+                TemplatePart::Literal(text) => rendered.push_str(text),
+                TemplatePart::Placeholder { key, segment } => {
+                    let value = metadata.get(key)?;
+                    match segment {
+                        None => rendered.push_str(value),
+                        Some(segment) => {
+                            let segments: Vec<&str> =
+                                value.split('/').filter(|s| !s.is_empty()).collect();
+                            let index = if *segment < 0 {
+                                segments.len().checked_sub(segment.unsigned_abs())?
+                            } else {
+                                segment.unsigned_abs()
+                            };
+                            rendered.push_str(segments.get(index)?);
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(rendered)
+    }
+}
+
+#[cfg(test)]
+mod transform_tests {
+    use super::*;
+
+    use freyja_test_common::mocks::MockCloudAdapter;
+
+    use crate::test_utils::create_message;
+
+    fn create_config(kind: &str, set: &[(&str, &str)], remove: &[&str]) -> TransformConfig {
+        TransformConfig {
+            adapters: HashMap::from([(
+                kind.to_string(),
+                TargetTransformation {
+                    set: set
+                        .iter()
+                        .map(|(key, template)| (key.to_string(), template.to_string()))
+                        .collect(),
+                    remove: remove.iter().map(|key| key.to_string()).collect(),
+                },
+            )]),
+        }
+    }
+
+    fn metadata(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn template_renders_entries_and_segments() {
+        let uut = Template::parse("{path[0]}.{path[-2]}/{path[-1]}:{vin}").unwrap();
+        assert_eq!(
+            uut.render(&metadata(&[
+                ("path", "/Cabin/HVAC/AmbientAirTemperature"),
+                ("vin", "1")
+            ])),
+            Some(String::from("Cabin.HVAC/AmbientAirTemperature:1"))
+        );
+
+        // Missing entries and segments can't be rendered
+        assert_eq!(uut.render(&metadata(&[("path", "/Cabin")])), None);
+        assert_eq!(uut.render(&metadata(&[])), None);
+    }
+
+    #[test]
+    fn template_parse_returns_err_for_invalid_templates() {
+        for template in ["{path", "{}", "{path[x]}", "{path[0}", "{{path}"] {
+            assert!(
+                Template::parse(template).is_err(),
+                "{template} should be invalid"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_transforms_metadata_for_decorated_adapter_kind() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| {
+                message.metadata
+                    == metadata(&[
+                        ("instance_id", "Cabin"),
+                        ("instance_property_path", "/AmbientAirTemperature"),
+                        ("model_id", "hvac"),
+                    ])
+            })
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = TargetTransformingCloudAdapter::new(
+            inner,
+            create_config(
+                "MockCloudAdapter",
+                &[
+                    ("instance_id", "{path[0]}"),
+                    ("instance_property_path", "/{path[-1]}"),
+                    ("model_id", "{model}"),
+                ],
+                &["path", "model"],
+            ),
+        )
+        .unwrap();
+
+        let mut message = create_message("42");
+        message.metadata = metadata(&[("path", "/Cabin/AmbientAirTemperature"), ("model", "hvac")]);
+        assert!(uut.send_to_cloud(message).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_does_not_transform_metadata_for_other_adapter_kinds() {
+        let mut inner = MockCloudAdapter::new();
+        inner
+            .expect_send_to_cloud()
+            .withf(|message| message.metadata == metadata(&[("path", "/Cabin")]))
+            .times(1)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = TargetTransformingCloudAdapter::new(
+            inner,
+            create_config("GRPCCloudAdapter", &[("id", "{path[0]}")], &["path"]),
+        )
+        .unwrap();
+
+        let mut message = create_message("42");
+        message.metadata = metadata(&[("path", "/Cabin")]);
+        assert!(uut.send_to_cloud(message).await.is_ok());
+    }
+}
@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use freyja_common::{grpc_utils::GrpcClientConfig, timestamp_format::TimestampOptions};
+
 /// Config for the GRPCCloudAdapter
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -15,4 +17,49 @@ pub struct Config {
 
     /// Retry interval in milliseconds
     pub retry_interval_ms: u64,
+
+    /// The options for the formatted timestamp included in the request metadata
+    #[serde(default)]
+    pub timestamp: TimestampOptions,
+
+    /// The settings for the connections to the cloud connector
+    #[serde(default)]
+    pub connection: ConnectionConfig,
+
+    /// The headers, bearer token, and deadline of requests sent to the cloud connector
+    #[serde(flatten)]
+    pub grpc: GrpcClientConfig,
+}
+
+/// Settings for the connections to the cloud connector
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionConfig {
+    /// The maximum time to wait for a connection to be established, in milliseconds
+    pub connect_timeout_ms: u64,
+
+    /// The interval at which HTTP/2 keepalive pings are sent, in milliseconds.
+    /// If this is `None`, keepalive pings are not sent
+    pub keep_alive_interval_ms: Option<u64>,
+
+    /// The time to wait for a keepalive ping to be acknowledged before the connection is closed, in milliseconds
+    pub keep_alive_timeout_ms: u64,
+
+    /// Indicates whether keepalive pings are sent while there are no requests in progress
+    pub keep_alive_while_idle: bool,
+
+    /// The number of connections to open to the cloud connector. Requests are spread across the connections in turn
+    pub pool_size: usize,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 5000,
+            keep_alive_interval_ms: Some(30000),
+            keep_alive_timeout_ms: 10000,
+            keep_alive_while_idle: true,
+            pool_size: 1,
+        }
+    }
 }
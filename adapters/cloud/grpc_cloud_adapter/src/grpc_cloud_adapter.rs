@@ -2,34 +2,128 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 use std::time::Duration;
 
 use async_trait::async_trait;
-use log::debug;
+use log::{debug, warn};
 use tokio::sync::Mutex;
-use tonic::transport::Channel;
+use tonic::transport::Endpoint;
 
 use cloud_connector_proto::v1::{
-    cloud_connector_client::CloudConnectorClient, UpdateDigitalTwinRequestBuilder,
+    cloud_connector_client::CloudConnectorClient, CheckForBackfillRequest,
+    UpdateDigitalTwinRequestBuilder,
 };
 use freyja_build_common::config_file_stem;
 use freyja_common::{
-    cloud_adapter::{CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse},
-    config_utils, out_dir,
-    retry_utils::execute_with_retry,
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
+    config_utils,
+    grpc_utils::{GrpcChannel, GrpcInterceptor, GrpcRequestContext},
+    out_dir,
+    retry_utils::RetryPolicy,
     service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+    uri::{EndpointUri, UriKind},
 };
 
-use crate::config::Config;
+use crate::config::{Config, ConnectionConfig};
+
+/// The metadata key for the timestamp formatted according to the adapter and target options
+const TIMESTAMP_METADATA_KEY: &str = "timestamp";
 
 /// A "standard" cloud adapter which communicates over gRPC
 pub struct GRPCCloudAdapter {
     // Adapter config
     config: Config,
 
-    // The gRPC client
-    client: CloudConnectorClient<Channel>,
+    // The gRPC clients, each with its own connection
+    clients: Vec<CloudConnectorClient<GrpcChannel>>,
+
+    // The index of the client to use for the next request
+    next_client_index: AtomicUsize,
+}
+
+impl GRPCCloudAdapter {
+    /// Gets the client to use for the next request.
+    /// Requests are spread across the clients in turn.
+    fn next_client(&self) -> CloudConnectorClient<GrpcChannel> {
+        let index = self.next_client_index.fetch_add(1, Ordering::Relaxed) % self.clients.len();
+        self.clients[index].clone()
+    }
+
+    /// Creates a new instance with the provided config and connects to the cloud connector
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `config`: the adapter config
+    fn create_with_config(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        config: Config,
+    ) -> Result<Self, CloudAdapterError> {
+        let cloud_connector_uri = futures::executor::block_on(async {
+            let selector = selector.lock().await;
+            selector.get_service_uri(&config.service_discovery_id).await
+        })
+        .map_err(CloudAdapterError::communication)?;
+
+        let endpoint = create_endpoint(cloud_connector_uri, &config.connection)?;
+        let interceptor =
+            GrpcInterceptor::from_config(&config.grpc).map_err(CloudAdapterError::deserialize)?;
+
+        // Each connect call opens a separate connection, so the pool spreads requests across connections
+        let clients = futures::executor::block_on(async {
+            let mut clients = Vec::new();
+            for _ in 0..config.connection.pool_size.max(1) {
+                let channel = RetryPolicy::fixed(Duration::from_millis(config.retry_interval_ms))
+                    .max_retries(config.max_retries)
+                    .context("Cloud adapter initial connection")
+                    .execute(|| endpoint.connect())
+                    .await
+                    .map_err(CloudAdapterError::communication)?;
+
+                clients.push(CloudConnectorClient::with_interceptor(
+                    channel,
+                    interceptor.clone(),
+                ));
+            }
+
+            Ok::<_, CloudAdapterError>(clients)
+        })?;
+
+        Ok(Self {
+            config,
+            clients,
+            next_client_index: AtomicUsize::new(0),
+        })
+    }
+}
+
+/// Creates an endpoint for the cloud connector with the connection settings applied.
+/// Keepalive pings detect half-open connections during idle periods,
+/// so that the first request after a silence doesn't fail on a dead connection.
+///
+/// # Arguments
+/// - `uri`: the uri of the cloud connector
+/// - `config`: the connection settings
+fn create_endpoint(uri: String, config: &ConnectionConfig) -> Result<Endpoint, CloudAdapterError> {
+    let uri = EndpointUri::parse(&uri, UriKind::Grpc).map_err(CloudAdapterError::communication)?;
+    let mut endpoint = Endpoint::from_shared(uri.to_string())
+        .map_err(CloudAdapterError::communication)?
+        .connect_timeout(Duration::from_millis(config.connect_timeout_ms));
+
+    if let Some(keep_alive_interval_ms) = config.keep_alive_interval_ms {
+        endpoint = endpoint
+            .http2_keep_alive_interval(Duration::from_millis(keep_alive_interval_ms))
+            .keep_alive_timeout(Duration::from_millis(config.keep_alive_timeout_ms))
+            .keep_alive_while_idle(config.keep_alive_while_idle)
+            .tcp_keepalive(Some(Duration::from_millis(keep_alive_interval_ms)));
+    }
+
+    Ok(endpoint)
 }
 
 #[async_trait]
@@ -49,24 +143,30 @@ impl CloudAdapter for GRPCCloudAdapter {
             CloudAdapterError::deserialize,
         )?;
 
-        let cloud_connector_uri = futures::executor::block_on(async {
-            let selector = selector.lock().await;
-            selector.get_service_uri(&config.service_discovery_id).await
-        })
-        .map_err(CloudAdapterError::communication)?;
+        Self::create_with_config(selector, config)
+    }
 
-        let client = futures::executor::block_on(async {
-            execute_with_retry(
-                config.max_retries,
-                Duration::from_millis(config.retry_interval_ms),
-                || CloudConnectorClient::connect(cloud_connector_uri.clone()),
-                Some("Cloud adapter initial connection".into()),
-            )
-            .await
-            .map_err(CloudAdapterError::communication)
-        })?;
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// The tenant's config overrides are applied on top of the adapter config,
+    /// so each tenant can use its own cloud connector.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        let config: Config = config_utils::read_from_files_for_tenant(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            tenant,
+            CloudAdapterError::io,
+            CloudAdapterError::deserialize,
+        )?;
 
-        Ok(Self { config, client })
+        Self::create_with_config(selector, config)
     }
 
     /// Sends the signal to the cloud
@@ -80,31 +180,72 @@ impl CloudAdapter for GRPCCloudAdapter {
     ) -> Result<CloudMessageResponse, CloudAdapterError> {
         debug!("Received a request to send to the cloud");
 
-        let request = UpdateDigitalTwinRequestBuilder::new()
-            .string_value(cloud_message.signal_value)
+        // Some backends can't read the protobuf timestamp, so the formatted timestamp is also included in the metadata
+        let mut metadata = cloud_message.metadata;
+        let timestamp_options = self
+            .config
+            .timestamp
+            .take_overrides(&mut metadata)
+            .map_err(CloudAdapterError::deserialize)?;
+        // Target metadata with the same key is kept rather than overwritten
+        if metadata.contains_key(TIMESTAMP_METADATA_KEY) {
+            warn!(
+                "The target metadata already has a {TIMESTAMP_METADATA_KEY} key, so the formatted timestamp is not included"
+            );
+        } else {
+            metadata.insert(
+                TIMESTAMP_METADATA_KEY.to_string(),
+                timestamp_options
+                    .format(cloud_message.signal_timestamp)
+                    .map_err(CloudAdapterError::serialize)?,
+            );
+        }
+
+        // Numeric values are sent as numbers so that they don't need to be parsed in the cloud
+        let builder = match cloud_message.numeric_value {
+            Some(n) => UpdateDigitalTwinRequestBuilder::new().number_value(n),
+            None => UpdateDigitalTwinRequestBuilder::new()
+                .string_value(cloud_message.signal_value.to_string()),
+        };
+
+        let builder = match cloud_message.idempotency_key {
+            Some(key) => builder.idempotency_key(key),
+            None => builder,
+        };
+
+        let request = builder
             .timestamp_offset(cloud_message.signal_timestamp)
-            .metadata(cloud_message.metadata)
+            .metadata(metadata)
             .build();
 
-        let response = execute_with_retry(
-            self.config.max_retries,
-            Duration::from_millis(self.config.retry_interval_ms),
-            || async {
-                let request = tonic::Request::new(request.clone());
-                self.client
-                    .clone()
-                    .update_digital_twin(request)
+        // Retries keep the request id, so the cloud connector can correlate them with the first attempt
+        let context = GrpcRequestContext::new(&self.config.grpc);
+        let response = RetryPolicy::fixed(Duration::from_millis(self.config.retry_interval_ms))
+            .max_retries(self.config.max_retries)
+            .retry_if_retryable()
+            .context("Cloud adapter request")
+            .execute(|| async {
+                self.next_client()
+                    .update_digital_twin(context.request(request.clone())?)
                     .await
-                    .map_err(CloudAdapterError::communication)
-            },
-            Some("Cloud adapter request".into()),
-        )
-        .await
-        .map_err(CloudAdapterError::communication)?;
+            })
+            .await
+            .map_err(CloudAdapterError::communication)?;
 
         debug!("Cloud adapter response: {response:?}");
 
-        Ok(CloudMessageResponse {})
+        Ok(response.into_inner().into())
+    }
+
+    /// Checks whether the cloud connector has requested that the current values of any signals be emitted again
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        let response = self
+            .next_client()
+            .check_for_backfill(tonic::Request::new(CheckForBackfillRequest {}))
+            .await
+            .map_err(CloudAdapterError::communication)?;
+
+        Ok(response.into_inner().into())
     }
 }
 
@@ -112,6 +253,56 @@ impl CloudAdapter for GRPCCloudAdapter {
 mod grpc_cloud_adapter_tests {
     use super::*;
 
+    use freyja_common::cloud_adapter::CloudAdapterErrorKind;
+
+    #[test]
+    fn create_endpoint_returns_err_for_invalid_uri() {
+        let result = create_endpoint(String::from("not a uri"), &ConnectionConfig::default());
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+    }
+
+    #[tokio::test]
+    async fn next_client_spreads_requests_across_pool() {
+        let endpoint = create_endpoint(
+            String::from("http://0.0.0.0:5176"), // Devskim: ignore DS137138
+            &ConnectionConfig::default(),
+        )
+        .unwrap();
+        let uut = GRPCCloudAdapter {
+            config: Config {
+                service_discovery_id: String::from("cloud_connector"),
+                max_retries: 0,
+                retry_interval_ms: 0,
+                timestamp: Default::default(),
+                connection: ConnectionConfig {
+                    pool_size: 2,
+                    ..Default::default()
+                },
+                grpc: Default::default(),
+            },
+            clients: vec![
+                CloudConnectorClient::with_interceptor(
+                    endpoint.connect_lazy(),
+                    GrpcInterceptor::default(),
+                ),
+                CloudConnectorClient::with_interceptor(
+                    endpoint.connect_lazy(),
+                    GrpcInterceptor::default(),
+                ),
+            ],
+            next_client_index: AtomicUsize::new(0),
+        };
+
+        for _ in 0..3 {
+            uut.next_client();
+        }
+
+        assert_eq!(
+            uut.next_client_index.load(Ordering::Relaxed) % uut.clients.len(),
+            1
+        );
+    }
+
     /// The tests below uses Unix sockets to create a channel between a gRPC client and a gRPC server.
     /// Unix sockets are more ideal than using TCP/IP sockets since Rust tests will run in parallel
     /// so you would need to set an arbitrary port per test for TCP/IP sockets.
@@ -119,8 +310,10 @@ mod grpc_cloud_adapter_tests {
     mod unix_tests {
         use super::*;
 
-        use std::path::PathBuf;
+        use std::{collections::HashMap, path::PathBuf};
 
+        use futures::future::join_all;
+        use time::OffsetDateTime;
         use tokio::net::{UnixListener, UnixStream};
         use tokio_stream::wrappers::UnixListenerStream;
         use tonic::{
@@ -159,6 +352,76 @@ mod grpc_cloud_adapter_tests {
                 .unwrap();
         }
 
+        #[tokio::test]
+        async fn send_to_cloud_reuses_bounded_pool_of_connections() {
+            const POOL_SIZE: usize = 2;
+
+            let fixture = GRPCTestFixture::new();
+
+            // Create the Unix Socket
+            let uds = UnixListener::bind(&fixture.socket_path).unwrap();
+            let uds_stream = UnixListenerStream::new(uds);
+
+            // Counts the connections which the clients open to the server
+            let connections = Arc::new(AtomicUsize::new(0));
+            let clients = (0..POOL_SIZE)
+                .map(|_| {
+                    let socket_path = fixture.socket_path.clone();
+                    let connections = connections.clone();
+                    let channel = Endpoint::try_from("http://URI_IGNORED") // Devskim: ignore DS137138
+                        .unwrap()
+                        .connect_with_connector_lazy(service_fn(move |_: Uri| {
+                            connections.fetch_add(1, Ordering::SeqCst);
+                            UnixStream::connect(socket_path.clone())
+                        }));
+
+                    CloudConnectorClient::with_interceptor(channel, GrpcInterceptor::default())
+                })
+                .collect();
+
+            let uut = GRPCCloudAdapter {
+                config: Config {
+                    service_discovery_id: String::from("cloud_connector"),
+                    max_retries: 0,
+                    retry_interval_ms: 0,
+                    timestamp: Default::default(),
+                    connection: ConnectionConfig {
+                        pool_size: POOL_SIZE,
+                        ..Default::default()
+                    },
+                    grpc: Default::default(),
+                },
+                clients,
+                next_client_index: AtomicUsize::new(0),
+            };
+
+            let create_request = || CloudMessageRequest {
+                metadata: HashMap::new(),
+                signal_value: "42".into(),
+                numeric_value: Some(42.0),
+                signal_timestamp: OffsetDateTime::UNIX_EPOCH,
+                idempotency_key: None,
+            };
+
+            let request_future = async {
+                // Concurrent requests share the pool instead of opening a connection each
+                let results = join_all((0..10).map(|_| uut.send_to_cloud(create_request()))).await;
+                assert!(results.iter().all(Result::is_ok));
+                assert_eq!(connections.load(Ordering::SeqCst), POOL_SIZE);
+
+                // Later requests reuse the same connections
+                for _ in 0..POOL_SIZE * 2 {
+                    assert!(uut.send_to_cloud(create_request()).await.is_ok());
+                }
+                assert_eq!(connections.load(Ordering::SeqCst), POOL_SIZE);
+            };
+
+            tokio::select! {
+                _ = run_test_grpc_server(uds_stream) => panic!("The server stopped unexpectedly"),
+                _ = request_future => ()
+            }
+        }
+
         #[tokio::test]
         async fn send_request_to_provider() {
             let fixture = GRPCTestFixture::new();
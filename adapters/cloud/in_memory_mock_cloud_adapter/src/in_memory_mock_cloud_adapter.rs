@@ -28,6 +28,19 @@ impl CloudAdapter for InMemoryMockCloudAdapter {
         Ok(Self {})
     }
 
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// This adapter doesn't have any settings, so every tenant's instance is the same.
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector to use (unused by this adapter)
+    /// - `_tenant`: the id of the tenant (unused by this adapter)
+    fn create_for_tenant(
+        _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        _tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self {})
+    }
+
     /// Sends the signal to the cloud
     ///
     /// # Arguments
@@ -43,7 +56,7 @@ impl CloudAdapter for InMemoryMockCloudAdapter {
 
         info!("Cloud canonical value:\n{cloud_message_json}");
 
-        Ok(CloudMessageResponse {})
+        Ok(CloudMessageResponse::default())
     }
 }
 
@@ -74,8 +87,10 @@ mod in_memory_mock_cloud_adapter_tests {
 
         let cloud_message = CloudMessageRequest {
             metadata: HashMap::new(),
-            signal_value: String::from("72"),
+            signal_value: "72".into(),
+            numeric_value: Some(72.0),
             signal_timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
         };
 
         assert!(cloud_adapter.send_to_cloud(cloud_message).await.is_ok());
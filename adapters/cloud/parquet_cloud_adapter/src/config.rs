@@ -0,0 +1,24 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the Parquet cloud adapter
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The directory to write files to. This will be created if it does not exist
+    pub output_directory: String,
+
+    /// The prefix for file names
+    pub file_prefix: String,
+
+    /// The number of emissions to buffer before writing them to the current file as a record batch
+    pub batch_size: usize,
+
+    /// The size in bytes after which the current file is completed and a new file is started
+    pub max_file_size_bytes: u64,
+
+    /// The time in seconds after which the current file is completed and a new file is started
+    pub max_file_duration_s: u64,
+}
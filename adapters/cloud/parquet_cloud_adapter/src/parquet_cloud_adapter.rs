@@ -0,0 +1,86 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use log::debug;
+
+use crate::{config::Config, parquet_sink::ParquetSink};
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    cloud_adapter::{CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse},
+    config_utils, out_dir,
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A cloud adapter which writes emissions to Parquet files on a local or removable disk
+/// instead of sending them over a network
+pub struct ParquetCloudAdapter {
+    /// The sink which batches and writes emissions
+    sink: Mutex<ParquetSink>,
+}
+
+#[async_trait]
+impl CloudAdapter for ParquetCloudAdapter {
+    /// Creates a new instance of a CloudAdapter with default settings
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector to use (unused by this adapter)
+    fn create_new(
+        _selector: Arc<tokio::sync::Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            CloudAdapterError::io,
+            CloudAdapterError::deserialize,
+        )?;
+
+        Ok(Self {
+            sink: Mutex::new(ParquetSink::new(config)?),
+        })
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// The tenant's config overrides are applied on top of the adapter config,
+    /// so each tenant's emissions can be written to their own directory.
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector to use (unused by this adapter)
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        _selector: Arc<tokio::sync::Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        let config: Config = config_utils::read_from_files_for_tenant(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            tenant,
+            CloudAdapterError::io,
+            CloudAdapterError::deserialize,
+        )?;
+
+        Ok(Self {
+            sink: Mutex::new(ParquetSink::new(config)?),
+        })
+    }
+
+    /// Sends the signal to the cloud
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        debug!("Buffering emission for the Parquet sink");
+
+        self.sink.lock().unwrap().push(cloud_message)?;
+
+        Ok(CloudMessageResponse::default())
+    }
+}
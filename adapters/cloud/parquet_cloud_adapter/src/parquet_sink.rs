@@ -0,0 +1,381 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use arrow::{
+    array::{ArrayRef, StringArray, TimestampMicrosecondArray},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    record_batch::RecordBatch,
+};
+use log::{error, info};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use time::OffsetDateTime;
+
+use crate::config::Config;
+use freyja_common::cloud_adapter::{CloudAdapterError, CloudMessageRequest};
+
+/// The extension for completed files
+const PARQUET_EXT: &str = "parquet";
+/// The extension for files which are still being written
+const IN_PROGRESS_EXT: &str = "inprogress";
+
+const TIMESTAMP_COLUMN: &str = "timestamp";
+const VALUE_COLUMN: &str = "value";
+const METADATA_COLUMN: &str = "metadata";
+const UTC_TIMEZONE: &str = "UTC";
+
+/// A Parquet file which is still being written
+struct OpenFile {
+    /// The writer for the file
+    writer: ArrowWriter<File>,
+
+    /// The path that the file is written to
+    in_progress_path: PathBuf,
+
+    /// The path that the file is moved to once it's complete
+    final_path: PathBuf,
+
+    /// The time at which the file was opened
+    opened_at: Instant,
+}
+
+/// Buffers emissions into Arrow record batches and writes them to rotating Parquet files.
+/// Files are written with an `.inprogress` extension and renamed once they are complete,
+/// so every `.parquet` file in the output directory can be read safely.
+pub struct ParquetSink {
+    /// The sink config
+    config: Config,
+
+    /// The schema for the record batches
+    schema: SchemaRef,
+
+    /// Emissions which have not been written yet
+    buffer: Vec<CloudMessageRequest>,
+
+    /// The file currently being written, if any
+    current_file: Option<OpenFile>,
+
+    /// The number of files opened by this sink, used to keep file names unique
+    file_count: u64,
+}
+
+impl ParquetSink {
+    /// Creates a new sink and the output directory if it does not exist
+    ///
+    /// # Arguments
+    /// - `config`: the sink config
+    pub fn new(config: Config) -> Result<Self, CloudAdapterError> {
+        fs::create_dir_all(&config.output_directory).map_err(CloudAdapterError::io)?;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                TIMESTAMP_COLUMN,
+                DataType::Timestamp(TimeUnit::Microsecond, Some(UTC_TIMEZONE.into())),
+                false,
+            ),
+            Field::new(VALUE_COLUMN, DataType::Utf8, false),
+            Field::new(METADATA_COLUMN, DataType::Utf8, false),
+        ]));
+
+        Ok(Self {
+            config,
+            schema,
+            buffer: Vec::new(),
+            current_file: None,
+            file_count: 0,
+        })
+    }
+
+    /// Adds an emission to the buffer.
+    /// The buffer is written once it is full or the current file has been open for too long.
+    ///
+    /// # Arguments
+    /// - `message`: the emission to add
+    pub fn push(&mut self, message: CloudMessageRequest) -> Result<(), CloudAdapterError> {
+        self.buffer.push(message);
+
+        let file_expired = self
+            .current_file
+            .as_ref()
+            .is_some_and(|f| f.opened_at.elapsed() >= self.max_file_duration());
+
+        if self.buffer.len() >= self.config.batch_size || file_expired {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the buffer to the current file as a record batch, rotating the file if necessary
+    pub fn flush(&mut self) -> Result<(), CloudAdapterError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let batch = self.create_batch()?;
+
+        if self.current_file.is_none() {
+            self.current_file = Some(self.open_file()?);
+        }
+
+        let max_file_duration = self.max_file_duration();
+        let should_rotate = {
+            // This is always Some since it was populated above
+            let file = self.current_file.as_mut().unwrap();
+            file.writer
+                .write(&batch)
+                .map_err(CloudAdapterError::serialize)?;
+            file.writer.flush().map_err(CloudAdapterError::io)?;
+
+            // Use the size of the completed row groups, since the writer buffers its output
+            let file_size: i64 = file
+                .writer
+                .flushed_row_groups()
+                .iter()
+                .map(|row_group| row_group.compressed_size())
+                .sum();
+
+            file_size as u64 >= self.config.max_file_size_bytes
+                || file.opened_at.elapsed() >= max_file_duration
+        };
+
+        if should_rotate {
+            self.close_file()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes any buffered emissions and completes the current file
+    pub fn close(&mut self) -> Result<(), CloudAdapterError> {
+        self.flush()?;
+        self.close_file()
+    }
+
+    /// Gets the maximum time that a file can stay open
+    fn max_file_duration(&self) -> Duration {
+        Duration::from_secs(self.config.max_file_duration_s)
+    }
+
+    /// Drains the buffer into a record batch
+    fn create_batch(&mut self) -> Result<RecordBatch, CloudAdapterError> {
+        let mut timestamps = Vec::with_capacity(self.buffer.len());
+        let mut values = Vec::with_capacity(self.buffer.len());
+        let mut metadata = Vec::with_capacity(self.buffer.len());
+
+        for message in self.buffer.drain(..) {
+            timestamps.push((message.signal_timestamp.unix_timestamp_nanos() / 1000) as i64);
+            values.push(message.signal_value.to_string());
+
+            // Sort the keys so that the serialized metadata is stable
+            let sorted: BTreeMap<_, _> = message.metadata.into_iter().collect();
+            metadata.push(serde_json::to_string(&sorted).map_err(CloudAdapterError::serialize)?);
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMicrosecondArray::from(timestamps).with_timezone(UTC_TIMEZONE)),
+            Arc::new(StringArray::from(values)),
+            Arc::new(StringArray::from(metadata)),
+        ];
+
+        RecordBatch::try_new(self.schema.clone(), columns).map_err(CloudAdapterError::serialize)
+    }
+
+    /// Opens a new file in the output directory
+    fn open_file(&mut self) -> Result<OpenFile, CloudAdapterError> {
+        let file_name = format!(
+            "{}-{}-{}.{PARQUET_EXT}",
+            self.config.file_prefix,
+            OffsetDateTime::now_utc().unix_timestamp_nanos(),
+            self.file_count
+        );
+        let final_path = Path::new(&self.config.output_directory).join(&file_name);
+        let in_progress_path =
+            Path::new(&self.config.output_directory).join(format!("{file_name}.{IN_PROGRESS_EXT}"));
+
+        let file = File::create(&in_progress_path).map_err(CloudAdapterError::io)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(file, self.schema.clone(), Some(properties))
+            .map_err(CloudAdapterError::io)?;
+
+        self.file_count += 1;
+        info!("Started writing emissions to {}", final_path.display());
+
+        Ok(OpenFile {
+            writer,
+            in_progress_path,
+            final_path,
+            opened_at: Instant::now(),
+        })
+    }
+
+    /// Completes the current file, if any, and moves it to its final path
+    fn close_file(&mut self) -> Result<(), CloudAdapterError> {
+        if let Some(file) = self.current_file.take() {
+            file.writer.close().map_err(CloudAdapterError::io)?;
+            fs::rename(&file.in_progress_path, &file.final_path).map_err(CloudAdapterError::io)?;
+
+            info!("Completed {}", file.final_path.display());
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ParquetSink {
+    /// Writes any buffered emissions and completes the current file
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            error!("Failed to complete Parquet file: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod parquet_sink_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use freyja_test_common::fixtures::TempDirFixture;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    /// Creates a unique output directory which is deleted when dropped
+    struct OutputDirectory {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl OutputDirectory {
+        fn new() -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join("output");
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+
+        /// Gets the paths of the files in the directory with the given extension
+        fn files_with_extension(&self, extension: &str) -> Vec<PathBuf> {
+            fs::read_dir(&self.path)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension().is_some_and(|e| e == extension))
+                .collect()
+        }
+    }
+
+    fn create_config(
+        output_directory: &OutputDirectory,
+        batch_size: usize,
+        max_file_size_bytes: u64,
+    ) -> Config {
+        Config {
+            output_directory: output_directory.path.to_string_lossy().into_owned(),
+            file_prefix: "test".to_string(),
+            batch_size,
+            max_file_size_bytes,
+            max_file_duration_s: 3600,
+        }
+    }
+
+    fn create_message(value: &str) -> CloudMessageRequest {
+        CloudMessageRequest {
+            metadata: HashMap::from([("signal_id".to_string(), "some-id".to_string())]),
+            signal_value: value.into(),
+            numeric_value: value.parse().ok(),
+            signal_timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+        }
+    }
+
+    fn count_rows(path: &Path) -> usize {
+        let file = File::open(path).unwrap();
+        ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum()
+    }
+
+    #[test]
+    fn push_buffers_until_batch_is_full() {
+        let output_directory = OutputDirectory::new();
+        let mut sink = ParquetSink::new(create_config(&output_directory, 3, u64::MAX)).unwrap();
+
+        sink.push(create_message("1")).unwrap();
+        sink.push(create_message("2")).unwrap();
+        assert!(sink.current_file.is_none());
+        assert_eq!(sink.buffer.len(), 2);
+
+        sink.push(create_message("3")).unwrap();
+        assert!(sink.current_file.is_some());
+        assert!(sink.buffer.is_empty());
+        assert_eq!(
+            output_directory.files_with_extension(IN_PROGRESS_EXT).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn close_completes_file() {
+        let output_directory = OutputDirectory::new();
+        let mut sink = ParquetSink::new(create_config(&output_directory, 2, u64::MAX)).unwrap();
+
+        for value in ["1", "2", "3"] {
+            sink.push(create_message(value)).unwrap();
+        }
+
+        sink.close().unwrap();
+
+        assert!(output_directory
+            .files_with_extension(IN_PROGRESS_EXT)
+            .is_empty());
+        let files = output_directory.files_with_extension(PARQUET_EXT);
+        assert_eq!(files.len(), 1);
+        assert_eq!(count_rows(&files[0]), 3);
+    }
+
+    #[test]
+    fn flush_rotates_file_when_size_is_exceeded() {
+        let output_directory = OutputDirectory::new();
+        let mut sink = ParquetSink::new(create_config(&output_directory, 1, 1)).unwrap();
+
+        for value in ["1", "2", "3"] {
+            sink.push(create_message(value)).unwrap();
+        }
+
+        assert!(sink.current_file.is_none());
+        let files = output_directory.files_with_extension(PARQUET_EXT);
+        assert_eq!(files.len(), 3);
+        for file in files.iter() {
+            assert_eq!(count_rows(file), 1);
+        }
+    }
+
+    #[test]
+    fn drop_completes_file() {
+        let output_directory = OutputDirectory::new();
+        {
+            let mut sink =
+                ParquetSink::new(create_config(&output_directory, 10, u64::MAX)).unwrap();
+            sink.push(create_message("1")).unwrap();
+        }
+
+        let files = output_directory.files_with_extension(PARQUET_EXT);
+        assert_eq!(files.len(), 1);
+        assert_eq!(count_rows(&files[0]), 1);
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the replicating cloud adapter
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The maximum number of messages to keep for each inner adapter while it's failing.
+    /// The oldest message is dropped when this is exceeded.
+    pub max_pending_messages: usize,
+
+    /// The time in milliseconds to wait before retrying an inner adapter after its first failure
+    pub retry_interval_ms: u64,
+
+    /// The maximum time in milliseconds to wait before retrying an inner adapter.
+    /// The retry interval doubles after each failure until it reaches this value.
+    pub max_retry_interval_ms: u64,
+}
@@ -0,0 +1,113 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{marker::PhantomData, sync::Arc};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::{config::Config, replicator::Replicator};
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    cloud_adapter::{
+        create_boxed, BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest,
+        CloudMessageResponse,
+    },
+    config_utils, out_dir,
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A cloud adapter which sends every emission to two inner adapters, such as the old and new
+/// backends during a migration. Each inner adapter has its own retry state, so one failing
+/// adapter doesn't affect what the other receives.
+/// More than two adapters can be used by nesting this type, for example
+/// `ReplicatingCloudAdapter<A, ReplicatingCloudAdapter<B, C>>`.
+pub struct ReplicatingCloudAdapter<TPrimary, TReplica> {
+    /// Sends messages to the inner adapters
+    replicator: Replicator,
+
+    _adapters: PhantomData<fn() -> (TPrimary, TReplica)>,
+}
+
+#[async_trait]
+impl<TPrimary, TReplica> CloudAdapter for ReplicatingCloudAdapter<TPrimary, TReplica>
+where
+    TPrimary: CloudAdapter + Send + Sync + 'static,
+    TReplica: CloudAdapter + Send + Sync + 'static,
+{
+    /// Creates a new instance of a CloudAdapter with default settings.
+    /// This also creates the inner adapters.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to pass to the inner adapters
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            CloudAdapterError::io,
+            CloudAdapterError::deserialize,
+        )?;
+
+        let adapters = vec![
+            create_boxed::<TPrimary>(selector.clone(), None)?,
+            create_boxed::<TReplica>(selector, None)?,
+        ];
+
+        Ok(Self {
+            replicator: Replicator::new(adapters, config),
+            _adapters: PhantomData,
+        })
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// The tenant's config overrides are applied, and the inner adapters are also created for the tenant.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to pass to the inner adapters
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        let config: Config = config_utils::read_from_files_for_tenant(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            tenant,
+            CloudAdapterError::io,
+            CloudAdapterError::deserialize,
+        )?;
+
+        let adapters = vec![
+            create_boxed::<TPrimary>(selector.clone(), Some(tenant))?,
+            create_boxed::<TReplica>(selector, Some(tenant))?,
+        ];
+
+        Ok(Self {
+            replicator: Replicator::new(adapters, config),
+            _adapters: PhantomData,
+        })
+    }
+
+    /// Sends the signal to every inner adapter.
+    /// Returns an error only if no inner adapter delivered or queued the message.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        self.replicator.send_to_cloud(cloud_message).await
+    }
+
+    /// Checks every inner adapter for backfill requests and merges them.
+    /// Returns an error only if every inner adapter failed.
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        self.replicator.check_for_backfill().await
+    }
+}
@@ -0,0 +1,432 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use log::{debug, warn};
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use freyja_common::cloud_adapter::{
+    BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+};
+
+/// The retry state of an inner adapter
+struct RetryState {
+    /// The messages which haven't been delivered yet, in the order in which they were received
+    pending: VecDeque<CloudMessageRequest>,
+
+    /// The time to wait after the next failure
+    retry_interval: Duration,
+
+    /// The time after which pending messages can be retried, if the adapter is failing
+    next_retry_at: Option<Instant>,
+}
+
+impl RetryState {
+    /// Checks whether pending messages can be retried
+    ///
+    /// # Arguments
+    /// - `now`: the current time
+    fn is_retry_due(&self, now: Instant) -> bool {
+        match self.next_retry_at {
+            Some(next_retry_at) => now >= next_retry_at,
+            None => true,
+        }
+    }
+
+    /// Schedules the next retry and increases the retry interval
+    ///
+    /// # Arguments
+    /// - `max_retry_interval`: the maximum retry interval
+    fn on_failure(&mut self, max_retry_interval: Duration) {
+        self.next_retry_at = Some(Instant::now() + self.retry_interval);
+        self.retry_interval = (self.retry_interval * 2).min(max_retry_interval);
+    }
+
+    /// Resets the retry interval after a message was delivered
+    ///
+    /// # Arguments
+    /// - `retry_interval`: the initial retry interval
+    fn on_success(&mut self, retry_interval: Duration) {
+        self.next_retry_at = None;
+        self.retry_interval = retry_interval;
+    }
+}
+
+/// An inner adapter and its retry state
+struct Replica {
+    /// The inner adapter
+    adapter: Box<dyn CloudAdapter + Send + Sync>,
+
+    /// The retry state, which is independent of the other inner adapters
+    state: Mutex<RetryState>,
+}
+
+impl Replica {
+    /// Sends a message with this replica's adapter, retrying pending messages first if they are due.
+    /// Messages are kept in order, so the message is queued if older messages are still pending.
+    /// Returns true if the message was delivered or queued.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: the message to send
+    /// - `config`: the adapter config
+    async fn send(&self, cloud_message: CloudMessageRequest, config: &Config) -> bool {
+        let retry_interval = Duration::from_millis(config.retry_interval_ms);
+        let max_retry_interval = Duration::from_millis(config.max_retry_interval_ms);
+        let mut state = self.state.lock().await;
+
+        if !state.pending.is_empty() && state.is_retry_due(Instant::now()) {
+            self.flush(&mut state, retry_interval, max_retry_interval)
+                .await;
+        }
+
+        if state.pending.is_empty() {
+            match self.adapter.send_to_cloud(cloud_message.clone()).await {
+                Ok(_) => {
+                    state.on_success(retry_interval);
+                    return true;
+                }
+                Err(e) => {
+                    warn!(
+                        "Cloud adapter {} failed to send message: {e}",
+                        self.adapter.kind()
+                    );
+                    state.on_failure(max_retry_interval);
+                }
+            }
+        }
+
+        if config.max_pending_messages == 0 {
+            return false;
+        }
+
+        if state.pending.len() >= config.max_pending_messages {
+            warn!(
+                "Too many pending messages for cloud adapter {}, dropping the oldest message",
+                self.adapter.kind()
+            );
+            state.pending.pop_front();
+        }
+
+        state.pending.push_back(cloud_message);
+        true
+    }
+
+    /// Sends pending messages in order until one fails
+    ///
+    /// # Arguments
+    /// - `state`: the retry state
+    /// - `retry_interval`: the initial retry interval
+    /// - `max_retry_interval`: the maximum retry interval
+    async fn flush(
+        &self,
+        state: &mut RetryState,
+        retry_interval: Duration,
+        max_retry_interval: Duration,
+    ) {
+        debug!(
+            "Retrying {} pending messages for cloud adapter {}",
+            state.pending.len(),
+            self.adapter.kind()
+        );
+
+        while let Some(cloud_message) = state.pending.front() {
+            match self.adapter.send_to_cloud(cloud_message.clone()).await {
+                Ok(_) => {
+                    state.pending.pop_front();
+                    state.on_success(retry_interval);
+                }
+                Err(e) => {
+                    warn!(
+                        "Cloud adapter {} failed to send pending message: {e}",
+                        self.adapter.kind()
+                    );
+                    state.on_failure(max_retry_interval);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Sends every message to several inner adapters.
+/// Each inner adapter keeps its own queue of pending messages and retry interval,
+/// so a failing adapter doesn't delay or duplicate messages for the others.
+pub(crate) struct Replicator {
+    /// The inner adapters
+    replicas: Vec<Replica>,
+
+    /// The adapter config
+    config: Config,
+}
+
+impl Replicator {
+    /// Creates a new replicator
+    ///
+    /// # Arguments
+    /// - `adapters`: the inner adapters
+    /// - `config`: the adapter config
+    pub fn new(adapters: Vec<Box<dyn CloudAdapter + Send + Sync>>, config: Config) -> Self {
+        let replicas = adapters
+            .into_iter()
+            .map(|adapter| Replica {
+                adapter,
+                state: Mutex::new(RetryState {
+                    pending: VecDeque::new(),
+                    retry_interval: Duration::from_millis(config.retry_interval_ms),
+                    next_retry_at: None,
+                }),
+            })
+            .collect();
+
+        Self { replicas, config }
+    }
+
+    /// Sends the message to every inner adapter concurrently.
+    /// Returns an error only if no inner adapter delivered or queued the message.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: the message to send
+    pub async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let results = join_all(
+            self.replicas
+                .iter()
+                .map(|replica| replica.send(cloud_message.clone(), &self.config)),
+        )
+        .await;
+
+        if results.into_iter().any(|accepted| accepted) {
+            Ok(CloudMessageResponse::default())
+        } else {
+            Err(CloudAdapterError::communication(
+                "No cloud adapter delivered or queued the message",
+            ))
+        }
+    }
+
+    /// Checks every inner adapter for backfill requests and merges them.
+    /// Returns an error only if every inner adapter failed.
+    pub async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        let results = join_all(
+            self.replicas
+                .iter()
+                .map(|replica| replica.adapter.check_for_backfill()),
+        )
+        .await;
+
+        let mut request = BackfillRequest::default();
+        let mut error = None;
+        let mut any_succeeded = false;
+        for (replica, result) in self.replicas.iter().zip(results) {
+            match result {
+                Ok(replica_request) => {
+                    any_succeeded = true;
+                    request.merge(replica_request);
+                }
+                Err(e) => {
+                    warn!(
+                        "Cloud adapter {} failed to check for backfill requests: {e}",
+                        replica.adapter.kind()
+                    );
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        match error {
+            Some(e) if !any_succeeded => Err(e),
+            _ => Ok(request),
+        }
+    }
+}
+
+#[cfg(test)]
+mod replicator_tests {
+    use super::*;
+
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use async_trait::async_trait;
+    use time::OffsetDateTime;
+
+    use freyja_common::{
+        cloud_adapter::CloudAdapterErrorKind,
+        service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+    };
+
+    /// A cloud adapter which records the values it receives and can be made to fail
+    struct RecordingCloudAdapter {
+        received: Arc<std::sync::Mutex<Vec<String>>>,
+        fail: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl CloudAdapter for RecordingCloudAdapter {
+        fn create_new(
+            _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        ) -> Result<Self, CloudAdapterError> {
+            Err(CloudAdapterErrorKind::Unknown.into())
+        }
+
+        async fn send_to_cloud(
+            &self,
+            cloud_message: CloudMessageRequest,
+        ) -> Result<CloudMessageResponse, CloudAdapterError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(CloudAdapterErrorKind::Communication.into());
+            }
+
+            self.received
+                .lock()
+                .unwrap()
+                .push(cloud_message.signal_value.to_string());
+            Ok(CloudMessageResponse::default())
+        }
+
+        async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(CloudAdapterErrorKind::Communication.into());
+            }
+
+            Ok(BackfillRequest {
+                all_signals: true,
+                ..Default::default()
+            })
+        }
+    }
+
+    /// Handles for inspecting and controlling a `RecordingCloudAdapter`
+    struct AdapterHandle {
+        received: Arc<std::sync::Mutex<Vec<String>>>,
+        fail: Arc<AtomicBool>,
+    }
+
+    impl AdapterHandle {
+        fn received(&self) -> Vec<String> {
+            self.received.lock().unwrap().clone()
+        }
+
+        fn set_failing(&self, fail: bool) {
+            self.fail.store(fail, Ordering::SeqCst);
+        }
+    }
+
+    fn create_adapter(fail: bool) -> (Box<dyn CloudAdapter + Send + Sync>, AdapterHandle) {
+        let handle = AdapterHandle {
+            received: Arc::new(std::sync::Mutex::new(Vec::new())),
+            fail: Arc::new(AtomicBool::new(fail)),
+        };
+        let adapter = RecordingCloudAdapter {
+            received: handle.received.clone(),
+            fail: handle.fail.clone(),
+        };
+
+        (Box::new(adapter), handle)
+    }
+
+    fn create_config(max_pending_messages: usize) -> Config {
+        Config {
+            max_pending_messages,
+            retry_interval_ms: 0,
+            max_retry_interval_ms: 0,
+        }
+    }
+
+    fn create_message(value: &str) -> CloudMessageRequest {
+        CloudMessageRequest {
+            metadata: Default::default(),
+            signal_value: value.into(),
+            numeric_value: None,
+            signal_timestamp: OffsetDateTime::UNIX_EPOCH,
+            idempotency_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_to_every_adapter() {
+        let (first, first_handle) = create_adapter(false);
+        let (second, second_handle) = create_adapter(false);
+        let (third, third_handle) = create_adapter(false);
+        let uut = Replicator::new(vec![first, second, third], create_config(10));
+
+        assert!(uut.send_to_cloud(create_message("1")).await.is_ok());
+
+        for handle in [first_handle, second_handle, third_handle] {
+            assert_eq!(handle.received(), vec!["1"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_messages_are_retried_in_order_without_duplicates() {
+        let (healthy, healthy_handle) = create_adapter(false);
+        let (failing, failing_handle) = create_adapter(true);
+        let uut = Replicator::new(vec![healthy, failing], create_config(10));
+
+        assert!(uut.send_to_cloud(create_message("1")).await.is_ok());
+        assert!(uut.send_to_cloud(create_message("2")).await.is_ok());
+        assert_eq!(healthy_handle.received(), vec!["1", "2"]);
+        assert!(failing_handle.received().is_empty());
+
+        failing_handle.set_failing(false);
+        assert!(uut.send_to_cloud(create_message("3")).await.is_ok());
+        assert_eq!(healthy_handle.received(), vec!["1", "2", "3"]);
+        assert_eq!(failing_handle.received(), vec!["1", "2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn oldest_pending_message_is_dropped_when_queue_is_full() {
+        let (healthy, _) = create_adapter(false);
+        let (failing, failing_handle) = create_adapter(true);
+        let uut = Replicator::new(vec![healthy, failing], create_config(2));
+
+        for value in ["1", "2", "3"] {
+            assert!(uut.send_to_cloud(create_message(value)).await.is_ok());
+        }
+
+        failing_handle.set_failing(false);
+        assert!(uut.send_to_cloud(create_message("4")).await.is_ok());
+        assert_eq!(failing_handle.received(), vec!["2", "3", "4"]);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_returns_err_when_no_adapter_accepts_message() {
+        let (first, _) = create_adapter(true);
+        let (second, _) = create_adapter(true);
+        let uut = Replicator::new(vec![first, second], create_config(0));
+
+        let result = uut.send_to_cloud(create_message("1")).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+    }
+
+    #[tokio::test]
+    async fn check_for_backfill_merges_requests_from_healthy_adapters() {
+        let (healthy, _) = create_adapter(false);
+        let (failing, _) = create_adapter(true);
+        let uut = Replicator::new(vec![healthy, failing], create_config(0));
+
+        let result = uut.check_for_backfill().await;
+        assert!(result.is_ok_and(|request| request.all_signals));
+    }
+
+    #[tokio::test]
+    async fn check_for_backfill_returns_err_when_every_adapter_fails() {
+        let (first, _) = create_adapter(true);
+        let (second, _) = create_adapter(true);
+        let uut = Replicator::new(vec![first, second], create_config(0));
+
+        let result = uut.check_for_backfill().await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the data adapter middleware
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// The transformations to apply to each entity's values, keyed by entity id.
+    /// Values of entities which aren't listed here are not changed.
+    #[serde(default)]
+    pub entities: HashMap<String, EntityTransform>,
+}
+
+/// The transformations to apply to an entity's values.
+/// Numeric transformations are applied in the following order: `scale`, `offset`, the `min` and `max` range check,
+/// then `precision`. If any numeric transformation is configured, values which aren't numbers are dropped.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityTransform {
+    /// The id that the data adapter and provider use for this entity, if it's different from the entity id
+    pub source_id: Option<String>,
+
+    /// The factor to multiply values by, such as for converting raw ADC counts to physical units
+    pub scale: Option<f64>,
+
+    /// The amount to add to values after scaling them
+    pub offset: Option<f64>,
+
+    /// The minimum valid value. Smaller values are dropped.
+    pub min: Option<f64>,
+
+    /// The maximum valid value. Larger values are dropped.
+    pub max: Option<f64>,
+
+    /// The number of decimal places to format values with
+    pub precision: Option<usize>,
+}
+
+impl EntityTransform {
+    /// Checks whether any numeric transformations are configured
+    pub fn is_numeric(&self) -> bool {
+        self.scale.is_some()
+            || self.offset.is_some()
+            || self.min.is_some()
+            || self.max.is_some()
+            || self.precision.is_some()
+    }
+}
@@ -0,0 +1,8 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+pub mod config;
+mod transform;
+pub mod transforming_data_adapter;
+pub mod transforming_data_adapter_factory;
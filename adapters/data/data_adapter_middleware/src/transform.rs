@@ -0,0 +1,246 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, sync::Arc};
+
+use log::{debug, warn};
+use time::OffsetDateTime;
+
+use freyja_common::signal_store::{SignalStore, SignalValueObserver};
+
+use crate::config::EntityTransform;
+
+/// The transformations for every configured entity
+pub(crate) struct Transforms {
+    /// The transformations, keyed by entity id
+    entities: HashMap<String, EntityTransform>,
+
+    /// The entity ids, keyed by the ids that data adapters use for them
+    entity_ids: HashMap<String, String>,
+}
+
+impl Transforms {
+    /// Creates a new set of transformations
+    ///
+    /// # Arguments
+    /// - `entities`: the transformations, keyed by entity id
+    pub fn new(entities: HashMap<String, EntityTransform>) -> Self {
+        let entity_ids = entities
+            .iter()
+            .filter_map(|(entity_id, transform)| {
+                transform
+                    .source_id
+                    .as_ref()
+                    .map(|source_id| (source_id.clone(), entity_id.clone()))
+            })
+            .collect();
+
+        Self {
+            entities,
+            entity_ids,
+        }
+    }
+
+    /// Gets the id that data adapters use for an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id
+    pub fn source_id<'a>(&'a self, entity_id: &'a str) -> &'a str {
+        self.entities
+            .get(entity_id)
+            .and_then(|transform| transform.source_id.as_deref())
+            .unwrap_or(entity_id)
+    }
+
+    /// Gets the entity id for an id that data adapters use
+    ///
+    /// # Arguments
+    /// - `source_id`: the id that data adapters use
+    pub fn entity_id<'a>(&'a self, source_id: &'a str) -> &'a str {
+        self.entity_ids
+            .get(source_id)
+            .map(String::as_str)
+            .unwrap_or(source_id)
+    }
+
+    /// Applies an entity's transformations to a value.
+    /// Returns `None` if the value should be dropped.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id
+    /// - `value`: the value to transform
+    pub fn apply(&self, entity_id: &str, value: &str) -> Option<String> {
+        let transform = match self.entities.get(entity_id) {
+            Some(transform) if transform.is_numeric() => transform,
+            _ => return Some(value.to_string()),
+        };
+
+        let raw_value: f64 = match value.trim().parse() {
+            Ok(raw_value) => raw_value,
+            Err(_) => {
+                warn!("Dropping value {value:?} for {entity_id} because it is not a number");
+                return None;
+            }
+        };
+
+        let value = raw_value * transform.scale.unwrap_or(1.0) + transform.offset.unwrap_or(0.0);
+        if transform.min.is_some_and(|min| value < min)
+            || transform.max.is_some_and(|max| value > max)
+        {
+            debug!("Dropping out of range value {value} for {entity_id}");
+            return None;
+        }
+
+        Some(match transform.precision {
+            Some(precision) => format!("{value:.precision$}"),
+            None => value.to_string(),
+        })
+    }
+}
+
+/// Forwards the values that a data adapter sets in its own signal store to the shared signal store
+/// after renaming and transforming them
+pub(crate) struct TransformingForwarder {
+    /// The transformations to apply
+    transforms: Arc<Transforms>,
+
+    /// The shared signal store
+    signals: Arc<SignalStore>,
+}
+
+impl TransformingForwarder {
+    /// Creates a new forwarder
+    ///
+    /// # Arguments
+    /// - `transforms`: the transformations to apply
+    /// - `signals`: the shared signal store
+    pub fn new(transforms: Arc<Transforms>, signals: Arc<SignalStore>) -> Self {
+        Self {
+            transforms,
+            signals,
+        }
+    }
+}
+
+impl SignalValueObserver for TransformingForwarder {
+    /// Forwards a value set by a data adapter to the shared signal store
+    ///
+    /// # Arguments
+    /// - `id`: the id that the data adapter used
+    /// - `value`: the value
+    /// - `_timestamp`: the time at which the value was set, which is set again by the shared store's clock
+    fn on_value_set(&self, id: &str, value: &Arc<str>, timestamp: OffsetDateTime) {
+        self.on_value_set_with_sequence_id(id, value, timestamp, None);
+    }
+
+    /// Forwards a value set by a data adapter to the shared signal store with its sequence id,
+    /// so that the shared store's duplicate filter still drops redelivered values
+    ///
+    /// # Arguments
+    /// - `id`: the id that the data adapter used
+    /// - `value`: the value
+    /// - `_timestamp`: the time at which the value was set, which is set again by the shared store's clock
+    /// - `sequence_id`: the id which the provider assigned to this delivery of the value, if any
+    fn on_value_set_with_sequence_id(
+        &self,
+        id: &str,
+        value: &Arc<str>,
+        _timestamp: OffsetDateTime,
+        sequence_id: Option<&str>,
+    ) {
+        let entity_id = self.transforms.entity_id(id).to_string();
+        if let Some(value) = self.transforms.apply(&entity_id, value) {
+            match sequence_id {
+                Some(sequence_id) => self.signals.set_value_with_sequence_id(
+                    entity_id,
+                    value,
+                    sequence_id.to_string(),
+                ),
+                None => self.signals.set_value(entity_id, value),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod transform_tests {
+    use super::*;
+
+    const ENTITY_ID: &str = "vehicle.speed";
+    const SOURCE_ID: &str = "speed_raw";
+
+    fn create_transforms(transform: EntityTransform) -> Transforms {
+        Transforms::new([(ENTITY_ID.to_string(), transform)].into_iter().collect())
+    }
+
+    #[test]
+    fn apply_scales_and_offsets_values() {
+        let uut = create_transforms(EntityTransform {
+            scale: Some(0.5),
+            offset: Some(-10.0),
+            ..Default::default()
+        });
+
+        assert_eq!(uut.apply(ENTITY_ID, "100"), Some(String::from("40")));
+    }
+
+    #[test]
+    fn apply_formats_values_with_precision() {
+        let uut = create_transforms(EntityTransform {
+            scale: Some(0.1),
+            precision: Some(2),
+            ..Default::default()
+        });
+
+        assert_eq!(uut.apply(ENTITY_ID, "3"), Some(String::from("0.30")));
+    }
+
+    #[test]
+    fn apply_drops_out_of_range_values() {
+        let uut = create_transforms(EntityTransform {
+            scale: Some(2.0),
+            min: Some(0.0),
+            max: Some(100.0),
+            ..Default::default()
+        });
+
+        assert_eq!(uut.apply(ENTITY_ID, "-1"), None);
+        assert_eq!(uut.apply(ENTITY_ID, "51"), None);
+        assert_eq!(uut.apply(ENTITY_ID, "50"), Some(String::from("100")));
+    }
+
+    #[test]
+    fn apply_drops_non_numeric_values_for_numeric_transforms() {
+        let uut = create_transforms(EntityTransform {
+            scale: Some(2.0),
+            ..Default::default()
+        });
+
+        assert_eq!(uut.apply(ENTITY_ID, "fast"), None);
+    }
+
+    #[test]
+    fn apply_does_not_change_values_without_numeric_transforms() {
+        let uut = create_transforms(EntityTransform {
+            source_id: Some(SOURCE_ID.to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(uut.apply(ENTITY_ID, "fast"), Some(String::from("fast")));
+        assert_eq!(uut.apply("other", "slow"), Some(String::from("slow")));
+    }
+
+    #[test]
+    fn ids_are_renamed_in_both_directions() {
+        let uut = create_transforms(EntityTransform {
+            source_id: Some(SOURCE_ID.to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(uut.source_id(ENTITY_ID), SOURCE_ID);
+        assert_eq!(uut.entity_id(SOURCE_ID), ENTITY_ID);
+        assert_eq!(uut.source_id("other"), "other");
+        assert_eq!(uut.entity_id("other"), "other");
+    }
+}
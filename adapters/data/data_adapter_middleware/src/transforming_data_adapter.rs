@@ -0,0 +1,107 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use freyja_common::{
+    data_adapter::{DataAdapter, DataAdapterError, EntityRegistration},
+    entity::EntityEndpoint,
+    signal_store::SignalStore,
+};
+
+use crate::transform::Transforms;
+
+/// A data adapter decorator which renames entity ids for the adapter it wraps.
+/// The values that the wrapped adapter sets are transformed before they reach the shared signal store
+/// by the forwarder that the `TransformingDataAdapterFactory` attaches to the wrapped adapter's signal store.
+pub struct TransformingDataAdapter {
+    /// The decorated adapter
+    inner: Arc<dyn DataAdapter + Send + Sync>,
+
+    /// The transformations to apply
+    transforms: Arc<Transforms>,
+}
+
+impl TransformingDataAdapter {
+    /// Creates a new TransformingDataAdapter
+    ///
+    /// # Arguments
+    /// - `inner`: the adapter to decorate
+    /// - `transforms`: the transformations to apply
+    pub(crate) fn new(
+        inner: Arc<dyn DataAdapter + Send + Sync>,
+        transforms: Arc<Transforms>,
+    ) -> Self {
+        Self { inner, transforms }
+    }
+}
+
+#[async_trait]
+impl DataAdapter for TransformingDataAdapter {
+    /// This adapter wraps an adapter created by another factory,
+    /// so it can only be created with a `TransformingDataAdapterFactory`
+    ///
+    /// # Arguments
+    /// - `_provider_uri`: the provider uri
+    /// - `_signals`: the shared signal store
+    fn create_new(_provider_uri: &str, _signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        Err(DataAdapterError::unknown(
+            "TransformingDataAdapters can only be created with a TransformingDataAdapterFactory",
+        ))
+    }
+
+    /// Starts the decorated adapter
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        self.inner.start().await
+    }
+
+    /// Stops the decorated adapter
+    async fn stop(&self) -> Result<(), DataAdapterError> {
+        self.inner.stop().await
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        self.inner
+            .send_request_to_provider(self.transforms.source_id(entity_id))
+            .await
+    }
+
+    /// Registers an entity with the decorated adapter using the id that the adapter knows it by
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        let registration = self
+            .inner
+            .register_entity(self.transforms.source_id(entity_id), endpoint)
+            .await?;
+
+        Ok(match registration {
+            EntityRegistration::Loopback(mut entity) => {
+                entity.id = self.transforms.entity_id(&entity.id).to_string();
+                EntityRegistration::Loopback(entity)
+            }
+            registration => registration,
+        })
+    }
+
+    /// Gets the kind of the decorated adapter
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterFactory},
+    entity::{Entity, EntityEndpoint},
+    out_dir,
+    signal_store::SignalStore,
+};
+
+use crate::{
+    config::Config,
+    transform::{TransformingForwarder, Transforms},
+    transforming_data_adapter::TransformingDataAdapter,
+};
+
+/// A data adapter factory decorator which applies the configured transformations to the values
+/// of every adapter that the wrapped factory creates, such as `TransformingDataAdapterFactory<MqttDataAdapterFactory>`.
+/// Each wrapped adapter writes its values to its own signal store, and the values are renamed
+/// and transformed before they are set in the shared signal store.
+pub struct TransformingDataAdapterFactory<TFactory> {
+    /// The decorated factory
+    inner: TFactory,
+
+    /// The transformations to apply
+    transforms: Arc<Transforms>,
+}
+
+impl<TFactory> TransformingDataAdapterFactory<TFactory> {
+    /// Creates a new TransformingDataAdapterFactory
+    ///
+    /// # Arguments
+    /// - `inner`: the factory to decorate
+    /// - `config`: the transformation config
+    pub fn new(inner: TFactory, config: Config) -> Self {
+        Self {
+            inner,
+            transforms: Arc::new(Transforms::new(config.entities)),
+        }
+    }
+}
+
+impl<TFactory: DataAdapterFactory> DataAdapterFactory for TransformingDataAdapterFactory<TFactory> {
+    /// Create a new `TransformingDataAdapterFactory`.
+    /// This also creates the decorated factory.
+    fn create_new() -> Result<Self, DataAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        Ok(Self::new(TFactory::create_new()?, config))
+    }
+
+    /// Check to see whether the decorated factory can create a data adapter for the requested entity.
+    /// Returns the first endpoint found that is supported by the decorated factory.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to check for compatibility
+    fn is_supported(&self, entity: &Entity) -> Option<EntityEndpoint> {
+        self.inner.is_supported(entity)
+    }
+
+    /// Create a new data adapter with the decorated factory
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider URI to associate with this data adapter
+    /// - `signals`: the shared signal store
+    fn create_adapter(
+        &self,
+        provider_uri: &str,
+        signals: Arc<SignalStore>,
+    ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError> {
+        // The adapter's own store shares the clock so that replayed timestamps still drive emissions
+        let adapter_signals = Arc::new(SignalStore::with_clock(signals.clock()));
+        adapter_signals.add_value_observer(Arc::new(TransformingForwarder::new(
+            self.transforms.clone(),
+            signals,
+        )));
+
+        let inner = self.inner.create_adapter(provider_uri, adapter_signals)?;
+        Ok(Arc::new(TransformingDataAdapter::new(
+            inner,
+            self.transforms.clone(),
+        )))
+    }
+
+    /// Gets the kind of the decorated factory so that it can be deregistered with the same kind
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+}
+
+#[cfg(test)]
+mod transforming_data_adapter_factory_tests {
+    use super::*;
+
+    use std::{
+        collections::HashSet,
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+
+    use freyja_common::{
+        data_adapter::EntityRegistration, duplicate_filter::DuplicateFilter, signal::SignalPatch,
+    };
+
+    use crate::config::EntityTransform;
+
+    const ENTITY_ID: &str = "vehicle.speed";
+    const SOURCE_ID: &str = "speed_raw";
+
+    /// A data adapter which sets a new raw value whenever a value is requested
+    struct RawValueDataAdapter {
+        signals: Arc<SignalStore>,
+        sequence_id: Option<String>,
+        request_count: AtomicU32,
+    }
+
+    #[async_trait]
+    impl DataAdapter for RawValueDataAdapter {
+        fn create_new(
+            _provider_uri: &str,
+            signals: Arc<SignalStore>,
+        ) -> Result<Self, DataAdapterError> {
+            Ok(Self {
+                signals,
+                sequence_id: None,
+                request_count: AtomicU32::new(0),
+            })
+        }
+
+        async fn start(&self) -> Result<(), DataAdapterError> {
+            Ok(())
+        }
+
+        async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+            let request_count = self.request_count.fetch_add(1, Ordering::SeqCst) + 1;
+            let value = (request_count * 1000).to_string();
+            match &self.sequence_id {
+                Some(sequence_id) => self.signals.set_value_with_sequence_id(
+                    entity_id.to_string(),
+                    value,
+                    sequence_id.clone(),
+                ),
+                None => self.signals.set_value(entity_id.to_string(), value),
+            };
+            Ok(())
+        }
+
+        async fn register_entity(
+            &self,
+            entity_id: &str,
+            _endpoint: &EntityEndpoint,
+        ) -> Result<EntityRegistration, DataAdapterError> {
+            Ok(EntityRegistration::Loopback(Entity {
+                id: entity_id.to_string(),
+                ..Default::default()
+            }))
+        }
+    }
+
+    #[derive(Default)]
+    struct RawValueDataAdapterFactory {
+        sequence_id: Option<String>,
+    }
+
+    impl DataAdapterFactory for RawValueDataAdapterFactory {
+        fn create_new() -> Result<Self, DataAdapterError> {
+            Ok(Self::default())
+        }
+
+        fn is_supported(&self, _entity: &Entity) -> Option<EntityEndpoint> {
+            Some(EntityEndpoint::default())
+        }
+
+        fn create_adapter(
+            &self,
+            provider_uri: &str,
+            signals: Arc<SignalStore>,
+        ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError> {
+            let mut adapter = RawValueDataAdapter::create_new(provider_uri, signals)?;
+            adapter.sequence_id = self.sequence_id.clone();
+            Ok(Arc::new(adapter))
+        }
+    }
+
+    fn create_config() -> Config {
+        Config {
+            entities: [(
+                ENTITY_ID.to_string(),
+                EntityTransform {
+                    source_id: Some(SOURCE_ID.to_string()),
+                    scale: Some(0.1),
+                    ..Default::default()
+                },
+            )]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    fn create_signals() -> Arc<SignalStore> {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ENTITY_ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        signals
+    }
+
+    #[tokio::test]
+    async fn adapters_set_renamed_and_transformed_values() {
+        let signals = create_signals();
+        let uut = TransformingDataAdapterFactory::new(
+            RawValueDataAdapterFactory::default(),
+            create_config(),
+        );
+        let adapter = uut.create_adapter("uri", signals.clone()).unwrap();
+
+        let registration = adapter
+            .register_entity(ENTITY_ID, &EntityEndpoint::default())
+            .await
+            .unwrap();
+        assert!(
+            matches!(registration, EntityRegistration::Loopback(entity) if entity.id == ENTITY_ID)
+        );
+
+        adapter.send_request_to_provider(ENTITY_ID).await.unwrap();
+        assert_eq!(
+            signals.get(&ENTITY_ID.to_string()).unwrap().value,
+            Some("100".into())
+        );
+        assert!(signals.get(&SOURCE_ID.to_string()).is_none());
+    }
+
+    #[tokio::test]
+    async fn adapters_keep_sequence_ids_for_duplicate_filter() {
+        let signals = create_signals();
+        signals.set_duplicate_filter(Arc::new(DuplicateFilter::new(
+            Duration::from_secs(60),
+            HashSet::new(),
+        )));
+
+        let factory = RawValueDataAdapterFactory {
+            sequence_id: Some(String::from("1")),
+        };
+        let uut = TransformingDataAdapterFactory::new(factory, create_config());
+        let adapter = uut.create_adapter("uri", signals.clone()).unwrap();
+
+        adapter.send_request_to_provider(ENTITY_ID).await.unwrap();
+        assert_eq!(
+            signals.get(&ENTITY_ID.to_string()).unwrap().value,
+            Some("100".into())
+        );
+
+        // The second value is delivered with the same sequence id, so it's dropped as a duplicate
+        adapter.send_request_to_provider(ENTITY_ID).await.unwrap();
+        assert_eq!(
+            signals.get(&ENTITY_ID.to_string()).unwrap().value,
+            Some("100".into())
+        );
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// The DTC data adapter config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The logical address which this adapter uses as a diagnostic tester
+    pub tester_address: u16,
+
+    /// The status mask for reading DTCs.
+    /// Only DTCs whose status has at least one of the bits in the mask set are reported
+    pub status_mask: u8,
+
+    /// The interval at which the DTCs of subscribed entities are read, in milliseconds
+    pub poll_interval_ms: u64,
+
+    /// The timeout for connecting to a DoIP entity and for each response from it, in milliseconds
+    pub request_timeout_ms: u64,
+}
@@ -0,0 +1,368 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+
+use crate::uds;
+use freyja_common::data_adapter::DataAdapterError;
+
+/// The version of the DoIP protocol which this client uses (ISO 13400-2:2012)
+const PROTOCOL_VERSION: u8 = 0x02;
+
+/// The length of the header of each DoIP message
+const HEADER_LENGTH: usize = 8;
+
+/// The maximum payload length which this client accepts, which prevents a faulty entity from exhausting memory
+const MAX_PAYLOAD_LENGTH: usize = 64 * 1024;
+
+/// The payload type of negative acknowledgements of a message's header
+const GENERIC_HEADER_NACK: u16 = 0x0000;
+
+/// The payload type of routing activation requests
+const ROUTING_ACTIVATION_REQUEST: u16 = 0x0005;
+
+/// The payload type of routing activation responses
+const ROUTING_ACTIVATION_RESPONSE: u16 = 0x0006;
+
+/// The payload type of the alive check requests which an entity sends to check that the tester is still connected
+const ALIVE_CHECK_REQUEST: u16 = 0x0007;
+
+/// The payload type of alive check responses
+const ALIVE_CHECK_RESPONSE: u16 = 0x0008;
+
+/// The payload type of diagnostic messages, which carry UDS requests and responses
+const DIAGNOSTIC_MESSAGE: u16 = 0x8001;
+
+/// The payload type of positive acknowledgements of diagnostic messages
+const DIAGNOSTIC_MESSAGE_ACK: u16 = 0x8002;
+
+/// The payload type of negative acknowledgements of diagnostic messages
+const DIAGNOSTIC_MESSAGE_NACK: u16 = 0x8003;
+
+/// The default routing activation type
+const DEFAULT_ACTIVATION_TYPE: u8 = 0x00;
+
+/// The routing activation response code which means that routing was activated
+const ROUTING_SUCCESSFULLY_ACTIVATED: u8 = 0x10;
+
+/// A connection to a DoIP entity, such as a vehicle's diagnostic gateway, with routing activated
+pub(crate) struct DoipConnection {
+    /// The connection to the entity
+    stream: TcpStream,
+
+    /// The logical address of the tester
+    tester_address: u16,
+
+    /// The timeout for each response from the entity
+    response_timeout: Duration,
+}
+
+impl DoipConnection {
+    /// Connects to a DoIP entity and activates routing
+    ///
+    /// # Arguments
+    /// - `host`: the host of the entity
+    /// - `port`: the port of the entity
+    /// - `tester_address`: the logical address of the tester
+    /// - `response_timeout`: the timeout for connecting and for each response from the entity
+    pub async fn connect(
+        host: &str,
+        port: u16,
+        tester_address: u16,
+        response_timeout: Duration,
+    ) -> Result<Self, DataAdapterError> {
+        let stream = timeout(response_timeout, TcpStream::connect((host, port)))
+            .await
+            .map_err(DataAdapterError::communication)?
+            .map_err(DataAdapterError::communication)?;
+
+        let mut connection = Self {
+            stream,
+            tester_address,
+            response_timeout,
+        };
+        connection.activate_routing().await?;
+
+        Ok(connection)
+    }
+
+    /// Sends a UDS request to an ECU and returns its final response.
+    /// Responses which mean that the ECU is still processing the request are skipped.
+    ///
+    /// # Arguments
+    /// - `target_address`: the logical address of the ECU
+    /// - `request`: the UDS request
+    pub async fn send_diagnostic_request(
+        &mut self,
+        target_address: u16,
+        request: &[u8],
+    ) -> Result<Vec<u8>, DataAdapterError> {
+        let mut payload = Vec::with_capacity(4 + request.len());
+        payload.extend_from_slice(&self.tester_address.to_be_bytes());
+        payload.extend_from_slice(&target_address.to_be_bytes());
+        payload.extend_from_slice(request);
+        self.write_message(DIAGNOSTIC_MESSAGE, &payload).await?;
+
+        loop {
+            let (payload_type, payload) = self.read_message().await?;
+            match payload_type {
+                DIAGNOSTIC_MESSAGE_ACK => continue,
+                DIAGNOSTIC_MESSAGE_NACK => {
+                    return Err(DataAdapterError::communication(format!(
+                        "The DoIP entity rejected the request to ECU {target_address:#06x} with code {:#04x}",
+                        payload.get(4).copied().unwrap_or_default()
+                    )));
+                }
+                DIAGNOSTIC_MESSAGE if payload.len() >= 4 => {
+                    let source_address = u16::from_be_bytes([payload[0], payload[1]]);
+                    let response = &payload[4..];
+                    if source_address != target_address || uds::is_response_pending(response) {
+                        continue;
+                    }
+
+                    return Ok(response.to_vec());
+                }
+                _ => {
+                    return Err(DataAdapterError::communication(format!(
+                        "Unexpected DoIP message with payload type {payload_type:#06x} while waiting for ECU {target_address:#06x}"
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Activates routing so that the entity forwards diagnostic messages to its ECUs
+    async fn activate_routing(&mut self) -> Result<(), DataAdapterError> {
+        let mut payload = Vec::with_capacity(7);
+        payload.extend_from_slice(&self.tester_address.to_be_bytes());
+        payload.push(DEFAULT_ACTIVATION_TYPE);
+        payload.extend_from_slice(&[0; 4]);
+        self.write_message(ROUTING_ACTIVATION_REQUEST, &payload)
+            .await?;
+
+        let (payload_type, payload) = self.read_message().await?;
+        match (payload_type, payload.get(4)) {
+            (ROUTING_ACTIVATION_RESPONSE, Some(&ROUTING_SUCCESSFULLY_ACTIVATED)) => Ok(()),
+            (ROUTING_ACTIVATION_RESPONSE, code) => Err(DataAdapterError::communication(format!(
+                "The DoIP entity denied routing activation with code {:#04x}",
+                code.copied().unwrap_or_default()
+            ))),
+            _ => Err(DataAdapterError::communication(format!(
+                "Unexpected DoIP message with payload type {payload_type:#06x} in response to routing activation"
+            ))),
+        }
+    }
+
+    /// Writes a message to the entity
+    ///
+    /// # Arguments
+    /// - `payload_type`: the payload type of the message
+    /// - `payload`: the payload of the message
+    async fn write_message(
+        &mut self,
+        payload_type: u16,
+        payload: &[u8],
+    ) -> Result<(), DataAdapterError> {
+        self.stream
+            .write_all(&encode_message(payload_type, payload))
+            .await
+            .map_err(DataAdapterError::communication)
+    }
+
+    /// Reads the next message from the entity and returns its payload type and payload.
+    /// Alive check requests are answered rather than returned.
+    async fn read_message(&mut self) -> Result<(u16, Vec<u8>), DataAdapterError> {
+        loop {
+            let mut header = [0; HEADER_LENGTH];
+            self.read_exact(&mut header).await?;
+            let (payload_type, payload_length) = decode_header(&header)?;
+
+            let mut payload = vec![0; payload_length];
+            self.read_exact(&mut payload).await?;
+
+            match payload_type {
+                ALIVE_CHECK_REQUEST => {
+                    let source_address = self.tester_address.to_be_bytes();
+                    self.write_message(ALIVE_CHECK_RESPONSE, &source_address)
+                        .await?;
+                }
+                GENERIC_HEADER_NACK => {
+                    return Err(DataAdapterError::communication(format!(
+                        "The DoIP entity rejected a message header with code {:#04x}",
+                        payload.first().copied().unwrap_or_default()
+                    )));
+                }
+                _ => return Ok((payload_type, payload)),
+            }
+        }
+    }
+
+    /// Fills a buffer with bytes from the entity, or returns an error if they don't arrive in time
+    ///
+    /// # Arguments
+    /// - `buffer`: the buffer to fill
+    async fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), DataAdapterError> {
+        timeout(self.response_timeout, self.stream.read_exact(buffer))
+            .await
+            .map_err(DataAdapterError::communication)?
+            .map_err(DataAdapterError::communication)?;
+
+        Ok(())
+    }
+}
+
+/// Encodes a DoIP message
+///
+/// # Arguments
+/// - `payload_type`: the payload type of the message
+/// - `payload`: the payload of the message
+fn encode_message(payload_type: u16, payload: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    message.push(PROTOCOL_VERSION);
+    message.push(!PROTOCOL_VERSION);
+    message.extend_from_slice(&payload_type.to_be_bytes());
+    message.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    message.extend_from_slice(payload);
+
+    message
+}
+
+/// Decodes the header of a DoIP message and returns its payload type and payload length
+///
+/// # Arguments
+/// - `header`: the header to decode
+fn decode_header(header: &[u8; HEADER_LENGTH]) -> Result<(u16, usize), DataAdapterError> {
+    if header[0] != !header[1] {
+        return Err(DataAdapterError::parse(format!(
+            "Invalid DoIP header with protocol version {:#04x} and inverse {:#04x}",
+            header[0], header[1]
+        )));
+    }
+
+    let payload_type = u16::from_be_bytes([header[2], header[3]]);
+    let payload_length = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    if payload_length > MAX_PAYLOAD_LENGTH {
+        return Err(DataAdapterError::parse(format!(
+            "The DoIP message has a payload of {payload_length} bytes, which is more than the maximum of {MAX_PAYLOAD_LENGTH}"
+        )));
+    }
+
+    Ok((payload_type, payload_length))
+}
+
+#[cfg(test)]
+pub(crate) mod doip_tests {
+    use super::*;
+
+    use tokio::net::TcpListener;
+
+    /// The logical address of the tester in the tests
+    pub const TESTER_ADDRESS: u16 = 0x0E00;
+
+    /// The logical address of the ECU in the tests
+    pub const ECU_ADDRESS: u16 = 0x0010;
+
+    /// Reads a message from a tester in a fake DoIP entity
+    ///
+    /// # Arguments
+    /// - `stream`: the connection to the tester
+    async fn read_request(stream: &mut TcpStream) -> (u16, Vec<u8>) {
+        let mut header = [0; HEADER_LENGTH];
+        stream.read_exact(&mut header).await.unwrap();
+        let (payload_type, payload_length) = decode_header(&header).unwrap();
+        let mut payload = vec![0; payload_length];
+        stream.read_exact(&mut payload).await.unwrap();
+
+        (payload_type, payload)
+    }
+
+    /// Starts a fake DoIP entity which activates routing for one tester and responds to its diagnostic request
+    /// with an acknowledgement, a pending response, an alive check, and then the provided UDS response.
+    /// Returns the port of the entity.
+    ///
+    /// # Arguments
+    /// - `response`: the UDS response to send from the ECU
+    pub async fn start_fake_entity(response: Vec<u8>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let (payload_type, _) = read_request(&mut stream).await;
+            assert_eq!(payload_type, ROUTING_ACTIVATION_REQUEST);
+            let mut activation = TESTER_ADDRESS.to_be_bytes().to_vec();
+            activation.extend_from_slice(&ECU_ADDRESS.to_be_bytes());
+            activation.extend_from_slice(&[ROUTING_SUCCESSFULLY_ACTIVATED, 0, 0, 0, 0]);
+            let message = encode_message(ROUTING_ACTIVATION_RESPONSE, &activation);
+            stream.write_all(&message).await.unwrap();
+
+            let (payload_type, _) = read_request(&mut stream).await;
+            assert_eq!(payload_type, DIAGNOSTIC_MESSAGE);
+
+            let addresses = [ECU_ADDRESS.to_be_bytes(), TESTER_ADDRESS.to_be_bytes()].concat();
+            let pending = [addresses.as_slice(), &[0x7F, 0x19, 0x78]].concat();
+            let final_response = [addresses.as_slice(), &response].concat();
+            for (payload_type, payload) in [
+                (
+                    DIAGNOSTIC_MESSAGE_ACK,
+                    [addresses.as_slice(), &[0x00]].concat(),
+                ),
+                (DIAGNOSTIC_MESSAGE, pending),
+                (ALIVE_CHECK_REQUEST, Vec::new()),
+            ] {
+                let message = encode_message(payload_type, &payload);
+                stream.write_all(&message).await.unwrap();
+            }
+
+            let (payload_type, _) = read_request(&mut stream).await;
+            assert_eq!(payload_type, ALIVE_CHECK_RESPONSE);
+
+            let message = encode_message(DIAGNOSTIC_MESSAGE, &final_response);
+            stream.write_all(&message).await.unwrap();
+        });
+
+        port
+    }
+
+    #[test]
+    fn decode_header_reads_encoded_message() {
+        let message = encode_message(DIAGNOSTIC_MESSAGE, &[1, 2, 3]);
+        let header: [u8; HEADER_LENGTH] = message[..HEADER_LENGTH].try_into().unwrap();
+
+        assert_eq!(decode_header(&header).unwrap(), (DIAGNOSTIC_MESSAGE, 3));
+    }
+
+    #[test]
+    fn decode_header_returns_err_for_invalid_header() {
+        let mut header = [0x02, 0xFD, 0x80, 0x01, 0, 0, 0, 3];
+        header[1] = 0xFC;
+        assert!(decode_header(&header).is_err());
+
+        let oversized = [0x02, 0xFD, 0x80, 0x01, 0xFF, 0xFF, 0xFF, 0xFF];
+        assert!(decode_header(&oversized).is_err());
+    }
+
+    #[tokio::test]
+    async fn send_diagnostic_request_returns_final_response() {
+        let port = start_fake_entity(vec![0x59, 0x02, 0xFF]).await;
+
+        let mut connection =
+            DoipConnection::connect("127.0.0.1", port, TESTER_ADDRESS, Duration::from_secs(5))
+                .await
+                .unwrap();
+        let response = connection
+            .send_diagnostic_request(ECU_ADDRESS, &uds::read_dtcs_by_status_mask(0xFF))
+            .await
+            .unwrap();
+
+        assert_eq!(response, vec![0x59, 0x02, 0xFF]);
+    }
+}
@@ -0,0 +1,420 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::{info, warn};
+use tokio::task::JoinHandle;
+
+use crate::{config::Config, doip::DoipConnection, uds};
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
+    entity::{EntityEndpoint, Operation},
+    out_dir,
+    signal_store::SignalStore,
+    uri::{EndpointUri, UriKind},
+};
+
+/// An entity whose value is the list of DTCs of an ECU
+#[derive(Clone, Debug)]
+struct DtcEntity {
+    /// The logical address of the ECU
+    ecu_address: u16,
+
+    /// The operation which the adapter uses for the entity
+    operation: Operation,
+
+    /// The last value which the adapter wrote to the signal store
+    last_value: Option<String>,
+}
+
+/// Reads diagnostic trouble codes from ECUs through a DoIP entity, such as a vehicle's diagnostic gateway,
+/// and writes each ECU's DTCs to the signal store as a JSON array
+pub struct DtcDataAdapter {
+    /// The adapter config
+    config: Config,
+
+    /// The host of the DoIP entity
+    host: String,
+
+    /// The port of the DoIP entity
+    port: u16,
+
+    /// The entities which this adapter reads
+    entities: Arc<Mutex<HashMap<String, DtcEntity>>>,
+
+    /// Shared signal store for all data adapters to push new signal values
+    signals: Arc<SignalStore>,
+
+    /// The task which polls the DTCs of subscribed entities, once the adapter has started
+    poll_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DtcDataAdapter {
+    /// Creates a data adapter with a config.
+    /// Returns an error if the provider uri isn't a valid DoIP uri or the poll interval is 0
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the uri of the DoIP entity
+    /// - `signals`: the shared signal store
+    /// - `config`: the adapter config
+    fn with_config(
+        provider_uri: &str,
+        signals: Arc<SignalStore>,
+        config: Config,
+    ) -> Result<Self, DataAdapterError> {
+        let provider_uri =
+            EndpointUri::parse(provider_uri, UriKind::Doip).map_err(DataAdapterError::parse)?;
+
+        if config.poll_interval_ms == 0 {
+            return Err(DataAdapterError::deserialize(
+                "The DTC data adapter's poll_interval_ms must not be 0",
+            ));
+        }
+
+        Ok(Self {
+            config,
+            host: provider_uri.host().to_string(),
+            port: provider_uri.port(),
+            entities: Arc::new(Mutex::new(HashMap::new())),
+            signals,
+            poll_task: Mutex::new(None),
+        })
+    }
+
+    /// Reads the DTCs of an ECU and returns them as a JSON array
+    ///
+    /// # Arguments
+    /// - `host`: the host of the DoIP entity
+    /// - `port`: the port of the DoIP entity
+    /// - `config`: the adapter config
+    /// - `ecu_address`: the logical address of the ECU
+    async fn read_dtcs(
+        host: &str,
+        port: u16,
+        config: &Config,
+        ecu_address: u16,
+    ) -> Result<String, DataAdapterError> {
+        let mut connection = DoipConnection::connect(
+            host,
+            port,
+            config.tester_address,
+            Duration::from_millis(config.request_timeout_ms),
+        )
+        .await?;
+
+        let request = uds::read_dtcs_by_status_mask(config.status_mask);
+        let response = connection
+            .send_diagnostic_request(ecu_address, &request)
+            .await?;
+        let dtcs = uds::parse_dtcs(&response)?;
+
+        serde_json::to_string(&dtcs).map_err(DataAdapterError::serialize)
+    }
+
+    /// Reads the DTCs of each subscribed entity and writes the ones which changed to the signal store
+    ///
+    /// # Arguments
+    /// - `host`: the host of the DoIP entity
+    /// - `port`: the port of the DoIP entity
+    /// - `config`: the adapter config
+    /// - `entities`: the entities which the adapter reads
+    /// - `signals`: the shared signal store
+    async fn poll_subscribed_entities(
+        host: &str,
+        port: u16,
+        config: &Config,
+        entities: &Mutex<HashMap<String, DtcEntity>>,
+        signals: &SignalStore,
+    ) {
+        let subscribed: Vec<(String, DtcEntity)> = entities
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entity)| entity.operation == Operation::Subscribe)
+            .map(|(id, entity)| (id.clone(), entity.clone()))
+            .collect();
+
+        for (entity_id, entity) in subscribed {
+            let value = match Self::read_dtcs(host, port, config, entity.ecu_address).await {
+                Ok(value) => value,
+                Err(e) => {
+                    warn!("Failed to read the DTCs of entity {entity_id}: {e}");
+                    continue;
+                }
+            };
+
+            // Only changes are written so that each new or cleared DTC is emitted once
+            if entity.last_value.as_ref() != Some(&value) {
+                Self::set_value(entities, signals, entity_id, value);
+            }
+        }
+    }
+
+    /// Writes the DTCs of an entity to the signal store
+    ///
+    /// # Arguments
+    /// - `entities`: the entities which the adapter reads
+    /// - `signals`: the shared signal store
+    /// - `entity_id`: the entity id
+    /// - `value`: the DTCs as a JSON array
+    fn set_value(
+        entities: &Mutex<HashMap<String, DtcEntity>>,
+        signals: &SignalStore,
+        entity_id: String,
+        value: String,
+    ) {
+        if let Some(entity) = entities.lock().unwrap().get_mut(&entity_id) {
+            entity.last_value = Some(value.clone());
+        }
+
+        if signals.set_value(entity_id.clone(), value).is_none() {
+            warn!("Read DTCs for unknown entity {entity_id}");
+        }
+    }
+}
+
+#[async_trait]
+impl DataAdapter for DtcDataAdapter {
+    /// Creates a data adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri for accessing an entity's information
+    /// - `signals`: the shared signal store
+    fn create_new(provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        Self::with_config(provider_uri, signals, config)
+    }
+
+    /// Starts a data adapter, which starts polling the DTCs of subscribed entities
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        let host = self.host.clone();
+        let port = self.port;
+        let config = self.config.clone();
+        let entities = self.entities.clone();
+        let signals = self.signals.clone();
+
+        let poll_task = tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_millis(config.poll_interval_ms));
+            loop {
+                interval.tick().await;
+                Self::poll_subscribed_entities(&host, port, &config, &entities, &signals).await;
+            }
+        });
+        *self.poll_task.lock().unwrap() = Some(poll_task);
+
+        info!("Started a DtcDataAdapter for {}:{}", self.host, self.port);
+
+        Ok(())
+    }
+
+    /// Stops a data adapter, which stops polling the DTCs of subscribed entities
+    async fn stop(&self) -> Result<(), DataAdapterError> {
+        if let Some(poll_task) = self.poll_task.lock().unwrap().take() {
+            poll_task.abort();
+        }
+
+        info!("Stopped the DtcDataAdapter for {}:{}", self.host, self.port);
+
+        Ok(())
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        let entity = self.entities.lock().unwrap().get(entity_id).cloned();
+        let entity = match entity {
+            Some(entity) => entity,
+            None => {
+                let message = format!("Entity {entity_id} does not have an operation registered");
+                info!("{message}");
+                return Err(DataAdapterError::unknown(message));
+            }
+        };
+
+        // Subscribed entities are read by the polling task
+        if entity.operation != Operation::Get {
+            return Ok(());
+        }
+
+        let value =
+            Self::read_dtcs(&self.host, self.port, &self.config, entity.ecu_address).await?;
+        Self::set_value(&self.entities, &self.signals, entity_id.to_string(), value);
+
+        Ok(())
+    }
+
+    /// Registers an entity id to a local cache inside a data adapter to keep track of which entities a data adapter contains.
+    /// The endpoint's context is the logical address of the ECU, such as `0x0010`.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        // Prefer subscribe if present
+        let operation = endpoint
+            .select_operation(&[Operation::Subscribe, Operation::Get])
+            .ok_or::<DataAdapterError>(DataAdapterErrorKind::OperationNotSupported.into())?;
+        let ecu_address = parse_address(&endpoint.context)?;
+
+        self.entities.lock().unwrap().insert(
+            String::from(entity_id),
+            DtcEntity {
+                ecu_address,
+                operation,
+                last_value: None,
+            },
+        );
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+/// Parses a logical address in hexadecimal with a `0x` prefix, such as `0x0010`, or in decimal
+///
+/// # Arguments
+/// - `address`: the address to parse
+fn parse_address(address: &str) -> Result<u16, DataAdapterError> {
+    let address = address.trim();
+    let result = match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => address.parse(),
+    };
+
+    result.map_err(|_| {
+        DataAdapterError::parse(format!(
+            "Invalid ECU address {address:?}. Expected a logical address such as 0x0010"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod dtc_data_adapter_tests {
+    use super::*;
+
+    use freyja_common::{entity::Protocol, signal::SignalPatch};
+
+    use crate::doip::doip_tests::{start_fake_entity, ECU_ADDRESS, TESTER_ADDRESS};
+
+    const ENTITY_ID: &str = "dtmi:sdv:Powertrain:Engine:DTCs;1";
+
+    fn create_config() -> Config {
+        Config {
+            tester_address: TESTER_ADDRESS,
+            status_mask: 0xFF,
+            poll_interval_ms: 1000,
+            request_timeout_ms: 5000,
+        }
+    }
+
+    fn create_adapter(port: u16) -> DtcDataAdapter {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ENTITY_ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        DtcDataAdapter::with_config(
+            &format!("doip://127.0.0.1:{port}"),
+            signals,
+            create_config(),
+        )
+        .unwrap()
+    }
+
+    fn create_endpoint(operation: Operation, context: &str) -> EntityEndpoint {
+        EntityEndpoint {
+            protocol: Protocol::Doip.to_string(),
+            operations: vec![operation.to_string()],
+            uri: String::from("doip://127.0.0.1"),
+            context: context.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_address_accepts_hex_and_decimal() {
+        assert_eq!(parse_address("0x0010").unwrap(), 0x0010);
+        assert_eq!(parse_address("16").unwrap(), 16);
+        assert!(parse_address("ecu").is_err());
+        assert!(parse_address("0x10000").is_err());
+    }
+
+    #[test]
+    fn with_config_returns_err_for_zero_poll_interval() {
+        let config = Config {
+            poll_interval_ms: 0,
+            ..create_config()
+        };
+
+        let result = DtcDataAdapter::with_config(
+            "doip://127.0.0.1:13400",
+            Arc::new(SignalStore::new()),
+            config,
+        );
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::Deserialize));
+    }
+
+    #[tokio::test]
+    async fn register_entity_fails_for_invalid_ecu_address() {
+        let adapter = create_adapter(13400);
+        let result = adapter
+            .register_entity(ENTITY_ID, &create_endpoint(Operation::Get, "engine"))
+            .await;
+
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::Parse));
+        assert!(adapter.entities.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_request_to_provider_reads_dtcs_into_signal_store() {
+        let port = start_fake_entity(vec![0x59, 0x02, 0xFF, 0x04, 0x20, 0x00, 0x2F]).await;
+        let adapter = create_adapter(port);
+        adapter
+            .register_entity(
+                ENTITY_ID,
+                &create_endpoint(Operation::Get, &format!("{ECU_ADDRESS:#06x}")),
+            )
+            .await
+            .unwrap();
+
+        adapter.send_request_to_provider(ENTITY_ID).await.unwrap();
+
+        let signal = adapter.signals.get(&ENTITY_ID.to_string()).unwrap();
+        assert_eq!(
+            signal.value.as_deref(),
+            Some(r#"[{"code":"P0420-00","status":47,"confirmed":true}]"#)
+        );
+    }
+}
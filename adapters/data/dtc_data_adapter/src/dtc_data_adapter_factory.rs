@@ -0,0 +1,19 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use freyja_common::{
+    data_adapter::DataAdapterFactory,
+    entity::{Operation, Protocol},
+};
+
+use crate::dtc_data_adapter::DtcDataAdapter;
+
+/// Factory for creating DtcDataAdapters
+#[derive(DataAdapterFactory)]
+#[data_adapter_factory(
+    adapter = DtcDataAdapter,
+    protocols = [Protocol::Doip],
+    operations = [Operation::Get, Operation::Subscribe]
+)]
+pub struct DtcDataAdapterFactory {}
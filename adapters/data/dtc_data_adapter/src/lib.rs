@@ -0,0 +1,9 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+mod config;
+mod doip;
+pub mod dtc_data_adapter;
+pub mod dtc_data_adapter_factory;
+mod uds;
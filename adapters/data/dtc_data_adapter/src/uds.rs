@@ -0,0 +1,189 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::Serialize;
+
+use freyja_common::data_adapter::DataAdapterError;
+
+/// The UDS service id of ReadDTCInformation
+const READ_DTC_INFORMATION_SERVICE: u8 = 0x19;
+
+/// The ReadDTCInformation subfunction which reports the DTCs matching a status mask
+const REPORT_DTC_BY_STATUS_MASK: u8 = 0x02;
+
+/// The offset which is added to a service id in its positive responses
+const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+
+/// The service id of negative responses
+const NEGATIVE_RESPONSE_SERVICE: u8 = 0x7F;
+
+/// The negative response code which means that the ECU is still processing the request
+const RESPONSE_PENDING: u8 = 0x78;
+
+/// The bit of a DTC's status which is set once the fault has been confirmed
+const CONFIRMED_DTC_STATUS_BIT: u8 = 0x08;
+
+/// The length of each DTC and its status in a response
+const DTC_RECORD_LENGTH: usize = 4;
+
+/// A diagnostic trouble code reported by an ECU
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Dtc {
+    /// The code in the SAE J2012 format followed by its failure type, such as `P0420-00`
+    pub code: String,
+
+    /// The status byte of the DTC
+    pub status: u8,
+
+    /// Whether the fault has been confirmed
+    pub confirmed: bool,
+}
+
+impl Dtc {
+    /// Creates a DTC from its three bytes and its status
+    ///
+    /// # Arguments
+    /// - `bytes`: the bytes of the DTC
+    /// - `status`: the status byte of the DTC
+    fn new(bytes: [u8; 3], status: u8) -> Self {
+        let system = match bytes[0] >> 6 {
+            0 => 'P',
+            1 => 'C',
+            2 => 'B',
+            _ => 'U',
+        };
+
+        Self {
+            code: format!(
+                "{system}{:X}{:X}{:02X}-{:02X}",
+                (bytes[0] >> 4) & 0x03,
+                bytes[0] & 0x0F,
+                bytes[1],
+                bytes[2]
+            ),
+            status,
+            confirmed: status & CONFIRMED_DTC_STATUS_BIT != 0,
+        }
+    }
+}
+
+/// Creates a request which reads the DTCs whose status matches a mask
+///
+/// # Arguments
+/// - `status_mask`: the status mask
+pub(crate) fn read_dtcs_by_status_mask(status_mask: u8) -> [u8; 3] {
+    [
+        READ_DTC_INFORMATION_SERVICE,
+        REPORT_DTC_BY_STATUS_MASK,
+        status_mask,
+    ]
+}
+
+/// Checks whether a response means that the ECU is still processing the request and will respond again later
+///
+/// # Arguments
+/// - `response`: the response to check
+pub(crate) fn is_response_pending(response: &[u8]) -> bool {
+    matches!(response, [NEGATIVE_RESPONSE_SERVICE, _, RESPONSE_PENDING])
+}
+
+/// Parses the response to a request created with `read_dtcs_by_status_mask`.
+/// The DTCs are sorted by their codes.
+///
+/// # Arguments
+/// - `response`: the response to parse
+pub(crate) fn parse_dtcs(response: &[u8]) -> Result<Vec<Dtc>, DataAdapterError> {
+    let records = match response {
+        [NEGATIVE_RESPONSE_SERVICE, READ_DTC_INFORMATION_SERVICE, code] => {
+            return Err(DataAdapterError::communication(format!(
+                "The ECU rejected the request to read DTCs with negative response code {code:#04x}"
+            )));
+        }
+        [service, REPORT_DTC_BY_STATUS_MASK, _availability_mask, records @ ..]
+            if *service == READ_DTC_INFORMATION_SERVICE + POSITIVE_RESPONSE_OFFSET =>
+        {
+            records
+        }
+        _ => {
+            return Err(DataAdapterError::parse(format!(
+                "Unexpected response to the request to read DTCs: {response:02x?}"
+            )));
+        }
+    };
+
+    if records.len() % DTC_RECORD_LENGTH != 0 {
+        return Err(DataAdapterError::parse(format!(
+            "The DTC records have a length of {}, which isn't a multiple of {DTC_RECORD_LENGTH}",
+            records.len()
+        )));
+    }
+
+    let mut dtcs: Vec<Dtc> = records
+        .chunks_exact(DTC_RECORD_LENGTH)
+        .map(|record| Dtc::new([record[0], record[1], record[2]], record[3]))
+        .collect();
+    dtcs.sort();
+
+    Ok(dtcs)
+}
+
+#[cfg(test)]
+mod uds_tests {
+    use super::*;
+
+    use freyja_common::data_adapter::DataAdapterErrorKind;
+
+    #[test]
+    fn dtc_new_formats_code() {
+        assert_eq!(Dtc::new([0x04, 0x20, 0x00], 0x09).code, "P0420-00");
+        assert_eq!(Dtc::new([0x5A, 0x01, 0x13], 0x00).code, "C1A01-13");
+        assert_eq!(Dtc::new([0xC1, 0x00, 0x87], 0x00).code, "U0100-87");
+    }
+
+    #[test]
+    fn parse_dtcs_returns_sorted_dtcs() {
+        let response = [
+            0x59, 0x02, 0xFF, 0xC1, 0x00, 0x87, 0x01, 0x04, 0x20, 0x00, 0x2F,
+        ];
+
+        let dtcs = parse_dtcs(&response).unwrap();
+        assert_eq!(
+            dtcs,
+            vec![
+                Dtc {
+                    code: String::from("P0420-00"),
+                    status: 0x2F,
+                    confirmed: true,
+                },
+                Dtc {
+                    code: String::from("U0100-87"),
+                    status: 0x01,
+                    confirmed: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_dtcs_returns_err_for_invalid_responses() {
+        for (response, expected) in [
+            (vec![0x7F, 0x19, 0x31], DataAdapterErrorKind::Communication),
+            (
+                vec![0x59, 0x02, 0xFF, 0x04, 0x20],
+                DataAdapterErrorKind::Parse,
+            ),
+            (vec![0x62, 0xF1, 0x90], DataAdapterErrorKind::Parse),
+        ] {
+            let result = parse_dtcs(&response);
+            assert!(result.is_err_and(|e| e.kind() == expected));
+        }
+    }
+
+    #[test]
+    fn is_response_pending_matches_only_response_pending() {
+        assert!(is_response_pending(&[0x7F, 0x19, 0x78]));
+        assert!(!is_response_pending(&[0x7F, 0x19, 0x31]));
+        assert!(!is_response_pending(&[0x59, 0x02, 0xFF]));
+    }
+}
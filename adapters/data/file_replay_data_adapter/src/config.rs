@@ -0,0 +1,18 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Config for the file replay data adapter
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The path of the trace file to replay
+    pub trace_path: String,
+
+    /// The factor by which playback runs faster than the original recording
+    pub speed: f64,
+
+    /// Whether to start again from the beginning of the trace after the last value is replayed
+    pub loop_playback: bool,
+}
@@ -0,0 +1,393 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use log::{info, warn};
+use tokio::{
+    sync::{Mutex, Notify},
+    task::JoinHandle,
+};
+
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
+    entity::{EntityEndpoint, Operation},
+    out_dir,
+    signal_store::SignalStore,
+    traffic_trace::{self, TraceRecord},
+};
+
+use crate::config::Config;
+
+/// Replays the values recorded in a trace file with their original timing.
+/// Only the values of entities registered with this adapter are replayed.
+pub struct FileReplayDataAdapter {
+    /// The records to replay
+    records: Arc<Vec<TraceRecord>>,
+
+    /// The entities registered with this adapter
+    entities: Arc<Mutex<HashSet<String>>>,
+
+    /// Notified when an entity is registered so that playback doesn't start before there are any entities
+    entity_registered: Arc<Notify>,
+
+    /// The shared signal store
+    signals: Arc<SignalStore>,
+
+    /// The factor by which playback runs faster than the original recording
+    speed: f64,
+
+    /// Whether to start again from the beginning of the trace after the last value is replayed
+    loop_playback: bool,
+
+    /// The task which replays the records, once the adapter has started
+    playback_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FileReplayDataAdapter {
+    /// Creates a new FileReplayDataAdapter with the specified config
+    ///
+    /// # Arguments
+    /// - `config`: the config to use
+    /// - `signals`: the shared signal store
+    pub fn from_config(
+        config: Config,
+        signals: Arc<SignalStore>,
+    ) -> Result<Self, DataAdapterError> {
+        if !config.speed.is_finite() || config.speed <= 0.0 {
+            return Err(DataAdapterError::deserialize(format!(
+                "The playback speed must be a positive number, but was {}",
+                config.speed
+            )));
+        }
+
+        let records = traffic_trace::read_trace(&config.trace_path).map_err(|e| {
+            DataAdapterError::io(format!("Could not read trace {}: {e}", config.trace_path))
+        })?;
+
+        Ok(Self {
+            records: Arc::new(records),
+            entities: Arc::new(Mutex::new(HashSet::new())),
+            entity_registered: Arc::new(Notify::new()),
+            signals,
+            speed: config.speed,
+            loop_playback: config.loop_playback,
+            playback_task: Mutex::new(None),
+        })
+    }
+
+    /// Replays the records once, waiting between records according to their timestamps
+    ///
+    /// # Arguments
+    /// - `records`: the records to replay
+    /// - `entities`: the entities whose values should be replayed
+    /// - `signals`: the shared signal store
+    /// - `speed`: the factor by which playback runs faster than the original recording
+    async fn replay(
+        records: &[TraceRecord],
+        entities: &Mutex<HashSet<String>>,
+        signals: &SignalStore,
+        speed: f64,
+    ) {
+        let mut previous_timestamp_ms = None;
+        for record in records {
+            if let Some(previous_timestamp_ms) = previous_timestamp_ms {
+                tokio::time::sleep(Self::replay_delay(
+                    previous_timestamp_ms,
+                    record.timestamp_ms,
+                    speed,
+                ))
+                .await;
+            }
+
+            previous_timestamp_ms = Some(record.timestamp_ms);
+
+            if !entities.lock().await.contains(&record.entity_id) {
+                continue;
+            }
+
+            // Let simulated clocks follow the time of the recording
+            match record.timestamp() {
+                Ok(timestamp) => signals.clock().sync_to(timestamp),
+                Err(e) => warn!("Invalid timestamp for {}: {e}", record.entity_id),
+            }
+
+            signals.set_value(record.entity_id.clone(), record.value.clone());
+        }
+    }
+
+    /// Gets how long to wait before replaying a record.
+    /// Records which are out of order are replayed without waiting.
+    ///
+    /// # Arguments
+    /// - `previous_timestamp_ms`: the timestamp of the previous record
+    /// - `timestamp_ms`: the timestamp of the record to replay
+    /// - `speed`: the factor by which playback runs faster than the original recording
+    fn replay_delay(previous_timestamp_ms: i64, timestamp_ms: i64, speed: f64) -> Duration {
+        let delay_ms = timestamp_ms.saturating_sub(previous_timestamp_ms).max(0);
+        Duration::from_secs_f64(delay_ms as f64 / 1000.0 / speed)
+    }
+}
+
+#[async_trait]
+impl DataAdapter for FileReplayDataAdapter {
+    /// Creates a data adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri, which is not used by this adapter
+    /// - `signals`: the shared signal store
+    fn create_new(_provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        Self::from_config(config, signals)
+    }
+
+    /// Starts a data adapter.
+    /// Playback begins once the first entity is registered.
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        let records = self.records.clone();
+        let entities = self.entities.clone();
+        let entity_registered = self.entity_registered.clone();
+        let signals = self.signals.clone();
+        let speed = self.speed;
+        let loop_playback = self.loop_playback;
+
+        let playback_task = tokio::spawn(async move {
+            entity_registered.notified().await;
+
+            loop {
+                Self::replay(&records, &entities, &signals, speed).await;
+
+                if !loop_playback {
+                    break;
+                }
+            }
+
+            info!("Finished replaying {} recorded values", records.len());
+        });
+        *self.playback_task.lock().await = Some(playback_task);
+
+        info!("Started a FileReplayDataAdapter!");
+
+        Ok(())
+    }
+
+    /// Stops a data adapter, which stops playback
+    async fn stop(&self) -> Result<(), DataAdapterError> {
+        if let Some(playback_task) = self.playback_task.lock().await.take() {
+            playback_task.abort();
+        }
+
+        info!("Stopped a FileReplayDataAdapter");
+
+        Ok(())
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity.
+    /// Values are only replayed from the trace, so this only checks that the entity is registered.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        if self.entities.lock().await.contains(entity_id) {
+            Ok(())
+        } else {
+            Err(DataAdapterError::entity_not_found(format!(
+                "Entity {entity_id} is not registered"
+            )))
+        }
+    }
+
+    /// Registers an entity id so that its recorded values are replayed
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        if endpoint
+            .select_operation(&[Operation::Get, Operation::Subscribe])
+            .is_none()
+        {
+            return Err(DataAdapterErrorKind::OperationNotSupported.into());
+        }
+
+        self.entities.lock().await.insert(String::from(entity_id));
+        self.entity_registered.notify_one();
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+#[cfg(test)]
+mod file_replay_data_adapter_tests {
+    use super::*;
+
+    use std::{fs, path::PathBuf};
+
+    use freyja_common::signal::SignalPatch;
+
+    use freyja_common::entity::Protocol;
+    use freyja_test_common::fixtures::TempDirFixture;
+
+    /// A trace file in a temp directory which is deleted when dropped
+    struct TraceFile {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl TraceFile {
+        fn new(name: &str, records: &[(i64, &str, &str)]) -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join(format!("{name}.jsonl"));
+            let contents: String = records
+                .iter()
+                .map(|(timestamp_ms, entity_id, value)| {
+                    format!(
+                        "{{\"timestamp_ms\":{timestamp_ms},\"entity_id\":\"{entity_id}\",\"value\":\"{value}\"}}\n"
+                    )
+                })
+                .collect();
+            fs::write(&path, contents).unwrap();
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+
+        fn config(&self, speed: f64) -> Config {
+            Config {
+                trace_path: self.path.to_string_lossy().into_owned(),
+                speed,
+                loop_playback: false,
+            }
+        }
+    }
+
+    fn endpoint(operation: Operation) -> EntityEndpoint {
+        EntityEndpoint {
+            protocol: Protocol::FileReplay.to_string(),
+            operations: vec![operation.to_string()],
+            uri: String::from("trace"),
+            context: String::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn replay_delay_is_scaled_by_speed() {
+        assert_eq!(
+            FileReplayDataAdapter::replay_delay(1000, 3000, 1.0),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            FileReplayDataAdapter::replay_delay(1000, 3000, 4.0),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn replay_delay_is_zero_for_out_of_order_records() {
+        assert_eq!(
+            FileReplayDataAdapter::replay_delay(3000, 1000, 1.0),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn from_config_returns_err_for_invalid_speed() {
+        let file = TraceFile::new("file_replay_data_adapter_invalid_speed", &[]);
+        for speed in [0.0, -1.0, f64::NAN] {
+            let result = FileReplayDataAdapter::from_config(
+                file.config(speed),
+                Arc::new(SignalStore::new()),
+            );
+            assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::Deserialize));
+        }
+    }
+
+    #[test]
+    fn from_config_returns_err_for_missing_trace() {
+        let config = Config {
+            trace_path: String::from("/this/path/does/not/exist.jsonl"),
+            speed: 1.0,
+            loop_playback: false,
+        };
+
+        let result = FileReplayDataAdapter::from_config(config, Arc::new(SignalStore::new()));
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::Io));
+    }
+
+    #[tokio::test]
+    async fn register_entity_returns_err_for_unsupported_operation() {
+        let file = TraceFile::new("file_replay_data_adapter_unsupported", &[]);
+        let uut =
+            FileReplayDataAdapter::from_config(file.config(1.0), Arc::new(SignalStore::new()))
+                .unwrap();
+
+        let result = uut
+            .register_entity("speed", &endpoint(Operation::Set))
+            .await;
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::OperationNotSupported));
+        assert!(uut.send_request_to_provider("speed").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn start_replays_values_of_registered_entities() {
+        const SPEED_ID: &str = "speed";
+        const TEMPERATURE_ID: &str = "temperature";
+
+        let file = TraceFile::new(
+            "file_replay_data_adapter_replay",
+            &[
+                (0, SPEED_ID, "10"),
+                (10, TEMPERATURE_ID, "21.5"),
+                (20, SPEED_ID, "20"),
+            ],
+        );
+
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SPEED_ID, TEMPERATURE_ID]
+                .into_iter()
+                .map(|id| SignalPatch {
+                    id: id.to_string(),
+                    ..Default::default()
+                }),
+        );
+
+        let uut = FileReplayDataAdapter::from_config(file.config(1.0), signals.clone()).unwrap();
+        uut.start().await.unwrap();
+        uut.register_entity(SPEED_ID, &endpoint(Operation::Subscribe))
+            .await
+            .unwrap();
+
+        let get_value = |id: &str| signals.get(&id.to_string()).unwrap().value;
+        for _ in 0..100 {
+            if get_value(SPEED_ID).as_deref() == Some("20") {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(get_value(SPEED_ID).as_deref(), Some("20"));
+        assert_eq!(get_value(TEMPERATURE_ID), None);
+    }
+}
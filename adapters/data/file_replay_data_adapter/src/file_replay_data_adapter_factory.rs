@@ -0,0 +1,63 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterFactory},
+    entity::{Entity, EntityEndpoint, Operation, Protocol},
+    out_dir,
+    signal_store::SignalStore,
+};
+
+use crate::{config::Config, file_replay_data_adapter::FileReplayDataAdapter};
+
+/// Factory for creating FileReplayDataAdapters
+pub struct FileReplayDataAdapterFactory {
+    /// The adapter config
+    config: Config,
+}
+
+impl DataAdapterFactory for FileReplayDataAdapterFactory {
+    /// Create a new `FileReplayDataAdapterFactory`
+    fn create_new() -> Result<Self, DataAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        Ok(Self { config })
+    }
+
+    /// Check to see whether this factory can create a data adapter for the requested entity.
+    /// Returns the first endpoint found that is supported by this factory.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to check for compatibility
+    fn is_supported(&self, entity: &Entity) -> Option<EntityEndpoint> {
+        entity.is_supported(
+            &[Protocol::FileReplay],
+            &[Operation::Get, Operation::Subscribe],
+        )
+    }
+
+    /// Create a new data adapter
+    ///
+    /// # Arguments
+    /// - `_provider_uri`: the provider URI to associate with this adapter, which is not used
+    /// - `signals`: the shared signal store
+    fn create_adapter(
+        &self,
+        _provider_uri: &str,
+        signals: Arc<SignalStore>,
+    ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError> {
+        let adapter = FileReplayDataAdapter::from_config(self.config.clone(), signals)?;
+        Ok(Arc::new(adapter))
+    }
+}
@@ -0,0 +1,12 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// The gRPC v2 data adapter config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The time to wait before resubscribing after a subscription stream ends, in milliseconds
+    pub resubscribe_interval_ms: u64,
+}
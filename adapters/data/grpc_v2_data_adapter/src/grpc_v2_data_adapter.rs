@@ -0,0 +1,537 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use digital_twin_provider_proto::v2::{
+    digital_twin_provider_client::DigitalTwinProviderClient, GetRequest, GetResponse,
+    SubscribeRequest, SubscribeResponse,
+};
+use log::{debug, info, warn};
+use tokio::task::JoinHandle;
+use tonic::{
+    transport::{Channel, Endpoint},
+    Streaming,
+};
+
+use crate::config::Config;
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
+    entity::{EntityEndpoint, Operation},
+    message_utils, out_dir,
+    signal_store::SignalStore,
+    uri::{EndpointUri, UriKind},
+};
+
+/// Interfaces with providers which implement the v2 digital twin provider interface.
+/// Values for subscribed entities are received on a server stream,
+/// so this adapter does not need to host a callback server.
+pub struct GRPCV2DataAdapter {
+    /// The adapter config
+    config: Config,
+
+    /// Client for connecting to a provider
+    provider_client: DigitalTwinProviderClient<Channel>,
+
+    /// Local cache for keeping track of which entities this data adapter contains
+    entity_operation_map: Mutex<HashMap<String, Operation>>,
+
+    /// The tasks which consume the subscription streams, keyed by entity id
+    subscriptions: Mutex<HashMap<String, JoinHandle<()>>>,
+
+    /// Shared signal store for all data adapters to push new signal values
+    signals: Arc<SignalStore>,
+}
+
+impl GRPCV2DataAdapter {
+    /// Subscribes to an entity and spawns a task to consume the subscription stream.
+    /// If the stream ends, the task will periodically try to resubscribe.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity to subscribe to
+    async fn subscribe(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        let mut client = self.provider_client.clone();
+        let stream = open_subscription(&mut client, entity_id).await?;
+
+        let handle = tokio::spawn(run_subscription(
+            client,
+            entity_id.to_string(),
+            stream,
+            self.signals.clone(),
+            Duration::from_millis(self.config.resubscribe_interval_ms),
+        ));
+
+        // Replace any subscription left over from a previous registration of the same entity
+        if let Some(previous) = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .insert(entity_id.to_string(), handle)
+        {
+            previous.abort();
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DataAdapter for GRPCV2DataAdapter {
+    /// Creates a data adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri for accessing an entity's information
+    /// - `signals`: the shared signal store
+    fn create_new(provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        let provider_uri =
+            EndpointUri::parse(provider_uri, UriKind::Grpc).map_err(DataAdapterError::parse)?;
+        let channel = Endpoint::from_shared(provider_uri.to_string())
+            .map_err(DataAdapterError::parse)?
+            .connect_lazy();
+
+        Ok(Self {
+            config,
+            provider_client: DigitalTwinProviderClient::new(channel),
+            entity_operation_map: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+            signals,
+        })
+    }
+
+    /// Starts a data adapter
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        // There is no callback server to start since subscriptions use server streams
+        info!("Started a GRPCV2DataAdapter!");
+
+        Ok(())
+    }
+
+    /// Stops a data adapter, which stops all of the subscription tasks
+    async fn stop(&self) -> Result<(), DataAdapterError> {
+        for (_, handle) in self.subscriptions.lock().unwrap().drain() {
+            handle.abort();
+        }
+
+        info!("Stopped a GRPCV2DataAdapter");
+
+        Ok(())
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        let operation_result = self
+            .entity_operation_map
+            .lock()
+            .unwrap()
+            .get(entity_id)
+            .cloned();
+
+        let operation = match operation_result {
+            Some(operation) => operation,
+            None => {
+                let message = format!("Entity {entity_id} does not have an operation registered");
+                info!("{message}");
+                return Err(DataAdapterError::unknown(message));
+            }
+        };
+
+        // Only need to handle Get operations since subscribe has already happened
+        if operation == Operation::Get {
+            let mut client = self.provider_client.clone();
+            let request = tonic::Request::new(GetRequest {
+                entity_id: String::from(entity_id),
+            });
+
+            let GetResponse { entity_id, value } = client
+                .get(request)
+                .await
+                .map_err(DataAdapterError::communication)?
+                .into_inner();
+
+            set_value(&self.signals, entity_id, value);
+        }
+
+        Ok(())
+    }
+
+    /// Registers an entity id to a local cache inside a data adapter to keep track of which entities a data adapter contains.
+    /// If the operation is Subscribe for an entity, the expectation is subscribe will happen in this function after registering an entity.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        // Prefer subscribe if present
+        let selected_operation = endpoint
+            .select_operation(&[Operation::Subscribe, Operation::Get])
+            .ok_or::<DataAdapterError>(DataAdapterErrorKind::OperationNotSupported.into())?;
+
+        self.entity_operation_map
+            .lock()
+            .unwrap()
+            .insert(String::from(entity_id), selected_operation.clone());
+
+        if selected_operation == Operation::Subscribe {
+            // Remove from map if subscribing to the provider fails
+            if let Err(e) = self.subscribe(entity_id).await {
+                self.entity_operation_map.lock().unwrap().remove(entity_id);
+                return Err(e);
+            }
+        }
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+impl Drop for GRPCV2DataAdapter {
+    /// Stops all of the subscription tasks
+    fn drop(&mut self) {
+        for (_, handle) in self.subscriptions.lock().unwrap().drain() {
+            handle.abort();
+        }
+    }
+}
+
+/// Opens a subscription stream for an entity
+///
+/// # Arguments
+/// - `client`: the provider client
+/// - `entity_id`: the entity to subscribe to
+async fn open_subscription(
+    client: &mut DigitalTwinProviderClient<Channel>,
+    entity_id: &str,
+) -> Result<Streaming<SubscribeResponse>, DataAdapterError> {
+    let request = tonic::Request::new(SubscribeRequest {
+        entity_id: String::from(entity_id),
+    });
+
+    client
+        .subscribe(request)
+        .await
+        .map(|response| response.into_inner())
+        .map_err(DataAdapterError::communication)
+}
+
+/// Consumes a subscription stream, resubscribing whenever the stream ends.
+/// This runs until the task is aborted.
+///
+/// # Arguments
+/// - `client`: the provider client
+/// - `entity_id`: the subscribed entity
+/// - `stream`: the initial subscription stream
+/// - `signals`: the shared signal store
+/// - `resubscribe_interval`: the time to wait before resubscribing
+async fn run_subscription(
+    mut client: DigitalTwinProviderClient<Channel>,
+    entity_id: String,
+    stream: Streaming<SubscribeResponse>,
+    signals: Arc<SignalStore>,
+    resubscribe_interval: Duration,
+) {
+    let mut stream = Some(stream);
+    loop {
+        match stream.take() {
+            Some(s) => consume_stream(&entity_id, s, &signals).await,
+            None => {
+                tokio::time::sleep(resubscribe_interval).await;
+                match open_subscription(&mut client, &entity_id).await {
+                    Ok(s) => {
+                        info!("Resubscribed to entity {entity_id}");
+                        stream = Some(s);
+                    }
+                    Err(e) => warn!("Failed to resubscribe to entity {entity_id}: {e}"),
+                }
+            }
+        }
+    }
+}
+
+/// Writes every value received on a subscription stream to the signal store until the stream ends
+///
+/// # Arguments
+/// - `entity_id`: the subscribed entity
+/// - `stream`: the subscription stream
+/// - `signals`: the shared signal store
+async fn consume_stream(
+    entity_id: &str,
+    mut stream: Streaming<SubscribeResponse>,
+    signals: &SignalStore,
+) {
+    loop {
+        match stream.message().await {
+            Ok(Some(SubscribeResponse { entity_id, value })) => {
+                debug!("Received a value for entity id {entity_id} with the value {value}");
+                set_value(signals, entity_id, value);
+            }
+            Ok(None) => {
+                info!("Subscription stream for entity {entity_id} was closed by the provider");
+                return;
+            }
+            Err(e) => {
+                warn!("Subscription stream for entity {entity_id} failed: {e}");
+                return;
+            }
+        }
+    }
+}
+
+/// Parses a value received from the provider and writes it to the signal store
+///
+/// # Arguments
+/// - `signals`: the shared signal store
+/// - `entity_id`: the entity id
+/// - `value`: the unparsed value
+fn set_value(signals: &SignalStore, entity_id: String, value: String) {
+    let value = message_utils::parse_value(value);
+    if signals.set_value(entity_id.clone(), value).is_none() {
+        warn!("Provider returned a value for unknown entity {entity_id}");
+    }
+}
+
+#[cfg(test)]
+mod grpc_v2_data_adapter_tests {
+    use std::pin::Pin;
+
+    use super::*;
+
+    use digital_twin_provider_proto::v2::digital_twin_provider_server::DigitalTwinProvider;
+    use tokio_stream::Stream;
+    use tonic::{Request, Response, Status};
+
+    const VALUE: &str = "42";
+
+    pub struct MockProvider {}
+
+    #[tonic::async_trait]
+    impl DigitalTwinProvider for MockProvider {
+        type SubscribeStream =
+            Pin<Box<dyn Stream<Item = Result<SubscribeResponse, Status>> + Send>>;
+
+        async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+            let response = GetResponse {
+                entity_id: request.into_inner().entity_id,
+                value: VALUE.to_string(),
+            };
+            Ok(Response::new(response))
+        }
+
+        async fn subscribe(
+            &self,
+            request: Request<SubscribeRequest>,
+        ) -> Result<Response<Self::SubscribeStream>, Status> {
+            let response = SubscribeResponse {
+                entity_id: request.into_inner().entity_id,
+                value: VALUE.to_string(),
+            };
+            let stream = tokio_stream::iter(vec![Ok(response)]);
+            Ok(Response::new(Box::pin(stream)))
+        }
+    }
+
+    /// The tests below uses Unix sockets to create a channel between a gRPC client and a gRPC server.
+    /// Unix sockets are more ideal than using TCP/IP sockets since Rust tests will run in parallel
+    /// so you would need to set an arbitrary port per test for TCP/IP sockets.
+    #[cfg(unix)]
+    mod unix_tests {
+        use freyja_common::entity::Protocol;
+
+        use super::*;
+
+        use std::path::PathBuf;
+
+        use digital_twin_provider_proto::v2::digital_twin_provider_server::DigitalTwinProviderServer;
+        use freyja_common::signal::SignalPatch;
+        use tokio::net::{UnixListener, UnixStream};
+        use tokio_stream::wrappers::UnixListenerStream;
+        use tonic::transport::{Server, Uri};
+        use tower::service_fn;
+
+        use freyja_test_common::fixtures::GRPCTestFixture;
+
+        async fn create_test_grpc_client(
+            socket_path: PathBuf,
+        ) -> DigitalTwinProviderClient<Channel> {
+            let channel = Endpoint::try_from("http://URI_IGNORED") // Devskim: ignore DS137138
+                .unwrap()
+                .connect_with_connector(service_fn(move |_: Uri| {
+                    let socket_path = socket_path.clone();
+                    async move { UnixStream::connect(socket_path).await }
+                }))
+                .await
+                .unwrap();
+
+            DigitalTwinProviderClient::new(channel)
+        }
+
+        async fn run_test_grpc_server(uds_stream: UnixListenerStream) {
+            let mock_provider = MockProvider {};
+            Server::builder()
+                .add_service(DigitalTwinProviderServer::new(mock_provider))
+                .serve_with_incoming(uds_stream)
+                .await
+                .unwrap();
+        }
+
+        fn create_adapter(
+            client: DigitalTwinProviderClient<Channel>,
+            entity_ids: &[&str],
+        ) -> GRPCV2DataAdapter {
+            let signals = Arc::new(SignalStore::new());
+            signals.add(entity_ids.iter().map(|id| SignalPatch {
+                id: id.to_string(),
+                ..Default::default()
+            }));
+
+            GRPCV2DataAdapter {
+                config: Config {
+                    resubscribe_interval_ms: 1000,
+                },
+                provider_client: client,
+                entity_operation_map: Mutex::new(HashMap::new()),
+                subscriptions: Mutex::new(HashMap::new()),
+                signals,
+            }
+        }
+
+        fn create_endpoint(operation: Operation) -> EntityEndpoint {
+            EntityEndpoint {
+                protocol: Protocol::GrpcV2.to_string(),
+                operations: vec![operation.to_string()],
+                uri: "foo".to_string(),
+                context: String::from("context"),
+                ..Default::default()
+            }
+        }
+
+        #[tokio::test]
+        async fn send_request_to_provider_sets_value() {
+            let fixture = GRPCTestFixture::new();
+
+            // Create the Unix Socket
+            let uds = UnixListener::bind(&fixture.socket_path).unwrap();
+            let uds_stream = UnixListenerStream::new(uds);
+
+            let entity_id = "operation_get_entity_id";
+
+            let request_future = async {
+                let client = create_test_grpc_client(fixture.socket_path.clone()).await;
+                let adapter = create_adapter(client, &[entity_id]);
+
+                assert!(adapter
+                    .send_request_to_provider("unknown_entity_id")
+                    .await
+                    .is_err());
+
+                let result = adapter
+                    .register_entity(entity_id, &create_endpoint(Operation::Get))
+                    .await;
+                assert!(result.is_ok());
+                assert!(adapter.send_request_to_provider(entity_id).await.is_ok());
+
+                let signal = adapter.signals.get(&entity_id.to_string()).unwrap();
+                assert_eq!(signal.value, Some(VALUE.into()));
+            };
+
+            tokio::select! {
+                _ = run_test_grpc_server(uds_stream) => panic!("Server terminated unexpectedly"),
+                _ = request_future => ()
+            }
+        }
+
+        #[tokio::test]
+        async fn register_entity_with_subscribe_consumes_stream() {
+            let fixture = GRPCTestFixture::new();
+
+            // Create the Unix Socket
+            let uds = UnixListener::bind(&fixture.socket_path).unwrap();
+            let uds_stream = UnixListenerStream::new(uds);
+
+            let entity_id = "operation_subscribe_entity_id";
+
+            let request_future = async {
+                let client = create_test_grpc_client(fixture.socket_path.clone()).await;
+                let adapter = create_adapter(client, &[entity_id]);
+
+                let result = adapter
+                    .register_entity(entity_id, &create_endpoint(Operation::Subscribe))
+                    .await;
+                assert!(result.is_ok());
+                assert!(adapter.send_request_to_provider(entity_id).await.is_ok());
+
+                // The value is written by the subscription task, so wait for it to arrive
+                let mut value = None;
+                for _ in 0..50 {
+                    value = adapter.signals.get(&entity_id.to_string()).unwrap().value;
+                    if value.is_some() {
+                        break;
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+
+                assert_eq!(value, Some(VALUE.into()));
+                assert!(adapter
+                    .subscriptions
+                    .lock()
+                    .unwrap()
+                    .contains_key(entity_id));
+            };
+
+            tokio::select! {
+                _ = run_test_grpc_server(uds_stream) => panic!("Server terminated unexpectedly"),
+                _ = request_future => ()
+            }
+        }
+
+        #[tokio::test]
+        async fn register_entity_fails_for_unsupported_operation() {
+            let fixture = GRPCTestFixture::new();
+
+            // Create the Unix Socket
+            let uds = UnixListener::bind(&fixture.socket_path).unwrap();
+            let uds_stream = UnixListenerStream::new(uds);
+
+            let request_future = async {
+                let client = create_test_grpc_client(fixture.socket_path.clone()).await;
+                let adapter = create_adapter(client, &[]);
+
+                let result = adapter
+                    .register_entity("entity_id", &create_endpoint(Operation::Set))
+                    .await;
+                assert!(result.is_err());
+                assert!(adapter.entity_operation_map.lock().unwrap().is_empty());
+            };
+
+            tokio::select! {
+                _ = run_test_grpc_server(uds_stream) => panic!("Server terminated unexpectedly"),
+                _ = request_future => ()
+            }
+        }
+    }
+}
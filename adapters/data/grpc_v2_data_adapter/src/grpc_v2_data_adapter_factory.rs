@@ -0,0 +1,19 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use freyja_common::{
+    data_adapter::DataAdapterFactory,
+    entity::{Operation, Protocol},
+};
+
+use crate::grpc_v2_data_adapter::GRPCV2DataAdapter;
+
+/// Factory for creating GRPCV2DataAdapters
+#[derive(DataAdapterFactory)]
+#[data_adapter_factory(
+    adapter = GRPCV2DataAdapter,
+    protocols = [Protocol::GrpcV2],
+    operations = [Operation::Get, Operation::Subscribe]
+)]
+pub struct GRPCV2DataAdapterFactory {}
@@ -0,0 +1,358 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex as StdMutex, OnceLock},
+};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use log::{debug, info, warn};
+use tokio::sync::Mutex;
+
+use crate::{messages::EntityValue, CALLBACK_PATH, SHARED_SECRET_HEADER};
+use freyja_common::{
+    data_adapter::DataAdapterError, message_utils, signal_store::SignalStore, uri,
+};
+
+/// The callback servers in this process, keyed by their hosting address
+static SHARED_SERVERS: OnceLock<StdMutex<HashMap<String, Arc<CallbackServer>>>> = OnceLock::new();
+
+/// Hosts the endpoint that providers publish subscribed values to.
+/// A single server is shared by all adapters which host callbacks on the same address,
+/// since only one server can bind it and entity ids are unique across providers.
+pub struct CallbackServer {
+    /// The hosting address
+    address: String,
+
+    /// The secret that providers must send with each callback
+    shared_secret: Option<String>,
+
+    /// Whether the server has been started
+    started: Mutex<bool>,
+}
+
+impl CallbackServer {
+    /// Creates a new callback server. The server is not started until `start` is called.
+    ///
+    /// # Arguments
+    /// - `address`: the hosting address
+    /// - `shared_secret`: the secret that providers must send with each callback
+    fn new(address: String, shared_secret: Option<String>) -> Self {
+        Self {
+            address,
+            shared_secret,
+            started: Mutex::new(false),
+        }
+    }
+
+    /// Gets the callback server for an address, creating it if there isn't one yet.
+    /// Every adapter and factory in this process which uses the same address gets the same server.
+    /// If the server already exists, its shared secret is kept.
+    ///
+    /// # Arguments
+    /// - `address`: the hosting address
+    /// - `shared_secret`: the secret that providers must send with each callback
+    pub fn shared(address: &str, shared_secret: Option<String>) -> Arc<Self> {
+        let mut servers = SHARED_SERVERS.get_or_init(Default::default).lock().unwrap();
+
+        let server = servers
+            .entry(address.to_string())
+            .or_insert_with(|| Arc::new(Self::new(address.to_string(), shared_secret.clone())));
+
+        if server.shared_secret != shared_secret {
+            warn!("The callback server on {address} already exists with a different shared secret, which it keeps using");
+        }
+
+        server.clone()
+    }
+
+    /// Starts the server if it hasn't already been started.
+    ///
+    /// # Arguments
+    /// - `signals`: the shared signal store
+    pub async fn start(&self, signals: Arc<SignalStore>) -> Result<(), DataAdapterError> {
+        let mut started = self.started.lock().await;
+        if *started {
+            return Ok(());
+        }
+
+        if self.shared_secret.is_none() {
+            warn!("No shared secret is configured for the HTTP data adapter. Callbacks will not be verified");
+        }
+
+        let addr = uri::parse_socket_address(&self.address).map_err(DataAdapterError::parse)?;
+        let state = Arc::new(CallbackState {
+            signals,
+            shared_secret: self.shared_secret.clone(),
+        });
+
+        let app = Router::new()
+            .route(CALLBACK_PATH, post(handle_callback))
+            .with_state(state);
+
+        let server = axum::Server::try_bind(&addr)
+            .map_err(DataAdapterError::communication)?
+            .serve(app.into_make_service());
+
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                warn!("HTTP data adapter callback server terminated with error: {e}");
+            }
+        });
+
+        *started = true;
+        info!("Started the HTTP data adapter callback server on {addr}");
+
+        Ok(())
+    }
+}
+
+/// The state shared with the callback handler
+struct CallbackState {
+    /// The store into which incoming signal values should be published
+    signals: Arc<SignalStore>,
+
+    /// The secret that providers must send with each callback
+    shared_secret: Option<String>,
+}
+
+/// Handles a value published by a provider.
+/// The shared secret is verified before the body is parsed.
+///
+/// # Arguments
+/// - `state`: the callback state
+/// - `headers`: the request headers
+/// - `body`: the request body
+async fn handle_callback(
+    State(state): State<Arc<CallbackState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    if let Some(secret) = state.shared_secret.as_ref() {
+        let authorized = headers
+            .get(SHARED_SECRET_HEADER)
+            .map(|v| secrets_match(secret.as_bytes(), v.as_bytes()))
+            .unwrap_or(false);
+
+        if !authorized {
+            warn!("Rejected a callback with a missing or invalid shared secret");
+            return StatusCode::UNAUTHORIZED;
+        }
+    }
+
+    let EntityValue {
+        entity_id,
+        value,
+        sequence_id,
+    } = match serde_json::from_slice(&body) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Could not parse callback body: {e}");
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    debug!("Received a callback for entity id {entity_id} with the value {value}");
+
+    let value = message_utils::parse_value(value);
+    let result = match sequence_id {
+        Some(sequence_id) => {
+            state
+                .signals
+                .set_value_with_sequence_id(entity_id.clone(), value, sequence_id)
+        }
+        None => state.signals.set_value(entity_id.clone(), value),
+    };
+
+    match result {
+        Some(_) => StatusCode::OK,
+        None => {
+            warn!("Received a callback for unknown entity {entity_id}");
+            StatusCode::NOT_FOUND
+        }
+    }
+}
+
+/// Compares two secrets in time that does not depend on where they differ.
+///
+/// # Arguments
+/// - `expected`: the expected secret
+/// - `actual`: the secret that was received
+fn secrets_match(expected: &[u8], actual: &[u8]) -> bool {
+    expected.len() == actual.len()
+        && expected
+            .iter()
+            .zip(actual.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod callback_server_tests {
+    use super::*;
+
+    use std::{collections::HashSet, time::Duration};
+
+    use axum::http::HeaderValue;
+    use freyja_common::{duplicate_filter::DuplicateFilter, signal::SignalPatch};
+
+    const ENTITY_ID: &str = "some-id";
+    const SECRET: &str = "some-secret";
+
+    fn create_state(shared_secret: Option<&str>) -> Arc<CallbackState> {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ENTITY_ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        Arc::new(CallbackState {
+            signals,
+            shared_secret: shared_secret.map(String::from),
+        })
+    }
+
+    fn create_body(entity_id: &str, value: &str) -> Bytes {
+        let message = EntityValue {
+            entity_id: entity_id.to_string(),
+            value: value.to_string(),
+            sequence_id: None,
+        };
+
+        Bytes::from(serde_json::to_vec(&message).unwrap())
+    }
+
+    fn create_headers(secret: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(SHARED_SECRET_HEADER, HeaderValue::from_str(secret).unwrap());
+        headers
+    }
+
+    #[test]
+    fn shared_returns_one_server_per_address() {
+        let server = CallbackServer::shared("127.0.0.1:60001", None);
+
+        assert!(Arc::ptr_eq(
+            &server,
+            &CallbackServer::shared("127.0.0.1:60001", Some(SECRET.to_string()))
+        ));
+        assert!(!Arc::ptr_eq(
+            &server,
+            &CallbackServer::shared("127.0.0.1:60002", None)
+        ));
+    }
+
+    #[test]
+    fn secrets_match_compares_contents() {
+        assert!(secrets_match(b"secret", b"secret"));
+        assert!(!secrets_match(b"secret", b"secreT"));
+        assert!(!secrets_match(b"secret", b"secret2"));
+        assert!(!secrets_match(b"secret", b""));
+    }
+
+    #[tokio::test]
+    async fn handle_callback_sets_value_with_valid_secret() {
+        let state = create_state(Some(SECRET));
+        let result = handle_callback(
+            State(state.clone()),
+            create_headers(SECRET),
+            create_body(ENTITY_ID, "42"),
+        )
+        .await;
+
+        assert_eq!(result, StatusCode::OK);
+        let signal = state.signals.get(&ENTITY_ID.to_string()).unwrap();
+        assert_eq!(signal.value, Some("42".into()));
+    }
+
+    #[tokio::test]
+    async fn handle_callback_rejects_invalid_secret() {
+        let state = create_state(Some(SECRET));
+        let result = handle_callback(
+            State(state.clone()),
+            create_headers("wrong-secret"),
+            create_body(ENTITY_ID, "42"),
+        )
+        .await;
+
+        assert_eq!(result, StatusCode::UNAUTHORIZED);
+        let signal = state.signals.get(&ENTITY_ID.to_string()).unwrap();
+        assert_eq!(signal.value, None);
+    }
+
+    #[tokio::test]
+    async fn handle_callback_rejects_missing_secret() {
+        let state = create_state(Some(SECRET));
+        let result =
+            handle_callback(State(state), HeaderMap::new(), create_body(ENTITY_ID, "42")).await;
+
+        assert_eq!(result, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn handle_callback_accepts_any_request_without_configured_secret() {
+        let state = create_state(None);
+        let result =
+            handle_callback(State(state), HeaderMap::new(), create_body(ENTITY_ID, "42")).await;
+
+        assert_eq!(result, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn handle_callback_returns_not_found_for_unknown_entity() {
+        let state = create_state(None);
+        let result =
+            handle_callback(State(state), HeaderMap::new(), create_body("unknown", "42")).await;
+
+        assert_eq!(result, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn handle_callback_drops_retried_values() {
+        let state = create_state(None);
+        state
+            .signals
+            .set_duplicate_filter(Arc::new(DuplicateFilter::new(
+                Duration::from_secs(60),
+                HashSet::new(),
+            )));
+
+        for value in ["42", "43"] {
+            let message = EntityValue {
+                entity_id: ENTITY_ID.to_string(),
+                value: value.to_string(),
+                sequence_id: Some(String::from("1")),
+            };
+
+            let result = handle_callback(
+                State(state.clone()),
+                HeaderMap::new(),
+                Bytes::from(serde_json::to_vec(&message).unwrap()),
+            )
+            .await;
+            assert_eq!(result, StatusCode::OK);
+        }
+
+        let signal = state.signals.get(&ENTITY_ID.to_string()).unwrap();
+        assert_eq!(signal.value, Some("42".into()));
+    }
+
+    #[tokio::test]
+    async fn handle_callback_returns_bad_request_for_invalid_body() {
+        let state = create_state(None);
+        let result = handle_callback(State(state), HeaderMap::new(), Bytes::from("foo")).await;
+
+        assert_eq!(result, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::CALLBACK_PATH;
+
+/// The HTTP data adapter config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The hosting address for the callback server
+    pub callback_address: String,
+
+    /// The advertised address given to providers as the callback address.
+    /// If not specified, the `callback_address` will be used
+    pub advertised_callback_address: Option<String>,
+
+    /// Headers to add to every request sent to a provider, such as authorization headers
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// The secret that providers must send with each callback.
+    /// If not specified, callbacks are accepted without verification
+    pub shared_secret: Option<String>,
+
+    /// Whether to refresh all entities from a provider's bulk endpoint instead of requesting them one at a time
+    #[serde(default)]
+    pub use_bulk_endpoint: bool,
+
+    /// The page size to request from a provider's bulk endpoint
+    pub bulk_page_size: u32,
+
+    /// The timeout for requests sent to a provider, in milliseconds
+    pub request_timeout_ms: u64,
+}
+
+impl Config {
+    /// Gets the advertised address.
+    /// Returns the value of `self.advertised_callback_address` if it's not `None`,
+    /// otherwise returns `self.callback_address`.
+    pub fn get_advertised_address(&self) -> &String {
+        self.advertised_callback_address
+            .as_ref()
+            .unwrap_or(&self.callback_address)
+    }
+
+    /// Gets the uri that providers should publish values to.
+    /// If the advertised address does not include a scheme, `http` is used.
+    /// Specify an `https` scheme in the advertised address when the callback server is behind a TLS-terminating proxy.
+    pub fn get_callback_uri(&self) -> String {
+        let address = self.get_advertised_address();
+        if address.contains("://") {
+            format!("{}{CALLBACK_PATH}", address.trim_end_matches('/'))
+        } else {
+            format!("http://{address}{CALLBACK_PATH}") // Devskim: ignore DS137138
+        }
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    fn create_config(advertised_callback_address: Option<&str>) -> Config {
+        Config {
+            callback_address: "0.0.0.0:60020".to_string(),
+            advertised_callback_address: advertised_callback_address.map(String::from),
+            headers: HashMap::new(),
+            shared_secret: None,
+            use_bulk_endpoint: false,
+            bulk_page_size: 100,
+            request_timeout_ms: 5000,
+        }
+    }
+
+    #[test]
+    fn get_callback_uri_uses_callback_address_by_default() {
+        let config = create_config(None);
+        assert_eq!(
+            config.get_callback_uri(),
+            format!("http://0.0.0.0:60020{CALLBACK_PATH}") // Devskim: ignore DS137138
+        );
+    }
+
+    #[test]
+    fn get_callback_uri_preserves_advertised_scheme() {
+        let config = create_config(Some("https://vehicle.example.com/"));
+        assert_eq!(
+            config.get_callback_uri(),
+            format!("https://vehicle.example.com{CALLBACK_PATH}")
+        );
+    }
+}
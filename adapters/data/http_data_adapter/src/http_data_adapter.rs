@@ -0,0 +1,516 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use ureq::{Agent, AgentBuilder};
+
+use crate::{
+    callback_server::CallbackServer,
+    config::Config,
+    messages::{EntityValue, SubscribeRequest, ValuePage},
+    SUBSCRIPTIONS_PATH, VALUES_PATH,
+};
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
+    entity::{EntityEndpoint, Operation},
+    message_utils, out_dir,
+    signal_store::SignalStore,
+    uri::{EndpointUri, UriKind},
+};
+
+/// Interfaces with providers which expose a REST API over HTTP or HTTPS
+pub struct HttpDataAdapter {
+    /// The adapter config
+    config: Config,
+
+    /// The base uri of the provider
+    provider_uri: String,
+
+    /// Client for sending requests to the provider
+    agent: Agent,
+
+    /// The server which receives values for subscribed entities
+    callback_server: Arc<CallbackServer>,
+
+    /// Local cache for keeping track of which entities this data adapter contains
+    entity_operation_map: Mutex<HashMap<String, Operation>>,
+
+    /// Shared signal store for all data adapters to push new signal values
+    signals: Arc<SignalStore>,
+}
+
+impl HttpDataAdapter {
+    /// Creates a data adapter which uses an existing callback server.
+    /// Returns an error if the provider uri isn't a valid HTTP or HTTPS uri
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the base uri of the provider
+    /// - `signals`: the shared signal store
+    /// - `config`: the adapter config
+    /// - `callback_server`: the server which receives values for subscribed entities
+    pub(crate) fn with_callback_server(
+        provider_uri: &str,
+        signals: Arc<SignalStore>,
+        config: Config,
+        callback_server: Arc<CallbackServer>,
+    ) -> Result<Self, DataAdapterError> {
+        let provider_uri =
+            EndpointUri::parse(provider_uri, UriKind::Http).map_err(DataAdapterError::parse)?;
+        let agent = AgentBuilder::new()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build();
+
+        Ok(Self {
+            config,
+            provider_uri: provider_uri.to_string(),
+            agent,
+            callback_server,
+            entity_operation_map: Mutex::new(HashMap::new()),
+            signals,
+        })
+    }
+
+    /// Gets the full uri for a path on the provider
+    ///
+    /// # Arguments
+    /// - `path`: the path relative to the provider uri
+    fn provider_endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.provider_uri)
+    }
+
+    /// Requests the value of a single entity from the provider and updates the signal store
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity to request
+    async fn refresh_entity(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        let agent = self.agent.clone();
+        let url = self.provider_endpoint(VALUES_PATH);
+        let headers = self.config.headers.clone();
+        let query = vec![("entity_id".to_string(), entity_id.to_string())];
+
+        let body = tokio::task::spawn_blocking(move || send_get(&agent, &url, &headers, &query))
+            .await
+            .map_err(DataAdapterError::unknown)??;
+
+        let EntityValue {
+            entity_id, value, ..
+        } = serde_json::from_str(&body).map_err(DataAdapterError::deserialize)?;
+
+        self.set_value(entity_id, value);
+
+        Ok(())
+    }
+
+    /// Reads every page of the provider's bulk endpoint and updates the signal store
+    /// for all of the returned entities that this adapter polls
+    async fn refresh_all(&self) -> Result<(), DataAdapterError> {
+        let agent = self.agent.clone();
+        let url = self.provider_endpoint(VALUES_PATH);
+        let headers = self.config.headers.clone();
+        let page_size = self.config.bulk_page_size.to_string();
+
+        let values = tokio::task::spawn_blocking(move || {
+            fetch_all_pages(|page_token| {
+                let mut query = vec![("page_size".to_string(), page_size.clone())];
+                if let Some(token) = page_token {
+                    query.push(("page_token".to_string(), token.to_string()));
+                }
+
+                let body = send_get(&agent, &url, &headers, &query)?;
+                serde_json::from_str(&body).map_err(DataAdapterError::deserialize)
+            })
+        })
+        .await
+        .map_err(DataAdapterError::unknown)??;
+
+        debug!(
+            "Received {} values from the bulk endpoint of {}",
+            values.len(),
+            self.provider_uri
+        );
+
+        for EntityValue {
+            entity_id, value, ..
+        } in values
+        {
+            let is_polled = self
+                .entity_operation_map
+                .lock()
+                .unwrap()
+                .get(&entity_id)
+                .is_some_and(|operation| *operation == Operation::Get);
+
+            if is_polled {
+                self.set_value(entity_id, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses a value returned by the provider and writes it to the signal store
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id
+    /// - `value`: the unparsed value
+    fn set_value(&self, entity_id: String, value: String) {
+        let value = message_utils::parse_value(value);
+        if self.signals.set_value(entity_id.clone(), value).is_none() {
+            warn!("Provider returned a value for unknown entity {entity_id}");
+        }
+    }
+}
+
+#[async_trait]
+impl DataAdapter for HttpDataAdapter {
+    /// Creates a data adapter.
+    /// Like adapters created by the factory, it uses the callback server for the configured address
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri for accessing an entity's information
+    /// - `signals`: the shared signal store
+    fn create_new(provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        let callback_server =
+            CallbackServer::shared(&config.callback_address, config.shared_secret.clone());
+
+        Self::with_callback_server(provider_uri, signals, config, callback_server)
+    }
+
+    /// Starts a data adapter
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        self.callback_server.start(self.signals.clone()).await?;
+
+        info!("Started an HttpDataAdapter for {}", self.provider_uri);
+
+        Ok(())
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        let operation_result = self
+            .entity_operation_map
+            .lock()
+            .unwrap()
+            .get(entity_id)
+            .cloned();
+
+        let operation = match operation_result {
+            Some(operation) => operation,
+            None => {
+                let message = format!("Entity {entity_id} does not have an operation registered");
+                info!("{message}");
+                return Err(DataAdapterError::unknown(message));
+            }
+        };
+
+        // Only need to handle Get operations since subscribe has already happened
+        if operation != Operation::Get {
+            return Ok(());
+        }
+
+        if self.config.use_bulk_endpoint {
+            self.refresh_all().await
+        } else {
+            self.refresh_entity(entity_id).await
+        }
+    }
+
+    /// Registers an entity id to a local cache inside a data adapter to keep track of which entities a data adapter contains.
+    /// If the operation is Subscribe for an entity, the expectation is subscribe will happen in this function after registering an entity.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        // Prefer subscribe if present
+        let selected_operation = endpoint
+            .select_operation(&[Operation::Subscribe, Operation::Get])
+            .ok_or::<DataAdapterError>(DataAdapterErrorKind::OperationNotSupported.into())?;
+
+        self.entity_operation_map
+            .lock()
+            .unwrap()
+            .insert(String::from(entity_id), selected_operation.clone());
+
+        if selected_operation == Operation::Subscribe {
+            let agent = self.agent.clone();
+            let url = self.provider_endpoint(SUBSCRIPTIONS_PATH);
+            let headers = self.config.headers.clone();
+            let request = SubscribeRequest {
+                entity_id: String::from(entity_id),
+                callback_uri: self.config.get_callback_uri(),
+            };
+            let body = serde_json::to_string(&request).map_err(DataAdapterError::serialize)?;
+
+            let result =
+                tokio::task::spawn_blocking(move || send_post(&agent, &url, &headers, &body))
+                    .await
+                    .map_err(DataAdapterError::unknown)
+                    .and_then(|r| r);
+
+            // Remove from map if subscribing to the provider fails
+            if let Err(e) = result {
+                self.entity_operation_map.lock().unwrap().remove(entity_id);
+                return Err(e);
+            }
+        }
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+/// Sends a blocking GET request and returns the response body
+///
+/// # Arguments
+/// - `agent`: the client to use
+/// - `url`: the request url
+/// - `headers`: the headers to add to the request
+/// - `query`: the query parameters to add to the request
+fn send_get(
+    agent: &Agent,
+    url: &str,
+    headers: &HashMap<String, String>,
+    query: &[(String, String)],
+) -> Result<String, DataAdapterError> {
+    let mut request = agent.get(url);
+    for (name, value) in headers.iter() {
+        request = request.set(name, value);
+    }
+
+    for (name, value) in query.iter() {
+        request = request.query(name, value);
+    }
+
+    request
+        .call()
+        .map_err(DataAdapterError::communication)?
+        .into_string()
+        .map_err(DataAdapterError::io)
+}
+
+/// Sends a blocking POST request with a JSON body
+///
+/// # Arguments
+/// - `agent`: the client to use
+/// - `url`: the request url
+/// - `headers`: the headers to add to the request
+/// - `body`: the serialized JSON body
+fn send_post(
+    agent: &Agent,
+    url: &str,
+    headers: &HashMap<String, String>,
+    body: &str,
+) -> Result<(), DataAdapterError> {
+    let mut request = agent.post(url).set("Content-Type", "application/json");
+    for (name, value) in headers.iter() {
+        request = request.set(name, value);
+    }
+
+    request
+        .send_string(body)
+        .map_err(DataAdapterError::communication)?;
+
+    Ok(())
+}
+
+/// Follows page tokens until the last page and returns the values from all pages
+///
+/// # Arguments
+/// - `fetch_page`: fetches the page for a page token. `None` requests the first page
+fn fetch_all_pages<F>(mut fetch_page: F) -> Result<Vec<EntityValue>, DataAdapterError>
+where
+    F: FnMut(Option<&str>) -> Result<ValuePage, DataAdapterError>,
+{
+    let mut values = Vec::new();
+    let mut page_token: Option<String> = None;
+
+    loop {
+        let page = fetch_page(page_token.as_deref())?;
+        values.extend(page.values);
+
+        match page.next_page_token {
+            None => break,
+            Some(next) if page_token.as_ref() == Some(&next) => {
+                return Err(DataAdapterError::communication(format!(
+                    "Provider returned the page token {next} twice in a row"
+                )));
+            }
+            Some(next) => page_token = Some(next),
+        }
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod http_data_adapter_tests {
+    use super::*;
+
+    use freyja_common::entity::Protocol;
+
+    fn create_adapter() -> HttpDataAdapter {
+        let config = Config {
+            callback_address: "0.0.0.0:60020".to_string(),
+            advertised_callback_address: None,
+            headers: HashMap::new(),
+            shared_secret: None,
+            use_bulk_endpoint: false,
+            bulk_page_size: 2,
+            request_timeout_ms: 1000,
+        };
+
+        let callback_server =
+            CallbackServer::shared(&config.callback_address, config.shared_secret.clone());
+
+        HttpDataAdapter::with_callback_server(
+            "https://provider.example.com/api/",
+            Arc::new(SignalStore::new()),
+            config,
+            callback_server,
+        )
+        .unwrap()
+    }
+
+    fn create_value(entity_id: &str) -> EntityValue {
+        EntityValue {
+            entity_id: entity_id.to_string(),
+            value: "42".to_string(),
+            sequence_id: None,
+        }
+    }
+
+    #[test]
+    fn provider_endpoint_joins_paths() {
+        let adapter = create_adapter();
+        assert_eq!(
+            adapter.provider_endpoint(VALUES_PATH),
+            format!("https://provider.example.com/api/{VALUES_PATH}")
+        );
+    }
+
+    #[test]
+    fn fetch_all_pages_follows_page_tokens() {
+        let mut requested_tokens = Vec::new();
+        let result = fetch_all_pages(|token| {
+            requested_tokens.push(token.map(String::from));
+            Ok(match token {
+                None => ValuePage {
+                    values: vec![create_value("a"), create_value("b")],
+                    next_page_token: Some("page2".to_string()),
+                },
+                Some(_) => ValuePage {
+                    values: vec![create_value("c")],
+                    next_page_token: None,
+                },
+            })
+        });
+
+        let values = result.unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[2].entity_id, "c");
+        assert_eq!(requested_tokens, vec![None, Some("page2".to_string())]);
+    }
+
+    #[test]
+    fn fetch_all_pages_fails_when_page_token_repeats() {
+        let result = fetch_all_pages(|_| {
+            Ok(ValuePage {
+                values: vec![create_value("a")],
+                next_page_token: Some("same".to_string()),
+            })
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn fetch_all_pages_propagates_errors() {
+        let result = fetch_all_pages(|_| Err(DataAdapterError::communication("failed")));
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn send_request_to_provider_fails_for_unknown_entity() {
+        let adapter = create_adapter();
+        let result = adapter.send_request_to_provider("unknown_entity_id").await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn register_entity_fails_for_unsupported_operation() {
+        let adapter = create_adapter();
+        let result = adapter
+            .register_entity(
+                "entity_id",
+                &EntityEndpoint {
+                    protocol: Protocol::Http.to_string(),
+                    operations: vec![Operation::Set.to_string()],
+                    uri: "foo".to_string(),
+                    context: String::from("context"),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert!(adapter.entity_operation_map.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn register_entity_with_get_does_not_contact_provider() {
+        let adapter = create_adapter();
+        let result = adapter
+            .register_entity(
+                "entity_id",
+                &EntityEndpoint {
+                    protocol: Protocol::Http.to_string(),
+                    operations: vec![Operation::Get.to_string()],
+                    uri: "foo".to_string(),
+                    context: String::from("context"),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            adapter
+                .entity_operation_map
+                .lock()
+                .unwrap()
+                .get("entity_id")
+                .cloned(),
+            Some(Operation::Get)
+        );
+    }
+}
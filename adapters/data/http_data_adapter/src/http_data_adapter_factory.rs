@@ -0,0 +1,79 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterFactory},
+    entity::{Entity, EntityEndpoint, Operation, Protocol},
+    out_dir,
+    signal_store::SignalStore,
+};
+
+use crate::{callback_server::CallbackServer, config::Config, http_data_adapter::HttpDataAdapter};
+
+/// Factory for creating HttpDataAdapters.
+/// All adapters created by this factory share the callback server for the configured address.
+pub struct HttpDataAdapterFactory {
+    /// The adapter config
+    config: Config,
+
+    /// The callback server shared by all adapters
+    callback_server: Arc<CallbackServer>,
+}
+
+impl DataAdapterFactory for HttpDataAdapterFactory {
+    /// Create a new `HttpDataAdapterFactory`
+    fn create_new() -> Result<Self, DataAdapterError> {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        let callback_server =
+            CallbackServer::shared(&config.callback_address, config.shared_secret.clone());
+
+        Ok(Self {
+            config,
+            callback_server,
+        })
+    }
+
+    /// Check to see whether this factory can create an adapter for the requested entity.
+    /// Returns the first endpoint found that is supported by this factory.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to check for compatibility
+    fn is_supported(&self, entity: &Entity) -> Option<EntityEndpoint> {
+        entity.is_supported(
+            &[Protocol::Http, Protocol::Https],
+            &[Operation::Get, Operation::Subscribe],
+        )
+    }
+
+    /// Create a new adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider URI to associate with this adapter
+    /// - `signals`: the shared signal store
+    fn create_adapter(
+        &self,
+        provider_uri: &str,
+        signals: Arc<SignalStore>,
+    ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError> {
+        let adapter = HttpDataAdapter::with_callback_server(
+            provider_uri,
+            signals,
+            self.config.clone(),
+            self.callback_server.clone(),
+        )?;
+
+        Ok(Arc::new(adapter))
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+mod callback_server;
+mod config;
+pub mod http_data_adapter;
+pub mod http_data_adapter_factory;
+mod messages;
+
+/// The path on the provider for reading values
+const VALUES_PATH: &str = "values";
+/// The path on the provider for creating subscriptions
+const SUBSCRIPTIONS_PATH: &str = "subscriptions";
+/// The path on the callback server which receives published values
+const CALLBACK_PATH: &str = "/callback";
+/// The header which providers must use to send the shared secret with each callback
+const SHARED_SECRET_HEADER: &str = "x-freyja-shared-secret";
@@ -0,0 +1,42 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// A value for an entity, as returned by a provider or published to the callback server
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityValue {
+    /// The id of the entity
+    pub entity_id: String,
+
+    /// The entity value. This is parsed with the same rules as the other data adapters,
+    /// so it can be either a raw value or a serialized JSON object
+    pub value: String,
+
+    /// An id which the provider assigns to each value it publishes and keeps when retrying a callback,
+    /// so that Freyja can drop retried values. Providers which don't retry callbacks can omit this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_id: Option<String>,
+}
+
+/// A single page returned by a provider's bulk endpoint
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValuePage {
+    /// The values on this page
+    pub values: Vec<EntityValue>,
+
+    /// The token for requesting the next page, or `None` if this is the last page
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// A request to subscribe to an entity
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscribeRequest {
+    /// The id of the entity
+    pub entity_id: String,
+
+    /// The uri which the provider should publish values to
+    pub callback_uri: String,
+}
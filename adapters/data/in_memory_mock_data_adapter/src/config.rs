@@ -32,22 +32,70 @@ pub enum SensorValueConfig {
 
     /// A value which changes according to a fixed interval with set start and end values
     Stepwise { start: f32, end: f32, delta: f32 },
+
+    /// A sine wave with the given amplitude around an offset, which repeats every `period` values
+    Sine {
+        offset: f32,
+        amplitude: f32,
+        period: u64,
+    },
+
+    /// A value which increases linearly from `start` to `end` over `period` values, then restarts at `start`
+    Ramp { start: f32, end: f32, period: u64 },
+
+    /// A value which changes by a random amount of at most `max_step` each time, bounded by `min` and `max`.
+    /// Set `seed` to generate the same sequence of values each time
+    RandomWalk {
+        start: f32,
+        max_step: f32,
+        min: f32,
+        max: f32,
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+
+    /// A sequence of values which repeats once all of the values have been used
+    Steps(Vec<f32>),
+
+    /// A sequence of values read from a column of a CSV file, which repeats once all of the values have been used.
+    /// Rows where the column isn't a number, such as a header row, are skipped
+    Csv { path: String, column: usize },
 }
 
 impl SensorValueConfig {
-    /// Gets the nth value of the sensor
+    /// Gets the nth value of the sensor.
+    /// Returns `None` for values which can't be derived from `n` alone,
+    /// such as random walks and CSV files, or if there are no values in a sequence.
     ///
     /// # Arguments
     ///
     /// - `n`: the seed for the value
-    pub fn get_nth(&self, n: u8) -> f32 {
+    pub fn get_nth(&self, n: u64) -> Option<f32> {
         match self {
-            Self::Static(val) => *val,
-            Self::Stepwise { start, end, delta } => match start + delta * n as f32 {
+            Self::Static(val) => Some(*val),
+            Self::Stepwise { start, end, delta } => Some(match start + delta * n as f32 {
                 val if val > *end && *delta > 0.0 => *end,
                 val if val < *end && *delta < 0.0 => *end,
                 val => val,
+            }),
+            Self::Sine {
+                offset,
+                amplitude,
+                period,
+            } => {
+                let period = (*period).max(1);
+                let phase = (n % period) as f32 / period as f32;
+                Some(offset + amplitude * (2.0 * std::f32::consts::PI * phase).sin())
+            }
+            Self::Ramp { start, end, period } => Some(match *period {
+                0 | 1 => *start,
+                period => start + (end - start) * (n % period) as f32 / (period - 1) as f32,
+            }),
+            Self::Steps(values) => match values.len() as u64 {
+                0 => None,
+                len => Some(values[(n % len) as usize]),
             },
+            Self::RandomWalk { .. } | Self::Csv { .. } => None,
         }
     }
 }
@@ -72,7 +120,7 @@ mod config_item_tests {
         let c = SensorValueConfig::Static(val);
 
         for i in 0..10 {
-            assert!(f32_approx_eq(val, c.get_nth(i), 0.001));
+            assert!(f32_approx_eq(val, c.get_nth(i).unwrap(), 0.001));
         }
     }
 
@@ -81,16 +129,90 @@ mod config_item_tests {
         let (start, end, delta) = (42.0, 44.4, 0.1);
         let c = SensorValueConfig::Stepwise { start, end, delta };
 
-        let iters_to_end = ((end - start) / delta) as u8;
+        let iters_to_end = ((end - start) / delta) as u64;
 
         // First check for values less than end
         for i in 0..iters_to_end {
-            assert!(f32_approx_eq(start + delta * i as f32, c.get_nth(i), 0.001));
+            assert!(f32_approx_eq(
+                start + delta * i as f32,
+                c.get_nth(i).unwrap(),
+                0.001
+            ));
         }
 
         // Now validate behavior past end
         for i in iters_to_end..(iters_to_end + 10) {
-            assert!(f32_approx_eq(end, c.get_nth(i), 0.001));
+            assert!(f32_approx_eq(end, c.get_nth(i).unwrap(), 0.001));
+        }
+    }
+
+    #[test]
+    fn get_nth_returns_sine_values() {
+        let c = SensorValueConfig::Sine {
+            offset: 10.0,
+            amplitude: 2.0,
+            period: 4,
+        };
+
+        let expected = [10.0, 12.0, 10.0, 8.0];
+        for i in 0..12 {
+            assert!(f32_approx_eq(
+                expected[i as usize % expected.len()],
+                c.get_nth(i).unwrap(),
+                0.001
+            ));
+        }
+    }
+
+    #[test]
+    fn get_nth_returns_ramp_values() {
+        let c = SensorValueConfig::Ramp {
+            start: 0.0,
+            end: 3.0,
+            period: 4,
+        };
+
+        let expected = [0.0, 1.0, 2.0, 3.0];
+        for i in 0..12 {
+            assert!(f32_approx_eq(
+                expected[i as usize % expected.len()],
+                c.get_nth(i).unwrap(),
+                0.001
+            ));
         }
     }
+
+    #[test]
+    fn get_nth_returns_step_values() {
+        let values = vec![1.0, 5.0, 3.0];
+        let c = SensorValueConfig::Steps(values.clone());
+
+        for i in 0..9 {
+            assert!(f32_approx_eq(
+                values[i as usize % values.len()],
+                c.get_nth(i).unwrap(),
+                0.001
+            ));
+        }
+
+        assert_eq!(SensorValueConfig::Steps(vec![]).get_nth(0), None);
+    }
+
+    #[test]
+    fn get_nth_returns_none_for_stateful_values() {
+        let random_walk = SensorValueConfig::RandomWalk {
+            start: 0.0,
+            max_step: 1.0,
+            min: -10.0,
+            max: 10.0,
+            seed: None,
+        };
+        assert_eq!(random_walk.get_nth(0), None);
+
+        let csv = SensorValueConfig::Csv {
+            path: "values.csv".to_string(),
+            column: 0,
+        };
+        assert_eq!(csv.get_nth(0), None);
+    }
 }
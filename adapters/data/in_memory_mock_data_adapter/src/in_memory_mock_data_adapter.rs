@@ -2,41 +2,36 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::{
-    collections::HashMap,
-    sync::atomic::{AtomicU8, Ordering},
-    sync::Arc,
-    time::Duration,
-};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use freyja_common::{config_utils, out_dir, signal_store::SignalStore};
 use log::{info, warn};
-use tokio::sync::Mutex;
+use tokio::{sync::Mutex, task::JoinHandle};
 
-use crate::{
-    config::{Config, EntityConfig},
-    GET_OPERATION, SUBSCRIBE_OPERATION,
-};
+use crate::{config::Config, value_generator::ValueGenerator};
 
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
-    entity::EntityEndpoint,
+    entity::{EntityEndpoint, Operation},
 };
 
 pub struct InMemoryMockDataAdapter {
-    /// Maps the number of calls to each provider so we can mock changing behavior
-    data: Arc<Mutex<HashMap<String, (EntityConfig, AtomicU8)>>>,
+    /// Maps each entity to the generator for its values so we can mock changing behavior
+    data: Arc<Mutex<HashMap<String, ValueGenerator>>>,
 
     /// Local cache for keeping track of which entities this data adapter contains
-    entity_operation_map: Arc<Mutex<HashMap<String, String>>>,
+    entity_operation_map: Arc<Mutex<HashMap<String, Operation>>>,
 
     /// Shared queue for all data adapters to push new signal values of entities
     signals: Arc<SignalStore>,
 
     /// The frequency between updates to signal values
     signal_update_frequency: Duration,
+
+    /// The task which updates the values of subscribed entities, once the adapter has started
+    update_task: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl InMemoryMockDataAdapter {
@@ -53,14 +48,15 @@ impl InMemoryMockDataAdapter {
         let data = config
             .entities
             .into_iter()
-            .map(|c| (c.entity_id.clone(), (c, AtomicU8::new(0))))
-            .collect();
+            .map(|c| ValueGenerator::new(c.values).map(|generator| (c.entity_id, generator)))
+            .collect::<Result<_, _>>()?;
 
         Ok(Self {
             entity_operation_map: Arc::new(Mutex::new(HashMap::new())),
             data: Arc::new(Mutex::new(data)),
             signals,
             signal_update_frequency: Duration::from_millis(config.signal_update_frequency_ms),
+            update_task: Mutex::new(None),
         })
     }
 
@@ -73,15 +69,14 @@ impl InMemoryMockDataAdapter {
     fn generate_signal_value(
         entity_id: &str,
         signals: Arc<SignalStore>,
-        data: &HashMap<String, (EntityConfig, AtomicU8)>,
+        data: &mut HashMap<String, ValueGenerator>,
     ) -> Result<(), DataAdapterError> {
-        let (entity_config, counter) = data
-            .get(entity_id)
+        let generator = data
+            .get_mut(entity_id)
             .ok_or_else(|| format!("Cannot find {entity_id}"))
             .map_err(DataAdapterError::entity_not_found)?;
-        let n = counter.fetch_add(1, Ordering::SeqCst);
 
-        let value = entity_config.values.get_nth(n).to_string();
+        let value = generator.next_value().to_string();
         let entity_id = String::from(entity_id);
 
         signals
@@ -120,7 +115,7 @@ impl DataAdapter for InMemoryMockDataAdapter {
         let signal_update_frequency = self.signal_update_frequency;
         let data = self.data.clone();
 
-        tokio::spawn(async move {
+        let update_task = tokio::spawn(async move {
             loop {
                 let entities_with_subscribe: Vec<String>;
 
@@ -130,16 +125,16 @@ impl DataAdapter for InMemoryMockDataAdapter {
                         .await
                         .clone()
                         .into_iter()
-                        .filter(|(_, operation)| *operation == SUBSCRIBE_OPERATION)
+                        .filter(|(_, operation)| *operation == Operation::Subscribe)
                         .map(|(entity_id, _)| entity_id)
                         .collect();
                 }
 
                 {
-                    let data = data.lock().await;
+                    let mut data = data.lock().await;
                     for entity_id in entities_with_subscribe {
                         if let Err(e) =
-                            Self::generate_signal_value(&entity_id, signals.clone(), &data)
+                            Self::generate_signal_value(&entity_id, signals.clone(), &mut data)
                         {
                             warn!("Attempt to set value for non-existent entity {entity_id}: {e}");
                         }
@@ -149,12 +144,24 @@ impl DataAdapter for InMemoryMockDataAdapter {
                 tokio::time::sleep(signal_update_frequency).await;
             }
         });
+        *self.update_task.lock().await = Some(update_task);
 
         info!("Started an InMemoryMockDataAdapter!");
 
         Ok(())
     }
 
+    /// Stops a data adapter, which stops updating the values of subscribed entities
+    async fn stop(&self) -> Result<(), DataAdapterError> {
+        if let Some(update_task) = self.update_task.lock().await.take() {
+            update_task.abort();
+        }
+
+        info!("Stopped an InMemoryMockDataAdapter");
+
+        Ok(())
+    }
+
     /// Sends a request to a provider for obtaining the value of an entity
     ///
     /// # Arguments
@@ -175,9 +182,9 @@ impl DataAdapter for InMemoryMockDataAdapter {
         // Only need to handle Get operations since subscribe has already happened
         let operation = operation_result.unwrap();
 
-        let data = self.data.lock().await;
-        if operation == GET_OPERATION {
-            let _ = Self::generate_signal_value(entity_id, self.signals.clone(), &data);
+        let mut data = self.data.lock().await;
+        if operation == Operation::Get {
+            let _ = Self::generate_signal_value(entity_id, self.signals.clone(), &mut data);
         }
 
         Ok(())
@@ -195,25 +202,14 @@ impl DataAdapter for InMemoryMockDataAdapter {
         endpoint: &EntityEndpoint,
     ) -> Result<EntityRegistration, DataAdapterError> {
         // Prefer subscribe if present
-        let selected_operation = {
-            let mut result = None;
-            for operation in endpoint.operations.iter() {
-                if operation == SUBSCRIBE_OPERATION {
-                    result = Some(SUBSCRIBE_OPERATION);
-                    break;
-                } else if operation == GET_OPERATION {
-                    // Set result, but don't break the loop in case there's a subscribe operation later in the list
-                    result = Some(GET_OPERATION);
-                }
-            }
-
-            result.ok_or::<DataAdapterError>(DataAdapterErrorKind::OperationNotSupported.into())?
-        };
+        let selected_operation = endpoint
+            .select_operation(&[Operation::Subscribe, Operation::Get])
+            .ok_or::<DataAdapterError>(DataAdapterErrorKind::OperationNotSupported.into())?;
 
         self.entity_operation_map
             .lock()
             .await
-            .insert(String::from(entity_id), String::from(selected_operation));
+            .insert(String::from(entity_id), selected_operation);
 
         Ok(EntityRegistration::Registered)
     }
@@ -225,7 +221,7 @@ mod in_memory_mock_data_adapter_tests {
 
     use super::*;
 
-    use crate::config::SensorValueConfig;
+    use crate::config::{EntityConfig, SensorValueConfig};
 
     fn validate_signal(signals: Arc<SignalStore>, id: &str, value: f32) {
         let signal = signals.get(&id.to_owned());
@@ -301,10 +297,13 @@ mod in_memory_mock_data_adapter_tests {
 
         // First for loop, we generate signal values for each entity until we've reached the end value of each
         // entity that has the stepwise functionality configured.
-        let data = in_memory_mock_data_adapter.data.lock().await;
+        let mut data = in_memory_mock_data_adapter.data.lock().await;
         for i in 0..END_OF_SENSOR_VALUE_CONFIG_ITERATION {
-            let result =
-                InMemoryMockDataAdapter::generate_signal_value(STATIC_ID, signals.clone(), &data);
+            let result = InMemoryMockDataAdapter::generate_signal_value(
+                STATIC_ID,
+                signals.clone(),
+                &mut data,
+            );
             assert!(result.is_ok());
 
             validate_signal(signals.clone(), STATIC_ID, 42.0);
@@ -312,7 +311,7 @@ mod in_memory_mock_data_adapter_tests {
             let result = InMemoryMockDataAdapter::generate_signal_value(
                 INCREASING_ID,
                 signals.clone(),
-                &data,
+                &mut data,
             );
             assert!(result.is_ok());
 
@@ -321,7 +320,7 @@ mod in_memory_mock_data_adapter_tests {
             let result = InMemoryMockDataAdapter::generate_signal_value(
                 DECREASING_ID,
                 signals.clone(),
-                &data,
+                &mut data,
             );
             assert!(result.is_ok());
 
@@ -330,8 +329,11 @@ mod in_memory_mock_data_adapter_tests {
 
         // Validating each entity that has the stepwise functionality configured is at its end value
         for _ in 0..END_OF_SENSOR_VALUE_CONFIG_ITERATION {
-            let result =
-                InMemoryMockDataAdapter::generate_signal_value(STATIC_ID, signals.clone(), &data);
+            let result = InMemoryMockDataAdapter::generate_signal_value(
+                STATIC_ID,
+                signals.clone(),
+                &mut data,
+            );
             assert!(result.is_ok());
 
             validate_signal(signals.clone(), STATIC_ID, 42.0);
@@ -339,7 +341,7 @@ mod in_memory_mock_data_adapter_tests {
             let result = InMemoryMockDataAdapter::generate_signal_value(
                 INCREASING_ID,
                 signals.clone(),
-                &data,
+                &mut data,
             );
             assert!(result.is_ok());
 
@@ -348,7 +350,7 @@ mod in_memory_mock_data_adapter_tests {
             let result = InMemoryMockDataAdapter::generate_signal_value(
                 DECREASING_ID,
                 signals.clone(),
-                &data,
+                &mut data,
             );
             assert!(result.is_ok());
 
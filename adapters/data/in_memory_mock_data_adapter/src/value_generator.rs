@@ -0,0 +1,263 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use freyja_common::data_adapter::DataAdapterError;
+
+use crate::config::SensorValueConfig;
+
+/// Generates the values of a mock entity's signal and keeps the state needed for values
+/// which can't be derived from the number of values generated so far
+pub(crate) struct ValueGenerator {
+    /// The config for the sensor values
+    config: SensorValueConfig,
+
+    /// The number of values generated so far
+    count: u64,
+
+    /// The last generated value
+    last_value: Option<f32>,
+
+    /// The state of the pseudo-random number generator used for random walks
+    rng_state: u64,
+
+    /// The values read from a CSV file, if the values are configured with a CSV file
+    csv_values: Vec<f32>,
+}
+
+impl ValueGenerator {
+    /// Creates a new generator, reading the values from a CSV file if configured
+    ///
+    /// # Arguments
+    /// - `config`: the config for the sensor values
+    pub fn new(config: SensorValueConfig) -> Result<Self, DataAdapterError> {
+        let csv_values = match &config {
+            SensorValueConfig::Csv { path, column } => Self::read_csv(path, *column)?,
+            SensorValueConfig::Steps(values) if values.is_empty() => {
+                return Err(DataAdapterError::deserialize(
+                    "Step sequences must have at least one value",
+                ))
+            }
+            SensorValueConfig::RandomWalk { min, max, .. } if min > max => {
+                return Err(DataAdapterError::deserialize(format!(
+                    "The minimum value of a random walk ({min}) is greater than the maximum ({max})"
+                )))
+            }
+            _ => Vec::new(),
+        };
+
+        let rng_state = match &config {
+            SensorValueConfig::RandomWalk {
+                seed: Some(seed), ..
+            } => *seed,
+            _ => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default(),
+        };
+
+        Ok(Self {
+            config,
+            count: 0,
+            last_value: None,
+            // The xorshift algorithm gets stuck at 0, so the state must be non-zero
+            rng_state: rng_state.max(1),
+            csv_values,
+        })
+    }
+
+    /// Generates the next value
+    pub fn next_value(&mut self) -> f32 {
+        let n = self.count;
+        self.count = self.count.wrapping_add(1);
+
+        let value = match self.config {
+            SensorValueConfig::RandomWalk {
+                start,
+                max_step,
+                min,
+                max,
+                ..
+            } => match self.last_value {
+                Some(last_value) => (last_value + max_step * self.next_random()).clamp(min, max),
+                None => start,
+            },
+            SensorValueConfig::Csv { .. } => {
+                self.csv_values[(n % self.csv_values.len() as u64) as usize]
+            }
+            // Values are only None for the variants above or empty step sequences, which are rejected in new()
+            ref config => config.get_nth(n).unwrap_or_default(),
+        };
+
+        self.last_value = Some(value);
+        value
+    }
+
+    /// Generates a pseudo-random number in the range [-1, 1] using the xorshift64 algorithm.
+    /// This doesn't need to be cryptographically secure since it's only used for mock data.
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+
+        (self.rng_state as f64 / u64::MAX as f64 * 2.0 - 1.0) as f32
+    }
+
+    /// Reads the numeric values from a column of a CSV file
+    ///
+    /// # Arguments
+    /// - `path`: the path to the CSV file
+    /// - `column`: the zero-based index of the column to read
+    fn read_csv(path: &str, column: usize) -> Result<Vec<f32>, DataAdapterError> {
+        let contents = fs::read_to_string(path).map_err(DataAdapterError::io)?;
+        let values: Vec<f32> = contents
+            .lines()
+            .filter_map(|line| line.split(',').nth(column))
+            .filter_map(|value| value.trim().parse().ok())
+            .collect();
+
+        if values.is_empty() {
+            return Err(DataAdapterError::parse(format!(
+                "Column {column} of {path} does not have any numeric values"
+            )));
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod value_generator_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use freyja_test_common::fixtures::TempDirFixture;
+
+    /// A CSV file in a temp directory which is deleted when dropped
+    struct CsvFile {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl CsvFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join(format!("{name}.csv"));
+            fs::write(&path, contents).unwrap();
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+    }
+
+    #[test]
+    fn next_value_uses_config_values() {
+        let mut uut = ValueGenerator::new(SensorValueConfig::Steps(vec![1.0, 2.0])).unwrap();
+
+        let values: Vec<f32> = (0..4).map(|_| uut.next_value()).collect();
+        assert_eq!(values, vec![1.0, 2.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn new_returns_err_for_empty_steps() {
+        assert!(ValueGenerator::new(SensorValueConfig::Steps(vec![])).is_err());
+    }
+
+    #[test]
+    fn random_walk_stays_within_bounds() {
+        let (start, max_step, min, max) = (0.0, 2.0, -5.0, 5.0);
+        let mut uut = ValueGenerator::new(SensorValueConfig::RandomWalk {
+            start,
+            max_step,
+            min,
+            max,
+            seed: Some(42),
+        })
+        .unwrap();
+
+        let mut last_value = uut.next_value();
+        assert_eq!(last_value, start);
+
+        for _ in 0..1000 {
+            let value = uut.next_value();
+            assert!((min..=max).contains(&value));
+            // Allow for floating-point errors when comparing the step size
+            assert!((value - last_value).abs() <= max_step + 0.0001);
+            last_value = value;
+        }
+    }
+
+    #[test]
+    fn new_returns_err_for_invalid_random_walk_bounds() {
+        let result = ValueGenerator::new(SensorValueConfig::RandomWalk {
+            start: 0.0,
+            max_step: 1.0,
+            min: 10.0,
+            max: -10.0,
+            seed: None,
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn random_walk_is_reproducible_with_seed() {
+        let config = SensorValueConfig::RandomWalk {
+            start: 0.0,
+            max_step: 1.0,
+            min: -10.0,
+            max: 10.0,
+            seed: Some(7),
+        };
+
+        let mut first = ValueGenerator::new(config.clone()).unwrap();
+        let mut second = ValueGenerator::new(config).unwrap();
+        for _ in 0..100 {
+            assert_eq!(first.next_value(), second.next_value());
+        }
+    }
+
+    #[test]
+    fn csv_values_are_read_from_column() {
+        let file = CsvFile::new(
+            "in_memory_mock_data_adapter_csv_values",
+            "time,speed\n0,10.5\n1,11\n2,not a number\n3,12.25\n",
+        );
+        let mut uut = ValueGenerator::new(SensorValueConfig::Csv {
+            path: file.path.to_string_lossy().into_owned(),
+            column: 1,
+        })
+        .unwrap();
+
+        let values: Vec<f32> = (0..4).map(|_| uut.next_value()).collect();
+        assert_eq!(values, vec![10.5, 11.0, 12.25, 10.5]);
+    }
+
+    #[test]
+    fn new_returns_err_for_csv_without_values() {
+        let file = CsvFile::new("in_memory_mock_data_adapter_csv_empty", "time,speed\n");
+        let result = ValueGenerator::new(SensorValueConfig::Csv {
+            path: file.path.to_string_lossy().into_owned(),
+            column: 1,
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn new_returns_err_for_missing_csv() {
+        let result = ValueGenerator::new(SensorValueConfig::Csv {
+            path: "/this/path/does/not/exist.csv".to_string(),
+            column: 0,
+        });
+
+        assert!(result.is_err());
+    }
+}
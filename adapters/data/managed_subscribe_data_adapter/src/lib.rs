@@ -5,8 +5,3 @@
 mod config;
 pub mod managed_subscribe_data_adapter;
 pub mod managed_subscribe_data_adapter_factory;
-
-const GRPC_PROTOCOL: &str = "grpc";
-const MQTT_PROTOCOL: &str = "mqtt";
-const MANAGED_SUBSCRIBE_OPERATION: &str = "ManagedSubscribe";
-const SUBSCRIBE_OPERATION: &str = "Subscribe";
@@ -11,17 +11,16 @@ use core_protobuf_data_access::module::managed_subscribe::v1::{
 use log::{debug, info};
 use tonic::transport::Channel;
 
-use crate::{
-    config::Config, GRPC_PROTOCOL, MANAGED_SUBSCRIBE_OPERATION, MQTT_PROTOCOL, SUBSCRIBE_OPERATION,
-};
+use crate::config::Config;
 
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     config_utils,
     data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
-    entity::{Entity, EntityEndpoint},
+    entity::{Entity, EntityEndpoint, Operation, Protocol},
     out_dir,
     signal_store::SignalStore,
+    uri::{EndpointUri, UriKind},
 };
 
 /// Interfaces with providers which utilize 'Managed Subscribe'. Based on the Ibeji managed
@@ -54,8 +53,10 @@ impl DataAdapter for ManagedSubscribeDataAdapter {
             DataAdapterError::deserialize,
         )?;
 
+        let provider_uri =
+            EndpointUri::parse(provider_uri, UriKind::Grpc).map_err(DataAdapterError::parse)?;
         let client = futures::executor::block_on(async {
-            ManagedSubscribeClient::connect(String::from(provider_uri))
+            ManagedSubscribeClient::connect(provider_uri.to_string())
                 .await
                 .map_err(DataAdapterError::communication)
         })?;
@@ -93,10 +94,8 @@ impl DataAdapter for ManagedSubscribeDataAdapter {
         // Verify that the endpoint has the expected data.
         // This shouldn't be necessary since it's first verified by the factory,
         // but this ensures we don't get hit by an edge case
-        if endpoint.protocol != GRPC_PROTOCOL
-            || !endpoint
-                .operations
-                .contains(&MANAGED_SUBSCRIBE_OPERATION.to_string())
+        if endpoint.protocol() != Protocol::Grpc
+            || !endpoint.supports_operation(&Operation::ManagedSubscribe)
         {
             return Err(DataAdapterErrorKind::OperationNotSupported.into());
         }
@@ -123,16 +122,20 @@ impl DataAdapter for ManagedSubscribeDataAdapter {
 
         // The MQTT data adapter supports v5 and v3 so do not need to make a distinction
         let mut protocol = sub_info.protocol;
-        if protocol.contains(MQTT_PROTOCOL) {
-            protocol = MQTT_PROTOCOL.to_string();
+        if protocol
+            .to_ascii_lowercase()
+            .contains(Protocol::Mqtt.as_str())
+        {
+            protocol = Protocol::Mqtt.to_string();
         }
 
         // Construct endpoint information from returned result
         let endpoint = EntityEndpoint {
             protocol,
-            operations: vec![SUBSCRIBE_OPERATION.to_string()],
+            operations: vec![Operation::Subscribe.to_string()],
             uri: sub_info.uri,
             context: sub_info.context,
+            ..Default::default()
         };
 
         // Create new entity object with updated endpoint information.
@@ -2,6 +2,9 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashMap;
+
+use freyja_common::protobuf_decoder::ProtobufDecoderConfig;
 use serde::{Deserialize, Serialize};
 
 /// The MQTT Data Adapter config
@@ -9,4 +12,20 @@ use serde::{Deserialize, Serialize};
 pub struct Config {
     /// The keep alive interval in seconds
     pub keep_alive_interval_s: u64,
+
+    /// The interval before the first attempt to reconnect to the broker in milliseconds.
+    /// The interval doubles after each failed attempt
+    pub reconnect_initial_interval_ms: u64,
+
+    /// The maximum interval between attempts to reconnect to the broker in milliseconds
+    pub reconnect_max_interval_ms: u64,
+
+    /// The name of the MQTT v5 user property containing the per-topic sequence number of a message.
+    /// If this is `None`, missed messages are not detected
+    pub sequence_number_property: Option<String>,
+
+    /// The decoders for topics whose payloads are protobuf messages, keyed by topic.
+    /// Payloads on other topics are parsed as JSON or raw values
+    #[serde(default)]
+    pub protobuf_decoders: HashMap<String, ProtobufDecoderConfig>,
 }
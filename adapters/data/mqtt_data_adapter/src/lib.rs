@@ -5,6 +5,4 @@
 mod config;
 pub mod mqtt_data_adapter;
 pub mod mqtt_data_adapter_factory;
-
-const MQTT_PROTOCOL: &str = "mqtt";
-const SUBSCRIBE_OPERATION: &str = "Subscribe";
+mod sequence_tracker;
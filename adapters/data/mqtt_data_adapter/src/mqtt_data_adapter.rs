@@ -5,19 +5,21 @@
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use log::{debug, info};
+use log::{debug, info, warn};
 use paho_mqtt::{Client, QOS_1};
-use tokio::sync::Mutex;
+use tokio::{sync::Mutex, time::sleep};
 use uuid::Uuid;
 
-use crate::{config::Config, MQTT_PROTOCOL, SUBSCRIBE_OPERATION};
+use crate::{config::Config, sequence_tracker::SequenceTracker};
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     config_utils,
     data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
-    entity::EntityEndpoint,
+    entity::{EntityEndpoint, Operation, Protocol},
     message_utils, out_dir,
+    protobuf_decoder::ProtobufDecoder,
     signal_store::SignalStore,
+    uri::{EndpointUri, UriKind},
 };
 
 const MQTT_CLIENT_ID_PREFIX: &str = "freyja-mqtt-data-adapter";
@@ -35,10 +37,68 @@ pub struct MqttDataAdapter {
     /// Maps subscribed topics to their associated entity id
     subscriptions: Arc<Mutex<HashMap<String, String>>>,
 
+    /// The decoders for topics whose payloads are protobuf messages, keyed by topic
+    decoders: Arc<HashMap<String, ProtobufDecoder>>,
+
     /// Shared signal store for all data adapters to push new signal values
     signals: Arc<SignalStore>,
 }
 
+impl MqttDataAdapter {
+    /// Reconnects to the broker, retrying with exponential backoff until it succeeds,
+    /// then resubscribes to the topics of all registered entities
+    ///
+    /// # Arguments
+    /// - `client`: the MQTT client
+    /// - `subscriptions`: the subscribed topics and their associated entity ids
+    /// - `config`: the adapter config
+    async fn reconnect(
+        client: &Mutex<Client>,
+        subscriptions: &Mutex<HashMap<String, String>>,
+        config: &Config,
+    ) {
+        let mut attempts: u32 = 0;
+        loop {
+            let interval = Self::reconnect_interval(config, attempts);
+            debug!("Reconnecting to the MQTT broker in {interval:?}");
+            sleep(interval).await;
+            attempts = attempts.saturating_add(1);
+
+            // Don't hold the locks while waiting or reconnecting so that entities can still be registered.
+            // Clones of the client share its connection
+            let client = client.lock().await.clone();
+            match client.reconnect() {
+                Ok(_) => {
+                    let topics: Vec<String> = subscriptions.lock().await.keys().cloned().collect();
+                    for topic in topics.iter() {
+                        if let Err(e) = client.subscribe(topic, QOS_1) {
+                            log::error!("Error resubscribing to topic {topic}: {e}");
+                        }
+                    }
+
+                    info!(
+                        "Reconnected to the MQTT broker after {attempts} attempt(s) and resubscribed to {} topic(s)",
+                        topics.len()
+                    );
+                    return;
+                }
+                Err(e) => warn!("Failed to reconnect to the MQTT broker (attempt {attempts}): {e}"),
+            }
+        }
+    }
+
+    /// Gets the interval before the next attempt to reconnect to the broker
+    ///
+    /// # Arguments
+    /// - `config`: the adapter config
+    /// - `failed_attempts`: the number of failed attempts so far
+    fn reconnect_interval(config: &Config, failed_attempts: u32) -> Duration {
+        Duration::from_millis(config.reconnect_initial_interval_ms)
+            .saturating_mul(2u32.saturating_pow(failed_attempts))
+            .min(Duration::from_millis(config.reconnect_max_interval_ms))
+    }
+}
+
 #[async_trait]
 impl DataAdapter for MqttDataAdapter {
     /// Creates a data adapter
@@ -50,7 +110,7 @@ impl DataAdapter for MqttDataAdapter {
     where
         Self: Sized,
     {
-        let config = config_utils::read_from_files(
+        let config: Config = config_utils::read_from_files(
             config_file_stem!(),
             config_utils::JSON_EXT,
             out_dir!(),
@@ -58,9 +118,14 @@ impl DataAdapter for MqttDataAdapter {
             DataAdapterError::deserialize,
         )?;
 
+        let decoders = ProtobufDecoder::from_configs(&config.protobuf_decoders)
+            .map_err(DataAdapterError::deserialize)?;
+
+        let provider_uri =
+            EndpointUri::parse(provider_uri, UriKind::Mqtt).map_err(DataAdapterError::parse)?;
         let client_id = format!("{MQTT_CLIENT_ID_PREFIX}_{}", Uuid::new_v4());
         let create_options = paho_mqtt::CreateOptionsBuilder::new()
-            .server_uri(provider_uri)
+            .server_uri(provider_uri.to_string())
             .client_id(client_id)
             .finalize();
 
@@ -71,6 +136,7 @@ impl DataAdapter for MqttDataAdapter {
             config,
             client: Arc::new(Mutex::new(client)),
             subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            decoders: Arc::new(decoders),
             signals,
         })
     }
@@ -97,38 +163,65 @@ impl DataAdapter for MqttDataAdapter {
                 .map_err(DataAdapterError::communication);
         }
 
+        let config = self.config.clone();
         let client = self.client.clone();
         let subscriptions = self.subscriptions.clone();
+        let decoders = self.decoders.clone();
         let signals = self.signals.clone();
 
         // Start the thread for handling publishes from providers
         tokio::spawn(async move {
             info!("Started MQTT listener");
+            let mut sequence_tracker = SequenceTracker::default();
             for msg in receiver.iter() {
                 if let Some(m) = msg {
+                    let sequence_number = config
+                        .sequence_number_property
+                        .as_ref()
+                        .and_then(|property| m.properties().find_user_property(property))
+                        .and_then(|sequence_number| sequence_number.parse::<u64>().ok());
+
+                    if let Some(sequence_number) = sequence_number {
+                        let missed = sequence_tracker.record(m.topic(), sequence_number);
+                        if missed > 0 {
+                            warn!(
+                                "Missed {missed} message(s) on topic {} ({} missed in total)",
+                                m.topic(),
+                                sequence_tracker.missed_messages(m.topic())
+                            );
+                        }
+                    }
+
                     let subsciptions = subscriptions.lock().await;
                     let entity_id = subsciptions.get(m.topic()).unwrap().clone();
-                    let value = message_utils::parse_value(m.payload_str().to_string());
-                    if signals.set_value(entity_id, value).is_none() {
+                    let value = match decoders.get(m.topic()) {
+                        Some(decoder) => match decoder.decode(m.payload()) {
+                            Ok(value) => value,
+                            Err(e) => {
+                                warn!("Could not decode payload on topic {}: {e}", m.topic());
+                                continue;
+                            }
+                        },
+                        None => message_utils::parse_value(m.payload_str().to_string()),
+                    };
+                    // Redelivered QoS 1 messages keep their sequence number, so it identifies duplicates
+                    let result = match sequence_number {
+                        Some(sequence_number) => signals.set_value_with_sequence_id(
+                            entity_id,
+                            value,
+                            sequence_number.to_string(),
+                        ),
+                        None => signals.set_value(entity_id, value),
+                    };
+
+                    if result.is_none() {
                         log::warn!("Attempt to set value for non-existent signal");
                     }
                 } else {
-                    let client = client.lock().await;
-                    if !client.is_connected() {
-                        match client.reconnect() {
-                            Ok(_) => {
-                                let subscriptions = subscriptions.lock().await;
-                                for (topic, _) in subscriptions.iter() {
-                                    if let Err(e) = client.subscribe(topic, QOS_1) {
-                                        log::error!("Error resubscribing to topic {topic}: {e}");
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("Fatal error trying to reconnect to mqtt client: {e}");
-                                break;
-                            }
-                        }
+                    let is_connected = client.lock().await.is_connected();
+                    if !is_connected {
+                        warn!("Lost connection to the MQTT broker");
+                        Self::reconnect(&client, &subscriptions, &config).await;
                     }
                 }
             }
@@ -154,6 +247,16 @@ impl DataAdapter for MqttDataAdapter {
         Ok(())
     }
 
+    /// Stops a data adapter.
+    /// The listener stops once it has handled the messages it already received, then unsubscribes and disconnects.
+    async fn stop(&self) -> Result<(), DataAdapterError> {
+        self.client.lock().await.stop_consuming();
+
+        info!("Stopped an MQTTDataAdapter");
+
+        Ok(())
+    }
+
     /// Sends a request to a provider for obtaining the value of an entity
     ///
     /// # Arguments
@@ -177,10 +280,8 @@ impl DataAdapter for MqttDataAdapter {
         // Verify that the endpoint has the expected data.
         // This shouldn't be necessary since it's first verified by the factory,
         // but this ensures we don't get hit by an edge case
-        if endpoint.protocol != MQTT_PROTOCOL
-            || !endpoint
-                .operations
-                .contains(&SUBSCRIBE_OPERATION.to_string())
+        if endpoint.protocol() != Protocol::Mqtt
+            || !endpoint.supports_operation(&Operation::Subscribe)
         {
             return Err(DataAdapterErrorKind::OperationNotSupported.into());
         }
@@ -199,3 +300,31 @@ impl DataAdapter for MqttDataAdapter {
         Ok(EntityRegistration::Registered)
     }
 }
+
+#[cfg(test)]
+mod mqtt_data_adapter_tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_interval_uses_exponential_backoff() {
+        let config = Config {
+            keep_alive_interval_s: 30,
+            reconnect_initial_interval_ms: 1000,
+            reconnect_max_interval_ms: 5000,
+            sequence_number_property: None,
+            protobuf_decoders: HashMap::new(),
+        };
+
+        let intervals: Vec<u64> = (0..5)
+            .map(|attempts| {
+                MqttDataAdapter::reconnect_interval(&config, attempts).as_millis() as u64
+            })
+            .collect();
+
+        assert_eq!(intervals, vec![1000, 2000, 4000, 5000, 5000]);
+        assert_eq!(
+            MqttDataAdapter::reconnect_interval(&config, u32::MAX),
+            Duration::from_millis(5000)
+        );
+    }
+}
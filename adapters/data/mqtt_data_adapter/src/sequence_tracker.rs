@@ -0,0 +1,106 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+/// Tracks per-topic sequence numbers to detect messages that were missed,
+/// for example while the adapter was disconnected from the broker
+#[derive(Debug, Default)]
+pub(crate) struct SequenceTracker {
+    /// Maps topics to the sequence number of the last message received on that topic
+    last_sequence_numbers: HashMap<String, u64>,
+
+    /// Maps topics to the total number of messages missed on that topic
+    missed_messages: HashMap<String, u64>,
+}
+
+impl SequenceTracker {
+    /// Records the sequence number of a message.
+    /// Returns the number of messages missed on the topic since the previous message.
+    /// Sequence numbers that don't increase, such as after a publisher restart, are not counted as gaps.
+    ///
+    /// # Arguments
+    /// - `topic`: the topic of the message
+    /// - `sequence_number`: the sequence number of the message
+    pub fn record(&mut self, topic: &str, sequence_number: u64) -> u64 {
+        let missed = match self
+            .last_sequence_numbers
+            .insert(topic.to_string(), sequence_number)
+        {
+            Some(last) => sequence_number
+                .checked_sub(last)
+                .map_or(0, |difference| difference.saturating_sub(1)),
+            None => 0,
+        };
+
+        if missed > 0 {
+            *self.missed_messages.entry(topic.to_string()).or_insert(0) += missed;
+        }
+
+        missed
+    }
+
+    /// Gets the total number of messages missed on a topic
+    ///
+    /// # Arguments
+    /// - `topic`: the topic to get the count for
+    pub fn missed_messages(&self, topic: &str) -> u64 {
+        self.missed_messages.get(topic).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod sequence_tracker_tests {
+    use super::*;
+
+    const TOPIC: &str = "topic";
+
+    #[test]
+    fn record_returns_zero_for_consecutive_messages() {
+        let mut uut = SequenceTracker::default();
+
+        for sequence_number in 1..=5 {
+            assert_eq!(uut.record(TOPIC, sequence_number), 0);
+        }
+
+        assert_eq!(uut.missed_messages(TOPIC), 0);
+    }
+
+    #[test]
+    fn record_detects_gaps() {
+        let mut uut = SequenceTracker::default();
+
+        assert_eq!(uut.record(TOPIC, 1), 0);
+        assert_eq!(uut.record(TOPIC, 4), 2);
+        assert_eq!(uut.record(TOPIC, 5), 0);
+        assert_eq!(uut.record(TOPIC, 7), 1);
+
+        assert_eq!(uut.missed_messages(TOPIC), 3);
+    }
+
+    #[test]
+    fn record_ignores_duplicates_and_resets() {
+        let mut uut = SequenceTracker::default();
+
+        assert_eq!(uut.record(TOPIC, 10), 0);
+        assert_eq!(uut.record(TOPIC, 10), 0);
+        assert_eq!(uut.record(TOPIC, 1), 0);
+        assert_eq!(uut.record(TOPIC, 2), 0);
+
+        assert_eq!(uut.missed_messages(TOPIC), 0);
+    }
+
+    #[test]
+    fn record_tracks_topics_independently() {
+        let mut uut = SequenceTracker::default();
+
+        uut.record(TOPIC, 1);
+        uut.record("other", 5);
+        assert_eq!(uut.record(TOPIC, 3), 1);
+        assert_eq!(uut.record("other", 6), 0);
+
+        assert_eq!(uut.missed_messages(TOPIC), 1);
+        assert_eq!(uut.missed_messages("other"), 0);
+    }
+}
@@ -4,7 +4,6 @@
 
 use std::{
     collections::HashMap,
-    net::SocketAddr,
     sync::{Arc, Mutex},
 };
 
@@ -17,14 +16,15 @@ use samples_protobuf_data_access::sample_grpc::v1::{
 };
 use tonic::transport::{Channel, Server};
 
-use crate::{config::Config, grpc_client_impl::GRPCClientImpl, GET_OPERATION, SUBSCRIBE_OPERATION};
+use crate::{config::Config, grpc_client_impl::GRPCClientImpl};
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     config_utils,
     data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
-    entity::EntityEndpoint,
+    entity::{EntityEndpoint, Operation},
     out_dir,
     signal_store::SignalStore,
+    uri::{self, EndpointUri, UriKind},
 };
 
 /// Interfaces with providers which support GRPC. Based on the Ibeji mixed sample.
@@ -36,7 +36,7 @@ pub struct SampleGRPCDataAdapter {
     provider_client: DigitalTwinProviderClient<Channel>,
 
     /// Local cache for keeping track of which entities this data adapter contains
-    entity_operation_map: Mutex<HashMap<String, String>>,
+    entity_operation_map: Mutex<HashMap<String, Operation>>,
 
     /// Shared signal store for all data adapters to push new signal values
     signals: Arc<SignalStore>,
@@ -61,8 +61,10 @@ impl DataAdapter for SampleGRPCDataAdapter {
             DataAdapterError::deserialize,
         )?;
 
+        let provider_uri =
+            EndpointUri::parse(provider_uri, UriKind::Grpc).map_err(DataAdapterError::parse)?;
         let provider_client = futures::executor::block_on(async {
-            DigitalTwinProviderClient::connect(String::from(provider_uri))
+            DigitalTwinProviderClient::connect(provider_uri.to_string())
                 .await
                 .map_err(DataAdapterError::communication)
         })?;
@@ -77,12 +79,8 @@ impl DataAdapter for SampleGRPCDataAdapter {
 
     /// Starts a data adapter
     async fn start(&self) -> Result<(), DataAdapterError> {
-        let addr: SocketAddr = self
-            .config
-            .consumer_address
-            .parse()
-            .map_err(DataAdapterError::parse)
-            .unwrap();
+        let addr = uri::parse_socket_address(&self.config.consumer_address)
+            .map_err(DataAdapterError::parse)?;
 
         let consumer_impl = GRPCClientImpl {
             signals: self.signals.clone(),
@@ -121,7 +119,7 @@ impl DataAdapter for SampleGRPCDataAdapter {
 
         // Only need to handle Get operations since subscribe has already happened
         let operation = operation_result.unwrap();
-        if operation == GET_OPERATION {
+        if operation == Operation::Get {
             let mut client = self.provider_client.clone();
             let request = tonic::Request::new(GetRequest {
                 entity_id: String::from(entity_id),
@@ -148,27 +146,16 @@ impl DataAdapter for SampleGRPCDataAdapter {
         endpoint: &EntityEndpoint,
     ) -> Result<EntityRegistration, DataAdapterError> {
         // Prefer subscribe if present
-        let selected_operation = {
-            let mut result = None;
-            for operation in endpoint.operations.iter() {
-                if operation == SUBSCRIBE_OPERATION {
-                    result = Some(SUBSCRIBE_OPERATION);
-                    break;
-                } else if operation == GET_OPERATION {
-                    // Set result, but don't break the loop in case there's a subscribe operation later in the list
-                    result = Some(GET_OPERATION);
-                }
-            }
-
-            result.ok_or::<DataAdapterError>(DataAdapterErrorKind::OperationNotSupported.into())?
-        };
+        let selected_operation = endpoint
+            .select_operation(&[Operation::Subscribe, Operation::Get])
+            .ok_or::<DataAdapterError>(DataAdapterErrorKind::OperationNotSupported.into())?;
 
         self.entity_operation_map
             .lock()
             .unwrap()
-            .insert(String::from(entity_id), String::from(selected_operation));
+            .insert(String::from(entity_id), selected_operation.clone());
 
-        if selected_operation == SUBSCRIBE_OPERATION {
+        if selected_operation == Operation::Subscribe {
             let consumer_uri = format!("http://{}", self.config.get_advertised_address()); // Devskim: ignore DS137138
             let mut client = self.provider_client.clone();
             let request = tonic::Request::new(SubscribeRequest {
@@ -264,7 +251,7 @@ mod sample_grpc_data_adapter_tests {
     /// so you would need to set an arbitrary port per test for TCP/IP sockets.
     #[cfg(unix)]
     mod unix_tests {
-        use crate::GRPC_PROTOCOL;
+        use freyja_common::entity::Protocol;
 
         use super::*;
 
@@ -331,10 +318,11 @@ mod sample_grpc_data_adapter_tests {
                     .register_entity(
                         entity_id,
                         &EntityEndpoint {
-                            protocol: GRPC_PROTOCOL.to_string(),
-                            operations: vec![GET_OPERATION.to_string()],
+                            protocol: Protocol::Grpc.to_string(),
+                            operations: vec![Operation::Get.to_string()],
                             uri: "foo".to_string(),
                             context: String::from("context"),
+                            ..Default::default()
                         },
                     )
                     .await;
@@ -349,10 +337,11 @@ mod sample_grpc_data_adapter_tests {
                     .register_entity(
                         entity_id,
                         &EntityEndpoint {
-                            protocol: GRPC_PROTOCOL.to_string(),
-                            operations: vec![SUBSCRIBE_OPERATION.to_string()],
+                            protocol: Protocol::Grpc.to_string(),
+                            operations: vec![Operation::Subscribe.to_string()],
                             uri: "foo".to_string(),
                             context: String::from("context"),
+                            ..Default::default()
                         },
                     )
                     .await;
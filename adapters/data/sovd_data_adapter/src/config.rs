@@ -0,0 +1,18 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The SOVD data adapter config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// Headers to add to every request sent to a SOVD server, such as authorization headers
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// The timeout for requests sent to a SOVD server, in milliseconds
+    pub request_timeout_ms: u64,
+}
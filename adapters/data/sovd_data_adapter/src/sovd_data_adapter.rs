@@ -0,0 +1,422 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::Value;
+use ureq::{Agent, AgentBuilder};
+
+use crate::config::Config;
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
+    entity::{EntityEndpoint, Operation},
+    out_dir,
+    signal_store::SignalStore,
+    uri::{EndpointUri, UriKind},
+};
+
+/// The SOVD entity collections which have data resources
+const ENTITY_COLLECTIONS: [&str; 3] = ["components", "apps", "areas"];
+
+/// Reads data identifiers from a Service-Oriented Vehicle Diagnostics (SOVD) server over HTTP or HTTPS
+pub struct SovdDataAdapter {
+    /// The adapter config
+    config: Config,
+
+    /// The base uri of the SOVD server
+    provider_uri: String,
+
+    /// Client for sending requests to the SOVD server
+    agent: Agent,
+
+    /// The path of the data resource of each entity, relative to the provider uri
+    data_paths: Mutex<HashMap<String, String>>,
+
+    /// Shared signal store for all data adapters to push new signal values
+    signals: Arc<SignalStore>,
+}
+
+impl SovdDataAdapter {
+    /// Creates a data adapter with a config.
+    /// Returns an error if the provider uri isn't a valid HTTP or HTTPS uri
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the base uri of the SOVD server, such as `https://gateway/sovd/v1`
+    /// - `signals`: the shared signal store
+    /// - `config`: the adapter config
+    fn with_config(
+        provider_uri: &str,
+        signals: Arc<SignalStore>,
+        config: Config,
+    ) -> Result<Self, DataAdapterError> {
+        let provider_uri =
+            EndpointUri::parse(provider_uri, UriKind::Http).map_err(DataAdapterError::parse)?;
+        let agent = AgentBuilder::new()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build();
+
+        Ok(Self {
+            config,
+            provider_uri: provider_uri.to_string(),
+            agent,
+            data_paths: Mutex::new(HashMap::new()),
+            signals,
+        })
+    }
+}
+
+#[async_trait]
+impl DataAdapter for SovdDataAdapter {
+    /// Creates a data adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri for accessing an entity's information
+    /// - `signals`: the shared signal store
+    fn create_new(provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        Self::with_config(provider_uri, signals, config)
+    }
+
+    /// Starts a data adapter
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        info!("Started a SovdDataAdapter for {}", self.provider_uri);
+
+        Ok(())
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        let data_path = match self.data_paths.lock().unwrap().get(entity_id).cloned() {
+            Some(data_path) => data_path,
+            None => {
+                let message =
+                    format!("Entity {entity_id} does not have a data identifier registered");
+                info!("{message}");
+                return Err(DataAdapterError::unknown(message));
+            }
+        };
+
+        let agent = self.agent.clone();
+        let url = format!("{}/{data_path}", self.provider_uri);
+        let headers = self.config.headers.clone();
+
+        let body = tokio::task::spawn_blocking(move || read_data(&agent, &url, &headers))
+            .await
+            .map_err(DataAdapterError::unknown)??;
+        let value = extract_value(&body)?;
+
+        if self
+            .signals
+            .set_value(entity_id.to_string(), value)
+            .is_none()
+        {
+            warn!("Read a value for unknown entity {entity_id}");
+        }
+
+        Ok(())
+    }
+
+    /// Registers an entity id to a local cache inside a data adapter to keep track of which entities a data adapter contains.
+    /// The endpoint's context identifies the data to read, such as `engine/rpm`.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        if !endpoint.supports_operation(&Operation::Get) {
+            return Err(DataAdapterErrorKind::OperationNotSupported.into());
+        }
+
+        let data_path = data_path(&endpoint.context)?;
+        self.data_paths
+            .lock()
+            .unwrap()
+            .insert(String::from(entity_id), data_path);
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+/// Gets the path of the data resource identified by an endpoint's context, relative to the server's base uri.
+/// The context is `{entity}/{data_id}` for the data of a component, or `{collection}/{entity}/{data_id}`
+/// for the data of an entity in another collection such as `apps`.
+///
+/// # Arguments
+/// - `context`: the endpoint's context
+fn data_path(context: &str) -> Result<String, DataAdapterError> {
+    let segments: Vec<&str> = context.trim().trim_matches('/').split('/').collect();
+    let (collection, entity, data_id) = match segments.as_slice() {
+        [entity, data_id] => (ENTITY_COLLECTIONS[0], *entity, *data_id),
+        [collection, entity, data_id] if ENTITY_COLLECTIONS.contains(collection) => {
+            (*collection, *entity, *data_id)
+        }
+        _ => {
+            return Err(DataAdapterError::parse(format!(
+                "Invalid SOVD data context {context:?}. Expected a context such as engine/rpm or apps/climate/cabin-temperature"
+            )));
+        }
+    };
+
+    let is_valid_id = |id: &str| {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    if !is_valid_id(entity) || !is_valid_id(data_id) {
+        return Err(DataAdapterError::parse(format!(
+            "Invalid SOVD data context {context:?}. Ids can only contain letters, digits, '-', '_', and '.'"
+        )));
+    }
+
+    Ok(format!("{collection}/{entity}/data/{data_id}"))
+}
+
+/// Sends a blocking request for a data resource and returns the response body.
+/// Error responses are returned as errors with the message from the server.
+///
+/// # Arguments
+/// - `agent`: the client to use
+/// - `url`: the url of the data resource
+/// - `headers`: the headers to add to the request
+fn read_data(
+    agent: &Agent,
+    url: &str,
+    headers: &HashMap<String, String>,
+) -> Result<String, DataAdapterError> {
+    let mut request = agent.get(url).set("Accept", "application/json");
+    for (name, value) in headers.iter() {
+        request = request.set(name, value);
+    }
+
+    match request.call() {
+        Ok(response) => response.into_string().map_err(DataAdapterError::io),
+        Err(ureq::Error::Status(status, response)) => {
+            let message = response
+                .into_string()
+                .ok()
+                .and_then(|body| serde_json::from_str::<Value>(&body).ok())
+                .and_then(|body| {
+                    body.get("message")
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                })
+                .unwrap_or_default();
+            let message = format!("The SOVD server returned status {status} for {url}: {message}");
+
+            if status == 404 {
+                Err(DataAdapterError::entity_not_found(message))
+            } else {
+                Err(DataAdapterError::communication(message))
+            }
+        }
+        Err(e) => Err(DataAdapterError::communication(e)),
+    }
+}
+
+/// Extracts the value from the body of a response to a data read, such as `{ "id": "rpm", "data": 800 }`.
+/// Strings are returned without quotes, and objects and arrays are returned as JSON.
+///
+/// # Arguments
+/// - `body`: the response body
+fn extract_value(body: &str) -> Result<String, DataAdapterError> {
+    let body: Value = serde_json::from_str(body).map_err(DataAdapterError::deserialize)?;
+    match body.get("data") {
+        Some(Value::String(value)) => Ok(value.clone()),
+        Some(Value::Null) | None => Err(DataAdapterError::deserialize(
+            "The SOVD server's response doesn't have any data",
+        )),
+        Some(value) => Ok(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod sovd_data_adapter_tests {
+    use super::*;
+
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use freyja_common::{entity::Protocol, signal::SignalPatch};
+
+    const ENTITY_ID: &str = "dtmi:sdv:Vehicle:Powertrain:Engine:Rpm;1";
+
+    fn create_adapter(provider_uri: &str) -> SovdDataAdapter {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ENTITY_ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        SovdDataAdapter::with_config(
+            provider_uri,
+            signals,
+            Config {
+                headers: HashMap::from([(
+                    String::from("Authorization"),
+                    String::from("Bearer token"),
+                )]),
+                request_timeout_ms: 5000,
+            },
+        )
+        .unwrap()
+    }
+
+    fn create_endpoint(operation: Operation, context: &str) -> EntityEndpoint {
+        EntityEndpoint {
+            protocol: Protocol::Sovd.to_string(),
+            operations: vec![operation.to_string()],
+            uri: String::from("https://gateway.example.com/sovd/v1"),
+            context: context.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Starts a server which responds to one request with a status and a body.
+    /// Returns the server's base uri and a handle which returns the request that the server received.
+    ///
+    /// # Arguments
+    /// - `status`: the status line of the response, such as `200 OK`
+    /// - `body`: the body of the response
+    fn start_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}/sovd/v1", listener.local_addr().unwrap()); // Devskim: ignore DS137138
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![0; 4096];
+            let length = stream.read(&mut request).unwrap();
+
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+
+            String::from_utf8_lossy(&request[..length]).to_string()
+        });
+
+        (uri, handle)
+    }
+
+    #[test]
+    fn data_path_supports_components_and_other_collections() {
+        assert_eq!(
+            data_path("engine/rpm").unwrap(),
+            "components/engine/data/rpm"
+        );
+        assert_eq!(
+            data_path("/apps/climate/cabin-temperature").unwrap(),
+            "apps/climate/data/cabin-temperature"
+        );
+
+        for context in [
+            "rpm",
+            "vehicles/engine/rpm",
+            "engine/rpm?x=1",
+            "engine//rpm",
+        ] {
+            assert!(data_path(context).is_err_and(|e| e.kind() == DataAdapterErrorKind::Parse));
+        }
+    }
+
+    #[test]
+    fn extract_value_returns_data() {
+        assert_eq!(extract_value(r#"{"id":"rpm","data":800}"#).unwrap(), "800");
+        assert_eq!(
+            extract_value(r#"{"id":"vin","data":"WVW"}"#).unwrap(),
+            "WVW"
+        );
+        assert_eq!(
+            extract_value(r#"{"id":"tires","data":{"front":2.4}}"#).unwrap(),
+            r#"{"front":2.4}"#
+        );
+        assert!(extract_value(r#"{"id":"rpm"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn register_entity_fails_without_get_operation() {
+        let adapter = create_adapter("https://gateway.example.com/sovd/v1");
+        let result = adapter
+            .register_entity(
+                ENTITY_ID,
+                &create_endpoint(Operation::Subscribe, "engine/rpm"),
+            )
+            .await;
+
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::OperationNotSupported));
+        assert!(adapter.data_paths.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_request_to_provider_reads_data_into_signal_store() {
+        let (uri, server) = start_server("200 OK", r#"{"id":"rpm","data":800}"#);
+        let adapter = create_adapter(&uri);
+        adapter
+            .register_entity(ENTITY_ID, &create_endpoint(Operation::Get, "engine/rpm"))
+            .await
+            .unwrap();
+
+        adapter.send_request_to_provider(ENTITY_ID).await.unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /sovd/v1/components/engine/data/rpm HTTP/1.1"));
+        assert!(request.contains("Bearer token"));
+
+        let signal = adapter.signals.get(&ENTITY_ID.to_string()).unwrap();
+        assert_eq!(signal.value.as_deref(), Some("800"));
+    }
+
+    #[tokio::test]
+    async fn send_request_to_provider_returns_err_for_missing_data() {
+        let (uri, server) = start_server(
+            "404 Not Found",
+            r#"{"error_code":"not-found","message":"Unknown data id"}"#,
+        );
+        let adapter = create_adapter(&uri);
+        adapter
+            .register_entity(ENTITY_ID, &create_endpoint(Operation::Get, "engine/rpm"))
+            .await
+            .unwrap();
+
+        let result = adapter.send_request_to_provider(ENTITY_ID).await;
+        server.join().unwrap();
+
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::EntityNotFound));
+    }
+}
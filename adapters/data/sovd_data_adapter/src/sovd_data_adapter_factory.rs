@@ -0,0 +1,19 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use freyja_common::{
+    data_adapter::DataAdapterFactory,
+    entity::{Operation, Protocol},
+};
+
+use crate::sovd_data_adapter::SovdDataAdapter;
+
+/// Factory for creating SovdDataAdapters
+#[derive(DataAdapterFactory)]
+#[data_adapter_factory(
+    adapter = SovdDataAdapter,
+    protocols = [Protocol::Sovd],
+    operations = [Operation::Get]
+)]
+pub struct SovdDataAdapterFactory {}
@@ -0,0 +1,21 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use freyja_common::protobuf_decoder::ProtobufDecoderConfig;
+use serde::{Deserialize, Serialize};
+
+/// The uProtocol Data Adapter config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The keep alive interval for the MQTT transport in seconds
+    pub keep_alive_interval_s: u64,
+
+    /// The decoders for topics whose payloads are protobuf messages,
+    /// keyed by the uProtocol URI of the topic as it appears in the endpoint context.
+    /// Payloads on other topics are parsed as JSON or raw values
+    #[serde(default)]
+    pub protobuf_decoders: HashMap<String, ProtobufDecoderConfig>,
+}
@@ -0,0 +1,10 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+mod config;
+mod mqtt_transport;
+pub mod uprotocol_data_adapter;
+pub mod uprotocol_data_adapter_factory;
+pub mod utransport;
+pub mod uuri;
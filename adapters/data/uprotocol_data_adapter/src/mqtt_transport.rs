@@ -0,0 +1,235 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::{error, info, warn};
+use paho_mqtt::{Client, QOS_1};
+use uuid::Uuid;
+
+use crate::{
+    utransport::{UListener, UTransport},
+    uuri::UUri,
+};
+use freyja_common::{
+    data_adapter::DataAdapterError,
+    uri::{EndpointUri, UriKind},
+};
+
+const MQTT_CLIENT_ID_PREFIX: &str = "freyja-uprotocol-data-adapter";
+
+type ListenerMap = HashMap<String, Vec<Arc<dyn UListener>>>;
+
+/// A uProtocol transport over MQTT 5.
+/// Messages published to a topic are expected on MQTT topics beginning with
+/// `{authority_name}/{ue_id}/{ue_version_major}/{resource_id}` in hexadecimal,
+/// and the MQTT payload is the uProtocol message payload.
+pub struct MqttTransport {
+    /// The keep alive interval for the connection
+    keep_alive_interval: Duration,
+
+    /// The MQTT client
+    client: Arc<Mutex<Client>>,
+
+    /// Maps MQTT topic prefixes to the listeners registered for them
+    listeners: Arc<Mutex<ListenerMap>>,
+}
+
+impl MqttTransport {
+    /// Creates a new transport. The transport does not connect until `connect` is called.
+    ///
+    /// # Arguments
+    /// - `broker_uri`: the URI of the MQTT broker
+    /// - `keep_alive_interval`: the keep alive interval for the connection
+    pub fn new(broker_uri: &str, keep_alive_interval: Duration) -> Result<Self, DataAdapterError> {
+        let broker_uri =
+            EndpointUri::parse(broker_uri, UriKind::Mqtt).map_err(DataAdapterError::parse)?;
+        let client_id = format!("{MQTT_CLIENT_ID_PREFIX}_{}", Uuid::new_v4());
+        let create_options = paho_mqtt::CreateOptionsBuilder::new()
+            .server_uri(broker_uri.to_string())
+            .client_id(client_id)
+            .finalize();
+
+        let client = Client::new(create_options).map_err(DataAdapterError::communication)?;
+
+        Ok(Self {
+            keep_alive_interval,
+            client: Arc::new(Mutex::new(client)),
+            listeners: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+}
+
+#[async_trait]
+impl UTransport for MqttTransport {
+    /// Connects the transport and starts the thread which dispatches incoming messages
+    async fn connect(&self) -> Result<(), DataAdapterError> {
+        let connection_options = paho_mqtt::ConnectOptionsBuilder::new_v5()
+            .keep_alive_interval(self.keep_alive_interval)
+            .finalize();
+
+        let receiver = {
+            let client = self.client.lock().unwrap();
+            let receiver = client.start_consuming();
+            client
+                .connect(connection_options)
+                .map_err(DataAdapterError::communication)?;
+            receiver
+        };
+
+        let client = self.client.clone();
+        let listeners = self.listeners.clone();
+
+        // The receiver blocks, so it gets a dedicated thread
+        tokio::task::spawn_blocking(move || {
+            info!("Started uProtocol MQTT listener");
+            for msg in receiver.iter() {
+                match msg {
+                    Some(m) => dispatch(&listeners.lock().unwrap(), m.topic(), m.payload()),
+                    None => {
+                        let client = client.lock().unwrap();
+                        if client.is_connected() {
+                            continue;
+                        }
+
+                        if let Err(e) = client.reconnect() {
+                            error!("Fatal error trying to reconnect to MQTT broker: {e}");
+                            break;
+                        }
+
+                        for prefix in listeners.lock().unwrap().keys() {
+                            if let Err(e) = client.subscribe(&to_topic_filter(prefix), QOS_1) {
+                                error!("Error resubscribing to topic {prefix}: {e}");
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Registers a listener for messages published to a topic
+    ///
+    /// # Arguments
+    /// - `topic`: the topic to listen to
+    /// - `listener`: the listener to call when a message is received
+    async fn register_listener(
+        &self,
+        topic: &UUri,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), DataAdapterError> {
+        let prefix = to_topic_prefix(topic);
+        let mut listeners = self.listeners.lock().unwrap();
+
+        if !listeners.contains_key(&prefix) {
+            self.client
+                .lock()
+                .unwrap()
+                .subscribe(&to_topic_filter(&prefix), QOS_1)
+                .map_err(DataAdapterError::communication)?;
+        }
+
+        listeners.entry(prefix).or_default().push(listener);
+
+        Ok(())
+    }
+}
+
+/// Gets the MQTT topic prefix for messages published to a uProtocol topic
+///
+/// # Arguments
+/// - `topic`: the uProtocol topic
+fn to_topic_prefix(topic: &UUri) -> String {
+    format!(
+        "{}/{:X}/{:X}/{:X}",
+        topic.authority_name, topic.ue_id, topic.ue_version_major, topic.resource_id
+    )
+}
+
+/// Gets the MQTT topic filter that matches a topic prefix and any topics below it
+///
+/// # Arguments
+/// - `prefix`: the topic prefix
+fn to_topic_filter(prefix: &str) -> String {
+    format!("{prefix}/#")
+}
+
+/// Calls the listeners whose topic prefix matches an incoming message
+///
+/// # Arguments
+/// - `listeners`: the registered listeners
+/// - `topic`: the MQTT topic of the message
+/// - `payload`: the message payload
+fn dispatch(listeners: &ListenerMap, topic: &str, payload: &[u8]) {
+    let mut delivered = false;
+    for (prefix, topic_listeners) in listeners.iter() {
+        let matches = topic
+            .strip_prefix(prefix.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+
+        if matches {
+            delivered = true;
+            for listener in topic_listeners.iter() {
+                listener.on_receive(payload);
+            }
+        }
+    }
+
+    if !delivered {
+        warn!("Received a message on topic {topic} with no registered listeners");
+    }
+}
+
+#[cfg(test)]
+mod mqtt_transport_tests {
+    use super::*;
+
+    struct CountingListener {
+        count: Mutex<usize>,
+    }
+
+    impl UListener for CountingListener {
+        fn on_receive(&self, _payload: &[u8]) {
+            *self.count.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn to_topic_prefix_uses_hex_segments() {
+        let topic: UUri = "//vehicle/10AB/1/8001".parse().unwrap();
+
+        assert_eq!(to_topic_prefix(&topic), "vehicle/10AB/1/8001");
+        assert_eq!(
+            to_topic_filter("vehicle/10AB/1/8001"),
+            "vehicle/10AB/1/8001/#"
+        );
+    }
+
+    #[test]
+    fn dispatch_matches_topic_prefix() {
+        let listener = Arc::new(CountingListener {
+            count: Mutex::new(0),
+        });
+
+        let mut listeners: ListenerMap = HashMap::new();
+        listeners.insert(
+            "vehicle/10AB/1/8001".to_string(),
+            vec![listener.clone() as Arc<dyn UListener>],
+        );
+
+        dispatch(&listeners, "vehicle/10AB/1/8001", b"42");
+        dispatch(&listeners, "vehicle/10AB/1/8001/cloud/0/0/0", b"42");
+        dispatch(&listeners, "vehicle/10AB/1/80011", b"42");
+        dispatch(&listeners, "vehicle/10AB/1/8002", b"42");
+
+        assert_eq!(*listener.count.lock().unwrap(), 2);
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::{debug, info, warn};
+
+use crate::{
+    config::Config,
+    mqtt_transport::MqttTransport,
+    utransport::{UListener, UTransport},
+    uuri::UUri,
+};
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterErrorKind, EntityRegistration},
+    entity::{EntityEndpoint, Operation, Protocol},
+    message_utils, out_dir,
+    protobuf_decoder::ProtobufDecoder,
+    signal_store::SignalStore,
+};
+
+/// Interfaces with uServices which publish to uProtocol topics.
+/// The topic for an entity is the uProtocol URI in the endpoint context.
+pub struct UProtocolDataAdapter {
+    /// The transport used to receive published messages
+    transport: Arc<dyn UTransport>,
+
+    /// Maps entity ids to the topics they are subscribed to
+    subscriptions: Mutex<HashMap<String, UUri>>,
+
+    /// The decoders for topics whose payloads are protobuf messages, keyed by the topic's URI
+    decoders: HashMap<String, Arc<ProtobufDecoder>>,
+
+    /// Shared signal store for all data adapters to push new signal values
+    signals: Arc<SignalStore>,
+}
+
+impl UProtocolDataAdapter {
+    /// Creates a data adapter which uses the provided transport
+    ///
+    /// # Arguments
+    /// - `transport`: the transport used to receive published messages
+    /// - `signals`: the shared signal store
+    pub fn with_transport(transport: Arc<dyn UTransport>, signals: Arc<SignalStore>) -> Self {
+        Self {
+            transport,
+            subscriptions: Mutex::new(HashMap::new()),
+            decoders: HashMap::new(),
+            signals,
+        }
+    }
+}
+
+#[async_trait]
+impl DataAdapter for UProtocolDataAdapter {
+    /// Creates a data adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the URI of the transport's MQTT broker
+    /// - `signals`: the shared signal store
+    fn create_new(provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        let decoders = ProtobufDecoder::from_configs(&config.protobuf_decoders)
+            .map_err(DataAdapterError::deserialize)?
+            .into_iter()
+            .map(|(topic, decoder)| (topic, Arc::new(decoder)))
+            .collect();
+
+        let transport = MqttTransport::new(
+            provider_uri,
+            Duration::from_secs(config.keep_alive_interval_s),
+        )?;
+
+        Ok(Self {
+            decoders,
+            ..Self::with_transport(Arc::new(transport), signals)
+        })
+    }
+
+    /// Starts a data adapter
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        self.transport.connect().await?;
+
+        info!("Started a UProtocolDataAdapter!");
+
+        Ok(())
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, _entity_id: &str) -> Result<(), DataAdapterError> {
+        // No actions for this provider when calling this function since values are published to topics
+        Ok(())
+    }
+
+    /// Registers an entity id to a local cache inside a data adapter to keep track of which entities a data adapter contains.
+    /// If the operation is Subscribe for an entity, the expectation is subscribe will happen in this function after registering an entity.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        // Verify that the endpoint has the expected data.
+        // This shouldn't be necessary since it's first verified by the factory,
+        // but this ensures we don't get hit by an edge case
+        if endpoint.protocol() != Protocol::UProtocol
+            || !endpoint.supports_operation(&Operation::Subscribe)
+        {
+            return Err(DataAdapterErrorKind::OperationNotSupported.into());
+        }
+
+        // Topic comes from the endpoint context
+        let topic: UUri = endpoint.context.parse()?;
+        if !topic.is_topic() {
+            return Err(DataAdapterError::parse(format!(
+                "URI {topic} for entity {entity_id} is not a topic"
+            )));
+        }
+
+        if self.subscriptions.lock().unwrap().get(entity_id) == Some(&topic) {
+            debug!("Entity {entity_id} is already subscribed to topic {topic}");
+            return Ok(EntityRegistration::Registered);
+        }
+
+        debug!("Subscribing to topic {topic}");
+
+        let listener = Arc::new(SignalListener {
+            entity_id: entity_id.to_string(),
+            decoder: self.decoders.get(&endpoint.context).cloned(),
+            signals: self.signals.clone(),
+        });
+
+        self.transport.register_listener(&topic, listener).await?;
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .insert(entity_id.to_string(), topic);
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+/// Writes messages published to a topic to the signal store
+struct SignalListener {
+    /// The entity that the topic provides values for
+    entity_id: String,
+
+    /// The decoder for the topic's payloads if they're protobuf messages
+    decoder: Option<Arc<ProtobufDecoder>>,
+
+    /// Shared signal store for all data adapters to push new signal values
+    signals: Arc<SignalStore>,
+}
+
+impl UListener for SignalListener {
+    /// Parses or decodes the payload as a value and writes it to the signal store
+    ///
+    /// # Arguments
+    /// - `payload`: the message payload
+    fn on_receive(&self, payload: &[u8]) {
+        let value = match &self.decoder {
+            Some(decoder) => match decoder.decode(payload) {
+                Ok(value) => value,
+                Err(e) => {
+                    warn!(
+                        "Could not decode payload for entity {}: {e}",
+                        self.entity_id
+                    );
+                    return;
+                }
+            },
+            None => message_utils::parse_value(String::from_utf8_lossy(payload).into_owned()),
+        };
+        if self
+            .signals
+            .set_value(self.entity_id.clone(), value)
+            .is_none()
+        {
+            warn!(
+                "Attempt to set value for non-existent signal {}",
+                self.entity_id
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod uprotocol_data_adapter_tests {
+    use super::*;
+
+    use freyja_common::signal::SignalPatch;
+
+    const ENTITY_ID: &str = "entity_id";
+    const TOPIC: &str = "//vehicle/10AB/1/8001";
+
+    /// A transport which records registered listeners
+    #[derive(Default)]
+    struct MockTransport {
+        listeners: Mutex<Vec<(UUri, Arc<dyn UListener>)>>,
+    }
+
+    #[async_trait]
+    impl UTransport for MockTransport {
+        async fn connect(&self) -> Result<(), DataAdapterError> {
+            Ok(())
+        }
+
+        async fn register_listener(
+            &self,
+            topic: &UUri,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), DataAdapterError> {
+            self.listeners
+                .lock()
+                .unwrap()
+                .push((topic.clone(), listener));
+            Ok(())
+        }
+    }
+
+    fn create_adapter() -> (UProtocolDataAdapter, Arc<MockTransport>) {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ENTITY_ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        let transport = Arc::new(MockTransport::default());
+        let adapter = UProtocolDataAdapter::with_transport(transport.clone(), signals);
+
+        (adapter, transport)
+    }
+
+    fn create_endpoint(context: &str) -> EntityEndpoint {
+        EntityEndpoint {
+            protocol: Protocol::UProtocol.to_string(),
+            operations: vec![Operation::Subscribe.to_string()],
+            uri: "tcp://localhost:1883".to_string(),
+            context: context.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn register_entity_sets_values_from_topic() {
+        let (adapter, transport) = create_adapter();
+
+        let result = adapter
+            .register_entity(ENTITY_ID, &create_endpoint(TOPIC))
+            .await;
+        assert!(result.is_ok());
+
+        let listeners = transport.listeners.lock().unwrap();
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0].0.to_string(), TOPIC);
+
+        listeners[0].1.on_receive(b"42");
+        let signal = adapter.signals.get(&ENTITY_ID.to_string()).unwrap();
+        assert_eq!(signal.value, Some("42".into()));
+    }
+
+    #[tokio::test]
+    async fn register_entity_twice_registers_one_listener() {
+        let (adapter, transport) = create_adapter();
+
+        for _ in 0..2 {
+            let result = adapter
+                .register_entity(ENTITY_ID, &create_endpoint(TOPIC))
+                .await;
+            assert!(result.is_ok());
+        }
+
+        assert_eq!(transport.listeners.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn register_entity_fails_for_invalid_topic() {
+        let (adapter, transport) = create_adapter();
+
+        for context in ["not a uri", "//vehicle/10AB/1/1"] {
+            let result = adapter
+                .register_entity(ENTITY_ID, &create_endpoint(context))
+                .await;
+            assert!(result.is_err());
+        }
+
+        assert!(transport.listeners.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn register_entity_fails_for_unsupported_operation() {
+        let (adapter, _) = create_adapter();
+        let mut endpoint = create_endpoint(TOPIC);
+        endpoint.operations = vec!["Get".to_string()];
+
+        let result = adapter.register_entity(ENTITY_ID, &endpoint).await;
+        assert!(result.is_err());
+    }
+}
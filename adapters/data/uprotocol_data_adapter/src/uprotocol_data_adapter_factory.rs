@@ -0,0 +1,19 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use freyja_common::{
+    data_adapter::DataAdapterFactory,
+    entity::{Operation, Protocol},
+};
+
+use crate::uprotocol_data_adapter::UProtocolDataAdapter;
+
+/// Factory for creating UProtocolDataAdapters
+#[derive(DataAdapterFactory)]
+#[data_adapter_factory(
+    adapter = UProtocolDataAdapter,
+    protocols = [Protocol::UProtocol],
+    operations = [Operation::Subscribe]
+)]
+pub struct UProtocolDataAdapterFactory {}
@@ -0,0 +1,39 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::uuri::UUri;
+use freyja_common::data_adapter::DataAdapterError;
+
+/// Receives messages published to a topic
+pub trait UListener: Send + Sync {
+    /// Called when a message is received
+    ///
+    /// # Arguments
+    /// - `payload`: the message payload
+    fn on_receive(&self, payload: &[u8]);
+}
+
+/// A uProtocol transport which can deliver messages published to topics
+#[async_trait]
+pub trait UTransport: Send + Sync {
+    /// Connects the transport.
+    /// This should not block once the connection is established,
+    /// so anything that needs to run indefinitely should spawn its own task.
+    async fn connect(&self) -> Result<(), DataAdapterError>;
+
+    /// Registers a listener for messages published to a topic
+    ///
+    /// # Arguments
+    /// - `topic`: the topic to listen to
+    /// - `listener`: the listener to call when a message is received
+    async fn register_listener(
+        &self,
+        topic: &UUri,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), DataAdapterError>;
+}
@@ -0,0 +1,132 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{fmt, str::FromStr};
+
+use freyja_common::data_adapter::DataAdapterError;
+
+/// The optional scheme for uProtocol URIs
+const UPROTOCOL_SCHEME: &str = "up:";
+
+/// The smallest resource id that identifies a topic. Lower resource ids identify RPC methods.
+const MIN_TOPIC_RESOURCE_ID: u16 = 0x8000;
+
+/// A uProtocol URI which identifies a resource of a uEntity, such as a published topic.
+/// The string form is `//{authority_name}/{ue_id}/{ue_version_major}/{resource_id}`,
+/// where the numeric segments are hexadecimal. An `up:` scheme prefix is also accepted.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UUri {
+    /// The authority, typically the name of the device hosting the uEntity
+    pub authority_name: String,
+
+    /// The uEntity id
+    pub ue_id: u32,
+
+    /// The major version of the uEntity
+    pub ue_version_major: u8,
+
+    /// The resource id
+    pub resource_id: u16,
+}
+
+impl UUri {
+    /// Returns true if this URI identifies a topic that can be subscribed to
+    pub fn is_topic(&self) -> bool {
+        self.resource_id >= MIN_TOPIC_RESOURCE_ID
+    }
+}
+
+impl FromStr for UUri {
+    type Err = DataAdapterError;
+
+    /// Parses a uProtocol URI from its string form
+    ///
+    /// # Arguments
+    /// - `s`: the string to parse
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix(UPROTOCOL_SCHEME).unwrap_or(s);
+        let path = s
+            .strip_prefix("//")
+            .ok_or_else(|| DataAdapterError::parse(format!("URI {s} has no authority")))?;
+
+        let segments: Vec<&str> = path.split('/').collect();
+        if segments.len() != 4 || segments[0].is_empty() {
+            return Err(DataAdapterError::parse(format!(
+                "URI {s} must have an authority, uEntity id, version, and resource id"
+            )));
+        }
+
+        Ok(Self {
+            authority_name: segments[0].to_string(),
+            ue_id: u32::from_str_radix(segments[1], 16).map_err(DataAdapterError::parse)?,
+            ue_version_major: u8::from_str_radix(segments[2], 16)
+                .map_err(DataAdapterError::parse)?,
+            resource_id: u16::from_str_radix(segments[3], 16).map_err(DataAdapterError::parse)?,
+        })
+    }
+}
+
+impl fmt::Display for UUri {
+    /// Formats the URI in its string form without a scheme
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "//{}/{:X}/{:X}/{:X}",
+            self.authority_name, self.ue_id, self.ue_version_major, self.resource_id
+        )
+    }
+}
+
+#[cfg(test)]
+mod uuri_tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_uri() {
+        let uri: UUri = "//vehicle/10AB/1/8001".parse().unwrap();
+
+        assert_eq!(uri.authority_name, "vehicle");
+        assert_eq!(uri.ue_id, 0x10AB);
+        assert_eq!(uri.ue_version_major, 1);
+        assert_eq!(uri.resource_id, 0x8001);
+        assert!(uri.is_topic());
+    }
+
+    #[test]
+    fn can_parse_uri_with_scheme() {
+        let uri: UUri = "up://vehicle/10ab/1/8001".parse().unwrap();
+
+        assert_eq!(uri.ue_id, 0x10AB);
+    }
+
+    #[test]
+    fn to_string_round_trips() {
+        let input = "//vehicle/10AB/1/8001";
+        let uri: UUri = input.parse().unwrap();
+
+        assert_eq!(uri.to_string(), input);
+    }
+
+    #[test]
+    fn method_uri_is_not_topic() {
+        let uri: UUri = "//vehicle/10AB/1/1".parse().unwrap();
+
+        assert!(!uri.is_topic());
+    }
+
+    #[test]
+    fn parse_fails_for_invalid_uris() {
+        for input in [
+            "vehicle/10AB/1/8001",
+            "///10AB/1/8001",
+            "//vehicle/10AB/1",
+            "//vehicle/10AB/1/8001/extra",
+            "//vehicle/XYZ/1/8001",
+            "//vehicle/10AB/100/8001",
+            "//vehicle/10AB/1/10000",
+        ] {
+            assert!(input.parse::<UUri>().is_err(), "{input} should not parse");
+        }
+    }
+}
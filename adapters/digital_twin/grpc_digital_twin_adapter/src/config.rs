@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use freyja_common::grpc_utils::GrpcClientConfig;
+
 /// Configuration for the Ibeji Adapter.
 /// Supports two different schemas based on the service discovery method.
 #[derive(Clone, Serialize, Deserialize)]
@@ -16,4 +18,35 @@ pub struct Config {
 
     /// The duration between retries in milliseconds
     pub retry_interval_ms: u64,
+
+    /// A URI to fall back to if none of the discovered URIs accept a connection
+    #[serde(default)]
+    pub static_uri: Option<String>,
+
+    /// The interval in milliseconds at which the service is rediscovered.
+    /// If this is `None`, the service is only rediscovered when it becomes unavailable
+    #[serde(default)]
+    pub rediscovery_interval_ms: Option<u64>,
+
+    /// How to handle a service which doesn't implement the interface that this adapter expects
+    #[serde(default)]
+    pub contract_check: ContractCheckMode,
+
+    /// The headers, bearer token, and deadline of requests sent to the service
+    #[serde(flatten)]
+    pub grpc: GrpcClientConfig,
+}
+
+/// How the adapter checks the interface of the service when it starts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractCheckMode {
+    /// The interface isn't checked
+    Disabled,
+
+    /// A mismatched interface is logged, but the adapter is still created
+    #[default]
+    Warn,
+
+    /// A mismatched interface causes the creation of the adapter to fail
+    Enforce,
 }
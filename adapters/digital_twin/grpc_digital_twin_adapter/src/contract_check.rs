@@ -0,0 +1,290 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use prost::Message;
+use prost_types::FileDescriptorProto;
+use tonic::{
+    transport::{Channel, Endpoint},
+    Code, Status,
+};
+use tonic_reflection::pb::{
+    server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
+
+/// The fully qualified name of the service that this adapter calls, including its interface version
+const EXPECTED_SERVICE: &str = "invehicle_digital_twin.v1.InvehicleDigitalTwin";
+
+/// The methods of the expected service that this adapter calls
+const EXPECTED_METHODS: &[&str] = &["FindById"];
+
+/// The time limit for the check if no request timeout is configured, so that an unresponsive service can't block startup
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The result of checking whether a service implements the interface that this adapter expects
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ContractCheck {
+    /// The service implements the expected interface
+    Verified,
+
+    /// The interface couldn't be checked, such as when the service doesn't support reflection.
+    /// This contains the reason
+    Unverifiable(String),
+
+    /// The service doesn't implement the expected interface. This contains a diagnostic describing the mismatch
+    Mismatch(String),
+}
+
+/// Checks whether the service at a URI implements the interface that this adapter expects using gRPC server reflection.
+/// The interface can't be verified if the check doesn't finish within the timeout.
+///
+/// # Arguments
+/// - `uri`: the URI of the service
+/// - `timeout`: the time limit for the check, including connecting to the service
+pub(crate) async fn check_contract(uri: String, timeout: Duration) -> ContractCheck {
+    tokio::time::timeout(timeout, check_contract_without_timeout(uri))
+        .await
+        .unwrap_or_else(|_| {
+            ContractCheck::Unverifiable(format!(
+                "The reflection service did not respond within {timeout:?}"
+            ))
+        })
+}
+
+/// Checks whether the service at a URI implements the interface that this adapter expects using gRPC server reflection
+///
+/// # Arguments
+/// - `uri`: the URI of the service
+async fn check_contract_without_timeout(uri: String) -> ContractCheck {
+    let channel = match Endpoint::from_shared(uri.clone()) {
+        Ok(endpoint) => endpoint.connect().await,
+        Err(e) => Err(e),
+    };
+    let mut client = match channel {
+        Ok(channel) => ServerReflectionClient::new(channel),
+        Err(e) => return ContractCheck::Unverifiable(format!("Could not connect to {uri}: {e}")),
+    };
+
+    let services = match send(&mut client, MessageRequest::ListServices(String::new())).await {
+        Ok(MessageResponse::ListServicesResponse(response)) => response
+            .service
+            .into_iter()
+            .map(|service| service.name)
+            .collect::<Vec<_>>(),
+        Ok(response) => {
+            return ContractCheck::Unverifiable(format!(
+                "Unexpected reflection response when listing services: {response:?}"
+            ))
+        }
+        Err(status) => return unverifiable(status),
+    };
+
+    if !services.iter().any(|service| service == EXPECTED_SERVICE) {
+        return ContractCheck::Mismatch(format!(
+            "The service at {uri} does not implement {EXPECTED_SERVICE}, so it's not a compatible in-vehicle digital twin service. \
+            It implements {services:?}"
+        ));
+    }
+
+    let file_descriptors = match send(
+        &mut client,
+        MessageRequest::FileContainingSymbol(EXPECTED_SERVICE.to_string()),
+    )
+    .await
+    {
+        Ok(MessageResponse::FileDescriptorResponse(response)) => response.file_descriptor_proto,
+        Ok(response) => {
+            return ContractCheck::Unverifiable(format!(
+                "Unexpected reflection response when describing {EXPECTED_SERVICE}: {response:?}"
+            ))
+        }
+        Err(status) => return unverifiable(status),
+    };
+
+    match find_missing_methods(&file_descriptors) {
+        Ok(missing) if missing.is_empty() => ContractCheck::Verified,
+        Ok(missing) => ContractCheck::Mismatch(format!(
+            "The service at {uri} implements a version of {EXPECTED_SERVICE} without the methods {missing:?}, \
+            which this adapter calls. Use a version of the in-vehicle digital twin service which implements them"
+        )),
+        Err(reason) => ContractCheck::Unverifiable(reason),
+    }
+}
+
+/// Sends a request to the reflection service and gets its response
+///
+/// # Arguments
+/// - `client`: the reflection client
+/// - `request`: the request to send
+async fn send(
+    client: &mut ServerReflectionClient<Channel>,
+    request: MessageRequest,
+) -> Result<MessageResponse, Status> {
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(request),
+    };
+
+    let mut responses = client
+        .server_reflection_info(futures::stream::iter([request]))
+        .await?
+        .into_inner();
+
+    responses
+        .message()
+        .await?
+        .and_then(|response| response.message_response)
+        .ok_or_else(|| Status::internal("The reflection service did not respond"))
+}
+
+/// Gets the reason why a contract can't be checked after the reflection service returned an error
+///
+/// # Arguments
+/// - `status`: the status returned by the reflection service
+fn unverifiable(status: Status) -> ContractCheck {
+    match status.code() {
+        Code::Unimplemented => ContractCheck::Unverifiable(String::from(
+            "The service does not support gRPC server reflection",
+        )),
+        _ => ContractCheck::Unverifiable(format!(
+            "The reflection request failed: {}",
+            status.message()
+        )),
+    }
+}
+
+/// Gets the expected methods which are missing from the expected service in serialized file descriptors.
+/// Returns an error if the descriptors can't be decoded or don't describe the expected service.
+///
+/// # Arguments
+/// - `file_descriptors`: the serialized file descriptors returned by the reflection service
+fn find_missing_methods(file_descriptors: &[Vec<u8>]) -> Result<Vec<&'static str>, String> {
+    for bytes in file_descriptors {
+        let file = FileDescriptorProto::decode(bytes.as_slice())
+            .map_err(|e| format!("Could not decode a file descriptor: {e}"))?;
+
+        let service = file.service.iter().find(|service| {
+            let name = match file.package() {
+                "" => service.name().to_string(),
+                package => format!("{package}.{}", service.name()),
+            };
+
+            name == EXPECTED_SERVICE
+        });
+
+        if let Some(service) = service {
+            return Ok(EXPECTED_METHODS
+                .iter()
+                .copied()
+                .filter(|expected| !service.method.iter().any(|m| m.name() == *expected))
+                .collect());
+        }
+    }
+
+    Err(format!(
+        "The reflection service did not describe {EXPECTED_SERVICE}"
+    ))
+}
+
+#[cfg(test)]
+mod contract_check_tests {
+    use super::*;
+
+    use prost_types::{MethodDescriptorProto, ServiceDescriptorProto};
+    use tokio::net::TcpListener;
+
+    fn create_file_descriptor(package: &str, service: &str, methods: &[&str]) -> Vec<u8> {
+        FileDescriptorProto {
+            package: Some(package.to_string()),
+            service: vec![ServiceDescriptorProto {
+                name: Some(service.to_string()),
+                method: methods
+                    .iter()
+                    .map(|method| MethodDescriptorProto {
+                        name: Some(method.to_string()),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn find_missing_methods_returns_empty_when_service_is_complete() {
+        let file_descriptors = vec![create_file_descriptor(
+            "invehicle_digital_twin.v1",
+            "InvehicleDigitalTwin",
+            &["FindById", "Register"],
+        )];
+
+        assert_eq!(find_missing_methods(&file_descriptors), Ok(vec![]));
+    }
+
+    #[test]
+    fn find_missing_methods_returns_missing_methods() {
+        let file_descriptors = vec![
+            create_file_descriptor(
+                "invehicle_digital_twin.v2",
+                "InvehicleDigitalTwin",
+                &["FindById"],
+            ),
+            create_file_descriptor(
+                "invehicle_digital_twin.v1",
+                "InvehicleDigitalTwin",
+                &["Register"],
+            ),
+        ];
+
+        assert_eq!(
+            find_missing_methods(&file_descriptors),
+            Ok(vec!["FindById"])
+        );
+    }
+
+    #[test]
+    fn find_missing_methods_returns_err_when_service_is_not_described() {
+        let file_descriptors = vec![create_file_descriptor("other", "Other", &["FindById"])];
+        assert!(find_missing_methods(&file_descriptors).is_err());
+
+        assert!(find_missing_methods(&[vec![0xff, 0xff]]).is_err());
+    }
+
+    #[tokio::test]
+    async fn check_contract_is_unverifiable_when_service_does_not_respond() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("http://{}", listener.local_addr().unwrap()); // Devskim: ignore DS137138
+
+        // Accepts connections but never responds on them
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+
+        let result = check_contract(uri, Duration::from_millis(100)).await;
+        assert_eq!(
+            result,
+            ContractCheck::Unverifiable(String::from(
+                "The reflection service did not respond within 100ms"
+            ))
+        );
+    }
+
+    #[test]
+    fn unverifiable_explains_missing_reflection_support() {
+        let result = unverifiable(Status::unimplemented("unknown service"));
+        assert_eq!(
+            result,
+            ContractCheck::Unverifiable(String::from(
+                "The service does not support gRPC server reflection"
+            ))
+        );
+    }
+}
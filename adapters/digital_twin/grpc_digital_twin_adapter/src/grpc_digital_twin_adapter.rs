@@ -5,15 +5,19 @@
 use std::{sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use tokio::sync::Mutex;
-use tonic::transport::Channel;
+use log::{error, info, warn};
+use tokio::{sync::Mutex, time::Instant};
+use tonic::Code;
 
 use core_protobuf_data_access::invehicle_digital_twin::v1::{
     invehicle_digital_twin_client::InvehicleDigitalTwinClient,
     FindByIdRequest as IbejiFindByIdRequest,
 };
 
-use crate::config::Config;
+use crate::{
+    config::{Config, ContractCheckMode},
+    contract_check::{self, ContractCheck},
+};
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     config_utils,
@@ -21,14 +25,210 @@ use freyja_common::{
         DigitalTwinAdapter, DigitalTwinAdapterError, FindByIdRequest, FindByIdResponse,
     },
     entity::{Entity, EntityEndpoint},
+    grpc_utils::{self, GrpcChannel, GrpcInterceptor, GrpcRequestContext},
     out_dir,
-    retry_utils::execute_with_retry,
+    retry_utils::RetryPolicy,
     service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
 };
 
+/// A connection to an In-Vehicle Digital Twin Service
+struct Connection {
+    /// The URI of the service
+    uri: String,
+
+    /// The client for the service
+    client: InvehicleDigitalTwinClient<GrpcChannel>,
+
+    /// The time at which the URI was discovered
+    discovered_at: Instant,
+}
+
 /// Communicates with an In-Vehicle Digital Twin Service
 pub struct GRPCDigitalTwinAdapter {
-    client: InvehicleDigitalTwinClient<Channel>,
+    /// The current connection to the service
+    connection: Mutex<Connection>,
+
+    /// The service discovery adapter selector, used for rediscovering the service
+    selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+
+    /// The adapter config
+    config: Config,
+}
+
+impl GRPCDigitalTwinAdapter {
+    /// Gets the URIs to try when connecting to the service.
+    /// These are the discovered URIs in priority order followed by the configured static URI, if any.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector
+    /// - `config`: the adapter config
+    async fn get_candidate_uris(
+        selector: &Mutex<dyn ServiceDiscoveryAdapterSelector>,
+        config: &Config,
+    ) -> Vec<String> {
+        let discovered_uris = {
+            let selector = selector.lock().await;
+            selector
+                .get_service_uri_candidates(&config.service_discovery_id)
+                .await
+        };
+
+        let mut uris = discovered_uris.unwrap_or_else(|e| {
+            warn!(
+                "Failed to discover the digital twin service {}: {e}",
+                config.service_discovery_id
+            );
+            Vec::new()
+        });
+
+        if let Some(static_uri) = &config.static_uri {
+            if !uris.contains(static_uri) {
+                uris.push(static_uri.clone());
+            }
+        }
+
+        uris
+    }
+
+    /// Connects to the first candidate URI which accepts a connection.
+    /// This validates discovered URIs so that stale ones are skipped.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector
+    /// - `config`: the adapter config
+    async fn try_connect(
+        selector: &Mutex<dyn ServiceDiscoveryAdapterSelector>,
+        config: &Config,
+    ) -> Result<Connection, DigitalTwinAdapterError> {
+        let interceptor = GrpcInterceptor::from_config(&config.grpc)
+            .map_err(DigitalTwinAdapterError::deserialize)?;
+
+        for uri in Self::get_candidate_uris(selector, config).await {
+            match grpc_utils::connect(uri.clone()).await {
+                Ok(channel) => {
+                    return Ok(Connection {
+                        uri,
+                        client: InvehicleDigitalTwinClient::with_interceptor(channel, interceptor),
+                        discovered_at: Instant::now(),
+                    })
+                }
+                Err(e) => warn!("Could not connect to the digital twin service at {uri}: {e}"),
+            }
+        }
+
+        Err(DigitalTwinAdapterError::communication(format!(
+            "Could not connect to any URI for the digital twin service {}",
+            config.service_discovery_id
+        )))
+    }
+
+    /// Connects to the service, retrying according to the config
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector
+    /// - `config`: the adapter config
+    async fn connect(
+        selector: &Mutex<dyn ServiceDiscoveryAdapterSelector>,
+        config: &Config,
+    ) -> Result<Connection, DigitalTwinAdapterError> {
+        RetryPolicy::fixed(Duration::from_millis(config.retry_interval_ms))
+            .max_retries(config.max_retries)
+            .retry_if_retryable()
+            .context("Connection retry for connecting to Ibeji")
+            .execute(|| Self::try_connect(selector, config))
+            .await
+    }
+
+    /// Gets a client for the service, rediscovering the service first if the rediscovery interval has elapsed.
+    /// If rediscovery fails, the current connection is kept.
+    async fn get_client(&self) -> InvehicleDigitalTwinClient<GrpcChannel> {
+        let mut connection = self.connection.lock().await;
+
+        let rediscovery_due = self
+            .config
+            .rediscovery_interval_ms
+            .is_some_and(|ms| connection.discovered_at.elapsed() >= Duration::from_millis(ms));
+
+        if rediscovery_due {
+            match Self::try_connect(&self.selector, &self.config).await {
+                Ok(new_connection) => {
+                    if new_connection.uri != connection.uri {
+                        info!(
+                            "Digital twin service moved from {} to {}",
+                            connection.uri, new_connection.uri
+                        );
+                    }
+
+                    *connection = new_connection;
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to rediscover the digital twin service, using {}: {e}",
+                        connection.uri
+                    );
+                    connection.discovered_at = Instant::now();
+                }
+            }
+        }
+
+        connection.client.clone()
+    }
+
+    /// Replaces the current connection after the service became unavailable
+    async fn reconnect(
+        &self,
+    ) -> Result<InvehicleDigitalTwinClient<GrpcChannel>, DigitalTwinAdapterError> {
+        let mut connection = self.connection.lock().await;
+        warn!(
+            "The digital twin service at {} is unavailable, rediscovering it",
+            connection.uri
+        );
+
+        *connection = Self::connect(&self.selector, &self.config).await?;
+        info!(
+            "Connected to the digital twin service at {}",
+            connection.uri
+        );
+
+        Ok(connection.client.clone())
+    }
+
+    /// Checks that the service implements the interface that this adapter expects.
+    /// Returns an error only if the interface doesn't match and the check is enforced.
+    ///
+    /// # Arguments
+    /// - `uri`: the URI of the service
+    /// - `mode`: how to handle a service which doesn't implement the expected interface
+    /// - `timeout`: the time limit for the check
+    async fn verify_contract(
+        uri: &str,
+        mode: ContractCheckMode,
+        timeout: Duration,
+    ) -> Result<(), DigitalTwinAdapterError> {
+        if mode == ContractCheckMode::Disabled {
+            return Ok(());
+        }
+
+        match contract_check::check_contract(uri.to_string(), timeout).await {
+            ContractCheck::Verified => {
+                info!("The digital twin service at {uri} implements the expected interface");
+                Ok(())
+            }
+            ContractCheck::Unverifiable(reason) => {
+                info!(
+                    "Could not check the interface of the digital twin service at {uri}: {reason}"
+                );
+                Ok(())
+            }
+            ContractCheck::Mismatch(diagnostic) if mode == ContractCheckMode::Enforce => {
+                Err(DigitalTwinAdapterError::incompatible_service(diagnostic))
+            }
+            ContractCheck::Mismatch(diagnostic) => {
+                error!("{diagnostic}. Requests to the digital twin service are likely to fail");
+                Ok(())
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -48,28 +248,25 @@ impl DigitalTwinAdapter for GRPCDigitalTwinAdapter {
             DigitalTwinAdapterError::deserialize,
         )?;
 
-        let digital_twin_service_uri = futures::executor::block_on(async {
-            let selector = selector.lock().await;
-            selector.get_service_uri(&config.service_discovery_id).await
+        let connection = futures::executor::block_on(async {
+            let connection = Self::connect(&selector, &config).await?;
+            let timeout = config
+                .grpc
+                .request_timeout()
+                .unwrap_or(contract_check::DEFAULT_TIMEOUT);
+            Self::verify_contract(&connection.uri, config.contract_check, timeout).await?;
+            Ok::<_, DigitalTwinAdapterError>(connection)
+        })?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            selector,
+            config,
         })
-        .map_err(DigitalTwinAdapterError::communication)?;
-
-        let client = futures::executor::block_on(async {
-            execute_with_retry(
-                config.max_retries,
-                Duration::from_millis(config.retry_interval_ms),
-                || InvehicleDigitalTwinClient::connect(digital_twin_service_uri.clone()),
-                Some(String::from("Connection retry for connecting to Ibeji")),
-            )
-            .await
-            .map_err(DigitalTwinAdapterError::communication)
-        })
-        .map_err(DigitalTwinAdapterError::communication)?;
-
-        Ok(Self { client })
     }
 
-    /// Gets entity access information
+    /// Gets entity access information.
+    /// If the service is unavailable, it's rediscovered and the request is sent again.
     ///
     /// # Arguments
     /// - `request`: the request for finding an entity's access information
@@ -78,16 +275,25 @@ impl DigitalTwinAdapter for GRPCDigitalTwinAdapter {
         request: FindByIdRequest,
     ) -> Result<FindByIdResponse, DigitalTwinAdapterError> {
         let entity_id = request.entity_id;
-        let request = tonic::Request::new(IbejiFindByIdRequest {
+        let request = IbejiFindByIdRequest {
             id: entity_id.clone(),
-        });
-
-        let response = self
-            .client
-            .clone()
-            .find_by_id(request)
-            .await
-            .map_err(DigitalTwinAdapterError::entity_not_found)?;
+        };
+
+        // The request is sent again with the same request id and deadline if the service is unavailable
+        let context = GrpcRequestContext::new(&self.config.grpc);
+        let first_request = context
+            .request(request.clone())
+            .map_err(DigitalTwinAdapterError::communication)?;
+        let response = match self.get_client().await.find_by_id(first_request).await {
+            Err(status) if status.code() == Code::Unavailable => {
+                let request = context
+                    .request(request)
+                    .map_err(DigitalTwinAdapterError::communication)?;
+                self.reconnect().await?.find_by_id(request).await
+            }
+            result => result,
+        }
+        .map_err(DigitalTwinAdapterError::entity_not_found)?;
 
         // Extract the response from find_by_id
         let entity_access_info = response
@@ -109,6 +315,7 @@ impl DigitalTwinAdapter for GRPCDigitalTwinAdapter {
                         operations: e.operations,
                         uri: e.uri,
                         context: e.context,
+                        ..Default::default()
                     })
                     .collect(),
             },
@@ -125,7 +332,75 @@ mod grpc_digital_twin_adapter_tests {
 
     use super::*;
 
+    use freyja_common::service_discovery_adapter::ServiceDiscoveryAdapterErrorKind;
+    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+
     const AMBIENT_AIR_TEMPERATURE_ID: &str = "dtmi:sdv:Vehicle:Cabin:HVAC:AmbientAirTemperature;1";
+    const DISCOVERED_URI: &str = "http://discovered"; // Devskim: ignore DS137138
+    const STATIC_URI: &str = "http://static"; // Devskim: ignore DS137138
+
+    fn create_config(static_uri: Option<&str>) -> Config {
+        Config {
+            service_discovery_id: String::from("id"),
+            max_retries: 1,
+            retry_interval_ms: 0,
+            static_uri: static_uri.map(String::from),
+            rediscovery_interval_ms: None,
+            contract_check: ContractCheckMode::Warn,
+            grpc: Default::default(),
+        }
+    }
+
+    /// Creates a selector which discovers the provided URIs, or nothing if `uris` is `None`
+    fn create_selector(uris: Option<Vec<String>>) -> Mutex<MockServiceDiscoveryAdapterSelector> {
+        let mut selector = MockServiceDiscoveryAdapterSelector::new();
+        selector
+            .expect_get_service_uri_candidates()
+            .returning(move |_| {
+                uris.clone()
+                    .ok_or(ServiceDiscoveryAdapterErrorKind::NotFound.into())
+            });
+
+        Mutex::new(selector)
+    }
+
+    #[tokio::test]
+    async fn get_candidate_uris_tries_static_uri_last() {
+        let selector = create_selector(Some(vec![String::from(DISCOVERED_URI)]));
+
+        let result =
+            GRPCDigitalTwinAdapter::get_candidate_uris(&selector, &create_config(Some(STATIC_URI)))
+                .await;
+        assert_eq!(result, vec![DISCOVERED_URI, STATIC_URI]);
+    }
+
+    #[tokio::test]
+    async fn get_candidate_uris_uses_static_uri_when_discovery_fails() {
+        let selector = create_selector(None);
+
+        let result =
+            GRPCDigitalTwinAdapter::get_candidate_uris(&selector, &create_config(Some(STATIC_URI)))
+                .await;
+        assert_eq!(result, vec![STATIC_URI]);
+    }
+
+    #[tokio::test]
+    async fn get_candidate_uris_does_not_repeat_static_uri() {
+        let selector = create_selector(Some(vec![String::from(STATIC_URI)]));
+
+        let result =
+            GRPCDigitalTwinAdapter::get_candidate_uris(&selector, &create_config(Some(STATIC_URI)))
+                .await;
+        assert_eq!(result, vec![STATIC_URI]);
+    }
+
+    #[tokio::test]
+    async fn try_connect_returns_err_without_candidates() {
+        let selector = create_selector(None);
+
+        let result = GRPCDigitalTwinAdapter::try_connect(&selector, &create_config(None)).await;
+        assert!(result.is_err());
+    }
 
     /// The tests below uses Unix sockets to create a channel between a gRPC client and a gRPC server.
     /// Unix sockets are more ideal than using TCP/IP sockets since Rust tests will run in parallel
@@ -139,14 +414,14 @@ mod grpc_digital_twin_adapter_tests {
         use core_protobuf_data_access::invehicle_digital_twin::v1::invehicle_digital_twin_server::InvehicleDigitalTwinServer;
         use tokio::net::{UnixListener, UnixStream};
         use tokio_stream::wrappers::UnixListenerStream;
-        use tonic::transport::{Channel, Endpoint, Server, Uri};
+        use tonic::transport::{Endpoint, Server, Uri};
         use tower::service_fn;
 
         use freyja_test_common::{fixtures::GRPCTestFixture, mocks::MockInVehicleDigitalTwin};
 
         async fn create_test_grpc_client(
             socket_path: PathBuf,
-        ) -> InvehicleDigitalTwinClient<Channel> {
+        ) -> InvehicleDigitalTwinClient<GrpcChannel> {
             let channel = Endpoint::try_from("http://URI_IGNORED") // Devskim: ignore DS137138
                 .unwrap()
                 .connect_with_connector(service_fn(move |_: Uri| {
@@ -156,7 +431,7 @@ mod grpc_digital_twin_adapter_tests {
                 .await
                 .unwrap();
 
-            InvehicleDigitalTwinClient::new(channel)
+            InvehicleDigitalTwinClient::with_interceptor(channel, GrpcInterceptor::default())
         }
 
         async fn run_test_grpc_server(uds_stream: UnixListenerStream) {
@@ -218,7 +493,15 @@ mod grpc_digital_twin_adapter_tests {
 
             let request_future = async {
                 let client = create_test_grpc_client(fixture.socket_path.clone()).await;
-                let ibeji_digital_twin_adapter = GRPCDigitalTwinAdapter { client };
+                let ibeji_digital_twin_adapter = GRPCDigitalTwinAdapter {
+                    connection: Mutex::new(Connection {
+                        uri: fixture.socket_path.to_string_lossy().into_owned(),
+                        client,
+                        discovered_at: Instant::now(),
+                    }),
+                    selector: Arc::new(Mutex::new(MockServiceDiscoveryAdapterSelector::new())),
+                    config: create_config(None),
+                };
 
                 let request = FindByIdRequest {
                     entity_id: String::from("invalid_entity"),
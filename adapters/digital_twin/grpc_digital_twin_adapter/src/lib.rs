@@ -3,4 +3,5 @@
 // SPDX-License-Identifier: MIT
 
 mod config;
+mod contract_check;
 pub mod grpc_digital_twin_adapter;
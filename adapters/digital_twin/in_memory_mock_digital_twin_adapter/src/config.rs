@@ -11,6 +11,18 @@ use freyja_common::entity::Entity;
 pub struct Config {
     /// The set of config values
     pub values: Vec<EntityConfig>,
+
+    /// Whether or not to periodically reload the entities from the config files
+    pub hot_reload: bool,
+
+    /// The minimum interval between reloads of the config files when `hot_reload` is enabled.
+    /// The config files are reloaded on the first request after this interval has passed
+    pub hot_reload_interval_ms: u64,
+
+    /// The address for hosting the management API, which lets tests add and remove entities over HTTP.
+    /// If this is `None`, the management API is disabled
+    #[serde(default)]
+    pub management_api_address: Option<String>,
 }
 
 /// Configuration for a entity
@@ -2,12 +2,19 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex as StdMutex, RwLock},
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
+use log::{info, warn};
 use tokio::sync::Mutex;
 
-use crate::config::Config;
+use crate::{
+    config::{Config, EntityConfig},
+    management_api,
+};
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     config_utils,
@@ -15,24 +22,105 @@ use freyja_common::{
         DigitalTwinAdapter, DigitalTwinAdapterError, DigitalTwinAdapterErrorKind, FindByIdRequest,
         FindByIdResponse,
     },
+    entity::Entity,
     out_dir,
     service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
 };
 
+/// The entities that are currently available, which are shared with the management API
+pub(crate) type Entities = Arc<RwLock<Vec<EntityConfig>>>;
+
 /// In-memory mock that mocks finding endpoint info about entities
 /// through find by id
 pub struct InMemoryMockDigitalTwinAdapter {
-    /// The adapter config
-    config: Config,
+    /// The entities that are currently available
+    entities: Entities,
+
+    /// Whether or not to periodically reload the entities from the config files
+    hot_reload: bool,
+
+    /// The minimum interval between reloads of the config files
+    hot_reload_interval: Duration,
+
+    /// The last time that the entities were loaded from the config files
+    last_reload: StdMutex<Instant>,
 }
 
 impl InMemoryMockDigitalTwinAdapter {
-    /// Creates a new InMemoryMockDigitalTwinAdapter with the specified config
+    /// Creates a new InMemoryMockDigitalTwinAdapter with the specified config.
+    /// This does not start the management API, which is only started by `create_new`.
     ///
     /// # Arguments
     /// - `config`: the config to use
     pub fn from_config(config: Config) -> Result<Self, DigitalTwinAdapterError> {
-        Ok(Self { config })
+        Ok(Self {
+            entities: Arc::new(RwLock::new(config.values)),
+            hot_reload: config.hot_reload,
+            hot_reload_interval: Duration::from_millis(config.hot_reload_interval_ms),
+            last_reload: StdMutex::new(Instant::now()),
+        })
+    }
+
+    /// Adds an entity at runtime. If an entity with the same id already exists, it is replaced.
+    /// If `hot_reload` is enabled, this change is discarded the next time the config files are reloaded.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to add
+    pub fn add_entity(&self, entity: Entity) {
+        add_entity(&self.entities, entity);
+    }
+
+    /// Removes an entity at runtime. Returns the removed entity, or `None` if it was not found.
+    /// If `hot_reload` is enabled, this change is discarded the next time the config files are reloaded.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the id of the entity to remove
+    pub fn remove_entity(&self, entity_id: &str) -> Option<Entity> {
+        remove_entity(&self.entities, entity_id)
+    }
+
+    /// Checks whether the config files should be reloaded, which is the case if `hot_reload` is enabled
+    /// and the reload interval has passed since they were last loaded
+    ///
+    /// # Arguments
+    /// - `now`: the current time
+    fn is_reload_due(&self, now: Instant) -> bool {
+        if !self.hot_reload {
+            return false;
+        }
+
+        let mut last_reload = self.last_reload.lock().unwrap();
+        if now.saturating_duration_since(*last_reload) < self.hot_reload_interval {
+            return false;
+        }
+
+        *last_reload = now;
+        true
+    }
+
+    /// Reloads the entities from the config files.
+    /// This replaces any entities that were added or removed at runtime.
+    fn reload(&self) -> Result<(), DigitalTwinAdapterError> {
+        let config: Config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DigitalTwinAdapterError::io,
+            DigitalTwinAdapterError::deserialize,
+        )?;
+
+        let mut entities = self.entities.write().unwrap();
+        if entities.len() != config.values.len() {
+            info!(
+                "Reloaded in-memory digital twin config. Entity count changed from {} to {}",
+                entities.len(),
+                config.values.len()
+            );
+        }
+
+        *entities = config.values;
+
+        Ok(())
     }
 }
 
@@ -45,7 +133,7 @@ impl DigitalTwinAdapter for InMemoryMockDigitalTwinAdapter {
     fn create_new(
         _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
     ) -> Result<Self, DigitalTwinAdapterError> {
-        let config = config_utils::read_from_files(
+        let config: Config = config_utils::read_from_files(
             config_file_stem!(),
             config_utils::JSON_EXT,
             out_dir!(),
@@ -53,7 +141,18 @@ impl DigitalTwinAdapter for InMemoryMockDigitalTwinAdapter {
             DigitalTwinAdapterError::deserialize,
         )?;
 
-        Self::from_config(config)
+        let management_api_address = config.management_api_address.clone();
+        let adapter = Self::from_config(config)?;
+
+        if let Some(address) = management_api_address {
+            if adapter.hot_reload {
+                warn!("Both hot reload and the management API are enabled for the in-memory digital twin adapter. Entities added or removed through the management API will be discarded when the config files are reloaded");
+            }
+
+            management_api::start(&address, adapter.entities.clone())?;
+        }
+
+        Ok(adapter)
     }
 
     /// Gets the entity information based on the request
@@ -64,8 +163,17 @@ impl DigitalTwinAdapter for InMemoryMockDigitalTwinAdapter {
         &self,
         request: FindByIdRequest,
     ) -> Result<FindByIdResponse, DigitalTwinAdapterError> {
-        self.config
-            .values
+        if self.is_reload_due(Instant::now()) {
+            if let Err(e) = self.reload() {
+                warn!(
+                    "Failed to reload in-memory digital twin config, using previous entities: {e}"
+                );
+            }
+        }
+
+        self.entities
+            .read()
+            .unwrap()
             .iter()
             .find(|entity_config| entity_config.entity.id == request.entity_id)
             .map(|entity_config| FindByIdResponse {
@@ -75,12 +183,44 @@ impl DigitalTwinAdapter for InMemoryMockDigitalTwinAdapter {
     }
 }
 
+/// Adds an entity. If an entity with the same id already exists, it is replaced.
+/// Returns true if an existing entity was replaced.
+///
+/// # Arguments
+/// - `entities`: the available entities
+/// - `entity`: the entity to add
+pub(crate) fn add_entity(entities: &RwLock<Vec<EntityConfig>>, entity: Entity) -> bool {
+    let mut entities = entities.write().unwrap();
+    let original_len = entities.len();
+    entities.retain(|entity_config| entity_config.entity.id != entity.id);
+    let replaced = entities.len() != original_len;
+    entities.push(EntityConfig { entity });
+
+    replaced
+}
+
+/// Removes an entity. Returns the removed entity, or `None` if it was not found.
+///
+/// # Arguments
+/// - `entities`: the available entities
+/// - `entity_id`: the id of the entity to remove
+pub(crate) fn remove_entity(
+    entities: &RwLock<Vec<EntityConfig>>,
+    entity_id: &str,
+) -> Option<Entity> {
+    let mut entities = entities.write().unwrap();
+    let index = entities
+        .iter()
+        .position(|entity_config| entity_config.entity.id == entity_id)?;
+
+    Some(entities.remove(index).entity)
+}
+
 #[cfg(test)]
 mod in_memory_mock_digital_twin_adapter_tests {
     use super::*;
 
-    use crate::config::EntityConfig;
-    use freyja_common::entity::{Entity, EntityEndpoint};
+    use freyja_common::entity::EntityEndpoint;
     use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
 
     const OPERATION: &str = "Subscribe";
@@ -108,12 +248,17 @@ mod in_memory_mock_digital_twin_adapter_tests {
                         operations: vec![OPERATION.to_string()],
                         uri: String::from("http://0.0.0.0:1111"), // Devskim: ignore DS137138
                         context: String::from("context"),
+                        ..Default::default()
                     }],
                 },
             }],
+            hot_reload: false,
+            hot_reload_interval_ms: 0,
+            management_api_address: None,
         };
 
-        let in_memory_digital_twin_adapter = InMemoryMockDigitalTwinAdapter { config };
+        let in_memory_digital_twin_adapter =
+            InMemoryMockDigitalTwinAdapter::from_config(config).unwrap();
         let request = FindByIdRequest {
             entity_id: String::from(ENTITY_ID),
         };
@@ -130,4 +275,87 @@ mod in_memory_mock_digital_twin_adapter_tests {
         let operation = endpoint.operations.first().unwrap();
         assert_eq!(operation, OPERATION);
     }
+
+    #[tokio::test]
+    async fn find_by_id_reflects_runtime_entity_changes() {
+        const ENTITY_ID: &str = "dtmi:sdv:Vehicle:Cabin:HVAC:AmbientAirTemperature;1";
+
+        let uut = InMemoryMockDigitalTwinAdapter::from_config(Config {
+            values: vec![],
+            hot_reload: false,
+            hot_reload_interval_ms: 0,
+            management_api_address: None,
+        })
+        .unwrap();
+
+        let request = || FindByIdRequest {
+            entity_id: String::from(ENTITY_ID),
+        };
+
+        let result = uut.find_by_id(request()).await;
+        assert!(result.is_err());
+        assert_eq!(
+            result.err().unwrap().kind(),
+            DigitalTwinAdapterErrorKind::EntityNotFound
+        );
+
+        let entity = Entity {
+            id: ENTITY_ID.to_string(),
+            ..Default::default()
+        };
+        uut.add_entity(entity.clone());
+
+        let result = uut.find_by_id(request()).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().entity, entity);
+
+        // Adding an entity with the same id replaces the existing one
+        let updated_entity = Entity {
+            id: ENTITY_ID.to_string(),
+            name: Some("name".to_string()),
+            ..Default::default()
+        };
+        uut.add_entity(updated_entity.clone());
+        assert_eq!(uut.entities.read().unwrap().len(), 1);
+
+        let removed = uut.remove_entity(ENTITY_ID);
+        assert_eq!(removed, Some(updated_entity));
+
+        let result = uut.find_by_id(request()).await;
+        assert!(result.is_err());
+
+        assert!(uut.remove_entity(ENTITY_ID).is_none());
+    }
+
+    #[test]
+    fn is_reload_due_waits_for_interval() {
+        let uut = InMemoryMockDigitalTwinAdapter::from_config(Config {
+            values: vec![],
+            hot_reload: true,
+            hot_reload_interval_ms: 1000,
+            management_api_address: None,
+        })
+        .unwrap();
+
+        let start = *uut.last_reload.lock().unwrap();
+        assert!(!uut.is_reload_due(start + Duration::from_millis(500)));
+        assert!(uut.is_reload_due(start + Duration::from_millis(1000)));
+
+        // The interval restarts after each reload
+        assert!(!uut.is_reload_due(start + Duration::from_millis(1500)));
+        assert!(uut.is_reload_due(start + Duration::from_millis(2000)));
+    }
+
+    #[test]
+    fn is_reload_due_returns_false_without_hot_reload() {
+        let uut = InMemoryMockDigitalTwinAdapter::from_config(Config {
+            values: vec![],
+            hot_reload: false,
+            hot_reload_interval_ms: 0,
+            management_api_address: None,
+        })
+        .unwrap();
+
+        assert!(!uut.is_reload_due(Instant::now() + Duration::from_secs(60)));
+    }
 }
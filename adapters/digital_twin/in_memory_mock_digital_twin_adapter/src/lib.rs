@@ -4,3 +4,4 @@
 
 mod config;
 pub mod in_memory_mock_digital_twin_adapter;
+mod management_api;
@@ -0,0 +1,149 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use log::{info, warn};
+
+use crate::{
+    config::EntityConfig,
+    in_memory_mock_digital_twin_adapter::{add_entity, remove_entity, Entities},
+};
+use freyja_common::{digital_twin_adapter::DigitalTwinAdapterError, uri};
+
+/// The path of the endpoint which lists and adds entities
+const ENTITIES_PATH: &str = "/entities";
+
+/// The path of the endpoint which removes an entity
+const ENTITY_PATH: &str = "/entities/:id";
+
+/// Starts the management API, which lets tests and demos add and remove entities while Freyja is running
+///
+/// # Arguments
+/// - `address`: the hosting address
+/// - `entities`: the adapter's entities
+pub(crate) fn start(address: &str, entities: Entities) -> Result<(), DigitalTwinAdapterError> {
+    let addr = uri::parse_socket_address(address).map_err(DigitalTwinAdapterError::parse_error)?;
+
+    let app = Router::new()
+        .route(ENTITIES_PATH, get(list_entities).post(post_entity))
+        .route(ENTITY_PATH, delete(delete_entity))
+        .with_state(entities);
+
+    let server = axum::Server::try_bind(&addr)
+        .map_err(DigitalTwinAdapterError::communication)?
+        .serve(app.into_make_service());
+
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            warn!("In-memory digital twin management API terminated with error: {e}");
+        }
+    });
+
+    info!("In-memory digital twin management API starting at {addr}");
+
+    Ok(())
+}
+
+/// Lists the available entities
+///
+/// # Arguments
+/// - `entities`: the adapter's entities
+async fn list_entities(State(entities): State<Entities>) -> Json<Vec<EntityConfig>> {
+    Json(entities.read().unwrap().clone())
+}
+
+/// Adds an entity, replacing any entity with the same id.
+/// Responds with `201 Created` if the entity is new or `204 No Content` if it replaced an existing entity.
+///
+/// # Arguments
+/// - `entities`: the adapter's entities
+/// - `entity`: the entity to add
+async fn post_entity(
+    State(entities): State<Entities>,
+    Json(entity): Json<EntityConfig>,
+) -> StatusCode {
+    let id = entity.entity.id.clone();
+    if add_entity(&entities, entity.entity) {
+        info!("Replaced entity {id}");
+        StatusCode::NO_CONTENT
+    } else {
+        info!("Added entity {id}");
+        StatusCode::CREATED
+    }
+}
+
+/// Removes an entity.
+/// Responds with `404 Not Found` if the entity doesn't exist.
+///
+/// # Arguments
+/// - `entities`: the adapter's entities
+/// - `id`: the id of the entity to remove
+async fn delete_entity(State(entities): State<Entities>, Path(id): Path<String>) -> StatusCode {
+    match remove_entity(&entities, &id) {
+        Some(_) => {
+            info!("Removed entity {id}");
+            StatusCode::NO_CONTENT
+        }
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+#[cfg(test)]
+mod management_api_tests {
+    use super::*;
+
+    use std::sync::{Arc, RwLock};
+
+    use freyja_common::entity::Entity;
+
+    const ID: &str = "dtmi:sdv:HVAC:AmbientAirTemperature;1";
+
+    fn create_entity(name: &str) -> EntityConfig {
+        EntityConfig {
+            entity: Entity {
+                id: ID.to_string(),
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn post_entity_adds_or_replaces_entity() {
+        let entities = Entities::default();
+
+        let result = post_entity(State(entities.clone()), Json(create_entity("first"))).await;
+        assert_eq!(result, StatusCode::CREATED);
+
+        let result = post_entity(State(entities.clone()), Json(create_entity("second"))).await;
+        assert_eq!(result, StatusCode::NO_CONTENT);
+
+        let Json(entities) = list_entities(State(entities)).await;
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].entity.name, Some("second".to_string()));
+    }
+
+    #[tokio::test]
+    async fn delete_entity_removes_entity() {
+        let entities = Arc::new(RwLock::new(vec![create_entity("first")]));
+
+        let result = delete_entity(State(entities.clone()), Path(ID.to_string())).await;
+        assert_eq!(result, StatusCode::NO_CONTENT);
+        assert!(entities.read().unwrap().is_empty());
+
+        let result = delete_entity(State(entities), Path(ID.to_string())).await;
+        assert_eq!(result, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn start_returns_err_for_invalid_address() {
+        let result = start("not an address", Entities::default());
+        assert!(result.is_err());
+    }
+}
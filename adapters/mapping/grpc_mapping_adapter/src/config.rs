@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use freyja_common::grpc_utils::GrpcClientConfig;
+
 /// Config for the GRPCMappingAdapter
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -15,4 +17,8 @@ pub struct Config {
 
     /// Retry interval in milliseconds
     pub retry_interval_ms: u64,
+
+    /// The headers, bearer token, and deadline of requests sent to the mapping service
+    #[serde(flatten)]
+    pub grpc: GrpcClientConfig,
 }
@@ -7,22 +7,25 @@ use std::{sync::Arc, time::Duration};
 use async_trait::async_trait;
 use log::debug;
 use tokio::sync::Mutex;
-use tonic::transport::Channel;
 
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     config_utils,
+    grpc_utils::{self, GrpcChannel, GrpcInterceptor, GrpcRequestContext},
     mapping_adapter::{
         CheckForWorkRequest, CheckForWorkResponse, GetMappingRequest, GetMappingResponse,
         MappingAdapter, MappingAdapterError,
     },
     out_dir,
-    retry_utils::execute_with_retry,
+    retry_utils::RetryPolicy,
     service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
 };
-use mapping_service_proto::v1::{
-    mapping_service_client::MappingServiceClient, CheckForWorkRequest as ProtoCheckForWorkRequest,
-    GetMappingRequest as ProtoGetMappingRequest,
+use mapping_service_proto::{
+    v1::{
+        CheckForWorkRequest as ProtoCheckForWorkRequest,
+        GetMappingRequest as ProtoGetMappingRequest,
+    },
+    v2::mapping_service_client::MappingServiceClient,
 };
 
 use crate::config::Config;
@@ -33,7 +36,7 @@ pub struct GRPCMappingAdapter {
     config: Config,
 
     // The gRPC client
-    client: MappingServiceClient<Channel>,
+    client: MappingServiceClient<GrpcChannel>,
 }
 
 #[async_trait]
@@ -53,24 +56,29 @@ impl MappingAdapter for GRPCMappingAdapter {
             MappingAdapterError::deserialize,
         )?;
 
+        let interceptor =
+            GrpcInterceptor::from_config(&config.grpc).map_err(MappingAdapterError::deserialize)?;
+
         let mapping_service_uri = futures::executor::block_on(async {
             let selector = selector.lock().await;
             selector.get_service_uri(&config.service_discovery_id).await
         })
         .map_err(MappingAdapterError::communication)?;
 
-        let client = futures::executor::block_on(async {
-            execute_with_retry(
-                config.max_retries,
-                Duration::from_millis(config.retry_interval_ms),
-                || MappingServiceClient::connect(mapping_service_uri.clone()),
-                Some("Mapping adapter initial connection".into()),
-            )
-            .await
-            .map_err(MappingAdapterError::communication)
+        let channel = futures::executor::block_on(async {
+            RetryPolicy::fixed(Duration::from_millis(config.retry_interval_ms))
+                .max_retries(config.max_retries)
+                .retry_if_retryable()
+                .context("Mapping adapter initial connection")
+                .execute(|| grpc_utils::connect(mapping_service_uri.clone()))
+                .await
+                .map_err(MappingAdapterError::communication)
         })?;
 
-        Ok(Self { config, client })
+        Ok(Self {
+            config,
+            client: MappingServiceClient::with_interceptor(channel, interceptor),
+        })
     }
 
     /// Checks for any additional work that the mapping service requires.
@@ -83,22 +91,20 @@ impl MappingAdapter for GRPCMappingAdapter {
 
         let request: ProtoCheckForWorkRequest = request.into();
 
-        let response = execute_with_retry(
-            self.config.max_retries,
-            Duration::from_millis(self.config.retry_interval_ms),
-            || async {
-                let request = tonic::Request::new(request.clone());
+        let context = GrpcRequestContext::new(&self.config.grpc);
+        let response = RetryPolicy::fixed(Duration::from_millis(self.config.retry_interval_ms))
+            .max_retries(self.config.max_retries)
+            .retry_if_retryable()
+            .context("Mapping adapter check for work request")
+            .execute(|| async {
                 self.client
                     .clone()
-                    .check_for_work(request)
+                    .check_for_work(context.request(request.clone())?)
                     .await
-                    .map_err(MappingAdapterError::communication)
-            },
-            Some(String::from("Mapping adapter check for work request")),
-        )
-        .await
-        .map_err(MappingAdapterError::communication)?
-        .into_inner();
+            })
+            .await
+            .map_err(MappingAdapterError::communication)?
+            .into_inner();
 
         debug!("Check for work response: {response:?}");
 
@@ -114,22 +120,20 @@ impl MappingAdapter for GRPCMappingAdapter {
 
         let request: ProtoGetMappingRequest = request.into();
 
-        let response = execute_with_retry(
-            self.config.max_retries,
-            Duration::from_millis(self.config.retry_interval_ms),
-            || async {
-                let request = tonic::Request::new(request.clone());
+        let context = GrpcRequestContext::new(&self.config.grpc);
+        let response = RetryPolicy::fixed(Duration::from_millis(self.config.retry_interval_ms))
+            .max_retries(self.config.max_retries)
+            .retry_if_retryable()
+            .context("Mapping adapter get mapping request")
+            .execute(|| async {
                 self.client
                     .clone()
-                    .get_mapping(request)
+                    .get_mapping(context.request(request.clone())?)
                     .await
-                    .map_err(MappingAdapterError::communication)
-            },
-            Some(String::from("Mapping adapter get mapping request")),
-        )
-        .await
-        .map_err(MappingAdapterError::communication)?
-        .into_inner();
+            })
+            .await
+            .map_err(MappingAdapterError::communication)?
+            .into_inner();
 
         debug!("Get mapping response: {response:?}");
 
@@ -159,7 +163,7 @@ mod grpc_mapping_adapter_tests {
         use tower::service_fn;
 
         use freyja_test_common::{fixtures::GRPCTestFixture, mocks::MockMappingService};
-        use mapping_service_proto::v1::{
+        use mapping_service_proto::v2::{
             mapping_service_server::MappingServiceServer,
             GetMappingResponse as ProtoGetMappingResponse,
         };
@@ -10,6 +10,25 @@ use freyja_common::digital_twin_map_entry::DigitalTwinMapEntry;
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
     /// The set of config values
+    #[serde(default)]
+    pub values: Vec<ConfigItem>,
+
+    /// The paths of mapping files whose values are used in addition to the config values.
+    /// Relative paths are resolved against the current directory
+    #[serde(default)]
+    pub mapping_files: Vec<String>,
+}
+
+/// A file containing mapping values, which can be shared between configs
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MappingFile {
+    /// The paths of other mapping files whose values are included before this file's values.
+    /// Relative paths are resolved against the directory containing this file
+    #[serde(default)]
+    pub includes: Vec<String>,
+
+    /// The mapping values
+    #[serde(default)]
     pub values: Vec<ConfigItem>,
 }
 
@@ -2,15 +2,20 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::sync::{
-    atomic::{AtomicU8, Ordering},
-    Arc,
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
 };
 
 use async_trait::async_trait;
 use tokio::sync::Mutex;
 
-use crate::config::Config;
+use crate::config::{Config, ConfigItem, MappingFile};
 use freyja_build_common::config_file_stem;
 
 use freyja_common::{
@@ -33,12 +38,18 @@ pub struct InMemoryMockMappingAdapter {
 }
 
 impl InMemoryMockMappingAdapter {
-    /// Creates a new InMemoryMockMappingAdapter with the specified config
+    /// Creates a new InMemoryMockMappingAdapter with the specified config.
+    /// The values of the config's mapping files are read and added after the config's own values
     ///
     /// # Arguments
     ///
     /// - `config`: the config to use
-    pub fn from_config(config: Config) -> Result<Self, MappingAdapterError> {
+    pub fn from_config(mut config: Config) -> Result<Self, MappingAdapterError> {
+        let mut read_files = HashSet::new();
+        for path in config.mapping_files.iter() {
+            read_mapping_file(Path::new(path), &mut read_files, &mut config.values)?;
+        }
+
         Ok(Self {
             config,
             counter: AtomicU8::new(0),
@@ -46,6 +57,52 @@ impl InMemoryMockMappingAdapter {
     }
 }
 
+/// Reads the values of a mapping file and the files it includes.
+/// Files which were already read are skipped, so the values of a file which is included several times are only added once
+/// and include cycles are not followed.
+///
+/// # Arguments
+/// - `path`: the path of the mapping file
+/// - `read_files`: the canonical paths of the files which were already read
+/// - `values`: the values to add the file's values to
+fn read_mapping_file(
+    path: &Path,
+    read_files: &mut HashSet<PathBuf>,
+    values: &mut Vec<ConfigItem>,
+) -> Result<(), MappingAdapterError> {
+    let path = fs::canonicalize(path).map_err(|e| {
+        MappingAdapterError::io(format!(
+            "Could not find mapping file {}: {e}",
+            path.display()
+        ))
+    })?;
+    if !read_files.insert(path.clone()) {
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(&path).map_err(|e| {
+        MappingAdapterError::io(format!(
+            "Could not read mapping file {}: {e}",
+            path.display()
+        ))
+    })?;
+    let file: MappingFile = serde_json::from_str(&contents).map_err(|e| {
+        MappingAdapterError::deserialize(format!(
+            "Could not parse mapping file {}: {e}",
+            path.display()
+        ))
+    })?;
+
+    // A canonical path always has a parent unless it's the root, which can't be a file
+    let directory = path.parent().unwrap_or(Path::new("/"));
+    for include in file.includes.iter() {
+        read_mapping_file(&directory.join(include), read_files, values)?;
+    }
+
+    values.extend(file.values);
+    Ok(())
+}
+
 #[async_trait]
 impl MappingAdapter for InMemoryMockMappingAdapter {
     /// Creates a new instance of an InMemoryMockMappingAdapter with default settings
@@ -114,10 +171,59 @@ mod in_memory_mock_mapping_adapter_tests {
 
     use std::collections::HashMap;
 
-    use freyja_common::{conversion::Conversion, digital_twin_map_entry::DigitalTwinMapEntry};
-    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+    use freyja_common::mapping_adapter::MappingAdapterErrorKind;
+    use freyja_common::{
+        conversion::{Conversion, ConversionFailurePolicy},
+        digital_twin_map_entry::DigitalTwinMapEntry,
+    };
+    use freyja_test_common::{
+        fixtures::TempDirFixture, mocks::MockServiceDiscoveryAdapterSelector,
+    };
+
+    /// A directory of mapping files in the temp directory which is deleted when dropped
+    struct MappingDirectory {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl MappingDirectory {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join(name);
+            fs::create_dir_all(&path).unwrap();
+            for (file_name, contents) in files {
+                fs::write(path.join(file_name), contents).unwrap();
+            }
 
-    use crate::config::ConfigItem;
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+
+        fn config(&self, file_name: &str) -> Config {
+            Config {
+                values: Vec::new(),
+                mapping_files: vec![self.path.join(file_name).to_string_lossy().into_owned()],
+            }
+        }
+    }
+
+    fn mapping_file(includes: &[&str], sources: &[&str]) -> String {
+        let values: Vec<String> = sources
+            .iter()
+            .map(|source| {
+                format!(
+                    r#"{{"begin": 0, "end": null, "value": {{"source": "{source}", "target": {{}}, "interval_ms": 1000, "conversion": null, "emit_on_change": false}}}}"#
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"includes": {includes:?}, "values": [{}]}}"#,
+            values.join(",")
+        )
+    }
 
     #[test]
     fn can_create_new() {
@@ -140,6 +246,16 @@ mod in_memory_mock_mapping_adapter_tests {
                         interval_ms: 0,
                         conversion: Conversion::None,
                         emit_on_change: false,
+                        conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                        decimal_places: None,
+                        namespace: None,
+                        tenant: None,
+                        trigger: None,
+                        adaptive_interval: None,
+                        privacy: None,
+                        not_before: None,
+                        not_after: None,
+                        metadata: HashMap::new(),
                     },
                 },
                 ConfigItem {
@@ -151,6 +267,16 @@ mod in_memory_mock_mapping_adapter_tests {
                         interval_ms: 0,
                         conversion: Conversion::None,
                         emit_on_change: false,
+                        conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                        decimal_places: None,
+                        namespace: None,
+                        tenant: None,
+                        trigger: None,
+                        adaptive_interval: None,
+                        privacy: None,
+                        not_before: None,
+                        not_after: None,
+                        metadata: HashMap::new(),
                     },
                 },
                 ConfigItem {
@@ -162,9 +288,20 @@ mod in_memory_mock_mapping_adapter_tests {
                         interval_ms: 0,
                         conversion: Conversion::None,
                         emit_on_change: false,
+                        conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                        decimal_places: None,
+                        namespace: None,
+                        tenant: None,
+                        trigger: None,
+                        adaptive_interval: None,
+                        privacy: None,
+                        not_before: None,
+                        not_after: None,
+                        metadata: HashMap::new(),
                     },
                 },
             ],
+            mapping_files: Vec::new(),
         };
 
         let uut = InMemoryMockMappingAdapter::from_config(config).unwrap();
@@ -195,6 +332,16 @@ mod in_memory_mock_mapping_adapter_tests {
                         interval_ms: 0,
                         conversion: Conversion::None,
                         emit_on_change: false,
+                        conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                        decimal_places: None,
+                        namespace: None,
+                        tenant: None,
+                        trigger: None,
+                        adaptive_interval: None,
+                        privacy: None,
+                        not_before: None,
+                        not_after: None,
+                        metadata: HashMap::new(),
                     },
                 },
                 ConfigItem {
@@ -206,6 +353,16 @@ mod in_memory_mock_mapping_adapter_tests {
                         interval_ms: 0,
                         conversion: Conversion::None,
                         emit_on_change: false,
+                        conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                        decimal_places: None,
+                        namespace: None,
+                        tenant: None,
+                        trigger: None,
+                        adaptive_interval: None,
+                        privacy: None,
+                        not_before: None,
+                        not_after: None,
+                        metadata: HashMap::new(),
                     },
                 },
                 ConfigItem {
@@ -217,9 +374,20 @@ mod in_memory_mock_mapping_adapter_tests {
                         interval_ms: 0,
                         conversion: Conversion::None,
                         emit_on_change: false,
+                        conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                        decimal_places: None,
+                        namespace: None,
+                        tenant: None,
+                        trigger: None,
+                        adaptive_interval: None,
+                        privacy: None,
+                        not_before: None,
+                        not_after: None,
+                        metadata: HashMap::new(),
                     },
                 },
             ],
+            mapping_files: Vec::new(),
         };
 
         let uut = InMemoryMockMappingAdapter::from_config(config).unwrap();
@@ -257,4 +425,54 @@ mod in_memory_mock_mapping_adapter_tests {
             assert!(!mapping.iter().any(|p| *p.0 == "not-always-active"));
         }
     }
+
+    #[tokio::test]
+    async fn from_config_reads_mapping_files_with_includes() {
+        let directory = MappingDirectory::new(
+            "mapping_files_with_includes",
+            &[
+                ("main.json", &mapping_file(&["shared.json"], &["main"])),
+                (
+                    "shared.json",
+                    &mapping_file(&["common.json", "main.json"], &["shared"]),
+                ),
+                ("common.json", &mapping_file(&["shared.json"], &["common"])),
+            ],
+        );
+
+        let uut = InMemoryMockMappingAdapter::from_config(directory.config("main.json")).unwrap();
+
+        // Each file's values are only added once even though the files include each other
+        let sources: Vec<&str> = uut
+            .config
+            .values
+            .iter()
+            .map(|c| c.value.source.as_str())
+            .collect();
+        assert_eq!(sources, vec!["common", "shared", "main"]);
+
+        let mapping = uut.get_mapping(GetMappingRequest {}).await.unwrap().map;
+        assert_eq!(mapping.len(), 3);
+    }
+
+    #[test]
+    fn from_config_returns_err_for_invalid_mapping_files() {
+        let directory = MappingDirectory::new(
+            "invalid_mapping_files",
+            &[
+                (
+                    "missing_include.json",
+                    &mapping_file(&["missing.json"], &[]),
+                ),
+                ("invalid.json", "{\"values\": 42}"),
+            ],
+        );
+
+        let result =
+            InMemoryMockMappingAdapter::from_config(directory.config("missing_include.json"));
+        assert!(result.is_err_and(|e| e.kind() == MappingAdapterErrorKind::Io));
+
+        let result = InMemoryMockMappingAdapter::from_config(directory.config("invalid.json"));
+        assert!(result.is_err_and(|e| e.kind() == MappingAdapterErrorKind::Deserialize));
+    }
 }
@@ -9,6 +9,25 @@ use serde::{Deserialize, Serialize};
 /// Configuration for the File Service Discovery Adapter
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// A map of service ids to uris.
+    /// Service ids can contain `*` wildcards which match any sequence of characters
+    #[serde(default)]
+    pub services: HashMap<String, String>,
+
+    /// Directories containing additional service files to merge with `services`.
+    /// Every `.json` file in these directories is read in file name order,
+    /// and services in later files override the ones in earlier files
+    #[serde(default)]
+    pub service_directories: Vec<String>,
+
+    /// Whether or not to reload the service files when they change
+    #[serde(default)]
+    pub hot_reload: bool,
+}
+
+/// The contents of a service file in one of the service directories
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ServiceFile {
     /// A map of service ids to uris
     pub services: HashMap<String, String>,
 }
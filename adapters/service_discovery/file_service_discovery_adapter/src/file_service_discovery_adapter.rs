@@ -2,9 +2,18 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::SystemTime,
+};
+
 use async_trait::async_trait;
+use log::{info, warn};
 
-use crate::config::Config;
+use crate::config::{Config, ServiceFile};
 use freyja_build_common::config_file_stem;
 use freyja_common::{
     config_utils, out_dir,
@@ -13,10 +22,180 @@ use freyja_common::{
     },
 };
 
-/// Uses a static config file for service discovery
+/// The extension of service files in the service directories
+const SERVICE_FILE_EXT: &str = "json";
+
+/// The wildcard character in service ids
+const WILDCARD: char = '*';
+
+/// The service files in the service directories and when they were last modified
+type FileVersions = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// The services loaded from the config and service files
+struct Services {
+    /// A map of service ids to uris
+    uris: HashMap<String, String>,
+
+    /// The versions of the service files that the uris were loaded from
+    file_versions: FileVersions,
+}
+
+/// Uses static config files for service discovery
 pub struct FileServiceDiscoveryAdapter {
     /// The adapter config
     config: Config,
+
+    /// The currently loaded services
+    services: RwLock<Services>,
+}
+
+impl FileServiceDiscoveryAdapter {
+    /// Creates a new FileServiceDiscoveryAdapter with the specified config
+    ///
+    /// # Arguments
+    /// - `config`: the config to use
+    pub fn from_config(config: Config) -> Result<Self, ServiceDiscoveryAdapterError> {
+        let services = Self::load(&config)?;
+
+        Ok(Self {
+            config,
+            services: RwLock::new(services),
+        })
+    }
+
+    /// Gets the service files in the configured service directories in the order in which they're merged
+    ///
+    /// # Arguments
+    /// - `config`: the adapter config
+    fn get_file_versions(config: &Config) -> Result<FileVersions, ServiceDiscoveryAdapterError> {
+        let mut file_versions = Vec::new();
+        for directory in config.service_directories.iter() {
+            let entries = fs::read_dir(directory).map_err(|e| {
+                ServiceDiscoveryAdapterError::io(format!("Cannot read directory {directory}: {e}"))
+            })?;
+
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.is_file()
+                        && path.extension().and_then(|ext| ext.to_str()) == Some(SERVICE_FILE_EXT)
+                })
+                .collect();
+            paths.sort();
+
+            file_versions.extend(paths.into_iter().map(|path| {
+                let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+                (path, modified)
+            }));
+        }
+
+        Ok(file_versions)
+    }
+
+    /// Reads the services from a service file
+    ///
+    /// # Arguments
+    /// - `path`: the path of the service file
+    fn read_service_file(path: &Path) -> Result<ServiceFile, ServiceDiscoveryAdapterError> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            ServiceDiscoveryAdapterError::io(format!("Cannot read {}: {e}", path.display()))
+        })?;
+
+        serde_json::from_str(&contents).map_err(|e| {
+            ServiceDiscoveryAdapterError::deserialize(format!(
+                "Invalid service file {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Loads the services from the config and every service file
+    ///
+    /// # Arguments
+    /// - `config`: the adapter config
+    fn load(config: &Config) -> Result<Services, ServiceDiscoveryAdapterError> {
+        let file_versions = Self::get_file_versions(config)?;
+
+        let mut uris = config.services.clone();
+        for (path, _) in file_versions.iter() {
+            uris.extend(Self::read_service_file(path)?.services);
+        }
+
+        Ok(Services {
+            uris,
+            file_versions,
+        })
+    }
+
+    /// Reloads the services if any service files were added, removed, or modified since they were last loaded
+    fn reload_if_changed(&self) -> Result<(), ServiceDiscoveryAdapterError> {
+        let file_versions = Self::get_file_versions(&self.config)?;
+        if file_versions == self.services.read().unwrap().file_versions {
+            return Ok(());
+        }
+
+        let services = Self::load(&self.config)?;
+        info!(
+            "Reloaded service files. Service count is now {}",
+            services.uris.len()
+        );
+
+        *self.services.write().unwrap() = services;
+
+        Ok(())
+    }
+
+    /// Finds the URI for a service id.
+    /// Exact matches take precedence over wildcard matches,
+    /// and otherwise the wildcard pattern with the most literal characters is used.
+    ///
+    /// # Arguments
+    /// - `uris`: a map of service ids or wildcard patterns to uris
+    /// - `id`: the service identifier
+    fn find_uri<'a>(uris: &'a HashMap<String, String>, id: &str) -> Option<&'a String> {
+        if let Some(uri) = uris.get(id) {
+            return Some(uri);
+        }
+
+        uris.iter()
+            .filter(|(pattern, _)| pattern.contains(WILDCARD) && Self::matches(pattern, id))
+            // Ties are broken by the pattern itself so that the result doesn't depend on the map's ordering
+            .max_by(|(a, _), (b, _)| {
+                let literal_len = |pattern: &str| pattern.len() - pattern.matches(WILDCARD).count();
+                literal_len(a.as_str())
+                    .cmp(&literal_len(b.as_str()))
+                    .then_with(|| b.cmp(a))
+            })
+            .map(|(_, uri)| uri)
+    }
+
+    /// Checks whether a service id matches a pattern where `*` matches any sequence of characters
+    ///
+    /// # Arguments
+    /// - `pattern`: the pattern to match
+    /// - `id`: the service identifier
+    fn matches(pattern: &str, id: &str) -> bool {
+        let parts: Vec<&str> = pattern.split(WILDCARD).collect();
+        let (first, last) = match parts[..] {
+            [_] => return pattern == id,
+            [first, .., last] => (first, last),
+            [] => return false,
+        };
+
+        if id.len() < first.len() + last.len() || !id.starts_with(first) || !id.ends_with(last) {
+            return false;
+        }
+
+        let mut remaining = &id[first.len()..id.len() - last.len()];
+        for part in parts[1..parts.len() - 1].iter() {
+            match remaining.find(part) {
+                Some(index) => remaining = &remaining[index + part.len()..],
+                None => return false,
+            }
+        }
+
+        true
+    }
 }
 
 #[async_trait]
@@ -31,7 +210,7 @@ impl ServiceDiscoveryAdapter for FileServiceDiscoveryAdapter {
             ServiceDiscoveryAdapterError::deserialize,
         )?;
 
-        Ok(Self { config })
+        Self::from_config(config)
     }
 
     /// Gets the name of this adapter. Used for diagnostic purposes.
@@ -47,10 +226,132 @@ impl ServiceDiscoveryAdapter for FileServiceDiscoveryAdapter {
         &self,
         id: &'a str,
     ) -> Result<String, ServiceDiscoveryAdapterError> {
-        self.config
-            .services
-            .get(id)
+        if self.config.hot_reload {
+            if let Err(e) = self.reload_if_changed() {
+                warn!("Failed to reload service files, using previous services: {e}");
+            }
+        }
+
+        Self::find_uri(&self.services.read().unwrap().uris, id)
             .cloned()
             .ok_or(ServiceDiscoveryAdapterErrorKind::NotFound.into())
     }
 }
+
+#[cfg(test)]
+mod file_service_discovery_adapter_tests {
+    use super::*;
+
+    use freyja_test_common::fixtures::TempDirFixture;
+
+    /// A directory of service files in the temp directory which is deleted when dropped
+    struct ServiceDirectory {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl ServiceDirectory {
+        fn new(name: &str) -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join(name);
+            fs::create_dir_all(&path).unwrap();
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+
+        fn write(&self, file_name: &str, contents: &str) {
+            fs::write(self.path.join(file_name), contents).unwrap();
+        }
+
+        fn config(&self, hot_reload: bool) -> Config {
+            Config {
+                services: HashMap::from([(
+                    String::from("sdv.ibeji/invehicle_digital_twin/1.0"),
+                    String::from("http://0.0.0.0:5010"), // Devskim: ignore DS137138
+                )]),
+                service_directories: vec![self.path.to_string_lossy().into_owned()],
+                hot_reload,
+            }
+        }
+    }
+
+    #[test]
+    fn matches_supports_wildcards() {
+        assert!(FileServiceDiscoveryAdapter::matches("a/b/c", "a/b/c"));
+        assert!(!FileServiceDiscoveryAdapter::matches("a/b/c", "a/b/d"));
+        assert!(FileServiceDiscoveryAdapter::matches("a/*/c", "a/b/c"));
+        assert!(FileServiceDiscoveryAdapter::matches("a/*", "a/b/c"));
+        assert!(FileServiceDiscoveryAdapter::matches("*", "a/b/c"));
+        assert!(FileServiceDiscoveryAdapter::matches("*/b/*", "a/b/c"));
+        assert!(!FileServiceDiscoveryAdapter::matches("a/*/c", "a/b/d"));
+        assert!(!FileServiceDiscoveryAdapter::matches("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn find_uri_prefers_exact_then_most_specific_match() {
+        let uris = HashMap::from([
+            (String::from("sdv.ibeji/*"), String::from("wildcard")),
+            (String::from("sdv.ibeji/*/1.0"), String::from("specific")),
+            (String::from("sdv.ibeji/dt/1.0"), String::from("exact")),
+        ]);
+
+        let find = |id| FileServiceDiscoveryAdapter::find_uri(&uris, id).map(String::as_str);
+        assert_eq!(find("sdv.ibeji/dt/1.0"), Some("exact"));
+        assert_eq!(find("sdv.ibeji/other/1.0"), Some("specific"));
+        assert_eq!(find("sdv.ibeji/other/2.0"), Some("wildcard"));
+        assert_eq!(find("sdv.freyja/mapping/1.0"), None);
+    }
+
+    #[tokio::test]
+    async fn service_files_are_merged_in_file_name_order() {
+        let directory = ServiceDirectory::new("file_service_discovery_adapter_merge");
+        directory.write("b.json", r#"{"services": {"shared": "from_b", "b": "b"}}"#);
+        directory.write("a.json", r#"{"services": {"shared": "from_a", "a": "a"}}"#);
+        directory.write("ignored.txt", "not a service file");
+
+        let uut = FileServiceDiscoveryAdapter::from_config(directory.config(false)).unwrap();
+        assert_eq!(uut.get_service_uri("shared").await.unwrap(), "from_b");
+        assert_eq!(uut.get_service_uri("a").await.unwrap(), "a");
+        assert_eq!(uut.get_service_uri("b").await.unwrap(), "b");
+        assert_eq!(
+            uut.get_service_uri("sdv.ibeji/invehicle_digital_twin/1.0")
+                .await
+                .unwrap(),
+            "http://0.0.0.0:5010" // Devskim: ignore DS137138
+        );
+    }
+
+    #[test]
+    fn from_config_returns_err_for_invalid_service_file() {
+        let directory = ServiceDirectory::new("file_service_discovery_adapter_invalid");
+        directory.write("a.json", "not json");
+
+        let result = FileServiceDiscoveryAdapter::from_config(directory.config(false));
+        assert!(result.is_err_and(|e| e.kind() == ServiceDiscoveryAdapterErrorKind::Deserialize));
+    }
+
+    #[tokio::test]
+    async fn hot_reload_picks_up_new_service_files() {
+        let directory = ServiceDirectory::new("file_service_discovery_adapter_hot_reload");
+        let uut = FileServiceDiscoveryAdapter::from_config(directory.config(true)).unwrap();
+        assert!(uut
+            .get_service_uri("new")
+            .await
+            .is_err_and(|e| e.kind() == ServiceDiscoveryAdapterErrorKind::NotFound));
+
+        directory.write("new.json", r#"{"services": {"new": "uri"}}"#);
+        assert_eq!(uut.get_service_uri("new").await.unwrap(), "uri");
+    }
+
+    #[tokio::test]
+    async fn hot_reload_keeps_previous_services_when_file_is_invalid() {
+        let directory = ServiceDirectory::new("file_service_discovery_adapter_reload_invalid");
+        directory.write("a.json", r#"{"services": {"a": "a"}}"#);
+        let uut = FileServiceDiscoveryAdapter::from_config(directory.config(true)).unwrap();
+
+        directory.write("b.json", "not json");
+        assert_eq!(uut.get_service_uri("a").await.unwrap(), "a");
+    }
+}
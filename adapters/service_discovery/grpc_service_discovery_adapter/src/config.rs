@@ -2,8 +2,12 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use freyja_common::grpc_utils::GrpcClientConfig;
+
 /// Configuration for the GRPCServiceDiscoveryAdapter
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -15,4 +19,30 @@ pub struct Config {
 
     /// The duration between retries in milliseconds
     pub retry_interval_ms: u64,
+
+    /// How long discovered URIs are cached in milliseconds.
+    /// If this is 0, URIs are not cached
+    #[serde(default)]
+    pub cache_ttl_ms: u64,
+
+    /// The namespace and version to discover for specific services, keyed by service id
+    #[serde(default)]
+    pub services: HashMap<String, ServiceSelection>,
+
+    /// The headers, bearer token, and deadline of requests sent to the service discovery system
+    #[serde(flatten)]
+    pub grpc: GrpcClientConfig,
+}
+
+/// Selects which namespace and version of a service to discover.
+/// Values which are set here take precedence over the ones in the service id.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ServiceSelection {
+    /// The namespace to discover the service in
+    #[serde(default)]
+    pub namespace: Option<String>,
+
+    /// The version of the service to discover
+    #[serde(default)]
+    pub version: Option<String>,
 }
@@ -2,32 +2,213 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use tonic::{transport::Channel, Code, Request};
+use log::{debug, warn};
+use tonic::{Code, Status};
 
 use service_discovery_proto::service_registry::v1::{
-    service_registry_client::ServiceRegistryClient, DiscoverRequest,
+    service_registry_client::ServiceRegistryClient, DiscoverByNamespaceRequest, DiscoverRequest,
+    ServiceMetadata,
 };
 
 use crate::config::Config;
 use freyja_build_common::config_file_stem;
 use freyja_common::{
-    config_utils, out_dir,
-    retry_utils::execute_with_retry,
+    config_utils,
+    grpc_utils::{self, GrpcChannel, GrpcInterceptor, GrpcRequestContext},
+    out_dir,
+    retry_utils::RetryPolicy,
     service_discovery_adapter::{
         ServiceDiscoveryAdapter, ServiceDiscoveryAdapterError, ServiceDiscoveryAdapterErrorKind,
     },
 };
 
+/// A discovered URI which is cached until it expires
+struct CacheEntry {
+    /// The discovered URI
+    uri: String,
+
+    /// The time after which the URI must be discovered again
+    expires_at: Instant,
+}
+
 /// Interfaces with a service discovery system to perform service discovery
 pub struct GRPCServiceDiscoveryAdapter {
     /// The adapter config
     config: Config,
 
     /// The service discovery client
-    client: ServiceRegistryClient<Channel>,
+    client: ServiceRegistryClient<GrpcChannel>,
+
+    /// The cached URIs, keyed by service id
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl GRPCServiceDiscoveryAdapter {
+    /// Creates a new adapter with the specified config and client
+    ///
+    /// # Arguments
+    /// - `config`: the adapter config
+    /// - `client`: the service discovery client
+    fn with_client(config: Config, client: ServiceRegistryClient<GrpcChannel>) -> Self {
+        Self {
+            config,
+            client,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates the policy for retrying requests to the service discovery system.
+    /// Only retryable statuses such as an unavailable server are retried.
+    ///
+    /// # Arguments
+    /// - `context`: context for logging retries
+    fn retry_policy(&self, context: &str) -> RetryPolicy<Status> {
+        RetryPolicy::fixed(Duration::from_millis(self.config.retry_interval_ms))
+            .max_retries(self.config.max_retries)
+            .retry_if_retryable()
+            .context(context)
+    }
+
+    /// Creates the request for discovering a service.
+    /// Service ids are either in the format `{namespace}/{name}/{version}`,
+    /// or just `{name}` if the namespace and version are selected in the config.
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    fn create_request(&self, id: &str) -> Result<DiscoverRequest, ServiceDiscoveryAdapterError> {
+        let pieces = id.split('/').collect::<Vec<_>>();
+        let (namespace, name, version) = match pieces[..] {
+            [namespace, name, version] => (Some(namespace), name, Some(version)),
+            [name] => (None, name, None),
+            _ => return Err(ServiceDiscoveryAdapterErrorKind::InvalidId.into()),
+        };
+
+        let selection = self.config.services.get(id);
+        let namespace = selection.and_then(|s| s.namespace.as_deref()).or(namespace);
+        let version = selection.and_then(|s| s.version.as_deref()).or(version);
+
+        match (namespace, version) {
+            (Some(namespace), Some(version)) => Ok(DiscoverRequest {
+                namespace: namespace.into(),
+                name: name.into(),
+                version: version.into(),
+            }),
+            _ => Err(ServiceDiscoveryAdapterError::invalid_id(format!(
+                "No namespace and version are selected for service {id}"
+            ))),
+        }
+    }
+
+    /// Gets a URI from the cache if it hasn't expired
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    fn get_cached_uri(&self, id: &str) -> Option<String> {
+        let cache = self.cache.lock().unwrap();
+        cache
+            .get(id)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.uri.clone())
+    }
+
+    /// Adds a URI to the cache if caching is enabled
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    /// - `uri`: the discovered URI
+    fn cache_uri(&self, id: &str, uri: &str) {
+        if self.config.cache_ttl_ms == 0 {
+            return;
+        }
+
+        let entry = CacheEntry {
+            uri: uri.to_string(),
+            expires_at: Instant::now() + Duration::from_millis(self.config.cache_ttl_ms),
+        };
+
+        self.cache.lock().unwrap().insert(id.to_string(), entry);
+    }
+
+    /// Discovers the URI for a single service
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    /// - `request`: the request for discovering the service
+    async fn discover(
+        &self,
+        id: &str,
+        request: DiscoverRequest,
+    ) -> Result<String, ServiceDiscoveryAdapterError> {
+        // Services which aren't registered aren't retried because the status isn't retryable
+        let context = GrpcRequestContext::new(&self.config.grpc);
+        let uri = match self
+            .retry_policy("Retrieving service uri")
+            .execute(|| async {
+                self.client
+                    .clone()
+                    .discover(context.request(request.clone())?)
+                    .await
+            })
+            .await
+        {
+            Ok(response) => {
+                response
+                    .into_inner()
+                    .service
+                    .ok_or_else(|| {
+                        ServiceDiscoveryAdapterError::communication(format!(
+                            "Cannot discover uri for service {id}"
+                        ))
+                    })?
+                    .uri
+            }
+            Err(status) if status.code() == Code::NotFound => {
+                return Err(ServiceDiscoveryAdapterError::not_found(status))
+            }
+            Err(e) => return Err(ServiceDiscoveryAdapterError::communication(e)),
+        };
+
+        self.cache_uri(id, &uri);
+
+        Ok(uri)
+    }
+
+    /// Discovers every service registered in a namespace with a single request
+    ///
+    /// # Arguments
+    /// - `namespace`: the namespace to discover services in
+    async fn discover_namespace(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<ServiceMetadata>, ServiceDiscoveryAdapterError> {
+        let request = DiscoverByNamespaceRequest {
+            namespace: namespace.into(),
+        };
+
+        let context = GrpcRequestContext::new(&self.config.grpc);
+        match self
+            .retry_policy("Retrieving service uris by namespace")
+            .execute(|| async {
+                self.client
+                    .clone()
+                    .discover_by_namespace(context.request(request.clone())?)
+                    .await
+            })
+            .await
+        {
+            Ok(response) => Ok(response.into_inner().services),
+            // A namespace without any services isn't an error
+            Err(status) if status.code() == Code::NotFound => Ok(Vec::new()),
+            Err(e) => Err(ServiceDiscoveryAdapterError::communication(e)),
+        }
+    }
 }
 
 #[async_trait]
@@ -42,21 +223,21 @@ impl ServiceDiscoveryAdapter for GRPCServiceDiscoveryAdapter {
             ServiceDiscoveryAdapterError::deserialize,
         )?;
 
-        let client = futures::executor::block_on(async {
-            execute_with_retry(
-                config.max_retries,
-                Duration::from_millis(config.retry_interval_ms),
-                || async {
-                    ServiceRegistryClient::connect(config.uri.clone())
-                        .await
-                        .map_err(ServiceDiscoveryAdapterError::communication)
-                },
-                Some("Connecting to Chariott Service Discovery".into()),
-            )
-            .await
+        let interceptor = GrpcInterceptor::from_config(&config.grpc)
+            .map_err(ServiceDiscoveryAdapterError::deserialize)?;
+
+        let channel = futures::executor::block_on(async {
+            RetryPolicy::fixed(Duration::from_millis(config.retry_interval_ms))
+                .max_retries(config.max_retries)
+                .retry_if_retryable()
+                .context("Connecting to Chariott Service Discovery")
+                .execute(|| grpc_utils::connect(config.uri.clone()))
+                .await
+                .map_err(ServiceDiscoveryAdapterError::communication)
         })?;
 
-        Ok(Self { config, client })
+        let client = ServiceRegistryClient::with_interceptor(channel, interceptor);
+        Ok(Self::with_client(config, client))
     }
 
     /// Gets the name of this adapter. Used for diagnostic purposes.
@@ -64,7 +245,8 @@ impl ServiceDiscoveryAdapter for GRPCServiceDiscoveryAdapter {
         String::from("ChariottServiceDiscoveryAdapter")
     }
 
-    /// Gets the URI for the requested service
+    /// Gets the URI for the requested service.
+    /// Cached URIs are returned without contacting the service discovery system.
     ///
     /// # Arguments
     /// - `id`: the service identifier
@@ -72,57 +254,184 @@ impl ServiceDiscoveryAdapter for GRPCServiceDiscoveryAdapter {
         &self,
         id: &'a str,
     ) -> Result<String, ServiceDiscoveryAdapterError> {
-        let pieces = id.split('/').collect::<Vec<_>>();
-        if pieces.len() != 3 {
-            return Err(ServiceDiscoveryAdapterErrorKind::InvalidId.into());
+        if let Some(uri) = self.get_cached_uri(id) {
+            return Ok(uri);
         }
 
-        let request = DiscoverRequest {
-            namespace: pieces[0].into(),
-            name: pieces[1].into(),
-            version: pieces[2].into(),
-        };
+        let request = self.create_request(id)?;
+        self.discover(id, request).await
+    }
 
-        let result = execute_with_retry(
-            self.config.max_retries,
-            Duration::from_millis(self.config.retry_interval_ms),
-            || async {
-                match self
-                    .client
-                    .clone()
-                    .discover(Request::new(request.clone()))
-                    .await
-                {
-                    Ok(response) => {
-                        let uri = response
-                            .into_inner()
-                            .service
-                            .ok_or_else(|| {
-                                ServiceDiscoveryAdapterError::communication(format!(
-                                    "Cannot discover uri for service {id}"
-                                ))
-                            })?
-                            .uri;
-                        Ok(Ok(uri))
+    /// Gets the URIs for several services at once, keyed by service identifier.
+    /// Services which aren't cached are discovered with one request per namespace.
+    /// If a namespace can't be discovered, its services are discovered individually instead.
+    /// Services which can't be discovered are omitted from the result.
+    ///
+    /// # Arguments
+    /// - `ids`: the service identifiers
+    async fn get_service_uris<'a>(&self, ids: &[&'a str]) -> HashMap<String, String> {
+        let mut uris = HashMap::new();
+        let mut requests_by_namespace: HashMap<String, Vec<(&str, DiscoverRequest)>> =
+            HashMap::new();
+
+        for &id in ids {
+            if let Some(uri) = self.get_cached_uri(id) {
+                uris.insert(id.to_string(), uri);
+                continue;
+            }
+
+            match self.create_request(id) {
+                Ok(request) => requests_by_namespace
+                    .entry(request.namespace.clone())
+                    .or_default()
+                    .push((id, request)),
+                Err(e) => debug!("Cannot discover service {id}: {e}"),
+            }
+        }
+
+        for (namespace, requests) in requests_by_namespace {
+            match self.discover_namespace(&namespace).await {
+                Ok(services) => {
+                    for (id, request) in requests {
+                        let service = services.iter().find(|service| {
+                            service.name == request.name && service.version == request.version
+                        });
+
+                        match service {
+                            Some(service) => {
+                                self.cache_uri(id, &service.uri);
+                                uris.insert(id.to_string(), service.uri.clone());
+                            }
+                            None => {
+                                debug!("Service {id} is not registered in namespace {namespace}")
+                            }
+                        }
                     }
-                    // This branch returns Ok(Err(_)) to indicate to the execute_with_retry wrapper that processing should stop
-                    Err(status) if status.code() == Code::NotFound => {
-                        Ok(Err(ServiceDiscoveryAdapterError::not_found(status)))
+                }
+                Err(e) => {
+                    warn!("Failed to discover services in namespace {namespace}: {e}. Discovering them individually...");
+                    for (id, request) in requests {
+                        match self.discover(id, request).await {
+                            Ok(uri) => {
+                                uris.insert(id.to_string(), uri);
+                            }
+                            Err(e) => debug!("Cannot discover service {id}: {e}"),
+                        }
                     }
-                    // This branch returns Err(_) to indicate to the execute_with_retry wrapper that the request should be retried
-                    Err(e) => Err(ServiceDiscoveryAdapterError::communication(e)),
                 }
-            },
-            Some("Retrieving service uri".into()),
-        )
-        .await;
-
-        // This is implemented with the `flatten` method in nightly rust toolchains, which should be used here once stable
-        // See https://doc.rust-lang.org/std/result/enum.Result.html#method.flatten
-        match result {
-            Ok(Ok(val)) => Ok(val),
-            Ok(Err(e)) => Err(e),
-            Err(e) => Err(e),
+            }
+        }
+
+        uris
+    }
+}
+
+#[cfg(test)]
+mod grpc_service_discovery_adapter_tests {
+    use super::*;
+
+    use tonic::transport::Channel;
+
+    use crate::config::ServiceSelection;
+
+    const CHARIOTT_URI: &str = "http://0.0.0.0:50000"; // Devskim: ignore DS137138
+
+    fn create_adapter(
+        cache_ttl_ms: u64,
+        services: HashMap<String, ServiceSelection>,
+    ) -> GRPCServiceDiscoveryAdapter {
+        let config = Config {
+            uri: CHARIOTT_URI.to_string(),
+            max_retries: 1,
+            retry_interval_ms: 0,
+            cache_ttl_ms,
+            services,
+            grpc: Default::default(),
+        };
+
+        let channel = Channel::from_static(CHARIOTT_URI).connect_lazy();
+        let client = ServiceRegistryClient::with_interceptor(channel, GrpcInterceptor::default());
+        GRPCServiceDiscoveryAdapter::with_client(config, client)
+    }
+
+    #[tokio::test]
+    async fn create_request_uses_namespace_and_version_from_id() {
+        let uut = create_adapter(0, HashMap::new());
+
+        let request = uut.create_request("sdv.ibeji/digital_twin/1.0").unwrap();
+        assert_eq!(request.namespace, "sdv.ibeji");
+        assert_eq!(request.name, "digital_twin");
+        assert_eq!(request.version, "1.0");
+    }
+
+    #[tokio::test]
+    async fn create_request_prefers_selection_from_config() {
+        let services = HashMap::from([
+            (
+                String::from("sdv.ibeji/digital_twin/1.0"),
+                ServiceSelection {
+                    namespace: None,
+                    version: Some(String::from("2.0")),
+                },
+            ),
+            (
+                String::from("mapping"),
+                ServiceSelection {
+                    namespace: Some(String::from("sdv.freyja")),
+                    version: Some(String::from("1.0")),
+                },
+            ),
+        ]);
+        let uut = create_adapter(0, services);
+
+        let request = uut.create_request("sdv.ibeji/digital_twin/1.0").unwrap();
+        assert_eq!(request.namespace, "sdv.ibeji");
+        assert_eq!(request.version, "2.0");
+
+        let request = uut.create_request("mapping").unwrap();
+        assert_eq!(request.namespace, "sdv.freyja");
+        assert_eq!(request.name, "mapping");
+        assert_eq!(request.version, "1.0");
+    }
+
+    #[tokio::test]
+    async fn create_request_returns_err_for_invalid_id() {
+        let uut = create_adapter(0, HashMap::new());
+
+        for id in ["sdv.ibeji/digital_twin", "mapping", "a/b/c/d"] {
+            let result = uut.create_request(id);
+            assert!(result.is_err_and(|e| e.kind() == ServiceDiscoveryAdapterErrorKind::InvalidId));
         }
     }
+
+    #[tokio::test]
+    async fn get_service_uri_returns_cached_uri() {
+        const ID: &str = "sdv.ibeji/digital_twin/1.0";
+        const URI: &str = "http://0.0.0.0:5010"; // Devskim: ignore DS137138
+
+        let uut = create_adapter(60000, HashMap::new());
+        uut.cache_uri(ID, URI);
+
+        assert_eq!(uut.get_service_uri(ID).await.unwrap(), URI);
+        assert_eq!(
+            uut.get_service_uris(&[ID]).await,
+            HashMap::from([(ID.to_string(), URI.to_string())])
+        );
+    }
+
+    #[tokio::test]
+    async fn cache_uri_does_nothing_when_caching_is_disabled() {
+        const ID: &str = "sdv.ibeji/digital_twin/1.0";
+
+        let uut = create_adapter(0, HashMap::new());
+        uut.cache_uri(ID, "http://0.0.0.0:5010"); // Devskim: ignore DS137138
+
+        assert!(uut.get_cached_uri(ID).is_none());
+    }
+
+    #[tokio::test]
+    async fn get_service_uris_omits_invalid_ids() {
+        let uut = create_adapter(60000, HashMap::new());
+        assert!(uut.get_service_uris(&["invalid"]).await.is_empty());
+    }
 }
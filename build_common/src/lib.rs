@@ -2,7 +2,15 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::{env, fs, path::Path};
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{ErrorKind, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
 
 const OUT_DIR: &str = "OUT_DIR";
 const CONFIG_FILE_STEM: &str = "CONFIG_FILE_STEM";
@@ -11,6 +19,20 @@ const DEFAULT_CONFIG_EXT: &str = ".default.json";
 
 pub const SERDE_DERIVE_ATTRIBUTE: &str = "#[derive(serde::Deserialize, serde::Serialize)]";
 
+/// The environment variable for the directory in which remote proto files are cached.
+/// Cached proto files are used instead of downloading them again,
+/// so the directory can also be checked in to vendor the proto files.
+/// This must be an absolute path since build scripts run in the directory of their own package
+pub const PROTO_CACHE_DIR_ENV: &str = "FREYJA_PROTO_CACHE_DIR";
+
+/// The environment variable which prevents remote proto files from being downloaded when set to `1` or `true`.
+/// Proto files must then be in the proto cache
+pub const PROTO_OFFLINE_ENV: &str = "FREYJA_PROTO_OFFLINE";
+
+/// The name of the file in the proto cache which pins the SHA-256 checksum of each cached proto file.
+/// It uses the same format as the output of `sha256sum`, so the cache can also be verified with `sha256sum -c`
+const PROTO_CHECKSUMS_FILENAME: &str = "checksums.sha256";
+
 /// Expands to `env!("CONFIG_FILE_STEM")`.
 /// Since we cannot use a constant in the `env!` macro,
 /// this is the next best option to avoid duplicating the `"CONFIG_FILE_STEM"` literal.
@@ -53,6 +75,10 @@ pub fn copy_config(config_file_stem: &str) {
 }
 
 /// Compiles proto files from a remote source, such as an external repo.
+/// If the `FREYJA_PROTO_CACHE_DIR` environment variable is set, the proto file is taken from that directory
+/// and only downloaded if it isn't there yet. Its checksum is pinned in the cache the first time it's used
+/// and verified by every later build.
+/// If the `FREYJA_PROTO_OFFLINE` environment variable is also set, the proto file is never downloaded.
 ///
 /// # Arguments
 /// - `url`: the url for retrieving the proto file.
@@ -61,27 +87,96 @@ pub fn copy_config(config_file_stem: &str) {
 pub fn compile_remote_proto(
     url: String,
     message_attributes: &[(&str, &str)],
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Retrieve file and write to OUT_DIR
-    let out_dir = env::var(OUT_DIR).unwrap();
+) -> Result<(), Box<dyn Error>> {
+    compile_remote_proto_in(
+        Path::new(&env::var(OUT_DIR).unwrap()),
+        url,
+        message_attributes,
+    )
+}
+
+/// Compiles proto files from a remote source for a specific interface version.
+/// The generated code is written to a subdirectory of `OUT_DIR` named after the version module,
+/// so several versions of the same interfaces can be generated side by side
+/// and included with the `include_versioned_proto!` macro from `proto_common`.
+///
+/// # Arguments
+/// - `url`: the url for retrieving the proto file.
+/// - `version_module`: the name of the module for the interface version, as returned by `version_module_name`.
+/// - `message_attributes`: a list of message attributes to add.
+pub fn compile_versioned_remote_proto(
+    url: String,
+    version_module: &str,
+    message_attributes: &[(&str, &str)],
+) -> Result<(), Box<dyn Error>> {
+    let out_dir = Path::new(&env::var(OUT_DIR).unwrap()).join(version_module);
+    fs::create_dir_all(&out_dir)?;
+
+    compile_remote_proto_in(&out_dir, url, message_attributes)
+}
+
+/// Gets the interface versions of a service which were selected with cargo features of the package being built.
+/// The feature for a version is named `{service}-{version}` with each `.` replaced by `_`, such as `ibeji-0_1_0`.
+///
+/// # Arguments
+/// - `service`: the service name, such as `ibeji`
+/// - `supported_versions`: the interface versions of the service which can be selected
+pub fn selected_versions<'a>(service: &str, supported_versions: &[&'a str]) -> Vec<&'a str> {
+    supported_versions
+        .iter()
+        .filter(|version| {
+            env::var_os(format!(
+                "CARGO_FEATURE_{}",
+                version_feature_suffix(service, version)
+            ))
+            .is_some()
+        })
+        .copied()
+        .collect()
+}
+
+/// Gets the name of the module for the generated code of an interface version, such as `ibeji_v0_1_0`
+///
+/// # Arguments
+/// - `service`: the service name, such as `ibeji`
+/// - `version`: the interface version, such as `0.1.0`
+pub fn version_module_name(service: &str, version: &str) -> String {
+    format!("{service}_v{}", version.replace('.', "_"))
+}
+
+/// Gets the part of the `CARGO_FEATURE_*` environment variable which identifies the feature for an interface version
+///
+/// # Arguments
+/// - `service`: the service name
+/// - `version`: the interface version
+fn version_feature_suffix(service: &str, version: &str) -> String {
+    format!("{service}_{version}")
+        .to_uppercase()
+        .replace(['.', '-'], "_")
+}
+
+/// Compiles proto files from a remote source, writing the generated code to the provided directory
+///
+/// # Arguments
+/// - `out_dir`: the directory for the generated code
+/// - `url`: the url for retrieving the proto file.
+/// - `message_attributes`: a list of message attributes to add.
+fn compile_remote_proto_in(
+    out_dir: &Path,
+    url: String,
+    message_attributes: &[(&str, &str)],
+) -> Result<(), Box<dyn Error>> {
+    // Retrieve file and write to the output directory
     let filename = url.rsplit('/').next().unwrap_or_default();
-    let proto_dir = Path::new(&out_dir).join("proto");
+    let proto_dir = out_dir.join("proto");
 
     std::fs::create_dir_all(&proto_dir)?;
 
     let target = proto_dir.join(filename);
-
-    match ureq::get(&url).call() {
-        Ok(response) => {
-            let mut out_file = fs::File::create(&target)?;
-
-            std::io::copy(&mut response.into_reader(), &mut out_file)?;
-        }
-        Err(e) => panic!("Unable to retrieve remote proto file: {e}"),
-    }
+    fs::write(&target, retrieve_remote_proto(&url)?)?;
 
     // Compile protos
-    let mut builder = tonic_build::configure();
+    let mut builder = tonic_build::configure().out_dir(out_dir);
 
     for (msg, attr) in message_attributes {
         builder = builder.message_attribute(msg, attr);
@@ -91,3 +186,259 @@ pub fn compile_remote_proto(
 
     Ok(())
 }
+
+/// Retrieves the contents of a remote proto file, using the proto cache if it's configured.
+/// Includes `cargo:rerun-if-env-changed` instructions for the proto cache environment variables.
+///
+/// # Arguments
+/// - `url`: the url for retrieving the proto file
+fn retrieve_remote_proto(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    println!("cargo:rerun-if-env-changed={PROTO_CACHE_DIR_ENV}");
+    println!("cargo:rerun-if-env-changed={PROTO_OFFLINE_ENV}");
+
+    let offline = env::var(PROTO_OFFLINE_ENV)
+        .is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
+
+    match env::var_os(PROTO_CACHE_DIR_ENV) {
+        Some(dir) => ProtoCache::new(PathBuf::from(dir))?.retrieve(url, offline, download),
+        None if offline => Err(format!(
+            "Cannot retrieve remote proto file {url} because {PROTO_OFFLINE_ENV} is set without {PROTO_CACHE_DIR_ENV}"
+        )
+        .into()),
+        None => download(url),
+    }
+}
+
+/// Downloads a remote file
+///
+/// # Arguments
+/// - `url`: the url of the file
+fn download(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| format!("Unable to retrieve remote proto file {url}: {e}"))?;
+
+    let mut contents = Vec::new();
+    response.into_reader().read_to_end(&mut contents)?;
+
+    Ok(contents)
+}
+
+/// A directory of cached remote proto files.
+/// Each proto file is stored at the path of its url without the scheme,
+/// such as `raw.githubusercontent.com/eclipse-ibeji/ibeji/0.1.1/interfaces/...`.
+/// The SHA-256 checksum of each file is pinned in the `checksums.sha256` file the first time the file is used,
+/// and later builds fail if the file no longer matches its pinned checksum.
+/// Checksums can also be pinned ahead of time by adding them to `checksums.sha256`.
+struct ProtoCache {
+    /// The cache directory
+    dir: PathBuf,
+}
+
+impl ProtoCache {
+    /// Creates a new ProtoCache
+    ///
+    /// # Arguments
+    /// - `dir`: the cache directory, which must be an absolute path
+    fn new(dir: PathBuf) -> Result<Self, Box<dyn Error>> {
+        if !dir.is_absolute() {
+            return Err(format!(
+                "{PROTO_CACHE_DIR_ENV} must be an absolute path, but it is {}",
+                dir.display()
+            )
+            .into());
+        }
+
+        Ok(Self { dir })
+    }
+
+    /// Retrieves the contents of a remote proto file from the cache,
+    /// downloading it and adding it to the cache if it's not there yet and downloads are allowed.
+    /// Returns an error if the contents don't match the pinned checksum.
+    ///
+    /// # Arguments
+    /// - `url`: the url for retrieving the proto file
+    /// - `offline`: whether downloads are prevented
+    /// - `download`: the function which downloads the proto file
+    fn retrieve(
+        &self,
+        url: &str,
+        offline: bool,
+        download: impl FnOnce(&str) -> Result<Vec<u8>, Box<dyn Error>>,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let relative_path = Self::relative_path(url)?;
+        let path = self.dir.join(&relative_path);
+
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            Err(_) if offline => {
+                return Err(format!(
+                    "Remote proto file {url} is not in the proto cache at {} and {PROTO_OFFLINE_ENV} is set",
+                    path.display()
+                )
+                .into())
+            }
+            Err(_) => {
+                let contents = download(url)?;
+                self.verify(&relative_path, &contents)?;
+
+                // Write to a temporary file first so that concurrent builds never read a partial file
+                fs::create_dir_all(path.parent().unwrap())?;
+                let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+                fs::write(&temp_path, &contents)?;
+                fs::rename(&temp_path, &path)?;
+
+                return Ok(contents);
+            }
+        };
+
+        self.verify(&relative_path, &contents)?;
+
+        Ok(contents)
+    }
+
+    /// Gets the path of a remote proto file relative to the cache directory
+    ///
+    /// # Arguments
+    /// - `url`: the url for retrieving the proto file
+    fn relative_path(url: &str) -> Result<String, Box<dyn Error>> {
+        let relative_path = url.split_once("://").map_or(url, |(_, path)| path);
+        let is_valid = Path::new(relative_path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        if relative_path.is_empty() || !is_valid {
+            return Err(format!("Cannot cache remote proto file with url {url}").into());
+        }
+
+        Ok(relative_path.to_string())
+    }
+
+    /// Verifies that the contents of a proto file match its pinned checksum,
+    /// pinning the checksum of the contents if the file doesn't have one yet
+    ///
+    /// # Arguments
+    /// - `relative_path`: the path of the proto file relative to the cache directory
+    /// - `contents`: the contents of the proto file
+    fn verify(&self, relative_path: &str, contents: &[u8]) -> Result<(), Box<dyn Error>> {
+        let checksum = format!("{:x}", Sha256::digest(contents));
+        let checksums_path = self.dir.join(PROTO_CHECKSUMS_FILENAME);
+
+        let checksums = match fs::read_to_string(&checksums_path) {
+            Ok(checksums) => checksums,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let pinned = checksums.lines().find_map(|line| {
+            line.split_once("  ")
+                .filter(|(_, path)| *path == relative_path)
+                .map(|(checksum, _)| checksum)
+        });
+
+        match pinned {
+            Some(pinned) if pinned == checksum => Ok(()),
+            Some(pinned) => Err(format!(
+                "Checksum of remote proto file {relative_path} is {checksum}, but {pinned} is pinned in {}",
+                checksums_path.display()
+            )
+            .into()),
+            None => {
+                fs::create_dir_all(&self.dir)?;
+                let mut checksums_file = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&checksums_path)?;
+                checksums_file.write_all(format!("{checksum}  {relative_path}\n").as_bytes())?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod build_common_tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/org/repo/1.0/interfaces/service.proto";
+    const RELATIVE_PATH: &str = "example.com/org/repo/1.0/interfaces/service.proto";
+    const CONTENTS: &[u8] = b"syntax = \"proto3\";";
+
+    /// Creates an empty proto cache in a temporary directory
+    ///
+    /// # Arguments
+    /// - `name`: a name for the directory which is unique to the test
+    fn create_cache(name: &str) -> ProtoCache {
+        let dir = env::temp_dir().join(format!("freyja_proto_cache_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        ProtoCache::new(dir).unwrap()
+    }
+
+    fn fail_download(_: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        Err("download should not be attempted".into())
+    }
+
+    #[test]
+    fn retrieve_downloads_once_and_pins_checksum() {
+        let uut = create_cache("download");
+
+        let result = uut.retrieve(URL, false, |_| Ok(CONTENTS.to_vec()));
+        assert_eq!(result.unwrap(), CONTENTS);
+
+        // The cached copy is used without downloading, even when offline
+        let result = uut.retrieve(URL, true, fail_download);
+        assert_eq!(result.unwrap(), CONTENTS);
+
+        let checksums = fs::read_to_string(uut.dir.join(PROTO_CHECKSUMS_FILENAME)).unwrap();
+        let expected = format!("{:x}  {RELATIVE_PATH}\n", Sha256::digest(CONTENTS));
+        assert_eq!(checksums, expected);
+    }
+
+    #[test]
+    fn retrieve_returns_err_when_checksum_does_not_match() {
+        let uut = create_cache("mismatch");
+        fs::create_dir_all(&uut.dir).unwrap();
+        let pinned = format!("{:x}  {RELATIVE_PATH}\n", Sha256::digest(b"other"));
+        fs::write(uut.dir.join(PROTO_CHECKSUMS_FILENAME), pinned).unwrap();
+
+        let result = uut.retrieve(URL, false, |_| Ok(CONTENTS.to_vec()));
+        assert!(result.is_err());
+
+        // The mismatched download is not cached
+        assert!(!uut.dir.join(RELATIVE_PATH).exists());
+    }
+
+    #[test]
+    fn retrieve_uses_vendored_copy_offline() {
+        let uut = create_cache("offline");
+
+        let result = uut.retrieve(URL, true, fail_download);
+        assert!(result.is_err());
+
+        let path = uut.dir.join(RELATIVE_PATH);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, CONTENTS).unwrap();
+
+        let result = uut.retrieve(URL, true, fail_download);
+        assert_eq!(result.unwrap(), CONTENTS);
+    }
+
+    #[test]
+    fn version_names_match_cargo_features() {
+        assert_eq!(version_module_name("ibeji", "0.1.0"), "ibeji_v0_1_0");
+
+        // Cargo sets CARGO_FEATURE_IBEJI_0_1_0 for the feature ibeji-0_1_0
+        assert_eq!(version_feature_suffix("ibeji", "0.1.0"), "IBEJI_0_1_0");
+    }
+
+    #[test]
+    fn relative_path_rejects_urls_outside_cache() {
+        assert_eq!(ProtoCache::relative_path(URL).unwrap(), RELATIVE_PATH);
+        assert!(ProtoCache::relative_path("https://example.com/../secret.proto").is_err());
+        assert!(ProtoCache::relative_path("/etc/service.proto").is_err());
+        assert!(ProtoCache::new(PathBuf::from("relative/cache")).is_err());
+    }
+}
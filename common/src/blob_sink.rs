@@ -0,0 +1,31 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+
+/// Uploads large signal values, such as images or point clouds, to object storage
+/// so that cloud adapters only need to send a reference to them
+#[async_trait]
+pub trait BlobSink {
+    /// Creates a new instance of a BlobSink with default settings
+    fn create_new() -> Result<Self, BlobSinkError>
+    where
+        Self: Sized;
+
+    /// Uploads a blob and returns a reference to it, such as its url, which receivers can use to download it.
+    /// Uploading the same key again should replace the blob, since a value can be uploaded again after a retry.
+    ///
+    /// # Arguments
+    /// - `key`: the key of the blob, which contains only lowercase letters and digits
+    /// - `content`: the content of the blob
+    async fn upload(&self, key: &str, content: &[u8]) -> Result<String, BlobSinkError>;
+}
+
+proc_macros::error! {
+    BlobSinkError {
+        Io,
+        Communication,
+        Unknown
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+/// A source of time for Freyja components.
+/// Deployments can provide their own implementation to use a disciplined time source such as PTP or GNSS,
+/// and tests can use a `ManualClock` to control the passage of time deterministically.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Gets the current time according to this clock
+    fn now(&self) -> OffsetDateTime;
+
+    /// Waits until the provided duration has elapsed according to this clock
+    ///
+    /// # Arguments
+    /// - `duration`: the amount of time to wait
+    async fn sleep(&self, duration: Duration);
+
+    /// Gets the time elapsed since an arbitrary point according to this clock, which never goes backwards.
+    /// This is used for scheduling, since the time returned by `now` can jump when the clock is adjusted.
+    /// The default implementation uses the time since the Unix epoch, which is suitable for clocks that are only advanced.
+    fn monotonic_now(&self) -> Duration {
+        Duration::try_from(self.now() - OffsetDateTime::UNIX_EPOCH).unwrap_or_default()
+    }
+
+    /// Informs the clock of the time at which an observed event occurred.
+    /// Virtual clocks can use this to follow an external time source, such as the timestamps of replayed data.
+    /// The default implementation does nothing.
+    ///
+    /// # Arguments
+    /// - `time`: the time of the observed event
+    fn sync_to(&self, _time: OffsetDateTime) {}
+}
+
+/// A clock which uses the system time
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    /// Gets the current system time in UTC
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+
+    /// Sleeps using the tokio timer
+    ///
+    /// # Arguments
+    /// - `duration`: the amount of time to wait
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+
+    /// Gets the time elapsed since this was first called according to the tokio timer,
+    /// which isn't affected by adjustments to the system time
+    fn monotonic_now(&self) -> Duration {
+        static START: OnceLock<tokio::time::Instant> = OnceLock::new();
+        START.get_or_init(tokio::time::Instant::now).elapsed()
+    }
+}
+
+/// A clock which only advances when requested.
+/// Sleeping on this clock advances it by the requested duration and returns immediately,
+/// which makes it possible to test interval scheduling without waiting in real time.
+#[derive(Debug)]
+pub struct ManualClock {
+    /// The current time of this clock
+    now: Mutex<OffsetDateTime>,
+}
+
+impl ManualClock {
+    /// Creates a new `ManualClock` starting at the provided time
+    ///
+    /// # Arguments
+    /// - `start`: the initial time of the clock
+    pub fn new(start: OffsetDateTime) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+
+    /// Advances the clock by the provided duration
+    ///
+    /// # Arguments
+    /// - `duration`: the amount of time to advance the clock by
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += duration;
+    }
+
+    /// Sets the clock to the provided time
+    ///
+    /// # Arguments
+    /// - `time`: the new time
+    pub fn set(&self, time: OffsetDateTime) {
+        let mut now = self.now.lock().unwrap();
+        *now = time;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new(OffsetDateTime::UNIX_EPOCH)
+    }
+}
+
+#[async_trait]
+impl Clock for ManualClock {
+    /// Gets the current time of this clock
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap()
+    }
+
+    /// Advances the clock by the provided duration and yields to the runtime
+    ///
+    /// # Arguments
+    /// - `duration`: the amount of time to wait
+    async fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+        tokio::task::yield_now().await
+    }
+}
+
+/// A virtual clock for running simulations faster than real time.
+/// Sleeping on this clock waits for the requested duration divided by the speedup factor
+/// and then advances the virtual time by the full duration.
+/// The clock can also be driven by an external time source using `sync_to`:
+/// the first call sets the virtual time, and subsequent calls can only move it forward.
+#[derive(Debug)]
+pub struct SimulatedClock {
+    /// The state of the clock
+    state: Mutex<SimulatedClockState>,
+
+    /// The factor by which the virtual time runs faster than real time
+    speedup: f64,
+}
+
+/// The mutable state of a `SimulatedClock`
+#[derive(Debug)]
+struct SimulatedClockState {
+    /// The current virtual time
+    now: OffsetDateTime,
+
+    /// Whether or not the clock has been synchronized to an external time source
+    synced: bool,
+}
+
+impl SimulatedClock {
+    /// Creates a new `SimulatedClock` starting at the provided time.
+    /// If `speedup` is not a positive number, a value of `1.0` is used instead.
+    ///
+    /// # Arguments
+    /// - `start`: the initial virtual time
+    /// - `speedup`: the factor by which the virtual time runs faster than real time
+    pub fn new(start: OffsetDateTime, speedup: f64) -> Self {
+        let speedup = if speedup > 0.0 { speedup } else { 1.0 };
+
+        Self {
+            state: Mutex::new(SimulatedClockState {
+                now: start,
+                synced: false,
+            }),
+            speedup,
+        }
+    }
+}
+
+#[async_trait]
+impl Clock for SimulatedClock {
+    /// Gets the current virtual time
+    fn now(&self) -> OffsetDateTime {
+        self.state.lock().unwrap().now
+    }
+
+    /// Waits for `duration / speedup` in real time, then advances the virtual time
+    /// to at least the time at which the sleep was requested plus `duration`
+    ///
+    /// # Arguments
+    /// - `duration`: the amount of virtual time to wait
+    async fn sleep(&self, duration: Duration) {
+        let target = self.now() + duration;
+        tokio::time::sleep(duration.div_f64(self.speedup)).await;
+
+        let mut state = self.state.lock().unwrap();
+        if state.now < target {
+            state.now = target;
+        }
+    }
+
+    /// Synchronizes the virtual time with an external time source.
+    /// The first call sets the virtual time, and later calls only move it forward.
+    ///
+    /// # Arguments
+    /// - `time`: the time of the observed event
+    fn sync_to(&self, time: OffsetDateTime) {
+        let mut state = self.state.lock().unwrap();
+        if !state.synced || state.now < time {
+            state.now = time;
+            state.synced = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn manual_clock_sleep_advances_time() {
+        let uut = ManualClock::default();
+        let start = uut.now();
+
+        uut.sleep(Duration::from_secs(42)).await;
+
+        assert_eq!(uut.now() - start, Duration::from_secs(42));
+    }
+
+    #[test]
+    fn manual_clock_advance_and_set() {
+        let uut = ManualClock::default();
+
+        uut.advance(Duration::from_millis(1500));
+        assert_eq!(
+            uut.now(),
+            OffsetDateTime::UNIX_EPOCH + Duration::from_millis(1500)
+        );
+
+        let time = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(3600);
+        uut.set(time);
+        assert_eq!(uut.now(), time);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn simulated_clock_sleep_is_accelerated() {
+        let uut = SimulatedClock::new(OffsetDateTime::UNIX_EPOCH, 60.0);
+        let real_start = tokio::time::Instant::now();
+
+        uut.sleep(Duration::from_secs(60)).await;
+
+        assert_eq!(real_start.elapsed(), Duration::from_secs(1));
+        assert_eq!(
+            uut.now(),
+            OffsetDateTime::UNIX_EPOCH + Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn simulated_clock_sync_to_only_moves_forward_after_first_sync() {
+        let start = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(3600);
+        let uut = SimulatedClock::new(start, 1.0);
+
+        // The first sync can move the clock backwards, for example to the start of a recording
+        let recording_start = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(60);
+        uut.sync_to(recording_start);
+        assert_eq!(uut.now(), recording_start);
+
+        let later = recording_start + Duration::from_secs(1);
+        uut.sync_to(later);
+        assert_eq!(uut.now(), later);
+
+        uut.sync_to(recording_start);
+        assert_eq!(uut.now(), later);
+    }
+
+    #[test]
+    fn simulated_clock_uses_default_speedup_for_invalid_values() {
+        for speedup in [0.0, -1.0, f64::NAN] {
+            let uut = SimulatedClock::new(OffsetDateTime::UNIX_EPOCH, speedup);
+            assert_eq!(uut.speedup, 1.0);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn system_clock_monotonic_now_follows_tokio_timer() {
+        let start = SystemClock.monotonic_now();
+
+        SystemClock.sleep(Duration::from_millis(1500)).await;
+
+        assert_eq!(
+            SystemClock.monotonic_now() - start,
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn system_clock_returns_current_time() {
+        let before = OffsetDateTime::now_utc();
+        let now = SystemClock.now();
+        let after = OffsetDateTime::now_utc();
+
+        assert!(before <= now && now <= after);
+    }
+}
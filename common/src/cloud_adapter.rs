@@ -2,14 +2,48 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
 
-use crate::service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector;
+use crate::{
+    data_adapter::short_type_name,
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// The metadata key for flags describing the quality of an emitted value
+pub const QUALITY_METADATA_KEY: &str = "quality";
+
+/// The quality flag for values which were emitted without their conversion because it failed
+pub const CONVERSION_FAILED_QUALITY: &str = "conversion_failed";
+
+/// The metadata key which marks values emitted as part of a flight recorder capture.
+/// Its value is the time at which the capture was triggered, in milliseconds since the Unix epoch,
+/// so that every value from the same capture can be grouped together
+pub const FLIGHT_RECORDER_CAPTURE_METADATA_KEY: &str = "flight_recorder_capture";
+
+/// The metadata key for the id of the tenant whose cloud backend a value is emitted to.
+/// Values without this key are emitted with the cloud adapters which don't belong to a tenant.
+/// The key is reserved, so mapping metadata with the same name is never sent to the cloud adapters
+pub const TENANT_METADATA_KEY: &str = "freyja_tenant";
+
+/// The metadata key for the name of a status signal, such as `uptime_s`, in the messages which report Freyja's own operational status
+pub const STATUS_SIGNAL_METADATA_KEY: &str = "freyja_status";
+
+/// The metadata key for the Freyja component which panicked, in a last-gasp message sent when Freyja crashes
+pub const CRASH_COMPONENT_METADATA_KEY: &str = "crash_component";
+
+/// The metadata key for the id of the signal which was being processed when Freyja crashed, if known
+pub const CRASH_SIGNAL_ID_METADATA_KEY: &str = "crash_signal_id";
+
+/// The metadata key for the source location of the panic which crashed Freyja
+pub const CRASH_LOCATION_METADATA_KEY: &str = "crash_location";
 
 #[async_trait]
 pub trait CloudAdapter {
@@ -23,6 +57,27 @@ pub trait CloudAdapter {
     where
         Self: Sized;
 
+    /// Creates a new instance of a CloudAdapter for a tenant, with the tenant's settings such as its credentials
+    /// and endpoints. Adapters which read config can use [`crate::config_utils::read_from_files_for_tenant`]
+    /// to apply the tenant's config overrides.
+    /// The default implementation returns an error since the adapter doesn't support tenants.
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    /// - `tenant`: the id of the tenant
+    fn create_for_tenant(
+        _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        tenant: &str,
+    ) -> Result<Self, CloudAdapterError>
+    where
+        Self: Sized,
+    {
+        Err(CloudAdapterError::unsupported(format!(
+            "{} does not support tenants, so it can't be created for tenant {tenant}",
+            short_type_name::<Self>()
+        )))
+    }
+
     /// Sends the signal to the cloud
     ///
     /// # Arguments
@@ -31,6 +86,45 @@ pub trait CloudAdapter {
         &self,
         cloud_message: CloudMessageRequest,
     ) -> Result<CloudMessageResponse, CloudAdapterError>;
+
+    /// Checks whether the cloud has requested that the current values of any signals be emitted again,
+    /// such as after data loss in the backend.
+    /// The default implementation never requests a backfill.
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        Ok(BackfillRequest::default())
+    }
+
+    /// Gets a name describing the kind of this adapter, which is used to route signals to it.
+    /// The default implementation returns the name of the adapter type.
+    fn kind(&self) -> String {
+        short_type_name::<Self>()
+    }
+}
+
+/// A function that creates a boxed `CloudAdapter`, optionally for a tenant.
+/// This enables Freyja to use multiple cloud adapters of different types.
+pub type CloudAdapterConstructor =
+    fn(
+        Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        Option<&str>,
+    ) -> Result<Box<dyn CloudAdapter + Send + Sync>, CloudAdapterError>;
+
+/// Creates a new boxed instance of a `CloudAdapter`.
+/// This function can be used as a `CloudAdapterConstructor`.
+///
+/// # Arguments
+/// - `selector`: the service discovery adapter selector to use
+/// - `tenant`: the id of the tenant to create the adapter for, or `None` to create it with its default settings
+pub fn create_boxed<TCloudAdapter: CloudAdapter + Send + Sync + 'static>(
+    selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    tenant: Option<&str>,
+) -> Result<Box<dyn CloudAdapter + Send + Sync>, CloudAdapterError> {
+    let adapter = match tenant {
+        Some(tenant) => TCloudAdapter::create_for_tenant(selector, tenant)?,
+        None => TCloudAdapter::create_new(selector)?,
+    };
+
+    Ok(Box::new(adapter))
 }
 
 /// Represents a message to send to the cloud canonical model
@@ -39,16 +133,178 @@ pub struct CloudMessageRequest {
     /// A map containing metadata to help identify the signal in the cloud
     pub metadata: HashMap<String, String>,
 
-    // The signal value
-    pub signal_value: String,
+    /// The signal value.
+    /// This is shared rather than copied, so a value that needs no conversion is the one held in the signal store
+    pub signal_value: Arc<str>,
+
+    /// The signal value as a number, if the value is numeric.
+    /// Cloud adapters can use this to emit numbers without parsing `signal_value`.
+    #[serde(default)]
+    pub numeric_value: Option<f64>,
 
     // Timestamp of when the signal was emitted
     pub signal_timestamp: OffsetDateTime,
+
+    /// A key which is the same for every message carrying the same sample of a signal,
+    /// such as when a message is sent again after a retry, so that the cloud can drop duplicates.
+    /// This is `None` for messages which don't carry a signal's sample, such as status messages.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+impl CloudMessageRequest {
+    /// Creates the idempotency key for a sample of a signal from the signal's id and the sample's timestamp
+    ///
+    /// # Arguments
+    /// - `signal_id`: the id of the signal
+    /// - `sample_timestamp`: the time at which the sample was taken
+    ///
+    /// # Example
+    /// ```rust
+    /// use freyja_common::cloud_adapter::CloudMessageRequest;
+    /// use time::OffsetDateTime;
+    /// let key = CloudMessageRequest::idempotency_key("Vehicle.Speed", OffsetDateTime::UNIX_EPOCH);
+    /// assert_eq!(key, "Vehicle.Speed@0");
+    /// ```
+    pub fn idempotency_key(signal_id: &str, sample_timestamp: OffsetDateTime) -> String {
+        format!("{signal_id}@{}", sample_timestamp.unix_timestamp_nanos())
+    }
 }
 
 /// Represents a response to a message sent to the cloud digital twin
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CloudMessageResponse {}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloudMessageResponse {
+    /// The acknowledgement of a message which the adapter sends after returning, such as in a batch.
+    /// If this is `None`, the cloud accepted the message before the adapter returned
+    #[serde(skip)]
+    pub acknowledgement: Option<Acknowledgement>,
+
+    /// The id which the backend assigned to the message, which identifies it in the backend's ingestion logs.
+    /// This is `None` if the backend doesn't assign ids
+    #[serde(default)]
+    pub message_id: Option<String>,
+
+    /// The time at which the backend accepted the message, if it reports one
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub accepted_at: Option<OffsetDateTime>,
+
+    /// Warnings from the backend about the message which didn't prevent it from being accepted
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// Resolves once the cloud accepts or rejects a message which a cloud adapter sends after `send_to_cloud` returns
+#[derive(Debug, Clone)]
+pub struct Acknowledgement {
+    /// The outcomes of the sends that the message is part of, which are `None` until they're known
+    outcomes: Vec<watch::Receiver<Option<bool>>>,
+}
+
+/// Reports the outcome of sending a message to the cloud to its `Acknowledgement`.
+/// If this is dropped without reporting an outcome, the message is considered rejected
+#[derive(Debug)]
+pub struct Acknowledger {
+    /// The outcome of the send
+    outcome: watch::Sender<Option<bool>>,
+}
+
+impl Acknowledgement {
+    /// Creates an acknowledgement which is pending until the returned acknowledger reports an outcome
+    pub fn pending() -> (Acknowledger, Self) {
+        let (outcome, receiver) = watch::channel(None);
+        (
+            Acknowledger { outcome },
+            Self {
+                outcomes: vec![receiver],
+            },
+        )
+    }
+
+    /// Combines the acknowledgements of a message which was sent several times, such as with several adapters,
+    /// into one which is only accepted if every send is accepted.
+    /// Returns `None` if there are no acknowledgements to combine
+    ///
+    /// # Arguments
+    /// - `acknowledgements`: the acknowledgements to combine
+    pub fn all<I: IntoIterator<Item = Acknowledgement>>(acknowledgements: I) -> Option<Self> {
+        let outcomes: Vec<_> = acknowledgements
+            .into_iter()
+            .flat_map(|acknowledgement| acknowledgement.outcomes)
+            .collect();
+
+        (!outcomes.is_empty()).then_some(Self { outcomes })
+    }
+
+    /// Waits for the outcome of the message.
+    /// Returns true if the cloud accepted it, or false if it was rejected
+    pub async fn accepted(self) -> bool {
+        for mut outcome in self.outcomes {
+            // An error means that the acknowledger was dropped without reporting an outcome
+            let accepted = matches!(
+                outcome.wait_for(Option::is_some).await.as_deref(),
+                Ok(Some(true))
+            );
+
+            if !accepted {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Acknowledger {
+    /// Reports the outcome of the send
+    ///
+    /// # Arguments
+    /// - `accepted`: whether the cloud accepted the message
+    pub fn acknowledge(self, accepted: bool) {
+        // Sending only fails if the acknowledgement was dropped, in which case nobody is waiting for the outcome
+        let _ = self.outcome.send(Some(accepted));
+    }
+}
+
+/// A request from the cloud to emit the current values of signals again
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackfillRequest {
+    /// The ids of the signals whose current values should be emitted again
+    #[serde(default)]
+    pub signal_ids: HashSet<String>,
+
+    /// Whether the current values of every signal should be emitted again, regardless of `signal_ids`
+    #[serde(default)]
+    pub all_signals: bool,
+
+    /// Whether the flight recorder should start a capture of the recent values of its designated signals
+    #[serde(default)]
+    pub flight_recorder: bool,
+}
+
+impl BackfillRequest {
+    /// Checks whether this request doesn't include any signals
+    pub fn is_empty(&self) -> bool {
+        !self.all_signals && self.signal_ids.is_empty()
+    }
+
+    /// Checks whether this request includes a signal
+    ///
+    /// # Arguments
+    /// - `signal_id`: the id of the signal
+    pub fn includes(&self, signal_id: &str) -> bool {
+        self.all_signals || self.signal_ids.contains(signal_id)
+    }
+
+    /// Adds the signals from another request to this one
+    ///
+    /// # Arguments
+    /// - `other`: the request to merge into this one
+    pub fn merge(&mut self, other: BackfillRequest) {
+        self.all_signals |= other.all_signals;
+        self.flight_recorder |= other.flight_recorder;
+        self.signal_ids.extend(other.signal_ids);
+    }
+}
 
 proc_macros::error! {
     CloudAdapterError {
@@ -57,6 +313,42 @@ proc_macros::error! {
         Deserialize,
         Communication,
         KeyNotFound,
+        Unsupported,
         Unknown
     }
 }
+
+#[cfg(test)]
+mod cloud_adapter_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn acknowledgement_resolves_with_outcome() {
+        for accepted in [true, false] {
+            let (acknowledger, acknowledgement) = Acknowledgement::pending();
+            acknowledger.acknowledge(accepted);
+            assert_eq!(acknowledgement.accepted().await, accepted);
+        }
+
+        let (acknowledger, acknowledgement) = Acknowledgement::pending();
+        drop(acknowledger);
+        assert!(!acknowledgement.accepted().await);
+    }
+
+    #[tokio::test]
+    async fn combined_acknowledgement_requires_every_outcome() {
+        assert!(Acknowledgement::all(Vec::<Acknowledgement>::new()).is_none());
+
+        let (first_acknowledger, first) = Acknowledgement::pending();
+        let (second_acknowledger, second) = Acknowledgement::pending();
+        let combined = Acknowledgement::all([first.clone(), second.clone()]).unwrap();
+        let waiting = tokio::spawn(combined.accepted());
+
+        first_acknowledger.acknowledge(true);
+        second_acknowledger.acknowledge(false);
+
+        assert!(!waiting.await.unwrap());
+        assert!(first.accepted().await);
+        assert!(!second.accepted().await);
+    }
+}
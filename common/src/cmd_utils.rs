@@ -2,69 +2,297 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, env, process, str::FromStr};
 
 use log::LevelFilter;
 use proc_macros::error;
 
-/// Gets the command-line arguments.
-/// Arguments should be formatted as `--key[=value]`.
-/// The output is a `HashMap` containing keys mapped to values.
-/// The keys have all leading `-` characters removed.
-/// If an argument is passed as a flag without a corresponding value,
-/// then an entry is created in the hash map with a value of `None`.
-/// Note that this does not support values containing the `=` character,
-/// nor does it support multiple instances of the same key!
-///
-/// # Arguments
-/// - `args`: the command-line arguments (usually obtained with `env::args()`)
-pub fn parse_args<T>(args: T) -> Result<HashMap<String, Option<String>>, ParseArgsError>
-where
-    T: Iterator<Item = String>,
-{
-    let mut result = HashMap::new();
-
-    // First item in this list is the program name
-    for arg in args.skip(1) {
-        let mut split = arg.split('=');
-        let key = match split.next() {
-            // Note that unwrapping here will always succeed because `s` is guaranteed to be at least 3 chars long
-            Some(s) if s.len() > 2 && s.get(..2) == Some("--") => s.get(2..).unwrap().to_owned(),
-            _ => return Err(ParseArgsErrorKind::KeyParseError { arg }.into()),
+/// The argument which sets the log level
+pub const LOG_LEVEL_ARG: ArgSpec = ArgSpec {
+    name: "log-level",
+    value_name: Some("LEVEL"),
+    help: "The most verbose level to log: off, error, warn, info, debug, or trace",
+};
+
+/// The argument which prints the help text, which every command accepts
+const HELP_ARG: ArgSpec = ArgSpec {
+    name: "help",
+    value_name: None,
+    help: "Prints this help text",
+};
+
+/// The exit code used when the command-line arguments are invalid
+const USAGE_EXIT_CODE: i32 = 2;
+
+/// Describes a command-line argument.
+/// Arguments are passed as `--{name}` if they're flags, or as `--{name}={value}` if they take a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArgSpec {
+    /// The name of the argument without the leading `--`
+    pub name: &'static str,
+
+    /// The placeholder for the argument's value in the help text, or `None` if the argument is a flag
+    pub value_name: Option<&'static str>,
+
+    /// The description of the argument in the help text
+    pub help: &'static str,
+}
+
+/// Describes a subcommand, which is passed as the first command-line argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubcommandSpec {
+    /// The name of the subcommand
+    pub name: &'static str,
+
+    /// The description of the subcommand in the help text
+    pub help: &'static str,
+
+    /// The arguments which are only accepted with this subcommand
+    pub args: &'static [ArgSpec],
+}
+
+/// Describes the command-line interface of an executable, which is used to parse and validate its arguments
+/// and to generate its help text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandSpec {
+    /// The name of the executable
+    pub name: &'static str,
+
+    /// The description of the executable in the help text
+    pub about: &'static str,
+
+    /// The arguments which are accepted with every subcommand
+    pub args: &'static [ArgSpec],
+
+    /// The subcommands. If there are any, the first one is used when no subcommand is passed
+    pub subcommands: &'static [SubcommandSpec],
+}
+
+impl CommandSpec {
+    /// Parses and validates command-line arguments.
+    /// Returns an error with the `HelpRequested` kind if `--help` is passed.
+    ///
+    /// # Arguments
+    /// - `args`: the command-line arguments (usually obtained with `env::args()`)
+    pub fn parse<T>(&self, args: T) -> Result<Args, ParseArgsError>
+    where
+        T: Iterator<Item = String>,
+    {
+        // First item in this list is the program name
+        let mut args = args.skip(1).peekable();
+
+        let subcommand = match self.subcommands.first() {
+            Some(default) => match args.next_if(|arg| !arg.starts_with("--")) {
+                Some(name) => Some(
+                    self.subcommands
+                        .iter()
+                        .find(|s| s.name == name)
+                        .ok_or(ParseArgsErrorKind::UnknownSubcommand { name })?,
+                ),
+                None => Some(default),
+            },
+            None => None,
         };
 
-        // If split.next() returns None here, then this was a flag argument and the call to map also returns None.
-        let val = split.next().map(|v| v.to_owned());
+        let specs: Vec<&ArgSpec> = self
+            .args
+            .iter()
+            .chain(subcommand.iter().flat_map(|s| s.args.iter()))
+            .collect();
+
+        let mut values = HashMap::new();
+        for arg in args {
+            let mut split = arg.split('=');
+            let key = match split.next() {
+                // Note that unwrapping here will always succeed because `s` is guaranteed to be at least 3 chars long
+                Some(s) if s.len() > 2 && s.get(..2) == Some("--") => s.get(2..).unwrap(),
+                _ => return Err(ParseArgsErrorKind::KeyParseError { arg }.into()),
+            };
+
+            // If split.next() returns None here, then this was a flag argument and the call to map also returns None.
+            let val = split.next().map(|v| v.to_owned());
+
+            if split.next().is_some() {
+                return Err(ParseArgsErrorKind::ValueParseError { arg }.into());
+            }
+
+            if key == HELP_ARG.name {
+                return Err(ParseArgsErrorKind::HelpRequested.into());
+            }
+
+            let spec = specs.iter().find(|spec| spec.name == key).ok_or_else(|| {
+                ParseArgsErrorKind::UnknownArgument {
+                    key: key.to_owned(),
+                }
+            })?;
+
+            match (spec.value_name, &val) {
+                (Some(_), None) => {
+                    return Err(ParseArgsErrorKind::MissingValue {
+                        key: key.to_owned(),
+                    }
+                    .into())
+                }
+                (None, Some(_)) => {
+                    return Err(ParseArgsErrorKind::UnexpectedValue {
+                        key: key.to_owned(),
+                    }
+                    .into())
+                }
+                _ => {}
+            }
+
+            if values.insert(spec.name, val).is_some() {
+                return Err(ParseArgsErrorKind::DuplicateKeys {
+                    key: key.to_owned(),
+                }
+                .into());
+            }
+        }
+
+        Ok(Args {
+            subcommand: subcommand.map(|s| s.name),
+            values,
+        })
+    }
+
+    /// Parses and validates the arguments of the current process.
+    /// If `--help` is passed, prints the help text and exits.
+    /// If the arguments are invalid, prints the error and the help text to stderr and exits with an error code.
+    pub fn parse_env_or_exit(&self) -> Args {
+        match self.parse(env::args()) {
+            Ok(args) => args,
+            Err(e) if e.kind() == ParseArgsErrorKind::HelpRequested => {
+                println!("{}", self.help());
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{e}\n\n{}", self.help());
+                process::exit(USAGE_EXIT_CODE);
+            }
+        }
+    }
 
-        if split.next().is_some() {
-            return Err(ParseArgsErrorKind::ValueParseError { arg }.into());
+    /// Gets the help text, which describes the subcommands and arguments
+    pub fn help(&self) -> String {
+        let mut sections = vec![self.about.to_owned()];
+
+        let mut rows: Vec<(String, &str)> = Vec::new();
+        if self.subcommands.is_empty() {
+            sections.push(format!("Usage: {} [OPTIONS]", self.name));
+        } else {
+            sections.push(format!("Usage: {} [SUBCOMMAND] [OPTIONS]", self.name));
+            rows.extend(self.subcommands.iter().map(|s| (s.name.to_owned(), s.help)));
+        }
+
+        let mut tables = Vec::new();
+        if !self.subcommands.is_empty() {
+            tables.push((String::from("Subcommands:"), 0..rows.len()));
         }
 
-        if result.contains_key(&key) {
-            return Err(ParseArgsErrorKind::DuplicateKeys { key }.into());
+        let start = rows.len();
+        rows.extend(self.args.iter().chain([&HELP_ARG]).map(Self::help_row));
+        tables.push((String::from("Options:"), start..rows.len()));
+
+        for subcommand in self.subcommands.iter().filter(|s| !s.args.is_empty()) {
+            let start = rows.len();
+            rows.extend(subcommand.args.iter().map(Self::help_row));
+            tables.push((
+                format!("Options for {}:", subcommand.name),
+                start..rows.len(),
+            ));
         }
 
-        result.insert(key, val);
+        // All of the tables are aligned to the same column
+        let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (title, range) in tables {
+            let table = rows[range]
+                .iter()
+                .map(|(name, help)| format!("  {name:width$}  {help}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            sections.push(format!("{title}\n{table}"));
+        }
+
+        sections.join("\n\n")
+    }
+
+    /// Gets the name and description of an argument in the help text
+    ///
+    /// # Arguments
+    /// - `spec`: the argument
+    fn help_row(spec: &ArgSpec) -> (String, &'static str) {
+        let name = match spec.value_name {
+            Some(value_name) => format!("--{}=<{value_name}>", spec.name),
+            None => format!("--{}", spec.name),
+        };
+
+        (name, spec.help)
     }
+}
 
-    Ok(result)
+/// Command-line arguments which have been validated against a `CommandSpec`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    /// The subcommand, or `None` if the command has no subcommands
+    subcommand: Option<&'static str>,
+
+    /// The arguments which were passed, mapped to their values. Flags have no value
+    values: HashMap<&'static str, Option<String>>,
+}
+
+impl Args {
+    /// Gets the subcommand, or `None` if the command has no subcommands
+    pub fn subcommand(&self) -> Option<&str> {
+        self.subcommand
+    }
+
+    /// Returns true if an argument was passed
+    ///
+    /// # Arguments
+    /// - `name`: the name of the argument
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Gets the value of an argument, or `None` if it wasn't passed
+    ///
+    /// # Arguments
+    /// - `name`: the name of the argument
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).and_then(|v| v.as_deref())
+    }
+
+    /// Parses the value of an argument.
+    /// Returns `None` if it wasn't passed, and an error if its value can't be parsed.
+    ///
+    /// # Arguments
+    /// - `name`: the name of the argument
+    pub fn parse_value<T: FromStr>(&self, name: &str) -> Result<Option<T>, ParseArgsError> {
+        self.value(name)
+            .map(|val| {
+                val.parse().map_err(|_| {
+                    ParseArgsErrorKind::ParseError {
+                        key: name.to_owned(),
+                        val: val.to_owned(),
+                    }
+                    .into()
+                })
+            })
+            .transpose()
+    }
 }
 
 /// Gets the log level from the provided args.
-/// Returns `ParseError` if the argument could not be parsed into a `LevelFilter`,
-/// and `MissingValue` if a log level argument was present but did not specify a value.
+/// Returns `ParseError` if the argument could not be parsed into a `LevelFilter`.
 ///
 /// # Arguments
 /// - `args`: the parsed command line arguments.
 /// - `default`: the default value to use if there is no log-level argument.
-pub fn get_log_level(
-    args: &HashMap<String, Option<String>>,
-    default: LevelFilter,
-) -> Result<LevelFilter, GetLogLevelError> {
-    match args.get("log-level") {
-        Some(Some(l)) => LevelFilter::from_str(l)
+pub fn get_log_level(args: &Args, default: LevelFilter) -> Result<LevelFilter, GetLogLevelError> {
+    match args.value(LOG_LEVEL_ARG.name) {
+        Some(l) => LevelFilter::from_str(l)
             .map_err(|_| GetLogLevelErrorKind::ParseError { val: l.to_owned() }.into()),
-        Some(None) => Err(GetLogLevelErrorKind::MissingValue.into()),
         None => Ok(default),
     }
 }
@@ -80,12 +308,28 @@ error! {
         DuplicateKeys {
             key: String
         },
+        UnknownSubcommand {
+            name: String
+        },
+        UnknownArgument {
+            key: String
+        },
+        MissingValue {
+            key: String
+        },
+        UnexpectedValue {
+            key: String
+        },
+        ParseError {
+            key: String,
+            val: String
+        },
+        HelpRequested,
     }
 }
 
 error! {
     GetLogLevelError {
-        MissingValue,
         ParseError {
             val: String
         },
@@ -96,119 +340,205 @@ error! {
 mod cmd_utils_tests {
     use super::*;
 
-    #[test]
-    fn parse_args_parses_valid_input() {
-        let cmd = "cmd".to_owned();
-        let key = "foo".to_owned();
-        let val = "bar".to_owned();
-        let flag = "flag".to_owned();
-
-        let input = vec![
-            // Mimics the behavior of real command-line args which have the command as the first entry.
-            cmd.clone(),
-            format!("--{key}={val}"),
-            format!("--{flag}"),
-        ];
-
-        let result = parse_args(input.into_iter());
-
-        assert!(result.is_ok());
-        let result = result.unwrap();
-        assert_eq!(result.get(&key), Some(&Some(val)));
-        assert_eq!(result.get(&flag), Some(&None));
-        assert!(!result.contains_key("dne"));
-        assert!(!result.contains_key(&cmd));
+    const FLAG_ARG: ArgSpec = ArgSpec {
+        name: "flag",
+        value_name: None,
+        help: "A flag",
+    };
+
+    const KEY_ARG: ArgSpec = ArgSpec {
+        name: "key",
+        value_name: Some("VALUE"),
+        help: "An argument with a value",
+    };
+
+    const COMMAND: CommandSpec = CommandSpec {
+        name: "cmd",
+        about: "A command",
+        args: &[LOG_LEVEL_ARG, FLAG_ARG],
+        subcommands: &[],
+    };
+
+    const COMMAND_WITH_SUBCOMMANDS: CommandSpec = CommandSpec {
+        name: "cmd",
+        about: "A command",
+        args: &[LOG_LEVEL_ARG],
+        subcommands: &[
+            SubcommandSpec {
+                name: "run",
+                help: "Runs the command",
+                args: &[KEY_ARG],
+            },
+            SubcommandSpec {
+                name: "check",
+                help: "Checks the command",
+                args: &[],
+            },
+        ],
+    };
+
+    fn parse(command: &CommandSpec, args: &[&str]) -> Result<Args, ParseArgsError> {
+        // Mimics the behavior of real command-line args which have the command as the first entry.
+        let args: Vec<String> = ["cmd"].iter().chain(args).map(|a| a.to_string()).collect();
+        command.parse(args.into_iter())
     }
 
-    #[test]
-    fn parse_args_returns_error_when_arg_too_short() {
-        let cmd = "cmd".to_owned();
-        let invalid_arg = "1".to_owned();
-
-        let input = vec![
-            // Mimics the behavior of real command-line args which have the command as the first entry.
-            cmd.clone(),
-            invalid_arg.clone(),
-        ];
-
-        let result = parse_args(input.into_iter());
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        match err.kind() {
-            ParseArgsErrorKind::KeyParseError { arg } => assert_eq!(arg, invalid_arg),
-            _ => panic!("Expected KeyParseError"),
+    fn assert_err_kind(result: Result<Args, ParseArgsError>, expected: ParseArgsErrorKind) {
+        match result {
+            Ok(args) => panic!("Expected {expected:?} but parsed {args:?}"),
+            Err(e) => assert_eq!(e.kind(), expected),
         }
     }
 
     #[test]
-    fn parse_args_returns_error_when_arg_missing_dashes() {
-        let cmd = "cmd".to_owned();
-        let invalid_arg = "arg".to_owned();
-
-        let input = vec![
-            // Mimics the behavior of real command-line args which have the command as the first entry.
-            cmd.clone(),
-            invalid_arg.clone(),
-        ];
-
-        let result = parse_args(input.into_iter());
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        match err.kind() {
-            ParseArgsErrorKind::KeyParseError { arg } => assert_eq!(arg, invalid_arg),
-            _ => panic!("Expected KeyParseError"),
-        }
+    fn parse_parses_valid_input() {
+        let result = parse(&COMMAND, &["--log-level=debug", "--flag"]).unwrap();
+
+        assert_eq!(result.subcommand(), None);
+        assert_eq!(result.value(LOG_LEVEL_ARG.name), Some("debug"));
+        assert!(result.contains(FLAG_ARG.name));
+        assert_eq!(result.value(FLAG_ARG.name), None);
+        assert!(!result.contains("dne"));
+        assert!(!result.contains("cmd"));
     }
 
     #[test]
-    fn parse_args_returns_error_when_too_many_equals() {
-        let cmd = "cmd".to_owned();
-        let invalid_arg = "--key=foo=bar".to_owned();
+    fn parse_selects_subcommand() {
+        let result = parse(&COMMAND_WITH_SUBCOMMANDS, &["check", "--log-level=info"]).unwrap();
+        assert_eq!(result.subcommand(), Some("check"));
+
+        let result = parse(&COMMAND_WITH_SUBCOMMANDS, &["--key=42"]).unwrap();
+        assert_eq!(result.subcommand(), Some("run"));
+        assert_eq!(result.parse_value::<u32>(KEY_ARG.name).unwrap(), Some(42));
+
+        let result = parse(&COMMAND_WITH_SUBCOMMANDS, &["check", "--key=42"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::UnknownArgument {
+                key: String::from("key"),
+            },
+        );
+
+        let result = parse(&COMMAND_WITH_SUBCOMMANDS, &["stop"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::UnknownSubcommand {
+                name: String::from("stop"),
+            },
+        );
+    }
 
-        let input = vec![
-            // Mimics the behavior of real command-line args which have the command as the first entry.
-            cmd.clone(),
-            invalid_arg.clone(),
-        ];
+    #[test]
+    fn parse_returns_error_when_arg_too_short() {
+        let result = parse(&COMMAND, &["1"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::KeyParseError {
+                arg: String::from("1"),
+            },
+        );
+    }
 
-        let result = parse_args(input.into_iter());
+    #[test]
+    fn parse_returns_error_when_arg_missing_dashes() {
+        let result = parse(&COMMAND, &["arg"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::KeyParseError {
+                arg: String::from("arg"),
+            },
+        );
+    }
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        match err.kind() {
-            ParseArgsErrorKind::ValueParseError { arg } => assert_eq!(arg, invalid_arg),
-            _ => panic!("Expected ValueParseError"),
-        }
+    #[test]
+    fn parse_returns_error_when_too_many_equals() {
+        let result = parse(&COMMAND, &["--log-level=foo=bar"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::ValueParseError {
+                arg: String::from("--log-level=foo=bar"),
+            },
+        );
     }
 
     #[test]
-    fn parse_args_returns_error_when_duplicate_keys() {
-        let cmd = "cmd".to_owned();
-        let dup_key = "key".to_owned();
+    fn parse_returns_error_when_duplicate_keys() {
+        let result = parse(&COMMAND, &["--flag", "--flag"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::DuplicateKeys {
+                key: String::from("flag"),
+            },
+        );
+    }
 
-        let input = vec![
-            // Mimics the behavior of real command-line args which have the command as the first entry.
-            cmd.clone(),
-            format!("--{dup_key}"),
-            format!("--{dup_key}"),
-        ];
+    #[test]
+    fn parse_validates_arguments() {
+        let result = parse(&COMMAND, &["--interactive"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::UnknownArgument {
+                key: String::from("interactive"),
+            },
+        );
+
+        let result = parse(&COMMAND, &["--log-level"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::MissingValue {
+                key: String::from("log-level"),
+            },
+        );
+
+        let result = parse(&COMMAND, &["--flag=true"]);
+        assert_err_kind(
+            result,
+            ParseArgsErrorKind::UnexpectedValue {
+                key: String::from("flag"),
+            },
+        );
+
+        let result = parse(&COMMAND, &["--flag", "--help"]);
+        assert_err_kind(result, ParseArgsErrorKind::HelpRequested);
+    }
 
-        let result = parse_args(input.into_iter());
+    #[test]
+    fn parse_value_returns_error_for_invalid_value() {
+        let result = parse(&COMMAND_WITH_SUBCOMMANDS, &["--key=soon"]).unwrap();
+        let result = result.parse_value::<u32>(KEY_ARG.name);
+        assert!(result.is_err_and(|e| e.kind()
+            == ParseArgsErrorKind::ParseError {
+                key: String::from("key"),
+                val: String::from("soon"),
+            }));
+    }
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        match err.kind() {
-            ParseArgsErrorKind::DuplicateKeys { key } => assert_eq!(key, dup_key),
-            _ => panic!("Expected DuplicateKeys"),
-        }
+    #[test]
+    fn help_describes_subcommands_and_arguments() {
+        let help = COMMAND_WITH_SUBCOMMANDS.help();
+
+        assert_eq!(
+            help,
+            "A command\n\
+            \n\
+            Usage: cmd [SUBCOMMAND] [OPTIONS]\n\
+            \n\
+            Subcommands:\n\
+            \x20 run                  Runs the command\n\
+            \x20 check                Checks the command\n\
+            \n\
+            Options:\n\
+            \x20 --log-level=<LEVEL>  The most verbose level to log: off, error, warn, info, debug, or trace\n\
+            \x20 --help               Prints this help text\n\
+            \n\
+            Options for run:\n\
+            \x20 --key=<VALUE>        An argument with a value"
+        );
     }
 
     #[test]
     fn get_log_level_returns_value() {
-        let mut args = HashMap::new();
-        args.insert("log-level".to_owned(), Some("debug".to_owned()));
+        let args = parse(&COMMAND, &["--log-level=debug"]).unwrap();
 
         let result = get_log_level(&args, LevelFilter::Info);
 
@@ -219,9 +549,8 @@ mod cmd_utils_tests {
 
     #[test]
     fn get_log_level_returns_parse_error() {
-        let mut args = HashMap::new();
         let invalid_value = "foo".to_owned();
-        args.insert("log-level".to_owned(), Some(invalid_value.clone()));
+        let args = parse(&COMMAND, &["--log-level=foo"]).unwrap();
 
         let result = get_log_level(&args, LevelFilter::Info);
 
@@ -229,28 +558,12 @@ mod cmd_utils_tests {
         let err = result.unwrap_err();
         match err.kind() {
             GetLogLevelErrorKind::ParseError { val } => assert_eq!(val, invalid_value),
-            _ => panic!("Expected ParseError"),
-        }
-    }
-
-    #[test]
-    fn get_log_level_returns_missing_value() {
-        let mut args = HashMap::new();
-        args.insert("log-level".to_owned(), None);
-
-        let result = get_log_level(&args, LevelFilter::Info);
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        match err.kind() {
-            GetLogLevelErrorKind::MissingValue => {}
-            _ => panic!("Expected MissingValue"),
         }
     }
 
     #[test]
     fn get_log_level_returns_default() {
-        let args = HashMap::new();
+        let args = parse(&COMMAND, &[]).unwrap();
         let default = LevelFilter::Info;
 
         let result = get_log_level(&args, default);
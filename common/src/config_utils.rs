@@ -2,23 +2,66 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::{env, path::Path};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
 
-use config::{ConfigError, File};
+use config::{ConfigError, File, Source, Value, ValueKind};
 use home::home_dir;
 use serde::Deserialize;
 
+use crate::{
+    cmd_utils::ArgSpec,
+    secret_provider::{EnvSecretProvider, FileSecretProvider, SecretProvider, SecretProviderError},
+};
+
 pub const JSON_EXT: &str = "json";
 
 const CONFIG_DIR: &str = "config";
 const DOT_FREYJA_DIR: &str = ".freyja";
 const FREYJA_HOME: &str = "FREYJA_HOME";
+const FREYJA_PROFILE: &str = "FREYJA_PROFILE";
+const PROFILES_DIR: &str = "profiles";
+
+/// The command-line argument which selects a config profile.
+/// Executables which read config should accept this argument.
+pub const PROFILE_ARG: ArgSpec = ArgSpec {
+    name: "profile",
+    value_name: Some("PROFILE"),
+    help: "The config profile, whose overrides take precedence over the other config overrides",
+};
+
+/// The prefix of secret references in config values, such as `${file:/run/secrets/x}`
+const SECRET_REFERENCE_PREFIX: &str = "${";
+/// The suffix of secret references in config values
+const SECRET_REFERENCE_SUFFIX: &str = "}";
+
+/// Secret providers registered in addition to the built-in providers
+static SECRET_PROVIDERS: RwLock<Vec<Arc<dyn SecretProvider>>> = RwLock::new(Vec::new());
+
+/// Registers a secret provider used to resolve secret references when reading config.
+/// Registered providers take precedence over the built-in `env` and `file` providers with the same scheme.
+///
+/// # Arguments
+/// - `provider`: the provider to register
+pub fn register_secret_provider(provider: Arc<dyn SecretProvider>) {
+    SECRET_PROVIDERS.write().unwrap().push(provider);
+}
 
 /// Read config from layered configuration files.
 /// Uses `{config_file_stem}.default.{config_file_ext}` as the base configuration,
 /// then searches for overrides named `{config_file_stem}.{config_file_ext}` in the current directory and `$FREYJA_HOME`.
 /// If `$FREYJA_HOME` is not set, it defaults to `$HOME/.freyja`.
 ///
+/// If a config profile is selected with the `--profile={profile}` command-line argument or `$FREYJA_PROFILE`,
+/// overrides are also searched for in the `profiles/{profile}` subdirectory of each of these directories,
+/// and take precedence over the overrides outside of it.
+///
+/// String values of the form `${scheme:reference}` are replaced with the secret resolved by the provider for `scheme`,
+/// so that secrets don't need to be stored in plaintext config files. Use `$${...}` for a literal `${...}` value.
+///
 /// # Arguments
 /// - `config_file_stem`: The config file name without an extension. This is used to construct the file names to search for
 /// - `config_file_ext`: The config file extension. This is used to construct the file names to search for
@@ -37,26 +80,117 @@ where
     TPath: AsRef<Path>,
     TIoErrorHandler: Fn(std::io::Error) -> TError,
     TConfigErrorHandler: Fn(ConfigError) -> TError,
+{
+    read_layers(
+        config_file_stem,
+        config_file_ext,
+        default_config_path,
+        None,
+        env::args(),
+        io_error_handler,
+        config_error_handler,
+    )
+}
+
+/// Read a tenant's config from layered configuration files.
+/// This reads the same files as [`read_from_files`], then applies the tenant's overrides named
+/// `{config_file_stem}.{tenant}.{config_file_ext}` from the same directories,
+/// so that settings such as credentials and endpoints can differ between tenants.
+///
+/// # Arguments
+/// - `config_file_stem`: The config file name without an extension. This is used to construct the file names to search for
+/// - `config_file_ext`: The config file extension. This is used to construct the file names to search for
+/// - `default_config_path`: The path to the directory containing the default configuration
+/// - `tenant`: The id of the tenant
+/// - `io_error_handler`: The error handler for `std::io::Error` errors
+/// - `config_error_handler`: The error handler for errors from the config library
+pub fn read_from_files_for_tenant<TConfig, TError, TPath, TIoErrorHandler, TConfigErrorHandler>(
+    config_file_stem: &str,
+    config_file_ext: &str,
+    default_config_path: TPath,
+    tenant: &str,
+    io_error_handler: TIoErrorHandler,
+    config_error_handler: TConfigErrorHandler,
+) -> Result<TConfig, TError>
+where
+    TConfig: for<'a> Deserialize<'a>,
+    TPath: AsRef<Path>,
+    TIoErrorHandler: Fn(std::io::Error) -> TError,
+    TConfigErrorHandler: Fn(ConfigError) -> TError,
+{
+    // The tenant id is part of a file name, so it must not be able to name a file in another directory
+    if !is_valid_tenant(tenant) {
+        return Err(io_error_handler(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid tenant id {tenant:?}: tenant ids may only contain ASCII letters, digits, '-' and '_'"),
+        )));
+    }
+
+    read_layers(
+        config_file_stem,
+        config_file_ext,
+        default_config_path,
+        Some(tenant),
+        env::args(),
+        io_error_handler,
+        config_error_handler,
+    )
+}
+
+/// Checks whether a tenant id is non-empty and only contains ASCII letters, digits, `-` and `_`
+///
+/// # Arguments
+/// - `tenant`: the id of the tenant
+pub fn is_valid_tenant(tenant: &str) -> bool {
+    !tenant.is_empty()
+        && tenant
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Reads the default configuration and its overrides, followed by the tenant's overrides if there is a tenant
+///
+/// # Arguments
+/// - `config_file_stem`: The config file name without an extension. This is used to construct the file names to search for
+/// - `config_file_ext`: The config file extension. This is used to construct the file names to search for
+/// - `default_config_path`: The path to the directory containing the default configuration
+/// - `tenant`: The id of the tenant whose overrides should be applied, if any
+/// - `args`: The command-line arguments, which can select a config profile
+/// - `io_error_handler`: The error handler for `std::io::Error` errors
+/// - `config_error_handler`: The error handler for errors from the config library
+fn read_layers<TConfig, TError, TPath, TArgs, TIoErrorHandler, TConfigErrorHandler>(
+    config_file_stem: &str,
+    config_file_ext: &str,
+    default_config_path: TPath,
+    tenant: Option<&str>,
+    args: TArgs,
+    io_error_handler: TIoErrorHandler,
+    config_error_handler: TConfigErrorHandler,
+) -> Result<TConfig, TError>
+where
+    TConfig: for<'a> Deserialize<'a>,
+    TPath: AsRef<Path>,
+    TArgs: Iterator<Item = String>,
+    TIoErrorHandler: Fn(std::io::Error) -> TError,
+    TConfigErrorHandler: Fn(ConfigError) -> TError,
 {
     let default_config_filename = format!("{config_file_stem}.default.{config_file_ext}");
     let default_config_file = default_config_path.as_ref().join(default_config_filename);
 
-    let overrides_filename = format!("{config_file_stem}.{config_file_ext}");
+    let mut overrides_filenames = vec![format!("{config_file_stem}.{config_file_ext}")];
+    if let Some(tenant) = tenant {
+        overrides_filenames.push(format!("{config_file_stem}.{tenant}.{config_file_ext}"));
+    }
 
-    // The path below resolves to {current_dir}/{overrides_filename}
-    let current_dir_config_path = env::current_dir()
-        .map_err(&io_error_handler)?
-        .join(overrides_filename.clone());
+    let current_dir = env::current_dir().map_err(&io_error_handler)?;
 
-    let freyja_dir_config_path = match env::var(FREYJA_HOME) {
+    let freyja_config_dir = match env::var(FREYJA_HOME) {
         Ok(freyja_home) => {
-            // The path below resolves to $FREYJA_HOME/config/{overrides_filename}
-            Path::new(&freyja_home)
-                .join(CONFIG_DIR)
-                .join(overrides_filename)
+            // The path below resolves to $FREYJA_HOME/config
+            Path::new(&freyja_home).join(CONFIG_DIR)
         }
         Err(_) => {
-            // The path below resolves to $HOME/.freyja/config/{overrides_filename}
+            // The path below resolves to $HOME/.freyja/config
             home_dir()
                 .ok_or_else(|| {
                     io_error_handler(std::io::Error::new(
@@ -66,16 +200,357 @@ where
                 })?
                 .join(DOT_FREYJA_DIR)
                 .join(CONFIG_DIR)
-                .join(overrides_filename)
         }
     };
 
-    let config_store = config::Config::builder()
-        .add_source(File::from(default_config_file).required(false))
-        .add_source(File::from(current_dir_config_path).required(false))
-        .add_source(File::from(freyja_dir_config_path).required(false))
-        .build()
-        .map_err(&config_error_handler)?;
+    let mut overrides_dirs = vec![current_dir, freyja_config_dir];
+    if let Some(profile) =
+        get_profile(args, env::var(FREYJA_PROFILE).ok()).map_err(&io_error_handler)?
+    {
+        let profile_dirs: Vec<PathBuf> = overrides_dirs
+            .iter()
+            .map(|dir| dir.join(PROFILES_DIR).join(&profile))
+            .collect();
+        overrides_dirs.extend(profile_dirs);
+    }
+
+    let mut builder =
+        config::Config::builder().add_source(File::from(default_config_file).required(false));
+    for overrides_filename in overrides_filenames {
+        for overrides_dir in overrides_dirs.iter() {
+            builder = builder
+                .add_source(File::from(overrides_dir.join(&overrides_filename)).required(false));
+        }
+    }
+
+    let config_store = builder.build().map_err(&config_error_handler)?;
+
+    let mut value = Value::new(
+        None,
+        ValueKind::Table(config_store.collect().map_err(&config_error_handler)?),
+    );
+    resolve_secrets(&mut value, &get_secret_providers())
+        .map_err(|e| config_error_handler(ConfigError::Foreign(Box::new(e))))?;
+
+    value.try_deserialize().map_err(config_error_handler)
+}
+
+/// Gets the selected config profile, which is passed as `--profile={profile}` or set in `$FREYJA_PROFILE`.
+/// The command-line argument takes precedence. Returns `None` if no profile is selected.
+/// Since the profile is used as a directory name, returns an error if it isn't a single path component.
+///
+/// # Arguments
+/// - `args`: the command-line arguments
+/// - `env_profile`: the value of `$FREYJA_PROFILE`, if it's set
+fn get_profile<T>(
+    mut args: T,
+    env_profile: Option<String>,
+) -> Result<Option<String>, std::io::Error>
+where
+    T: Iterator<Item = String>,
+{
+    let prefix = format!("--{}=", PROFILE_ARG.name);
+    let profile = args
+        .find_map(|arg| arg.strip_prefix(&prefix).map(str::to_string))
+        .or(env_profile)
+        .filter(|profile| !profile.is_empty());
+
+    match profile {
+        Some(profile)
+            if profile == "." || profile == ".." || profile.contains(|c| c == '/' || c == '\\') =>
+        {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid config profile {profile:?}. Profiles must be directory names"),
+            ))
+        }
+        profile => Ok(profile),
+    }
+}
+
+/// Gets the registered secret providers followed by the built-in providers
+fn get_secret_providers() -> Vec<Arc<dyn SecretProvider>> {
+    let mut providers = SECRET_PROVIDERS.read().unwrap().clone();
+    providers.push(Arc::new(EnvSecretProvider));
+    providers.push(Arc::new(FileSecretProvider));
+
+    providers
+}
+
+/// Replaces the secret references in a config value and its children with the resolved secrets
+///
+/// # Arguments
+/// - `value`: the value to resolve secrets in
+/// - `providers`: the secret providers, in order of precedence
+fn resolve_secrets(
+    value: &mut Value,
+    providers: &[Arc<dyn SecretProvider>],
+) -> Result<(), SecretProviderError> {
+    match &mut value.kind {
+        ValueKind::String(s) => {
+            if let Some(resolved) = resolve_secret_reference(s, providers)? {
+                *s = resolved;
+            }
+        }
+        ValueKind::Table(table) => {
+            for child in table.values_mut() {
+                resolve_secrets(child, providers)?;
+            }
+        }
+        ValueKind::Array(array) => {
+            for child in array.iter_mut() {
+                resolve_secrets(child, providers)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Resolves a secret reference.
+/// Returns `None` if the value is not a secret reference.
+///
+/// # Arguments
+/// - `value`: the config value
+/// - `providers`: the secret providers, in order of precedence
+fn resolve_secret_reference(
+    value: &str,
+    providers: &[Arc<dyn SecretProvider>],
+) -> Result<Option<String>, SecretProviderError> {
+    if !value.ends_with(SECRET_REFERENCE_SUFFIX) {
+        return Ok(None);
+    }
+
+    // Escaped references are read as the literal value without the leading `$`
+    if let Some(literal) = value
+        .strip_prefix('$')
+        .filter(|v| v.starts_with(SECRET_REFERENCE_PREFIX))
+    {
+        return Ok(Some(literal.to_string()));
+    }
+
+    let reference = match value
+        .strip_prefix(SECRET_REFERENCE_PREFIX)
+        .and_then(|r| r.strip_suffix(SECRET_REFERENCE_SUFFIX))
+    {
+        Some(reference) => reference,
+        None => return Ok(None),
+    };
+
+    let (scheme, reference) = reference.split_once(':').ok_or_else(|| {
+        SecretProviderError::invalid_reference(format!(
+            "Secret reference {value:?} does not have a scheme"
+        ))
+    })?;
+
+    providers
+        .iter()
+        .find(|provider| provider.scheme() == scheme)
+        .ok_or_else(|| {
+            SecretProviderError::unknown_scheme(format!(
+                "No secret provider is registered for scheme {scheme:?}"
+            ))
+        })?
+        .get_secret(reference)
+        .map(Some)
+}
+
+#[cfg(test)]
+mod config_utils_tests {
+    use super::*;
+
+    use std::fs;
+
+    use config::Map;
+    use freyja_test_common::fixtures::TempDirFixture;
+
+    use crate::secret_provider::SecretProviderErrorKind;
+
+    /// A secret provider which returns the reference in uppercase
+    struct UppercaseSecretProvider;
+
+    impl SecretProvider for UppercaseSecretProvider {
+        fn scheme(&self) -> &str {
+            "upper"
+        }
+
+        fn get_secret(&self, reference: &str) -> Result<String, SecretProviderError> {
+            Ok(reference.to_uppercase())
+        }
+    }
+
+    fn providers() -> Vec<Arc<dyn SecretProvider>> {
+        vec![Arc::new(UppercaseSecretProvider)]
+    }
+
+    fn string_value(s: &str) -> Value {
+        Value::new(None, ValueKind::String(s.to_string()))
+    }
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        ["freyja"]
+            .iter()
+            .chain(args)
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn get_profile_prefers_command_line_argument() {
+        let result = get_profile(
+            args(&["--log-level=info", "--profile=container"]),
+            Some("vehicle".to_string()),
+        );
+        assert_eq!(result.unwrap(), Some("container".to_string()));
+
+        let result = get_profile(args(&["--log-level=info"]), Some("vehicle".to_string()));
+        assert_eq!(result.unwrap(), Some("vehicle".to_string()));
+    }
+
+    #[test]
+    fn get_profile_returns_none_without_profile() {
+        assert_eq!(get_profile(args(&[]), None).unwrap(), None);
+        assert_eq!(get_profile(args(&[]), Some(String::new())).unwrap(), None);
+        assert_eq!(get_profile(args(&["--profile="]), None).unwrap(), None);
+    }
+
+    #[test]
+    fn get_profile_returns_err_for_paths() {
+        for profile in ["..", "dev/../..", "/etc", "dev\\container"] {
+            let result = get_profile(args(&[]), Some(profile.to_string()));
+            assert!(result.is_err_and(|e| e.kind() == std::io::ErrorKind::InvalidInput));
+        }
+    }
+
+    #[test]
+    fn read_layers_reads_default_config() {
+        let directory = TempDirFixture::new();
+        fs::write(
+            directory.path.join("read_layers_test.default.json"),
+            r#"{"value": "default"}"#,
+        )
+        .unwrap();
+
+        let result: Result<Map<String, String>, String> = read_layers(
+            "read_layers_test",
+            JSON_EXT,
+            &directory.path,
+            None,
+            args(&[]),
+            |e| e.to_string(),
+            |e| e.to_string(),
+        );
+
+        assert_eq!(result.unwrap()["value"], "default");
+    }
+
+    #[test]
+    fn read_layers_returns_err_for_invalid_profile_argument() {
+        let directory = TempDirFixture::new();
+
+        let result: Result<Map<String, String>, String> = read_layers(
+            "read_layers_test",
+            JSON_EXT,
+            &directory.path,
+            None,
+            args(&["--profile=../dev"]),
+            |e| e.to_string(),
+            |e| e.to_string(),
+        );
+
+        assert!(result.unwrap_err().contains("Invalid config profile"));
+    }
+
+    #[test]
+    fn is_valid_tenant_rejects_paths() {
+        assert!(is_valid_tenant("contoso-east_1"));
+        assert!(!is_valid_tenant(""));
+        assert!(!is_valid_tenant("../contoso"));
+        assert!(!is_valid_tenant("contoso/east"));
+    }
+
+    #[test]
+    fn read_from_files_for_tenant_returns_err_for_invalid_tenant() {
+        let result: Result<Map<String, String>, String> = read_from_files_for_tenant(
+            "config",
+            "json",
+            env::temp_dir(),
+            "../contoso",
+            |e| e.to_string(),
+            |e| e.to_string(),
+        );
+
+        assert!(result.unwrap_err().contains("Invalid tenant id"));
+    }
+
+    #[test]
+    fn resolve_secret_reference_resolves_references() {
+        let result = resolve_secret_reference("${upper:secret}", &providers());
+        assert_eq!(result.unwrap(), Some("SECRET".to_string()));
+    }
+
+    #[test]
+    fn resolve_secret_reference_ignores_other_values() {
+        for value in [
+            "secret",
+            "${upper:secret",
+            "upper:secret}",
+            "prefix ${upper:secret}",
+        ] {
+            let result = resolve_secret_reference(value, &providers());
+            assert_eq!(result.unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn resolve_secret_reference_unescapes_escaped_references() {
+        let result = resolve_secret_reference("$${upper:secret}", &providers());
+        assert_eq!(result.unwrap(), Some("${upper:secret}".to_string()));
+    }
+
+    #[test]
+    fn resolve_secret_reference_returns_err_for_invalid_references() {
+        let result = resolve_secret_reference("${secret}", &providers());
+        assert!(result.is_err_and(|e| e.kind() == SecretProviderErrorKind::InvalidReference));
+
+        let result = resolve_secret_reference("${keyvault:secret}", &providers());
+        assert!(result.is_err_and(|e| e.kind() == SecretProviderErrorKind::UnknownScheme));
+    }
+
+    #[test]
+    fn resolve_secrets_resolves_nested_values() {
+        let mut value = Value::new(
+            None,
+            ValueKind::Table(Map::from([
+                ("password".to_string(), string_value("${upper:password}")),
+                (
+                    "tokens".to_string(),
+                    Value::new(
+                        None,
+                        ValueKind::Array(vec![
+                            string_value("${upper:token}"),
+                            string_value("plain"),
+                        ]),
+                    ),
+                ),
+                ("retries".to_string(), Value::new(None, ValueKind::I64(5))),
+            ])),
+        );
+
+        resolve_secrets(&mut value, &providers()).unwrap();
 
-    config_store.try_deserialize().map_err(config_error_handler)
+        let table = value.into_table().unwrap();
+        assert_eq!(table["password"].clone().into_string().unwrap(), "PASSWORD");
+        let tokens: Vec<String> = table["tokens"]
+            .clone()
+            .into_array()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.into_string().unwrap())
+            .collect();
+        assert_eq!(tokens, vec!["TOKEN".to_string(), "plain".to_string()]);
+        assert_eq!(table["retries"].clone().into_int().unwrap(), 5);
+    }
 }
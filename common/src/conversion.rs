@@ -11,7 +11,28 @@ pub enum Conversion {
     /// No conversion
     None,
     /// A conversion from x to y in the form y = mul * x + offset
-    Linear { mul: f32, offset: f32 },
+    Linear { mul: f64, offset: f64 },
+    /// A conversion which formats the value into a string template, such as `"{value:.1} km/h"`.
+    /// See [`apply_template`] for the supported placeholders.
+    Template { template: String },
+    /// A conversion from a number to a boolean flag with hysteresis.
+    /// See [`apply_threshold`] for how the `on` and `off` thresholds are evaluated.
+    Threshold { on: f64, off: f64 },
+}
+
+/// Policies for handling signal values which cannot be parsed as numbers for a conversion.
+/// Values of signals with a privacy policy are never emitted raw, so they are skipped instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConversionFailurePolicy {
+    /// The raw value is emitted without applying the conversion
+    #[default]
+    EmitRaw,
+    /// The value is not emitted and a warning is logged
+    SkipAndWarn,
+    /// The raw value is emitted with a quality flag in the message metadata
+    EmitWithQualityFlag,
+    /// The value is not emitted and an error is reported
+    Error,
 }
 
 impl Conversion {
@@ -36,11 +57,13 @@ impl Conversion {
     /// ```rust
     /// use freyja_common::conversion::Conversion;
     /// let c2f = Conversion::c_to_f();
-    /// assert!(42.0 == c2f.inverse().apply(c2f.apply(42.0)));
+    /// assert!((42.0 - c2f.inverse().apply(c2f.apply(42.0))).abs() < 1e-9);
     /// ```
     pub fn inverse(&self) -> Self {
         match self {
             Self::None => Self::None,
+            // Formatting a value into a string or a flag can't be inverted
+            Self::Template { .. } | Self::Threshold { .. } => Self::None,
             Self::Linear { mul: m, offset: o } => Self::Linear {
                 mul: 1.0 / m,
                 offset: -o / m,
@@ -48,7 +71,9 @@ impl Conversion {
         }
     }
 
-    /// Converts the input
+    /// Converts the input.
+    /// Template and threshold conversions don't change the numeric value;
+    /// use [`apply_template`] or [`apply_threshold`] to convert it.
     ///
     /// # Arguments
     ///
@@ -61,9 +86,9 @@ impl Conversion {
     /// assert!(32.0 == c2f.apply(0.0));
     /// assert!(212.0 == c2f.apply(100.0));
     /// ```
-    pub fn apply(&self, input: f32) -> f32 {
+    pub fn apply(&self, input: f64) -> f64 {
         match self {
-            Self::None => input,
+            Self::None | Self::Template { .. } | Self::Threshold { .. } => input,
             Self::Linear { mul: m, offset: o } => input * m + o,
         }
     }
@@ -75,13 +100,200 @@ impl Default for Conversion {
     }
 }
 
+/// Converts a value to a boolean flag with hysteresis, so that the flag doesn't chatter
+/// when the value hovers around a single threshold.
+///
+/// If `on` is greater than or equal to `off`, the flag is set once the value reaches `on`
+/// and cleared once the value drops below `off`.
+/// If `on` is less than `off`, the flag is set once the value drops to `on`
+/// and cleared once the value rises above `off`.
+/// Otherwise the flag keeps its previous state.
+///
+/// # Arguments
+/// - `on`: the threshold at which the flag is set
+/// - `off`: the threshold at which the flag is cleared
+/// - `value`: the value to convert
+/// - `previous`: the state of the flag before this value
+///
+/// # Example
+/// ```rust
+/// use freyja_common::conversion::apply_threshold;
+/// // A low fuel flag which is set below 10% and cleared above 12%
+/// assert!(apply_threshold(10.0, 12.0, 9.5, false));
+/// assert!(apply_threshold(10.0, 12.0, 11.0, true));
+/// assert!(!apply_threshold(10.0, 12.0, 11.0, false));
+/// assert!(!apply_threshold(10.0, 12.0, 12.5, true));
+/// ```
+pub fn apply_threshold(on: f64, off: f64, value: f64, previous: bool) -> bool {
+    if on >= off {
+        if value >= on {
+            true
+        } else if value < off {
+            false
+        } else {
+            previous
+        }
+    } else if value <= on {
+        true
+    } else if value > off {
+        false
+    } else {
+        previous
+    }
+}
+
+/// Formats a value into a string template.
+///
+/// Placeholders have the form `{value}` or `{value:spec}`, where `spec` is `[0][width][.precision][type]`:
+/// - `0` pads the value with zeros rather than spaces up to `width` characters
+/// - `precision` is the number of decimal places
+/// - `type` is `x` or `X` to format the value as a hexadecimal integer
+///
+/// Use `{{` and `}}` for literal braces.
+///
+/// # Arguments
+/// - `template`: the template to format the value into
+/// - `value`: the value to format
+///
+/// # Example
+/// ```rust
+/// use freyja_common::conversion::apply_template;
+/// assert_eq!(apply_template("{value:.1} km/h", 42.25).unwrap(), "42.2 km/h");
+/// assert_eq!(apply_template("{value:04}", 7.0).unwrap(), "0007");
+/// assert_eq!(apply_template("0x{value:02X}", 255.0).unwrap(), "0xFF");
+/// ```
+pub fn apply_template(template: &str, value: f64) -> Result<String, ConversionError> {
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(ConversionError::invalid_template(format!(
+                                "Unclosed placeholder in template {template:?}"
+                            )))
+                        }
+                    }
+                }
+
+                result.push_str(&format_placeholder(&placeholder, value)?);
+            }
+            '}' => {
+                return Err(ConversionError::invalid_template(format!(
+                    "Unmatched '}}' in template {template:?}"
+                )))
+            }
+            c => result.push(c),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Formats a value according to the contents of a template placeholder
+///
+/// # Arguments
+/// - `placeholder`: the contents of the placeholder without the surrounding braces
+/// - `value`: the value to format
+fn format_placeholder(placeholder: &str, value: f64) -> Result<String, ConversionError> {
+    let invalid_placeholder =
+        || ConversionError::invalid_template(format!("Invalid placeholder {{{placeholder}}}"));
+
+    let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+    if name != "value" {
+        return Err(invalid_placeholder());
+    }
+
+    let (spec, hex) = match spec.strip_suffix('x') {
+        Some(spec) => (spec, Some(false)),
+        None => match spec.strip_suffix('X') {
+            Some(spec) => (spec, Some(true)),
+            None => (spec, None),
+        },
+    };
+
+    let (zero_pad, spec) = match spec.strip_prefix('0') {
+        Some(spec) => (true, spec),
+        None => (false, spec),
+    };
+
+    let (width, precision) = match spec.split_once('.') {
+        Some((width, precision)) => (
+            width,
+            Some(
+                precision
+                    .parse::<usize>()
+                    .map_err(|_| invalid_placeholder())?,
+            ),
+        ),
+        None => (spec, None),
+    };
+
+    let width = if width.is_empty() {
+        0
+    } else {
+        width.parse::<usize>().map_err(|_| invalid_placeholder())?
+    };
+
+    match (hex, precision) {
+        (Some(_), Some(_)) => Err(invalid_placeholder()),
+        (Some(uppercase), None) => {
+            if !value.is_finite() || value < 0.0 || value.fract() != 0.0 || value > u64::MAX as f64
+            {
+                return Err(ConversionError::invalid_value(format!(
+                    "{value} cannot be formatted as a hexadecimal integer"
+                )));
+            }
+
+            let value = value as u64;
+            Ok(match (uppercase, zero_pad) {
+                (false, false) => format!("{value:width$x}"),
+                (false, true) => format!("{value:0width$x}"),
+                (true, false) => format!("{value:width$X}"),
+                (true, true) => format!("{value:0width$X}"),
+            })
+        }
+        (None, Some(precision)) => Ok(if zero_pad {
+            format!("{value:0width$.precision$}")
+        } else {
+            format!("{value:width$.precision$}")
+        }),
+        (None, None) => Ok(if zero_pad {
+            format!("{value:0width$}")
+        } else {
+            format!("{value:width$}")
+        }),
+    }
+}
+
+proc_macros::error! {
+    ConversionError {
+        InvalidTemplate,
+        InvalidValue
+    }
+}
+
 #[cfg(test)]
 mod conversion_tests {
     use super::*;
 
-    /// Valdiates that abs(lhs - rhs) < epsilon, or that lhs and rhs are both f32::NAN or infinite with the same sign
-    fn f32_close_enough(lhs: f32, rhs: f32, epsilon: f32) -> bool {
-        f32::abs(lhs - rhs) < epsilon
+    /// Valdiates that abs(lhs - rhs) < epsilon, or that lhs and rhs are both f64::NAN or infinite with the same sign
+    fn f64_close_enough(lhs: f64, rhs: f64, epsilon: f64) -> bool {
+        f64::abs(lhs - rhs) < epsilon
             || lhs.is_nan() && rhs.is_nan()
             || lhs.is_infinite()
                 && rhs.is_infinite()
@@ -110,23 +322,142 @@ mod conversion_tests {
         assert_eq!(c1.inverse(), c2);
     }
 
+    #[test]
+    fn invert_template_returns_none() {
+        let c = Conversion::Template {
+            template: "{value} km/h".to_string(),
+        };
+        assert_eq!(c.inverse(), Conversion::None);
+    }
+
+    #[test]
+    fn invert_threshold_returns_none() {
+        let c = Conversion::Threshold {
+            on: 10.0,
+            off: 12.0,
+        };
+        assert_eq!(c.inverse(), Conversion::None);
+    }
+
+    #[test]
+    fn can_deserialize_threshold() {
+        let c: Conversion = serde_json::from_str(r#"{"on": 10.0, "off": 12.0}"#).unwrap();
+        assert_eq!(
+            c,
+            Conversion::Threshold {
+                on: 10.0,
+                off: 12.0
+            }
+        );
+    }
+
+    #[test]
+    fn apply_threshold_rising_has_hysteresis() {
+        let (on, off) = (90.0, 80.0);
+
+        let mut state = false;
+        for (value, expected) in [
+            (50.0, false),
+            (85.0, false),
+            (90.0, true),
+            (85.0, true),
+            (80.0, true),
+            (79.9, false),
+            (85.0, false),
+        ] {
+            state = apply_threshold(on, off, value, state);
+            assert_eq!(state, expected, "unexpected state for {value}");
+        }
+    }
+
+    #[test]
+    fn apply_threshold_falling_has_hysteresis() {
+        let (on, off) = (10.0, 12.0);
+
+        let mut state = false;
+        for (value, expected) in [
+            (50.0, false),
+            (11.0, false),
+            (10.0, true),
+            (11.0, true),
+            (12.0, true),
+            (12.1, false),
+            (11.0, false),
+        ] {
+            state = apply_threshold(on, off, value, state);
+            assert_eq!(state, expected, "unexpected state for {value}");
+        }
+    }
+
+    #[test]
+    fn apply_threshold_keeps_state_for_nan() {
+        assert!(apply_threshold(10.0, 12.0, f64::NAN, true));
+        assert!(!apply_threshold(10.0, 12.0, f64::NAN, false));
+    }
+
+    #[test]
+    fn can_deserialize_template() {
+        let c: Conversion = serde_json::from_str(r#"{"template": "{value} km/h"}"#).unwrap();
+        assert_eq!(
+            c,
+            Conversion::Template {
+                template: "{value} km/h".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn apply_template_formats_value() {
+        let cases = [
+            ("{value} km/h", 42.5, "42.5 km/h"),
+            ("{value:.2}", std::f64::consts::PI, "3.14"),
+            ("{value:05}", 42.0, "00042"),
+            ("{value:07.2}", -1.5, "-001.50"),
+            ("{value:4}", 7.0, "   7"),
+            ("{value:x}", 255.0, "ff"),
+            ("0x{value:04X}", 255.0, "0x00FF"),
+            ("{{{value}}}", 1.0, "{1}"),
+            ("no placeholder", 1.0, "no placeholder"),
+        ];
+
+        for (template, value, expected) in cases {
+            assert_eq!(apply_template(template, value).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn apply_template_rejects_invalid_templates() {
+        for template in ["{value", "value}", "{other}", "{value:abc}", "{value:.2x}"] {
+            let result = apply_template(template, 1.0);
+            assert!(result.is_err_and(|e| e.kind() == ConversionErrorKind::InvalidTemplate));
+        }
+    }
+
+    #[test]
+    fn apply_template_rejects_non_integer_hex_values() {
+        for value in [1.5, -1.0, f64::NAN, f64::INFINITY] {
+            let result = apply_template("{value:x}", value);
+            assert!(result.is_err_and(|e| e.kind() == ConversionErrorKind::InvalidValue));
+        }
+    }
+
     #[test]
     fn can_apply_none() {
         let c = Conversion::None;
 
-        // Try multiple values to make sure it works across various f32 and not just one input
+        // Try multiple values to make sure it works across various f64 and not just one input
         let vals = vec![
             0.0,
             -0.0,
             -1.23,
             42.0,
             77.7,
-            f32::NAN,
-            f32::INFINITY,
-            std::f32::consts::PI,
+            f64::NAN,
+            f64::INFINITY,
+            std::f64::consts::PI,
         ];
         for v in vals.into_iter() {
-            assert!(f32_close_enough(v, c.apply(v), 0.001));
+            assert!(f64_close_enough(v, c.apply(v), 0.001));
         }
     }
 
@@ -135,23 +466,34 @@ mod conversion_tests {
         let (mul, offset) = (0.125, 1.0);
         let c = Conversion::Linear { mul, offset };
 
-        // Try multiple values to make sure it works across various f32 and not just one input
+        // Try multiple values to make sure it works across various f64 and not just one input
         let vals = vec![
             0.0,
             -0.0,
             -1.23,
             42.0,
             77.7,
-            f32::NAN,
-            f32::INFINITY,
-            std::f32::consts::PI,
+            f64::NAN,
+            f64::INFINITY,
+            std::f64::consts::PI,
         ];
         for v in vals.into_iter() {
             let expected = v * mul + offset;
-            assert!(f32_close_enough(expected, c.apply(v), 0.001));
+            assert!(f64_close_enough(expected, c.apply(v), 0.001));
         }
     }
 
+    #[test]
+    fn apply_preserves_precision_of_large_values() {
+        // An odometer reading in meters with millimeter precision, converted to kilometers
+        let c = Conversion::Linear {
+            mul: 0.001,
+            offset: 0.0,
+        };
+
+        assert!(f64_close_enough(123456.789, c.apply(123456789.0), 1e-9));
+    }
+
     #[test]
     fn inverse_returns_correct_value() {
         let c = Conversion::Linear {
@@ -160,19 +502,19 @@ mod conversion_tests {
         };
         let i = c.inverse();
 
-        // Try multiple values to make sure it works across various f32 and not just one input
+        // Try multiple values to make sure it works across various f64 and not just one input
         let vals = vec![
             0.0,
             -0.0,
             -1.23,
             42.0,
             77.7,
-            f32::NAN,
-            f32::INFINITY,
-            std::f32::consts::PI,
+            f64::NAN,
+            f64::INFINITY,
+            std::f64::consts::PI,
         ];
         for v in vals.into_iter() {
-            assert!(f32_close_enough(v, i.apply(c.apply(v)), 0.001));
+            assert!(f64_close_enough(v, i.apply(c.apply(v)), 0.001));
         }
     }
 
@@ -190,8 +532,8 @@ mod conversion_tests {
         ];
 
         for (f, c) in vals.into_iter() {
-            assert!(f32_close_enough(f, c2f.apply(c), 0.001));
-            assert!(f32_close_enough(f2c.apply(f), c, 0.001));
+            assert!(f64_close_enough(f, c2f.apply(c), 0.001));
+            assert!(f64_close_enough(f2c.apply(f), c, 0.001));
         }
     }
 }
@@ -8,10 +8,14 @@ use async_trait::async_trait;
 use strum_macros::Display;
 
 use crate::{
-    entity::{Entity, EntityEndpoint},
+    entity::{Entity, EntityEndpoint, Operation},
     signal_store::SignalStore,
 };
 
+/// Derives `DataAdapterFactory` for factories which only match protocols and operations.
+/// The derive macro shares the trait's name, so importing the trait also imports the macro.
+pub use proc_macros::DataAdapterFactory;
+
 #[derive(Clone, Debug, Display, Eq, PartialEq)]
 /// Return options for when a data adapter attempts to register an entity
 pub enum EntityRegistration {
@@ -21,6 +25,36 @@ pub enum EntityRegistration {
     Loopback(Entity),
 }
 
+/// What a data adapter and its provider support, which the data adapter selector uses to decide
+/// which adapter should serve an entity when several can handle its protocol.
+/// Capabilities which aren't set are unknown and don't restrict which entities the adapter serves
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataAdapterCapabilities {
+    /// The operations that the adapter supports.
+    /// If this is `None`, the adapter supports every operation that its factory accepts
+    pub operations: Option<Vec<Operation>>,
+
+    /// The maximum number of entities that can be registered with the adapter, such as the provider's subscription limit
+    pub max_subscriptions: Option<usize>,
+
+    /// The maximum sustained number of requests per second that the provider accepts.
+    /// This limits requests to the provider unless the provider has a configured request budget
+    pub max_requests_per_second: Option<f64>,
+}
+
+impl DataAdapterCapabilities {
+    /// Checks whether the adapter supports one of an endpoint's operations
+    ///
+    /// # Arguments
+    /// - `endpoint`: the endpoint to check
+    pub fn supports(&self, endpoint: &EntityEndpoint) -> bool {
+        match self.operations.as_ref() {
+            Some(operations) => endpoint.select_operation(operations).is_some(),
+            None => true,
+        }
+    }
+}
+
 /// Interfacess with a data source, such as a digital twin provider
 #[async_trait]
 pub trait DataAdapter {
@@ -38,6 +72,13 @@ pub trait DataAdapter {
     /// (such as a server or a listener) should spawn its own task.
     async fn start(&self) -> Result<(), DataAdapterError>;
 
+    /// Stops a data adapter, such as when its entities are migrated to another adapter.
+    /// Tasks spawned by `start` should stop so that they no longer write to the signal store.
+    /// The default implementation does nothing.
+    async fn stop(&self) -> Result<(), DataAdapterError> {
+        Ok(())
+    }
+
     /// Sends a request to a provider for obtaining the value of an entity
     ///
     /// # Arguments
@@ -56,6 +97,19 @@ pub trait DataAdapter {
         entity_id: &str,
         endpoint: &EntityEndpoint,
     ) -> Result<EntityRegistration, DataAdapterError>;
+
+    /// Gets what the adapter and its provider support.
+    /// This is called once the adapter has started and before any entities are registered with it.
+    /// The default implementation returns capabilities which don't restrict the adapter.
+    async fn get_capabilities(&self) -> Result<DataAdapterCapabilities, DataAdapterError> {
+        Ok(DataAdapterCapabilities::default())
+    }
+
+    /// Gets a name describing the kind of this adapter.
+    /// The default implementation returns the name of the adapter type.
+    fn kind(&self) -> String {
+        short_type_name::<Self>()
+    }
 }
 
 /// Factory for creating DataAdapters
@@ -82,6 +136,23 @@ pub trait DataAdapterFactory {
         provider_uri: &str,
         signals: Arc<SignalStore>,
     ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError>;
+
+    /// Gets a name describing the kind of this factory, which is used to deregister it.
+    /// The default implementation returns the name of the factory type.
+    fn kind(&self) -> String {
+        short_type_name::<Self>()
+    }
+}
+
+/// Gets the name of a type without its module path
+pub(crate) fn short_type_name<T: ?Sized>() -> String {
+    let type_name = std::any::type_name::<T>();
+
+    // Only the path of the outer type is removed so that generic parameters are preserved
+    let (path, generics) = type_name.split_at(type_name.find('<').unwrap_or(type_name.len()));
+    let name = path.rsplit("::").next().unwrap_or(path);
+
+    format!("{name}{generics}")
 }
 
 proc_macros::error! {
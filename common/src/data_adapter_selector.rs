@@ -3,14 +3,34 @@
 // SPDX-License-Identifier: MIT
 
 use async_trait::async_trait;
+use serde::Serialize;
+use time::OffsetDateTime;
 
-use crate::{data_adapter::DataAdapterFactory, entity::Entity};
+use crate::{data_adapter::DataAdapterFactory, entity::Entity, signal::EmissionPolicy};
+
+/// Information about the data adapter that serves an entity
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DataAdapterInfo {
+    /// The kind of data adapter, such as the name of the adapter type
+    pub adapter_kind: String,
+
+    /// The uri of the provider endpoint that the adapter communicates with
+    pub endpoint: String,
+
+    /// The protocol used to communicate with the provider
+    pub protocol: String,
+
+    /// The time at which the entity was registered with the adapter, according to the signal store's clock
+    #[serde(with = "time::serde::rfc3339")]
+    pub registered_at: OffsetDateTime,
+}
 
 /// Manages a collection of data adapters and provides access to them.
 /// Conceptually similar to a gateway for the adapters.
 #[async_trait]
 pub trait DataAdapterSelector {
     /// Registers a `DataAdapterFactory` with this selector.
+    /// Factories registered later take precedence over earlier ones.
     ///
     /// # Arguments
     /// - `factory`: the factory to register
@@ -19,14 +39,23 @@ pub trait DataAdapterSelector {
         factory: Box<dyn DataAdapterFactory + Send + Sync>,
     ) -> Result<(), DataAdapterSelectorError>;
 
+    /// Deregisters every `DataAdapterFactory` of the given kind from this selector.
+    ///
+    /// # Arguments
+    /// - `factory_kind`: the kind of factory to deregister, as returned by `DataAdapterFactory::kind`
+    fn deregister(&mut self, factory_kind: &str) -> Result<(), DataAdapterSelectorError>;
+
     /// Updates an existing data adapter to include an entity if possible,
     /// otherwise creates a new data adapter to handle that entity.
+    /// The entity's endpoints are scored against the emission policy to choose which endpoint to use.
     ///
     /// # Arguments
     /// - `entity`: the entity that the adapter should handle
+    /// - `emission_policy`: the emission policy of the signal whose source is the entity
     async fn create_or_update_adapter(
         &self,
         entity: &Entity,
+        emission_policy: &EmissionPolicy,
     ) -> Result<(), DataAdapterSelectorError>;
 
     /// Requests that the value of an entity be published as soon as possible
@@ -34,6 +63,20 @@ pub trait DataAdapterSelector {
     /// # Arguments
     /// - `entity_id`: the entity to request
     async fn request_entity_value(&self, entity_id: &str) -> Result<(), DataAdapterSelectorError>;
+
+    /// Moves registered entities to new adapters if their preferred factory has changed,
+    /// such as after registering or deregistering a factory.
+    /// The adapters that the entities are moved away from are stopped
+    async fn migrate_entities(&self) -> Result<(), DataAdapterSelectorError>;
+
+    /// Gets information about the data adapter that serves an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity to look up
+    async fn get_adapter_info(
+        &self,
+        entity_id: &str,
+    ) -> Result<DataAdapterInfo, DataAdapterSelectorError>;
 }
 
 proc_macros::error! {
@@ -46,6 +89,8 @@ proc_macros::error! {
         Serialize,
         Deserialize,
         Communication,
+        RateLimited,
+        FactoryNotFound,
         Unknown
     }
 }
@@ -55,6 +55,7 @@ proc_macros::error! {
         Deserialize,
         Communication,
         ParseError,
+        IncompatibleService,
         Unknown
     }
 }
@@ -5,8 +5,25 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
-use crate::conversion::Conversion;
+use crate::{
+    conversion::{Conversion, ConversionFailurePolicy},
+    privacy::PrivacyPolicy,
+    signal::AdaptiveInterval,
+};
+
+/// The mapping entry metadata key for the priority of a signal
+pub const PRIORITY_METADATA_KEY: &str = "priority";
+
+/// The mapping entry metadata key for the group that a signal belongs to
+pub const GROUP_METADATA_KEY: &str = "group";
+
+/// The mapping entry metadata key for the unit of a signal's values
+pub const UNIT_METADATA_KEY: &str = "unit";
+
+/// The mapping entry metadata key for the category of consent which the vehicle's owner must give for a signal to be collected
+pub const CONSENT_CATEGORY_METADATA_KEY: &str = "consent_category";
 
 /// Represents a mapping from the device digital twin to the cloud
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +42,64 @@ pub struct DigitalTwinMapEntry {
 
     /// Specifies whether to emit the signal when there's a change
     pub emit_on_change: bool,
+
+    /// How to handle values which cannot be converted
+    #[serde(default)]
+    pub conversion_failure_policy: ConversionFailurePolicy,
+
+    /// The number of decimal places to round numeric values to. If this is `None`, values are not rounded
+    #[serde(default)]
+    pub decimal_places: Option<u32>,
+
+    /// The namespace of the signal, which keeps it distinguishable from signals with the same id from other sources.
+    /// If this is `None`, the signal is not in a namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+
+    /// The id of the tenant whose cloud backend the signal is emitted to.
+    /// If this is `None`, the signal is emitted with the cloud adapters which don't belong to a tenant
+    #[serde(default)]
+    pub tenant: Option<String>,
+
+    /// A trigger expression over other signals which must be satisfied for the signal to be emitted.
+    /// If this is `None`, the signal is always emitted
+    #[serde(default)]
+    pub trigger: Option<String>,
+
+    /// The bounds of an adaptive emission interval, which starts at `interval_ms`.
+    /// If this is `None`, the signal is always emitted at `interval_ms`
+    #[serde(default)]
+    pub adaptive_interval: Option<AdaptiveInterval>,
+
+    /// A transformation which reduces the precision of the signal's values before emission.
+    /// If this is `None`, values are emitted with full precision
+    #[serde(default)]
+    pub privacy: Option<PrivacyPolicy>,
+
+    /// The time at which the signal becomes active. If this is `None`, the signal is active immediately
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub not_before: Option<OffsetDateTime>,
+
+    /// The time at which the signal is retired. If this is `None`, the signal is never retired
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub not_after: Option<OffsetDateTime>,
+
+    /// Free-form properties of the signal, such as the ones with the well-known keys in this module.
+    /// Settings for new features can be added here without changing the mapping service interface
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl DigitalTwinMapEntry {
+    /// Returns true if the signal is active at the given time according to its validity window.
+    /// The window includes `not_before` and excludes `not_after`.
+    ///
+    /// # Arguments
+    /// - `time`: the time to check
+    pub fn is_active_at(&self, time: OffsetDateTime) -> bool {
+        !self.not_before.is_some_and(|not_before| time < not_before)
+            && !self.not_after.is_some_and(|not_after| not_after <= time)
+    }
 }
 
 impl Default for DigitalTwinMapEntry {
@@ -35,6 +110,86 @@ impl Default for DigitalTwinMapEntry {
             interval_ms: 0,
             conversion: Conversion::None,
             emit_on_change: false,
+            conversion_failure_policy: ConversionFailurePolicy::default(),
+            decimal_places: None,
+            namespace: None,
+            tenant: None,
+            trigger: None,
+            adaptive_interval: None,
+            privacy: None,
+            not_before: None,
+            not_after: None,
+            metadata: HashMap::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod digital_twin_map_entry_tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn is_active_at_respects_validity_window() {
+        let now = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(100);
+        let uut = DigitalTwinMapEntry {
+            not_before: Some(now),
+            not_after: Some(now + Duration::from_secs(10)),
+            ..Default::default()
+        };
+
+        assert!(!uut.is_active_at(now - Duration::from_secs(1)));
+        assert!(uut.is_active_at(now));
+        assert!(uut.is_active_at(now + Duration::from_secs(9)));
+        assert!(!uut.is_active_at(now + Duration::from_secs(10)));
+        assert!(DigitalTwinMapEntry::default().is_active_at(now));
+    }
+
+    #[test]
+    fn validity_window_deserializes_from_rfc3339() {
+        let json = r#"{
+            "source": "brake_temperature",
+            "target": {},
+            "interval_ms": 1000,
+            "conversion": null,
+            "emit_on_change": false,
+            "not_before": "1970-01-01T00:01:40Z"
+        }"#;
+
+        let uut: DigitalTwinMapEntry = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            uut.not_before,
+            Some(OffsetDateTime::UNIX_EPOCH + Duration::from_secs(100))
+        );
+        assert_eq!(uut.not_after, None);
+        assert!(uut.metadata.is_empty());
+    }
+
+    #[test]
+    fn metadata_deserializes_from_map() {
+        let json = r#"{
+            "source": "cabin_temperature",
+            "target": {},
+            "interval_ms": 1000,
+            "conversion": null,
+            "emit_on_change": false,
+            "metadata": {
+                "unit": "celsius",
+                "consent_category": "comfort"
+            }
+        }"#;
+
+        let uut: DigitalTwinMapEntry = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            uut.metadata.get(UNIT_METADATA_KEY),
+            Some(&String::from("celsius"))
+        );
+        assert_eq!(
+            uut.metadata.get(CONSENT_CATEGORY_METADATA_KEY),
+            Some(&String::from("comfort"))
+        );
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+    time::Duration,
+};
+
+use time::OffsetDateTime;
+
+/// Identifies a delivery of a value for deduplication
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DeliveryKey {
+    /// A sequence id supplied by the provider
+    SequenceId(String),
+
+    /// A hash of the value, for providers which don't supply sequence ids
+    Content(u64),
+}
+
+/// Suppresses values which providers deliver more than once, such as MQTT QoS 1 messages and retried HTTP callbacks.
+/// A value is a duplicate if a value with the same key was received for the same signal within the window.
+/// Values with a provider-supplied sequence id are keyed by that id.
+/// Values without one are only keyed by their content for designated signals,
+/// since other signals can legitimately report the same value several times in a row.
+pub struct DuplicateFilter {
+    /// How long a delivery is remembered
+    window: Duration,
+
+    /// The ids of the signals whose values are deduplicated by content when there is no sequence id
+    content_signal_ids: HashSet<String>,
+
+    /// The deliveries received within the window for each signal, oldest first
+    deliveries: Mutex<HashMap<String, VecDeque<(OffsetDateTime, DeliveryKey)>>>,
+}
+
+impl DuplicateFilter {
+    /// Creates a new DuplicateFilter
+    ///
+    /// # Arguments
+    /// - `window`: how long a delivery is remembered
+    /// - `content_signal_ids`: the ids of the signals whose values are deduplicated by content
+    ///   when the provider doesn't supply a sequence id
+    pub fn new(window: Duration, content_signal_ids: HashSet<String>) -> Self {
+        Self {
+            window,
+            content_signal_ids,
+            deliveries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a delivery of a value and returns true if it duplicates a delivery within the window.
+    /// Duplicates aren't recorded, so a value which is repeated continuously is admitted again once per window.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `value`: the value
+    /// - `sequence_id`: the sequence id supplied by the provider, if any
+    /// - `timestamp`: the time at which the value was received
+    pub fn is_duplicate(
+        &self,
+        id: &str,
+        value: &str,
+        sequence_id: Option<&str>,
+        timestamp: OffsetDateTime,
+    ) -> bool {
+        let key = match sequence_id {
+            Some(sequence_id) => DeliveryKey::SequenceId(sequence_id.to_string()),
+            None if self.content_signal_ids.contains(id) => {
+                let mut hasher = DefaultHasher::new();
+                value.hash(&mut hasher);
+                DeliveryKey::Content(hasher.finish())
+            }
+            None => return false,
+        };
+
+        let mut deliveries = self.deliveries.lock().unwrap();
+        let signal_deliveries = deliveries.entry(id.to_string()).or_default();
+
+        while signal_deliveries
+            .front()
+            .is_some_and(|(received_at, _)| *received_at + self.window <= timestamp)
+        {
+            signal_deliveries.pop_front();
+        }
+
+        if signal_deliveries.iter().any(|(_, k)| *k == key) {
+            return true;
+        }
+
+        signal_deliveries.push_back((timestamp, key));
+        false
+    }
+}
+
+#[cfg(test)]
+mod duplicate_filter_tests {
+    use super::*;
+
+    const ID: &str = "Vehicle.Door.Open";
+
+    #[test]
+    fn is_duplicate_suppresses_repeated_sequence_ids_within_window() {
+        let uut = DuplicateFilter::new(Duration::from_secs(10), HashSet::new());
+        let start = OffsetDateTime::UNIX_EPOCH;
+
+        assert!(!uut.is_duplicate(ID, "true", Some("1"), start));
+        assert!(uut.is_duplicate(ID, "true", Some("1"), start + Duration::from_secs(1)));
+        assert!(!uut.is_duplicate(ID, "true", Some("2"), start + Duration::from_secs(2)));
+
+        // The same sequence id on another signal isn't a duplicate
+        assert!(!uut.is_duplicate("other", "true", Some("1"), start));
+
+        // Once the window has elapsed, the sequence id is forgotten
+        assert!(!uut.is_duplicate(ID, "true", Some("1"), start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn is_duplicate_only_compares_content_for_designated_signals() {
+        let uut = DuplicateFilter::new(Duration::from_secs(10), HashSet::from([ID.to_string()]));
+        let start = OffsetDateTime::UNIX_EPOCH;
+
+        assert!(!uut.is_duplicate(ID, "true", None, start));
+        assert!(uut.is_duplicate(ID, "true", None, start + Duration::from_secs(1)));
+        assert!(!uut.is_duplicate(ID, "false", None, start + Duration::from_secs(2)));
+
+        assert!(!uut.is_duplicate("other", "true", None, start));
+        assert!(!uut.is_duplicate("other", "true", None, start));
+    }
+}
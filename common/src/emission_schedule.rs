@@ -0,0 +1,235 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Orders signals by the time of their next emission,
+/// so that the signals which are due can be found without visiting every signal.
+/// Times are measured on the schedule's own timeline, which starts at 0 and advances as the emitter sleeps.
+/// The timeline is wider than the `u64` delays that it's advanced by, so it can't overflow in practice.
+#[derive(Debug, Default)]
+pub struct EmissionSchedule {
+    /// The current time on the timeline
+    now_ms: u128,
+
+    /// The time of each signal's next emission on the timeline
+    deadlines: HashMap<String, u128>,
+
+    /// The signals ordered by the time of their next emission
+    queue: BTreeSet<(u128, String)>,
+
+    /// The time at which each signal which has been rescheduled since the timeline last advanced became due
+    due_since: HashMap<String, u128>,
+}
+
+impl EmissionSchedule {
+    /// Creates an empty EmissionSchedule
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules the next emission of a signal, replacing its previous emission time if it has one
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `delay_ms`: the time from now until the signal's next emission
+    pub fn schedule(&mut self, id: &str, delay_ms: u64) {
+        self.due_since.remove(id);
+        self.set_deadline(id, self.now_ms + u128::from(delay_ms));
+    }
+
+    /// Schedules the next emission of a signal after it was emitted or its interval changed.
+    /// A due signal is scheduled one interval after it became due rather than one interval from now,
+    /// so the time that it waited to be emitted doesn't delay all of its later emissions.
+    /// If it has been due for a whole interval or more, it's scheduled one interval from now instead of catching up.
+    /// Rescheduling it again before the timeline advances, such as when its interval changes as it's emitted,
+    /// starts from the same time. Other signals are scheduled one interval from now.
+    /// Returns the time until the signal's next emission.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `interval_ms`: the signal's emission interval
+    pub fn reschedule(&mut self, id: &str, interval_ms: u64) -> u64 {
+        let due_since = match self.due_since.get(id) {
+            Some(due_since) => Some(*due_since),
+            None => self
+                .deadlines
+                .get(id)
+                .copied()
+                .filter(|deadline| *deadline <= self.now_ms),
+        };
+
+        let interval = u128::from(interval_ms);
+        let deadline = match due_since {
+            Some(due_since) => {
+                self.due_since.insert(id.to_string(), due_since);
+                if self.now_ms - due_since < interval {
+                    due_since + interval
+                } else {
+                    self.now_ms + interval
+                }
+            }
+            None => self.now_ms + interval,
+        };
+
+        self.set_deadline(id, deadline);
+        self.time_until_deadline(deadline)
+    }
+
+    /// Removes a signal from the schedule
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    pub fn remove(&mut self, id: &str) {
+        if let Some(deadline) = self.deadlines.remove(id) {
+            self.queue.remove(&(deadline, id.to_string()));
+        }
+
+        self.due_since.remove(id);
+    }
+
+    /// Advances the timeline. Signals stay due until they're scheduled again
+    ///
+    /// # Arguments
+    /// - `elapsed_ms`: the time that has elapsed since the timeline was last advanced
+    pub fn advance(&mut self, elapsed_ms: u64) {
+        self.now_ms += u128::from(elapsed_ms);
+        self.due_since.clear();
+    }
+
+    /// Gets the time until a signal's next emission, which is 0 if the signal is due.
+    /// Returns `None` if the signal isn't scheduled.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    pub fn time_until(&self, id: &str) -> Option<u64> {
+        self.deadlines
+            .get(id)
+            .map(|deadline| self.time_until_deadline(*deadline))
+    }
+
+    /// Gets the time since a signal became due, which is 0 if the signal isn't due.
+    /// Returns `None` if the signal isn't scheduled.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    pub fn time_overdue(&self, id: &str) -> Option<u64> {
+        self.deadlines.get(id).map(|deadline| {
+            u64::try_from(self.now_ms.saturating_sub(*deadline)).unwrap_or(u64::MAX)
+        })
+    }
+
+    /// Gets the signals whose next emission is within the lookahead, soonest first,
+    /// followed by the first signal after them so that the caller knows when the next one is due.
+    /// Each signal is returned with the time until its next emission.
+    ///
+    /// # Arguments
+    /// - `lookahead_ms`: how far ahead to look for emissions
+    pub fn upcoming(&self, lookahead_ms: u64) -> Vec<(&str, u64)> {
+        let horizon = self.now_ms + u128::from(lookahead_ms);
+        let mut upcoming = Vec::new();
+
+        for (deadline, id) in self.queue.iter() {
+            upcoming.push((id.as_str(), self.time_until_deadline(*deadline)));
+            if *deadline > horizon {
+                break;
+            }
+        }
+
+        upcoming
+    }
+
+    /// Sets the time of a signal's next emission, replacing its previous emission time if it has one
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `deadline`: the time of the signal's next emission on the timeline
+    fn set_deadline(&mut self, id: &str, deadline: u128) {
+        if let Some(previous) = self.deadlines.insert(id.to_string(), deadline) {
+            self.queue.remove(&(previous, id.to_string()));
+        }
+
+        self.queue.insert((deadline, id.to_string()));
+    }
+
+    /// Gets the time until a point on the timeline, which is 0 if it has passed
+    ///
+    /// # Arguments
+    /// - `deadline`: the point on the timeline
+    fn time_until_deadline(&self, deadline: u128) -> u64 {
+        u64::try_from(deadline.saturating_sub(self.now_ms)).unwrap_or(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod emission_schedule_tests {
+    use super::*;
+
+    #[test]
+    fn upcoming_returns_signals_within_lookahead_and_the_next_one() {
+        let mut uut = EmissionSchedule::new();
+        uut.schedule("a", 100);
+        uut.schedule("b", 20);
+        uut.schedule("c", 0);
+        uut.schedule("d", 500);
+
+        assert_eq!(uut.upcoming(0), vec![("c", 0), ("b", 20)]);
+        assert_eq!(
+            uut.upcoming(100),
+            vec![("c", 0), ("b", 20), ("a", 100), ("d", 500)]
+        );
+
+        uut.advance(50);
+        assert_eq!(uut.upcoming(0), vec![("c", 0), ("b", 0), ("a", 50)]);
+    }
+
+    #[test]
+    fn schedule_replaces_previous_emission_time() {
+        let mut uut = EmissionSchedule::new();
+        uut.schedule("a", 100);
+        uut.advance(150);
+        assert_eq!(uut.time_until("a"), Some(0));
+
+        uut.schedule("a", 100);
+        assert_eq!(uut.time_until("a"), Some(100));
+        assert_eq!(uut.upcoming(0), vec![("a", 100)]);
+
+        uut.remove("a");
+        assert_eq!(uut.time_until("a"), None);
+        assert!(uut.upcoming(u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn reschedule_keeps_the_phase_of_late_emissions() {
+        let mut uut = EmissionSchedule::new();
+        uut.schedule("a", 1000);
+
+        // Emitted 30ms late, so the next emission is 970ms away
+        uut.advance(1030);
+        assert_eq!(uut.time_overdue("a"), Some(30));
+        assert_eq!(uut.reschedule("a", 1000), 970);
+        assert_eq!(uut.time_overdue("a"), Some(0));
+
+        // Rescheduling again, such as after the interval changes, starts from the same time
+        assert_eq!(uut.reschedule("a", 1000), 970);
+        assert_eq!(uut.reschedule("a", 500), 470);
+
+        // A signal which missed a whole interval starts over instead of catching up
+        uut.advance(2000);
+        assert_eq!(uut.reschedule("a", 500), 500);
+
+        assert_eq!(uut.reschedule("b", 100), 100);
+    }
+
+    #[test]
+    fn advance_does_not_overflow() {
+        let mut uut = EmissionSchedule::new();
+        uut.schedule("a", 20);
+        uut.advance(u64::MAX);
+        assert_eq!(uut.time_until("a"), Some(0));
+
+        uut.schedule("a", 20);
+        assert_eq!(uut.time_until("a"), Some(20));
+    }
+}
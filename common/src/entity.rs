@@ -2,8 +2,15 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::{convert::Infallible, fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 
+use crate::{conversion::Conversion, signal::EmissionPolicy};
+
+/// Payload formats which carry typed values, so numbers don't need to be parsed from text
+const TYPED_PAYLOAD_FORMATS: [&str; 2] = ["json", "protobuf"];
+
 /// Represents an entity
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Entity {
@@ -34,6 +41,36 @@ pub struct EntityEndpoint {
 
     /// Additional context provided by the endpoint
     pub context: String,
+
+    /// Hints about the endpoint's capabilities, which are used to choose between endpoints
+    #[serde(default)]
+    pub capabilities: EndpointCapabilities,
+}
+
+/// Optional hints about what an entity endpoint can provide.
+/// Hints which aren't set don't affect how the endpoint is scored.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointCapabilities {
+    /// The shortest interval at which the endpoint can publish values, which bounds its maximum rate
+    #[serde(default)]
+    pub min_interval_ms: Option<u64>,
+
+    /// The format of the values that the endpoint publishes, such as `json`, `protobuf`, or `text`
+    #[serde(default)]
+    pub payload_format: Option<String>,
+
+    /// The delivery guarantee of the endpoint
+    #[serde(default)]
+    pub reliability: Option<Reliability>,
+}
+
+/// The delivery guarantee of an entity endpoint
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Reliability {
+    /// Values may be lost
+    BestEffort,
+    /// Every value is delivered
+    Reliable,
 }
 
 impl Entity {
@@ -46,19 +83,406 @@ impl Entity {
     /// - `accepted_operations`: the list of operations which are acceptable
     pub fn is_supported(
         &self,
-        accepted_protocols: &[&str],
-        accepted_operations: &[&str],
+        accepted_protocols: &[Protocol],
+        accepted_operations: &[Operation],
     ) -> Option<EntityEndpoint> {
-        for endpoint in self.endpoints.iter() {
-            if accepted_protocols.contains(&endpoint.protocol.as_str()) {
-                for operation in endpoint.operations.iter() {
-                    if accepted_operations.contains(&operation.as_str()) {
-                        return Some(endpoint.clone());
-                    }
-                }
-            }
+        self.endpoints
+            .iter()
+            .find(|endpoint| {
+                accepted_protocols.contains(&endpoint.protocol())
+                    && accepted_operations
+                        .iter()
+                        .any(|operation| endpoint.supports_operation(operation))
+            })
+            .cloned()
+    }
+
+    /// Checks to see if any of this entity's endpoints supports the requested operation
+    ///
+    /// # Arguments
+    /// - `operation`: the operation to check for
+    pub fn supports_operation(&self, operation: &Operation) -> bool {
+        self.endpoints
+            .iter()
+            .any(|endpoint| endpoint.supports_operation(operation))
+    }
+}
+
+impl EntityEndpoint {
+    /// Gets the protocol to use to contact this entity
+    pub fn protocol(&self) -> Protocol {
+        Protocol::from(self.protocol.as_str())
+    }
+
+    /// Checks to see if this endpoint supports the requested operation
+    ///
+    /// # Arguments
+    /// - `operation`: the operation to check for
+    pub fn supports_operation(&self, operation: &Operation) -> bool {
+        self.operations
+            .iter()
+            .any(|op| Operation::from(op.as_str()) == *operation)
+    }
+
+    /// Scores how well this endpoint's capabilities suit a signal's emission policy.
+    /// Endpoints with higher scores are preferred. An endpoint without capability hints scores 0.
+    ///
+    /// - An endpoint which can publish at least as often as the signal is emitted scores higher,
+    ///   and one which can't keep up scores lower.
+    /// - If the signal is only emitted on changes or when a trigger is satisfied, a lost value might never be emitted,
+    ///   so reliable endpoints score higher and best effort endpoints score lower.
+    /// - If the signal is converted or rounded, its values must be numeric,
+    ///   so endpoints with typed payload formats such as `json` or `protobuf` score higher.
+    ///
+    /// # Arguments
+    /// - `policy`: the emission policy of the signal
+    pub fn score(&self, policy: &EmissionPolicy) -> i32 {
+        let capabilities = &self.capabilities;
+        let mut score = 0;
+
+        if let Some(min_interval_ms) = capabilities.min_interval_ms {
+            score += if min_interval_ms <= policy.interval_ms {
+                2
+            } else {
+                -2
+            };
         }
 
-        None
+        if policy.emit_only_if_changed || policy.trigger.is_some() {
+            score += match capabilities.reliability {
+                Some(Reliability::Reliable) => 1,
+                Some(Reliability::BestEffort) => -1,
+                None => 0,
+            };
+        }
+
+        let needs_numeric_values = policy.conversion != Conversion::None
+            || policy.decimal_places.is_some()
+            || policy.privacy.is_some();
+        let has_typed_payload = capabilities
+            .payload_format
+            .as_deref()
+            .is_some_and(|format| TYPED_PAYLOAD_FORMATS.contains(&format.to_lowercase().as_str()));
+        if needs_numeric_values && has_typed_payload {
+            score += 1;
+        }
+
+        score
+    }
+
+    /// Selects the first of the requested operations which this endpoint supports,
+    /// or `None` if it supports none of them.
+    ///
+    /// # Arguments
+    /// - `preferred_operations`: the operations to select from, in order of preference
+    pub fn select_operation(&self, preferred_operations: &[Operation]) -> Option<Operation> {
+        preferred_operations
+            .iter()
+            .find(|operation| self.supports_operation(operation))
+            .cloned()
+    }
+}
+
+/// A protocol which providers use to communicate with data adapters.
+/// Protocols are parsed from entity endpoints case-insensitively.
+/// Protocols which aren't known to Freyja are parsed as `Custom`, so adapters can support their own protocols.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    /// The protocol of the Ibeji sample providers
+    Grpc,
+    /// The v2 digital twin provider protocol
+    GrpcV2,
+    /// REST over HTTP
+    Http,
+    /// REST over HTTPS
+    Https,
+    /// MQTT
+    Mqtt,
+    /// Eclipse uProtocol
+    UProtocol,
+    /// The protocol of the in-memory mock adapters
+    InMemory,
+    /// The protocol of recorded signal files
+    FileReplay,
+    /// The protocol of Freyja's built-in self-test adapter
+    Loopback,
+    /// Diagnostics over IP, which is used to read diagnostic trouble codes from ECUs
+    Doip,
+    /// Service-Oriented Vehicle Diagnostics, which exposes diagnostic data over REST
+    Sovd,
+    /// A protocol which isn't known to Freyja.
+    /// Use `Protocol::from` rather than constructing this directly, so that known protocols are never custom.
+    Custom(String),
+}
+
+impl Protocol {
+    /// The known protocols and their names in entity endpoints
+    const KNOWN: [(Protocol, &'static str); 11] = [
+        (Protocol::Grpc, "grpc"),
+        (Protocol::GrpcV2, "grpc-v2"),
+        (Protocol::Http, "http"),
+        (Protocol::Https, "https"),
+        (Protocol::Mqtt, "mqtt"),
+        (Protocol::UProtocol, "uprotocol"),
+        (Protocol::InMemory, "in-memory"),
+        (Protocol::FileReplay, "file-replay"),
+        (Protocol::Loopback, "loopback"),
+        (Protocol::Doip, "doip"),
+        (Protocol::Sovd, "sovd"),
+    ];
+
+    /// Gets the name of this protocol in entity endpoints
+    pub fn as_str(&self) -> &str {
+        match self {
+            Protocol::Custom(name) => name,
+            known => Self::KNOWN
+                .iter()
+                .find(|(protocol, _)| protocol == known)
+                .map(|(_, name)| *name)
+                .unwrap(),
+        }
+    }
+}
+
+impl From<&str> for Protocol {
+    /// Parses a protocol name from an entity endpoint
+    ///
+    /// # Arguments
+    /// - `name`: the protocol name
+    fn from(name: &str) -> Self {
+        Self::KNOWN
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map(|(protocol, _)| protocol.clone())
+            .unwrap_or_else(|| Protocol::Custom(name.to_owned()))
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = Infallible;
+
+    /// Parses a protocol name from an entity endpoint
+    ///
+    /// # Arguments
+    /// - `s`: the protocol name
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An operation which an entity endpoint supports.
+/// Operations are parsed from entity endpoints case-insensitively.
+/// Operations which aren't known to Freyja are parsed as `Custom`, so adapters can support their own operations.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Gets the current value
+    Get,
+    /// Sets the value
+    Set,
+    /// Subscribes to value changes
+    Subscribe,
+    /// Subscribes to value changes through Ibeji's managed subscribe module
+    ManagedSubscribe,
+    /// Invokes a command
+    Invoke,
+    /// Streams values
+    Stream,
+    /// An operation which isn't known to Freyja.
+    /// Use `Operation::from` rather than constructing this directly, so that known operations are never custom.
+    Custom(String),
+}
+
+impl Operation {
+    /// The known operations and their names in entity endpoints
+    const KNOWN: [(Operation, &'static str); 6] = [
+        (Operation::Get, "Get"),
+        (Operation::Set, "Set"),
+        (Operation::Subscribe, "Subscribe"),
+        (Operation::ManagedSubscribe, "ManagedSubscribe"),
+        (Operation::Invoke, "Invoke"),
+        (Operation::Stream, "Stream"),
+    ];
+
+    /// Gets the name of this operation in entity endpoints
+    pub fn as_str(&self) -> &str {
+        match self {
+            Operation::Custom(name) => name,
+            known => Self::KNOWN
+                .iter()
+                .find(|(operation, _)| operation == known)
+                .map(|(_, name)| *name)
+                .unwrap(),
+        }
+    }
+}
+
+impl From<&str> for Operation {
+    /// Parses an operation name from an entity endpoint
+    ///
+    /// # Arguments
+    /// - `name`: the operation name
+    fn from(name: &str) -> Self {
+        Self::KNOWN
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map(|(operation, _)| operation.clone())
+            .unwrap_or_else(|| Operation::Custom(name.to_owned()))
+    }
+}
+
+impl FromStr for Operation {
+    type Err = Infallible;
+
+    /// Parses an operation name from an entity endpoint
+    ///
+    /// # Arguments
+    /// - `s`: the operation name
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod entity_tests {
+    use super::*;
+
+    fn create_entity(protocol: &str, operations: &[&str]) -> Entity {
+        Entity {
+            id: String::from("dtmi:sdv:HVAC:AmbientAirTemperature;1"),
+            endpoints: vec![EntityEndpoint {
+                protocol: protocol.to_string(),
+                operations: operations.iter().map(|o| o.to_string()).collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn protocols_and_operations_round_trip() {
+        for (protocol, name) in Protocol::KNOWN.iter() {
+            assert_eq!(&Protocol::from(*name), protocol);
+            assert_eq!(protocol.to_string(), *name);
+        }
+
+        for (operation, name) in Operation::KNOWN.iter() {
+            assert_eq!(&Operation::from(*name), operation);
+            assert_eq!(operation.to_string(), *name);
+        }
+    }
+
+    #[test]
+    fn parsing_is_case_insensitive_and_keeps_custom_names() {
+        assert_eq!(Protocol::from("MQTT"), Protocol::Mqtt);
+        assert_eq!(Operation::from("subscribe"), Operation::Subscribe);
+
+        let custom = "my-Protocol".parse::<Protocol>().unwrap();
+        assert_eq!(custom, Protocol::Custom(String::from("my-Protocol")));
+        assert_eq!(custom.as_str(), "my-Protocol");
+    }
+
+    #[test]
+    fn is_supported_matches_protocol_and_operation() {
+        let entity = create_entity("Mqtt", &["subscribe"]);
+
+        assert!(entity
+            .is_supported(&[Protocol::Mqtt], &[Operation::Get, Operation::Subscribe])
+            .is_some());
+        assert!(entity
+            .is_supported(&[Protocol::Grpc], &[Operation::Subscribe])
+            .is_none());
+        assert!(entity
+            .is_supported(&[Protocol::Mqtt], &[Operation::Get])
+            .is_none());
+    }
+
+    #[test]
+    fn select_operation_uses_preference_order() {
+        let entity = create_entity("grpc", &["Get", "Subscribe"]);
+        let endpoint = &entity.endpoints[0];
+
+        assert_eq!(
+            endpoint.select_operation(&[Operation::Subscribe, Operation::Get]),
+            Some(Operation::Subscribe)
+        );
+        assert_eq!(endpoint.select_operation(&[Operation::Set]), None);
+        assert!(entity.supports_operation(&Operation::Get));
+    }
+
+    fn create_endpoint(capabilities: EndpointCapabilities) -> EntityEndpoint {
+        EntityEndpoint {
+            capabilities,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn score_prefers_endpoints_which_keep_up_with_emission() {
+        let policy = EmissionPolicy {
+            interval_ms: 1000,
+            ..Default::default()
+        };
+
+        let fast = create_endpoint(EndpointCapabilities {
+            min_interval_ms: Some(100),
+            ..Default::default()
+        });
+        let slow = create_endpoint(EndpointCapabilities {
+            min_interval_ms: Some(5000),
+            ..Default::default()
+        });
+        let unknown = create_endpoint(EndpointCapabilities::default());
+
+        assert!(fast.score(&policy) > unknown.score(&policy));
+        assert!(unknown.score(&policy) > slow.score(&policy));
+        assert_eq!(unknown.score(&policy), 0);
+    }
+
+    #[test]
+    fn score_considers_reliability_and_payload_format_when_relevant() {
+        let reliable = create_endpoint(EndpointCapabilities {
+            reliability: Some(Reliability::Reliable),
+            payload_format: Some(String::from("JSON")),
+            ..Default::default()
+        });
+        let best_effort = create_endpoint(EndpointCapabilities {
+            reliability: Some(Reliability::BestEffort),
+            payload_format: Some(String::from("text")),
+            ..Default::default()
+        });
+
+        let default_policy = EmissionPolicy::default();
+        assert_eq!(reliable.score(&default_policy), 0);
+        assert_eq!(best_effort.score(&default_policy), 0);
+
+        let on_change_policy = EmissionPolicy {
+            emit_only_if_changed: true,
+            decimal_places: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(reliable.score(&on_change_policy), 2);
+        assert_eq!(best_effort.score(&on_change_policy), -1);
+    }
+
+    #[test]
+    fn capabilities_default_when_missing_from_json() {
+        let endpoint: EntityEndpoint = serde_json::from_str(
+            r#"{"protocol":"grpc","operations":["Get"],"uri":"http://0.0.0.0:1","context":""}"#,
+        )
+        .unwrap();
+
+        assert_eq!(endpoint.capabilities, EndpointCapabilities::default());
     }
 }
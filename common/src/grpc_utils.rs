@@ -0,0 +1,365 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tonic::{
+    metadata::{Ascii, MetadataKey, MetadataValue},
+    service::{interceptor::InterceptedService, Interceptor},
+    transport::{Channel, Endpoint},
+    Request, Status,
+};
+
+use crate::uri::{EndpointUri, UriKind};
+
+/// The metadata key for the bearer token
+pub const AUTHORIZATION_METADATA_KEY: &str = "authorization";
+
+/// The metadata key for the id which identifies a request and its retries in the logs of both sides
+pub const REQUEST_ID_METADATA_KEY: &str = "x-request-id";
+
+/// The metadata key which tonic uses for the deadline of a request
+const TIMEOUT_METADATA_KEY: &str = "grpc-timeout";
+
+/// The channel type for clients which use the shared request middleware
+pub type GrpcChannel = InterceptedService<Channel, GrpcInterceptor>;
+
+/// The request settings which are shared by every gRPC adapter.
+/// Adapters flatten this into their own config, so these settings are at the top level of the adapter's config file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcClientConfig {
+    /// Headers to add to every request, such as API keys
+    pub headers: HashMap<String, String>,
+
+    /// A bearer token to send in the `authorization` header of every request
+    pub bearer_token: Option<String>,
+
+    /// The path of a file containing a bearer token to send in the `authorization` header of every request.
+    /// The file is read before each request, so tokens which are rotated by another process are picked up.
+    /// This takes precedence over `bearer_token`
+    pub bearer_token_path: Option<String>,
+
+    /// The maximum time that a request may take in milliseconds, including its retries.
+    /// The remaining time is sent to the server as the request's deadline.
+    /// If this is `None`, requests don't have a deadline
+    pub request_timeout_ms: Option<u64>,
+}
+
+impl GrpcClientConfig {
+    /// Gets the request timeout, if any
+    pub fn request_timeout(&self) -> Option<Duration> {
+        self.request_timeout_ms.map(Duration::from_millis)
+    }
+}
+
+/// An interceptor which attaches the configured headers, bearer token, a request id,
+/// and a deadline to every request sent by a client
+#[derive(Clone, Default)]
+pub struct GrpcInterceptor {
+    /// The configured headers
+    headers: Vec<(MetadataKey<Ascii>, MetadataValue<Ascii>)>,
+
+    /// The `authorization` value for the configured bearer token, if any
+    bearer_token: Option<MetadataValue<Ascii>>,
+
+    /// The path of the file to read the bearer token from before each request, if any
+    bearer_token_path: Option<PathBuf>,
+
+    /// The deadline for requests which don't already have one
+    request_timeout: Option<Duration>,
+}
+
+impl GrpcInterceptor {
+    /// Creates an interceptor from the shared gRPC settings.
+    /// Returns an error if a header or the bearer token can't be sent as gRPC metadata
+    ///
+    /// # Arguments
+    /// - `config`: the shared gRPC settings
+    pub fn from_config(config: &GrpcClientConfig) -> Result<Self, GrpcUtilsError> {
+        let headers = config
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let key = MetadataKey::from_bytes(name.as_bytes()).map_err(|e| {
+                    GrpcUtilsError::invalid_metadata(format!("Invalid header name {name:?}: {e}"))
+                })?;
+                let value = MetadataValue::try_from(value.as_str()).map_err(|e| {
+                    GrpcUtilsError::invalid_metadata(format!(
+                        "Invalid value for header {name:?}: {e}"
+                    ))
+                })?;
+
+                Ok((key, value))
+            })
+            .collect::<Result<_, GrpcUtilsError>>()?;
+
+        Ok(Self {
+            headers,
+            bearer_token: config
+                .bearer_token
+                .as_deref()
+                .map(authorization_value)
+                .transpose()?,
+            bearer_token_path: config.bearer_token_path.as_ref().map(PathBuf::from),
+            request_timeout: config.request_timeout(),
+        })
+    }
+}
+
+impl Interceptor for GrpcInterceptor {
+    /// Attaches the metadata to a request.
+    /// If the bearer token is read from a file, the file is read again for each request so that rotated tokens are used.
+    /// A request id and a deadline are only added if the request doesn't already have them,
+    /// so requests created with a `GrpcRequestContext` keep theirs
+    ///
+    /// # Arguments
+    /// - `request`: the outgoing request
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(timeout) = self.request_timeout {
+            if !request.metadata().contains_key(TIMEOUT_METADATA_KEY) {
+                request.set_timeout(timeout);
+            }
+        }
+
+        let metadata = request.metadata_mut();
+        for (key, value) in self.headers.iter() {
+            metadata.insert(key.clone(), value.clone());
+        }
+
+        let bearer_token = match &self.bearer_token_path {
+            Some(path) => {
+                let token = fs::read_to_string(path).map_err(|e| {
+                    Status::unauthenticated(format!(
+                        "Could not read bearer token from {}: {e}",
+                        path.display()
+                    ))
+                })?;
+                Some(
+                    authorization_value(token.trim())
+                        .map_err(|e| Status::unauthenticated(e.to_string()))?,
+                )
+            }
+            None => self.bearer_token.clone(),
+        };
+
+        if let Some(value) = bearer_token {
+            metadata.insert(AUTHORIZATION_METADATA_KEY, value);
+        }
+
+        if !metadata.contains_key(REQUEST_ID_METADATA_KEY) {
+            metadata.insert(REQUEST_ID_METADATA_KEY, new_request_id());
+        }
+
+        Ok(request)
+    }
+}
+
+/// The state which is shared by every attempt of a request, so that retries are sent with the same request id
+/// and the deadline covers the retries rather than restarting with each attempt.
+/// Create one context for each logical request and create each attempt's request with it
+pub struct GrpcRequestContext {
+    /// The request id
+    request_id: MetadataValue<Ascii>,
+
+    /// The time by which the request must complete, if any
+    deadline: Option<Instant>,
+}
+
+impl GrpcRequestContext {
+    /// Creates a new GrpcRequestContext whose deadline starts now
+    ///
+    /// # Arguments
+    /// - `config`: the shared gRPC settings
+    pub fn new(config: &GrpcClientConfig) -> Self {
+        Self {
+            request_id: new_request_id(),
+            deadline: config
+                .request_timeout()
+                .map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// Creates a request for an attempt, with the request id and the time remaining until the deadline.
+    /// Returns `DeadlineExceeded` if the deadline has passed, since the server would reject the attempt anyway
+    ///
+    /// # Arguments
+    /// - `message`: the request message
+    // The error is a Status so that callers can use `?` in the attempts that they pass to a retry policy,
+    // which returns the same Status as a failed call and classifies it by its code. Boxing it would need
+    // to be undone at every call site, so the size of the error is accepted
+    #[allow(clippy::result_large_err)]
+    pub fn request<T>(&self, message: T) -> Result<Request<T>, Status> {
+        let mut request = Request::new(message);
+        request
+            .metadata_mut()
+            .insert(REQUEST_ID_METADATA_KEY, self.request_id.clone());
+
+        if let Some(deadline) = self.deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Status::deadline_exceeded(format!(
+                    "The deadline of request {:?} passed before it could be sent",
+                    self.request_id
+                )));
+            }
+
+            request.set_timeout(remaining);
+        }
+
+        Ok(request)
+    }
+}
+
+/// Opens a channel to a gRPC server.
+/// The uri is validated first, so that an invalid uri fails with an `InvalidUri` error which can't be retried
+///
+/// # Arguments
+/// - `uri`: the server's uri
+pub async fn connect(uri: String) -> Result<Channel, GrpcUtilsError> {
+    let uri = EndpointUri::parse(&uri, UriKind::Grpc).map_err(GrpcUtilsError::invalid_uri)?;
+
+    Endpoint::from_shared(uri.to_string())
+        .map_err(GrpcUtilsError::invalid_uri)?
+        .connect()
+        .await
+        .map_err(GrpcUtilsError::communication)
+}
+
+/// Creates a new random request id
+fn new_request_id() -> MetadataValue<Ascii> {
+    // Hex digits are always valid metadata
+    MetadataValue::try_from(format!("{:032x}", rand::random::<u128>())).unwrap()
+}
+
+/// Creates the `authorization` value for a bearer token.
+/// The value is marked as sensitive so that the token isn't logged
+///
+/// # Arguments
+/// - `token`: the bearer token
+fn authorization_value(token: &str) -> Result<MetadataValue<Ascii>, GrpcUtilsError> {
+    // The error would contain the token, so it's not included in the message
+    let mut value = MetadataValue::try_from(format!("Bearer {token}").as_str())
+        .map_err(|_| GrpcUtilsError::invalid_metadata("The bearer token is not valid metadata"))?;
+    value.set_sensitive(true);
+
+    Ok(value)
+}
+
+proc_macros::error! {
+    GrpcUtilsError {
+        InvalidMetadata,
+        InvalidUri,
+        Communication,
+        Unknown
+    }
+}
+
+#[cfg(test)]
+mod grpc_utils_tests {
+    use super::*;
+
+    use std::{env, process};
+
+    fn create_config(headers: &[(&str, &str)], bearer_token: Option<&str>) -> GrpcClientConfig {
+        GrpcClientConfig {
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            bearer_token: bearer_token.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn interceptor_attaches_headers_bearer_token_and_request_id() {
+        let mut uut = GrpcInterceptor::from_config(&create_config(
+            &[("x-api-key", "key"), ("X-Tenant", "fleet")],
+            Some("token"),
+        ))
+        .unwrap();
+
+        let request = uut.call(Request::new(())).unwrap();
+        let metadata = request.metadata();
+
+        assert_eq!(metadata.get("x-api-key").unwrap(), "key");
+        assert_eq!(metadata.get("x-tenant").unwrap(), "fleet");
+        assert!(metadata.contains_key(REQUEST_ID_METADATA_KEY));
+        assert!(!metadata.contains_key(TIMEOUT_METADATA_KEY));
+
+        let authorization = metadata.get(AUTHORIZATION_METADATA_KEY).unwrap();
+        assert_eq!(authorization, "Bearer token");
+        assert!(authorization.is_sensitive());
+    }
+
+    #[test]
+    fn interceptor_reads_bearer_token_from_file() {
+        let path = env::temp_dir().join(format!("grpc_bearer_token_{}", process::id()));
+        let mut config = create_config(&[], Some("unused"));
+        config.bearer_token_path = Some(path.to_string_lossy().into_owned());
+        let mut uut = GrpcInterceptor::from_config(&config).unwrap();
+
+        // The token is read again for each request
+        fs::write(&path, "first\n").unwrap();
+        let first = uut.call(Request::new(())).unwrap();
+        fs::write(&path, "second\n").unwrap();
+        let second = uut.call(Request::new(())).unwrap();
+        fs::remove_file(&path).unwrap();
+        let missing = uut.call(Request::new(()));
+
+        assert_eq!(
+            first.metadata().get(AUTHORIZATION_METADATA_KEY).unwrap(),
+            "Bearer first"
+        );
+        assert_eq!(
+            second.metadata().get(AUTHORIZATION_METADATA_KEY).unwrap(),
+            "Bearer second"
+        );
+        assert!(missing.is_err_and(|s| s.code() == tonic::Code::Unauthenticated));
+    }
+
+    #[test]
+    fn from_config_returns_err_for_invalid_metadata() {
+        for config in [
+            create_config(&[("invalid header", "value")], None),
+            create_config(&[("x-api-key", "line\nbreak")], None),
+            create_config(&[], Some("line\nbreak")),
+        ] {
+            let result = GrpcInterceptor::from_config(&config);
+            assert!(result.is_err_and(|e| e.kind() == GrpcUtilsErrorKind::InvalidMetadata));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn context_keeps_request_id_and_deadline_across_attempts() {
+        let config = GrpcClientConfig {
+            request_timeout_ms: Some(1000),
+            ..Default::default()
+        };
+        let context = GrpcRequestContext::new(&config);
+        let mut interceptor = GrpcInterceptor::from_config(&config).unwrap();
+
+        let first = context.request(()).unwrap();
+        tokio::time::advance(Duration::from_millis(600)).await;
+        let second = context.request(()).unwrap();
+
+        // The interceptor keeps the id and deadline which the context set
+        let second = interceptor.call(second).unwrap();
+        assert_eq!(
+            first.metadata().get(REQUEST_ID_METADATA_KEY),
+            second.metadata().get(REQUEST_ID_METADATA_KEY)
+        );
+        assert_eq!(
+            second.metadata().get(TIMEOUT_METADATA_KEY).unwrap(),
+            "400000u"
+        );
+
+        tokio::time::advance(Duration::from_millis(400)).await;
+        let result = context.request(());
+        assert!(result.is_err_and(|s| s.code() == tonic::Code::DeadlineExceeded));
+    }
+}
@@ -2,6 +2,8 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+pub mod blob_sink;
+pub mod clock;
 pub mod cloud_adapter;
 pub mod cmd_utils;
 pub mod config_utils;
@@ -10,15 +12,27 @@ pub mod data_adapter;
 pub mod data_adapter_selector;
 pub mod digital_twin_adapter;
 pub mod digital_twin_map_entry;
+pub mod duplicate_filter;
+pub mod emission_schedule;
 pub mod entity;
+pub mod grpc_utils;
 pub mod http_utils;
 pub mod mapping_adapter;
 pub mod message_utils;
+pub mod namespace;
+pub mod privacy;
+pub mod protobuf_decoder;
 pub mod retry_utils;
+pub mod secret_provider;
 pub mod service_discovery_adapter;
 pub mod service_discovery_adapter_selector;
+pub mod shutdown_signal;
 pub mod signal;
 pub mod signal_store;
+pub mod timestamp_format;
+pub mod traffic_trace;
+pub mod trigger;
+pub mod uri;
 
 /// Expands to `env!("OUT_DIR")`.
 /// Since we cannot use a constant in the `env!` macro,
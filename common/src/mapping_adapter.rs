@@ -48,6 +48,24 @@ pub trait MappingAdapter {
     ) -> Result<GetMappingResponse, MappingAdapterError>;
 }
 
+/// A function that creates a boxed `MappingAdapter`.
+/// This enables Freyja to use multiple mapping adapters of different types.
+pub type MappingAdapterConstructor =
+    fn(
+        Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Box<dyn MappingAdapter + Send + Sync>, MappingAdapterError>;
+
+/// Creates a new boxed instance of a `MappingAdapter`.
+/// This function can be used as a `MappingAdapterConstructor`.
+///
+/// # Arguments
+/// - `selector`: the service discovery adapter selector to use
+pub fn create_boxed<TMappingAdapter: MappingAdapter + Send + Sync + 'static>(
+    selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+) -> Result<Box<dyn MappingAdapter + Send + Sync>, MappingAdapterError> {
+    Ok(Box::new(TMappingAdapter::create_new(selector)?))
+}
+
 /// A request for the check for work api
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CheckForWorkRequest {}
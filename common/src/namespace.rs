@@ -0,0 +1,56 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+/// The separator between a namespace and an id in a qualified id.
+/// This doesn't appear in DTMIs, so entity ids which are DTMIs are never mistaken for qualified ids
+pub const NAMESPACE_SEPARATOR: &str = "::";
+
+/// Qualifies an id with a namespace. If there is no namespace, the id is returned unchanged.
+/// Namespaces keep signals from different sources distinguishable when their entity ids are identical.
+/// A signal in a namespace has a qualified id such as `cabin::dtmi:sdv:HVAC:AmbientAirTemperature;1`,
+/// which is its id in the signal store and data adapter selector,
+/// while data adapters and providers continue to use the unqualified entity id.
+///
+/// # Arguments
+/// - `namespace`: the namespace, if any
+/// - `id`: the id to qualify
+pub fn qualify(namespace: Option<&str>, id: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{namespace}{NAMESPACE_SEPARATOR}{id}"),
+        None => id.to_owned(),
+    }
+}
+
+/// Splits a qualified id into its namespace and the unqualified id.
+/// Ids without a namespace are returned with a namespace of `None`.
+///
+/// # Arguments
+/// - `qualified_id`: the id to split
+pub fn split(qualified_id: &str) -> (Option<&str>, &str) {
+    match qualified_id.split_once(NAMESPACE_SEPARATOR) {
+        Some((namespace, id)) => (Some(namespace), id),
+        None => (None, qualified_id),
+    }
+}
+
+#[cfg(test)]
+mod namespace_tests {
+    use super::*;
+
+    const ENTITY_ID: &str = "dtmi:sdv:HVAC:AmbientAirTemperature;1";
+
+    #[test]
+    fn qualify_and_split_round_trip() {
+        let qualified_id = qualify(Some("cabin"), ENTITY_ID);
+
+        assert_eq!(qualified_id, format!("cabin::{ENTITY_ID}"));
+        assert_eq!(split(&qualified_id), (Some("cabin"), ENTITY_ID));
+    }
+
+    #[test]
+    fn ids_without_namespace_are_unchanged() {
+        assert_eq!(qualify(None, ENTITY_ID), ENTITY_ID);
+        assert_eq!(split(ENTITY_ID), (None, ENTITY_ID));
+    }
+}
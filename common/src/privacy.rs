@@ -0,0 +1,166 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A transformation which reduces the precision of a signal's values before they're emitted,
+/// for signals such as a vehicle's location which are subject to privacy constraints.
+/// Noise is added first and the noisy value is then quantized, so the emitted values are always multiples of the step
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyPolicy {
+    /// Random noise to add to values. If this is `None`, no noise is added
+    #[serde(default)]
+    pub noise: Option<Noise>,
+
+    /// Values are rounded to the nearest multiple of this step, such as `0.01` for a latitude.
+    /// If this is `None` or not positive, values are not quantized
+    #[serde(default)]
+    pub quantization_step: Option<f64>,
+}
+
+/// Random noise which is added to a value
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Noise {
+    /// Noise drawn from a Laplace distribution centered on zero.
+    /// A `scale` of `sensitivity / epsilon` gives epsilon-differential privacy
+    /// for a value which differs by at most `sensitivity` between the individuals it could describe
+    Laplace { scale: f64 },
+
+    /// Noise chosen uniformly between `-amplitude` and `amplitude`
+    Uniform { amplitude: f64 },
+}
+
+impl Noise {
+    /// Draws a sample of the noise
+    ///
+    /// # Arguments
+    /// - `rng`: the random number generator to sample the noise with
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match self {
+            Self::Laplace { scale } => {
+                // The difference of two exponentially distributed values is Laplace distributed.
+                // 1 - u is in (0, 1], so the logarithms are finite
+                let u1: f64 = rng.gen();
+                let u2: f64 = rng.gen();
+                scale * ((1.0 - u2).ln() - (1.0 - u1).ln())
+            }
+            Self::Uniform { amplitude } => (2.0 * rng.gen::<f64>() - 1.0) * amplitude,
+        }
+    }
+}
+
+impl PrivacyPolicy {
+    /// Applies the policy to a value
+    ///
+    /// # Arguments
+    /// - `value`: the value to transform
+    /// - `rng`: the random number generator to sample noise with
+    ///
+    /// # Example
+    /// ```rust
+    /// use freyja_common::privacy::PrivacyPolicy;
+    /// let policy = PrivacyPolicy {
+    ///     noise: None,
+    ///     quantization_step: Some(0.5),
+    /// };
+    /// assert_eq!(policy.apply(47.6394, &mut rand::thread_rng()), 47.5);
+    /// ```
+    pub fn apply<R: Rng + ?Sized>(&self, value: f64, rng: &mut R) -> f64 {
+        let noise_sample = match &self.noise {
+            Some(noise) => noise.sample(rng),
+            None => 0.0,
+        };
+
+        self.apply_with_noise(value, noise_sample)
+    }
+
+    /// Applies the policy to a value with a sample of the noise which has already been drawn,
+    /// such as a sample which is reused for each emission of the same value
+    ///
+    /// # Arguments
+    /// - `value`: the value to transform
+    /// - `noise_sample`: the noise to add to the value
+    pub fn apply_with_noise(&self, value: f64, noise_sample: f64) -> f64 {
+        let noisy = value + noise_sample;
+
+        match self.quantization_step {
+            Some(step) if step > 0.0 => (noisy / step).round() * step,
+            _ => noisy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod privacy_tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn apply_quantizes_to_nearest_step() {
+        let uut = PrivacyPolicy {
+            noise: None,
+            quantization_step: Some(0.25),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        assert_eq!(uut.apply(1.1, &mut rng), 1.0);
+        assert_eq!(uut.apply(1.2, &mut rng), 1.25);
+        assert_eq!(uut.apply(-1.2, &mut rng), -1.25);
+    }
+
+    #[test]
+    fn apply_ignores_non_positive_step() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for step in [0.0, -1.0] {
+            let uut = PrivacyPolicy {
+                noise: None,
+                quantization_step: Some(step),
+            };
+            assert_eq!(uut.apply(1.1, &mut rng), 1.1);
+        }
+    }
+
+    #[test]
+    fn uniform_noise_is_within_amplitude() {
+        let noise = Noise::Uniform { amplitude: 0.5 };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let samples: Vec<f64> = (0..1000).map(|_| noise.sample(&mut rng)).collect();
+        assert!(samples.iter().all(|s| s.abs() <= 0.5));
+        assert!(samples.iter().any(|s| *s != 0.0));
+    }
+
+    #[test]
+    fn laplace_noise_has_expected_spread() {
+        const SCALE: f64 = 2.0;
+        let noise = Noise::Laplace { scale: SCALE };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let samples: Vec<f64> = (0..10_000).map(|_| noise.sample(&mut rng)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let mean_absolute_deviation =
+            samples.iter().map(|s| s.abs()).sum::<f64>() / samples.len() as f64;
+
+        // The mean absolute deviation of a Laplace distribution centered on zero is its scale
+        assert!(mean.abs() < 0.1);
+        assert!((mean_absolute_deviation - SCALE).abs() < 0.1);
+    }
+
+    #[test]
+    fn apply_quantizes_noisy_value() {
+        let uut = PrivacyPolicy {
+            noise: Some(Noise::Uniform { amplitude: 0.4 }),
+            quantization_step: Some(1.0),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        // The noise is never large enough to move the value to another step
+        for _ in 0..100 {
+            assert_eq!(uut.apply(10.0, &mut rng), 10.0);
+        }
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, fs};
+
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FieldDescriptor, Kind, MessageDescriptor, Value,
+};
+use serde::{Deserialize, Serialize};
+
+/// The separator between the field names in a field path
+const FIELD_PATH_SEPARATOR: char = '.';
+
+/// Config for decoding protobuf payloads of a single message type
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtobufDecoderConfig {
+    /// The path of a compiled descriptor set containing the message type,
+    /// such as one generated with `protoc --include_imports --descriptor_set_out`
+    pub descriptor_set_path: String,
+
+    /// The fully qualified name of the message type, such as `vehicle.cabin.Temperature`
+    pub message_type: String,
+
+    /// The names of the fields leading to the signal value separated by `.`, such as `temperature.value`.
+    /// Every field except the last must be a message field, and the last must be a scalar or enum field
+    pub field_path: String,
+}
+
+/// Decodes protobuf payloads of an arbitrary message type using a compiled descriptor set
+/// and extracts the signal value from a field of the message.
+/// This lets data adapters consume providers with their own message types without a bespoke adapter for every schema.
+#[derive(Clone, Debug)]
+pub struct ProtobufDecoder {
+    /// The message type of the payloads
+    message: MessageDescriptor,
+
+    /// The fields leading to the signal value, starting from the payload message
+    field_path: Vec<FieldDescriptor>,
+}
+
+impl ProtobufDecoder {
+    /// Creates a decoder from its config, reading the descriptor set from the file system
+    ///
+    /// # Arguments
+    /// - `config`: the decoder config
+    pub fn new(config: &ProtobufDecoderConfig) -> Result<Self, ProtobufDecoderError> {
+        let descriptor_set = fs::read(&config.descriptor_set_path).map_err(|e| {
+            ProtobufDecoderError::io(format!(
+                "Could not read descriptor set {}: {e}",
+                config.descriptor_set_path
+            ))
+        })?;
+
+        Self::from_descriptor_set(&descriptor_set, &config.message_type, &config.field_path)
+    }
+
+    /// Creates decoders for each entry of a map of configs.
+    /// Returns an error if any of the decoders could not be created.
+    ///
+    /// # Arguments
+    /// - `configs`: the decoder configs, such as a map of topics to the config for their payloads
+    pub fn from_configs(
+        configs: &HashMap<String, ProtobufDecoderConfig>,
+    ) -> Result<HashMap<String, ProtobufDecoder>, ProtobufDecoderError> {
+        configs
+            .iter()
+            .map(|(key, config)| Ok((key.clone(), Self::new(config)?)))
+            .collect()
+    }
+
+    /// Creates a decoder from an encoded descriptor set
+    ///
+    /// # Arguments
+    /// - `descriptor_set`: the encoded `FileDescriptorSet`
+    /// - `message_type`: the fully qualified name of the message type
+    /// - `field_path`: the names of the fields leading to the signal value separated by `.`
+    pub fn from_descriptor_set(
+        descriptor_set: &[u8],
+        message_type: &str,
+        field_path: &str,
+    ) -> Result<Self, ProtobufDecoderError> {
+        let pool = DescriptorPool::decode(descriptor_set)
+            .map_err(ProtobufDecoderError::invalid_descriptor)?;
+
+        let message = pool.get_message_by_name(message_type).ok_or_else(|| {
+            ProtobufDecoderError::invalid_descriptor(format!(
+                "Message type {message_type} is not in the descriptor set"
+            ))
+        })?;
+
+        let invalid = |reason: &str| {
+            ProtobufDecoderError::invalid_field_path(format!(
+                "{reason} in field path {field_path:?} of message type {message_type}"
+            ))
+        };
+
+        let mut fields = Vec::new();
+        let mut current = message.clone();
+        let mut names = field_path.split(FIELD_PATH_SEPARATOR).peekable();
+        while let Some(name) = names.next() {
+            let field = current
+                .get_field_by_name(name)
+                .ok_or_else(|| invalid(&format!("Unknown field {name:?}")))?;
+            if field.is_list() || field.is_map() {
+                return Err(invalid(&format!("Repeated field {name:?}")));
+            }
+
+            match (field.kind(), names.peek()) {
+                (Kind::Message(next), Some(_)) => current = next,
+                (Kind::Message(_), None) => {
+                    return Err(invalid(&format!("Message field {name:?} at the end")))
+                }
+                (Kind::Bytes, None) => return Err(invalid(&format!("Bytes field {name:?}"))),
+                (_, Some(_)) => {
+                    return Err(invalid(&format!("Scalar field {name:?} before the end")))
+                }
+                (_, None) => {}
+            }
+
+            fields.push(field);
+        }
+
+        Ok(Self {
+            message,
+            field_path: fields,
+        })
+    }
+
+    /// Decodes a payload and gets the signal value from it.
+    /// Fields which aren't present in the payload have their default value.
+    /// Enum values are returned as the name of the enum value if it's known, or the number otherwise.
+    ///
+    /// # Arguments
+    /// - `payload`: the encoded message
+    pub fn decode(&self, payload: &[u8]) -> Result<String, ProtobufDecoderError> {
+        let mut message = DynamicMessage::decode(self.message.clone(), payload)
+            .map_err(ProtobufDecoderError::decode)?;
+
+        // The field path is validated when the decoder is created, so it always has a leaf
+        // and every field before the leaf is a message field
+        let (leaf, parents) = self.field_path.split_last().unwrap();
+        for field in parents {
+            let next = message.get_field(field).as_message().cloned();
+            message = next.ok_or_else(|| {
+                ProtobufDecoderError::invalid_field_path(format!(
+                    "Field {} is not a message",
+                    field.full_name()
+                ))
+            })?;
+        }
+
+        let value = match (message.get_field(leaf).as_ref(), leaf.kind()) {
+            (Value::EnumNumber(number), Kind::Enum(descriptor)) => descriptor
+                .get_value(*number)
+                .map(|value| value.name().to_owned())
+                .unwrap_or_else(|| number.to_string()),
+            (Value::Bool(value), _) => value.to_string(),
+            (Value::I32(value), _) => value.to_string(),
+            (Value::I64(value), _) => value.to_string(),
+            (Value::U32(value), _) => value.to_string(),
+            (Value::U64(value), _) => value.to_string(),
+            (Value::F32(value), _) => value.to_string(),
+            (Value::F64(value), _) => value.to_string(),
+            (Value::String(value), _) => value.clone(),
+            (value, _) => {
+                return Err(ProtobufDecoderError::invalid_field_path(format!(
+                    "Field {} has an unsupported value {value:?}",
+                    leaf.full_name()
+                )))
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+proc_macros::error! {
+    ProtobufDecoderError {
+        Io,
+        InvalidDescriptor,
+        InvalidFieldPath,
+        Decode
+    }
+}
+
+#[cfg(test)]
+mod protobuf_decoder_tests {
+    use super::*;
+
+    use prost::Message;
+    use prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto, FileDescriptorSet,
+    };
+
+    const CABIN_TYPE: &str = "vehicle.Cabin";
+
+    fn create_field(
+        name: &str,
+        number: i32,
+        field_type: Type,
+        label: Label,
+    ) -> FieldDescriptorProto {
+        let type_name = match field_type {
+            Type::Message => Some(".vehicle.Reading".to_string()),
+            Type::Enum => Some(".vehicle.Mode".to_string()),
+            _ => None,
+        };
+
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(field_type as i32),
+            type_name,
+            ..Default::default()
+        }
+    }
+
+    /// Creates an encoded descriptor set with a `vehicle.Cabin` message containing
+    /// a nested `vehicle.Reading` message, an enum, and a repeated field
+    fn create_descriptor_set() -> Vec<u8> {
+        let file = FileDescriptorProto {
+            name: Some("vehicle.proto".to_string()),
+            package: Some("vehicle".to_string()),
+            syntax: Some("proto3".to_string()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("Reading".to_string()),
+                    field: vec![create_field("value", 1, Type::Double, Label::Optional)],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("Cabin".to_string()),
+                    field: vec![
+                        create_field("temperature", 1, Type::Message, Label::Optional),
+                        create_field("mode", 2, Type::Enum, Label::Optional),
+                        create_field("tags", 3, Type::String, Label::Repeated),
+                        create_field("raw", 4, Type::Bytes, Label::Optional),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("Mode".to_string()),
+                value: ["MODE_OFF", "MODE_AUTO"]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(number, name)| EnumValueDescriptorProto {
+                        name: Some(name.to_string()),
+                        number: Some(number as i32),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        FileDescriptorSet { file: vec![file] }.encode_to_vec()
+    }
+
+    /// Creates an encoded `vehicle.Cabin` message
+    ///
+    /// # Arguments
+    /// - `decoder`: a decoder for `vehicle.Cabin` messages
+    /// - `temperature`: the value of the nested reading, or `None` to leave it unset
+    /// - `mode`: the number of the enum value
+    fn create_payload(decoder: &ProtobufDecoder, temperature: Option<f64>, mode: i32) -> Vec<u8> {
+        let mut cabin = DynamicMessage::new(decoder.message.clone());
+        if let Some(temperature) = temperature {
+            let reading_type = decoder.field_path[0].kind().as_message().unwrap().clone();
+            let mut reading = DynamicMessage::new(reading_type);
+            reading.set_field_by_name("value", Value::F64(temperature));
+            cabin.set_field_by_name("temperature", Value::Message(reading));
+        }
+        cabin.set_field_by_name("mode", Value::EnumNumber(mode));
+
+        cabin.encode_to_vec()
+    }
+
+    #[test]
+    fn decode_extracts_nested_field() {
+        let uut = ProtobufDecoder::from_descriptor_set(
+            &create_descriptor_set(),
+            CABIN_TYPE,
+            "temperature.value",
+        )
+        .unwrap();
+
+        let result = uut.decode(&create_payload(&uut, Some(21.5), 0));
+        assert!(result.is_ok_and(|value| value == "21.5"));
+
+        // Unset fields have their default value
+        let result = uut.decode(&create_payload(&uut, None, 0));
+        assert!(result.is_ok_and(|value| value == "0"));
+    }
+
+    #[test]
+    fn decode_returns_enum_value_names() {
+        let descriptor_set = create_descriptor_set();
+        let temperature_decoder =
+            ProtobufDecoder::from_descriptor_set(&descriptor_set, CABIN_TYPE, "temperature.value")
+                .unwrap();
+        let uut =
+            ProtobufDecoder::from_descriptor_set(&descriptor_set, CABIN_TYPE, "mode").unwrap();
+
+        let result = uut.decode(&create_payload(&temperature_decoder, None, 1));
+        assert!(result.is_ok_and(|value| value == "MODE_AUTO"));
+
+        let result = uut.decode(&create_payload(&temperature_decoder, None, 7));
+        assert!(result.is_ok_and(|value| value == "7"));
+    }
+
+    #[test]
+    fn decode_returns_err_for_invalid_payload() {
+        let uut = ProtobufDecoder::from_descriptor_set(
+            &create_descriptor_set(),
+            CABIN_TYPE,
+            "temperature.value",
+        )
+        .unwrap();
+
+        // A length-delimited field which claims to be longer than the payload
+        let result = uut.decode(&[0x0a, 0x05, 0x01]);
+        assert!(result.is_err_and(|e| e.kind() == ProtobufDecoderErrorKind::Decode));
+    }
+
+    #[test]
+    fn from_descriptor_set_returns_err_for_unknown_message_type() {
+        let result =
+            ProtobufDecoder::from_descriptor_set(&create_descriptor_set(), "vehicle.Engine", "rpm");
+        assert!(result.is_err_and(|e| e.kind() == ProtobufDecoderErrorKind::InvalidDescriptor));
+    }
+
+    #[test]
+    fn from_descriptor_set_returns_err_for_invalid_field_paths() {
+        let descriptor_set = create_descriptor_set();
+        for field_path in [
+            "humidity",
+            "temperature.humidity",
+            "temperature",
+            "mode.value",
+            "tags",
+            "raw",
+            "",
+        ] {
+            let result =
+                ProtobufDecoder::from_descriptor_set(&descriptor_set, CABIN_TYPE, field_path);
+            assert!(
+                result.is_err_and(|e| e.kind() == ProtobufDecoderErrorKind::InvalidFieldPath),
+                "{field_path:?} should be invalid"
+            );
+        }
+    }
+}
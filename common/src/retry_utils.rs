@@ -6,8 +6,242 @@ use std::future::Future;
 
 use log::debug;
 use tokio::time::{sleep, Duration};
+use tonic::{Code, Status};
+
+use crate::{
+    cloud_adapter::{CloudAdapterError, CloudAdapterErrorKind},
+    data_adapter::{DataAdapterError, DataAdapterErrorKind},
+    data_adapter_selector::{DataAdapterSelectorError, DataAdapterSelectorErrorKind},
+    digital_twin_adapter::{DigitalTwinAdapterError, DigitalTwinAdapterErrorKind},
+    grpc_utils::{GrpcUtilsError, GrpcUtilsErrorKind},
+    mapping_adapter::{MappingAdapterError, MappingAdapterErrorKind},
+    secret_provider::{SecretProviderError, SecretProviderErrorKind},
+    service_discovery_adapter::{ServiceDiscoveryAdapterError, ServiceDiscoveryAdapterErrorKind},
+};
+
+/// The default maximum number of retries for a `RetryPolicy`
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// The default time to wait before the first retry of an exponential `RetryPolicy`
+const DEFAULT_INITIAL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The default maximum time to wait between retries of an exponential `RetryPolicy`
+const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(10);
+
+/// An error which knows whether the operation that caused it might succeed if it's retried
+pub trait RetryableError {
+    /// Returns true if the error is transient, such as a communication failure.
+    /// Errors such as a missing entity or invalid data are not retryable because retrying won't change the result.
+    fn is_retryable(&self) -> bool;
+}
+
+impl RetryableError for CloudAdapterError {
+    fn is_retryable(&self) -> bool {
+        self.kind() == CloudAdapterErrorKind::Communication
+    }
+}
+
+impl RetryableError for DataAdapterError {
+    fn is_retryable(&self) -> bool {
+        self.kind() == DataAdapterErrorKind::Communication
+    }
+}
+
+impl RetryableError for DataAdapterSelectorError {
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind(),
+            DataAdapterSelectorErrorKind::Communication | DataAdapterSelectorErrorKind::RateLimited
+        )
+    }
+}
+
+impl RetryableError for DigitalTwinAdapterError {
+    fn is_retryable(&self) -> bool {
+        self.kind() == DigitalTwinAdapterErrorKind::Communication
+    }
+}
+
+impl RetryableError for GrpcUtilsError {
+    fn is_retryable(&self) -> bool {
+        self.kind() == GrpcUtilsErrorKind::Communication
+    }
+}
+
+impl RetryableError for MappingAdapterError {
+    fn is_retryable(&self) -> bool {
+        self.kind() == MappingAdapterErrorKind::Communication
+    }
+}
+
+impl RetryableError for SecretProviderError {
+    fn is_retryable(&self) -> bool {
+        self.kind() == SecretProviderErrorKind::Communication
+    }
+}
+
+impl RetryableError for ServiceDiscoveryAdapterError {
+    fn is_retryable(&self) -> bool {
+        self.kind() == ServiceDiscoveryAdapterErrorKind::Communication
+    }
+}
+
+impl RetryableError for Status {
+    /// Only statuses which indicate that the server was temporarily unable to handle the request are retryable.
+    /// `DeadlineExceeded` isn't retryable because the deadline covers every attempt of the request
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self.code(),
+            Code::Unavailable | Code::ResourceExhausted | Code::Aborted
+        )
+    }
+}
+
+/// Describes how to retry an async operation which can fail.
+/// Policies are built by starting with a backoff strategy and then customizing it, for example:
+///
+/// `RetryPolicy::exponential().max_retries(5).retry_if_retryable().execute(|| client.send()).await`
+pub struct RetryPolicy<E> {
+    /// The maximum number of times to retry the operation after the first attempt fails
+    max_retries: u32,
+
+    /// The time to wait before the first retry
+    initial_interval: Duration,
+
+    /// The maximum time to wait between retries
+    max_interval: Duration,
+
+    /// The factor that the wait time is multiplied by after each retry
+    multiplier: u32,
+
+    /// Decides whether an error should be retried. Other errors are returned immediately
+    retry_if: Box<dyn Fn(&E) -> bool + Send + Sync>,
+
+    /// Context for logging
+    context: Option<String>,
+}
+
+impl<E> RetryPolicy<E> {
+    /// Creates a policy which waits the same amount of time between each retry and retries every error
+    ///
+    /// # Arguments
+    /// - `interval`: the time to wait between retries
+    pub fn fixed(interval: Duration) -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_interval: interval,
+            max_interval: Duration::MAX,
+            multiplier: 1,
+            retry_if: Box::new(|_: &E| true),
+            context: None,
+        }
+    }
+
+    /// Creates a policy which doubles the time to wait after each retry and retries every error.
+    /// The wait time starts at 100ms and is capped at 10s unless they're customized.
+    pub fn exponential() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_interval: DEFAULT_INITIAL_INTERVAL,
+            max_interval: DEFAULT_MAX_INTERVAL,
+            multiplier: 2,
+            retry_if: Box::new(|_: &E| true),
+            context: None,
+        }
+    }
+
+    /// Sets the maximum number of times to retry the operation after the first attempt fails
+    ///
+    /// # Arguments
+    /// - `max_retries`: the maximum number of retries
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the time to wait before the first retry
+    ///
+    /// # Arguments
+    /// - `interval`: the time to wait
+    pub fn initial_interval(mut self, interval: Duration) -> Self {
+        self.initial_interval = interval;
+        self
+    }
+
+    /// Sets the maximum time to wait between retries
+    ///
+    /// # Arguments
+    /// - `interval`: the maximum time to wait
+    pub fn max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Sets which errors are retried. Errors for which the predicate returns false are returned immediately.
+    ///
+    /// # Arguments
+    /// - `predicate`: returns true if an error should be retried
+    pub fn retry_if(mut self, predicate: impl Fn(&E) -> bool + Send + Sync + 'static) -> Self {
+        self.retry_if = Box::new(predicate);
+        self
+    }
+
+    /// Sets context which is included when logging retries
+    ///
+    /// # Arguments
+    /// - `context`: the context
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Executes an operation, retrying it according to this policy.
+    /// Returns the first success, or the last error if the error can't be retried or the retries run out.
+    ///
+    /// # Arguments
+    /// - `function`: the function which starts the operation
+    pub async fn execute<T, Fut, F>(&self, mut function: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut interval = self.initial_interval;
+        let mut retries = 0;
+
+        loop {
+            let error = match function().await {
+                Ok(t) => return Ok(t),
+                Err(error) => error,
+            };
+
+            if retries >= self.max_retries || !(self.retry_if)(&error) {
+                return Err(error);
+            }
+
+            retries += 1;
+            debug!(
+                "Retrying the function call in {interval:?}. Retry attempt: {retries} (context: {:?})",
+                self.context
+            );
+
+            sleep(interval).await;
+            interval = interval
+                .saturating_mul(self.multiplier)
+                .min(self.max_interval);
+        }
+    }
+}
+
+impl<E: RetryableError + 'static> RetryPolicy<E> {
+    /// Retries only the errors which are retryable, such as communication failures
+    pub fn retry_if_retryable(self) -> Self {
+        self.retry_if(E::is_retryable)
+    }
+}
 
 /// Retry a function that returns an error.
+/// This calls the function at most `max_retries` times in total and retries every error.
+/// New code should use a `RetryPolicy`, which can also back off exponentially and skip errors that can't be retried.
 ///
 /// # Arguments
 /// * `max_retries` - The maximum number of retries.
@@ -17,35 +251,19 @@ use tokio::time::{sleep, Duration};
 pub async fn execute_with_retry<T, E, Fut, F: FnMut() -> Fut>(
     max_retries: u32,
     retry_interval_ms: Duration,
-    mut function: F,
+    function: F,
     context: Option<String>,
 ) -> Result<T, E>
 where
     Fut: Future<Output = Result<T, E>>,
 {
-    let mut last_error: Result<T, E>;
-    let mut retries = 0;
-
-    loop {
-        match function().await {
-            Ok(t) => return Ok(t),
-            Err(error) => {
-                last_error = Err(error);
-            }
-        }
-        debug!(
-            "Retrying the function call. Total retry attempts: {retries} (context: {context:?})"
-        );
-
-        sleep(retry_interval_ms).await;
-
-        retries += 1;
-
-        if retries == max_retries {
-            break;
-        }
+    let mut policy =
+        RetryPolicy::fixed(retry_interval_ms).max_retries(max_retries.saturating_sub(1));
+    if let Some(context) = context {
+        policy = policy.context(context);
     }
-    last_error
+
+    policy.execute(function).await
 }
 
 #[cfg(test)]
@@ -65,6 +283,19 @@ mod tests {
         }
     }
 
+    /// Fails with the provided error kinds in order, then succeeds
+    async fn fail_with(
+        attempts: Rc<RefCell<usize>>,
+        errors: &[CloudAdapterErrorKind],
+    ) -> Result<(), CloudAdapterError> {
+        let mut attempts = attempts.borrow_mut();
+        *attempts += 1;
+        match errors.get(*attempts - 1) {
+            Some(kind) => Err(kind.clone().into()),
+            None => Ok(()),
+        }
+    }
+
     #[tokio::test]
     async fn test_retry_async_function() {
         const MAX_RETRIES: u32 = 3;
@@ -91,4 +322,81 @@ mod tests {
         .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn policy_retries_until_success() {
+        let attempts = Rc::new(RefCell::new(0));
+        let errors = vec![CloudAdapterErrorKind::Communication; 2];
+
+        let result = RetryPolicy::exponential()
+            .max_retries(2)
+            .execute(|| fail_with(attempts.clone(), &errors))
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(*attempts.borrow(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn policy_returns_last_error_after_max_retries() {
+        let attempts = Rc::new(RefCell::new(0));
+        let errors = [
+            CloudAdapterErrorKind::Communication,
+            CloudAdapterErrorKind::Communication,
+            CloudAdapterErrorKind::Unknown,
+        ];
+
+        let result = RetryPolicy::fixed(Duration::from_secs(1))
+            .max_retries(2)
+            .execute(|| fail_with(attempts.clone(), &errors))
+            .await;
+
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Unknown));
+        assert_eq!(*attempts.borrow(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn policy_does_not_retry_errors_that_are_not_retryable() {
+        let attempts = Rc::new(RefCell::new(0));
+        let errors = [
+            CloudAdapterErrorKind::Communication,
+            CloudAdapterErrorKind::Serialize,
+        ];
+
+        let result = RetryPolicy::exponential()
+            .max_retries(5)
+            .retry_if_retryable()
+            .execute(|| fail_with(attempts.clone(), &errors))
+            .await;
+
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Serialize));
+        assert_eq!(*attempts.borrow(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn exponential_policy_doubles_interval_up_to_max() {
+        let attempts = Rc::new(RefCell::new(0));
+        let errors = vec![CloudAdapterErrorKind::Communication; 4];
+        let start = tokio::time::Instant::now();
+
+        RetryPolicy::exponential()
+            .max_retries(4)
+            .initial_interval(Duration::from_millis(100))
+            .max_interval(Duration::from_millis(300))
+            .execute(|| fail_with(attempts.clone(), &errors))
+            .await
+            .unwrap();
+
+        // 100ms + 200ms + 300ms + 300ms
+        assert_eq!(start.elapsed(), Duration::from_millis(900));
+    }
+
+    #[test]
+    fn status_is_retryable_only_for_transient_codes() {
+        assert!(Status::unavailable("").is_retryable());
+        assert!(Status::resource_exhausted("").is_retryable());
+        assert!(!Status::deadline_exceeded("").is_retryable());
+        assert!(!Status::not_found("").is_retryable());
+        assert!(!Status::unauthenticated("").is_retryable());
+    }
 }
@@ -0,0 +1,108 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{env, fs};
+
+/// Resolves secret references in config values, such as `${file:/run/secrets/x}`.
+/// Providers can be registered with [`crate::config_utils::register_secret_provider`]
+/// to support additional secret stores such as Azure Key Vault.
+pub trait SecretProvider: Send + Sync {
+    /// Gets the scheme of the references that this provider resolves,
+    /// such as `file` for `${file:/run/secrets/x}`
+    fn scheme(&self) -> &str;
+
+    /// Gets the value of a secret
+    ///
+    /// # Arguments
+    /// - `reference`: the reference to the secret without the scheme, such as `/run/secrets/x`
+    fn get_secret(&self, reference: &str) -> Result<String, SecretProviderError>;
+}
+
+/// Resolves secret references from environment variables, such as `${env:MQTT_PASSWORD}`
+pub struct EnvSecretProvider;
+
+impl SecretProvider for EnvSecretProvider {
+    /// Gets the scheme of the references that this provider resolves
+    fn scheme(&self) -> &str {
+        "env"
+    }
+
+    /// Gets the value of the environment variable with the given name
+    ///
+    /// # Arguments
+    /// - `reference`: the name of the environment variable
+    fn get_secret(&self, reference: &str) -> Result<String, SecretProviderError> {
+        env::var(reference).map_err(SecretProviderError::secret_not_found)
+    }
+}
+
+/// Resolves secret references from files, such as `${file:/run/secrets/mqtt_password}`.
+/// This is suitable for secrets mounted by a container orchestrator.
+pub struct FileSecretProvider;
+
+impl SecretProvider for FileSecretProvider {
+    /// Gets the scheme of the references that this provider resolves
+    fn scheme(&self) -> &str {
+        "file"
+    }
+
+    /// Gets the contents of the file at the given path without any trailing line break
+    ///
+    /// # Arguments
+    /// - `reference`: the path of the file
+    fn get_secret(&self, reference: &str) -> Result<String, SecretProviderError> {
+        let contents = fs::read_to_string(reference).map_err(SecretProviderError::io)?;
+        Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+proc_macros::error! {
+    SecretProviderError {
+        InvalidReference,
+        UnknownScheme,
+        SecretNotFound,
+        Io,
+        Communication,
+        Unknown
+    }
+}
+
+#[cfg(test)]
+mod secret_provider_tests {
+    use super::*;
+
+    #[test]
+    fn env_secret_provider_gets_variable() {
+        const VARIABLE: &str = "FREYJA_ENV_SECRET_PROVIDER_TEST";
+        env::set_var(VARIABLE, "secret");
+
+        assert_eq!(EnvSecretProvider.get_secret(VARIABLE).unwrap(), "secret");
+    }
+
+    #[test]
+    fn env_secret_provider_returns_err_for_missing_variable() {
+        let result = EnvSecretProvider.get_secret("FREYJA_ENV_SECRET_PROVIDER_MISSING");
+        assert!(result.is_err_and(|e| e.kind() == SecretProviderErrorKind::SecretNotFound));
+    }
+
+    #[test]
+    fn file_secret_provider_gets_contents_without_line_break() {
+        let path = env::temp_dir().join(format!(
+            "freyja_file_secret_provider_test_{}",
+            std::process::id()
+        ));
+        fs::write(&path, "secret\n").unwrap();
+
+        let result = FileSecretProvider.get_secret(&path.to_string_lossy());
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(result.unwrap(), "secret");
+    }
+
+    #[test]
+    fn file_secret_provider_returns_err_for_missing_file() {
+        let result = FileSecretProvider.get_secret("/this/path/does/not/exist");
+        assert!(result.is_err_and(|e| e.kind() == SecretProviderErrorKind::Io));
+    }
+}
@@ -2,6 +2,8 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 
 /// Adapter for services which provide discoverable and dynamic service URIs
@@ -23,6 +25,24 @@ pub trait ServiceDiscoveryAdapter {
         &self,
         id: &'a str,
     ) -> Result<String, ServiceDiscoveryAdapterError>;
+
+    /// Gets the URIs for several services at once, keyed by service identifier.
+    /// Services which can't be discovered are omitted from the result.
+    /// The default implementation discovers each service individually,
+    /// but adapters can override this to discover every service in a single request.
+    ///
+    /// # Arguments
+    /// - `ids`: the service identifiers
+    async fn get_service_uris<'a>(&self, ids: &[&'a str]) -> HashMap<String, String> {
+        let mut uris = HashMap::new();
+        for id in ids {
+            if let Ok(uri) = self.get_service_uri(id).await {
+                uris.insert(id.to_string(), uri);
+            }
+        }
+
+        uris
+    }
 }
 
 proc_macros::error! {
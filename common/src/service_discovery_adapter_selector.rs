@@ -2,13 +2,16 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 
 use crate::service_discovery_adapter::{ServiceDiscoveryAdapter, ServiceDiscoveryAdapterError};
 
-/// Selector for `ServiceDiscoveryAdapters`
+/// Selector for `ServiceDiscoveryAdapters`.
+/// Selectors must be thread-safe so that adapters can keep them for rediscovering services.
 #[async_trait]
-pub trait ServiceDiscoveryAdapterSelector {
+pub trait ServiceDiscoveryAdapterSelector: Send + Sync {
     /// Registers a `ServiceDiscoveryAdapter` with this selector
     ///
     /// # Arguments
@@ -26,4 +29,35 @@ pub trait ServiceDiscoveryAdapterSelector {
         &self,
         id: &'a str,
     ) -> Result<String, ServiceDiscoveryAdapterError>;
+
+    /// Gets every URI that can be discovered for the requested service, in priority order.
+    /// Callers can use this to fall back to another URI if the preferred one is stale.
+    /// The default implementation only returns the result of `get_service_uri`.
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    async fn get_service_uri_candidates<'a>(
+        &self,
+        id: &'a str,
+    ) -> Result<Vec<String>, ServiceDiscoveryAdapterError> {
+        self.get_service_uri(id).await.map(|uri| vec![uri])
+    }
+
+    /// Gets the URIs for several services at once, keyed by service identifier.
+    /// This lets startup code resolve all of its dependencies together.
+    /// Services which can't be discovered are omitted from the result.
+    /// The default implementation discovers each service individually with `get_service_uri`.
+    ///
+    /// # Arguments
+    /// - `ids`: the service identifiers
+    async fn get_service_uris<'a>(&self, ids: &[&'a str]) -> HashMap<String, String> {
+        let mut uris = HashMap::new();
+        for id in ids {
+            if let Ok(uri) = self.get_service_uri(id).await {
+                uris.insert(id.to_string(), uri);
+            }
+        }
+
+        uris
+    }
 }
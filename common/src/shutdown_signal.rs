@@ -0,0 +1,63 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::io;
+
+/// Waits until the operating system asks the process to shut down.
+/// The signals that are handled depend on the platform:
+/// - On Unix, this is `SIGINT` (ctrl-c) or `SIGTERM`
+/// - On Windows, this is ctrl-c, ctrl-break, or the console window being closed
+/// - On other platforms, this is ctrl-c
+pub async fn wait_for_shutdown_signal() -> io::Result<()> {
+    platform::wait_for_shutdown_signal().await
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::io;
+
+    use tokio::signal::{
+        self,
+        unix::{self as unix_signal, SignalKind},
+    };
+
+    /// Waits for `SIGINT` or `SIGTERM`
+    pub(super) async fn wait_for_shutdown_signal() -> io::Result<()> {
+        let mut terminate = unix_signal::signal(SignalKind::terminate())?;
+
+        tokio::select! {
+            result = signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::io;
+
+    use tokio::signal::{self, windows as windows_signal};
+
+    /// Waits for ctrl-c, ctrl-break, or the console window being closed
+    pub(super) async fn wait_for_shutdown_signal() -> io::Result<()> {
+        let mut ctrl_break = windows_signal::ctrl_break()?;
+        let mut ctrl_close = windows_signal::ctrl_close()?;
+
+        tokio::select! {
+            result = signal::ctrl_c() => result,
+            _ = ctrl_break.recv() => Ok(()),
+            _ = ctrl_close.recv() => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io;
+
+    /// Waits for ctrl-c
+    pub(super) async fn wait_for_shutdown_signal() -> io::Result<()> {
+        tokio::signal::ctrl_c().await
+    }
+}
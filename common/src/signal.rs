@@ -2,17 +2,34 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 
-use crate::{conversion::Conversion, entity::Entity};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    cloud_adapter::TENANT_METADATA_KEY,
+    conversion::{Conversion, ConversionFailurePolicy},
+    entity::Entity,
+    privacy::PrivacyPolicy,
+};
 
 /// Conveys information about a signal, its current state, and how the data should be emitted
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Signal {
     /// The signal id. In most cases, this should be the same as the source id
     pub id: String,
-    /// The signal's current value, if it's been set
-    pub value: Option<String>,
+    /// The signal's current value, if it's been set.
+    /// Values are shared rather than copied when the signal is read from the store, since they can be large
+    pub value: Option<Arc<str>>,
+    /// The signal's current value as a number, if it's been set and is numeric.
+    /// This is parsed when the value is set so that unchanged values aren't parsed again on every emission
+    pub numeric_value: Option<f64>,
+    /// The time at which the signal's current value was set, according to the signal store's clock
+    pub value_timestamp: Option<OffsetDateTime>,
     /// The signal's source entity information
     pub source: Entity,
     /// The signal's target mapping information
@@ -39,6 +56,23 @@ pub struct SignalPatch {
 pub struct Target {
     /// Metadata that will be passed to the cloud adapter to perform the mapping
     pub metadata: HashMap<String, String>,
+    /// The id of the tenant whose cloud backend the signal is emitted to.
+    /// If this is `None`, the signal is emitted with the cloud adapters which don't belong to a tenant
+    pub tenant: Option<String>,
+}
+
+impl Target {
+    /// Gets the metadata to send to the cloud adapter, which includes the tenant if the signal has one.
+    /// Any mapping metadata with the reserved tenant key is dropped so that it can't change the signal's tenant
+    pub fn cloud_metadata(&self) -> HashMap<String, String> {
+        let mut metadata = self.metadata.clone();
+        metadata.remove(TENANT_METADATA_KEY);
+        if let Some(tenant) = &self.tenant {
+            metadata.insert(TENANT_METADATA_KEY.to_string(), tenant.clone());
+        }
+
+        metadata
+    }
 }
 
 /// Metadata about a signal's emission
@@ -50,7 +84,32 @@ pub struct Emission {
     /// Note that the default for this value is 0, which the emitter will interpret as ready to emit ASAP
     pub next_emission_ms: u64,
     /// The last emitted value
-    pub last_emitted_value: Option<String>,
+    pub last_emitted_value: Option<Arc<str>>,
+    /// The state of the flag for a threshold conversion after the last emission, if any.
+    /// This is kept so that the conversion can apply hysteresis across emissions
+    pub threshold_state: Option<bool>,
+    /// The signal's most recent emissions, oldest first
+    pub history: VecDeque<EmissionRecord>,
+    /// The current interval of a signal with an adaptive interval, if it has been adapted.
+    /// If this is `None`, the signal is emitted at the interval from its policy
+    pub adaptive_interval_ms: Option<u64>,
+}
+
+impl Emission {
+    /// Gets the interval at which the signal is currently emitted,
+    /// which is the adapted interval if there is one and the policy's interval otherwise
+    pub fn interval_ms(&self) -> u64 {
+        self.adaptive_interval_ms.unwrap_or(self.policy.interval_ms)
+    }
+}
+
+/// A record of one of a signal's emissions
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmissionRecord {
+    /// The time at which the signal was emitted, according to the signal store's clock
+    pub timestamp: OffsetDateTime,
+    /// The emitted value, before any conversion
+    pub value: Arc<str>,
 }
 
 /// A signal's emission policy
@@ -62,6 +121,58 @@ pub struct EmissionPolicy {
     pub emit_only_if_changed: bool,
     /// A conversion to apply to the signal before emission
     pub conversion: Conversion,
+    /// How to handle values which cannot be converted
+    pub conversion_failure_policy: ConversionFailurePolicy,
+    /// The number of decimal places to round numeric values to before emission.
+    /// If this is `None`, values are not rounded
+    pub decimal_places: Option<u32>,
+    /// A trigger expression over other signals which must be satisfied for the signal to be emitted.
+    /// If this is `None`, the signal is always emitted. See [`crate::trigger::evaluate`] for the syntax
+    pub trigger: Option<String>,
+    /// The bounds of an adaptive emission interval. If this is `None`, the signal is always emitted at `interval_ms`.
+    /// Otherwise, `interval_ms` is the initial interval, which is halved each time the signal's value changes
+    /// between emissions and doubled each time it doesn't, within these bounds
+    pub adaptive_interval: Option<AdaptiveInterval>,
+    /// A transformation which reduces the precision of numeric values before emission, such as noise or quantization.
+    /// It's applied after the conversion and before rounding. If this is `None`, values are emitted with full precision
+    pub privacy: Option<PrivacyPolicy>,
+}
+
+/// The bounds of an emission interval which adapts to how often a signal's value changes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdaptiveInterval {
+    /// The shortest interval, used while the signal's value changes frequently
+    pub min_interval_ms: u64,
+    /// The longest interval, used while the signal's value is stable
+    pub max_interval_ms: u64,
+}
+
+/// The parts of a signal which determine when it's emitted.
+/// This is much cheaper to copy than a whole signal since it doesn't include the signal's values and history
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EmissionDescriptor {
+    /// The signal id
+    pub id: String,
+    /// The time until the signal's next emission
+    pub next_emission_ms: u64,
+    /// The time since the signal became due, which is 0 if it isn't due
+    pub overdue_ms: u64,
+    /// The interval at which the signal is currently emitted. See [`Emission::interval_ms`]
+    pub interval_ms: u64,
+    /// The bounds of the signal's adaptive interval, if it has one
+    pub adaptive_interval: Option<AdaptiveInterval>,
+}
+
+impl From<&Signal> for EmissionDescriptor {
+    fn from(value: &Signal) -> Self {
+        Self {
+            id: value.id.clone(),
+            next_emission_ms: value.emission.next_emission_ms,
+            overdue_ms: 0,
+            interval_ms: value.emission.interval_ms(),
+            adaptive_interval: value.emission.policy.adaptive_interval,
+        }
+    }
 }
 
 impl From<Signal> for SignalPatch {
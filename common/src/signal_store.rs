@@ -2,46 +2,212 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::{collections::HashMap, sync::RwLock};
-
-use crate::signal::{Emission, Signal, SignalPatch};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+use log::{debug, warn};
+use serde_json::{json, Map, Value};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    clock::{Clock, SystemClock},
+    duplicate_filter::DuplicateFilter,
+    emission_schedule::EmissionSchedule,
+    namespace,
+    signal::{Emission, EmissionDescriptor, EmissionRecord, Signal, SignalPatch},
+};
+
+/// The number of emissions that are kept in each signal's emission history
+const EMISSION_HISTORY_LENGTH: usize = 10;
+
+/// Observes the values that data adapters set in a `SignalStore`, such as for recording provider traffic
+pub trait SignalValueObserver: Send + Sync {
+    /// Called whenever a value is set in the store, including values for signals that aren't in the store.
+    /// This is called without holding any of the store's locks.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `value`: the new value of the signal, which is shared with the store
+    /// - `timestamp`: the time at which the value was set
+    fn on_value_set(&self, id: &str, value: &Arc<str>, timestamp: OffsetDateTime);
+
+    /// Called instead of `on_value_set` with the sequence id that the provider assigned to the value, if any.
+    /// Observers which forward values to another store can override this to keep the sequence id.
+    /// By default, this ignores the sequence id and calls `on_value_set`.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `value`: the new value of the signal, which is shared with the store
+    /// - `timestamp`: the time at which the value was set
+    /// - `_sequence_id`: the id which the provider assigned to this delivery of the value, if any
+    fn on_value_set_with_sequence_id(
+        &self,
+        id: &str,
+        value: &Arc<str>,
+        timestamp: OffsetDateTime,
+        _sequence_id: Option<&str>,
+    ) {
+        self.on_value_set(id, value, timestamp);
+    }
+}
 
 /// Stores signals and allows access in a thread-safe manner with support for multiple concurrent readers.
 /// Suitable for use as `Arc<SignalStore>`.
 pub struct SignalStore {
-    /// The data being stored
-    signals: RwLock<HashMap<String, Signal>>,
+    /// The data being stored, which is shared with namespaced views of this store
+    signals: Arc<RwLock<HashMap<String, Signal>>>,
+
+    /// The clock used to timestamp signal values
+    clock: Arc<dyn Clock>,
+
+    /// The observers which are notified of every value set in the store
+    value_observers: Arc<RwLock<Vec<Arc<dyn SignalValueObserver>>>>,
+
+    /// The filter which suppresses values that providers deliver more than once, if any
+    duplicate_filter: Arc<RwLock<Option<Arc<DuplicateFilter>>>>,
+
+    /// The time at which the emission times were last updated, which is when each signal's `next_emission_ms` starts.
+    /// This is `None` until the emission times are first updated
+    emission_times_updated_at: Arc<Mutex<Option<OffsetDateTime>>>,
+
+    /// The signals ordered by their next emission. This is the source of the `next_emission_ms` of the signals returned by the store.
+    /// It's locked after `signals` when both are needed
+    emission_schedule: Arc<Mutex<EmissionSchedule>>,
+
+    /// The number of times that signals have been added or synced, which is shared with namespaced views of this store
+    generation: Arc<AtomicU64>,
+
+    /// The ids of the signals which `sync` keeps even though they aren't in its input
+    pinned_ids: Arc<RwLock<HashSet<String>>>,
+
+    /// The namespace that ids passed to `get` and `set_value` are qualified with, if this is a namespaced view
+    namespace: Option<String>,
 }
 
 impl SignalStore {
-    /// Creates an empty SignalStore
+    /// Creates an empty SignalStore which uses the system clock
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Creates an empty SignalStore which uses the provided clock
+    ///
+    /// # Arguments
+    /// - `clock`: the clock used to timestamp signal values
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
-            signals: RwLock::new(HashMap::new()),
+            signals: Arc::new(RwLock::new(HashMap::new())),
+            clock,
+            value_observers: Arc::new(RwLock::new(Vec::new())),
+            duplicate_filter: Arc::new(RwLock::new(None)),
+            emission_times_updated_at: Arc::new(Mutex::new(None)),
+            emission_schedule: Arc::new(Mutex::new(EmissionSchedule::new())),
+            generation: Arc::new(AtomicU64::new(0)),
+            pinned_ids: Arc::new(RwLock::new(HashSet::new())),
+            namespace: None,
         }
     }
 
+    /// Creates a view of this store for the signals in a namespace.
+    /// The view shares its signals with this store, but the ids passed to its `get` and `set_value` functions
+    /// are qualified with the namespace. This lets data adapters use unqualified entity ids for namespaced signals.
+    ///
+    /// # Arguments
+    /// - `namespace`: the namespace of the view
+    pub fn namespaced(&self, namespace: &str) -> Self {
+        Self {
+            signals: self.signals.clone(),
+            clock: self.clock.clone(),
+            value_observers: self.value_observers.clone(),
+            duplicate_filter: self.duplicate_filter.clone(),
+            emission_times_updated_at: self.emission_times_updated_at.clone(),
+            emission_schedule: self.emission_schedule.clone(),
+            generation: self.generation.clone(),
+            pinned_ids: self.pinned_ids.clone(),
+            namespace: Some(namespace.to_owned()),
+        }
+    }
+
+    /// Adds an observer which is notified of every value set in the store
+    ///
+    /// # Arguments
+    /// - `observer`: the observer to add
+    pub fn add_value_observer(&self, observer: Arc<dyn SignalValueObserver>) {
+        self.value_observers.write().unwrap().push(observer);
+    }
+
+    /// Sets the filter which suppresses values that providers deliver more than once.
+    /// Duplicates are dropped before they reach the value observers or the signals.
+    ///
+    /// # Arguments
+    /// - `filter`: the filter to use
+    pub fn set_duplicate_filter(&self, filter: Arc<DuplicateFilter>) {
+        *self.duplicate_filter.write().unwrap() = Some(filter);
+    }
+
+    /// Gets the clock used by this store.
+    /// Data adapters can use this to synchronize a simulated clock with the timestamps of their data.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Gets the generation of the store, which is incremented each time signals are added or synced.
+    /// Readers can compare generations to detect that the signals changed without holding a lock between reads.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
     /// Get a value from the store. Returns `None` if the signal was not found.
     /// Acquires a read lock.
     ///
     /// # Arguments
     /// - `id`: The id of the entity to retrieve
     pub fn get(&self, id: &String) -> Option<Signal> {
+        let id = namespace::qualify(self.namespace.as_deref(), id);
         let signals = self.signals.read().unwrap();
-        signals.get(id).cloned()
+        let emission_schedule = self.emission_schedule.lock().unwrap();
+        signals
+            .get(&id)
+            .map(|signal| Self::with_next_emission(signal, &emission_schedule))
     }
 
     /// Gets a `Vec` containing copies all of the signals in the store.
     /// Acquires a read lock.
     pub fn get_all(&self) -> Vec<Signal> {
         let signals = self.signals.read().unwrap();
-        signals.iter().map(|(_, signal)| signal.clone()).collect()
+        let emission_schedule = self.emission_schedule.lock().unwrap();
+        signals
+            .values()
+            .map(|signal| Self::with_next_emission(signal, &emission_schedule))
+            .collect()
+    }
+
+    /// Copies a signal with the time until its next emission from the emission schedule
+    ///
+    /// # Arguments
+    /// - `signal`: the signal to copy
+    /// - `emission_schedule`: the emission schedule
+    fn with_next_emission(signal: &Signal, emission_schedule: &EmissionSchedule) -> Signal {
+        let mut signal = signal.clone();
+        if let Some(next_emission_ms) = emission_schedule.time_until(&signal.id) {
+            signal.emission.next_emission_ms = next_emission_ms;
+        }
+
+        signal
     }
 
     /// For each signal in the input:
     /// - If the incoming signal is already in the data store, apply the patch.
     /// - If the incoming signal is not in the data store, create a new signal from the patch.
     ///
+    /// The generation of the store is incremented.
+    ///
     /// # Arguments
     /// - `incoming_signals`: The signal patches used to generate the new signal
     pub fn add<SyncIterator, IntoSignalPatch>(&self, incoming_signals: SyncIterator)
@@ -50,6 +216,7 @@ impl SignalStore {
         IntoSignalPatch: Into<SignalPatch>,
     {
         let mut signals = self.signals.write().unwrap();
+        let mut emission_schedule = self.emission_schedule.lock().unwrap();
         for value in incoming_signals {
             let SignalPatch {
                 id,
@@ -58,10 +225,27 @@ impl SignalStore {
                 emission_policy,
             } = value.into();
 
+            // New signals are due for emission immediately
+            if !signals.contains_key(&id) {
+                emission_schedule.schedule(&id, 0);
+            }
+
             signals
                 .entry(id.clone())
                 // If the incoming signal is already in the data store, update only its target and emission policy
                 .and_modify(|s| {
+                    // The threshold state is only meaningful for the conversion that produced it
+                    if s.emission.policy.conversion != emission_policy.conversion {
+                        s.emission.threshold_state = None;
+                    }
+
+                    // An adapted interval is only meaningful for the policy that it was adapted from
+                    if s.emission.policy.interval_ms != emission_policy.interval_ms
+                        || s.emission.policy.adaptive_interval != emission_policy.adaptive_interval
+                    {
+                        s.emission.adaptive_interval_ms = None;
+                    }
+
                     s.source = source.clone();
                     s.target = target.clone();
                     s.emission.policy = emission_policy.clone();
@@ -78,6 +262,30 @@ impl SignalStore {
                     ..Default::default()
                 });
         }
+
+        // The generation is incremented while the lock is held so that it's never older than the signals it describes
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Adds signals which aren't part of the mapping, such as Freyja's self-test signal.
+    /// These signals are added like the signals passed to `add`, but `sync` keeps them even though they aren't in its input.
+    ///
+    /// # Arguments
+    /// - `incoming_signals`: The signal patches used to generate the new signals
+    pub fn add_pinned<SyncIterator, IntoSignalPatch>(&self, incoming_signals: SyncIterator)
+    where
+        SyncIterator: Iterator<Item = IntoSignalPatch>,
+        IntoSignalPatch: Into<SignalPatch>,
+    {
+        let patches: Vec<SignalPatch> = incoming_signals.map(Into::into).collect();
+
+        // The pinned ids are released before the signals are locked, since sync locks them in the opposite order
+        self.pinned_ids
+            .write()
+            .unwrap()
+            .extend(patches.iter().map(|patch| patch.id.clone()));
+
+        self.add(patches.into_iter());
     }
 
     /// For each signal in the input:
@@ -85,9 +293,9 @@ impl SignalStore {
     /// - If the incoming signal is not in the data store, create a new signal from the patch.
     ///
     /// For each signal in the data store:
-    /// - If the stored signal is not in the input, delete it
+    /// - If the stored signal is not in the input and wasn't added with `add_pinned`, delete it
     ///
-    /// The previous state of the store is discarded.
+    /// The previous state of the store is discarded and the generation of the store is incremented.
     /// Acquires a write lock.
     ///
     /// # Arguments
@@ -98,6 +306,7 @@ impl SignalStore {
         IntoSignalPatch: Into<SignalPatch>,
     {
         let mut signals = self.signals.write().unwrap();
+        let mut emission_schedule = self.emission_schedule.lock().unwrap();
 
         // This algorithm avoids trying to iterate over incoming_signals multiple times since iterators are consumed in this process.
         // If the iterator were cloneable then the implementation could be better, but in general that's not always a feasible constraint.
@@ -116,10 +325,27 @@ impl SignalStore {
             // We track it separately from the input iterator since we can't reuse the iterator.
             incoming_ids.push(id.clone());
 
+            // New signals are due for emission immediately
+            if !signals.contains_key(&id) {
+                emission_schedule.schedule(&id, 0);
+            }
+
             signals
                 .entry(id.clone())
                 // If the incoming signal is already in the data store, update only its target and emission policy
                 .and_modify(|s| {
+                    // The threshold state is only meaningful for the conversion that produced it
+                    if s.emission.policy.conversion != emission_policy.conversion {
+                        s.emission.threshold_state = None;
+                    }
+
+                    // An adapted interval is only meaningful for the policy that it was adapted from
+                    if s.emission.policy.interval_ms != emission_policy.interval_ms
+                        || s.emission.policy.adaptive_interval != emission_policy.adaptive_interval
+                    {
+                        s.emission.adaptive_interval_ms = None;
+                    }
+
                     s.source = source.clone();
                     s.target = target.clone();
                     s.emission.policy = emission_policy.clone();
@@ -137,69 +363,314 @@ impl SignalStore {
                 });
         }
 
-        // Delete signals in the store but not in the incoming list
-        signals.retain(|id, _| incoming_ids.contains(id));
+        // Delete signals in the store but not in the incoming list, except for pinned signals
+        let pinned_ids = self.pinned_ids.read().unwrap();
+        signals.retain(|id, _| {
+            let retain = incoming_ids.contains(id) || pinned_ids.contains(id);
+            if !retain {
+                emission_schedule.remove(id);
+            }
+
+            retain
+        });
+
+        self.generation.fetch_add(1, Ordering::AcqRel);
     }
 
-    /// Sets the value of the signal with the given id to the requested value.
+    /// Sets the value of the signal with the given id to the requested value
+    /// and records the current time of the store's clock as the value's timestamp.
+    /// The value is also parsed and cached as a number if it's numeric.
     /// Returns the old value, or `None` if the signal could not be found.
     /// Acquires a write lock.
     ///
     /// # Arguments
     /// - `id`: The id of the signal to edit
     /// - `value`: The new value to assign to the signal
-    pub fn set_value(&self, id: String, value: String) -> Option<Option<String>> {
+    pub fn set_value(&self, id: String, value: impl Into<Arc<str>>) -> Option<Option<Arc<str>>> {
+        self.set_value_internal(id, value.into(), None)
+    }
+
+    /// Sets the value of the signal with the given id like `set_value`,
+    /// using a sequence id supplied by the provider to detect redelivered values.
+    /// If a duplicate filter is set and it has seen the sequence id for this signal within its window,
+    /// the value is dropped and the current value is returned as the old value.
+    /// Returns the old value, or `None` if the signal could not be found.
+    ///
+    /// # Arguments
+    /// - `id`: The id of the signal to edit
+    /// - `value`: The new value to assign to the signal
+    /// - `sequence_id`: The id which the provider assigned to this delivery of the value
+    pub fn set_value_with_sequence_id(
+        &self,
+        id: String,
+        value: impl Into<Arc<str>>,
+        sequence_id: String,
+    ) -> Option<Option<Arc<str>>> {
+        self.set_value_internal(id, value.into(), Some(sequence_id))
+    }
+
+    /// Sets the value of a signal after checking whether it's a duplicate
+    ///
+    /// # Arguments
+    /// - `id`: The id of the signal to edit
+    /// - `value`: The new value to assign to the signal
+    /// - `sequence_id`: The id which the provider assigned to this delivery of the value, if any
+    fn set_value_internal(
+        &self,
+        id: String,
+        value: Arc<str>,
+        sequence_id: Option<String>,
+    ) -> Option<Option<Arc<str>>> {
+        let id = match self.namespace.as_deref() {
+            Some(namespace) => namespace::qualify(Some(namespace), &id),
+            None => id,
+        };
+        let timestamp = self.clock.now();
+
+        let filter = self.duplicate_filter.read().unwrap().clone();
+        if filter.is_some_and(|f| f.is_duplicate(&id, &value, sequence_id.as_deref(), timestamp)) {
+            debug!("Dropped a duplicate value for {id}");
+            let signals = self.signals.read().unwrap();
+            return signals.get(&id).map(|s| s.value.clone());
+        }
+
+        let numeric_value = value.parse::<f64>().ok();
+
+        {
+            let observers = self.value_observers.read().unwrap();
+            for observer in observers.iter() {
+                observer.on_value_set_with_sequence_id(
+                    &id,
+                    &value,
+                    timestamp,
+                    sequence_id.as_deref(),
+                );
+            }
+        }
+
         let mut signals = self.signals.write().unwrap();
 
         let mut result = None;
         signals.entry(id).and_modify(|s| {
             result = Some(s.value.clone());
             s.value = Some(value);
+            s.numeric_value = numeric_value;
+            s.value_timestamp = Some(timestamp);
         });
 
         result
     }
 
-    /// Sets the last emitted value of the signal with the given id to the requested value
-    /// and resets its `next_emssion_ms` based on the emission policy.
+    /// Sets the last emitted value of the signal with the given id to the requested value,
+    /// schedules its next emission one interval after it became due,
+    /// and records the emission in its history with the current time of the store's clock.
     /// Returns the old value, or `None` if the signal could not be found.
     /// Acquires a write lock.
     ///
     /// # Arguments
     /// - `id`: The id of the signal to edit
     /// - `value`: The new value to assign to the signal's last emitted value
-    pub fn set_last_emitted_value(&self, id: String, value: String) -> Option<Option<String>> {
+    pub fn set_last_emitted_value(
+        &self,
+        id: String,
+        value: impl Into<Arc<str>>,
+    ) -> Option<Option<Arc<str>>> {
+        let value = value.into();
+        let timestamp = self.clock.now();
         let mut signals = self.signals.write().unwrap();
 
         let mut result = None;
         signals.entry(id).and_modify(|s| {
+            if s.emission.history.len() >= EMISSION_HISTORY_LENGTH {
+                s.emission.history.pop_front();
+            }
+
+            s.emission.history.push_back(EmissionRecord {
+                timestamp,
+                value: value.clone(),
+            });
+
             result = Some(s.emission.last_emitted_value.clone());
             s.emission.last_emitted_value = Some(value);
-            s.emission.next_emission_ms = s.emission.policy.interval_ms;
+            s.emission.next_emission_ms = self
+                .emission_schedule
+                .lock()
+                .unwrap()
+                .reschedule(&s.id, s.emission.interval_ms());
         });
 
         result
     }
 
-    /// Adjusts the emission times of all signals in the store by subtracting the provided interval from next_emission_ms.
-    /// If overflow would occur, the value saturates at `u64::MIN` (`0`).
-    /// Returns the updated list of all signals.
+    /// Sets the threshold conversion state of the signal with the given id.
+    /// Returns the old state, or `None` if the signal could not be found.
     /// Acquires a write lock.
     ///
     /// # Arguments
-    /// - `interval_ms`: The value to subtract from each signal's next_emission_ms value
-    pub fn update_emission_times_and_get_all(&self, interval_ms: u64) -> Vec<Signal> {
+    /// - `id`: The id of the signal to edit
+    /// - `state`: The new state of the signal's threshold conversion
+    pub fn set_threshold_state(&self, id: String, state: bool) -> Option<Option<bool>> {
         let mut signals = self.signals.write().unwrap();
-        let mut result = Vec::new();
 
-        for (_, signal) in signals.iter_mut() {
-            signal.emission.next_emission_ms =
-                signal.emission.next_emission_ms.saturating_sub(interval_ms);
-            result.push(signal.clone());
-        }
+        let mut result = None;
+        signals.entry(id).and_modify(|s| {
+            result = Some(s.emission.threshold_state);
+            s.emission.threshold_state = Some(state);
+        });
 
         result
     }
+
+    /// Sets the adapted emission interval of the signal with the given id and schedules its next emission after it.
+    /// Returns the old adapted interval, or `None` if the signal could not be found.
+    /// Acquires a write lock.
+    ///
+    /// # Arguments
+    /// - `id`: The id of the signal to edit
+    /// - `interval_ms`: The new interval of the signal
+    pub fn set_adaptive_interval(&self, id: String, interval_ms: u64) -> Option<Option<u64>> {
+        let mut signals = self.signals.write().unwrap();
+
+        let mut result = None;
+        signals.entry(id).and_modify(|s| {
+            result = Some(s.emission.adaptive_interval_ms);
+            s.emission.adaptive_interval_ms = Some(interval_ms);
+            s.emission.next_emission_ms = self
+                .emission_schedule
+                .lock()
+                .unwrap()
+                .reschedule(&s.id, interval_ms);
+        });
+
+        result
+    }
+
+    /// Advances the emission times of all signals in the store by the provided interval.
+    /// Signals which become due stay due, with a `next_emission_ms` of 0, until they're emitted.
+    /// Returns the emission descriptors of the signals which are due within the lookahead, soonest first,
+    /// followed by the descriptor of the first signal after them so that the caller knows when to check again.
+    /// Only these signals are visited, so the cost of each update doesn't grow with the signals which aren't due.
+    /// Acquires a read lock.
+    ///
+    /// # Arguments
+    /// - `interval_ms`: The time that has elapsed since the emission times were last updated
+    /// - `lookahead_ms`: How far ahead to get the signals which will be due, such as for prefetching their values
+    pub fn update_emission_times(
+        &self,
+        interval_ms: u64,
+        lookahead_ms: u64,
+    ) -> Vec<EmissionDescriptor> {
+        let updated_at = self.clock.now();
+        let signals = self.signals.read().unwrap();
+        let mut emission_schedule = self.emission_schedule.lock().unwrap();
+        *self.emission_times_updated_at.lock().unwrap() = Some(updated_at);
+        emission_schedule.advance(interval_ms);
+
+        emission_schedule
+            .upcoming(lookahead_ms)
+            .into_iter()
+            .filter_map(|(id, next_emission_ms)| {
+                signals.get(id).map(|signal| EmissionDescriptor {
+                    next_emission_ms,
+                    overdue_ms: emission_schedule.time_overdue(id).unwrap_or_default(),
+                    ..EmissionDescriptor::from(signal)
+                })
+            })
+            .collect()
+    }
+
+    /// Exports the current signal values as a VSS-style JSON tree.
+    /// Each signal's source id is split on `.` into a path of branches,
+    /// and the leaf at the end of the path has the signal's `value` and `timestamp`.
+    /// For example, `Vehicle.Speed` is exported as `{"Vehicle": {"Speed": {"value": "42", "timestamp": "..."}}}`.
+    /// Signals whose paths conflict with the value or timestamp of another signal may be left out.
+    /// Acquires a read lock.
+    pub fn export_vss_json(&self) -> Value {
+        let signals = self.signals.read().unwrap();
+        let mut root = Map::new();
+
+        'signals: for signal in signals.values() {
+            let mut node = &mut root;
+            for segment in signal.source.id.split('.') {
+                node = match node
+                    .entry(segment)
+                    .or_insert_with(|| Value::Object(Map::new()))
+                {
+                    Value::Object(child) => child,
+                    _ => {
+                        warn!(
+                            "Skipping signal {} in the VSS export because its path conflicts with another signal",
+                            signal.id
+                        );
+                        continue 'signals;
+                    }
+                };
+            }
+
+            let timestamp = Self::format_timestamp(signal.value_timestamp);
+            node.insert(String::from("value"), signal.value.as_deref().into());
+            node.insert(String::from("timestamp"), timestamp.into());
+        }
+
+        Value::Object(root)
+    }
+
+    /// Exports the emission timeline of every signal as JSON for debugging emission intervals.
+    /// Each signal has its current `interval_ms`, which reflects any adaptation, the time of its `next_emission` if the emitter has scheduled it,
+    /// and its most recent `emissions` with their `timestamp` and `value`, oldest first.
+    /// Signals are sorted by id.
+    /// Acquires a read lock.
+    pub fn export_emission_timeline(&self) -> Value {
+        let generated_at = self.clock.now();
+        let signals = self.signals.read().unwrap();
+        let emission_schedule = self.emission_schedule.lock().unwrap();
+        let emission_times_updated_at = *self.emission_times_updated_at.lock().unwrap();
+
+        let mut entries: Vec<&Signal> = signals.values().collect();
+        entries.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let entries: Vec<Value> = entries
+            .into_iter()
+            .map(|signal| {
+                let next_emission_ms = emission_schedule
+                    .time_until(&signal.id)
+                    .unwrap_or(signal.emission.next_emission_ms);
+                let next_emission = emission_times_updated_at
+                    .map(|updated_at| updated_at + Duration::from_millis(next_emission_ms));
+                let emissions: Vec<Value> = signal
+                    .emission
+                    .history
+                    .iter()
+                    .map(|record| {
+                        json!({
+                            "timestamp": Self::format_timestamp(Some(record.timestamp)),
+                            "value": &*record.value,
+                        })
+                    })
+                    .collect();
+
+                json!({
+                    "id": signal.id,
+                    "interval_ms": signal.emission.interval_ms(),
+                    "next_emission": Self::format_timestamp(next_emission),
+                    "emissions": emissions,
+                })
+            })
+            .collect();
+
+        json!({
+            "generated_at": Self::format_timestamp(Some(generated_at)),
+            "signals": entries,
+        })
+    }
+
+    /// Formats a timestamp as RFC 3339. Returns `None` if there is no timestamp or it can't be formatted.
+    ///
+    /// # Arguments
+    /// - `timestamp`: the timestamp to format
+    fn format_timestamp(timestamp: Option<OffsetDateTime>) -> Option<String> {
+        timestamp.and_then(|timestamp| timestamp.format(&Rfc3339).ok())
+    }
 }
 
 impl Default for SignalStore {
@@ -212,16 +683,18 @@ impl Default for SignalStore {
 mod signal_store_tests {
     use super::*;
 
-    use std::collections::HashSet;
+    use std::{
+        collections::{HashSet, VecDeque},
+        time::Duration,
+    };
 
     use crate::{
-        conversion::Conversion,
-        entity::{Entity, EntityEndpoint},
-        signal::{Emission, EmissionPolicy, Target},
+        clock::ManualClock,
+        conversion::{Conversion, ConversionFailurePolicy},
+        entity::{Entity, EntityEndpoint, Operation},
+        signal::{AdaptiveInterval, Emission, EmissionPolicy, Target},
     };
 
-    const GET_OPERATION: &str = "Get";
-
     #[test]
     fn get_returns_existing_signal() {
         const ID: &str = "testid";
@@ -304,31 +777,43 @@ mod signal_store_tests {
 
         let original_signal = Signal {
             id: ID.to_string(),
-            value: Some(ORIGINAL.to_string()),
+            value: Some(ORIGINAL.into()),
+            numeric_value: None,
+            value_timestamp: None,
             source: Entity {
                 name: Some(ORIGINAL.to_string()),
                 id: ID.to_string(),
                 description: Some(ORIGINAL.to_string()),
                 endpoints: vec![EntityEndpoint {
                     protocol: ORIGINAL.to_string(),
-                    operations: vec![GET_OPERATION.to_string()],
+                    operations: vec![Operation::Get.to_string()],
                     uri: ORIGINAL.to_string(),
                     context: ORIGINAL.to_string(),
+                    ..Default::default()
                 }],
             },
             target: Target {
                 metadata: [(ORIGINAL.to_string(), ORIGINAL.to_string())]
                     .into_iter()
                     .collect(),
+                tenant: None,
             },
             emission: Emission {
                 policy: EmissionPolicy {
                     interval_ms: 42,
                     emit_only_if_changed: false,
                     conversion: Conversion::None,
+                    conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                    decimal_places: None,
+                    trigger: None,
+                    adaptive_interval: None,
+                    privacy: None,
                 },
                 next_emission_ms: 42,
-                last_emitted_value: Some(ORIGINAL.to_string()),
+                last_emitted_value: Some(ORIGINAL.into()),
+                threshold_state: None,
+                history: VecDeque::new(),
+                adaptive_interval_ms: None,
             },
         };
 
@@ -336,7 +821,9 @@ mod signal_store_tests {
         // (except the id)
         let incoming_signal = Signal {
             id: ID.to_string(),
-            value: Some(INCOMING.to_string()),
+            value: Some(INCOMING.into()),
+            numeric_value: None,
+            value_timestamp: None,
             source: Entity {
                 name: Some(INCOMING.to_string()),
                 id: ID.to_string(),
@@ -346,12 +833,14 @@ mod signal_store_tests {
                     operations: vec!["FooOperation".to_string()],
                     uri: INCOMING.to_string(),
                     context: INCOMING.to_string(),
+                    ..Default::default()
                 }],
             },
             target: Target {
                 metadata: [(INCOMING.to_string(), INCOMING.to_string())]
                     .into_iter()
                     .collect(),
+                tenant: None,
             },
             emission: Emission {
                 policy: EmissionPolicy {
@@ -361,9 +850,17 @@ mod signal_store_tests {
                         mul: 1.2,
                         offset: 3.4,
                     },
+                    conversion_failure_policy: ConversionFailurePolicy::SkipAndWarn,
+                    decimal_places: Some(2),
+                    trigger: None,
+                    adaptive_interval: None,
+                    privacy: None,
                 },
                 next_emission_ms: 123,
-                last_emitted_value: Some(INCOMING.to_string()),
+                last_emitted_value: Some(INCOMING.into()),
+                threshold_state: None,
+                history: VecDeque::new(),
+                adaptive_interval_ms: None,
             },
         };
 
@@ -402,6 +899,91 @@ mod signal_store_tests {
         );
     }
 
+    #[test]
+    fn sync_resets_threshold_state_when_conversion_changes() {
+        const UNCHANGED_ID: &str = "unchanged";
+        const CHANGED_ID: &str = "changed";
+
+        let create_signal = |id: &str, on: f64| Signal {
+            id: id.to_string(),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    conversion: Conversion::Threshold { on, off: 12.0 },
+                    ..Default::default()
+                },
+                threshold_state: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let uut = SignalStore::new();
+        {
+            let mut signals = uut.signals.write().unwrap();
+            for id in [UNCHANGED_ID, CHANGED_ID] {
+                signals.insert(id.to_string(), create_signal(id, 10.0));
+            }
+        }
+
+        uut.sync(
+            [
+                create_signal(UNCHANGED_ID, 10.0),
+                create_signal(CHANGED_ID, 5.0),
+            ]
+            .into_iter(),
+        );
+
+        let unchanged = uut.get(&UNCHANGED_ID.to_string()).unwrap();
+        assert_eq!(unchanged.emission.threshold_state, Some(true));
+        let changed = uut.get(&CHANGED_ID.to_string()).unwrap();
+        assert_eq!(changed.emission.threshold_state, None);
+    }
+
+    #[test]
+    fn sync_resets_adaptive_interval_when_policy_changes() {
+        const UNCHANGED_ID: &str = "unchanged";
+        const CHANGED_ID: &str = "changed";
+
+        let create_signal = |id: &str, max_interval_ms: u64| Signal {
+            id: id.to_string(),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    interval_ms: 1000,
+                    adaptive_interval: Some(AdaptiveInterval {
+                        min_interval_ms: 100,
+                        max_interval_ms,
+                    }),
+                    ..Default::default()
+                },
+                adaptive_interval_ms: Some(4000),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let uut = SignalStore::new();
+        {
+            let mut signals = uut.signals.write().unwrap();
+            for id in [UNCHANGED_ID, CHANGED_ID] {
+                signals.insert(id.to_string(), create_signal(id, 10000));
+            }
+        }
+
+        uut.sync(
+            [
+                create_signal(UNCHANGED_ID, 10000),
+                create_signal(CHANGED_ID, 5000),
+            ]
+            .into_iter(),
+        );
+
+        let unchanged = uut.get(&UNCHANGED_ID.to_string()).unwrap();
+        assert_eq!(unchanged.emission.interval_ms(), 4000);
+        let changed = uut.get(&CHANGED_ID.to_string()).unwrap();
+        assert_eq!(changed.emission.adaptive_interval_ms, None);
+        assert_eq!(changed.emission.interval_ms(), 1000);
+    }
+
     #[test]
     fn sync_inserts_new_signal() {
         const ID: &str = "id";
@@ -409,22 +991,26 @@ mod signal_store_tests {
 
         let incoming_signal = Signal {
             id: ID.to_string(),
-            value: Some(INCOMING.to_string()),
+            value: Some(INCOMING.into()),
+            numeric_value: None,
+            value_timestamp: None,
             source: Entity {
                 name: Some(INCOMING.to_string()),
                 id: ID.to_string(),
                 description: Some(INCOMING.to_string()),
                 endpoints: vec![EntityEndpoint {
                     protocol: INCOMING.to_string(),
-                    operations: vec![GET_OPERATION.to_string()],
+                    operations: vec![Operation::Get.to_string()],
                     uri: INCOMING.to_string(),
                     context: INCOMING.to_string(),
+                    ..Default::default()
                 }],
             },
             target: Target {
                 metadata: [(INCOMING.to_string(), INCOMING.to_string())]
                     .into_iter()
                     .collect(),
+                tenant: None,
             },
             emission: Emission {
                 policy: EmissionPolicy {
@@ -434,9 +1020,17 @@ mod signal_store_tests {
                         mul: 1.2,
                         offset: 3.4,
                     },
+                    conversion_failure_policy: ConversionFailurePolicy::SkipAndWarn,
+                    decimal_places: Some(2),
+                    trigger: None,
+                    adaptive_interval: None,
+                    privacy: None,
                 },
                 next_emission_ms: 123,
-                last_emitted_value: Some(INCOMING.to_string()),
+                last_emitted_value: Some(INCOMING.into()),
+                threshold_state: None,
+                history: VecDeque::new(),
+                adaptive_interval_ms: None,
             },
         };
 
@@ -468,6 +1062,24 @@ mod signal_store_tests {
         );
     }
 
+    #[test]
+    fn sync_keeps_pinned_signals() {
+        let patch = |id: &str| SignalPatch {
+            id: id.to_string(),
+            ..Default::default()
+        };
+
+        let uut = SignalStore::new();
+        uut.add_pinned([patch("pinned")].into_iter());
+        uut.add([patch("mapped")].into_iter());
+
+        uut.sync([patch("other")].into_iter());
+
+        let mut ids: Vec<String> = uut.get_all().into_iter().map(|s| s.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![String::from("other"), String::from("pinned")]);
+    }
+
     #[test]
     fn sync_deletes_signals_not_in_input() {
         const ID: &str = "id";
@@ -475,31 +1087,43 @@ mod signal_store_tests {
 
         let original_signal = Signal {
             id: ID.to_string(),
-            value: Some(ORIGINAL.to_string()),
+            value: Some(ORIGINAL.into()),
+            numeric_value: None,
+            value_timestamp: None,
             source: Entity {
                 name: Some(ORIGINAL.to_string()),
                 id: ID.to_string(),
                 description: Some(ORIGINAL.to_string()),
                 endpoints: vec![EntityEndpoint {
                     protocol: ORIGINAL.to_string(),
-                    operations: vec![GET_OPERATION.to_string()],
+                    operations: vec![Operation::Get.to_string()],
                     uri: ORIGINAL.to_string(),
                     context: ORIGINAL.to_string(),
+                    ..Default::default()
                 }],
             },
             target: Target {
                 metadata: [(ORIGINAL.to_string(), ORIGINAL.to_string())]
                     .into_iter()
                     .collect(),
+                tenant: None,
             },
             emission: Emission {
                 policy: EmissionPolicy {
                     interval_ms: 42,
                     emit_only_if_changed: false,
                     conversion: Conversion::None,
+                    conversion_failure_policy: ConversionFailurePolicy::EmitRaw,
+                    decimal_places: None,
+                    trigger: None,
+                    adaptive_interval: None,
+                    privacy: None,
                 },
                 next_emission_ms: 42,
-                last_emitted_value: Some(ORIGINAL.to_string()),
+                last_emitted_value: Some(ORIGINAL.into()),
+                threshold_state: None,
+                history: VecDeque::new(),
+                adaptive_interval_ms: None,
             },
         };
 
@@ -514,6 +1138,30 @@ mod signal_store_tests {
         assert!(maybe_updated_signal.is_none());
     }
 
+    #[test]
+    fn generation_is_incremented_by_add_and_sync() {
+        let uut = SignalStore::new();
+        let namespaced = uut.namespaced("ns");
+        assert_eq!(uut.generation(), 0);
+
+        uut.add(
+            [SignalPatch {
+                id: String::from("a"),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+        assert_eq!(uut.generation(), 1);
+
+        namespaced.sync(Vec::<SignalPatch>::new().into_iter());
+        assert_eq!(uut.generation(), 2);
+        assert_eq!(namespaced.generation(), 2);
+
+        // Setting values doesn't change the signals in the store
+        uut.set_value(String::from("a"), String::from("42"));
+        assert_eq!(uut.generation(), 2);
+    }
+
     #[test]
     fn set_value_tests() {
         const ID: &str = "testid";
@@ -530,7 +1178,7 @@ mod signal_store_tests {
         }
 
         // Test first set returns Some(None) and changes state
-        let value = String::from("value");
+        let value: Arc<str> = Arc::from("value");
         let result = uut.set_value(ID.to_string(), value.clone());
         assert!(result.is_some());
         assert!(result.unwrap().is_none());
@@ -567,6 +1215,230 @@ mod signal_store_tests {
         }
     }
 
+    #[test]
+    fn set_value_with_sequence_id_drops_duplicates() {
+        const ID: &str = "testid";
+
+        let uut = SignalStore::new();
+        uut.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+        uut.set_duplicate_filter(Arc::new(DuplicateFilter::new(
+            Duration::from_secs(60),
+            HashSet::new(),
+        )));
+
+        let result =
+            uut.set_value_with_sequence_id(ID.to_string(), "1".to_string(), "a".to_string());
+        assert_eq!(result, Some(None));
+
+        // A redelivery with the same sequence id is dropped even if the value differs
+        let result =
+            uut.set_value_with_sequence_id(ID.to_string(), "2".to_string(), "a".to_string());
+        assert_eq!(result, Some(Some("1".into())));
+        assert_eq!(uut.get(&ID.to_string()).unwrap().value, Some("1".into()));
+
+        // Values without a sequence id aren't deduplicated unless the signal is designated for content deduplication
+        uut.set_value(ID.to_string(), "1".to_string());
+        let result = uut.set_value(ID.to_string(), "1".to_string());
+        assert_eq!(result, Some(Some("1".into())));
+
+        let result =
+            uut.set_value_with_sequence_id(ID.to_string(), "3".to_string(), "b".to_string());
+        assert_eq!(result, Some(Some("1".into())));
+        assert_eq!(uut.get(&ID.to_string()).unwrap().value, Some("3".into()));
+    }
+
+    #[test]
+    fn set_value_records_timestamp_from_clock() {
+        const ID: &str = "testid";
+
+        let clock = Arc::new(ManualClock::default());
+        let uut = SignalStore::with_clock(clock.clone());
+        {
+            let mut signals = uut.signals.write().unwrap();
+            let signal = Signal {
+                id: ID.to_string(),
+                ..Default::default()
+            };
+
+            signals.insert(ID.to_string(), signal);
+        }
+
+        clock.advance(Duration::from_secs(42));
+        uut.set_value(ID.to_string(), String::from("value"));
+
+        let signal = uut.get(&ID.to_string()).unwrap();
+        assert_eq!(signal.value_timestamp, Some(clock.now()));
+    }
+
+    #[test]
+    fn set_value_caches_numeric_value() {
+        const ID: &str = "testid";
+
+        let uut = SignalStore::new();
+        {
+            let mut signals = uut.signals.write().unwrap();
+            let signal = Signal {
+                id: ID.to_string(),
+                ..Default::default()
+            };
+
+            signals.insert(ID.to_string(), signal);
+        }
+
+        uut.set_value(ID.to_string(), String::from("42.5"));
+        assert_eq!(uut.get(&ID.to_string()).unwrap().numeric_value, Some(42.5));
+
+        uut.set_value(ID.to_string(), String::from("on"));
+        assert_eq!(uut.get(&ID.to_string()).unwrap().numeric_value, None);
+    }
+
+    #[test]
+    fn set_value_notifies_observers() {
+        /// An observer which keeps every value it's notified of
+        #[derive(Default)]
+        struct RecordingObserver {
+            values: std::sync::Mutex<Vec<(String, String, OffsetDateTime)>>,
+        }
+
+        impl SignalValueObserver for RecordingObserver {
+            fn on_value_set(&self, id: &str, value: &Arc<str>, timestamp: OffsetDateTime) {
+                self.values
+                    .lock()
+                    .unwrap()
+                    .push((id.to_string(), value.to_string(), timestamp));
+            }
+        }
+
+        const ID: &str = "testid";
+
+        let clock = Arc::new(ManualClock::default());
+        let uut = SignalStore::with_clock(clock.clone());
+        uut.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        let observer = Arc::new(RecordingObserver::default());
+        uut.add_value_observer(observer.clone());
+
+        uut.set_value(ID.to_string(), String::from("value"));
+        uut.set_value(String::from("unknown"), String::from("other"));
+
+        assert_eq!(
+            *observer.values.lock().unwrap(),
+            vec![
+                (ID.to_string(), String::from("value"), clock.now()),
+                (String::from("unknown"), String::from("other"), clock.now()),
+            ]
+        );
+    }
+
+    #[test]
+    fn namespaced_view_qualifies_ids() {
+        const ID: &str = "testid";
+        const NAMESPACE: &str = "cabin";
+
+        let uut = SignalStore::new();
+        let qualified_id = namespace::qualify(Some(NAMESPACE), ID);
+        uut.add(
+            [
+                SignalPatch {
+                    id: ID.to_string(),
+                    ..Default::default()
+                },
+                SignalPatch {
+                    id: qualified_id.clone(),
+                    ..Default::default()
+                },
+            ]
+            .into_iter(),
+        );
+
+        let view = uut.namespaced(NAMESPACE);
+        view.set_value(ID.to_string(), String::from("namespaced"));
+
+        assert_eq!(
+            view.get(&ID.to_string()).unwrap().value,
+            Some("namespaced".into())
+        );
+        assert_eq!(
+            uut.get(&qualified_id).unwrap().value,
+            Some("namespaced".into())
+        );
+        assert_eq!(uut.get(&ID.to_string()).unwrap().value, None);
+    }
+
+    #[test]
+    fn set_threshold_state_tests() {
+        const ID: &str = "testid";
+
+        let uut = SignalStore::new();
+        {
+            let mut signals = uut.signals.write().unwrap();
+            let signal = Signal {
+                id: ID.to_string(),
+                ..Default::default()
+            };
+
+            signals.insert(ID.to_string(), signal);
+        }
+
+        assert_eq!(uut.set_threshold_state(ID.to_string(), true), Some(None));
+        assert_eq!(
+            uut.set_threshold_state(ID.to_string(), false),
+            Some(Some(true))
+        );
+        assert_eq!(
+            uut.get(&ID.to_string()).unwrap().emission.threshold_state,
+            Some(false)
+        );
+        assert_eq!(uut.set_threshold_state("foo".to_string(), true), None);
+    }
+
+    #[test]
+    fn set_adaptive_interval_tests() {
+        const ID: &str = "testid";
+
+        let uut = SignalStore::new();
+        {
+            let mut signals = uut.signals.write().unwrap();
+            let signal = Signal {
+                id: ID.to_string(),
+                ..Default::default()
+            };
+
+            signals.insert(ID.to_string(), signal);
+        }
+
+        assert_eq!(uut.set_adaptive_interval(ID.to_string(), 500), Some(None));
+        assert_eq!(
+            uut.set_adaptive_interval(ID.to_string(), 250),
+            Some(Some(500))
+        );
+
+        let signal = uut.get(&ID.to_string()).unwrap();
+        assert_eq!(signal.emission.interval_ms(), 250);
+        assert_eq!(signal.emission.next_emission_ms, 250);
+
+        // The next emission after a successful send also uses the adapted interval
+        uut.set_last_emitted_value(ID.to_string(), String::from("value"));
+        assert_eq!(
+            uut.get(&ID.to_string()).unwrap().emission.next_emission_ms,
+            250
+        );
+
+        assert_eq!(uut.set_adaptive_interval("foo".to_string(), 100), None);
+    }
+
     #[test]
     fn set_last_emitted_value_tests() {
         const ID: &str = "testid";
@@ -592,7 +1464,7 @@ mod signal_store_tests {
         }
 
         // Test first set returns Some(None) and changes state
-        let value = String::from("value");
+        let value: Arc<str> = Arc::from("value");
         let result = uut.set_last_emitted_value(ID.to_string(), value.clone());
         assert!(result.is_some());
         assert!(result.unwrap().is_none());
@@ -643,82 +1515,237 @@ mod signal_store_tests {
     }
 
     #[test]
-    fn update_emission_times_and_get_all_sets_correct_value() {
+    fn set_last_emitted_value_keeps_recent_history() {
+        const ID: &str = "testid";
+
+        let clock = Arc::new(ManualClock::default());
+        let uut = SignalStore::with_clock(clock.clone());
+        uut.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        for i in 0..EMISSION_HISTORY_LENGTH + 2 {
+            clock.advance(Duration::from_secs(1));
+            uut.set_last_emitted_value(ID.to_string(), i.to_string());
+        }
+
+        let history = uut.get(&ID.to_string()).unwrap().emission.history;
+        assert_eq!(history.len(), EMISSION_HISTORY_LENGTH);
+
+        // The oldest emissions are dropped first
+        let oldest = history.front().unwrap();
+        assert_eq!(&*oldest.value, "2");
+        assert_eq!(
+            oldest.timestamp,
+            OffsetDateTime::UNIX_EPOCH + Duration::from_secs(3)
+        );
+        assert_eq!(
+            history.back().unwrap().value.to_string(),
+            (EMISSION_HISTORY_LENGTH + 1).to_string()
+        );
+    }
+
+    #[test]
+    fn update_emission_times_sets_correct_value() {
         const ID: &str = "testid";
         const ORIGINAL_VALUE: u64 = 42;
         const INTERVAL: u64 = 20;
 
         let uut = SignalStore::new();
-        {
-            let mut signals = uut.signals.write().unwrap();
-            let signal = Signal {
+        uut.add(
+            [SignalPatch {
                 id: ID.to_string(),
-                emission: Emission {
-                    next_emission_ms: ORIGINAL_VALUE,
-                    ..Default::default()
-                },
                 ..Default::default()
-            };
-
-            signals.insert(ID.to_string(), signal);
-        }
+            }]
+            .into_iter(),
+        );
+        uut.set_value(ID.to_string(), String::from("foo"));
+        uut.set_adaptive_interval(ID.to_string(), ORIGINAL_VALUE);
 
-        let mut result = uut.update_emission_times_and_get_all(INTERVAL);
+        let mut result = uut.update_emission_times(INTERVAL, 0);
 
         // Validate the values in the result
         assert_eq!(result.len(), 1);
-        let signal = result.pop().unwrap();
-        assert_eq!(signal.id, ID.to_string());
-        assert_eq!(signal.emission.next_emission_ms, ORIGINAL_VALUE - INTERVAL);
+        let descriptor = result.pop().unwrap();
+        assert_eq!(
+            descriptor,
+            EmissionDescriptor {
+                id: ID.to_string(),
+                next_emission_ms: ORIGINAL_VALUE - INTERVAL,
+                overdue_ms: 0,
+                interval_ms: ORIGINAL_VALUE,
+                adaptive_interval: None,
+            }
+        );
 
         // Validate the values in the store itself
-        {
-            let signals = uut.signals.read().unwrap();
-            assert_eq!(signals.len(), 1);
-            assert!(signals.contains_key(&ID.to_string()));
-            let signal = signals.get(&ID.to_string()).unwrap();
-            assert_eq!(signal.id, ID.to_string());
-            assert_eq!(signal.emission.next_emission_ms, ORIGINAL_VALUE - INTERVAL);
-        }
+        let signal = uut.get(&ID.to_string()).unwrap();
+        assert_eq!(signal.value, Some("foo".into()));
+        assert_eq!(signal.emission.next_emission_ms, ORIGINAL_VALUE - INTERVAL);
     }
 
     #[test]
-    fn update_emission_times_and_get_all_saturates_overflowed_value() {
+    fn update_emission_times_saturates_overflowed_value() {
         const ID: &str = "testid";
         const ORIGINAL_VALUE: u64 = 20;
         const INTERVAL: u64 = u64::MAX;
 
         let uut = SignalStore::new();
-        {
-            let mut signals = uut.signals.write().unwrap();
-            let signal = Signal {
+        uut.add(
+            [SignalPatch {
                 id: ID.to_string(),
-                emission: Emission {
-                    next_emission_ms: ORIGINAL_VALUE,
-                    ..Default::default()
-                },
                 ..Default::default()
-            };
-
-            signals.insert(ID.to_string(), signal);
-        }
+            }]
+            .into_iter(),
+        );
+        uut.set_adaptive_interval(ID.to_string(), ORIGINAL_VALUE);
 
-        let mut result = uut.update_emission_times_and_get_all(INTERVAL);
+        let mut result = uut.update_emission_times(INTERVAL, 0);
 
         // Validate the values in the result
         assert_eq!(result.len(), 1);
-        let signal = result.pop().unwrap();
-        assert_eq!(signal.id, ID.to_string());
-        assert_eq!(signal.emission.next_emission_ms, 0);
+        let descriptor = result.pop().unwrap();
+        assert_eq!(descriptor.id, ID.to_string());
+        assert_eq!(descriptor.next_emission_ms, 0);
 
         // Validate the values in the store itself
-        {
-            let signals = uut.signals.read().unwrap();
-            assert_eq!(signals.len(), 1);
-            assert!(signals.contains_key(&ID.to_string()));
-            let signal = signals.get(&ID.to_string()).unwrap();
-            assert_eq!(signal.id, ID.to_string());
-            assert_eq!(signal.emission.next_emission_ms, 0);
-        }
+        let signal = uut.get(&ID.to_string()).unwrap();
+        assert_eq!(signal.emission.next_emission_ms, 0);
+    }
+
+    #[test]
+    fn update_emission_times_only_returns_upcoming_signals() {
+        let uut = SignalStore::new();
+        uut.add(["a", "b", "c"].into_iter().map(|id| SignalPatch {
+            id: id.to_string(),
+            ..Default::default()
+        }));
+
+        let ids = |descriptors: Vec<EmissionDescriptor>| -> Vec<(String, u64)> {
+            descriptors
+                .into_iter()
+                .map(|d| (d.id, d.next_emission_ms))
+                .collect()
+        };
+
+        // New signals are due immediately
+        assert_eq!(uut.update_emission_times(0, 0).len(), 3);
+
+        uut.set_adaptive_interval(String::from("a"), 100);
+        uut.set_adaptive_interval(String::from("b"), 1000);
+        uut.set_adaptive_interval(String::from("c"), 5000);
+
+        // Only the signals within the lookahead and the first one after them are returned
+        assert_eq!(
+            ids(uut.update_emission_times(50, 0)),
+            vec![(String::from("a"), 50)]
+        );
+        assert_eq!(
+            ids(uut.update_emission_times(0, 100)),
+            vec![(String::from("a"), 50), (String::from("b"), 950)]
+        );
+        assert_eq!(
+            ids(uut.update_emission_times(1000, 0)),
+            vec![
+                (String::from("a"), 0),
+                (String::from("b"), 0),
+                (String::from("c"), 3950)
+            ]
+        );
+
+        // Signals which are removed from the store are removed from the schedule
+        uut.sync(
+            [SignalPatch {
+                id: String::from("c"),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+        assert_eq!(
+            ids(uut.update_emission_times(0, 0)),
+            vec![(String::from("c"), 3950)]
+        );
+    }
+
+    #[test]
+    fn export_vss_json_builds_tree_from_source_ids() {
+        const SPEED_ID: &str = "Vehicle.Speed";
+        const TEMPERATURE_ID: &str = "Vehicle.Cabin.Temperature";
+
+        let uut = SignalStore::with_clock(Arc::new(ManualClock::default()));
+        uut.add(
+            [SPEED_ID, TEMPERATURE_ID]
+                .into_iter()
+                .map(|id| SignalPatch {
+                    id: id.to_string(),
+                    source: Entity {
+                        id: id.to_string(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+        );
+        uut.set_value(SPEED_ID.to_string(), String::from("42"));
+
+        let expected = serde_json::json!({
+            "Vehicle": {
+                "Speed": {
+                    "value": "42",
+                    "timestamp": "1970-01-01T00:00:00Z"
+                },
+                "Cabin": {
+                    "Temperature": {
+                        "value": null,
+                        "timestamp": null
+                    }
+                }
+            }
+        });
+        assert_eq!(uut.export_vss_json(), expected);
+    }
+
+    #[test]
+    fn export_emission_timeline_has_next_emission_and_history() {
+        const ID: &str = "testid";
+        const INTERVAL: u64 = 1000;
+
+        let clock = Arc::new(ManualClock::default());
+        let uut = SignalStore::with_clock(clock.clone());
+        uut.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                emission_policy: EmissionPolicy {
+                    interval_ms: INTERVAL,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        // The next emission isn't known until the emitter has updated the emission times
+        let timeline = uut.export_emission_timeline();
+        assert_eq!(timeline["signals"][0]["next_emission"], Value::Null);
+
+        uut.update_emission_times(0, 0);
+        uut.set_last_emitted_value(ID.to_string(), String::from("42"));
+
+        let expected = serde_json::json!({
+            "generated_at": "1970-01-01T00:00:00Z",
+            "signals": [{
+                "id": ID,
+                "interval_ms": INTERVAL,
+                "next_emission": "1970-01-01T00:00:01Z",
+                "emissions": [{
+                    "timestamp": "1970-01-01T00:00:00Z",
+                    "value": "42"
+                }]
+            }]
+        });
+        assert_eq!(uut.export_emission_timeline(), expected);
     }
 }
@@ -0,0 +1,229 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime, UtcOffset};
+
+/// The metadata key for overriding the timestamp format for a target
+pub const TIMESTAMP_FORMAT_METADATA_KEY: &str = "timestamp_format";
+
+/// The metadata key for overriding the UTC offset of timestamps for a target, in minutes
+pub const TIMESTAMP_UTC_OFFSET_METADATA_KEY: &str = "timestamp_utc_offset_minutes";
+
+/// Formats for serializing emitted timestamps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampFormat {
+    /// An RFC 3339 timestamp, such as `2024-01-02T03:04:05.678Z`
+    #[default]
+    Rfc3339,
+    /// The number of milliseconds since the Unix epoch
+    EpochMillis,
+    /// The number of seconds since the Unix epoch
+    EpochSeconds,
+}
+
+impl FromStr for TimestampFormat {
+    type Err = TimestampFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Rfc3339" => Ok(Self::Rfc3339),
+            "EpochMillis" => Ok(Self::EpochMillis),
+            "EpochSeconds" => Ok(Self::EpochSeconds),
+            _ => Err(TimestampFormatError::invalid_override(format!(
+                "Unknown timestamp format {s:?}"
+            ))),
+        }
+    }
+}
+
+/// Options for serializing emitted timestamps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampOptions {
+    /// The format for timestamps
+    #[serde(default)]
+    pub format: TimestampFormat,
+
+    /// The offset from UTC in minutes to convert timestamps to before formatting them.
+    /// If this is `None`, timestamps are converted to UTC.
+    /// Note that this has no effect on epoch formats, which don't include an offset
+    #[serde(default)]
+    pub utc_offset_minutes: Option<i16>,
+}
+
+impl TimestampOptions {
+    /// Removes the timestamp overrides from a target's metadata and applies them to a copy of these options
+    ///
+    /// # Arguments
+    /// - `metadata`: the target metadata to take the overrides from
+    pub fn take_overrides(
+        &self,
+        metadata: &mut HashMap<String, String>,
+    ) -> Result<Self, TimestampFormatError> {
+        let mut result = *self;
+
+        if let Some(format) = metadata.remove(TIMESTAMP_FORMAT_METADATA_KEY) {
+            result.format = format.parse()?;
+        }
+
+        if let Some(offset) = metadata.remove(TIMESTAMP_UTC_OFFSET_METADATA_KEY) {
+            let offset = offset.parse::<i16>().map_err(|e| {
+                TimestampFormatError::invalid_override(format!(
+                    "Invalid UTC offset {offset:?}: {e}"
+                ))
+            })?;
+            result.utc_offset_minutes = Some(offset);
+        }
+
+        Ok(result)
+    }
+
+    /// Formats a timestamp according to these options
+    ///
+    /// # Arguments
+    /// - `timestamp`: the timestamp to format
+    pub fn format(&self, timestamp: OffsetDateTime) -> Result<String, TimestampFormatError> {
+        match self.format {
+            TimestampFormat::Rfc3339 => {
+                let offset = match self.utc_offset_minutes {
+                    Some(minutes) => UtcOffset::from_whole_seconds(i32::from(minutes) * 60)
+                        .map_err(TimestampFormatError::format)?,
+                    None => UtcOffset::UTC,
+                };
+
+                timestamp
+                    .to_offset(offset)
+                    .format(&Rfc3339)
+                    .map_err(TimestampFormatError::format)
+            }
+            TimestampFormat::EpochMillis => {
+                Ok((timestamp.unix_timestamp_nanos() / 1_000_000).to_string())
+            }
+            TimestampFormat::EpochSeconds => Ok(timestamp.unix_timestamp().to_string()),
+        }
+    }
+}
+
+proc_macros::error! {
+    TimestampFormatError {
+        InvalidOverride,
+        Format
+    }
+}
+
+#[cfg(test)]
+mod timestamp_format_tests {
+    use super::*;
+
+    /// 2024-01-02T03:04:05.678Z
+    const TIMESTAMP_MILLIS: i128 = 1_704_164_645_678;
+
+    fn timestamp() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp_nanos(TIMESTAMP_MILLIS * 1_000_000).unwrap()
+    }
+
+    #[test]
+    fn format_rfc3339_defaults_to_utc() {
+        let options = TimestampOptions::default();
+        assert_eq!(
+            options.format(timestamp()).unwrap(),
+            "2024-01-02T03:04:05.678Z"
+        );
+    }
+
+    #[test]
+    fn format_rfc3339_applies_offset() {
+        let options = TimestampOptions {
+            format: TimestampFormat::Rfc3339,
+            utc_offset_minutes: Some(-90),
+        };
+        assert_eq!(
+            options.format(timestamp()).unwrap(),
+            "2024-01-02T01:34:05.678-01:30"
+        );
+    }
+
+    #[test]
+    fn format_rfc3339_returns_err_for_invalid_offset() {
+        let options = TimestampOptions {
+            format: TimestampFormat::Rfc3339,
+            utc_offset_minutes: Some(i16::MAX),
+        };
+        let result = options.format(timestamp());
+        assert!(result.is_err_and(|e| e.kind() == TimestampFormatErrorKind::Format));
+    }
+
+    #[test]
+    fn format_epoch_ignores_offset() {
+        for utc_offset_minutes in [None, Some(120)] {
+            let millis = TimestampOptions {
+                format: TimestampFormat::EpochMillis,
+                utc_offset_minutes,
+            };
+            assert_eq!(millis.format(timestamp()).unwrap(), "1704164645678");
+
+            let seconds = TimestampOptions {
+                format: TimestampFormat::EpochSeconds,
+                utc_offset_minutes,
+            };
+            assert_eq!(seconds.format(timestamp()).unwrap(), "1704164645");
+        }
+    }
+
+    #[test]
+    fn take_overrides_applies_and_removes_metadata() {
+        let mut metadata = HashMap::from([
+            (
+                TIMESTAMP_FORMAT_METADATA_KEY.to_string(),
+                "EpochSeconds".to_string(),
+            ),
+            (
+                TIMESTAMP_UTC_OFFSET_METADATA_KEY.to_string(),
+                "60".to_string(),
+            ),
+            ("instance_id".to_string(), "hvac".to_string()),
+        ]);
+
+        let result = TimestampOptions::default()
+            .take_overrides(&mut metadata)
+            .unwrap();
+
+        assert_eq!(
+            result,
+            TimestampOptions {
+                format: TimestampFormat::EpochSeconds,
+                utc_offset_minutes: Some(60),
+            }
+        );
+        assert_eq!(
+            metadata,
+            HashMap::from([("instance_id".to_string(), "hvac".to_string())])
+        );
+    }
+
+    #[test]
+    fn take_overrides_keeps_options_without_overrides() {
+        let options = TimestampOptions {
+            format: TimestampFormat::EpochMillis,
+            utc_offset_minutes: Some(30),
+        };
+        let mut metadata = HashMap::new();
+
+        assert_eq!(options.take_overrides(&mut metadata).unwrap(), options);
+    }
+
+    #[test]
+    fn take_overrides_returns_err_for_invalid_values() {
+        for (key, value) in [
+            (TIMESTAMP_FORMAT_METADATA_KEY, "Iso8601"),
+            (TIMESTAMP_UTC_OFFSET_METADATA_KEY, "+01:00"),
+        ] {
+            let mut metadata = HashMap::from([(key.to_string(), value.to_string())]);
+            let result = TimestampOptions::default().take_overrides(&mut metadata);
+            assert!(result.is_err_and(|e| e.kind() == TimestampFormatErrorKind::InvalidOverride));
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs::{self, File},
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::signal_store::SignalValueObserver;
+
+/// A value received from a provider, as recorded in a traffic trace.
+/// Traces are stored as JSON lines with one record per line.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceRecord {
+    /// The time at which the value was received, in milliseconds since the Unix epoch
+    pub timestamp_ms: i64,
+
+    /// The id of the entity that the value belongs to
+    pub entity_id: String,
+
+    /// The value
+    pub value: String,
+}
+
+impl TraceRecord {
+    /// Gets the time at which the value was received
+    pub fn timestamp(&self) -> Result<OffsetDateTime, TrafficTraceError> {
+        OffsetDateTime::from_unix_timestamp_nanos(i128::from(self.timestamp_ms) * 1_000_000)
+            .map_err(TrafficTraceError::deserialize)
+    }
+}
+
+/// Records every value set in a signal store to a trace file which can be replayed later.
+/// Each record is written as soon as the value is set so that traces survive crashes.
+pub struct TrafficRecorder {
+    /// The trace file
+    file: Mutex<File>,
+}
+
+impl TrafficRecorder {
+    /// Creates a new recorder, replacing the trace file if it already exists
+    ///
+    /// # Arguments
+    /// - `path`: the path of the trace file
+    pub fn create(path: &str) -> Result<Self, TrafficTraceError> {
+        let file = File::create(path).map_err(TrafficTraceError::io)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Writes a record to the trace file
+    ///
+    /// # Arguments
+    /// - `record`: the record to write
+    pub fn write(&self, record: &TraceRecord) -> Result<(), TrafficTraceError> {
+        let mut line = serde_json::to_string(record).map_err(TrafficTraceError::serialize)?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())
+            .map_err(TrafficTraceError::io)
+    }
+}
+
+impl SignalValueObserver for TrafficRecorder {
+    /// Records a value set in the signal store
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `value`: the new value of the signal
+    /// - `timestamp`: the time at which the value was set
+    fn on_value_set(&self, id: &str, value: &Arc<str>, timestamp: OffsetDateTime) {
+        let record = TraceRecord {
+            timestamp_ms: (timestamp.unix_timestamp_nanos() / 1_000_000) as i64,
+            entity_id: id.to_string(),
+            value: value.to_string(),
+        };
+
+        if let Err(e) = self.write(&record) {
+            warn!("Failed to record value for {id}: {e}");
+        }
+    }
+}
+
+/// Reads the records of a trace file in the order in which they were recorded.
+/// Blank lines are ignored.
+///
+/// # Arguments
+/// - `path`: the path of the trace file
+pub fn read_trace(path: &str) -> Result<Vec<TraceRecord>, TrafficTraceError> {
+    let contents = fs::read_to_string(path).map_err(TrafficTraceError::io)?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                TrafficTraceError::deserialize(format!("Invalid record on line {}: {e}", i + 1))
+            })
+        })
+        .collect()
+}
+
+proc_macros::error! {
+    TrafficTraceError {
+        Io,
+        Serialize,
+        Deserialize
+    }
+}
+
+#[cfg(test)]
+mod traffic_trace_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use freyja_test_common::fixtures::TempDirFixture;
+
+    /// A trace file in a temp directory which is deleted when dropped
+    struct TraceFile {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl TraceFile {
+        fn new(name: &str) -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join(format!("{name}.jsonl"));
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+
+        fn path(&self) -> String {
+            self.path.to_string_lossy().into_owned()
+        }
+    }
+
+    #[test]
+    fn recorded_values_can_be_read() {
+        let file = TraceFile::new("freyja_traffic_trace_round_trip");
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_704_164_645).unwrap();
+
+        let uut = TrafficRecorder::create(&file.path()).unwrap();
+        uut.on_value_set("speed", &Arc::from("42"), timestamp);
+        uut.on_value_set("temperature", &Arc::from("21.5"), timestamp);
+
+        let records = read_trace(&file.path()).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                TraceRecord {
+                    timestamp_ms: 1_704_164_645_000,
+                    entity_id: String::from("speed"),
+                    value: String::from("42"),
+                },
+                TraceRecord {
+                    timestamp_ms: 1_704_164_645_000,
+                    entity_id: String::from("temperature"),
+                    value: String::from("21.5"),
+                },
+            ]
+        );
+        assert_eq!(records[0].timestamp().unwrap(), timestamp);
+    }
+
+    #[test]
+    fn read_trace_returns_err_for_invalid_record() {
+        let file = TraceFile::new("freyja_traffic_trace_invalid");
+        fs::write(
+            &file.path,
+            "{\"timestamp_ms\":0,\"entity_id\":\"speed\",\"value\":\"42\"}\n\nnot a record\n",
+        )
+        .unwrap();
+
+        let result = read_trace(&file.path());
+        assert!(result.is_err_and(|e| e.kind() == TrafficTraceErrorKind::Deserialize));
+    }
+
+    #[test]
+    fn read_trace_returns_err_for_missing_file() {
+        let result = read_trace("/this/path/does/not/exist.jsonl");
+        assert!(result.is_err_and(|e| e.kind() == TrafficTraceErrorKind::Io));
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{cmp::Ordering, iter::Peekable, str::Chars, sync::Arc};
+
+/// Evaluates a trigger expression which gates the emission of a signal on the values of other signals,
+/// such as `{dtmi:sdv:Vehicle:Speed;1} > 100 && {dtmi:sdv:Vehicle:Gear;1} != "R"`.
+///
+/// Expressions compare operands with `==`, `!=`, `<`, `<=`, `>` and `>=`,
+/// and comparisons can be combined with `&&`, `||`, `!` and parentheses.
+/// An operand is a signal id in braces, a number, or a string in double quotes.
+/// Values are compared as numbers when both operands are numeric, otherwise only `==` and `!=` are satisfied.
+/// A comparison with a signal that is not in the store or has no value is never satisfied,
+/// so a trigger stays closed until all of the signals it depends on have values.
+///
+/// # Arguments
+/// - `expression`: the trigger expression
+/// - `lookup`: gets the current value of a signal by id
+pub fn evaluate(
+    expression: &str,
+    lookup: impl Fn(&str) -> Option<Arc<str>>,
+) -> Result<bool, TriggerError> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        expression,
+        tokens: tokens.into_iter().peekable(),
+    };
+
+    let result = parser.parse_or()?;
+    match parser.tokens.next() {
+        Some(token) => Err(TriggerError::parse(format!(
+            "Unexpected {token:?} in trigger {expression:?}"
+        ))),
+        None => Ok(result.evaluate(&lookup)),
+    }
+}
+
+/// A token of a trigger expression
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Signal(String),
+    Number(f64),
+    Text(String),
+    Comparison(Comparison),
+    And,
+    Or,
+    Not,
+    OpenParen,
+    CloseParen,
+}
+
+/// A comparison operator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    /// Returns true if an ordering satisfies this comparison
+    ///
+    /// # Arguments
+    /// - `ordering`: the ordering of the left operand relative to the right one
+    fn is_satisfied_by(&self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering == Ordering::Equal,
+            Self::Ne => ordering != Ordering::Equal,
+            Self::Lt => ordering == Ordering::Less,
+            Self::Le => ordering != Ordering::Greater,
+            Self::Gt => ordering == Ordering::Greater,
+            Self::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// An operand of a comparison
+#[derive(Clone, Debug, PartialEq)]
+enum Operand {
+    Signal(String),
+    Number(f64),
+    Text(String),
+}
+
+impl Operand {
+    /// Resolves the value of this operand, or `None` if it refers to a signal without a value.
+    /// Signal values which can be parsed as numbers are treated as numbers.
+    ///
+    /// # Arguments
+    /// - `lookup`: gets the current value of a signal by id
+    fn resolve(&self, lookup: &impl Fn(&str) -> Option<Arc<str>>) -> Option<Operand> {
+        match self {
+            Self::Signal(id) => {
+                let value = lookup(id)?;
+                Some(match value.parse::<f64>() {
+                    Ok(n) => Self::Number(n),
+                    Err(_) => Self::Text(value.to_string()),
+                })
+            }
+            operand => Some(operand.clone()),
+        }
+    }
+}
+
+/// A parsed trigger expression
+#[derive(Clone, Debug, PartialEq)]
+enum Expression {
+    Compare(Operand, Comparison, Operand),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+}
+
+impl Expression {
+    /// Evaluates this expression
+    ///
+    /// # Arguments
+    /// - `lookup`: gets the current value of a signal by id
+    fn evaluate(&self, lookup: &impl Fn(&str) -> Option<Arc<str>>) -> bool {
+        match self {
+            Self::Compare(left, comparison, right) => {
+                match (left.resolve(lookup), right.resolve(lookup)) {
+                    (Some(Operand::Number(l)), Some(Operand::Number(r))) => l
+                        .partial_cmp(&r)
+                        .is_some_and(|ordering| comparison.is_satisfied_by(ordering)),
+                    (Some(Operand::Text(l)), Some(Operand::Text(r))) => match comparison {
+                        Comparison::Eq => l == r,
+                        Comparison::Ne => l != r,
+                        _ => false,
+                    },
+                    (Some(_), Some(_)) => *comparison == Comparison::Ne,
+                    _ => false,
+                }
+            }
+            Self::And(left, right) => left.evaluate(lookup) && right.evaluate(lookup),
+            Self::Or(left, right) => left.evaluate(lookup) || right.evaluate(lookup),
+            Self::Not(inner) => !inner.evaluate(lookup),
+        }
+    }
+}
+
+/// A recursive descent parser for trigger expressions
+struct Parser<'a> {
+    /// The expression being parsed, for error messages
+    expression: &'a str,
+
+    /// The remaining tokens
+    tokens: Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser<'_> {
+    /// Parses expressions separated by `||`
+    fn parse_or(&mut self) -> Result<Expression, TriggerError> {
+        let mut result = self.parse_and()?;
+        while self.tokens.next_if_eq(&Token::Or).is_some() {
+            result = Expression::Or(Box::new(result), Box::new(self.parse_and()?));
+        }
+
+        Ok(result)
+    }
+
+    /// Parses expressions separated by `&&`
+    fn parse_and(&mut self) -> Result<Expression, TriggerError> {
+        let mut result = self.parse_unary()?;
+        while self.tokens.next_if_eq(&Token::And).is_some() {
+            result = Expression::And(Box::new(result), Box::new(self.parse_unary()?));
+        }
+
+        Ok(result)
+    }
+
+    /// Parses a negation, a parenthesized expression, or a comparison
+    fn parse_unary(&mut self) -> Result<Expression, TriggerError> {
+        match self.tokens.next() {
+            Some(Token::Not) => Ok(Expression::Not(Box::new(self.parse_unary()?))),
+            Some(Token::OpenParen) => {
+                let result = self.parse_or()?;
+                match self.tokens.next() {
+                    Some(Token::CloseParen) => Ok(result),
+                    _ => Err(TriggerError::parse(format!(
+                        "Unclosed parenthesis in trigger {:?}",
+                        self.expression
+                    ))),
+                }
+            }
+            token => {
+                let left = self.to_operand(token)?;
+                let comparison = match self.tokens.next() {
+                    Some(Token::Comparison(comparison)) => comparison,
+                    _ => {
+                        return Err(TriggerError::parse(format!(
+                            "Expected a comparison after {left:?} in trigger {:?}",
+                            self.expression
+                        )))
+                    }
+                };
+                let token = self.tokens.next();
+                let right = self.to_operand(token)?;
+
+                Ok(Expression::Compare(left, comparison, right))
+            }
+        }
+    }
+
+    /// Converts a token to an operand
+    ///
+    /// # Arguments
+    /// - `token`: the token to convert
+    fn to_operand(&self, token: Option<Token>) -> Result<Operand, TriggerError> {
+        match token {
+            Some(Token::Signal(id)) => Ok(Operand::Signal(id)),
+            Some(Token::Number(n)) => Ok(Operand::Number(n)),
+            Some(Token::Text(s)) => Ok(Operand::Text(s)),
+            token => Err(TriggerError::parse(format!(
+                "Expected an operand but found {token:?} in trigger {:?}",
+                self.expression
+            ))),
+        }
+    }
+}
+
+/// Splits a trigger expression into tokens
+///
+/// # Arguments
+/// - `expression`: the trigger expression
+fn tokenize(expression: &str) -> Result<Vec<Token>, TriggerError> {
+    let invalid = |reason: &str| TriggerError::parse(format!("{reason} in trigger {expression:?}"));
+
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '{' => Token::Signal(
+                read_until(&mut chars, '}').ok_or_else(|| invalid("Unclosed signal reference"))?,
+            ),
+            '"' => {
+                Token::Text(read_until(&mut chars, '"').ok_or_else(|| invalid("Unclosed string"))?)
+            }
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Comparison(Comparison::Eq),
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Comparison(Comparison::Ne),
+            '!' => Token::Not,
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Comparison(Comparison::Le),
+            '<' => Token::Comparison(Comparison::Lt),
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Comparison(Comparison::Ge),
+            '>' => Token::Comparison(Comparison::Gt),
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut number = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '.') {
+                    number.push(c);
+                }
+
+                Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| invalid(&format!("Invalid number {number:?}")))?,
+                )
+            }
+            c => return Err(invalid(&format!("Unexpected character {c:?}"))),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Reads characters up to a terminator, which is consumed.
+/// Returns `None` if the terminator is not found.
+///
+/// # Arguments
+/// - `chars`: the characters to read
+/// - `terminator`: the character which ends the text
+fn read_until(chars: &mut Peekable<Chars>, terminator: char) -> Option<String> {
+    let mut result = String::new();
+    loop {
+        match chars.next()? {
+            c if c == terminator => return Some(result),
+            c => result.push(c),
+        }
+    }
+}
+
+proc_macros::error! {
+    TriggerError {
+        Parse
+    }
+}
+
+#[cfg(test)]
+mod trigger_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    const SPEED_ID: &str = "dtmi:sdv:Vehicle:Speed;1";
+    const GEAR_ID: &str = "dtmi:sdv:Vehicle:Gear;1";
+
+    fn lookup(values: &[(&str, &str)]) -> impl Fn(&str) -> Option<Arc<str>> {
+        let values: HashMap<String, Arc<str>> = values
+            .iter()
+            .map(|(id, value)| (id.to_string(), Arc::from(*value)))
+            .collect();
+
+        move |id| values.get(id).cloned()
+    }
+
+    #[test]
+    fn evaluate_compares_numbers() {
+        let values = lookup(&[(SPEED_ID, "120.5")]);
+        let cases = [
+            ("> 100", true),
+            (">= 120.5", true),
+            ("< 100", false),
+            ("<= 120", false),
+            ("== 120.5", true),
+            ("!= 120.5", false),
+        ];
+
+        for (comparison, expected) in cases {
+            let expression = format!("{{{SPEED_ID}}} {comparison}");
+            assert_eq!(evaluate(&expression, &values).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn evaluate_compares_text_for_equality_only() {
+        let values = lookup(&[(GEAR_ID, "R")]);
+
+        assert!(evaluate(&format!("{{{GEAR_ID}}} == \"R\""), &values).unwrap());
+        assert!(!evaluate(&format!("{{{GEAR_ID}}} != \"R\""), &values).unwrap());
+        assert!(!evaluate(&format!("{{{GEAR_ID}}} > \"A\""), &values).unwrap());
+        assert!(evaluate(&format!("{{{GEAR_ID}}} != 3"), &values).unwrap());
+    }
+
+    #[test]
+    fn evaluate_combines_comparisons() {
+        let values = lookup(&[(SPEED_ID, "120"), (GEAR_ID, "D")]);
+        let cases = [
+            (
+                format!("{{{SPEED_ID}}} > 100 && {{{GEAR_ID}}} == \"D\""),
+                true,
+            ),
+            (
+                format!("{{{SPEED_ID}}} > 150 && {{{GEAR_ID}}} == \"D\""),
+                false,
+            ),
+            (
+                format!("{{{SPEED_ID}}} > 150 || {{{GEAR_ID}}} == \"D\""),
+                true,
+            ),
+            (format!("!({{{SPEED_ID}}} > 100)"), false),
+            (
+                format!("({{{SPEED_ID}}} > 150 || {{{SPEED_ID}}} < 10) && {{{GEAR_ID}}} == \"D\""),
+                false,
+            ),
+            (
+                format!("{{{SPEED_ID}}} > 150 || {{{SPEED_ID}}} > 10 && {{{GEAR_ID}}} == \"D\""),
+                true,
+            ),
+        ];
+
+        for (expression, expected) in cases {
+            assert_eq!(
+                evaluate(&expression, &values).unwrap(),
+                expected,
+                "{expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn evaluate_is_not_satisfied_by_signals_without_values() {
+        let values = lookup(&[]);
+
+        assert!(!evaluate(&format!("{{{SPEED_ID}}} > 100"), &values).unwrap());
+        assert!(!evaluate(&format!("{{{SPEED_ID}}} != 100"), &values).unwrap());
+    }
+
+    #[test]
+    fn evaluate_rejects_invalid_expressions() {
+        let values = lookup(&[(SPEED_ID, "120")]);
+        let expressions = [
+            "",
+            "{speed > 100",
+            "{speed} >",
+            "{speed} 100",
+            "({speed} > 100",
+            "{speed} > 100)",
+            "{speed} > \"fast",
+            "{speed} > 1.2.3",
+            "{speed} = 100",
+            "{speed} > 100 &&",
+        ];
+
+        for expression in expressions {
+            let result = evaluate(expression, &values);
+            assert!(
+                result.is_err_and(|e| e.kind() == TriggerErrorKind::Parse),
+                "{expression}"
+            );
+        }
+    }
+}
@@ -0,0 +1,309 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt,
+    net::{Ipv6Addr, SocketAddr},
+};
+
+/// The kinds of endpoints that adapters connect to, which determine the schemes and default ports that a uri can have
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UriKind {
+    /// A gRPC server
+    Grpc,
+    /// An HTTP server
+    Http,
+    /// An MQTT broker
+    Mqtt,
+    /// A DoIP entity, such as a vehicle's diagnostic gateway
+    Doip,
+}
+
+impl UriKind {
+    /// Gets the schemes which are valid for this kind of endpoint and the default port of each
+    fn schemes(&self) -> &'static [(&'static str, u16)] {
+        match self {
+            UriKind::Grpc | UriKind::Http => &[("http", 80), ("https", 443)],
+            UriKind::Mqtt => &[
+                ("tcp", 1883),
+                ("mqtt", 1883),
+                ("ssl", 8883),
+                ("mqtts", 8883),
+                ("ws", 80),
+                ("wss", 443),
+            ],
+            UriKind::Doip => &[("doip", 13400), ("tcp", 13400)],
+        }
+    }
+}
+
+impl fmt::Display for UriKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UriKind::Grpc => write!(f, "gRPC"),
+            UriKind::Http => write!(f, "HTTP"),
+            UriKind::Mqtt => write!(f, "MQTT"),
+            UriKind::Doip => write!(f, "DoIP"),
+        }
+    }
+}
+
+/// A validated endpoint uri in the form `{scheme}://{host}[:{port}][{path}]`.
+/// The scheme and host are lowercase and the path has no trailing slash.
+/// The port is only included when the uri is displayed if it was specified
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndpointUri {
+    /// The scheme
+    scheme: String,
+
+    /// The host name or IP address. IPv6 addresses don't have brackets
+    host: String,
+
+    /// The port, if it was specified
+    explicit_port: Option<u16>,
+
+    /// The default port of the scheme
+    default_port: u16,
+
+    /// The path, which is empty or starts with a slash
+    path: String,
+}
+
+impl EndpointUri {
+    /// Parses and validates an endpoint uri.
+    /// Returns an error which explains what's wrong with the uri and gives an example of a valid one
+    ///
+    /// # Arguments
+    /// - `uri`: the uri to parse
+    /// - `kind`: the kind of endpoint that the uri refers to
+    pub fn parse(uri: &str, kind: UriKind) -> Result<Self, UriError> {
+        let invalid = |reason: String| {
+            let (example_scheme, _) = kind.schemes()[0];
+            UriError::invalid_uri(format!(
+                "Invalid {kind} uri {uri:?}: {reason}. Expected a uri such as {example_scheme}://localhost:5000"
+            ))
+        };
+
+        let trimmed = uri.trim();
+        let (scheme, rest) = trimmed
+            .split_once("://")
+            .ok_or_else(|| invalid(String::from("the scheme is missing")))?;
+        let scheme = scheme.to_ascii_lowercase();
+        let default_port = kind
+            .schemes()
+            .iter()
+            .find(|(s, _)| *s == scheme)
+            .map(|(_, port)| *port)
+            .ok_or_else(|| {
+                let supported = kind
+                    .schemes()
+                    .iter()
+                    .map(|(s, _)| *s)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                UriError::unsupported_scheme(format!(
+                    "Unsupported scheme {scheme:?} in {kind} uri {uri:?}. The supported schemes are {supported}"
+                ))
+            })?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+        if authority.contains('@') {
+            return Err(invalid(String::from(
+                "credentials must be configured separately rather than included in the uri",
+            )));
+        }
+
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            let (host, port) = bracketed
+                .split_once(']')
+                .ok_or_else(|| invalid(String::from("the IPv6 address is missing a ']'")))?;
+            host.parse::<Ipv6Addr>()
+                .map_err(|_| invalid(format!("{host:?} is not a valid IPv6 address")))?;
+            match port {
+                "" => (host, None),
+                port => match port.strip_prefix(':') {
+                    Some(port) => (host, Some(port)),
+                    None => {
+                        return Err(invalid(String::from(
+                            "the host is followed by an unexpected character",
+                        )))
+                    }
+                },
+            }
+        } else {
+            match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+
+        if host.is_empty() {
+            return Err(invalid(String::from("the host is missing")));
+        }
+        if !host.contains(':')
+            && !host
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_'))
+        {
+            return Err(invalid(format!("{host:?} is not a valid host")));
+        }
+
+        let explicit_port = port
+            .map(|port| match port.parse::<u16>() {
+                Ok(port) if port != 0 => Ok(port),
+                _ => Err(invalid(format!("{port:?} is not a valid port"))),
+            })
+            .transpose()?;
+
+        Ok(Self {
+            scheme,
+            host: host.to_ascii_lowercase(),
+            explicit_port,
+            default_port,
+            path: path.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Gets the scheme
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Gets the host name or IP address
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Gets the port, which is the default port of the scheme if the uri doesn't specify one
+    pub fn port(&self) -> u16 {
+        self.explicit_port.unwrap_or(self.default_port)
+    }
+
+    /// Gets the path, which is empty or starts with a slash
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl fmt::Display for EndpointUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://", self.scheme)?;
+        if self.host.contains(':') {
+            write!(f, "[{}]", self.host)?;
+        } else {
+            write!(f, "{}", self.host)?;
+        }
+
+        if let Some(port) = self.explicit_port {
+            write!(f, ":{port}")?;
+        }
+
+        write!(f, "{}", self.path)
+    }
+}
+
+/// Parses the address that a server listens on, such as `0.0.0.0:8080` or `[::1]:8080`
+///
+/// # Arguments
+/// - `address`: the address to parse
+pub fn parse_socket_address(address: &str) -> Result<SocketAddr, UriError> {
+    address.trim().parse().map_err(|_| {
+        UriError::invalid_address(format!(
+            "Invalid listen address {address:?}. Expected an IP address and a port such as 0.0.0.0:8080 or [::1]:8080"
+        ))
+    })
+}
+
+proc_macros::error! {
+    UriError {
+        InvalidUri,
+        UnsupportedScheme,
+        InvalidAddress,
+        Unknown
+    }
+}
+
+#[cfg(test)]
+mod uri_tests {
+    use super::*;
+
+    #[test]
+    fn parse_normalizes_uri() {
+        let uri = EndpointUri::parse(" HTTP://Provider.Example.com/api/ ", UriKind::Http) // Devskim: ignore DS137138
+            .unwrap();
+
+        assert_eq!(uri.scheme(), "http");
+        assert_eq!(uri.host(), "provider.example.com");
+        assert_eq!(uri.port(), 80);
+        assert_eq!(uri.path(), "/api");
+        assert_eq!(uri.to_string(), "http://provider.example.com/api"); // Devskim: ignore DS137138
+    }
+
+    #[test]
+    fn parse_extracts_explicit_port_and_ipv6_host() {
+        let uri = EndpointUri::parse("mqtts://[::1]:9000", UriKind::Mqtt).unwrap();
+
+        assert_eq!(uri.host(), "::1");
+        assert_eq!(uri.port(), 9000);
+        assert_eq!(uri.to_string(), "mqtts://[::1]:9000");
+
+        let uri = EndpointUri::parse("tcp://broker", UriKind::Mqtt).unwrap();
+        assert_eq!(uri.port(), 1883);
+    }
+
+    #[test]
+    fn parse_returns_err_for_invalid_uris() {
+        for (uri, kind, expected) in [
+            ("localhost:5000", UriKind::Grpc, UriErrorKind::InvalidUri),
+            (
+                "tcp://localhost:5000",
+                UriKind::Grpc,
+                UriErrorKind::UnsupportedScheme,
+            ),
+            (
+                "https://localhost:1883",
+                UriKind::Mqtt,
+                UriErrorKind::UnsupportedScheme,
+            ),
+            ("http://:5000", UriKind::Http, UriErrorKind::InvalidUri), // Devskim: ignore DS137138
+            (
+                "http://localhost:port", // Devskim: ignore DS137138
+                UriKind::Http,
+                UriErrorKind::InvalidUri,
+            ),
+            (
+                "http://localhost:70000", // Devskim: ignore DS137138
+                UriKind::Http,
+                UriErrorKind::InvalidUri,
+            ),
+            (
+                "http://user:pw@localhost", // Devskim: ignore DS137138
+                UriKind::Http,
+                UriErrorKind::InvalidUri,
+            ),
+            ("http://[::g]:5000", UriKind::Grpc, UriErrorKind::InvalidUri), // Devskim: ignore DS137138
+            ("http://local host", UriKind::Grpc, UriErrorKind::InvalidUri), // Devskim: ignore DS137138
+        ] {
+            let result = EndpointUri::parse(uri, kind);
+            assert!(
+                result.as_ref().is_err_and(|e| e.kind() == expected),
+                "{uri} should be rejected with {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_socket_address_explains_invalid_address() {
+        assert_eq!(
+            parse_socket_address("[::1]:8080").unwrap(),
+            "[::1]:8080".parse::<SocketAddr>().unwrap()
+        );
+
+        let result = parse_socket_address("localhost:8080");
+        assert!(result.is_err_and(|e| e.kind() == UriErrorKind::InvalidAddress));
+    }
+}
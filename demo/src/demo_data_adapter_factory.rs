@@ -0,0 +1,94 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use freyja_common::{
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterFactory},
+    entity::{Entity, EntityEndpoint, Operation, Protocol},
+    signal_store::SignalStore,
+};
+use in_memory_mock_data_adapter::{
+    config::Config, in_memory_mock_data_adapter::InMemoryMockDataAdapter,
+};
+
+/// The demo's synthetic waveforms, which are bundled into the binary so that it doesn't depend on its working directory
+const CONFIG: &str = include_str!("../res/demo_data_config.json");
+
+/// Factory for creating in-memory data adapters which generate the demo's synthetic waveforms
+pub struct DemoDataAdapterFactory {
+    /// The bundled waveforms
+    config: Config,
+}
+
+impl DataAdapterFactory for DemoDataAdapterFactory {
+    /// Create a new factory with the bundled waveforms
+    fn create_new() -> Result<Self, DataAdapterError> {
+        let config = serde_json::from_str(CONFIG).map_err(DataAdapterError::deserialize)?;
+
+        Ok(Self { config })
+    }
+
+    /// Check to see whether this factory can create a data adapter for the requested entity.
+    /// Returns the first endpoint found that is supported by this factory.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to check for compatibility
+    fn is_supported(&self, entity: &Entity) -> Option<EntityEndpoint> {
+        entity.is_supported(
+            &[Protocol::InMemory],
+            &[Operation::Get, Operation::Subscribe],
+        )
+    }
+
+    /// Create a new data adapter which generates the bundled waveforms
+    ///
+    /// # Arguments
+    /// - `_provider_uri`: the provider URI to associate with this data adapter (unused by this factory)
+    /// - `signals`: the shared signal store
+    fn create_adapter(
+        &self,
+        _provider_uri: &str,
+        signals: Arc<SignalStore>,
+    ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError> {
+        Ok(Arc::new(InMemoryMockDataAdapter::from_config(
+            self.config.clone(),
+            signals,
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod demo_data_adapter_factory_tests {
+    use super::*;
+
+    #[test]
+    fn can_create_adapter_for_bundled_waveforms() {
+        let uut = DemoDataAdapterFactory::create_new().unwrap();
+        assert_eq!(uut.config.entities.len(), 4);
+
+        let result = uut.create_adapter("in-memory://demo", Arc::new(SignalStore::new()));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn is_supported_only_supports_in_memory_endpoints() {
+        let uut = DemoDataAdapterFactory::create_new().unwrap();
+        let mut entity = Entity {
+            id: String::from("dtmi:sdv:Vehicle:Speed;1"),
+            endpoints: vec![EntityEndpoint {
+                protocol: String::from("mqtt"),
+                operations: vec![String::from("Subscribe")],
+                uri: String::from("mqtt://localhost:1883"),
+                context: String::from("vehicle/speed"),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(uut.is_supported(&entity).is_none());
+
+        entity.endpoints[0].protocol = String::from("in-memory");
+        assert!(uut.is_supported(&entity).is_some());
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use freyja_common::{
+    digital_twin_adapter::{
+        DigitalTwinAdapter, DigitalTwinAdapterError, FindByIdRequest, FindByIdResponse,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+use in_memory_mock_digital_twin_adapter::in_memory_mock_digital_twin_adapter::InMemoryMockDigitalTwinAdapter;
+
+/// The demo's entities, which are bundled into the binary so that it doesn't depend on its working directory
+const CONFIG: &str = include_str!("../res/demo_digital_twin_config.json");
+
+/// An in-memory digital twin adapter which finds the demo's entities
+pub struct DemoDigitalTwinAdapter {
+    /// The adapter which serves the bundled entities
+    inner: InMemoryMockDigitalTwinAdapter,
+}
+
+#[async_trait]
+impl DigitalTwinAdapter for DemoDigitalTwinAdapter {
+    /// Creates a new instance of a DigitalTwinAdapter with the bundled entities
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector to use (unused by this adapter)
+    fn create_new(
+        _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, DigitalTwinAdapterError> {
+        let config = serde_json::from_str(CONFIG).map_err(DigitalTwinAdapterError::deserialize)?;
+
+        Ok(Self {
+            inner: InMemoryMockDigitalTwinAdapter::from_config(config)?,
+        })
+    }
+
+    /// Gets the entity information based on the request
+    ///
+    /// # Arguments
+    /// - `request`: the request to send
+    async fn find_by_id(
+        &self,
+        request: FindByIdRequest,
+    ) -> Result<FindByIdResponse, DigitalTwinAdapterError> {
+        self.inner.find_by_id(request).await
+    }
+}
+
+#[cfg(test)]
+mod demo_digital_twin_adapter_tests {
+    use super::*;
+
+    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+
+    #[tokio::test]
+    async fn find_by_id_finds_bundled_entities() {
+        let uut = DemoDigitalTwinAdapter::create_new(Arc::new(Mutex::new(
+            MockServiceDiscoveryAdapterSelector::new(),
+        )))
+        .unwrap();
+
+        let request = FindByIdRequest {
+            entity_id: String::from("dtmi:sdv:Vehicle:Speed;1"),
+        };
+        let response = uut.find_by_id(request).await.unwrap();
+        assert_eq!(response.entity.id, "dtmi:sdv:Vehicle:Speed;1");
+    }
+}
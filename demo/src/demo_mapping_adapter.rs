@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use freyja_common::{
+    mapping_adapter::{
+        CheckForWorkRequest, CheckForWorkResponse, GetMappingRequest, GetMappingResponse,
+        MappingAdapter, MappingAdapterError,
+    },
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+use in_memory_mock_mapping_adapter::in_memory_mock_mapping_adapter::InMemoryMockMappingAdapter;
+
+/// The demo's mapping, which is bundled into the binary so that it doesn't depend on its working directory
+const CONFIG: &str = include_str!("../res/demo_mapping_config.json");
+
+/// An in-memory mapping adapter which maps the demo's entities to cloud properties
+pub struct DemoMappingAdapter {
+    /// The adapter which serves the bundled mapping
+    inner: InMemoryMockMappingAdapter,
+}
+
+#[async_trait]
+impl MappingAdapter for DemoMappingAdapter {
+    /// Creates a new instance of a MappingAdapter with the bundled mapping
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector to use (unused by this adapter)
+    fn create_new(
+        _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, MappingAdapterError> {
+        let config = serde_json::from_str(CONFIG).map_err(MappingAdapterError::deserialize)?;
+
+        Ok(Self {
+            inner: InMemoryMockMappingAdapter::from_config(config)?,
+        })
+    }
+
+    /// Checks for any additional work that the mapping service requires.
+    /// The bundled mapping only reports work the first time this is called.
+    ///
+    /// # Arguments
+    /// - `request`: the request to send
+    async fn check_for_work(
+        &self,
+        request: CheckForWorkRequest,
+    ) -> Result<CheckForWorkResponse, MappingAdapterError> {
+        self.inner.check_for_work(request).await
+    }
+
+    /// Gets the bundled mapping
+    ///
+    /// # Arguments
+    /// - `request`: the request to send
+    async fn get_mapping(
+        &self,
+        request: GetMappingRequest,
+    ) -> Result<GetMappingResponse, MappingAdapterError> {
+        self.inner.get_mapping(request).await
+    }
+}
+
+#[cfg(test)]
+mod demo_mapping_adapter_tests {
+    use super::*;
+
+    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+
+    #[tokio::test]
+    async fn get_mapping_returns_bundled_mapping() {
+        let uut = DemoMappingAdapter::create_new(Arc::new(Mutex::new(
+            MockServiceDiscoveryAdapterSelector::new(),
+        )))
+        .unwrap();
+
+        let has_work = uut
+            .check_for_work(CheckForWorkRequest {})
+            .await
+            .unwrap()
+            .has_work;
+        assert!(has_work);
+
+        let map = uut.get_mapping(GetMappingRequest {}).await.unwrap().map;
+        assert_eq!(map.len(), 4);
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+mod demo_data_adapter_factory;
+mod demo_digital_twin_adapter;
+mod demo_mapping_adapter;
+mod stdout_cloud_adapter;
+
+use env_logger::Target;
+use log::LevelFilter;
+
+use demo_data_adapter_factory::DemoDataAdapterFactory;
+use demo_digital_twin_adapter::DemoDigitalTwinAdapter;
+use demo_mapping_adapter::DemoMappingAdapter;
+use freyja_common::{
+    cloud_adapter::{self, CloudAdapterConstructor},
+    cmd_utils::get_log_level,
+    data_adapter::DataAdapterFactory,
+    mapping_adapter::{self, MappingAdapterConstructor},
+};
+use stdout_cloud_adapter::StdoutCloudAdapter;
+
+// Runs Freyja with in-memory adapters which serve a bundled mapping and synthetic waveforms,
+// so that it can be tried out without any external services.
+// Emissions are printed to stdout, so logs are written to stderr and only warnings are shown unless --log-level is passed.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let args = freyja::FREYJA_COMMAND.parse_env_or_exit();
+    let log_level = get_log_level(&args, LevelFilter::Warn).expect("Could not parse log level");
+    env_logger::Builder::new()
+        .filter(None, log_level)
+        .target(Target::Stderr)
+        .init();
+
+    let cloud_adapter_constructors: Vec<CloudAdapterConstructor> =
+        vec![cloud_adapter::create_boxed::<StdoutCloudAdapter>];
+
+    let mapping_adapter_constructors: Vec<MappingAdapterConstructor> =
+        vec![mapping_adapter::create_boxed::<DemoMappingAdapter>];
+
+    let data_adapter_factories: Vec<Box<dyn DataAdapterFactory + Send + Sync>> = vec![Box::new(
+        DemoDataAdapterFactory::create_new().expect("Could not create DemoDataAdapterFactory"),
+    )];
+
+    freyja::freyja_main::<DemoDigitalTwinAdapter>(
+        args,
+        cloud_adapter_constructors,
+        mapping_adapter_constructors,
+        data_adapter_factories,
+        Vec::new(),
+    )
+    .await
+}
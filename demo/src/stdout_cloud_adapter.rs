@@ -0,0 +1,124 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use time::format_description::well_known::Rfc3339;
+use tokio::sync::Mutex;
+
+use freyja_common::{
+    cloud_adapter::{CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse},
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A cloud adapter which prints each emission to stdout on a single line
+pub struct StdoutCloudAdapter {}
+
+impl StdoutCloudAdapter {
+    /// Formats an emission as a line with its timestamp, its metadata sorted by key, and its value
+    ///
+    /// # Arguments
+    /// - `cloud_message`: the emission to format
+    fn format(cloud_message: &CloudMessageRequest) -> String {
+        let timestamp = cloud_message
+            .signal_timestamp
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| cloud_message.signal_timestamp.to_string());
+
+        let mut metadata: Vec<_> = cloud_message.metadata.iter().collect();
+        metadata.sort();
+
+        let metadata = metadata
+            .into_iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(
+            "{timestamp} {metadata} value={}",
+            cloud_message.signal_value
+        )
+    }
+}
+
+#[async_trait]
+impl CloudAdapter for StdoutCloudAdapter {
+    /// Creates a new instance of a CloudAdapter with default settings
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector to use (unused by this adapter)
+    fn create_new(
+        _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self {})
+    }
+
+    /// Creates a new instance of a CloudAdapter for a tenant.
+    /// Every tenant's emissions are printed in the same way.
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector to use (unused by this adapter)
+    /// - `_tenant`: the id of the tenant (unused by this adapter)
+    fn create_for_tenant(
+        _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        _tenant: &str,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self {})
+    }
+
+    /// Prints the signal to stdout
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        println!("{}", Self::format(&cloud_message));
+
+        Ok(CloudMessageResponse::default())
+    }
+}
+
+#[cfg(test)]
+mod stdout_cloud_adapter_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use time::OffsetDateTime;
+
+    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+
+    #[test]
+    fn can_get_new() {
+        let result = StdoutCloudAdapter::create_new(Arc::new(Mutex::new(
+            MockServiceDiscoveryAdapterSelector::new(),
+        )));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn format_sorts_metadata_by_key() {
+        let cloud_message = CloudMessageRequest {
+            metadata: HashMap::from([
+                (
+                    String::from("model_id"),
+                    String::from("dtmi:sdv:Vehicle:Speed;1"),
+                ),
+                (String::from("instance_id"), String::from("vehicle")),
+            ]),
+            signal_value: "42.0 km/h".into(),
+            numeric_value: None,
+            signal_timestamp: OffsetDateTime::UNIX_EPOCH,
+            idempotency_key: None,
+        };
+
+        assert_eq!(
+            StdoutCloudAdapter::format(&cloud_message),
+            "1970-01-01T00:00:00Z instance_id=vehicle model_id=dtmi:sdv:Vehicle:Speed;1 value=42.0 km/h"
+        );
+    }
+}
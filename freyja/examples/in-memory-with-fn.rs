@@ -4,7 +4,10 @@
 
 use file_service_discovery_adapter::file_service_discovery_adapter::FileServiceDiscoveryAdapter;
 use freyja_common::{
-    data_adapter::DataAdapterFactory, service_discovery_adapter::ServiceDiscoveryAdapter,
+    cloud_adapter::{self, CloudAdapterConstructor},
+    data_adapter::DataAdapterFactory,
+    mapping_adapter::{self, MappingAdapterConstructor},
+    service_discovery_adapter::ServiceDiscoveryAdapter,
 };
 use in_memory_mock_cloud_adapter::in_memory_mock_cloud_adapter::InMemoryMockCloudAdapter;
 use in_memory_mock_data_adapter::in_memory_mock_data_adapter_factory::InMemoryMockDataAdapterFactory;
@@ -19,6 +22,14 @@ use sample_grpc_data_adapter::sample_grpc_data_adapter_factory::SampleGRPCDataAd
 // The following code is functionally equivalent to the expanded macro.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let args = freyja::FREYJA_COMMAND.parse_env_or_exit();
+
+    let cloud_adapter_constructors: Vec<CloudAdapterConstructor> =
+        vec![cloud_adapter::create_boxed::<InMemoryMockCloudAdapter>];
+
+    let mapping_adapter_constructors: Vec<MappingAdapterConstructor> =
+        vec![mapping_adapter::create_boxed::<InMemoryMockMappingAdapter>];
+
     let data_adapter_factories: Vec<Box<dyn DataAdapterFactory + Send + Sync>> = vec![
         Box::new(
             SampleGRPCDataAdapterFactory::create_new()
@@ -43,10 +54,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .expect("Could not create FileServiceDiscoveryAdapter"),
         )];
 
-    freyja::freyja_main::<
-        InMemoryMockDigitalTwinAdapter,
-        InMemoryMockCloudAdapter,
-        InMemoryMockMappingAdapter,
-    >(data_adapter_factories, service_discovery_adapters)
+    freyja::freyja_main::<InMemoryMockDigitalTwinAdapter>(
+        args,
+        cloud_adapter_constructors,
+        mapping_adapter_constructors,
+        data_adapter_factories,
+        service_discovery_adapters,
+    )
     .await
 }
@@ -0,0 +1,64 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{future::Future, time::Duration};
+
+use tokio::time::error::Elapsed;
+
+/// Awaits a call to an adapter, failing if the call doesn't finish within the timeout.
+/// This keeps an adapter which never responds from stalling the component that called it.
+///
+/// # Arguments
+/// - `timeout_ms`: the maximum time to wait for the call, or `None` to wait indefinitely
+/// - `call`: the adapter call
+/// - `timed_out`: creates the error which is returned if the call times out
+pub(crate) async fn with_timeout<T, E, Fut>(
+    timeout_ms: Option<u64>,
+    call: Fut,
+    timed_out: impl FnOnce(Elapsed) -> E,
+) -> Result<T, E>
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    match timeout_ms {
+        Some(timeout_ms) => tokio::time::timeout(Duration::from_millis(timeout_ms), call)
+            .await
+            .map_err(timed_out)?,
+        None => call.await,
+    }
+}
+
+#[cfg(test)]
+mod adapter_timeout_tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn with_timeout_returns_result_of_call_that_finishes() {
+        let result: Result<u32, String> =
+            with_timeout(Some(1000), async { Ok(42) }, |e| e.to_string()).await;
+
+        assert_eq!(result, Ok(42));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn with_timeout_returns_error_for_call_that_hangs() {
+        let result: Result<u32, String> = with_timeout(Some(1000), std::future::pending(), |_| {
+            String::from("timed out")
+        })
+        .await;
+
+        assert_eq!(result, Err(String::from("timed out")));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn with_timeout_waits_indefinitely_without_timeout() {
+        let call = async {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            Ok(42)
+        };
+        let result: Result<u32, String> = with_timeout(None, call, |e| e.to_string()).await;
+
+        assert_eq!(result, Ok(42));
+    }
+}
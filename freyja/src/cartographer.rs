@@ -2,30 +2,108 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::{sync::Mutex, task::JoinHandle, time::Instant};
+use tokio_util::sync::CancellationToken;
 
 use log::{debug, info, warn};
+use serde::{Serialize, Serializer};
 
 use freyja_common::signal_store::SignalStore;
 use freyja_common::{
-    conversion::Conversion,
     data_adapter_selector::DataAdapterSelector,
     digital_twin_adapter::{
         DigitalTwinAdapter, DigitalTwinAdapterError, DigitalTwinAdapterErrorKind, FindByIdRequest,
     },
-    mapping_adapter::{CheckForWorkRequest, GetMappingRequest, MappingAdapter},
+    digital_twin_map_entry::DigitalTwinMapEntry,
+    entity::Operation,
+    mapping_adapter::{
+        CheckForWorkRequest, GetMappingRequest, GetMappingResponse, MappingAdapter,
+        MappingAdapterError,
+    },
+    namespace,
     signal::{EmissionPolicy, SignalPatch, Target},
 };
 
+use crate::{
+    adapter_timeout::with_timeout,
+    config::{AdapterTimeouts, MappingMergePolicy},
+    crash_report,
+    freyja_handle::FreyjaComponent,
+    mapping_diff::{MappingChangeLog, MappingDiff},
+    watchdog::Heartbeat,
+};
+
+/// The maximum amount of time to wait before retrying resolution of a failed signal
+const MAX_RESOLUTION_BACKOFF: Duration = Duration::from_secs(300);
+
+/// The resolution state of each signal in the current mapping, keyed by signal id
+pub(crate) type ResolutionStates = RwLock<HashMap<String, ResolutionState>>;
+
+/// The resolution state of a signal's source entity
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ResolutionState {
+    /// The signal's source has not been resolved yet
+    Pending,
+
+    /// The signal's source has been resolved and the signal is being tracked
+    Resolved,
+
+    /// Resolving the signal's source failed. Resolution will be retried after a backoff period
+    Failed {
+        /// The reason for the most recent failure
+        reason: ResolutionFailure,
+
+        /// The number of consecutive failed attempts
+        attempts: u32,
+
+        /// The earliest time at which resolution will be attempted again.
+        /// This is serialized as the number of milliseconds until then
+        #[serde(rename = "retry_in_ms", serialize_with = "serialize_retry_in_ms")]
+        next_attempt: Instant,
+    },
+}
+
+/// Serializes the time of the next resolution attempt as the number of milliseconds until then
+///
+/// # Arguments
+/// - `next_attempt`: the time of the next attempt
+/// - `serializer`: the serializer
+fn serialize_retry_in_ms<S: Serializer>(
+    next_attempt: &Instant,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let retry_in = next_attempt.saturating_duration_since(Instant::now());
+    serializer.serialize_u64(retry_in.as_millis().try_into().unwrap_or(u64::MAX))
+}
+
+/// Reasons that resolution of a signal's source can fail
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum ResolutionFailure {
+    /// The digital twin service does not know about the entity
+    EntityNotFound,
+
+    /// The digital twin adapter returned an error
+    DigitalTwinError(String),
+
+    /// The data adapter selector could not create or update an adapter for the entity
+    DataAdapterError(String),
+}
+
 /// Manages mappings from the mapping service
-pub struct Cartographer<TMappingAdapter, TDigitalTwinAdapter, TDataAdapterSelector> {
+pub struct Cartographer<TDigitalTwinAdapter, TDataAdapterSelector> {
     /// The shared signal store
     signals: Arc<SignalStore>,
 
-    /// The mapping adapter
-    mapping_adapter: TMappingAdapter,
+    /// The mapping adapters, in the order that they were provided
+    mapping_adapters: Vec<Box<dyn MappingAdapter + Send + Sync>>,
+
+    /// The policy for merging mappings from multiple mapping adapters
+    merge_policy: MappingMergePolicy,
 
     /// The digital twin adapter
     digital_twin_adapter: TDigitalTwinAdapter,
@@ -35,87 +113,168 @@ pub struct Cartographer<TMappingAdapter, TDigitalTwinAdapter, TDataAdapterSelect
 
     /// The mapping service polling interval
     poll_interval: Duration,
+
+    /// The timeouts for calls to the mapping and digital twin adapters
+    timeouts: AdapterTimeouts,
+
+    /// The heartbeat which tracks the progress of the cartographer loop
+    heartbeat: Arc<Heartbeat>,
+
+    /// The resolution state of each signal in the current mapping, which the management API exposes
+    resolution_states: Arc<ResolutionStates>,
+
+    /// The log of changes to the mapping, which the management API exposes
+    mapping_changes: Arc<MappingChangeLog>,
 }
 
-impl<
-        TMappingAdapter: MappingAdapter,
-        TDigitalTwinAdapter: DigitalTwinAdapter,
-        TDataAdapterSelector: DataAdapterSelector,
-    > Cartographer<TMappingAdapter, TDigitalTwinAdapter, TDataAdapterSelector>
+impl<TDigitalTwinAdapter: DigitalTwinAdapter, TDataAdapterSelector: DataAdapterSelector>
+    Cartographer<TDigitalTwinAdapter, TDataAdapterSelector>
 {
     /// Create a new instance of a Cartographer
     ///
     /// # Arguments
     /// - `signals`: the shared signal store
-    /// - `mapping_adapter`: the adapter for the mapping service
+    /// - `mapping_adapters`: the adapters for the mapping services, in order
+    /// - `merge_policy`: the policy for merging mappings from multiple mapping adapters
     /// - `digital_twin_adapter`: the adapter for the digital twin service
     /// - `data_adapter_selector`: the data adapter selector
     /// - `poll_interval`: the interval at which the cartographer should poll for changes
+    /// - `timeouts`: the timeouts for calls to the mapping and digital twin adapters
+    /// - `mapping_changes`: the log to record changes to the mapping in
+    /// - `resolution_states`: the resolution states to track the signals in the current mapping in
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         signals: Arc<SignalStore>,
-        mapping_adapter: TMappingAdapter,
+        mapping_adapters: Vec<Box<dyn MappingAdapter + Send + Sync>>,
+        merge_policy: MappingMergePolicy,
         digital_twin_adapter: TDigitalTwinAdapter,
         data_adapter_selector: Arc<Mutex<TDataAdapterSelector>>,
         poll_interval: Duration,
+        timeouts: AdapterTimeouts,
+        mapping_changes: Arc<MappingChangeLog>,
+        resolution_states: Arc<ResolutionStates>,
     ) -> Self {
         Self {
             signals,
-            mapping_adapter,
+            mapping_adapters,
+            merge_policy,
             digital_twin_adapter,
             data_adapter_selector,
             poll_interval,
+            timeouts,
+            heartbeat: Arc::new(Heartbeat::default()),
+            resolution_states,
+            mapping_changes,
         }
     }
 
+    /// Gets the heartbeat which tracks the progress of the cartographer loop
+    pub fn heartbeat(&self) -> Arc<Heartbeat> {
+        self.heartbeat.clone()
+    }
+
     /// Run the cartographer. This will do the following in a loop:
     ///
-    /// 1. Check to see if the mapping service has more work
+    /// 1. Check to see if any of the mapping services have more work
     ///     - If there is work, do the following:
     ///         1. Clear the list of previously failed signals
     ///         1. ~~Send the new inventory to the mapping service~~
-    ///         1. Get the new mapping from the mapping service
+    ///         1. Get the new mappings from the mapping services and merge them according to the merge policy
+    ///         1. Select the mapping entries whose validity window contains the current time
     ///         1. Query the digital twin service for entity information
     ///         1. Create or update data adapters for the new entities
     ///         1. Update the signal store with the new data and track any failed signals for future iterations
+    ///     - If there is no work but mapping entries have been activated or retired since the last update,
+    ///         execute the steps above starting from step 4 with the current mapping
     ///     - If there is no work but previous attempts to start up adapters failed,
-    ///         execute the steps above starting from step 4 for these failed cases
+    ///         execute the steps above starting from step 5 for the failed cases whose backoff has expired
     ///     - If the check failed, log the error
     /// 1. Sleep until the next iteration
-    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ///
+    /// If getting the mapping fails, the work is considered to still be pending and will be retried in the next iteration.
+    ///
+    /// Validity windows are checked once per iteration, so signals are activated and retired within one poll interval
+    /// of their scheduled times.
+    ///
+    /// The resolution state of each signal is tracked individually.
+    /// Signals that fail to resolve are retried with an exponential backoff based on the poll interval.
+    ///
+    /// The cartographer runs in a new task and stops when the cancellation token is cancelled.
+    /// Cancellation is only observed between iterations, so the signal store is never left partially updated.
+    ///
+    /// # Arguments
+    /// - `cancellation_token`: the token for stopping the cartographer
+    pub fn run(
+        self: Arc<Self>,
+        cancellation_token: CancellationToken,
+    ) -> JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>
+    where
+        TDigitalTwinAdapter: Send + Sync + 'static,
+        TDataAdapterSelector: Send + Sync + 'static,
+    {
+        tokio::spawn(crash_report::in_component(
+            FreyjaComponent::Cartographer,
+            async move { self.run_until_cancelled(cancellation_token).await },
+        ))
+    }
+
+    /// Runs the cartographer loop described in `run` until the cancellation token is cancelled
+    ///
+    /// # Arguments
+    /// - `cancellation_token`: the token for stopping the cartographer
+    async fn run_until_cancelled(
+        &self,
+        cancellation_token: CancellationToken,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut mapping: HashMap<String, DigitalTwinMapEntry> = HashMap::new();
+        let mut active_ids: HashSet<String> = HashSet::new();
         let mut failed_signals: Vec<SignalPatch> = Vec::new();
+        let mut pending_work = false;
         loop {
+            self.heartbeat.beat();
             let mut successes = Vec::new();
 
-            // Check for new work from the mapping service
-            match self
-                .mapping_adapter
-                .check_for_work(CheckForWorkRequest {})
-                .await
-            {
-                Ok(r) if r.has_work => {
+            // Check for new work from the mapping services
+            match self.check_for_work().await.map(|w| w || pending_work) {
+                Ok(true) => {
                     info!("Cartographer detected mapping work");
 
-                    match self.get_mapping_as_signal_patches().await {
-                        Ok(p) => {
-                            // We clear the failed signals here because the incoming mapping is used as the source of truth,
-                            // so anything left over from previous mappings shouldn't get used.
-                            failed_signals.clear();
-                            self.process_signal_patches(&p, &mut successes, &mut failed_signals)
-                                .await;
-                            self.signals.sync(successes.into_iter());
+                    match self.get_merged_mapping().await {
+                        Ok(m) => {
+                            pending_work = false;
+                            self.record_mapping_changes(&mapping, &m);
+                            mapping = m;
+                            active_ids = self.apply_mapping(&mapping, &mut failed_signals).await;
+                        }
+                        Err(e) => {
+                            pending_work = true;
+                            log::error!("Failed to get mapping from mapping adapters: {e}");
                         }
-                        Err(e) => log::error!("Failed to get mapping from mapping adapter: {e}"),
                     }
                 }
+                Ok(_) if self.is_schedule_changed(&mapping, &active_ids) => {
+                    info!("Mapping entries were activated or retired on schedule");
+
+                    active_ids = self.apply_mapping(&mapping, &mut failed_signals).await;
+                }
                 Ok(_) if !failed_signals.is_empty() => {
                     info!("No new mappings found, but some mappings failed to be created in previous iterations");
 
-                    // Retry previously failed signals
-                    let mut failures = Vec::new();
-                    self.process_signal_patches(&failed_signals, &mut successes, &mut failures)
-                        .await;
+                    // Retry previously failed signals whose backoff has expired
+                    let now = Instant::now();
+                    let (due, mut failures): (Vec<_>, Vec<_>) = failed_signals
+                        .into_iter()
+                        .partition(|p| self.is_retry_due(&p.id, now));
+
+                    if !due.is_empty() {
+                        self.process_signal_patches(&due, &mut successes, &mut failures)
+                            .await;
+                        let new_signals = self.get_new_signals(&successes);
+                        self.signals.add(successes.into_iter());
+                        self.request_initial_values(&new_signals).await;
+                        self.log_resolution_summary();
+                    }
 
-                    self.signals.add(successes.into_iter());
                     failed_signals = failures;
                 }
                 Ok(_) => debug!("No work for cartographer"),
@@ -124,10 +283,85 @@ impl<
                 ),
             }
 
-            tokio::time::sleep(self.poll_interval).await;
+            self.heartbeat.idle();
+            tokio::select! {
+                biased;
+                _ = cancellation_token.cancelled() => {
+                    info!("Cartographer stopped");
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(self.poll_interval) => {}
+            }
         }
     }
 
+    /// Checks each of the mapping adapters for work.
+    /// Returns `true` if any adapter has work.
+    /// All adapters are checked so that any pending work is consumed together.
+    /// Returns an error only if every adapter fails the check.
+    async fn check_for_work(&self) -> Result<bool, MappingAdapterError> {
+        let mut has_work = false;
+        let mut last_error = None;
+        let mut any_succeeded = false;
+
+        for (i, adapter) in self.mapping_adapters.iter().enumerate() {
+            let result = with_timeout(
+                self.timeouts.mapping_ms,
+                adapter.check_for_work(CheckForWorkRequest {}),
+                MappingAdapterError::communication,
+            )
+            .await;
+
+            match result {
+                Ok(r) => {
+                    any_succeeded = true;
+                    has_work |= r.has_work;
+                }
+                Err(e) => {
+                    warn!("Failed to check for work with mapping adapter {i}: {e}");
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if !any_succeeded => Err(e),
+            _ => Ok(has_work),
+        }
+    }
+
+    /// Gets the mappings from the mapping adapters and merges them according to the merge policy
+    async fn get_merged_mapping(
+        &self,
+    ) -> Result<HashMap<String, DigitalTwinMapEntry>, MappingAdapterError> {
+        merge_mappings(
+            &self.mapping_adapters,
+            self.merge_policy,
+            self.timeouts.mapping_ms,
+        )
+        .await
+    }
+
+    /// Logs the differences between the previous and new mappings and records them in the mapping change log
+    ///
+    /// # Arguments
+    /// - `previous`: the previous mapping
+    /// - `current`: the new mapping
+    fn record_mapping_changes(
+        &self,
+        previous: &HashMap<String, DigitalTwinMapEntry>,
+        current: &HashMap<String, DigitalTwinMapEntry>,
+    ) {
+        let diff = MappingDiff::between(previous, current, self.signals.clock().now());
+        if diff.is_empty() {
+            debug!("The new mapping is the same as the previous mapping");
+            return;
+        }
+
+        diff.log();
+        self.mapping_changes.record(diff);
+    }
+
     /// Processes a list of signal patches by calling `populate_source` for each one.
     /// The signals for which this call succeeds are pushed into `successes`, while others are put into `failures`.
     ///
@@ -145,20 +379,36 @@ impl<
             // Many of the API calls in populate_entity are probably unnecessary, but this code gets executed
             // infrequently enough that the sub-optimal performance is not a major concern.
             // A bulk find_by_id API in the digital twin service would make this a non-issue
+            let _signal_scope = crash_report::enter_signal(&patch.id);
             let mut patch = patch.clone();
             match self.populate_source(&mut patch).await {
-                Ok(_) => successes.push(patch),
+                Ok(_) => {
+                    self.set_resolution_state(&patch.id, ResolutionState::Resolved);
+                    successes.push(patch);
+                }
                 Err(e) => {
-                    match e.downcast::<DigitalTwinAdapterError>() {
+                    let reason = match e.downcast::<DigitalTwinAdapterError>() {
                         Ok(e) if e.kind() == DigitalTwinAdapterErrorKind::EntityNotFound => {
-                            warn!("Entity not found for signal {}", patch.id);
-                        }
-                        Ok(e) => {
-                            log::error!("Error fetching entity for signal {}: {e:?}", patch.id);
-                        }
-                        Err(e) => {
-                            log::error!("Error fetching entity for signal {}: {e:?}", patch.id);
+                            ResolutionFailure::EntityNotFound
                         }
+                        Ok(e) => ResolutionFailure::DigitalTwinError(format!("{e:?}")),
+                        Err(e) => ResolutionFailure::DataAdapterError(format!("{e:?}")),
+                    };
+
+                    let attempts = self.record_resolution_failure(&patch.id, reason.clone());
+                    match reason {
+                        ResolutionFailure::EntityNotFound => warn!(
+                            "Entity not found for signal {} (attempt {attempts})",
+                            patch.id
+                        ),
+                        ResolutionFailure::DigitalTwinError(e) => log::error!(
+                            "Error fetching entity for signal {} (attempt {attempts}): {e}",
+                            patch.id
+                        ),
+                        ResolutionFailure::DataAdapterError(e) => log::error!(
+                            "Error creating data adapter for signal {} (attempt {attempts}): {e}",
+                            patch.id
+                        ),
                     }
 
                     failures.push(patch);
@@ -167,34 +417,209 @@ impl<
         }
     }
 
-    /// Gets the mapping from the mapping adapter and returns a corresponding list of signal patches.
-    async fn get_mapping_as_signal_patches(
+    /// Replaces the tracked resolution states with a `Pending` state for each of the provided signals.
+    ///
+    /// # Arguments
+    /// - `patches`: the signals in the current mapping
+    fn reset_resolution_states(&self, patches: &[SignalPatch]) {
+        let mut states = self.resolution_states.write().unwrap();
+        *states = patches
+            .iter()
+            .map(|p| (p.id.clone(), ResolutionState::Pending))
+            .collect();
+    }
+
+    /// Sets the resolution state for a signal.
+    ///
+    /// # Arguments
+    /// - `id`: the signal id
+    /// - `state`: the new state
+    fn set_resolution_state(&self, id: &str, state: ResolutionState) {
+        let mut states = self.resolution_states.write().unwrap();
+        states.insert(id.to_string(), state);
+    }
+
+    /// Records a failure to resolve a signal and schedules the next attempt using exponential backoff.
+    /// Returns the number of consecutive failed attempts for this signal.
+    ///
+    /// # Arguments
+    /// - `id`: the signal id
+    /// - `reason`: the reason for the failure
+    fn record_resolution_failure(&self, id: &str, reason: ResolutionFailure) -> u32 {
+        let mut states = self.resolution_states.write().unwrap();
+        let attempts = match states.get(id) {
+            Some(ResolutionState::Failed { attempts, .. }) => attempts.saturating_add(1),
+            _ => 1,
+        };
+
+        let backoff = self
+            .poll_interval
+            .saturating_mul(2u32.saturating_pow(attempts - 1))
+            .min(MAX_RESOLUTION_BACKOFF);
+
+        states.insert(
+            id.to_string(),
+            ResolutionState::Failed {
+                reason,
+                attempts,
+                next_attempt: Instant::now() + backoff,
+            },
+        );
+
+        attempts
+    }
+
+    /// Checks whether a failed signal is due to be retried.
+    ///
+    /// # Arguments
+    /// - `id`: the signal id
+    /// - `now`: the current time
+    fn is_retry_due(&self, id: &str, now: Instant) -> bool {
+        let states = self.resolution_states.read().unwrap();
+        match states.get(id) {
+            Some(ResolutionState::Failed { next_attempt, .. }) => *next_attempt <= now,
+            _ => true,
+        }
+    }
+
+    /// Logs a summary of the resolution states of the signals in the current mapping.
+    fn log_resolution_summary(&self) {
+        let states = self.resolution_states.read().unwrap();
+        let (mut pending, mut resolved, mut failed) = (0, 0, 0);
+        for (id, state) in states.iter() {
+            match state {
+                ResolutionState::Pending => pending += 1,
+                ResolutionState::Resolved => resolved += 1,
+                ResolutionState::Failed {
+                    reason, attempts, ..
+                } => {
+                    failed += 1;
+                    debug!("Signal {id} has failed to resolve {attempts} time(s): {reason:?}");
+                }
+            }
+        }
+
+        info!("Signal resolution: {resolved} resolved, {pending} pending, {failed} failed");
+    }
+
+    /// Updates the signal store with the entries of the mapping which are currently active.
+    /// Returns the ids of the active signals.
+    ///
+    /// # Arguments
+    /// - `mapping`: the merged mapping
+    /// - `failed_signals`: the list to replace with signals which failed to resolve
+    async fn apply_mapping(
         &self,
-    ) -> Result<Vec<SignalPatch>, Box<dyn std::error::Error + Send + Sync>> {
-        Ok(self
-            .mapping_adapter
-            .get_mapping(GetMappingRequest {})
-            .await?
-            .map
-            .into_iter()
+        mapping: &HashMap<String, DigitalTwinMapEntry>,
+        failed_signals: &mut Vec<SignalPatch>,
+    ) -> HashSet<String> {
+        let patches = self.get_active_signal_patches(mapping);
+        let mut successes = Vec::new();
+
+        // We clear the failed signals here because the incoming mapping is used as the source of truth,
+        // so anything left over from previous mappings shouldn't get used.
+        failed_signals.clear();
+        self.reset_resolution_states(&patches);
+        self.process_signal_patches(&patches, &mut successes, failed_signals)
+            .await;
+        let new_signals = self.get_new_signals(&successes);
+        self.signals.sync(successes.into_iter());
+        self.request_initial_values(&new_signals).await;
+        self.log_resolution_summary();
+
+        patches.into_iter().map(|p| p.id).collect()
+    }
+
+    /// Returns true if any entry of the mapping has been activated or retired since the active signals were last updated
+    ///
+    /// # Arguments
+    /// - `mapping`: the merged mapping
+    /// - `active_ids`: the ids of the signals which were active at the last update
+    fn is_schedule_changed(
+        &self,
+        mapping: &HashMap<String, DigitalTwinMapEntry>,
+        active_ids: &HashSet<String>,
+    ) -> bool {
+        let now = self.signals.clock().now();
+        mapping
+            .iter()
+            .any(|(id, entry)| entry.is_active_at(now) != active_ids.contains(id))
+    }
+
+    /// Gets the signal patches for the entries of the mapping whose validity window contains the current time,
+    /// in order of their ids so that signals are always resolved and logged in the same order
+    ///
+    /// # Arguments
+    /// - `mapping`: the merged mapping
+    fn get_active_signal_patches(
+        &self,
+        mapping: &HashMap<String, DigitalTwinMapEntry>,
+    ) -> Vec<SignalPatch> {
+        let now = self.signals.clock().now();
+        let mut patches: Vec<SignalPatch> = mapping
+            .iter()
+            .filter(|(_, entry)| entry.is_active_at(now))
             .map(|(id, entry)| SignalPatch {
-                id,
+                id: id.clone(),
                 // this gets populated later, set to default for now
                 source: Default::default(),
                 target: Target {
-                    metadata: entry.target,
+                    metadata: entry.target.clone(),
+                    tenant: entry.tenant.clone(),
                 },
                 emission_policy: EmissionPolicy {
                     interval_ms: entry.interval_ms,
                     emit_only_if_changed: entry.emit_on_change,
-                    conversion: Conversion::default(),
+                    conversion: entry.conversion.clone(),
+                    conversion_failure_policy: entry.conversion_failure_policy,
+                    decimal_places: entry.decimal_places,
+                    trigger: entry.trigger.clone(),
+                    adaptive_interval: entry.adaptive_interval,
+                    privacy: entry.privacy.clone(),
                 },
             })
-            .collect())
+            .collect();
+
+        patches.sort_by(|a, b| a.id.cmp(&b.id));
+        patches
+    }
+
+    /// Gets the patches for signals which are not yet in the signal store
+    ///
+    /// # Arguments
+    /// - `patches`: the signal patches to check
+    fn get_new_signals(&self, patches: &[SignalPatch]) -> Vec<SignalPatch> {
+        patches
+            .iter()
+            .filter(|patch| self.signals.get(&patch.id).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Requests values for newly added signals which support the `Get` operation.
+    /// This allows these signals to be emitted without waiting for the emitter to request their first value.
+    ///
+    /// # Arguments
+    /// - `patches`: the newly added signals
+    async fn request_initial_values(&self, patches: &[SignalPatch]) {
+        let data_adapter_selector = self.data_adapter_selector.lock().await;
+        for patch in patches
+            .iter()
+            .filter(|patch| patch.source.supports_operation(&Operation::Get))
+        {
+            if let Err(e) = data_adapter_selector.request_entity_value(&patch.id).await {
+                warn!(
+                    "Failed to request initial value for signal {}: {e}",
+                    patch.id
+                );
+            }
+        }
     }
 
     /// Populates the source of the provided signal with data retrieved from the digital twin service.
     /// This will also create or update a data adapter to handle incoming requests from the provider.
+    /// The digital twin service is queried with the unqualified entity id,
+    /// and the source id is qualified with the signal's namespace.
     ///
     /// Arguments
     /// - `signal`: The signal patch to update
@@ -202,18 +627,24 @@ impl<
         &self,
         signal: &mut SignalPatch,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        signal.source = self
-            .digital_twin_adapter
-            .find_by_id(FindByIdRequest {
-                entity_id: signal.id.clone(),
-            })
-            .await?
-            .entity;
+        let (namespace, entity_id) = namespace::split(&signal.id);
+        let mut source = with_timeout(
+            self.timeouts.digital_twin_ms,
+            self.digital_twin_adapter.find_by_id(FindByIdRequest {
+                entity_id: entity_id.to_owned(),
+            }),
+            DigitalTwinAdapterError::communication,
+        )
+        .await?
+        .entity;
+
+        source.id = namespace::qualify(namespace, &source.id);
+        signal.source = source;
 
         {
             let data_adapter_selector = self.data_adapter_selector.lock().await;
             data_adapter_selector
-                .create_or_update_adapter(&signal.source)
+                .create_or_update_adapter(&signal.source, &signal.emission_policy)
                 .await
                 .map_err(|e| format!("Error sending request to data adapter selector: {e:?}"))?;
         }
@@ -222,32 +653,123 @@ impl<
     }
 }
 
+/// Gets the mapping from a mapping adapter.
+/// The entries are keyed by their qualified ids, so entries with the same id in different namespaces stay distinct.
+///
+/// # Arguments
+/// - `adapter`: the mapping adapter
+/// - `timeout_ms`: the timeout for the call to the mapping adapter
+async fn get_mapping(
+    adapter: &(dyn MappingAdapter + Send + Sync),
+    timeout_ms: Option<u64>,
+) -> Result<GetMappingResponse, MappingAdapterError> {
+    let mut response = with_timeout(
+        timeout_ms,
+        adapter.get_mapping(GetMappingRequest {}),
+        MappingAdapterError::communication,
+    )
+    .await?;
+
+    response.map = response
+        .map
+        .into_iter()
+        .map(|(id, entry)| (namespace::qualify(entry.namespace.as_deref(), &id), entry))
+        .collect();
+
+    Ok(response)
+}
+
+/// Gets the mappings from the mapping adapters and merges them according to the merge policy.
+///
+/// With the `LastWins` and `FirstWins` policies, a failure from any adapter fails the whole operation
+/// to avoid removing signals that are provided by the failing adapter.
+/// With the `FirstAvailable` policy, adapters are tried in order until one succeeds.
+///
+/// # Arguments
+/// - `mapping_adapters`: the mapping adapters, in order
+/// - `merge_policy`: the policy for merging the mappings
+/// - `timeout_ms`: the timeout for each call to a mapping adapter
+pub(crate) async fn merge_mappings(
+    mapping_adapters: &[Box<dyn MappingAdapter + Send + Sync>],
+    merge_policy: MappingMergePolicy,
+    timeout_ms: Option<u64>,
+) -> Result<HashMap<String, DigitalTwinMapEntry>, MappingAdapterError> {
+    match merge_policy {
+        MappingMergePolicy::FirstAvailable => {
+            let mut last_error = None;
+            for (i, adapter) in mapping_adapters.iter().enumerate() {
+                match get_mapping(adapter.as_ref(), timeout_ms).await {
+                    Ok(r) => return Ok(r.map),
+                    Err(e) => {
+                        warn!("Failed to get mapping from mapping adapter {i}: {e}");
+                        last_error = Some(e);
+                    }
+                }
+            }
+
+            Err(last_error.unwrap_or_else(|| {
+                MappingAdapterError::unknown("No mapping adapters are registered")
+            }))
+        }
+        policy => {
+            let mut result = HashMap::new();
+            for adapter in mapping_adapters.iter() {
+                let map = get_mapping(adapter.as_ref(), timeout_ms).await?.map;
+                for (id, entry) in map.into_iter() {
+                    if policy == MappingMergePolicy::FirstWins {
+                        result.entry(id).or_insert(entry);
+                    } else {
+                        result.insert(id, entry);
+                    }
+                }
+            }
+
+            Ok(result)
+        }
+    }
+}
+
 #[cfg(test)]
 mod cartographer_tests {
     use super::*;
 
     use std::collections::HashMap;
 
+    use async_trait::async_trait;
+
     use freyja_common::{
+        clock::{Clock, ManualClock},
+        conversion::{Conversion, ConversionFailurePolicy},
         digital_twin_adapter::FindByIdResponse,
         digital_twin_map_entry::DigitalTwinMapEntry,
         entity::{Entity, EntityEndpoint},
-        mapping_adapter::GetMappingResponse,
+        mapping_adapter::{CheckForWorkResponse, GetMappingResponse, MappingAdapterErrorKind},
+        service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
     };
     use freyja_test_common::{
-        mockall::predicate::eq,
+        mockall::predicate::{always, eq},
         mocks::{MockDataAdapterSelector, MockDigitalTwinAdapter, MockMappingAdapter},
     };
 
     #[tokio::test]
-    async fn get_mapping_as_signals_returns_correct_value() {
+    async fn get_active_signal_patches_returns_correct_value() {
         const ID: &str = "testid";
         let test_map_entry = DigitalTwinMapEntry {
             source: ID.to_string(),
             target: HashMap::new(),
             interval_ms: 42,
-            conversion: Default::default(),
+            conversion: Conversion::c_to_f(),
             emit_on_change: true,
+            conversion_failure_policy: ConversionFailurePolicy::SkipAndWarn,
+            decimal_places: Some(2),
+            namespace: None,
+            tenant: None,
+            trigger: None,
+            adaptive_interval: None,
+            privacy: None,
+            not_before: None,
+            not_after: None,
+            metadata: HashMap::new(),
         };
 
         let test_map_entry_clone = test_map_entry.clone();
@@ -265,16 +787,21 @@ mod cartographer_tests {
 
         let uut = Cartographer {
             signals: Arc::new(SignalStore::new()),
-            mapping_adapter: mock_mapping_adapter,
+            mapping_adapters: vec![Box::new(mock_mapping_adapter)],
+            merge_policy: MappingMergePolicy::default(),
             digital_twin_adapter: MockDigitalTwinAdapter::new(),
             data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
             poll_interval: Duration::from_secs(1),
+            timeouts: AdapterTimeouts::default(),
+            heartbeat: Arc::new(Heartbeat::default()),
+            resolution_states: Arc::default(),
+            mapping_changes: Arc::new(MappingChangeLog::default()),
         };
 
-        let result = uut.get_mapping_as_signal_patches().await;
+        let result = uut.get_merged_mapping().await;
 
         assert!(result.is_ok());
-        let mut signals = result.unwrap();
+        let mut signals = uut.get_active_signal_patches(&result.unwrap());
         assert_eq!(signals.len(), 1);
         let signal = signals.pop().unwrap();
         assert_eq!(signal.id, ID.to_string());
@@ -288,6 +815,14 @@ mod cartographer_tests {
             test_map_entry.emit_on_change
         );
         assert_eq!(signal.emission_policy.conversion, test_map_entry.conversion);
+        assert_eq!(
+            signal.emission_policy.conversion_failure_policy,
+            test_map_entry.conversion_failure_policy
+        );
+        assert_eq!(
+            signal.emission_policy.decimal_places,
+            test_map_entry.decimal_places
+        );
     }
 
     #[tokio::test]
@@ -302,6 +837,7 @@ mod cartographer_tests {
                 protocol: "in-memory".to_string(),
                 uri: "uri".to_string(),
                 context: "context".to_string(),
+                ..Default::default()
             }],
         };
 
@@ -315,9 +851,9 @@ mod cartographer_tests {
         let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
         mock_data_adapter_selector
             .expect_create_or_update_adapter()
-            .with(eq(test_entity.clone()))
+            .with(eq(test_entity.clone()), always())
             .once()
-            .returning(|_| Ok(()));
+            .returning(|_, _| Ok(()));
         let data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
 
         let mut mock_dt_adapter = MockDigitalTwinAdapter::new();
@@ -329,10 +865,15 @@ mod cartographer_tests {
 
         let uut = Cartographer {
             signals: Arc::new(SignalStore::new()),
-            mapping_adapter: MockMappingAdapter::new(),
+            mapping_adapters: vec![Box::new(MockMappingAdapter::new())],
+            merge_policy: MappingMergePolicy::default(),
             digital_twin_adapter: mock_dt_adapter,
             data_adapter_selector,
             poll_interval: Duration::from_secs(1),
+            timeouts: AdapterTimeouts::default(),
+            heartbeat: Arc::new(Heartbeat::default()),
+            resolution_states: Arc::default(),
+            mapping_changes: Arc::new(MappingChangeLog::default()),
         };
 
         let result = uut.populate_source(test_signal_patch).await;
@@ -342,4 +883,503 @@ mod cartographer_tests {
         assert!(result.is_ok());
         assert_eq!(test_signal_patch.source, test_entity);
     }
+
+    fn create_mock_mapping_adapter(
+        map: HashMap<String, DigitalTwinMapEntry>,
+    ) -> Box<dyn MappingAdapter + Send + Sync> {
+        let mut mock_mapping_adapter = MockMappingAdapter::new();
+        mock_mapping_adapter
+            .expect_get_mapping()
+            .returning(move |_| Ok(GetMappingResponse { map: map.clone() }));
+
+        Box::new(mock_mapping_adapter)
+    }
+
+    fn create_failing_mock_mapping_adapter() -> Box<dyn MappingAdapter + Send + Sync> {
+        let mut mock_mapping_adapter = MockMappingAdapter::new();
+        mock_mapping_adapter
+            .expect_get_mapping()
+            .returning(|_| Err(MappingAdapterError::communication("test error")));
+        mock_mapping_adapter
+            .expect_check_for_work()
+            .returning(|_| Err(MappingAdapterError::communication("test error")));
+
+        Box::new(mock_mapping_adapter)
+    }
+
+    /// A mapping adapter which never responds
+    struct HangingMappingAdapter;
+
+    #[async_trait]
+    impl MappingAdapter for HangingMappingAdapter {
+        fn create_new(
+            _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        ) -> Result<Self, MappingAdapterError> {
+            Ok(Self)
+        }
+
+        async fn check_for_work(
+            &self,
+            _request: CheckForWorkRequest,
+        ) -> Result<CheckForWorkResponse, MappingAdapterError> {
+            std::future::pending().await
+        }
+
+        async fn get_mapping(
+            &self,
+            _request: GetMappingRequest,
+        ) -> Result<GetMappingResponse, MappingAdapterError> {
+            std::future::pending().await
+        }
+    }
+
+    fn create_cartographer(
+        mapping_adapters: Vec<Box<dyn MappingAdapter + Send + Sync>>,
+        merge_policy: MappingMergePolicy,
+    ) -> Cartographer<MockDigitalTwinAdapter, MockDataAdapterSelector> {
+        Cartographer {
+            signals: Arc::new(SignalStore::new()),
+            mapping_adapters,
+            merge_policy,
+            digital_twin_adapter: MockDigitalTwinAdapter::new(),
+            data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
+            poll_interval: Duration::from_secs(1),
+            timeouts: AdapterTimeouts::default(),
+            heartbeat: Arc::new(Heartbeat::default()),
+            resolution_states: Arc::default(),
+            mapping_changes: Arc::new(MappingChangeLog::default()),
+        }
+    }
+
+    fn create_map_entry(source: &str, interval_ms: u64) -> (String, DigitalTwinMapEntry) {
+        (
+            source.to_string(),
+            DigitalTwinMapEntry {
+                source: source.to_string(),
+                interval_ms,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn record_mapping_changes_only_records_changed_mappings() {
+        let uut = create_cartographer(Vec::new(), MappingMergePolicy::LastWins);
+        let previous = HashMap::from([create_map_entry("a", 1000), create_map_entry("b", 1000)]);
+        let current = HashMap::from([create_map_entry("a", 500), create_map_entry("c", 1000)]);
+
+        uut.record_mapping_changes(&HashMap::new(), &previous);
+        uut.record_mapping_changes(&previous, &previous);
+        uut.record_mapping_changes(&previous, &current);
+
+        let changes = uut.mapping_changes.recent();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].sequence, 1);
+        assert_eq!(changes[0].added, vec![String::from("a"), String::from("b")]);
+        assert_eq!(changes[1].sequence, 2);
+        assert_eq!(changes[1].added, vec![String::from("c")]);
+        assert_eq!(changes[1].removed, vec![String::from("b")]);
+        assert_eq!(changes[1].changed[0].id, "a");
+    }
+
+    #[tokio::test]
+    async fn get_merged_mapping_last_wins_overrides_earlier_entries() {
+        let baseline = [create_map_entry("a", 1), create_map_entry("b", 1)];
+        let overrides = [create_map_entry("b", 2), create_map_entry("c", 2)];
+
+        let uut = create_cartographer(
+            vec![
+                create_mock_mapping_adapter(baseline.into_iter().collect()),
+                create_mock_mapping_adapter(overrides.into_iter().collect()),
+            ],
+            MappingMergePolicy::LastWins,
+        );
+
+        let result = uut.get_merged_mapping().await.unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["a"].interval_ms, 1);
+        assert_eq!(result["b"].interval_ms, 2);
+        assert_eq!(result["c"].interval_ms, 2);
+    }
+
+    #[tokio::test]
+    async fn get_merged_mapping_first_wins_keeps_earlier_entries() {
+        let baseline = [create_map_entry("a", 1), create_map_entry("b", 1)];
+        let overrides = [create_map_entry("b", 2), create_map_entry("c", 2)];
+
+        let uut = create_cartographer(
+            vec![
+                create_mock_mapping_adapter(baseline.into_iter().collect()),
+                create_mock_mapping_adapter(overrides.into_iter().collect()),
+            ],
+            MappingMergePolicy::FirstWins,
+        );
+
+        let result = uut.get_merged_mapping().await.unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["a"].interval_ms, 1);
+        assert_eq!(result["b"].interval_ms, 1);
+        assert_eq!(result["c"].interval_ms, 2);
+    }
+
+    #[tokio::test]
+    async fn get_merged_mapping_keeps_entries_in_different_namespaces() {
+        let in_namespace = |namespace: &str, interval_ms| {
+            let (id, mut entry) = create_map_entry("a", interval_ms);
+            entry.namespace = Some(namespace.to_string());
+            (id, entry)
+        };
+
+        let uut = create_cartographer(
+            vec![
+                create_mock_mapping_adapter([in_namespace("cabin", 1)].into_iter().collect()),
+                create_mock_mapping_adapter([in_namespace("body", 2)].into_iter().collect()),
+            ],
+            MappingMergePolicy::LastWins,
+        );
+
+        let result = uut.get_merged_mapping().await.unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["cabin::a"].interval_ms, 1);
+        assert_eq!(result["body::a"].interval_ms, 2);
+    }
+
+    #[tokio::test]
+    async fn get_merged_mapping_fails_if_any_adapter_fails_when_merging() {
+        for policy in [MappingMergePolicy::LastWins, MappingMergePolicy::FirstWins] {
+            let uut = create_cartographer(
+                vec![
+                    create_mock_mapping_adapter([create_map_entry("a", 1)].into_iter().collect()),
+                    create_failing_mock_mapping_adapter(),
+                ],
+                policy,
+            );
+
+            assert!(uut.get_merged_mapping().await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn get_merged_mapping_first_available_falls_back() {
+        let uut = create_cartographer(
+            vec![
+                create_failing_mock_mapping_adapter(),
+                create_mock_mapping_adapter([create_map_entry("a", 1)].into_iter().collect()),
+                create_mock_mapping_adapter([create_map_entry("b", 2)].into_iter().collect()),
+            ],
+            MappingMergePolicy::FirstAvailable,
+        );
+
+        let result = uut.get_merged_mapping().await.unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert!(result.contains_key("a"));
+    }
+
+    #[tokio::test]
+    async fn get_merged_mapping_first_available_fails_if_all_adapters_fail() {
+        let uut = create_cartographer(
+            vec![
+                create_failing_mock_mapping_adapter(),
+                create_failing_mock_mapping_adapter(),
+            ],
+            MappingMergePolicy::FirstAvailable,
+        );
+
+        assert!(uut.get_merged_mapping().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn check_for_work_returns_true_if_any_adapter_has_work() {
+        let mut no_work_adapter = MockMappingAdapter::new();
+        no_work_adapter
+            .expect_check_for_work()
+            .once()
+            .returning(|_| Ok(CheckForWorkResponse { has_work: false }));
+
+        let mut work_adapter = MockMappingAdapter::new();
+        work_adapter
+            .expect_check_for_work()
+            .once()
+            .returning(|_| Ok(CheckForWorkResponse { has_work: true }));
+
+        let uut = create_cartographer(
+            vec![
+                create_failing_mock_mapping_adapter(),
+                Box::new(no_work_adapter),
+                Box::new(work_adapter),
+            ],
+            MappingMergePolicy::default(),
+        );
+
+        let result = uut.check_for_work().await;
+
+        assert!(result.is_ok());
+        assert!(result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn check_for_work_fails_if_all_adapters_fail() {
+        let uut = create_cartographer(
+            vec![
+                create_failing_mock_mapping_adapter(),
+                create_failing_mock_mapping_adapter(),
+            ],
+            MappingMergePolicy::default(),
+        );
+
+        assert!(uut.check_for_work().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn check_for_work_times_out_hanging_adapters() {
+        let mut mock_mapping_adapter = MockMappingAdapter::new();
+        mock_mapping_adapter
+            .expect_check_for_work()
+            .once()
+            .returning(|_| Ok(CheckForWorkResponse { has_work: true }));
+
+        let mut uut = create_cartographer(
+            vec![
+                Box::new(HangingMappingAdapter),
+                Box::new(mock_mapping_adapter),
+            ],
+            MappingMergePolicy::default(),
+        );
+        uut.timeouts.mapping_ms = Some(1000);
+
+        assert!(uut.check_for_work().await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn get_merged_mapping_times_out_hanging_adapters() {
+        let mut uut = create_cartographer(
+            vec![Box::new(HangingMappingAdapter)],
+            MappingMergePolicy::default(),
+        );
+        uut.timeouts.mapping_ms = Some(1000);
+
+        let result = uut.get_merged_mapping().await;
+        assert!(result.is_err_and(|e| e.kind() == MappingAdapterErrorKind::Communication));
+    }
+
+    #[tokio::test]
+    async fn process_signal_patches_tracks_resolution_state() {
+        const RESOLVED_ID: &str = "resolved";
+        const NOT_FOUND_ID: &str = "not_found";
+        const DT_ERROR_ID: &str = "dt_error";
+
+        let mut mock_dt_adapter = MockDigitalTwinAdapter::new();
+        mock_dt_adapter
+            .expect_find_by_id()
+            .returning(|request| match request.entity_id.as_str() {
+                RESOLVED_ID => Ok(FindByIdResponse {
+                    entity: Entity {
+                        id: RESOLVED_ID.to_string(),
+                        ..Default::default()
+                    },
+                }),
+                NOT_FOUND_ID => Err(DigitalTwinAdapterErrorKind::EntityNotFound.into()),
+                _ => Err(DigitalTwinAdapterError::communication("test error")),
+            });
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_create_or_update_adapter()
+            .returning(|_, _| Ok(()));
+
+        let mut uut = create_cartographer(vec![], MappingMergePolicy::default());
+        uut.digital_twin_adapter = mock_dt_adapter;
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+
+        let patches: Vec<SignalPatch> = [RESOLVED_ID, NOT_FOUND_ID, DT_ERROR_ID]
+            .into_iter()
+            .map(|id| SignalPatch {
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        uut.reset_resolution_states(&patches);
+        let mut successes = Vec::new();
+        let mut failures = Vec::new();
+        uut.process_signal_patches(&patches, &mut successes, &mut failures)
+            .await;
+
+        assert_eq!(successes.len(), 1);
+        assert_eq!(failures.len(), 2);
+
+        let states = uut.resolution_states.read().unwrap().clone();
+        assert_eq!(states[RESOLVED_ID], ResolutionState::Resolved);
+        assert!(matches!(
+            states[NOT_FOUND_ID],
+            ResolutionState::Failed {
+                reason: ResolutionFailure::EntityNotFound,
+                attempts: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            states[DT_ERROR_ID],
+            ResolutionState::Failed {
+                reason: ResolutionFailure::DigitalTwinError(_),
+                attempts: 1,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn record_resolution_failure_uses_exponential_backoff() {
+        const ID: &str = "testid";
+        let uut = create_cartographer(vec![], MappingMergePolicy::default());
+        let poll_interval = uut.poll_interval;
+
+        for expected_attempts in 1..=3 {
+            let attempts = uut.record_resolution_failure(ID, ResolutionFailure::EntityNotFound);
+            assert_eq!(attempts, expected_attempts);
+        }
+
+        // After 3 failures the backoff should be 4 poll intervals
+        let now = Instant::now();
+        assert!(!uut.is_retry_due(ID, now));
+        assert!(!uut.is_retry_due(ID, now + poll_interval * 3));
+        assert!(uut.is_retry_due(ID, now + poll_interval * 4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn record_resolution_failure_backoff_is_capped() {
+        const ID: &str = "testid";
+        let uut = create_cartographer(vec![], MappingMergePolicy::default());
+
+        for _ in 0..100 {
+            uut.record_resolution_failure(ID, ResolutionFailure::EntityNotFound);
+        }
+
+        assert!(uut.is_retry_due(ID, Instant::now() + MAX_RESOLUTION_BACKOFF));
+    }
+
+    #[test]
+    fn is_retry_due_returns_true_for_untracked_signals() {
+        let uut = create_cartographer(vec![], MappingMergePolicy::default());
+        assert!(uut.is_retry_due("untracked", Instant::now()));
+    }
+
+    fn create_signal_patch(id: &str, operation: Operation) -> SignalPatch {
+        SignalPatch {
+            id: id.to_string(),
+            source: Entity {
+                id: id.to_string(),
+                endpoints: vec![EntityEndpoint {
+                    operations: vec![operation.to_string()],
+                    protocol: "in-memory".to_string(),
+                    uri: "uri".to_string(),
+                    context: "context".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn active_signals_follow_validity_windows() {
+        let clock = Arc::new(ManualClock::default());
+        let now = clock.now();
+        let mut uut = create_cartographer(Vec::new(), MappingMergePolicy::default());
+        uut.signals = Arc::new(SignalStore::with_clock(clock.clone()));
+
+        let (id, mut entry) = create_map_entry("brake_temperature", 1);
+        entry.not_before = Some(now + Duration::from_secs(10));
+        entry.not_after = Some(now + Duration::from_secs(20));
+        let mapping = HashMap::from([(id.clone(), entry)]);
+
+        let mut active_ids = HashSet::new();
+        assert!(uut.get_active_signal_patches(&mapping).is_empty());
+        assert!(!uut.is_schedule_changed(&mapping, &active_ids));
+
+        clock.advance(Duration::from_secs(10));
+        assert!(uut.is_schedule_changed(&mapping, &active_ids));
+        active_ids = uut
+            .get_active_signal_patches(&mapping)
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(active_ids, HashSet::from([id]));
+        assert!(!uut.is_schedule_changed(&mapping, &active_ids));
+
+        clock.advance(Duration::from_secs(10));
+        assert!(uut.is_schedule_changed(&mapping, &active_ids));
+        assert!(uut.get_active_signal_patches(&mapping).is_empty());
+    }
+
+    #[test]
+    fn get_new_signals_excludes_signals_in_store() {
+        let existing = create_signal_patch("existing", Operation::Get);
+        let new = create_signal_patch("new", Operation::Get);
+
+        let uut = create_cartographer(vec![], MappingMergePolicy::default());
+        uut.signals.add([existing.clone()].into_iter());
+
+        let result = uut.get_new_signals(&[existing, new.clone()]);
+
+        assert_eq!(result, vec![new]);
+    }
+
+    #[tokio::test]
+    async fn request_initial_values_only_requests_get_signals() {
+        const GET_ID: &str = "get";
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .withf(|id| id == GET_ID)
+            .once()
+            .returning(|_| Ok(()));
+
+        let mut uut = create_cartographer(vec![], MappingMergePolicy::default());
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+
+        uut.request_initial_values(&[
+            create_signal_patch(GET_ID, Operation::Get),
+            create_signal_patch("subscribe", Operation::Subscribe),
+        ])
+        .await;
+
+        uut.data_adapter_selector.lock().await.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn run_stops_when_cancelled() {
+        let mut mock_mapping_adapter = MockMappingAdapter::new();
+        mock_mapping_adapter
+            .expect_check_for_work()
+            .once()
+            .returning(|_| Ok(CheckForWorkResponse { has_work: false }));
+
+        let uut = Arc::new(Cartographer {
+            signals: Arc::new(SignalStore::new()),
+            mapping_adapters: vec![Box::new(mock_mapping_adapter)],
+            merge_policy: MappingMergePolicy::default(),
+            digital_twin_adapter: MockDigitalTwinAdapter::new(),
+            data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
+            poll_interval: Duration::from_secs(3600),
+            timeouts: AdapterTimeouts::default(),
+            heartbeat: Arc::new(Heartbeat::default()),
+            resolution_states: Arc::default(),
+            mapping_changes: Arc::new(MappingChangeLog::default()),
+        });
+
+        // The iteration in progress finishes before the cartographer stops
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), uut.run(cancellation_token)).await;
+        assert!(result.is_ok_and(|r| r.is_ok_and(|r| r.is_ok())));
+    }
 }
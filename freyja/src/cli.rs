@@ -0,0 +1,78 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use freyja_common::{
+    cmd_utils::{CommandSpec, SubcommandSpec, LOG_LEVEL_ARG},
+    config_utils::PROFILE_ARG,
+};
+
+use crate::restart_policy::{BACKOFF_ARG, MAX_BACKOFF_ARG, MAX_RESTARTS_ARG};
+
+/// The subcommand which runs Freyja
+pub(crate) const RUN: &str = "run";
+
+/// The subcommand which prints Freyja's config
+pub(crate) const VALIDATE_CONFIG: &str = "validate-config";
+
+/// The subcommand which prints the mapping
+pub(crate) const PRINT_MAPPING: &str = "print-mapping";
+
+/// Freyja's command-line interface
+pub const FREYJA_COMMAND: CommandSpec = CommandSpec {
+    name: "freyja",
+    about: "Synchronizes the in-vehicle digital twin with the cloud digital twin",
+    args: &[LOG_LEVEL_ARG, PROFILE_ARG],
+    subcommands: &[
+        SubcommandSpec {
+            name: RUN,
+            help:
+                "Runs Freyja until the operating system asks it to shut down. This is the default",
+            args: &[MAX_RESTARTS_ARG, BACKOFF_ARG, MAX_BACKOFF_ARG],
+        },
+        SubcommandSpec {
+            name: VALIDATE_CONFIG,
+            help: "Reads Freyja's config and prints it after applying the config overrides",
+            args: &[],
+        },
+        SubcommandSpec {
+            name: PRINT_MAPPING,
+            help: "Gets the mapping from the mapping adapters and prints it",
+            args: &[],
+        },
+    ],
+};
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    use freyja_common::cmd_utils::{Args, ParseArgsError, ParseArgsErrorKind};
+
+    fn parse(args: &[&str]) -> Result<Args, ParseArgsError> {
+        let args: Vec<String> = ["freyja"]
+            .iter()
+            .chain(args)
+            .map(|a| a.to_string())
+            .collect();
+        FREYJA_COMMAND.parse(args.into_iter())
+    }
+
+    #[test]
+    fn parse_runs_by_default() {
+        let result = parse(&["--log-level=debug", "--restart-max=3"]).unwrap();
+        assert_eq!(result.subcommand(), Some(RUN));
+    }
+
+    #[test]
+    fn parse_only_accepts_restart_policy_with_run() {
+        let result = parse(&[VALIDATE_CONFIG, "--profile=container"]).unwrap();
+        assert_eq!(result.subcommand(), Some(VALIDATE_CONFIG));
+
+        let result = parse(&[PRINT_MAPPING, "--restart-max=3"]);
+        assert!(result.is_err_and(|e| e.kind()
+            == ParseArgsErrorKind::UnknownArgument {
+                key: MAX_RESTARTS_ARG.name.to_string()
+            }));
+    }
+}
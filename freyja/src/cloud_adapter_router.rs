@@ -0,0 +1,582 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use freyja_common::cloud_adapter::{
+    Acknowledgement, BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest,
+    CloudMessageResponse, TENANT_METADATA_KEY,
+};
+
+use crate::{adapter_timeout::with_timeout, config::CloudRoute};
+
+/// Sends messages to the cloud.
+/// This is implemented for every cloud adapter and for the `CloudAdapterRouter`,
+/// so the emitter can use either a single adapter or several adapters with routing rules.
+#[async_trait]
+pub trait CloudMessageSender {
+    /// Sends the signal to the cloud
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError>;
+
+    /// Checks whether the cloud has requested that the current values of any signals be emitted again
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError>;
+}
+
+#[async_trait]
+impl<TCloudAdapter: CloudAdapter + Send + Sync> CloudMessageSender for TCloudAdapter {
+    /// Sends the signal to the cloud with this adapter
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        CloudAdapter::send_to_cloud(self, cloud_message).await
+    }
+
+    /// Checks this adapter for backfill requests
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        CloudAdapter::check_for_backfill(self).await
+    }
+}
+
+/// Routes messages to one or more cloud adapters according to their metadata
+pub struct CloudAdapterRouter {
+    /// The cloud adapters, in the order in which they were provided
+    adapters: Vec<Box<dyn CloudAdapter + Send + Sync>>,
+
+    /// The routing rules, in priority order
+    routes: Vec<CloudRoute>,
+
+    /// The timeout for each call to a cloud adapter, or `None` to wait indefinitely
+    timeout_ms: Option<u64>,
+}
+
+impl CloudAdapterRouter {
+    /// Creates a new router.
+    /// Returns an error if there are no adapters or if a route refers to an adapter kind that wasn't provided.
+    ///
+    /// # Arguments
+    /// - `adapters`: the cloud adapters
+    /// - `routes`: the routing rules, in priority order
+    /// - `timeout_ms`: the timeout for each call to a cloud adapter, or `None` to wait indefinitely
+    pub fn new(
+        adapters: Vec<Box<dyn CloudAdapter + Send + Sync>>,
+        routes: Vec<CloudRoute>,
+        timeout_ms: Option<u64>,
+    ) -> Result<Self, CloudAdapterRouterError> {
+        if adapters.is_empty() {
+            return Err(CloudAdapterRouterErrorKind::NoAdapters.into());
+        }
+
+        let kinds: Vec<String> = adapters.iter().map(|adapter| adapter.kind()).collect();
+        for kind in routes.iter().flat_map(|route| route.cloud_adapters.iter()) {
+            if !kinds.contains(kind) {
+                return Err(CloudAdapterRouterError::unknown_adapter(format!(
+                    "No cloud adapter of kind {kind} was provided. Available kinds are {kinds:?}"
+                )));
+            }
+        }
+
+        Ok(Self {
+            adapters,
+            routes,
+            timeout_ms,
+        })
+    }
+
+    /// Gets the adapters that a message with the provided metadata should be sent to.
+    /// The first route whose metadata matches is used, and messages which don't match any route
+    /// are sent to every adapter.
+    ///
+    /// # Arguments
+    /// - `metadata`: the message metadata
+    fn select(&self, metadata: &HashMap<String, String>) -> Vec<&(dyn CloudAdapter + Send + Sync)> {
+        let route = self.routes.iter().find(|route| {
+            route
+                .metadata
+                .iter()
+                .all(|(key, value)| metadata.get(key) == Some(value))
+        });
+
+        self.adapters
+            .iter()
+            .filter(|adapter| match route {
+                Some(route) => route.cloud_adapters.contains(&adapter.kind()),
+                None => true,
+            })
+            .map(|adapter| adapter.as_ref())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl CloudMessageSender for CloudAdapterRouter {
+    /// Sends the signal to every cloud adapter that it's routed to.
+    /// Every adapter is attempted even if an earlier one fails, and the first error is returned.
+    /// Otherwise, the response is only acknowledged once every adapter which acknowledges messages later has accepted it.
+    /// The response has the first message id that an adapter reported, the latest acceptance time, and every adapter's warnings.
+    /// Each adapter's message id is logged so that the message can be found in every backend.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let mut first_error = None;
+        let mut acknowledgements = Vec::new();
+        let mut merged = CloudMessageResponse::default();
+        for adapter in self.select(&cloud_message.metadata) {
+            let result = with_timeout(
+                self.timeout_ms,
+                adapter.send_to_cloud(cloud_message.clone()),
+                CloudAdapterError::communication,
+            )
+            .await;
+
+            match result {
+                Ok(response) => {
+                    if let Some(message_id) = response.message_id.as_ref() {
+                        log::debug!(
+                            "Cloud adapter {} sent message as {message_id}",
+                            adapter.kind()
+                        );
+                    }
+
+                    acknowledgements.extend(response.acknowledgement);
+                    merged.message_id = merged.message_id.or(response.message_id);
+                    merged.accepted_at = merged.accepted_at.max(response.accepted_at);
+                    merged.warnings.extend(
+                        response
+                            .warnings
+                            .into_iter()
+                            .map(|warning| format!("{}: {warning}", adapter.kind())),
+                    );
+                }
+                Err(e) => {
+                    log::error!(
+                        "Cloud adapter {} failed to send message: {e:?}",
+                        adapter.kind()
+                    );
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(CloudMessageResponse {
+                acknowledgement: Acknowledgement::all(acknowledgements),
+                ..merged
+            }),
+        }
+    }
+
+    /// Checks every cloud adapter for backfill requests and merges them.
+    /// Adapters which fail are skipped so that the requests from the other adapters aren't lost,
+    /// and an error is returned only if every adapter failed.
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        let mut request = BackfillRequest::default();
+        let mut first_error = None;
+        let mut any_succeeded = false;
+        for adapter in self.adapters.iter() {
+            let result = with_timeout(
+                self.timeout_ms,
+                adapter.check_for_backfill(),
+                CloudAdapterError::communication,
+            )
+            .await;
+
+            match result {
+                Ok(adapter_request) => {
+                    any_succeeded = true;
+                    request.merge(adapter_request);
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Cloud adapter {} failed to check for backfill requests: {e:?}",
+                        adapter.kind()
+                    );
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) if !any_succeeded => Err(e),
+            _ => Ok(request),
+        }
+    }
+}
+
+/// Routes messages to the cloud adapters of their tenant, so that each tenant's signals are only emitted to its own backend.
+/// Messages without a tenant are routed to the cloud adapters which don't belong to a tenant
+pub struct TenantRouter {
+    /// The router for messages without a tenant
+    default: CloudAdapterRouter,
+
+    /// The router for each tenant's messages, keyed by tenant id
+    tenants: HashMap<String, CloudAdapterRouter>,
+}
+
+impl TenantRouter {
+    /// Creates a new TenantRouter
+    ///
+    /// # Arguments
+    /// - `default`: the router for messages without a tenant
+    /// - `tenants`: the router for each tenant's messages, keyed by tenant id
+    pub fn new(default: CloudAdapterRouter, tenants: HashMap<String, CloudAdapterRouter>) -> Self {
+        Self { default, tenants }
+    }
+}
+
+#[async_trait]
+impl CloudMessageSender for TenantRouter {
+    /// Sends the signal with the router of its tenant.
+    /// Returns an error without sending the signal if its tenant doesn't have any cloud adapters,
+    /// so that a tenant's signals never reach another backend.
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let router = match cloud_message.metadata.get(TENANT_METADATA_KEY) {
+            Some(tenant) => self.tenants.get(tenant).ok_or_else(|| {
+                CloudAdapterError::key_not_found(format!(
+                    "No cloud adapters are configured for tenant {tenant}"
+                ))
+            })?,
+            None => &self.default,
+        };
+
+        router.send_to_cloud(cloud_message).await
+    }
+
+    /// Checks the routers of every tenant and the default router for backfill requests and merges them.
+    /// Routers which fail are skipped so that the requests from the other routers aren't lost,
+    /// and an error is returned only if every router failed.
+    async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+        let mut request = BackfillRequest::default();
+        let mut first_error = None;
+        let mut any_succeeded = false;
+        for router in std::iter::once(&self.default).chain(self.tenants.values()) {
+            match router.check_for_backfill().await {
+                Ok(router_request) => {
+                    any_succeeded = true;
+                    request.merge(router_request);
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) if !any_succeeded => Err(e),
+            _ => Ok(request),
+        }
+    }
+}
+
+proc_macros::error! {
+    CloudAdapterRouterError {
+        NoAdapters,
+        UnknownAdapter
+    }
+}
+
+#[cfg(test)]
+mod cloud_adapter_router_tests {
+    use super::*;
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use time::OffsetDateTime;
+    use tokio::sync::Mutex;
+
+    use freyja_common::{
+        cloud_adapter::CloudAdapterErrorKind,
+        service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+    };
+
+    /// A cloud adapter which counts the messages it receives
+    struct CountingCloudAdapter {
+        kind: &'static str,
+        sent: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl CloudAdapter for CountingCloudAdapter {
+        fn create_new(
+            _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        ) -> Result<Self, CloudAdapterError> {
+            Err(CloudAdapterErrorKind::Unknown.into())
+        }
+
+        async fn send_to_cloud(
+            &self,
+            _cloud_message: CloudMessageRequest,
+        ) -> Result<CloudMessageResponse, CloudAdapterError> {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                Err(CloudAdapterErrorKind::Communication.into())
+            } else {
+                Ok(CloudMessageResponse {
+                    message_id: Some(format!("{}-1", self.kind)),
+                    warnings: vec![String::from("clamped")],
+                    ..Default::default()
+                })
+            }
+        }
+
+        async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError> {
+            if self.fail {
+                Err(CloudAdapterErrorKind::Communication.into())
+            } else {
+                Ok(BackfillRequest {
+                    signal_ids: [self.kind.to_string()].into_iter().collect(),
+                    all_signals: false,
+                    ..Default::default()
+                })
+            }
+        }
+
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+    }
+
+    /// A cloud adapter which never responds
+    struct HangingCloudAdapter;
+
+    #[async_trait]
+    impl CloudAdapter for HangingCloudAdapter {
+        fn create_new(
+            _selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+        ) -> Result<Self, CloudAdapterError> {
+            Ok(Self)
+        }
+
+        async fn send_to_cloud(
+            &self,
+            _cloud_message: CloudMessageRequest,
+        ) -> Result<CloudMessageResponse, CloudAdapterError> {
+            std::future::pending().await
+        }
+
+        fn kind(&self) -> String {
+            String::from("Hanging")
+        }
+    }
+
+    fn create_adapter(
+        kind: &'static str,
+        fail: bool,
+    ) -> (Box<dyn CloudAdapter + Send + Sync>, Arc<AtomicUsize>) {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let adapter = CountingCloudAdapter {
+            kind,
+            sent: sent.clone(),
+            fail,
+        };
+
+        (Box::new(adapter), sent)
+    }
+
+    fn create_message(metadata: &[(&str, &str)]) -> CloudMessageRequest {
+        CloudMessageRequest {
+            metadata: metadata
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            signal_value: "42".into(),
+            numeric_value: Some(42.0),
+            signal_timestamp: OffsetDateTime::UNIX_EPOCH,
+            idempotency_key: None,
+        }
+    }
+
+    fn create_route(metadata: &[(&str, &str)], cloud_adapters: &[&str]) -> CloudRoute {
+        CloudRoute {
+            metadata: metadata
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            cloud_adapters: cloud_adapters.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn new_returns_err_without_adapters() {
+        let result = CloudAdapterRouter::new(vec![], vec![], None);
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterRouterErrorKind::NoAdapters));
+    }
+
+    #[test]
+    fn new_returns_err_for_unknown_adapter_kind() {
+        let (azure, _) = create_adapter("Azure", false);
+        let result =
+            CloudAdapterRouter::new(vec![azure], vec![create_route(&[], &["Kafka"])], None);
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterRouterErrorKind::UnknownAdapter));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sends_to_every_adapter_without_matching_route() {
+        let (azure, azure_sent) = create_adapter("Azure", false);
+        let (kafka, kafka_sent) = create_adapter("Kafka", false);
+        let uut = CloudAdapterRouter::new(
+            vec![azure, kafka],
+            vec![create_route(&[("group", "diagnostics")], &["Kafka"])],
+            None,
+        )
+        .unwrap();
+
+        let response = uut
+            .send_to_cloud(create_message(&[("group", "telemetry")]))
+            .await
+            .unwrap();
+        assert_eq!(azure_sent.load(Ordering::SeqCst), 1);
+        assert_eq!(kafka_sent.load(Ordering::SeqCst), 1);
+
+        // The response merges what every adapter reported
+        assert_eq!(response.message_id, Some(String::from("Azure-1")));
+        assert_eq!(
+            response.warnings,
+            vec![
+                String::from("Azure: clamped"),
+                String::from("Kafka: clamped")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_uses_first_matching_route() {
+        let (azure, azure_sent) = create_adapter("Azure", false);
+        let (kafka, kafka_sent) = create_adapter("Kafka", false);
+        let uut = CloudAdapterRouter::new(
+            vec![azure, kafka],
+            vec![
+                create_route(&[("group", "diagnostics")], &["Kafka"]),
+                create_route(&[], &["Azure"]),
+            ],
+            None,
+        )
+        .unwrap();
+
+        uut.send_to_cloud(create_message(&[("group", "diagnostics"), ("id", "a")]))
+            .await
+            .unwrap();
+        assert_eq!(azure_sent.load(Ordering::SeqCst), 0);
+        assert_eq!(kafka_sent.load(Ordering::SeqCst), 1);
+
+        uut.send_to_cloud(create_message(&[("id", "b")]))
+            .await
+            .unwrap();
+        assert_eq!(azure_sent.load(Ordering::SeqCst), 1);
+        assert_eq!(kafka_sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_attempts_every_adapter_when_one_fails() {
+        let (azure, azure_sent) = create_adapter("Azure", true);
+        let (kafka, kafka_sent) = create_adapter("Kafka", false);
+        let uut = CloudAdapterRouter::new(vec![azure, kafka], vec![], None).unwrap();
+
+        let result = uut.send_to_cloud(create_message(&[])).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+        assert_eq!(azure_sent.load(Ordering::SeqCst), 1);
+        assert_eq!(kafka_sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn check_for_backfill_merges_requests_and_skips_failing_adapters() {
+        let (azure, _) = create_adapter("Azure", false);
+        let (kafka, _) = create_adapter("Kafka", true);
+        let (mqtt, _) = create_adapter("Mqtt", false);
+        let uut = CloudAdapterRouter::new(vec![azure, kafka, mqtt], vec![], None).unwrap();
+
+        let request = uut.check_for_backfill().await.unwrap();
+        assert!(request.includes("Azure"));
+        assert!(!request.includes("Kafka"));
+        assert!(request.includes("Mqtt"));
+    }
+
+    #[tokio::test]
+    async fn check_for_backfill_returns_err_when_every_adapter_fails() {
+        let (azure, _) = create_adapter("Azure", true);
+        let uut = CloudAdapterRouter::new(vec![azure], vec![], None).unwrap();
+
+        let result = uut.check_for_backfill().await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+    }
+
+    #[tokio::test]
+    async fn tenant_router_sends_to_router_of_tenant() {
+        let (default, default_sent) = create_adapter("Azure", false);
+        let (contoso, contoso_sent) = create_adapter("Azure", false);
+        let uut = TenantRouter::new(
+            CloudAdapterRouter::new(vec![default], vec![], None).unwrap(),
+            HashMap::from([(
+                String::from("contoso"),
+                CloudAdapterRouter::new(vec![contoso], vec![], None).unwrap(),
+            )]),
+        );
+
+        uut.send_to_cloud(create_message(&[(TENANT_METADATA_KEY, "contoso")]))
+            .await
+            .unwrap();
+        assert_eq!(default_sent.load(Ordering::SeqCst), 0);
+        assert_eq!(contoso_sent.load(Ordering::SeqCst), 1);
+
+        uut.send_to_cloud(create_message(&[])).await.unwrap();
+        assert_eq!(default_sent.load(Ordering::SeqCst), 1);
+        assert_eq!(contoso_sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn tenant_router_returns_err_for_unknown_tenant() {
+        let (default, default_sent) = create_adapter("Azure", false);
+        let uut = TenantRouter::new(
+            CloudAdapterRouter::new(vec![default], vec![], None).unwrap(),
+            HashMap::new(),
+        );
+
+        let result = uut
+            .send_to_cloud(create_message(&[(TENANT_METADATA_KEY, "fabrikam")]))
+            .await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::KeyNotFound));
+        assert_eq!(default_sent.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn send_to_cloud_times_out_hanging_adapters() {
+        let (azure, azure_sent) = create_adapter("Azure", false);
+        let uut = CloudAdapterRouter::new(
+            vec![Box::new(HangingCloudAdapter), azure],
+            vec![],
+            Some(1000),
+        )
+        .unwrap();
+
+        let result = uut.send_to_cloud(create_message(&[])).await;
+        assert!(result.is_err_and(|e| e.kind() == CloudAdapterErrorKind::Communication));
+        assert_eq!(azure_sent.load(Ordering::SeqCst), 1);
+    }
+}
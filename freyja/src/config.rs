@@ -0,0 +1,488 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{HashMap, HashSet},
+    num::NonZeroU64,
+};
+
+use serde::{Deserialize, Serialize};
+
+use freyja_common::{
+    conversion::{Conversion, ConversionFailurePolicy},
+    entity::Entity,
+    privacy::PrivacyPolicy,
+    signal::AdaptiveInterval,
+};
+
+/// Config for the core Freyja components
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The policy for merging mappings when multiple mapping adapters are registered
+    pub mapping_merge_policy: MappingMergePolicy,
+
+    /// The simulation settings. If this is `None`, Freyja runs in real time
+    pub simulation: Option<SimulationConfig>,
+
+    /// The limits on how often values can be requested from each provider
+    #[serde(default)]
+    pub provider_request_budgets: ProviderRequestBudgets,
+
+    /// When the emitter requests new values relative to emitting signals
+    #[serde(default)]
+    pub value_request_mode: ValueRequestMode,
+
+    /// When the emitter records that a value was emitted, which is what `emit_only_if_changed` compares new values with
+    #[serde(default)]
+    pub emission_acknowledgement_mode: EmissionAcknowledgementMode,
+
+    /// The path of a file to record all values received from providers to.
+    /// The recording can be replayed later with the file replay data adapter.
+    /// If this is `None`, values are not recorded
+    #[serde(default)]
+    pub traffic_recording_path: Option<String>,
+
+    /// The settings for suppressing values which providers deliver more than once.
+    /// If this is `None`, every value is passed to the signal store
+    #[serde(default)]
+    pub duplicate_suppression: Option<DuplicateSuppressionConfig>,
+
+    /// The ids of services to discover together at startup before any adapters are created.
+    /// Service discovery adapters which cache their results can then serve later lookups without another request
+    #[serde(default)]
+    pub prefetch_service_ids: Vec<String>,
+
+    /// The path of a JSON file which maps service ids to URIs that are used instead of discovering the services,
+    /// so that a misbehaving service discovery system can be bypassed. The file is checked for changes at most once per second
+    /// and reloaded whenever it changes, so it can be created or edited while Freyja is running. If this is `None`,
+    /// only the overrides in the `FREYJA_SERVICE_URI_OVERRIDES` environment variable are used
+    #[serde(default)]
+    pub service_uri_overrides_path: Option<String>,
+
+    /// The interval at which the cloud adapters are checked for requests to emit the current values of signals again,
+    /// such as after data loss in the backend. If this is `None`, the cloud adapters are never checked
+    #[serde(default)]
+    pub backfill_poll_interval_ms: Option<u64>,
+
+    /// The period over which overdue signals are spread when Freyja starts or cloud connectivity returns,
+    /// so that the backlog doesn't overwhelm the cloud connector and backend.
+    /// If this is `None`, overdue signals are emitted at once
+    #[serde(default)]
+    pub emission_ramp_up_ms: Option<u64>,
+
+    /// The maximum time to wait for calls to each kind of adapter.
+    /// Calls which take longer fail so that an adapter which never responds can't stall Freyja
+    #[serde(default)]
+    pub adapter_timeouts: AdapterTimeouts,
+
+    /// The settings for the watchdog which detects when the cartographer or emitter stops making progress.
+    /// If this is `None`, the watchdog is disabled
+    #[serde(default)]
+    pub watchdog: Option<WatchdogConfig>,
+
+    /// The settings for the flight recorder which captures the recent values of designated signals on demand.
+    /// If this is `None`, the flight recorder is disabled
+    #[serde(default)]
+    pub flight_recorder: Option<FlightRecorderConfig>,
+
+    /// The rules for choosing which cloud adapters each signal is emitted to, in priority order.
+    /// Signals which don't match any route are emitted to every cloud adapter
+    #[serde(default)]
+    pub cloud_routes: Vec<CloudRoute>,
+
+    /// The ids of the tenants whose signals are emitted to their own cloud backends.
+    /// An instance of every cloud adapter is created for each tenant with the tenant's config,
+    /// and signals with a tenant are only emitted with that tenant's adapters
+    #[serde(default)]
+    pub tenants: Vec<String>,
+
+    /// Signals to register directly from this config instead of with the mapping and digital twin services.
+    /// If this is not `None`, the mapping and digital twin adapters are not used
+    #[serde(default)]
+    pub static_signals: Option<Vec<StaticSignal>>,
+
+    /// The hosting address of the management API, such as `0.0.0.0:8700`.
+    /// If this is `None`, the management API is not started
+    #[serde(default)]
+    pub management_api_address: Option<String>,
+
+    /// The hosting address of the gRPC signal service, such as `0.0.0.0:8701`.
+    /// If this is `None`, the signal service is not started
+    #[serde(default)]
+    pub signal_service_address: Option<String>,
+
+    /// The settings for emitting status signals which report Freyja's own operational status to the cloud.
+    /// If this is `None`, status signals aren't emitted
+    #[serde(default)]
+    pub status_signals: Option<StatusSignalsConfig>,
+
+    /// The settings for notifying the cloud when Freyja stops because of a panic.
+    /// If this is `None`, the cloud isn't notified
+    #[serde(default)]
+    pub last_gasp: Option<LastGaspConfig>,
+
+    /// The settings for the self-test signal, which verifies that signals flow from a data adapter to the cloud.
+    /// If this is `None`, the self-test signal isn't registered
+    #[serde(default)]
+    pub self_test: Option<SelfTestConfig>,
+
+    /// The settings for also writing logs to a rotating file, so that they're kept across reboots.
+    /// If this is `None`, logs are only written to stdout
+    #[serde(default)]
+    pub log_file: Option<LogFileConfig>,
+
+    /// The private zones in which designated signals aren't emitted, based on the vehicle's location.
+    /// If this is `None`, signals are emitted regardless of the location
+    #[serde(default)]
+    pub geofences: Option<GeofencesConfig>,
+}
+
+/// A signal which is registered directly from config.
+/// This combines the entity information from the digital twin service with the mapping from the mapping service
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StaticSignal {
+    /// The source entity, including the endpoints for reaching its provider. The signal id is the entity id
+    pub entity: Entity,
+
+    /// Metadata that will be passed to the cloud adapter to perform the mapping
+    #[serde(default)]
+    pub target: HashMap<String, String>,
+
+    /// The id of the tenant whose cloud backend the signal is emitted to.
+    /// If this is `None`, the signal is emitted with the cloud adapters which don't belong to a tenant
+    #[serde(default)]
+    pub tenant: Option<String>,
+
+    /// The interval at which the signal should be emitted
+    pub interval_ms: u64,
+
+    /// Whether the signal should only be emitted if its value has changed
+    #[serde(default)]
+    pub emit_on_change: bool,
+
+    /// A conversion to apply to the signal before emission
+    #[serde(default)]
+    pub conversion: Conversion,
+
+    /// How to handle values which cannot be converted
+    #[serde(default)]
+    pub conversion_failure_policy: ConversionFailurePolicy,
+
+    /// The number of decimal places to round numeric values to. If this is `None`, values are not rounded
+    #[serde(default)]
+    pub decimal_places: Option<u32>,
+
+    /// A trigger expression over other signals which must be satisfied for the signal to be emitted.
+    /// If this is `None`, the signal is always emitted
+    #[serde(default)]
+    pub trigger: Option<String>,
+
+    /// The bounds of an adaptive emission interval, which starts at `interval_ms`.
+    /// If this is `None`, the signal is always emitted at `interval_ms`
+    #[serde(default)]
+    pub adaptive_interval: Option<AdaptiveInterval>,
+
+    /// A transformation which reduces the precision of the signal's values before emission.
+    /// If this is `None`, values are emitted with full precision
+    #[serde(default)]
+    pub privacy: Option<PrivacyPolicy>,
+}
+
+/// Timeouts for calls to adapters. Calls to adapters without a timeout wait indefinitely
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdapterTimeouts {
+    /// The timeout for calls to the digital twin adapter, such as `find_by_id`
+    #[serde(default)]
+    pub digital_twin_ms: Option<u64>,
+
+    /// The timeout for calls to the mapping adapters, such as `check_for_work` and `get_mapping`
+    #[serde(default)]
+    pub mapping_ms: Option<u64>,
+
+    /// The timeout for calls to each cloud adapter, such as `send_to_cloud`
+    #[serde(default)]
+    pub cloud_ms: Option<u64>,
+
+    /// The timeout for requesting new values from the data adapters
+    #[serde(default)]
+    pub data_ms: Option<u64>,
+}
+
+/// Config for the watchdog which detects when the cartographer or emitter stops making progress
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// The maximum time that an iteration of a component's loop can take before the component is considered stalled.
+    /// This should be longer than the adapter timeouts so that slow adapters aren't mistaken for stalls
+    pub stall_timeout_ms: u64,
+
+    /// The interval at which the watchdog checks the components
+    pub check_interval_ms: u64,
+
+    /// What to do when a component stalls
+    #[serde(default)]
+    pub action: StallAction,
+}
+
+/// Config for suppressing values which providers deliver more than once, such as redelivered MQTT messages
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateSuppressionConfig {
+    /// How long a delivery is remembered. A value received again within this window is dropped
+    pub window_ms: u64,
+
+    /// The ids of the signals whose values are compared by content when the provider doesn't supply a sequence id.
+    /// This should only include event signals, since other signals can legitimately repeat a value
+    #[serde(default)]
+    pub content_signal_ids: HashSet<String>,
+}
+
+/// Config for the flight recorder which captures the recent values of designated signals on demand
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlightRecorderConfig {
+    /// The ids of the signals whose values are recorded
+    pub signal_ids: HashSet<String>,
+
+    /// The maximum number of recent values kept for each signal.
+    /// This should cover both the pre-trigger and post-trigger windows at the rate the signals are updated
+    pub capacity: usize,
+
+    /// How far before the trigger a capture reaches
+    pub pre_trigger_ms: u64,
+
+    /// How long after the trigger a capture continues recording before it's emitted
+    pub post_trigger_ms: u64,
+}
+
+/// Config for writing logs to a file which is rotated by size, by age, or both
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFileConfig {
+    /// The path of the log file. Missing parent directories are created
+    pub path: String,
+
+    /// The size in bytes after which the log file is rotated. If this is `None`, the file isn't rotated by size
+    #[serde(default)]
+    pub max_size_bytes: Option<u64>,
+
+    /// The time after which the log file is rotated, measured from when it was opened.
+    /// If this is `None`, the file isn't rotated by age
+    #[serde(default)]
+    pub rotation_interval_ms: Option<u64>,
+
+    /// The number of rotated files to keep, which are named after the log file with the suffixes `.1`, `.2`, and so on.
+    /// If this is 0, the log file is truncated when it's rotated
+    #[serde(default)]
+    pub max_rotated_files: usize,
+}
+
+/// Config for the status signals which report Freyja's own operational status to the cloud
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusSignalsConfig {
+    /// The interval at which the status signals are emitted, which must not be 0
+    pub interval_ms: NonZeroU64,
+
+    /// The metadata of every status signal, which identifies this Freyja instance in the cloud
+    /// and chooses the cloud adapters that the status signals are routed to
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Config for suppressing the emission of designated signals while the vehicle is inside a private zone
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeofencesConfig {
+    /// The id of the signal with the vehicle's latitude in degrees
+    pub latitude_signal_id: String,
+
+    /// The id of the signal with the vehicle's longitude in degrees
+    pub longitude_signal_id: String,
+
+    /// The private zones
+    pub zones: Vec<GeofenceConfig>,
+
+    /// Whether the designated signals are emitted while the vehicle's location is unknown.
+    /// By default they aren't, since the vehicle could be inside a private zone
+    #[serde(default)]
+    pub emit_when_location_unknown: bool,
+}
+
+/// Config for a private zone
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeofenceConfig {
+    /// The name of the zone, which is included in the logs
+    pub name: String,
+
+    /// The area covered by the zone
+    pub area: GeofenceArea,
+
+    /// The ids of the signals which aren't emitted while the vehicle is inside the zone
+    pub suppressed_signal_ids: HashSet<String>,
+}
+
+/// The area covered by a private zone
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GeofenceArea {
+    /// The area within a distance of a center point
+    Circle { center: Coordinates, radius_m: f64 },
+
+    /// The area inside a polygon, whose vertices are listed in order.
+    /// The edges are straight lines in latitude and longitude, which is accurate enough for zones up to a few kilometers across
+    Polygon { vertices: Vec<Coordinates> },
+}
+
+/// A location on the Earth's surface
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    /// The latitude in degrees
+    pub latitude: f64,
+
+    /// The longitude in degrees
+    pub longitude: f64,
+}
+
+/// Config for the self-test signal, which Freyja serves with its built-in loopback data adapter
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfTestConfig {
+    /// The interval at which the self-test signal is emitted
+    pub interval_ms: u64,
+
+    /// The target metadata of the self-test signal, which identifies it in the cloud
+    /// and chooses the cloud adapters it's routed to
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Config for the last-gasp message which is sent to the cloud when Freyja stops because of a panic
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastGaspConfig {
+    /// The metadata of the message, which identifies it in the cloud and chooses the cloud adapters it's routed to.
+    /// Freyja adds the component which panicked, the signal it was processing, and the location of the panic
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Actions the watchdog can take when a component stalls
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StallAction {
+    /// The stalled component is aborted and started again
+    #[default]
+    Restart,
+
+    /// Freyja exits with a distinct exit code so that a supervisor can restart the whole process
+    Exit,
+}
+
+/// A rule for choosing which cloud adapters a signal is emitted to
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CloudRoute {
+    /// The target metadata that a signal must have for this route to apply.
+    /// Every entry must match, so a route without any entries applies to every signal
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+
+    /// The kinds of the cloud adapters that matching signals are emitted to.
+    /// If this is empty, matching signals are not emitted
+    pub cloud_adapters: Vec<String>,
+}
+
+/// Config for limiting how often values are requested from each provider.
+/// Each provider, identified by its endpoint URI, gets its own budget.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProviderRequestBudgets {
+    /// The budget for providers without an override. If this is `None`, these providers are not limited
+    #[serde(default)]
+    pub default: Option<RequestBudgetConfig>,
+
+    /// Budgets for specific providers, keyed by provider URI
+    #[serde(default)]
+    pub overrides: HashMap<String, RequestBudgetConfig>,
+}
+
+impl ProviderRequestBudgets {
+    /// Gets the budget for a provider, or `None` if the provider is not limited
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider's URI
+    pub fn get(&self, provider_uri: &str) -> Option<&RequestBudgetConfig> {
+        self.overrides.get(provider_uri).or(self.default.as_ref())
+    }
+}
+
+/// Config for the request budget of a single provider
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RequestBudgetConfig {
+    /// The sustained number of requests per second that can be sent to the provider
+    pub max_requests_per_second: f64,
+
+    /// The number of requests that can be sent at once after the provider has been idle
+    pub burst: u32,
+}
+
+/// Modes for when the emitter requests new signal values from data adapters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValueRequestMode {
+    /// A new value is requested when a signal is emitted and will be used for the next emission.
+    /// This means that emitted values can be up to one emission interval old.
+    #[default]
+    AfterEmission,
+
+    /// New values are requested when signals are due for emission,
+    /// and the emitter waits for signals with the `Get` operation to receive a new value before emitting them.
+    /// If a value does not arrive before the timeout, the cached value is emitted instead.
+    AwaitFresh {
+        /// The maximum time to wait for new values
+        timeout_ms: u64,
+    },
+
+    /// New values are requested ahead of each signal's next emission by the lead time.
+    /// If the lead time is not shorter than a signal's emission interval,
+    /// the signal's values are requested right after it's emitted instead.
+    Prefetch {
+        /// How long before each emission new values are requested
+        lead_time_ms: u64,
+    },
+}
+
+/// Modes for when the emitter records that a value was emitted.
+/// Recording a value updates the signal's `last_emitted_value` and schedules its next emission
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmissionAcknowledgementMode {
+    /// Values are recorded once the cloud adapter returns without an error.
+    /// A value which the adapter sends later and fails to deliver, such as in a batch, is lost,
+    /// and it isn't emitted again if the signal is only emitted when its value changes.
+    #[default]
+    AtSend,
+
+    /// Values which the cloud adapter sends later are recorded once the cloud accepts them,
+    /// and the signal isn't emitted again while it waits.
+    /// If the value is rejected or isn't acknowledged before the timeout, the signal is emitted again on the next iteration.
+    AtAcknowledgement {
+        /// The maximum time to wait for the acknowledgement
+        timeout_ms: u64,
+    },
+}
+
+/// Config for running Freyja with a simulated clock
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    /// The factor by which simulated time runs faster than real time
+    pub speedup: f64,
+}
+
+/// Policies for merging the mappings returned by multiple mapping adapters.
+/// Mapping adapters are ordered according to the order in which they were provided to Freyja.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MappingMergePolicy {
+    /// All mappings are merged together.
+    /// If multiple adapters provide a mapping for the same signal, the one from the last adapter is used.
+    /// This is useful for layering overrides on top of a baseline mapping.
+    #[default]
+    LastWins,
+
+    /// All mappings are merged together.
+    /// If multiple adapters provide a mapping for the same signal, the one from the first adapter is used.
+    FirstWins,
+
+    /// No merging is performed.
+    /// The mapping from the first adapter that successfully returns a mapping is used,
+    /// and later adapters are only consulted as fallbacks.
+    FirstAvailable,
+}
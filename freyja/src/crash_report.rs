@@ -0,0 +1,358 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    future::Future,
+    panic,
+    sync::{Mutex, Once},
+    time::Duration,
+};
+
+use serde::Serialize;
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
+
+use freyja_common::cloud_adapter::{
+    CloudAdapterError, CloudMessageRequest, CloudMessageResponse, CRASH_COMPONENT_METADATA_KEY,
+    CRASH_LOCATION_METADATA_KEY, CRASH_SIGNAL_ID_METADATA_KEY,
+};
+
+use crate::{config::LastGaspConfig, freyja_handle::FreyjaComponent};
+
+/// How long to wait for the last-gasp message to be sent before giving up
+const LAST_GASP_TIMEOUT: Duration = Duration::from_secs(5);
+
+tokio::task_local! {
+    /// What the component running in the current task is doing, so that it can be included in panic reports
+    static CRASH_CONTEXT: CrashContext;
+}
+
+/// The most recent panic, which is kept until it's reported to the cloud
+static LAST_PANIC: Mutex<Option<PanicReport>> = Mutex::new(None);
+
+/// Ensures that the panic hook is only installed once
+static INSTALL_PANIC_HOOK: Once = Once::new();
+
+/// A function which sends a message to the cloud with the emitter's cloud adapters
+pub(crate) type StatusSender = Box<
+    dyn Fn(CloudMessageRequest) -> JoinHandle<Result<CloudMessageResponse, CloudAdapterError>>
+        + Send
+        + Sync,
+>;
+
+/// What a component is doing
+struct CrashContext {
+    /// The component
+    component: FreyjaComponent,
+
+    /// The id of the signal that the component is processing, if any
+    signal_id: RefCell<Option<String>>,
+}
+
+/// Marks the signal that the current component is processing until it's dropped
+#[must_use]
+pub(crate) struct SignalScope;
+
+impl Drop for SignalScope {
+    fn drop(&mut self) {
+        let _ = CRASH_CONTEXT.try_with(|context| context.signal_id.borrow_mut().take());
+    }
+}
+
+/// Information about a panic
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PanicReport {
+    /// The component which panicked, or `None` if the panic happened outside of a component
+    pub component: Option<String>,
+
+    /// The id of the signal that the component was processing, if known
+    pub signal_id: Option<String>,
+
+    /// The panic message
+    pub message: String,
+
+    /// The source location of the panic, if known
+    pub location: Option<String>,
+
+    /// The name of the thread which panicked, if it has one
+    pub thread: Option<String>,
+}
+
+impl PanicReport {
+    /// Creates a report for a panic in the current task
+    ///
+    /// # Arguments
+    /// - `payload`: the panic payload
+    /// - `location`: the source location of the panic, if known
+    fn new(payload: &(dyn Any + Send), location: Option<String>) -> Self {
+        // Panics created with a message have a string payload
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("Box<dyn Any>"));
+
+        let (component, signal_id) = current_context();
+
+        Self {
+            component: component.map(|c| format!("{c:?}")),
+            signal_id,
+            message,
+            location,
+            thread: std::thread::current().name().map(String::from),
+        }
+    }
+}
+
+/// Sends a status message to the cloud when Freyja stops because of a panic
+pub(crate) struct LastGasp {
+    /// The last-gasp config
+    config: LastGaspConfig,
+
+    /// Sends the message to the cloud
+    send: StatusSender,
+}
+
+impl LastGasp {
+    /// Creates a new LastGasp
+    ///
+    /// # Arguments
+    /// - `config`: the last-gasp config
+    /// - `send`: sends the message to the cloud
+    pub fn new(config: LastGaspConfig, send: StatusSender) -> Self {
+        Self { config, send }
+    }
+
+    /// Creates the last-gasp message for a panic.
+    /// The message contains the configured metadata and describes where the panic happened,
+    /// and its value is the panic message
+    ///
+    /// # Arguments
+    /// - `report`: the panic report
+    fn message(&self, report: &PanicReport) -> CloudMessageRequest {
+        let mut metadata: HashMap<String, String> = self.config.metadata.clone();
+        for (key, value) in [
+            (CRASH_COMPONENT_METADATA_KEY, &report.component),
+            (CRASH_SIGNAL_ID_METADATA_KEY, &report.signal_id),
+            (CRASH_LOCATION_METADATA_KEY, &report.location),
+        ] {
+            if let Some(value) = value {
+                metadata.insert(key.to_string(), value.clone());
+            }
+        }
+
+        CloudMessageRequest {
+            metadata,
+            signal_value: report.message.as_str().into(),
+            numeric_value: None,
+            signal_timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+        }
+    }
+
+    /// Sends the last-gasp message for a panic, giving up if it takes too long
+    ///
+    /// # Arguments
+    /// - `report`: the panic report
+    pub async fn send(&self, report: &PanicReport) {
+        log::info!("Sending last-gasp message to the cloud");
+
+        let mut join_handle = (self.send)(self.message(report));
+        match tokio::time::timeout(LAST_GASP_TIMEOUT, &mut join_handle).await {
+            Ok(Ok(Ok(_))) => {}
+            Ok(Ok(Err(e))) => log::error!("Could not send last-gasp message: {e:?}"),
+            Ok(Err(e)) => log::error!("Could not send last-gasp message: {e:?}"),
+            Err(_) => {
+                join_handle.abort();
+                log::error!("Timed out sending last-gasp message");
+            }
+        }
+    }
+}
+
+/// Runs a component's future with a context that's included in reports of panics in the component
+///
+/// # Arguments
+/// - `component`: the component
+/// - `future`: the component's future
+pub(crate) fn in_component<F: Future>(
+    component: FreyjaComponent,
+    future: F,
+) -> impl Future<Output = F::Output> {
+    CRASH_CONTEXT.scope(
+        CrashContext {
+            component,
+            signal_id: RefCell::new(None),
+        },
+        future,
+    )
+}
+
+/// Marks the signal that the current component is processing, so that it's included in reports of panics.
+/// The signal is no longer marked once the returned scope is dropped.
+/// This has no effect outside of a component.
+///
+/// # Arguments
+/// - `signal_id`: the id of the signal
+pub(crate) fn enter_signal(signal_id: &str) -> SignalScope {
+    let _ = CRASH_CONTEXT
+        .try_with(|context| *context.signal_id.borrow_mut() = Some(signal_id.to_string()));
+
+    SignalScope
+}
+
+/// Gets the component running in the current task and the signal it's processing, if any
+fn current_context() -> (Option<FreyjaComponent>, Option<String>) {
+    CRASH_CONTEXT
+        .try_with(|context| {
+            // The signal id can't be borrowed if the panic happened while it was being set
+            let signal_id = context
+                .signal_id
+                .try_borrow()
+                .ok()
+                .and_then(|signal_id| signal_id.clone());
+            (Some(context.component), signal_id)
+        })
+        .unwrap_or((None, None))
+}
+
+/// Installs a panic hook which logs a structured report of the panic and flushes the logs
+/// before running the previously installed hook.
+/// The report is kept so that it can be sent to the cloud as a last-gasp message if Freyja stops because of the panic.
+/// Installing the hook more than once has no effect.
+pub(crate) fn install_panic_hook() {
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+            let report = PanicReport::new(info.payload(), location);
+            match serde_json::to_string(&report) {
+                Ok(json) => log::error!("Freyja panicked: {json}"),
+                Err(_) => log::error!("Freyja panicked: {report:?}"),
+            }
+            log::logger().flush();
+
+            if let Ok(mut last_panic) = LAST_PANIC.lock() {
+                *last_panic = Some(report);
+            }
+
+            previous_hook(info);
+        }));
+    });
+}
+
+/// Takes the report of the most recent panic if it happened in the provided component.
+/// A report of a panic elsewhere is kept.
+///
+/// # Arguments
+/// - `component`: the component whose panic should be taken
+pub(crate) fn take_panic_report(component: FreyjaComponent) -> Option<PanicReport> {
+    let component = format!("{component:?}");
+    let mut last_panic = LAST_PANIC.lock().ok()?;
+    match last_panic.as_ref() {
+        Some(report) if report.component.as_ref() == Some(&component) => last_panic.take(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod crash_report_tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn current_context_includes_component_and_signal() {
+        let context = in_component(FreyjaComponent::Emitter, async {
+            let before = current_context();
+            let during = {
+                let _scope = enter_signal("speed");
+                current_context()
+            };
+            let after = current_context();
+
+            (before, during, after)
+        })
+        .await;
+
+        assert_eq!(context.0, (Some(FreyjaComponent::Emitter), None));
+        assert_eq!(
+            context.1,
+            (Some(FreyjaComponent::Emitter), Some(String::from("speed")))
+        );
+        assert_eq!(context.2, (Some(FreyjaComponent::Emitter), None));
+    }
+
+    #[test]
+    fn current_context_is_empty_outside_of_component() {
+        let _scope = enter_signal("speed");
+        assert_eq!(current_context(), (None, None));
+    }
+
+    #[test]
+    fn take_panic_report_only_takes_report_of_component() {
+        let report = PanicReport {
+            component: Some(String::from("Cartographer")),
+            signal_id: None,
+            message: String::from("oops"),
+            location: None,
+            thread: None,
+        };
+        *LAST_PANIC.lock().unwrap() = Some(report.clone());
+
+        assert_eq!(take_panic_report(FreyjaComponent::Emitter), None);
+        assert_eq!(
+            take_panic_report(FreyjaComponent::Cartographer),
+            Some(report)
+        );
+        assert_eq!(take_panic_report(FreyjaComponent::Cartographer), None);
+    }
+
+    #[tokio::test]
+    async fn last_gasp_sends_message_with_report() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sent_clone = sent.clone();
+        let uut = LastGasp::new(
+            LastGaspConfig {
+                metadata: HashMap::from([(String::from("status"), String::from("crashed"))]),
+            },
+            Box::new(move |message: CloudMessageRequest| {
+                sent_clone.lock().unwrap().push(message);
+                tokio::spawn(async { Ok(CloudMessageResponse::default()) })
+            }),
+        );
+
+        let report = PanicReport {
+            component: Some(String::from("Emitter")),
+            signal_id: None,
+            message: String::from("oops"),
+            location: Some(String::from("src/emitter.rs:1:1")),
+            thread: None,
+        };
+        uut.send(&report).await;
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(&*sent[0].signal_value, "oops");
+        assert_eq!(
+            sent[0].metadata,
+            HashMap::from([
+                (String::from("status"), String::from("crashed")),
+                (
+                    CRASH_COMPONENT_METADATA_KEY.to_string(),
+                    String::from("Emitter")
+                ),
+                (
+                    CRASH_LOCATION_METADATA_KEY.to_string(),
+                    String::from("src/emitter.rs:1:1")
+                ),
+            ])
+        );
+    }
+}
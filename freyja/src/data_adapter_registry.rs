@@ -0,0 +1,110 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use log::info;
+use tokio::sync::Mutex;
+
+use freyja_common::{
+    data_adapter::DataAdapterFactory,
+    data_adapter_selector::{DataAdapterSelector, DataAdapterSelectorError},
+};
+
+/// A handle for registering and deregistering data adapter factories while Freyja is running.
+/// After each change, entities whose preferred factory has changed are migrated to new adapters
+/// and the adapters that served them are stopped.
+#[derive(Clone)]
+pub struct DataAdapterRegistry {
+    /// The data adapter selector
+    data_adapter_selector: Arc<Mutex<dyn DataAdapterSelector + Send + Sync>>,
+}
+
+impl DataAdapterRegistry {
+    /// Creates a new registry for a data adapter selector
+    ///
+    /// # Arguments
+    /// - `data_adapter_selector`: the data adapter selector
+    pub(crate) fn new(
+        data_adapter_selector: Arc<Mutex<dyn DataAdapterSelector + Send + Sync>>,
+    ) -> Self {
+        Self {
+            data_adapter_selector,
+        }
+    }
+
+    /// Registers a data adapter factory, then migrates the entities which it's now preferred for
+    ///
+    /// # Arguments
+    /// - `factory`: the factory to register
+    pub async fn register(
+        &self,
+        factory: Box<dyn DataAdapterFactory + Send + Sync>,
+    ) -> Result<(), DataAdapterSelectorError> {
+        let kind = factory.kind();
+        let mut data_adapter_selector = self.data_adapter_selector.lock().await;
+        data_adapter_selector.register(factory)?;
+
+        info!("Registered a {kind} data adapter factory");
+        data_adapter_selector.migrate_entities().await
+    }
+
+    /// Deregisters every data adapter factory of the given kind, then migrates the entities which they served.
+    /// Entities which no other factory supports are removed.
+    ///
+    /// # Arguments
+    /// - `factory_kind`: the kind of factory to deregister, as returned by `DataAdapterFactory::kind`
+    pub async fn deregister(&self, factory_kind: &str) -> Result<(), DataAdapterSelectorError> {
+        let mut data_adapter_selector = self.data_adapter_selector.lock().await;
+        data_adapter_selector.deregister(factory_kind)?;
+
+        info!("Deregistered the {factory_kind} data adapter factories");
+        data_adapter_selector.migrate_entities().await
+    }
+
+    /// Moves registered entities to new adapters if their preferred factory has changed.
+    /// `register` and `deregister` already do this, so this is only needed to retry entities which failed to migrate.
+    pub async fn migrate_entities(&self) -> Result<(), DataAdapterSelectorError> {
+        self.data_adapter_selector
+            .lock()
+            .await
+            .migrate_entities()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod data_adapter_registry_tests {
+    use super::*;
+
+    use freyja_common::{
+        data_adapter_selector::DataAdapterSelectorErrorKind, signal_store::SignalStore,
+    };
+
+    use crate::{
+        config::ProviderRequestBudgets, data_adapter_selector_impl::DataAdapterSelectorImpl,
+        self_test::LoopbackDataAdapterFactory,
+    };
+
+    fn create_registry() -> DataAdapterRegistry {
+        DataAdapterRegistry::new(Arc::new(Mutex::new(DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        ))))
+    }
+
+    #[tokio::test]
+    async fn deregister_removes_registered_factories() {
+        let uut = create_registry();
+        let factory = LoopbackDataAdapterFactory {};
+        let kind = factory.kind();
+
+        assert!(uut.register(Box::new(factory)).await.is_ok());
+        assert!(uut.deregister(&kind).await.is_ok());
+
+        let result = uut.deregister(&kind).await;
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterSelectorErrorKind::FactoryNotFound));
+    }
+}
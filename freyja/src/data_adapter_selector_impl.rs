@@ -3,32 +3,122 @@
 // SPDX-License-Identifier: MIT
 
 use std::{
-    collections::{hash_map::Entry, HashMap},
-    sync::Arc,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
 };
 
 use async_trait::async_trait;
-use log::debug;
-use tokio::sync::Mutex;
+use log::{debug, info, warn};
+use tokio::{sync::Mutex, time::Instant};
 
 use freyja_common::{
-    data_adapter::{DataAdapter, DataAdapterFactory, EntityRegistration},
+    data_adapter::{
+        DataAdapter, DataAdapterCapabilities, DataAdapterError, DataAdapterFactory,
+        EntityRegistration,
+    },
     data_adapter_selector::{
-        DataAdapterSelector, DataAdapterSelectorError, DataAdapterSelectorErrorKind,
+        DataAdapterInfo, DataAdapterSelector, DataAdapterSelectorError,
+        DataAdapterSelectorErrorKind,
     },
-    entity::Entity,
+    entity::{Entity, EntityEndpoint},
+    namespace,
+    signal::EmissionPolicy,
     signal_store::SignalStore,
+    traffic_trace::TrafficRecorder,
+};
+
+use crate::{
+    adapter_timeout::with_timeout,
+    config::{ProviderRequestBudgets, RequestBudgetConfig},
+    registration_metrics::RegistrationMetrics,
+    request_budget::RequestBudget,
 };
 
 const LOOPBACK_MAX: u16 = 10;
 
 /// Represents the state of the DataAdapterSelector and allows for simplified access through a mutex
 struct DataAdapterSelectorState {
-    /// A map of entity uris to data adapters
+    /// A map of entity uris to data adapters.
+    /// The uris of adapters serving namespaced entities are qualified with the namespace
     data_adapters: HashMap<String, Arc<dyn DataAdapter + Send + Sync>>,
 
-    /// A map of entity id to provider uri
-    entity_map: HashMap<String, String>,
+    /// A map of entity id to information about the adapter serving it, including the provider uri
+    entity_map: HashMap<String, DataAdapterInfo>,
+
+    /// A map of provider uris to their request budgets.
+    /// Budgets are created when a provider is first requested.
+    request_budgets: HashMap<String, RequestBudget>,
+
+    /// A map of entity id to the details of its registration, used for migrating entities between adapters.
+    /// Records of entities which fail to migrate are kept so that they're migrated again on the next migration
+    registrations: HashMap<String, EntityRecord>,
+
+    /// A map of adapter uris to the capabilities that the adapters reported when they were created
+    capabilities: HashMap<String, DataAdapterCapabilities>,
+}
+
+impl DataAdapterSelectorState {
+    /// Checks whether an existing adapter's capabilities allow it to serve an entity with an endpoint.
+    /// Returns the reason if they don't
+    ///
+    /// # Arguments
+    /// - `adapter_uri`: the uri of the adapter
+    /// - `entity_id`: the qualified id of the entity, which doesn't count towards the adapter's subscriptions
+    /// - `endpoint`: the endpoint that the entity would be registered with
+    fn check_capabilities(
+        &self,
+        adapter_uri: &str,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<(), String> {
+        let capabilities = match self.capabilities.get(adapter_uri) {
+            Some(capabilities) => capabilities,
+            None => return Ok(()),
+        };
+
+        if !capabilities.supports(endpoint) {
+            return Err(format!(
+                "it doesn't support the operations {:?}",
+                endpoint.operations
+            ));
+        }
+
+        if let Some(max_subscriptions) = capabilities.max_subscriptions {
+            let subscriptions = self
+                .registrations
+                .iter()
+                .filter(|(id, record)| {
+                    id.as_str() != entity_id
+                        && record.provider_uris.last().map(String::as_str) == Some(adapter_uri)
+                })
+                .count();
+            if subscriptions >= max_subscriptions {
+                return Err(format!(
+                    "it already serves its maximum of {max_subscriptions} entities"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The details of an entity's registration with the selector
+struct EntityRecord {
+    /// The entity as originally requested, before any loopbacks
+    entity: Entity,
+
+    /// The emission policy that the entity's endpoints were scored against
+    emission_policy: EmissionPolicy,
+
+    /// The kind of the factory that was preferred for the entity when it was registered,
+    /// or `None` if the entity failed to migrate and isn't served by any adapter
+    factory_kind: Option<String>,
+
+    /// The uris of every adapter that handled the entity during registration, including loopbacks
+    provider_uris: Vec<String>,
 }
 
 /// The data adapter selector selects which data adapter to create based on protocol and operation.
@@ -42,6 +132,18 @@ pub struct DataAdapterSelectorImpl {
 
     /// The signal store used for creating the adapters
     signals: Arc<SignalStore>,
+
+    /// The limits on how often values can be requested from each provider
+    request_budget_config: ProviderRequestBudgets,
+
+    /// The maximum time to wait for a data adapter to request a value, or `None` to wait indefinitely
+    request_timeout_ms: Option<u64>,
+
+    /// The outcomes of registering entities
+    registration_metrics: Arc<RegistrationMetrics>,
+
+    /// The ids of entities whose requests are waiting for a token from their provider's request budget
+    deferred_requests: Arc<StdMutex<HashSet<String>>>,
 }
 
 impl DataAdapterSelectorImpl {
@@ -49,44 +151,252 @@ impl DataAdapterSelectorImpl {
     ///
     /// # Arguments
     /// - `signals`: the shared signal store
-    pub fn new(signals: Arc<SignalStore>) -> Self {
+    /// - `request_budget_config`: the limits on how often values can be requested from each provider
+    /// - `request_timeout_ms`: the maximum time to wait for a data adapter to request a value
+    pub fn new(
+        signals: Arc<SignalStore>,
+        request_budget_config: ProviderRequestBudgets,
+        request_timeout_ms: Option<u64>,
+    ) -> Self {
         DataAdapterSelectorImpl {
             factories: Vec::new(),
             state: Mutex::new(DataAdapterSelectorState {
                 data_adapters: HashMap::new(),
                 entity_map: HashMap::new(),
+                request_budgets: HashMap::new(),
+                registrations: HashMap::new(),
+                capabilities: HashMap::new(),
             }),
             signals,
+            request_budget_config,
+            request_timeout_ms,
+            registration_metrics: Arc::new(RegistrationMetrics::default()),
+            deferred_requests: Arc::new(StdMutex::new(HashSet::new())),
         }
     }
-}
 
-#[async_trait]
-impl DataAdapterSelector for DataAdapterSelectorImpl {
-    /// Registers a `DataAdapterFactory` with this selector.
+    /// Gets the outcomes of registering entities, which are updated as entities are registered
+    pub(crate) fn registration_metrics(&self) -> Arc<RegistrationMetrics> {
+        self.registration_metrics.clone()
+    }
+
+    /// Records every value that the data adapters created by this selector receive to a trace file.
+    /// The trace can be replayed later with the file replay data adapter.
     ///
     /// # Arguments
-    /// - `factory`: the factory to register
-    fn register(
-        &mut self,
-        factory: Box<dyn DataAdapterFactory + Send + Sync>,
-    ) -> Result<(), DataAdapterSelectorError> {
-        self.factories.push(factory);
+    /// - `path`: the path of the trace file, which is replaced if it already exists
+    pub fn record_traffic(&self, path: &str) -> Result<(), DataAdapterSelectorError> {
+        let recorder = TrafficRecorder::create(path).map_err(DataAdapterSelectorError::io)?;
+        self.signals.add_value_observer(Arc::new(recorder));
+
+        info!("Recording provider traffic to {path}");
+
         Ok(())
     }
 
-    /// Updates an existing data adapter to include an entity if possible,
-    /// otherwise creates a new data adapter to handle that entity.
+    /// Sends a request for the value of an entity once its provider's request budget has a token for it.
+    /// Until then, further requests for the entity are ignored
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity to request
+    /// - `unqualified_id`: the entity id without its namespace, which the data adapter knows it by
+    /// - `data_adapter`: the data adapter that serves the entity
+    /// - `wait`: how long to wait for the token, according to the signal store's clock
+    fn defer_request(
+        &self,
+        entity_id: &str,
+        unqualified_id: &str,
+        data_adapter: Arc<dyn DataAdapter + Send + Sync>,
+        wait: Duration,
+    ) {
+        self.deferred_requests
+            .lock()
+            .unwrap()
+            .insert(entity_id.to_string());
+
+        let deferred_requests = self.deferred_requests.clone();
+        let request_timeout_ms = self.request_timeout_ms;
+        let clock = self.signals.clock();
+        let entity_id = entity_id.to_string();
+        let unqualified_id = unqualified_id.to_string();
+        tokio::spawn(async move {
+            clock.sleep(wait).await;
+            deferred_requests.lock().unwrap().remove(&entity_id);
+
+            let result = with_timeout(
+                request_timeout_ms,
+                data_adapter.send_request_to_provider(&unqualified_id),
+                DataAdapterError::communication,
+            )
+            .await;
+            if let Err(e) = result {
+                warn!("Deferred request for the value of {entity_id} failed: {e}");
+            }
+        });
+    }
+
+    /// Finds the factories which support an entity and the endpoint that each supports, in order of preference.
+    /// Factories whose endpoints score higher against the emission policy are preferred,
+    /// and factories registered later take precedence over earlier ones with equally scored endpoints.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to find factories for
+    /// - `emission_policy`: the emission policy to score the entity's endpoints against
+    fn find_factories(
+        &self,
+        entity: &Entity,
+        emission_policy: &EmissionPolicy,
+    ) -> Vec<(&(dyn DataAdapterFactory + Send + Sync), EntityEndpoint)> {
+        // Factories return the first endpoint they support, so ranking the endpoints gives each factory's best endpoint
+        let entity = rank_endpoints(entity, emission_policy);
+
+        let mut factories: Vec<_> = self
+            .factories
+            .iter()
+            .rev()
+            .filter_map(|factory| {
+                factory
+                    .is_supported(&entity)
+                    .map(|endpoint| (factory.as_ref(), endpoint))
+            })
+            .collect();
+
+        // The sort is stable, so equally scored factories stay in reverse registration order
+        factories.sort_by_key(|(_, endpoint)| Reverse(endpoint.score(emission_policy)));
+
+        factories
+    }
+
+    /// Finds the preferred factory for an entity and the endpoint that it supports
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to find a factory for
+    /// - `emission_policy`: the emission policy to score the entity's endpoints against
+    fn find_factory(
+        &self,
+        entity: &Entity,
+        emission_policy: &EmissionPolicy,
+    ) -> Option<(&(dyn DataAdapterFactory + Send + Sync), EntityEndpoint)> {
+        self.find_factories(entity, emission_policy)
+            .into_iter()
+            .next()
+    }
+
+    /// Creates and starts a data adapter for an entity with the most preferred factory whose adapter supports the entity's endpoint.
+    /// Adapters are started before their capabilities are queried. Adapters whose capabilities don't include
+    /// the endpoint's operations are stopped and discarded, and the next factory is tried instead.
+    /// Returns the adapter, the endpoint it supports, and its capabilities
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to create an adapter for
+    /// - `emission_policy`: the emission policy to score the entity's endpoints against
+    /// - `namespace`: the namespace of the entity, if any
+    async fn create_adapter(
+        &self,
+        entity: &Entity,
+        emission_policy: &EmissionPolicy,
+        namespace: Option<&str>,
+    ) -> Result<
+        (
+            Arc<dyn DataAdapter + Send + Sync>,
+            EntityEndpoint,
+            DataAdapterCapabilities,
+        ),
+        DataAdapterSelectorError,
+    > {
+        // Adapters for a namespace use unqualified entity ids with a namespaced view of the signal store
+        let signals = match namespace {
+            Some(namespace) => Arc::new(self.signals.namespaced(namespace)),
+            None => self.signals.clone(),
+        };
+
+        for (factory, endpoint) in self.find_factories(entity, emission_policy) {
+            let adapter = factory
+                .create_adapter(&endpoint.uri, signals.clone())
+                .map_err(DataAdapterSelectorError::data_adapter_error)?;
+
+            adapter
+                .start()
+                .await
+                .map_err(DataAdapterSelectorError::data_adapter_error)?;
+
+            let capabilities = adapter.get_capabilities().await.unwrap_or_else(|e| {
+                warn!(
+                    "Could not get the capabilities of the {} data adapter for {}: {e}",
+                    adapter.kind(),
+                    endpoint.uri
+                );
+                DataAdapterCapabilities::default()
+            });
+
+            if capabilities.supports(&endpoint) {
+                return Ok((adapter, endpoint, capabilities));
+            }
+
+            debug!(
+                "The {} data adapter for {} doesn't support the operations {:?}. Trying the next factory...",
+                adapter.kind(),
+                endpoint.uri,
+                endpoint.operations
+            );
+
+            if let Err(e) = adapter.stop().await {
+                warn!(
+                    "Failed to stop the discarded {} data adapter for {}: {e}",
+                    adapter.kind(),
+                    endpoint.uri
+                );
+            }
+        }
+
+        Err(DataAdapterSelectorErrorKind::OperationNotSupported.into())
+    }
+
+    /// Creates the information recorded when an entity is registered with a data adapter
+    ///
+    /// # Arguments
+    /// - `data_adapter`: the adapter that registered the entity
+    /// - `endpoint`: the endpoint that the entity was registered with
+    fn create_adapter_info(
+        &self,
+        data_adapter: &Arc<dyn DataAdapter + Send + Sync>,
+        endpoint: &EntityEndpoint,
+    ) -> DataAdapterInfo {
+        DataAdapterInfo {
+            adapter_kind: data_adapter.kind(),
+            endpoint: endpoint.uri.clone(),
+            protocol: endpoint.protocol.clone(),
+            registered_at: self.signals.clock().now(),
+        }
+    }
+
+    /// Registers an entity with an existing or new data adapter, following any loopbacks that the adapters request.
+    /// Returns the kind of the adapter which registered the entity
     ///
     /// # Arguments
     /// - `entity`: the entity that the adapter should handle
-    async fn create_or_update_adapter(
+    /// - `emission_policy`: the emission policy of the signal whose source is the entity
+    async fn register_entity(
         &self,
         entity: &Entity,
-    ) -> Result<(), DataAdapterSelectorError> {
+        emission_policy: &EmissionPolicy,
+    ) -> Result<String, DataAdapterSelectorError> {
         // Keeps track of max depth loopback can reach.
         let mut loopback_count = 0;
-        let mut current_entity = entity.to_owned();
+        let (namespace, entity_id) = namespace::split(&entity.id);
+        let mut current_entity = rank_endpoints(
+            &Entity {
+                id: entity_id.to_owned(),
+                ..entity.to_owned()
+            },
+            emission_policy,
+        );
+
+        // Keeps track of how the entity was registered so that it can be migrated if the preferred factory changes
+        let factory_kind = self
+            .find_factory(entity, emission_policy)
+            .map(|(factory, _)| factory.kind());
+        let mut provider_uris = Vec::new();
 
         // The selector will loop (up to max attempts) until a data adapter registers the entity.
         // Will break out of loop on an error.
@@ -96,8 +406,19 @@ impl DataAdapterSelector for DataAdapterSelectorImpl {
             // If a data adapter already exists for one of this entity's uris,
             // then we notify that adapter to include this new entity
             for endpoint in current_entity.endpoints.iter() {
-                if let Some(data_adapter) = state.data_adapters.get(&endpoint.uri) {
-                    debug!("A data adapter for {} already exists", &endpoint.uri);
+                let adapter_uri = namespace::qualify(namespace, &endpoint.uri);
+                if let Some(data_adapter) = state.data_adapters.get(&adapter_uri) {
+                    debug!("A data adapter for {adapter_uri} already exists");
+
+                    let id = namespace::qualify(namespace, &current_entity.id);
+                    if let Err(reason) = state.check_capabilities(&adapter_uri, &id, endpoint) {
+                        debug!(
+                            "The data adapter for {adapter_uri} can't serve {id} because {reason}"
+                        );
+                        continue;
+                    }
+
+                    provider_uris.push(adapter_uri);
 
                     let entity_registration = data_adapter
                         .register_entity(&current_entity.id, endpoint)
@@ -108,16 +429,24 @@ impl DataAdapterSelector for DataAdapterSelectorImpl {
                         EntityRegistration::Registered => {
                             // There was a successful registration of the entity.
                             // The entity is added to the map and the selector returns.
-                            state.entity_map.insert(
-                                String::from(&current_entity.id),
-                                String::from(&endpoint.uri),
+                            let adapter_info = self.create_adapter_info(data_adapter, endpoint);
+                            let adapter_kind = adapter_info.adapter_kind.clone();
+                            state.entity_map.insert(id.clone(), adapter_info);
+                            state.registrations.insert(
+                                id,
+                                EntityRecord {
+                                    entity: entity.to_owned(),
+                                    emission_policy: emission_policy.to_owned(),
+                                    factory_kind,
+                                    provider_uris,
+                                },
                             );
 
-                            return Ok(());
+                            return Ok(adapter_kind);
                         }
                         EntityRegistration::Loopback(new_entity) => {
                             // The adapter is requesting a loopback with new entity information
-                            current_entity = new_entity.to_owned();
+                            current_entity = rank_endpoints(&new_entity, emission_policy);
                             loopback_count += 1;
 
                             debug!("Loopback requested with: {current_entity:?}. Loopback count is: {loopback_count}.");
@@ -128,28 +457,24 @@ impl DataAdapterSelector for DataAdapterSelectorImpl {
                 }
             }
 
-            // If there's not a data adapter we can reuse, find the right factory to create a new one
-            let (data_adapter, endpoint) = {
-                let mut result = None;
-                for factory in self.factories.iter() {
-                    if let Some(endpoint) = factory.is_supported(&current_entity) {
-                        let adapter = factory
-                            .create_adapter(&endpoint.uri, self.signals.clone())
-                            .map_err(DataAdapterSelectorError::data_adapter_error)?;
-                        result = Some((adapter, endpoint));
-                    }
-                }
-
-                result.ok_or::<DataAdapterSelectorError>(
-                    DataAdapterSelectorErrorKind::OperationNotSupported.into(),
-                )?
+            // If there's not a data adapter we can reuse, find the right factory to create a new one.
+            // Endpoints whose adapters can't serve the entity are skipped, since each uri has only one adapter
+            let unserved_entity = Entity {
+                endpoints: current_entity
+                    .endpoints
+                    .iter()
+                    .filter(|endpoint| {
+                        !state
+                            .data_adapters
+                            .contains_key(&namespace::qualify(namespace, &endpoint.uri))
+                    })
+                    .cloned()
+                    .collect(),
+                ..current_entity.clone()
             };
-
-            // Start the data adapter
-            data_adapter
-                .start()
-                .await
-                .map_err(DataAdapterSelectorError::data_adapter_error)?;
+            let (data_adapter, endpoint, capabilities) = self
+                .create_adapter(&unserved_entity, emission_policy, namespace)
+                .await?;
 
             // Register the entity with the data adapter
             let entity_registration = data_adapter
@@ -158,24 +483,36 @@ impl DataAdapterSelector for DataAdapterSelectorImpl {
                 .map_err(DataAdapterSelectorError::data_adapter_error)?;
 
             // As long as there was not an error with registration, add the adapter to the map
+            let adapter_info = self.create_adapter_info(&data_adapter, &endpoint);
+            let adapter_kind = adapter_info.adapter_kind.clone();
+            let adapter_uri = namespace::qualify(namespace, &endpoint.uri);
             state
                 .data_adapters
-                .insert(endpoint.uri.clone(), data_adapter);
+                .insert(adapter_uri.clone(), data_adapter);
+            state.capabilities.insert(adapter_uri.clone(), capabilities);
+            provider_uris.push(adapter_uri);
 
             match entity_registration {
                 EntityRegistration::Registered => {
                     // There was a successful registration of the entity.
                     // The entity is added to the map and the selector returns.
-                    state.entity_map.insert(
-                        String::from(&current_entity.id),
-                        String::from(&endpoint.uri),
+                    let id = namespace::qualify(namespace, &current_entity.id);
+                    state.entity_map.insert(id.clone(), adapter_info);
+                    state.registrations.insert(
+                        id,
+                        EntityRecord {
+                            entity: entity.to_owned(),
+                            emission_policy: emission_policy.to_owned(),
+                            factory_kind,
+                            provider_uris,
+                        },
                     );
 
-                    return Ok(());
+                    return Ok(adapter_kind);
                 }
                 EntityRegistration::Loopback(new_entity) => {
                     // The adapter is requesting a loopback with new entity information
-                    current_entity = new_entity.to_owned();
+                    current_entity = rank_endpoints(&new_entity, emission_policy);
                     loopback_count += 1;
 
                     debug!("Loopback requested with: {current_entity:?}. Loopback count is: {loopback_count}.");
@@ -189,8 +526,102 @@ impl DataAdapterSelector for DataAdapterSelectorImpl {
             "Unable to select a data adapter: reached maximum loopback attempts ({LOOPBACK_MAX})."
         )))
     }
+}
 
-    /// Requests that the value of an entity be published as soon as possible
+#[async_trait]
+impl DataAdapterSelector for DataAdapterSelectorImpl {
+    /// Registers a `DataAdapterFactory` with this selector.
+    /// Factories registered later take precedence over earlier ones.
+    ///
+    /// # Arguments
+    /// - `factory`: the factory to register
+    fn register(
+        &mut self,
+        factory: Box<dyn DataAdapterFactory + Send + Sync>,
+    ) -> Result<(), DataAdapterSelectorError> {
+        self.factories.push(factory);
+        Ok(())
+    }
+
+    /// Deregisters every `DataAdapterFactory` of the given kind from this selector.
+    /// Adapters created by these factories keep running until `migrate_entities` is called, which stops them.
+    ///
+    /// # Arguments
+    /// - `factory_kind`: the kind of factory to deregister
+    fn deregister(&mut self, factory_kind: &str) -> Result<(), DataAdapterSelectorError> {
+        let factory_count = self.factories.len();
+        self.factories
+            .retain(|factory| factory.kind() != factory_kind);
+
+        if self.factories.len() == factory_count {
+            Err(DataAdapterSelectorError::factory_not_found(format!(
+                "No factory of kind {factory_kind} is registered"
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Updates an existing data adapter to include an entity if possible,
+    /// otherwise creates a new data adapter to handle that entity.
+    /// The entity's endpoints are scored against the emission policy, and better scoring endpoints are preferred
+    /// both when reusing an existing adapter and when creating a new one.
+    /// Adapters are only used if their capabilities allow them to serve the entity.
+    /// If the entity id is qualified with a namespace, the entity is served by adapters for that namespace,
+    /// which receive the unqualified entity id.
+    /// The outcome and any endpoints which no factory supports are recorded in the registration metrics.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity that the adapter should handle
+    /// - `emission_policy`: the emission policy of the signal whose source is the entity
+    async fn create_or_update_adapter(
+        &self,
+        entity: &Entity,
+        emission_policy: &EmissionPolicy,
+    ) -> Result<(), DataAdapterSelectorError> {
+        let start = Instant::now();
+
+        // Endpoints which no factory supports point to mismatches between the mapping and the available providers
+        for endpoint in entity.endpoints.iter() {
+            let endpoint_entity = Entity {
+                endpoints: vec![endpoint.clone()],
+                ..entity.to_owned()
+            };
+            if self
+                .factories
+                .iter()
+                .all(|factory| factory.is_supported(&endpoint_entity).is_none())
+            {
+                debug!(
+                    "No data adapter supports the {} endpoint {} of {}",
+                    endpoint.protocol, endpoint.uri, entity.id
+                );
+                self.registration_metrics.record_rejected_endpoint(
+                    &entity.id,
+                    &endpoint.protocol,
+                    &endpoint.uri,
+                );
+            }
+        }
+
+        match self.register_entity(entity, emission_policy).await {
+            Ok(adapter_kind) => {
+                self.registration_metrics
+                    .record_registered(&adapter_kind, start.elapsed());
+                Ok(())
+            }
+            Err(e) => {
+                self.registration_metrics.record_failed_registration();
+                Err(e)
+            }
+        }
+    }
+
+    /// Requests that the value of an entity be published as soon as possible.
+    /// If the provider's request budget is exhausted, the request is deferred until the budget has a token for it,
+    /// so requests are sent in the order they were made and every entity eventually gets its turn.
+    /// The budget is refilled according to the signal store's clock.
+    /// Returns a `RateLimited` error without contacting the provider if the budget is exhausted and never refills.
     ///
     /// # Arguments
     /// - `entity_id`: the entity to request
@@ -203,37 +634,327 @@ impl DataAdapterSelector for DataAdapterSelectorImpl {
             .ok_or(DataAdapterSelectorError::entity_not_found(format!(
                 "Unable to retrieve entity uri for {entity_id}"
             )))?
-            .to_owned();
+            .endpoint
+            .clone();
+
+        // Providers without a configured budget are limited to the rate that their adapter reported, if any
+        let (namespace, unqualified_id) = namespace::split(entity_id);
+        let adapter_uri = namespace::qualify(namespace, &provider_uri);
+        let budget_config = self
+            .request_budget_config
+            .get(&provider_uri)
+            .copied()
+            .or_else(|| {
+                let max_requests_per_second = state
+                    .capabilities
+                    .get(&adapter_uri)?
+                    .max_requests_per_second?;
+                Some(RequestBudgetConfig {
+                    max_requests_per_second,
+                    burst: 1,
+                })
+            });
+
+        let data_adapter = match state.data_adapters.get(&adapter_uri) {
+            Some(data_adapter) => data_adapter.clone(),
+            None => {
+                return Err(DataAdapterSelectorError::entity_not_found(format!(
+                    "Data adapter for {entity_id} is not available"
+                )))
+            }
+        };
+
+        if let Some(budget_config) = budget_config {
+            // A request which is already waiting for a token will publish the entity's value, so another isn't needed
+            if self.deferred_requests.lock().unwrap().contains(entity_id) {
+                return Ok(());
+            }
+
+            let now = self.signals.clock().monotonic_now();
+            let budget = state
+                .request_budgets
+                .entry(provider_uri.clone())
+                .or_insert_with(|| RequestBudget::new(&budget_config, now));
+
+            let wait = budget.reserve(now).ok_or_else(|| {
+                DataAdapterSelectorError::rate_limited(format!(
+                    "Request budget for provider {provider_uri} is exhausted"
+                ))
+            })?;
+
+            if !wait.is_zero() {
+                debug!(
+                    "Request budget for provider {provider_uri} is exhausted, deferring the request for {entity_id} by {wait:?}"
+                );
+                self.defer_request(entity_id, unqualified_id, data_adapter, wait);
+                return Ok(());
+            }
+        }
+
+        with_timeout(
+            self.request_timeout_ms,
+            data_adapter.send_request_to_provider(unqualified_id),
+            DataAdapterError::communication,
+        )
+        .await
+        .map_err(DataAdapterSelectorError::communication)
+    }
+
+    /// Moves registered entities to new adapters if their preferred factory has changed,
+    /// such as after registering or deregistering a factory.
+    /// The adapters that served these entities are removed and stopped, so any other entities they served are registered again as well.
+    /// Entities which fail to migrate aren't served until a later migration succeeds.
+    async fn migrate_entities(&self) -> Result<(), DataAdapterSelectorError> {
+        let (records, stale_adapters) = {
+            let mut state = self.state.lock().await;
+
+            // Find the providers of entities whose preferred factory has changed
+            let stale_uris: HashSet<String> = state
+                .registrations
+                .values()
+                .filter(|record| {
+                    self.find_factory(&record.entity, &record.emission_policy)
+                        .map(|(factory, _)| factory.kind())
+                        != record.factory_kind
+                })
+                .flat_map(|record| record.provider_uris.iter().cloned())
+                .collect();
+
+            // Every entity served by one of these providers needs to be registered again
+            let migrated_ids: Vec<String> = state
+                .registrations
+                .iter()
+                .filter(|(_, record)| {
+                    record
+                        .provider_uris
+                        .iter()
+                        .any(|uri| stale_uris.contains(uri))
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
 
-        match state.data_adapters.entry(provider_uri) {
-            Entry::Occupied(data_adapter) => data_adapter
-                .get()
-                .send_request_to_provider(entity_id)
+            let mut stale_adapters = Vec::new();
+            for uri in stale_uris.iter() {
+                if let Some(data_adapter) = state.data_adapters.remove(uri) {
+                    stale_adapters.push((uri.clone(), data_adapter));
+                }
+                state.capabilities.remove(uri);
+            }
+
+            let mut records = Vec::new();
+            for id in migrated_ids {
+                state.entity_map.remove(&id);
+                if let Some(record) = state.registrations.remove(&id) {
+                    records.push((id, record));
+                }
+            }
+
+            (records, stale_adapters)
+        };
+
+        // The stale adapters are stopped so that they don't keep writing values for the migrated entities
+        for (uri, data_adapter) in stale_adapters {
+            if let Err(e) = data_adapter.stop().await {
+                warn!("Failed to stop the data adapter for {uri}: {e}");
+            }
+        }
+
+        let mut failed_ids = Vec::new();
+        for (id, record) in records {
+            info!("Migrating {} to a new data adapter", record.entity.id);
+
+            if let Err(e) = self
+                .create_or_update_adapter(&record.entity, &record.emission_policy)
                 .await
-                .map_err(DataAdapterSelectorError::communication),
-            Entry::Vacant(_) => Err(DataAdapterSelectorError::entity_not_found(format!(
-                "Data adapter for {entity_id} is not available"
-            ))),
+            {
+                log::error!(
+                    "Failed to migrate {} to a new data adapter: {e}",
+                    record.entity.id
+                );
+                failed_ids.push(record.entity.id.clone());
+
+                // The record is restored without a factory kind so that the entity is migrated again
+                // once a factory which can serve it is preferred
+                self.state.lock().await.registrations.insert(
+                    id,
+                    EntityRecord {
+                        factory_kind: None,
+                        ..record
+                    },
+                );
+            }
+        }
+
+        if failed_ids.is_empty() {
+            Ok(())
+        } else {
+            Err(DataAdapterSelectorError::data_adapter_error(format!(
+                "Unable to migrate entities {failed_ids:?}"
+            )))
         }
     }
+
+    /// Gets information about the data adapter that serves an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity to look up
+    async fn get_adapter_info(
+        &self,
+        entity_id: &str,
+    ) -> Result<DataAdapterInfo, DataAdapterSelectorError> {
+        let state = self.state.lock().await;
+
+        state.entity_map.get(entity_id).cloned().ok_or_else(|| {
+            DataAdapterSelectorError::entity_not_found(format!(
+                "No data adapter is serving {entity_id}"
+            ))
+        })
+    }
+}
+
+/// Orders an entity's endpoints from the highest to the lowest score against an emission policy.
+/// Equally scored endpoints keep their original order.
+///
+/// # Arguments
+/// - `entity`: the entity whose endpoints should be ranked
+/// - `emission_policy`: the emission policy to score the endpoints against
+fn rank_endpoints(entity: &Entity, emission_policy: &EmissionPolicy) -> Entity {
+    let mut entity = entity.to_owned();
+    entity
+        .endpoints
+        .sort_by_key(|endpoint| Reverse(endpoint.score(emission_policy)));
+
+    entity
 }
 
 #[cfg(test)]
 mod data_adapter_selector_tests {
     use super::*;
 
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
     use freyja_common::{
-        data_adapter_selector::DataAdapterSelectorErrorKind, entity::EntityEndpoint,
+        clock::{Clock, ManualClock},
+        data_adapter::DataAdapterErrorKind,
+        data_adapter_selector::DataAdapterSelectorErrorKind,
+        entity::{EndpointCapabilities, Operation},
     };
+    use freyja_test_common::fixtures::TempDirFixture;
     use sample_grpc_data_adapter::sample_grpc_data_adapter_factory::SampleGRPCDataAdapterFactory;
 
+    use crate::config::RequestBudgetConfig;
+
     const AMBIENT_AIR_TEMPERATURE_ID: &str = "dtmi:sdv:Vehicle:Cabin:HVAC:AmbientAirTemperature;1";
     const OPERATION: &str = "Subscribe";
+    const PROVIDER_URI: &str = "http://provider"; // Devskim: ignore DS137138
+
+    /// A data adapter which counts the requests sent to its provider
+    #[derive(Default)]
+    struct CountingDataAdapter {
+        request_count: AtomicUsize,
+        requested_entities: StdMutex<HashSet<String>>,
+        capabilities: DataAdapterCapabilities,
+        started: AtomicBool,
+        stopped: AtomicBool,
+    }
+
+    #[async_trait]
+    impl DataAdapter for CountingDataAdapter {
+        fn create_new(
+            _provider_uri: &str,
+            _signals: Arc<SignalStore>,
+        ) -> Result<Self, DataAdapterError> {
+            Ok(Self::default())
+        }
+
+        async fn start(&self) -> Result<(), DataAdapterError> {
+            self.started.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn stop(&self) -> Result<(), DataAdapterError> {
+            self.stopped.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+            self.request_count.fetch_add(1, Ordering::SeqCst);
+            self.requested_entities
+                .lock()
+                .unwrap()
+                .insert(entity_id.to_string());
+            Ok(())
+        }
+
+        async fn register_entity(
+            &self,
+            _entity_id: &str,
+            _endpoint: &EntityEndpoint,
+        ) -> Result<EntityRegistration, DataAdapterError> {
+            Ok(EntityRegistration::Registered)
+        }
+
+        async fn get_capabilities(&self) -> Result<DataAdapterCapabilities, DataAdapterError> {
+            // Capabilities are only known once the adapter has started
+            if !self.started.load(Ordering::SeqCst) {
+                return Err(DataAdapterErrorKind::Communication.into());
+            }
+
+            Ok(self.capabilities.clone())
+        }
+    }
+
+    /// Creates a selector with a counting adapter registered for the given entities
+    async fn create_selector_with_adapter(
+        request_budget_config: ProviderRequestBudgets,
+        entity_ids: &[&str],
+    ) -> (DataAdapterSelectorImpl, Arc<CountingDataAdapter>) {
+        create_selector_with_clock(
+            request_budget_config,
+            entity_ids,
+            Arc::new(ManualClock::default()),
+        )
+        .await
+    }
+
+    async fn create_selector_with_clock(
+        request_budget_config: ProviderRequestBudgets,
+        entity_ids: &[&str],
+        clock: Arc<dyn Clock>,
+    ) -> (DataAdapterSelectorImpl, Arc<CountingDataAdapter>) {
+        let uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::with_clock(clock)),
+            request_budget_config,
+            None,
+        );
+        let adapter = Arc::new(CountingDataAdapter::default());
+
+        {
+            let mut state = uut.state.lock().await;
+            state
+                .data_adapters
+                .insert(PROVIDER_URI.to_string(), adapter.clone());
+            for entity_id in entity_ids {
+                state.entity_map.insert(
+                    entity_id.to_string(),
+                    DataAdapterInfo {
+                        adapter_kind: adapter.kind(),
+                        endpoint: PROVIDER_URI.to_string(),
+                        protocol: "protocol".to_string(),
+                        registered_at: uut.signals.clock().now(),
+                    },
+                );
+            }
+        }
+
+        (uut, adapter)
+    }
 
     #[tokio::test]
     async fn handle_start_data_adapter_request_return_err_test() {
         let signals: Arc<SignalStore> = Arc::new(SignalStore::new());
-        let mut uut = DataAdapterSelectorImpl::new(signals);
+        let mut uut =
+            DataAdapterSelectorImpl::new(signals, ProviderRequestBudgets::default(), None);
         uut.register(Box::new(
             SampleGRPCDataAdapterFactory::create_new().unwrap(),
         ))
@@ -249,10 +970,13 @@ mod data_adapter_selector_tests {
                 uri: String::new(),
                 protocol: String::from("grpc"),
                 context: String::from("context"),
+                ..Default::default()
             }],
         };
 
-        let result = uut.create_or_update_adapter(&entity).await;
+        let result = uut
+            .create_or_update_adapter(&entity, &EmissionPolicy::default())
+            .await;
         println!("{result:?}");
 
         assert!(result.is_err());
@@ -261,4 +985,654 @@ mod data_adapter_selector_tests {
             DataAdapterSelectorErrorKind::DataAdapterError
         );
     }
+
+    #[tokio::test]
+    async fn request_entity_value_without_budget_is_not_limited() {
+        let (uut, adapter) =
+            create_selector_with_adapter(ProviderRequestBudgets::default(), &["a"]).await;
+
+        for _ in 0..10 {
+            assert!(uut.request_entity_value("a").await.is_ok());
+        }
+
+        assert_eq!(adapter.request_count.load(Ordering::SeqCst), 10);
+    }
+
+    #[tokio::test]
+    async fn request_entity_value_respects_provider_budget() {
+        let budgets = ProviderRequestBudgets {
+            default: Some(RequestBudgetConfig {
+                max_requests_per_second: 0.0,
+                burst: 2,
+            }),
+            overrides: HashMap::new(),
+        };
+        let (uut, adapter) = create_selector_with_adapter(budgets, &["a", "b"]).await;
+
+        // The budget is shared by all entities from the same provider
+        assert!(uut.request_entity_value("a").await.is_ok());
+        assert!(uut.request_entity_value("b").await.is_ok());
+
+        let result = uut.request_entity_value("a").await;
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().kind(),
+            DataAdapterSelectorErrorKind::RateLimited
+        );
+        assert_eq!(adapter.request_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn request_entity_value_defers_requests_until_budget_refills() {
+        let budgets = ProviderRequestBudgets {
+            default: Some(RequestBudgetConfig {
+                max_requests_per_second: 1.0,
+                burst: 1,
+            }),
+            overrides: HashMap::new(),
+        };
+        let entity_ids = ["a", "b", "c"];
+        let clock = Arc::new(ManualClock::default());
+        let (uut, adapter) = create_selector_with_clock(budgets, &entity_ids, clock.clone()).await;
+
+        // Requesting the entities in the same order each round used to starve the trailing ones.
+        // The deferred requests only run once this task yields, so the second round finds them still waiting
+        for _ in 0..2 {
+            for entity_id in entity_ids {
+                assert!(uut.request_entity_value(entity_id).await.is_ok());
+            }
+        }
+
+        assert_eq!(adapter.request_count.load(Ordering::SeqCst), 1);
+        assert_eq!(uut.deferred_requests.lock().unwrap().len(), 3);
+
+        // Sleeping on the manual clock advances it, so the deferred requests are sent as soon as they're polled
+        while !uut.deferred_requests.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        tokio::task::yield_now().await;
+
+        let requested_entities = adapter.requested_entities.lock().unwrap().clone();
+        assert_eq!(
+            requested_entities,
+            HashSet::from(entity_ids.map(String::from))
+        );
+
+        // Requests which were already waiting for a token aren't duplicated
+        assert_eq!(adapter.request_count.load(Ordering::SeqCst), 4);
+
+        // The deferred requests advanced the clock while they waited, so the budget has refilled
+        assert_eq!(clock.monotonic_now(), Duration::from_secs(6));
+        assert!(uut.request_entity_value("a").await.is_ok());
+        assert_eq!(adapter.request_count.load(Ordering::SeqCst), 5);
+        assert!(uut.deferred_requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_entity_value_uses_provider_override() {
+        let budgets = ProviderRequestBudgets {
+            default: Some(RequestBudgetConfig {
+                max_requests_per_second: 0.0,
+                burst: 1,
+            }),
+            overrides: HashMap::from([(
+                PROVIDER_URI.to_string(),
+                RequestBudgetConfig {
+                    max_requests_per_second: 0.0,
+                    burst: 3,
+                },
+            )]),
+        };
+        let (uut, adapter) = create_selector_with_adapter(budgets, &["a"]).await;
+
+        for _ in 0..5 {
+            let _ = uut.request_entity_value("a").await;
+        }
+
+        assert_eq!(adapter.request_count.load(Ordering::SeqCst), 3);
+    }
+
+    /// A data adapter whose provider never responds
+    struct HangingDataAdapter;
+
+    #[async_trait]
+    impl DataAdapter for HangingDataAdapter {
+        fn create_new(
+            _provider_uri: &str,
+            _signals: Arc<SignalStore>,
+        ) -> Result<Self, DataAdapterError> {
+            Ok(Self)
+        }
+
+        async fn start(&self) -> Result<(), DataAdapterError> {
+            Ok(())
+        }
+
+        async fn send_request_to_provider(&self, _entity_id: &str) -> Result<(), DataAdapterError> {
+            std::future::pending().await
+        }
+
+        async fn register_entity(
+            &self,
+            _entity_id: &str,
+            _endpoint: &EntityEndpoint,
+        ) -> Result<EntityRegistration, DataAdapterError> {
+            Ok(EntityRegistration::Registered)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn request_entity_value_times_out_hanging_adapters() {
+        let (mut uut, _) =
+            create_selector_with_adapter(ProviderRequestBudgets::default(), &["a"]).await;
+        uut.request_timeout_ms = Some(1000);
+        uut.state
+            .lock()
+            .await
+            .data_adapters
+            .insert(PROVIDER_URI.to_string(), Arc::new(HangingDataAdapter));
+
+        let result = uut.request_entity_value("a").await;
+
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterSelectorErrorKind::Communication));
+    }
+
+    /// A factory which creates counting adapters for entities with the test protocol
+    struct CountingDataAdapterFactory {
+        kind: String,
+        capabilities: DataAdapterCapabilities,
+    }
+
+    impl CountingDataAdapterFactory {
+        fn with_kind(kind: &str) -> Self {
+            Self::with_capabilities(kind, DataAdapterCapabilities::default())
+        }
+
+        fn with_capabilities(kind: &str, capabilities: DataAdapterCapabilities) -> Self {
+            Self {
+                kind: kind.to_string(),
+                capabilities,
+            }
+        }
+    }
+
+    impl DataAdapterFactory for CountingDataAdapterFactory {
+        fn create_new() -> Result<Self, DataAdapterError> {
+            Ok(Self::with_kind("counting"))
+        }
+
+        fn is_supported(&self, entity: &Entity) -> Option<EntityEndpoint> {
+            entity
+                .endpoints
+                .iter()
+                .find(|endpoint| endpoint.protocol == "counting")
+                .cloned()
+        }
+
+        fn create_adapter(
+            &self,
+            _provider_uri: &str,
+            _signals: Arc<SignalStore>,
+        ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError> {
+            Ok(Arc::new(CountingDataAdapter {
+                capabilities: self.capabilities.clone(),
+                ..Default::default()
+            }))
+        }
+
+        fn kind(&self) -> String {
+            self.kind.clone()
+        }
+    }
+
+    fn create_counting_entity() -> Entity {
+        Entity {
+            id: String::from(AMBIENT_AIR_TEMPERATURE_ID),
+            name: None,
+            description: None,
+            endpoints: vec![EntityEndpoint {
+                operations: vec![OPERATION.to_string()],
+                uri: PROVIDER_URI.to_string(),
+                protocol: String::from("counting"),
+                context: String::from("context"),
+                ..Default::default()
+            }],
+        }
+    }
+
+    /// Gets the adapter that currently serves the test provider
+    async fn get_provider_adapter(
+        uut: &DataAdapterSelectorImpl,
+    ) -> Option<Arc<dyn DataAdapter + Send + Sync>> {
+        let state = uut.state.lock().await;
+        state.data_adapters.get(PROVIDER_URI).cloned()
+    }
+
+    #[tokio::test]
+    async fn get_adapter_info_returns_registration_details() {
+        let clock = Arc::new(ManualClock::default());
+        let signals = Arc::new(SignalStore::with_clock(clock.clone()));
+        let mut uut =
+            DataAdapterSelectorImpl::new(signals, ProviderRequestBudgets::default(), None);
+        uut.register(Box::new(CountingDataAdapterFactory::create_new().unwrap()))
+            .unwrap();
+
+        let entity = create_counting_entity();
+        assert!(uut
+            .create_or_update_adapter(&entity, &EmissionPolicy::default())
+            .await
+            .is_ok());
+
+        let result = uut.get_adapter_info(AMBIENT_AIR_TEMPERATURE_ID).await;
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            DataAdapterInfo {
+                adapter_kind: "CountingDataAdapter".to_string(),
+                endpoint: PROVIDER_URI.to_string(),
+                protocol: "counting".to_string(),
+                registered_at: clock.now(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn create_or_update_adapter_records_registration_metrics() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::create_new().unwrap()))
+            .unwrap();
+
+        // The unsupported endpoint has its own provider so that the counting adapter isn't reused for it
+        let unsupported_endpoint = EntityEndpoint {
+            operations: vec![OPERATION.to_string()],
+            uri: String::from("http://unsupported-provider"), // Devskim: ignore DS137138
+            protocol: String::from("unsupported"),
+            context: String::from("context"),
+            ..Default::default()
+        };
+        let mut entity = create_counting_entity();
+        entity.endpoints.push(unsupported_endpoint.clone());
+        let unsupported_entity = Entity {
+            id: String::from("unsupported"),
+            endpoints: vec![unsupported_endpoint],
+            ..Default::default()
+        };
+
+        assert!(uut
+            .create_or_update_adapter(&entity, &EmissionPolicy::default())
+            .await
+            .is_ok());
+        assert!(uut
+            .create_or_update_adapter(&unsupported_entity, &EmissionPolicy::default())
+            .await
+            .is_err());
+
+        let metrics = uut.registration_metrics().snapshot();
+        assert_eq!(metrics.adapters["CountingDataAdapter"].registered, 1);
+        assert_eq!(
+            metrics.rejected_endpoints,
+            std::collections::BTreeMap::from([(String::from("unsupported"), 2)])
+        );
+        assert_eq!(metrics.failed_registrations, 1);
+
+        // Registering the entity again, such as when the mapping is synced, doesn't count its endpoints again
+        assert!(uut
+            .create_or_update_adapter(&entity, &EmissionPolicy::default())
+            .await
+            .is_ok());
+        assert_eq!(
+            uut.registration_metrics().snapshot().rejected_endpoints,
+            metrics.rejected_endpoints
+        );
+    }
+
+    #[tokio::test]
+    async fn create_or_update_adapter_skips_factories_whose_adapters_do_not_support_operation() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::with_kind("subscribe")))
+            .unwrap();
+        uut.register(Box::new(CountingDataAdapterFactory::with_capabilities(
+            "get",
+            DataAdapterCapabilities {
+                operations: Some(vec![Operation::Get]),
+                ..Default::default()
+            },
+        )))
+        .unwrap();
+
+        assert!(uut
+            .create_or_update_adapter(&create_counting_entity(), &EmissionPolicy::default())
+            .await
+            .is_ok());
+
+        // The adapter from the factory registered first serves the entity
+        let state = uut.state.lock().await;
+        assert_eq!(
+            state.capabilities.get(PROVIDER_URI),
+            Some(&DataAdapterCapabilities::default())
+        );
+    }
+
+    #[tokio::test]
+    async fn create_or_update_adapter_respects_max_subscriptions() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::with_capabilities(
+            "counting",
+            DataAdapterCapabilities {
+                max_subscriptions: Some(1),
+                ..Default::default()
+            },
+        )))
+        .unwrap();
+
+        let first = create_counting_entity();
+        let mut second = create_counting_entity();
+        second.id = String::from("second");
+        let mut third = second.clone();
+        third.id = String::from("third");
+        third.endpoints.push(EntityEndpoint {
+            uri: String::from("http://other"), // Devskim: ignore DS137138
+            ..third.endpoints[0].clone()
+        });
+
+        let policy = EmissionPolicy::default();
+        assert!(uut.create_or_update_adapter(&first, &policy).await.is_ok());
+
+        // Registering the same entity again doesn't count against the limit
+        assert!(uut.create_or_update_adapter(&first, &policy).await.is_ok());
+
+        let result = uut.create_or_update_adapter(&second, &policy).await;
+        assert!(
+            result.is_err_and(|e| e.kind() == DataAdapterSelectorErrorKind::OperationNotSupported)
+        );
+
+        // Entities with another endpoint are served by a new adapter instead
+        assert!(uut.create_or_update_adapter(&third, &policy).await.is_ok());
+        assert_eq!(
+            uut.get_adapter_info("third").await.unwrap().endpoint,
+            "http://other" // Devskim: ignore DS137138
+        );
+    }
+
+    #[tokio::test]
+    async fn request_entity_value_respects_adapter_rate_limit() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::with_capabilities(
+            "counting",
+            DataAdapterCapabilities {
+                max_requests_per_second: Some(0.0),
+                ..Default::default()
+            },
+        )))
+        .unwrap();
+
+        assert!(uut
+            .create_or_update_adapter(&create_counting_entity(), &EmissionPolicy::default())
+            .await
+            .is_ok());
+
+        assert!(uut
+            .request_entity_value(AMBIENT_AIR_TEMPERATURE_ID)
+            .await
+            .is_ok());
+        let result = uut.request_entity_value(AMBIENT_AIR_TEMPERATURE_ID).await;
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterSelectorErrorKind::RateLimited));
+    }
+
+    #[tokio::test]
+    async fn create_or_update_adapter_prefers_endpoints_which_suit_emission_policy() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::create_new().unwrap()))
+            .unwrap();
+
+        let fast_uri = "http://fast-provider"; // Devskim: ignore DS137138
+        let mut entity = create_counting_entity();
+        entity.endpoints[0].capabilities.min_interval_ms = Some(5000);
+        entity.endpoints.push(EntityEndpoint {
+            uri: fast_uri.to_string(),
+            capabilities: EndpointCapabilities {
+                min_interval_ms: Some(100),
+                ..Default::default()
+            },
+            ..entity.endpoints[0].clone()
+        });
+        let emission_policy = EmissionPolicy {
+            interval_ms: 1000,
+            ..Default::default()
+        };
+
+        assert!(uut
+            .create_or_update_adapter(&entity, &emission_policy)
+            .await
+            .is_ok());
+
+        let adapter_info = uut
+            .get_adapter_info(AMBIENT_AIR_TEMPERATURE_ID)
+            .await
+            .unwrap();
+        assert_eq!(adapter_info.endpoint, fast_uri);
+    }
+
+    #[tokio::test]
+    async fn create_or_update_adapter_keeps_namespaces_separate() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::create_new().unwrap()))
+            .unwrap();
+
+        let plain_entity = create_counting_entity();
+        let namespaced_entity = Entity {
+            id: namespace::qualify(Some("cabin"), AMBIENT_AIR_TEMPERATURE_ID),
+            ..create_counting_entity()
+        };
+        assert!(uut
+            .create_or_update_adapter(&plain_entity, &EmissionPolicy::default())
+            .await
+            .is_ok());
+        assert!(uut
+            .create_or_update_adapter(&namespaced_entity, &EmissionPolicy::default())
+            .await
+            .is_ok());
+
+        assert!(uut.get_adapter_info(&namespaced_entity.id).await.is_ok());
+        assert!(uut
+            .request_entity_value(&namespaced_entity.id)
+            .await
+            .is_ok());
+
+        let state = uut.state.lock().await;
+        assert_eq!(state.data_adapters.len(), 2);
+        assert!(state
+            .data_adapters
+            .contains_key(&namespace::qualify(Some("cabin"), PROVIDER_URI)));
+    }
+
+    #[tokio::test]
+    async fn get_adapter_info_returns_err_for_unknown_entity() {
+        let (uut, _) =
+            create_selector_with_adapter(ProviderRequestBudgets::default(), &["a"]).await;
+
+        let result = uut.get_adapter_info("b").await;
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().kind(),
+            DataAdapterSelectorErrorKind::EntityNotFound
+        );
+    }
+
+    #[test]
+    fn deregister_removes_factories_of_kind() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::with_kind("a")))
+            .unwrap();
+        uut.register(Box::new(CountingDataAdapterFactory::with_kind("b")))
+            .unwrap();
+
+        assert!(uut.deregister("a").is_ok());
+        assert_eq!(uut.factories.len(), 1);
+        assert_eq!(uut.factories[0].kind(), "b");
+
+        let result = uut.deregister("a");
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().kind(),
+            DataAdapterSelectorErrorKind::FactoryNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn migrate_entities_moves_entities_to_preferred_factory() {
+        let clock = Arc::new(ManualClock::default());
+        let signals = Arc::new(SignalStore::with_clock(clock.clone()));
+        let mut uut =
+            DataAdapterSelectorImpl::new(signals, ProviderRequestBudgets::default(), None);
+        uut.register(Box::new(CountingDataAdapterFactory::with_kind("original")))
+            .unwrap();
+
+        assert!(uut
+            .create_or_update_adapter(&create_counting_entity(), &EmissionPolicy::default())
+            .await
+            .is_ok());
+        let original_adapter = get_provider_adapter(&uut).await.unwrap();
+
+        // Nothing is migrated while the preferred factory is unchanged
+        assert!(uut.migrate_entities().await.is_ok());
+        let current_adapter = get_provider_adapter(&uut).await.unwrap();
+        assert!(Arc::ptr_eq(&original_adapter, &current_adapter));
+
+        clock.advance(Duration::from_secs(1));
+        uut.register(Box::new(CountingDataAdapterFactory::with_kind("preferred")))
+            .unwrap();
+
+        assert!(uut.migrate_entities().await.is_ok());
+        let current_adapter = get_provider_adapter(&uut).await.unwrap();
+        assert!(!Arc::ptr_eq(&original_adapter, &current_adapter));
+
+        let adapter_info = uut
+            .get_adapter_info(AMBIENT_AIR_TEMPERATURE_ID)
+            .await
+            .unwrap();
+        assert_eq!(adapter_info.registered_at, clock.now());
+        assert!(uut
+            .request_entity_value(AMBIENT_AIR_TEMPERATURE_ID)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn migrate_entities_keeps_entities_which_fail_to_migrate() {
+        let mut uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+        uut.register(Box::new(CountingDataAdapterFactory::with_kind("original")))
+            .unwrap();
+
+        assert!(uut
+            .create_or_update_adapter(&create_counting_entity(), &EmissionPolicy::default())
+            .await
+            .is_ok());
+
+        uut.deregister("original").unwrap();
+
+        assert!(uut.migrate_entities().await.is_err());
+        assert!(get_provider_adapter(&uut).await.is_none());
+        assert!(uut
+            .get_adapter_info(AMBIENT_AIR_TEMPERATURE_ID)
+            .await
+            .is_err());
+
+        // Nothing is migrated again while no factory can serve the entity
+        assert!(uut.migrate_entities().await.is_ok());
+
+        // The entity is migrated once a factory can serve it again
+        uut.register(Box::new(CountingDataAdapterFactory::with_kind("original")))
+            .unwrap();
+
+        assert!(uut.migrate_entities().await.is_ok());
+        assert!(get_provider_adapter(&uut).await.is_some());
+        assert!(uut
+            .get_adapter_info(AMBIENT_AIR_TEMPERATURE_ID)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn migrate_entities_stops_stale_adapters() {
+        let (uut, adapter) =
+            create_selector_with_adapter(ProviderRequestBudgets::default(), &[]).await;
+        uut.state.lock().await.registrations.insert(
+            AMBIENT_AIR_TEMPERATURE_ID.to_string(),
+            EntityRecord {
+                entity: create_counting_entity(),
+                emission_policy: EmissionPolicy::default(),
+                factory_kind: Some(String::from("deregistered")),
+                provider_uris: vec![PROVIDER_URI.to_string()],
+            },
+        );
+
+        // No factory can serve the entity anymore, so it can't be migrated, but its adapter is still stopped
+        assert!(uut.migrate_entities().await.is_err());
+        assert!(adapter.stopped.load(Ordering::SeqCst));
+        assert!(get_provider_adapter(&uut).await.is_none());
+    }
+
+    #[test]
+    fn record_traffic_records_values_set_by_adapters() {
+        let directory = TempDirFixture::new();
+        let path = directory.path.join("trace.jsonl");
+        let path = path.to_string_lossy().into_owned();
+
+        let signals = Arc::new(SignalStore::new());
+        let uut =
+            DataAdapterSelectorImpl::new(signals.clone(), ProviderRequestBudgets::default(), None);
+        uut.record_traffic(&path).unwrap();
+
+        signals.set_value(AMBIENT_AIR_TEMPERATURE_ID.to_string(), String::from("21.5"));
+
+        let records = freyja_common::traffic_trace::read_trace(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].entity_id, AMBIENT_AIR_TEMPERATURE_ID);
+        assert_eq!(records[0].value, "21.5");
+    }
+
+    #[test]
+    fn record_traffic_returns_err_for_invalid_path() {
+        let uut = DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        );
+
+        let result = uut.record_traffic("/this/path/does/not/exist/trace.jsonl");
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterSelectorErrorKind::Io));
+    }
 }
@@ -0,0 +1,143 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use time::OffsetDateTime;
+
+/// A ramp-up which spreads a backlog of overdue signals over time
+struct RampUp {
+    /// The time at which the ramp-up started
+    started_at: OffsetDateTime,
+
+    /// The number of signals which were due when the ramp-up started
+    backlog: usize,
+
+    /// The number of signals which have been admitted since the ramp-up started
+    admitted: usize,
+}
+
+/// Spreads the emission of overdue signals over a ramp-up period,
+/// so that the connector and backend aren't overwhelmed when Freyja starts or cloud connectivity returns.
+/// A ramp-up starts on the first emission and on the first emission after a failure to reach the cloud.
+/// While connectivity is down, only one signal is admitted at a time,
+/// which probes the connection without sending the whole backlog.
+pub(crate) struct EmissionPacer {
+    /// The period over which the backlog is spread
+    ramp_up: Duration,
+
+    /// Indicates whether a ramp-up should start the next time that signals are due
+    ramp_up_pending: bool,
+
+    /// The ramp-up in progress, if any
+    ramp_up_in_progress: Option<RampUp>,
+}
+
+impl EmissionPacer {
+    /// Creates a new EmissionPacer which ramps up on the first emission
+    ///
+    /// # Arguments
+    /// - `ramp_up_ms`: the period over which the backlog is spread
+    pub fn new(ramp_up_ms: u64) -> Self {
+        Self {
+            ramp_up: Duration::from_millis(ramp_up_ms),
+            ramp_up_pending: true,
+            ramp_up_in_progress: None,
+        }
+    }
+
+    /// Gets the number of due signals which can be emitted now.
+    /// The remaining signals stay due and are admitted on later iterations.
+    ///
+    /// # Arguments
+    /// - `now`: the current time
+    /// - `due`: the number of signals which are due
+    pub fn admit(&mut self, now: OffsetDateTime, due: usize) -> usize {
+        if self.ramp_up_pending && due > 0 {
+            self.ramp_up_pending = false;
+            self.ramp_up_in_progress = Some(RampUp {
+                started_at: now,
+                backlog: due,
+                admitted: 0,
+            });
+        }
+
+        let ramp_up = match self.ramp_up_in_progress.as_mut() {
+            Some(ramp_up) => ramp_up,
+            None => return due,
+        };
+
+        let elapsed: Duration = (now - ramp_up.started_at)
+            .try_into()
+            .unwrap_or(Duration::ZERO);
+        if elapsed >= self.ramp_up {
+            self.ramp_up_in_progress = None;
+            return due;
+        }
+
+        // The backlog is admitted in proportion to the elapsed time, starting with one signal
+        let allowed = ((ramp_up.backlog as u128 * elapsed.as_millis()) / self.ramp_up.as_millis())
+            .max(1) as usize;
+        let admitted = allowed.saturating_sub(ramp_up.admitted).min(due);
+        ramp_up.admitted += admitted;
+
+        admitted
+    }
+
+    /// Records a failure to reach the cloud, so that the backlog is ramped up again once connectivity returns
+    pub fn on_cloud_failure(&mut self) {
+        self.ramp_up_pending = true;
+        self.ramp_up_in_progress = None;
+    }
+
+    /// Gets the time until more of the backlog can be admitted,
+    /// or `None` if there is no ramp-up in progress
+    pub fn time_until_next_admission_ms(&self) -> Option<u64> {
+        let ramp_up = self.ramp_up_in_progress.as_ref()?;
+        let ramp_up_ms = u64::try_from(self.ramp_up.as_millis()).unwrap_or(u64::MAX);
+
+        Some((ramp_up_ms / ramp_up.backlog.max(1) as u64).max(1))
+    }
+}
+
+#[cfg(test)]
+mod emission_pacer_tests {
+    use super::*;
+
+    #[test]
+    fn admit_spreads_backlog_over_ramp_up() {
+        let start = OffsetDateTime::UNIX_EPOCH;
+        let mut uut = EmissionPacer::new(1000);
+
+        assert_eq!(uut.admit(start, 10), 1);
+        assert_eq!(uut.time_until_next_admission_ms(), Some(100));
+        assert_eq!(uut.admit(start + Duration::from_millis(500), 9), 4);
+        assert_eq!(uut.admit(start + Duration::from_millis(900), 5), 4);
+
+        // Once the ramp-up is over, every due signal is admitted
+        assert_eq!(uut.admit(start + Duration::from_millis(1000), 7), 7);
+        assert_eq!(uut.time_until_next_admission_ms(), None);
+        assert_eq!(uut.admit(start + Duration::from_millis(2000), 20), 20);
+    }
+
+    #[test]
+    fn admit_ramps_up_again_after_cloud_failure() {
+        let start = OffsetDateTime::UNIX_EPOCH;
+        let mut uut = EmissionPacer::new(1000);
+
+        // Nothing is due yet, so the ramp-up waits for the first due signals
+        assert_eq!(uut.admit(start, 0), 0);
+        assert_eq!(uut.admit(start + Duration::from_millis(2000), 4), 1);
+        assert_eq!(uut.admit(start + Duration::from_millis(3000), 4), 4);
+
+        uut.on_cloud_failure();
+
+        // Only one signal is admitted at a time until the cloud is reached again
+        let retry = start + Duration::from_millis(5000);
+        assert_eq!(uut.admit(retry, 8), 1);
+        uut.on_cloud_failure();
+        assert_eq!(uut.admit(retry + Duration::from_millis(1), 8), 1);
+        assert_eq!(uut.admit(retry + Duration::from_millis(501), 7), 3);
+    }
+}
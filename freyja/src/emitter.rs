@@ -2,112 +2,463 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::{cmp::min, sync::Arc, time::Duration};
+use std::{
+    cmp::{max, min},
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
 
-use log::info;
-use time::OffsetDateTime;
-use tokio::{sync::Mutex, time::sleep};
+use log::{debug, info, warn};
+use tokio::{sync::Mutex, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
 
 use freyja_common::{
-    cloud_adapter::{CloudAdapter, CloudMessageRequest, CloudMessageResponse},
-    data_adapter_selector::DataAdapterSelector,
-    signal::Signal,
+    clock::Clock,
+    cloud_adapter::{
+        Acknowledgement, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+        CONVERSION_FAILED_QUALITY, FLIGHT_RECORDER_CAPTURE_METADATA_KEY, QUALITY_METADATA_KEY,
+    },
+    conversion::{self, Conversion, ConversionFailurePolicy},
+    data_adapter_selector::{DataAdapterSelector, DataAdapterSelectorErrorKind},
+    entity::Operation,
+    privacy::{Noise, PrivacyPolicy},
+    signal::{EmissionDescriptor, EmissionPolicy, Signal},
     signal_store::SignalStore,
+    trigger,
+};
+
+use crate::{
+    cloud_adapter_router::CloudMessageSender,
+    config::{EmissionAcknowledgementMode, ValueRequestMode},
+    crash_report,
+    emission_pacer::EmissionPacer,
+    flight_recorder::FlightRecorder,
+    freyja_handle::FreyjaComponent,
+    geofence::GeofenceConditionProvider,
+    status_reporter::StatusReporter,
+    watchdog::Heartbeat,
 };
 
 const DEFAULT_SLEEP_INTERVAL_MS: u64 = 1000;
 
+/// The interval at which the signal store is checked while waiting for fresh values
+const FRESH_VALUE_POLL_INTERVAL_MS: u64 = 10;
+
+/// A signal's converted value and the parts of its emission policy that it was converted with
+struct ConvertedValue {
+    /// The value before conversion
+    raw_value: Arc<str>,
+
+    /// The conversion that was applied
+    conversion: Conversion,
+
+    /// The privacy policy that was applied
+    privacy: Option<PrivacyPolicy>,
+
+    /// The number of decimal places that the value was rounded to
+    decimal_places: Option<u32>,
+
+    /// The converted value
+    converted: Arc<str>,
+
+    /// The converted value as a number, if it's emitted as one
+    numeric_value: Option<f64>,
+}
+
 /// Emits sensor data at regular intervals as configured in the store
 pub struct Emitter<TCloudAdapter, TDataAdapterSelector> {
     /// The shared signal store
     signals: Arc<SignalStore>,
 
-    /// The cloud adapter used to emit data to the cloud
+    /// The cloud adapter used to emit data to the cloud.
+    /// This can be a `CloudAdapterRouter` to emit data with several cloud adapters
     cloud_adapter: TCloudAdapter,
 
     /// The data adapter selector
     data_adapter_selector: Arc<Mutex<TDataAdapterSelector>>,
+
+    /// The clock used for scheduling emissions and timestamping messages
+    clock: Arc<dyn Clock>,
+
+    /// When new values are requested relative to emitting signals
+    value_request_mode: ValueRequestMode,
+
+    /// The ids of signals whose values have been requested ahead of their next emission
+    prefetched: StdMutex<HashSet<String>>,
+
+    /// The number of values that could not be converted for each signal
+    conversion_failures: StdMutex<HashMap<String, u64>>,
+
+    /// The raw value that each signal with privacy noise last emitted, as bits, and the noise that was added to it.
+    /// The noise is reused while the value doesn't change so that averaging repeated emissions doesn't reveal the value
+    privacy_noise: StdMutex<HashMap<String, (u64, f64)>>,
+
+    /// The last converted value of each signal, which is reused while its value and conversion policy don't change
+    converted_values: StdMutex<HashMap<String, ConvertedValue>>,
+
+    /// The interval at which the cloud is checked for backfill requests.
+    /// If this is `None`, the cloud is never checked
+    backfill_poll_interval_ms: Option<u64>,
+
+    /// The flight recorder whose captures are emitted once they're ready, if enabled
+    flight_recorder: Option<Arc<FlightRecorder>>,
+
+    /// The pacer which spreads overdue signals over a ramp-up period, if enabled
+    pacer: Option<StdMutex<EmissionPacer>>,
+
+    /// The reporter which tracks Freyja's operational status and creates status signals, if enabled
+    status_reporter: Option<StatusReporter>,
+
+    /// The provider which suppresses designated signals while the vehicle is inside a private zone, if enabled
+    geofences: Option<GeofenceConditionProvider>,
+
+    /// When values are recorded as emitted
+    emission_acknowledgement_mode: EmissionAcknowledgementMode,
+
+    /// The ids of signals whose emitted values are waiting for the cloud to acknowledge them.
+    /// This is shared with the tasks which wait for the acknowledgements
+    awaiting_acknowledgement: Arc<StdMutex<HashSet<String>>>,
+
+    /// The heartbeat which tracks the progress of the emission loop
+    heartbeat: Arc<Heartbeat>,
 }
 
-impl<TCloudAdapter: CloudAdapter, TDataAdapterSelector: DataAdapterSelector>
+impl<TCloudAdapter: CloudMessageSender, TDataAdapterSelector: DataAdapterSelector>
     Emitter<TCloudAdapter, TDataAdapterSelector>
 {
     /// Creates a new instance of the Emitter
     ///
     /// # Arguments
     /// - `signals`: the shared signal store
-    /// - `cloud_adapter`: the cloud adapter used to emit to the cloud
+    /// - `cloud_adapter`: the cloud adapter or router used to emit to the cloud
     /// - `data_adapter_selector`: the data adapter selector
+    /// - `clock`: the clock used for scheduling emissions and timestamping messages
+    /// - `value_request_mode`: when new values are requested relative to emitting signals
+    /// - `backfill_poll_interval_ms`: the interval at which the cloud is checked for backfill requests, if at all
+    /// - `flight_recorder`: the flight recorder whose captures are emitted, if enabled
+    /// - `emission_ramp_up_ms`: the period over which overdue signals are spread after a start or reconnect, if at all
+    /// - `status_reporter`: the reporter whose status signals are emitted, if enabled
+    /// - `geofences`: the provider which suppresses designated signals inside private zones, if enabled
+    /// - `emission_acknowledgement_mode`: when values are recorded as emitted
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         signals: Arc<SignalStore>,
         cloud_adapter: TCloudAdapter,
         data_adapter_selector: Arc<Mutex<TDataAdapterSelector>>,
+        clock: Arc<dyn Clock>,
+        value_request_mode: ValueRequestMode,
+        backfill_poll_interval_ms: Option<u64>,
+        flight_recorder: Option<Arc<FlightRecorder>>,
+        emission_ramp_up_ms: Option<u64>,
+        status_reporter: Option<StatusReporter>,
+        geofences: Option<GeofenceConditionProvider>,
+        emission_acknowledgement_mode: EmissionAcknowledgementMode,
     ) -> Self {
         Self {
             signals,
             cloud_adapter,
             data_adapter_selector,
+            clock,
+            value_request_mode,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms,
+            flight_recorder,
+            pacer: emission_ramp_up_ms
+                .map(|ramp_up_ms| StdMutex::new(EmissionPacer::new(ramp_up_ms))),
+            status_reporter,
+            geofences,
+            emission_acknowledgement_mode,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         }
     }
 
-    /// Execute this Emitter
-    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut sleep_interval = u64::MAX;
+    /// Gets the heartbeat which tracks the progress of the emission loop
+    pub fn heartbeat(&self) -> Arc<Heartbeat> {
+        self.heartbeat.clone()
+    }
+
+    /// Execute this Emitter in a new task until the cancellation token is cancelled.
+    /// Cancellation is only observed between emissions, so a signal is never partially emitted.
+    ///
+    /// # Arguments
+    /// - `cancellation_token`: the token for stopping the emitter
+    pub fn run(
+        self: Arc<Self>,
+        cancellation_token: CancellationToken,
+    ) -> JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>
+    where
+        TCloudAdapter: Send + Sync + 'static,
+        TDataAdapterSelector: Send + Sync + 'static,
+    {
+        tokio::spawn(crash_report::in_component(
+            FreyjaComponent::Emitter,
+            async move { self.run_until_cancelled(cancellation_token).await },
+        ))
+    }
+
+    /// Sends a message which doesn't belong to a signal to the cloud, such as a status message.
+    /// The message is sent with the cloud adapters that its metadata is routed to
+    ///
+    /// # Arguments
+    /// - `message`: the message to send
+    pub async fn send_status(
+        &self,
+        message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        self.cloud_adapter.send_to_cloud(message).await
+    }
+
+    /// Runs the emission loop until the cancellation token is cancelled
+    ///
+    /// # Arguments
+    /// - `cancellation_token`: the token for stopping the emitter
+    async fn run_until_cancelled(
+        &self,
+        cancellation_token: CancellationToken,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // The time at which the emission times were last updated according to the clock's monotonic time
+        let mut last_update: Option<Duration> = None;
+        let mut next_backfill_check_ms: u64 = 0;
+        let mut next_status_emission_ms: u64 = 0;
         loop {
-            // Update the emission times and get the list of all signals.
-            // This is performed as a single operation to minimize the impact of changes to the signal set during processing.
-            // Note that the first time the loop is executed sleep_interval will still be u64::MAX,
+            self.heartbeat.beat();
+
+            // The elapsed time is measured rather than assumed to be the previous sleep interval,
+            // so the time spent emitting and any oversleeping don't accumulate as drift.
+            // Only whole milliseconds are consumed and the remainder is carried over to the next iteration.
+            // Note that the first time the loop is executed the elapsed time is u64::MAX,
             // which will have the effect of force-emitting every signal in the store (though typically there won't be anything).
-            // After that, the intervals will be no more than the max configured interval.
-            let signals = self
+            let now = self.clock.monotonic_now();
+            let elapsed_ms = match last_update {
+                Some(previous) => {
+                    let elapsed_ms =
+                        u64::try_from(now.saturating_sub(previous).as_millis()).unwrap_or(u64::MAX);
+                    last_update = previous.checked_add(Duration::from_millis(elapsed_ms));
+                    elapsed_ms
+                }
+                None => u64::MAX,
+            };
+            let updated_at = *last_update.get_or_insert(now);
+
+            // Update the emission times and get the emission descriptors of the signals which are due soon.
+            // Only these signals are visited, since the store keeps the signals ordered by their next emission.
+            // This is performed as a single operation to minimize the impact of changes to the signal set during processing.
+            let descriptors = self
                 .signals
-                .update_emission_times_and_get_all(sleep_interval);
+                .update_emission_times(elapsed_ms, self.lookahead_ms());
+
+            if let Some(backfill_poll_interval_ms) = self.backfill_poll_interval_ms {
+                next_backfill_check_ms = next_backfill_check_ms.saturating_sub(elapsed_ms);
+                if next_backfill_check_ms == 0 {
+                    self.backfill().await;
+                    next_backfill_check_ms = backfill_poll_interval_ms;
+                }
+            }
+
+            if let Some(status_reporter) = self.status_reporter.as_ref() {
+                next_status_emission_ms = next_status_emission_ms.saturating_sub(elapsed_ms);
+                if next_status_emission_ms == 0 {
+                    self.emit_status().await;
+                    next_status_emission_ms = status_reporter.interval_ms();
+                }
+            }
 
-            sleep_interval = self.emit_data(signals).await?;
+            let mut sleep_interval = self.emit_data(descriptors).await?;
+            if self.backfill_poll_interval_ms.is_some() {
+                sleep_interval = min(sleep_interval, next_backfill_check_ms);
+            }
+
+            if self.status_reporter.is_some() {
+                sleep_interval = min(sleep_interval, next_status_emission_ms);
+            }
+
+            self.flush_flight_recorder().await;
+
+            // The sleep interval starts when the emission times were updated,
+            // so the time spent processing this iteration is subtracted from it
+            let sleep_interval = Duration::from_millis(sleep_interval);
+            let mut sleep_duration = match updated_at.checked_add(sleep_interval) {
+                Some(deadline) => deadline.saturating_sub(self.clock.monotonic_now()),
+                None => sleep_interval,
+            };
+
+            if let Some(time_until_capture_ready) = self
+                .flight_recorder
+                .as_ref()
+                .and_then(|recorder| recorder.time_until_capture_ready())
+            {
+                // Wake up as soon as the capture in progress is ready, but not without delay
+                // so that the values received at the end of the post-trigger window are included
+                sleep_duration = min(
+                    sleep_duration,
+                    time_until_capture_ready.max(Duration::from_millis(1)),
+                );
+            }
 
-            info!("Checking for next emission in {sleep_interval}ms\n");
-            sleep(Duration::from_millis(sleep_interval)).await;
+            info!(
+                "Checking for next emission in {}ms\n",
+                sleep_duration.as_millis()
+            );
+            self.heartbeat.idle();
+            tokio::select! {
+                biased;
+                _ = cancellation_token.cancelled() => {
+                    info!("Emitter stopped");
+                    return Ok(());
+                }
+                _ = self.clock.sleep(sleep_duration) => {}
+            }
         }
     }
 
-    /// Performs data emissions of the provided signals.
+    /// Performs data emissions of the signals which are due according to the provided emission descriptors.
+    /// Only the due signals are read from the signal store.
     /// Returns the amount of time that the main emitter loop should sleep before the next iteration.
     ///
     /// # Arguments
-    /// - `signals`: the set of signals to emit
-    async fn emit_data(&self, signals: Vec<Signal>) -> Result<u64, EmitterError> {
-        if signals.is_empty() {
+    /// - `descriptors`: the emission descriptors of the signals to emit
+    async fn emit_data(&self, descriptors: Vec<EmissionDescriptor>) -> Result<u64, EmitterError> {
+        if descriptors.is_empty() {
             Ok(DEFAULT_SLEEP_INTERVAL_MS)
         } else {
             info!("********************BEGIN EMISSION********************");
             let mut sleep_interval = u64::MAX;
+            let mut due_descriptors = Vec::new();
 
-            for signal in signals {
-                if signal.emission.next_emission_ms > 0 {
+            for descriptor in descriptors {
+                if descriptor.next_emission_ms > 0 {
                     // Don't emit this signal on this iteration, but use the value to update the sleep interval
-                    sleep_interval = min(sleep_interval, signal.emission.next_emission_ms);
+                    sleep_interval = min(
+                        sleep_interval,
+                        self.time_until_next_iteration(descriptor.next_emission_ms),
+                    );
+
+                    if let ValueRequestMode::Prefetch { lead_time_ms } = self.value_request_mode {
+                        let should_prefetch = descriptor.next_emission_ms <= lead_time_ms
+                            && self
+                                .prefetched
+                                .lock()
+                                .unwrap()
+                                .insert(descriptor.id.clone());
+
+                        if should_prefetch {
+                            self.request_value(&descriptor.id).await;
+                        }
+                    }
 
                     // Go to next signal
                     continue;
                 } else {
                     // We will emit this signal since the timer is expired,
-                    // but need to also check the new interval in case it's smaller than the remaining intervals
-                    sleep_interval = min(sleep_interval, signal.emission.policy.interval_ms);
+                    // but need to also check the new interval in case it's smaller than the remaining intervals.
+                    // An adaptive interval can be halved by this emission, so check the shortest possible interval.
+                    // The next emission is one interval after the signal became due rather than after this emission,
+                    // unless it has been due for a whole interval
+                    let next_interval_ms = match descriptor.adaptive_interval {
+                        Some(bounds) => max(bounds.min_interval_ms, descriptor.interval_ms / 2),
+                        None => descriptor.interval_ms,
+                    };
+                    let time_until_emission_ms = if descriptor.overdue_ms < next_interval_ms {
+                        next_interval_ms - descriptor.overdue_ms
+                    } else {
+                        next_interval_ms
+                    };
+                    sleep_interval = min(
+                        sleep_interval,
+                        self.time_until_next_iteration(time_until_emission_ms),
+                    );
+                }
+
+                due_descriptors.push(descriptor);
+            }
+
+            if let Some(pacer) = self.pacer.as_ref() {
+                let mut pacer = pacer.lock().unwrap();
+                let admitted = pacer.admit(self.clock.now(), due_descriptors.len());
+                let deferred = due_descriptors.len().saturating_sub(admitted);
+                if admitted < due_descriptors.len() {
+                    // The remaining signals stay due, so they're emitted on a later iteration
+                    info!(
+                        "Pacing emissions: emitting {admitted} of {} due signals",
+                        due_descriptors.len()
+                    );
+                    due_descriptors.truncate(admitted);
+                }
+
+                if let Some(status_reporter) = self.status_reporter.as_ref() {
+                    status_reporter.set_pending_emissions(deferred);
+                }
+
+                if let Some(time_until_next_admission_ms) = pacer.time_until_next_admission_ms() {
+                    sleep_interval = min(sleep_interval, time_until_next_admission_ms);
+                }
+            }
+
+            // Signals which were removed from the store since the emission times were updated are skipped.
+            // The generation is noted so that changes to the mapping during the emission can be detected
+            // without holding the store's lock for the whole emission
+            let generation = self.signals.generation();
+            let mut due_signals: Vec<Signal> = due_descriptors
+                .iter()
+                .filter_map(|descriptor| self.signals.get(&descriptor.id))
+                .collect();
+
+            if let ValueRequestMode::AwaitFresh { timeout_ms } = self.value_request_mode {
+                due_signals = self.await_fresh_values(due_signals, timeout_ms).await;
+            }
+
+            let suppressed_signal_ids = self.geofence_suppressed_signal_ids();
+            for signal in due_signals {
+                let _signal_scope = crash_report::enter_signal(&signal.id);
+                if self
+                    .awaiting_acknowledgement
+                    .lock()
+                    .unwrap()
+                    .contains(&signal.id)
+                {
+                    info!(
+                        "Signal {} is waiting for the cloud to acknowledge its last value. Skipping emission for this signal.",
+                        signal.id
+                    );
+
+                    // Go to next signal
+                    continue;
+                }
+
+                match self.value_request_mode {
+                    // Submit a request for a new value for the next iteration.
+                    // This approach to requesting signal values introduces an inherent delay in uploading data
+                    // of signal.emission.policy.interval_ms, which the other modes avoid.
+                    ValueRequestMode::AfterEmission => {
+                        self.request_value(&signal.id).await;
+                    }
+                    // The value was already requested before waiting for fresh values
+                    ValueRequestMode::AwaitFresh { .. } => {}
+                    // If the value wasn't prefetched, fall back to requesting it for the next iteration
+                    ValueRequestMode::Prefetch { .. } => {
+                        let was_prefetched = self.prefetched.lock().unwrap().remove(&signal.id);
+                        if !was_prefetched {
+                            self.request_value(&signal.id).await;
+                        }
+                    }
                 }
 
-                // Submit a request for a new value for the next iteration.
-                // This approach to requesting signal values introduces an inherent delay in uploading data
-                // of signal.emission.policy.interval_ms and needs to be revisited.
-                let selector_result = {
-                    let data_adapter_selector = self.data_adapter_selector.lock().await;
-                    data_adapter_selector
-                        .request_entity_value(&signal.id)
-                        .await
-                        .map_err(EmitterError::data_adapter_error)
-                };
-
-                if selector_result.is_err() {
-                    log::error!("Error submitting request for signal value while processing signal {}: {:?}", signal.id, selector_result.err());
+                // If the mapping changed during this emission, signals which were removed are no longer emitted.
+                // Signals which were added are emitted on a later iteration
+                if self.signals.generation() != generation && self.signals.get(&signal.id).is_none()
+                {
+                    info!(
+                        "Signal {} was removed from the signal store during this emission. Skipping emission for this signal.",
+                        signal.id
+                    );
+
+                    // Go to next signal
+                    continue;
                 }
 
                 if signal.value.is_none() {
@@ -120,20 +471,49 @@ impl<TCloudAdapter: CloudAdapter, TDataAdapterSelector: DataAdapterSelector>
                     continue;
                 }
 
+                if !self.is_triggered(&signal) {
+                    // Go to next signal
+                    continue;
+                }
+
+                if suppressed_signal_ids.contains(&signal.id) {
+                    info!(
+                        "Signal {} is suppressed at the vehicle's location. Skipping emission for this signal.",
+                        signal.id
+                    );
+
+                    // Go to next signal
+                    continue;
+                }
+
                 if signal.emission.policy.emit_only_if_changed
                     && signal.emission.last_emitted_value.is_some()
                     && signal.value == signal.emission.last_emitted_value
                 {
                     info!("Signal {} did not change and has already been emitted. Skipping emission for this signal.", signal.id);
 
+                    // A stable value lengthens an adaptive interval even though it isn't emitted
+                    adapt_interval(&self.signals, &signal);
+
                     // Go to next signal
                     continue;
                 }
 
                 let signal_id = signal.id.clone();
-                let send_to_cloud_result = self.send_to_cloud(signal).await;
+                let send_to_cloud_result = self.send_to_cloud(signal, None).await;
+
+                if let (Some(pacer), Err(e)) = (self.pacer.as_ref(), send_to_cloud_result.as_ref())
+                {
+                    if e.kind() == EmitterErrorKind::CloudError {
+                        pacer.lock().unwrap().on_cloud_failure();
+                    }
+                }
 
                 if send_to_cloud_result.is_err() {
+                    if let Some(status_reporter) = self.status_reporter.as_ref() {
+                        status_reporter.record_emission_error();
+                    }
+
                     log::error!(
                         "Error sending data to cloud while processing signal {}: {:?}",
                         signal_id,
@@ -148,20 +528,451 @@ impl<TCloudAdapter: CloudAdapter, TDataAdapterSelector: DataAdapterSelector>
         }
     }
 
-    /// Applies a conversion implicitly to a signal value and sends it to the cloud
+    /// Returns true if the signal's trigger is satisfied by the current values of the signals in the store,
+    /// or if the signal has no trigger. Triggers which can't be evaluated are never satisfied.
+    ///
+    /// # Arguments
+    /// - `signal`: the signal to check
+    fn is_triggered(&self, signal: &Signal) -> bool {
+        let expression = match signal.emission.policy.trigger.as_deref() {
+            Some(expression) => expression,
+            None => return true,
+        };
+
+        match trigger::evaluate(expression, |id| {
+            self.signals.get(&id.to_string()).and_then(|s| s.value)
+        }) {
+            Ok(true) => true,
+            Ok(false) => {
+                info!(
+                    "Trigger for signal {} is not satisfied. Skipping emission for this signal.",
+                    signal.id
+                );
+                false
+            }
+            Err(e) => {
+                log::error!(
+                    "Error evaluating trigger for signal {}: {e}. Skipping emission for this signal.",
+                    signal.id
+                );
+                false
+            }
+        }
+    }
+
+    /// Gets the ids of the signals which must not be emitted because the vehicle is inside a private zone,
+    /// based on the current location in the signal store
+    fn geofence_suppressed_signal_ids(&self) -> HashSet<String> {
+        match self.geofences.as_ref() {
+            Some(geofences) => geofences.suppressed_signal_ids(|id| {
+                self.signals.get(&id.to_string()).and_then(|s| s.value)
+            }),
+            None => HashSet::new(),
+        }
+    }
+
+    /// Emits the status signals which report Freyja's operational status.
+    /// Status signals aren't stored in the signal store, so they're only emitted at the status interval
+    async fn emit_status(&self) {
+        let status_reporter = match self.status_reporter.as_ref() {
+            Some(status_reporter) => status_reporter,
+            None => return,
+        };
+
+        let conversion_failures = self.conversion_failures.lock().unwrap().values().sum();
+        let messages = status_reporter.messages(
+            self.signals.get_all().len(),
+            conversion_failures,
+            self.clock.now(),
+        );
+
+        for message in messages {
+            if let Err(e) = self.cloud_adapter.send_to_cloud(message).await {
+                warn!("Error sending status signal to cloud: {e:?}");
+            }
+        }
+    }
+
+    /// Checks the cloud for backfill requests and emits the current values of the requested signals.
+    /// Requests can also trigger a flight recorder capture, which is emitted once it's ready.
+    /// The values are emitted even if they haven't changed since they were last emitted,
+    /// and new values are not requested from the providers. Signals which are suppressed at the vehicle's location are not emitted.
+    async fn backfill(&self) {
+        let request = match self.cloud_adapter.check_for_backfill().await {
+            Ok(request) => request,
+            Err(e) => {
+                warn!(
+                    "Error checking for backfill requests: {:?}",
+                    EmitterError::cloud_error(e)
+                );
+                return;
+            }
+        };
+
+        if request.flight_recorder {
+            match &self.flight_recorder {
+                Some(recorder) if recorder.trigger() => {
+                    info!("Flight recorder capture triggered by the cloud")
+                }
+                Some(_) => {
+                    info!("Ignoring flight recorder trigger since a capture is already in progress")
+                }
+                None => warn!(
+                    "Ignoring flight recorder trigger since the flight recorder is not configured"
+                ),
+            }
+        }
+
+        if request.is_empty() {
+            return;
+        }
+
+        // The backfill id is included so that backfilled values aren't dropped as duplicates of earlier emissions
+        let backfill_id = format!(
+            "backfill-{}",
+            self.clock.now().unix_timestamp_nanos() / 1_000_000
+        );
+        info!("Emitting the current values of the signals requested by the cloud");
+        let suppressed_signal_ids = self.geofence_suppressed_signal_ids();
+        for signal in self.signals.get_all() {
+            if !request.includes(&signal.id) {
+                continue;
+            }
+
+            if suppressed_signal_ids.contains(&signal.id) {
+                info!(
+                    "Signal {} is suppressed at the vehicle's location. Skipping backfill for this signal.",
+                    signal.id
+                );
+                continue;
+            }
+
+            if signal.value.is_none() {
+                info!(
+                    "No signal value for {} in our cache. Skipping backfill for this signal.",
+                    signal.id
+                );
+                continue;
+            }
+
+            let signal_id = signal.id.clone();
+            if let Err(e) = self.send_to_cloud(signal, Some(&backfill_id)).await {
+                log::error!(
+                    "Error sending data to cloud while backfilling signal {signal_id}: {e:?}"
+                );
+            }
+        }
+    }
+
+    /// Emits the flight recorder's capture if it's ready.
+    /// Captured values are emitted as they were received, without conversions and with their original timestamps,
+    /// and are marked with the time at which the capture was triggered.
+    /// Values of signals with a privacy policy are not emitted since they can't be emitted with full precision,
+    /// and neither are values of signals which are suppressed at the vehicle's location.
+    async fn flush_flight_recorder(&self) {
+        let capture = match self
+            .flight_recorder
+            .as_ref()
+            .and_then(|recorder| recorder.take_capture())
+        {
+            Some(capture) => capture,
+            None => return,
+        };
+
+        let capture_id = (capture.triggered_at.unix_timestamp_nanos() / 1_000_000).to_string();
+        info!(
+            "Emitting {} values captured by the flight recorder",
+            capture.records.len()
+        );
+
+        let suppressed_signal_ids = self.geofence_suppressed_signal_ids();
+        for (signal_id, record) in capture.records {
+            if suppressed_signal_ids.contains(&signal_id) {
+                debug!("Signal {signal_id} is suppressed at the vehicle's location. Skipping its captured values.");
+                continue;
+            }
+
+            let mut metadata = match self.signals.get(&signal_id) {
+                Some(signal) if signal.emission.policy.privacy.is_some() => {
+                    debug!(
+                        "Signal {signal_id} has a privacy policy. Skipping its captured values."
+                    );
+                    continue;
+                }
+                Some(signal) => signal.target.cloud_metadata(),
+                None => {
+                    warn!(
+                        "Signal {signal_id} is no longer registered. Skipping its captured values."
+                    );
+                    continue;
+                }
+            };
+            metadata.insert(
+                FLIGHT_RECORDER_CAPTURE_METADATA_KEY.to_string(),
+                capture_id.clone(),
+            );
+
+            let cloud_message = CloudMessageRequest {
+                metadata,
+                numeric_value: record.value.parse().ok(),
+                signal_value: record.value.clone(),
+                signal_timestamp: record.timestamp,
+                // The capture id is included so that captured values aren't dropped as duplicates of regular emissions
+                idempotency_key: Some(format!(
+                    "{capture_id}/{}",
+                    CloudMessageRequest::idempotency_key(&signal_id, record.timestamp)
+                )),
+            };
+
+            if let Err(e) = self.cloud_adapter.send_to_cloud(cloud_message).await {
+                log::error!(
+                    "Error sending data to cloud while emitting the flight recorder capture for signal {signal_id}: {:?}",
+                    EmitterError::cloud_error(e)
+                );
+            }
+        }
+    }
+
+    /// Gets how far ahead the emitter needs to know which signals will be due.
+    /// In prefetch mode, this is the lead time for requesting values.
+    fn lookahead_ms(&self) -> u64 {
+        match self.value_request_mode {
+            ValueRequestMode::Prefetch { lead_time_ms } => lead_time_ms,
+            _ => 0,
+        }
+    }
+
+    /// Gets the time until the emitter loop should run again for a signal which will be emitted after the provided time.
+    /// In prefetch mode, this is early enough to request the signal's value ahead of the emission.
+    ///
+    /// # Arguments
+    /// - `time_until_emission_ms`: the time until the signal's next emission
+    fn time_until_next_iteration(&self, time_until_emission_ms: u64) -> u64 {
+        match self.value_request_mode {
+            ValueRequestMode::Prefetch { lead_time_ms }
+                if time_until_emission_ms > lead_time_ms =>
+            {
+                time_until_emission_ms - lead_time_ms
+            }
+            _ => time_until_emission_ms,
+        }
+    }
+
+    /// Submits a request for a new value of a signal.
+    /// Returns true if the request was submitted.
+    ///
+    /// # Arguments
+    /// - `signal_id`: the id of the signal
+    async fn request_value(&self, signal_id: &str) -> bool {
+        let selector_result = {
+            let data_adapter_selector = self.data_adapter_selector.lock().await;
+            data_adapter_selector.request_entity_value(signal_id).await
+        };
+
+        match selector_result {
+            Ok(_) => true,
+            Err(e) if e.kind() == DataAdapterSelectorErrorKind::RateLimited => {
+                // The provider's request budget is exhausted and never refills, so the cached value will be used
+                debug!("Skipped requesting a new value for signal {signal_id}: {e}");
+                false
+            }
+            Err(e) => {
+                log::error!(
+                    "Error submitting request for signal value while processing signal {}: {:?}",
+                    signal_id,
+                    EmitterError::data_adapter_error(e)
+                );
+                false
+            }
+        }
+    }
+
+    /// Requests new values for the provided signals and waits until every signal with the `Get` operation
+    /// has received a new value or the timeout expires.
+    /// Returns the signals with their latest values from the signal store.
+    ///
+    /// # Arguments
+    /// - `signals`: the signals to request values for
+    /// - `timeout_ms`: the maximum time to wait for new values
+    async fn await_fresh_values(&self, signals: Vec<Signal>, timeout_ms: u64) -> Vec<Signal> {
+        let requested_at = self.clock.now();
+        let mut pending = HashSet::new();
+        for signal in signals.iter() {
+            if self.request_value(&signal.id).await
+                && signal.source.supports_operation(&Operation::Get)
+            {
+                pending.insert(signal.id.clone());
+            }
+        }
+
+        let mut waited_ms = 0;
+        loop {
+            pending.retain(|id| {
+                !self
+                    .signals
+                    .get(id)
+                    .and_then(|s| s.value_timestamp)
+                    .is_some_and(|timestamp| timestamp >= requested_at)
+            });
+
+            if pending.is_empty() || waited_ms >= timeout_ms {
+                break;
+            }
+
+            let poll_interval_ms = min(FRESH_VALUE_POLL_INTERVAL_MS, timeout_ms - waited_ms);
+            self.clock
+                .sleep(Duration::from_millis(poll_interval_ms))
+                .await;
+            waited_ms += poll_interval_ms;
+        }
+
+        if !pending.is_empty() {
+            info!("Timed out waiting for fresh values for signals {pending:?}. Cached values will be used.");
+        }
+
+        signals
+            .into_iter()
+            .map(|signal| self.signals.get(&signal.id).unwrap_or(signal))
+            .collect()
+    }
+
+    /// Applies a conversion implicitly to a signal value and sends it to the cloud.
+    /// Returns `None` if the value could not be converted and the signal's policy is to skip it.
     ///
     /// # Arguments
     /// - `signal`: the signal to emit
-    async fn send_to_cloud(&self, signal: Signal) -> Result<CloudMessageResponse, EmitterError> {
+    /// - `idempotency_key_prefix`: a prefix for the message's idempotency key, such as the id of a backfill, so that a value emitted again on purpose isn't dropped as a duplicate
+    async fn send_to_cloud(
+        &self,
+        signal: Signal,
+        idempotency_key_prefix: Option<&str>,
+    ) -> Result<Option<CloudMessageResponse>, EmitterError> {
         let value = signal
             .value
             .clone()
             // This error case should actually be unreachable, but always good to check!
             .ok_or::<EmitterError>(EmitterErrorKind::SignalValueEmpty.into())?;
 
-        let converted = value.parse::<f32>().map_or(value.clone(), |v| {
-            signal.emission.policy.conversion.apply(v).to_string()
-        });
+        let mut metadata = signal.target.cloud_metadata();
+        let mut numeric_value = None;
+        let mut threshold_state = None;
+        let policy = &signal.emission.policy;
+        // Values set through the signal store have already been parsed
+        let parsed = match signal.numeric_value {
+            Some(v) => Ok(v),
+            None => value.parse::<f64>(),
+        };
+        let converted = match parsed {
+            // Values without a conversion share the stored value if formatting the number wouldn't change it
+            Ok(v)
+                if policy.conversion == Conversion::None
+                    && policy.privacy.is_none()
+                    && policy.decimal_places.is_none()
+                    && v.to_string() == *value =>
+            {
+                numeric_value = Some(v);
+                Ok(value.clone())
+            }
+            Ok(v) => match self.get_converted_value(&signal.id, &value, policy) {
+                Some((converted, cached_numeric_value)) => {
+                    numeric_value = cached_numeric_value;
+                    Ok(converted)
+                }
+                None => {
+                    let mut number = policy.conversion.apply(v);
+                    // The privacy policy is in the units of the converted value
+                    if let Some(privacy) = &policy.privacy {
+                        let noise_sample = match &privacy.noise {
+                            Some(noise) => self.get_privacy_noise(&signal.id, v, noise),
+                            None => 0.0,
+                        };
+                        number = privacy.apply_with_noise(number, noise_sample);
+                    }
+
+                    let (converted, rounded) = Self::format_number(number, policy.decimal_places);
+                    let result = match &policy.conversion {
+                        // Templated values are only emitted as strings
+                        Conversion::Template { template } => {
+                            conversion::apply_template(template, rounded)
+                                .map(Arc::from)
+                                .map_err(|e| e.to_string())
+                        }
+                        // Thresholds are evaluated against the unrounded value
+                        Conversion::Threshold { on, off } => {
+                            let previous = signal.emission.threshold_state.unwrap_or(false);
+                            let state = conversion::apply_threshold(*on, *off, v, previous);
+                            threshold_state = Some(state);
+                            Ok(state.to_string().into())
+                        }
+                        _ => {
+                            numeric_value = Some(rounded);
+                            Ok(converted.into())
+                        }
+                    };
+
+                    // Thresholds depend on the previous state, so their results can't be reused
+                    if let (Ok(converted), None) = (&result, threshold_state) {
+                        self.set_converted_value(
+                            &signal.id,
+                            &value,
+                            policy,
+                            converted,
+                            numeric_value,
+                        );
+                    }
+
+                    result
+                }
+            },
+            // Values without a conversion are emitted as-is, unless their precision must be reduced
+            Err(_) if policy.conversion == Conversion::None && policy.privacy.is_none() => {
+                Ok(value.clone())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        let converted = match converted {
+            Ok(converted) => converted,
+            Err(e) => {
+                let failure_count = self.record_conversion_failure(&signal.id);
+                let message = format!(
+                    "Could not convert value {value:?} for signal {} ({failure_count} failure(s) so far): {e}",
+                    signal.id
+                );
+
+                match policy.conversion_failure_policy {
+                    // Values of signals with a privacy policy are never emitted without it
+                    ConversionFailurePolicy::EmitRaw
+                    | ConversionFailurePolicy::EmitWithQualityFlag
+                        if policy.privacy.is_some() =>
+                    {
+                        warn!(
+                            "{message}. Skipping emission since the signal has a privacy policy."
+                        );
+                        return Ok(None);
+                    }
+                    ConversionFailurePolicy::EmitRaw => {
+                        debug!("{message}. Emitting the raw value.");
+                        value.clone()
+                    }
+                    ConversionFailurePolicy::SkipAndWarn => {
+                        warn!("{message}. Skipping emission for this signal.");
+                        return Ok(None);
+                    }
+                    ConversionFailurePolicy::EmitWithQualityFlag => {
+                        warn!("{message}. Emitting the raw value with a quality flag.");
+                        metadata.insert(
+                            QUALITY_METADATA_KEY.to_string(),
+                            CONVERSION_FAILED_QUALITY.to_string(),
+                        );
+                        value.clone()
+                    }
+                    ConversionFailurePolicy::Error => {
+                        return Err(EmitterError::conversion_failed(message));
+                    }
+                }
+            }
+        };
 
         info!(
             "Digital Twin Instance {:?}: {}",
@@ -171,9 +982,17 @@ impl<TCloudAdapter: CloudAdapter, TDataAdapterSelector: DataAdapterSelector>
         info!("\t(from {}: {:?})", signal.source.id, signal.value);
 
         let cloud_message = CloudMessageRequest {
-            metadata: signal.target.metadata.clone(),
+            metadata,
             signal_value: converted,
-            signal_timestamp: OffsetDateTime::now_utc(),
+            numeric_value,
+            signal_timestamp: self.clock.now(),
+            idempotency_key: signal.value_timestamp.map(|timestamp| {
+                let key = CloudMessageRequest::idempotency_key(&signal.id, timestamp);
+                match idempotency_key_prefix {
+                    Some(prefix) => format!("{prefix}/{key}"),
+                    None => key,
+                }
+            }),
         };
 
         let response = self
@@ -181,53 +1000,314 @@ impl<TCloudAdapter: CloudAdapter, TDataAdapterSelector: DataAdapterSelector>
             .send_to_cloud(cloud_message)
             .await
             .map_err(EmitterError::cloud_error)?;
+        log_cloud_response(&signal.id, &response);
 
-        // We don't set the last emitted value to the converted value so that we can meaningfully compare
-        // this value with the value coming directly from the signal.
-        self.signals.set_last_emitted_value(signal.id, value);
+        if let Some(state) = threshold_state {
+            self.signals.set_threshold_state(signal.id.clone(), state);
+        }
 
-        Ok(response)
-    }
-}
+        match (
+            self.emission_acknowledgement_mode,
+            response.acknowledgement.clone(),
+        ) {
+            (
+                EmissionAcknowledgementMode::AtAcknowledgement { timeout_ms },
+                Some(acknowledgement),
+            ) => self.record_emission_when_acknowledged(signal, value, acknowledgement, timeout_ms),
+            _ => record_emission(&self.signals, &signal, value),
+        }
 
-proc_macros::error! {
-    EmitterError {
-        SignalValueEmpty,
-        DataAdapterError,
-        CloudError,
+        Ok(Some(response))
     }
-}
-
-#[cfg(test)]
-mod emitter_tests {
-    use super::*;
 
-    use freyja_common::{
-        cloud_adapter::CloudAdapterErrorKind,
-        signal::{Emission, EmissionPolicy},
-    };
-    use freyja_test_common::mocks::{MockCloudAdapter, MockDataAdapterSelector};
+    /// Records the emission of a value once the cloud accepts it, without waiting for the acknowledgement here.
+    /// The signal isn't emitted again until the acknowledgement resolves or times out.
+    /// If the value isn't accepted, the signal stays due so that it's emitted again on the next iteration.
+    ///
+    /// # Arguments
+    /// - `signal`: the signal, as it was before the emission
+    /// - `value`: the emitted value, before it was converted
+    /// - `acknowledgement`: the acknowledgement of the message
+    /// - `timeout_ms`: the maximum time to wait for the acknowledgement
+    fn record_emission_when_acknowledged(
+        &self,
+        signal: Signal,
+        value: Arc<str>,
+        acknowledgement: Acknowledgement,
+        timeout_ms: u64,
+    ) {
+        self.awaiting_acknowledgement
+            .lock()
+            .unwrap()
+            .insert(signal.id.clone());
+
+        let signals = self.signals.clone();
+        let awaiting_acknowledgement = self.awaiting_acknowledgement.clone();
+        let clock = self.clock.clone();
+        tokio::spawn(async move {
+            let accepted = tokio::select! {
+                accepted = acknowledgement.accepted() => accepted,
+                _ = clock.sleep(Duration::from_millis(timeout_ms)) => false,
+            };
+
+            if accepted {
+                record_emission(&signals, &signal, value);
+            } else {
+                warn!(
+                    "The cloud did not accept the value {value:?} of signal {}. It will be emitted again.",
+                    signal.id
+                );
+            }
 
-    #[tokio::test]
-    async fn emit_data_returns_default_on_empty_input() {
-        let uut = Emitter {
-            signals: Arc::new(SignalStore::new()),
-            cloud_adapter: MockCloudAdapter::new(),
-            data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
-        };
+            awaiting_acknowledgement.lock().unwrap().remove(&signal.id);
+        });
+    }
 
-        let result = uut.emit_data(vec![]).await;
+    /// Formats a numeric value for emission, rounding it to the given number of decimal places if provided.
+    /// Returns the formatted value and the corresponding number.
+    ///
+    /// # Arguments
+    /// - `value`: the value to format
+    /// - `decimal_places`: the number of decimal places to round to, or `None` to skip rounding
+    fn format_number(value: f64, decimal_places: Option<u32>) -> (String, f64) {
+        match decimal_places {
+            Some(decimal_places) => {
+                let formatted = format!("{value:.precision$}", precision = decimal_places as usize);
+                // Parse the formatted value so that the number matches the string exactly
+                let rounded = formatted.parse().unwrap_or(value);
+                (formatted, rounded)
+            }
+            None => (value.to_string(), value),
+        }
+    }
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), DEFAULT_SLEEP_INTERVAL_MS);
+    /// Increments the number of values for a signal that could not be converted.
+    /// Returns the updated count.
+    ///
+    /// # Arguments
+    /// - `signal_id`: the id of the signal
+    fn record_conversion_failure(&self, signal_id: &str) -> u64 {
+        let mut conversion_failures = self.conversion_failures.lock().unwrap();
+        let count = conversion_failures
+            .entry(signal_id.to_string())
+            .or_insert(0);
+        *count += 1;
+
+        *count
     }
 
-    #[tokio::test]
-    async fn emit_data_handles_nonzero_next_emission_time() {
-        const NEXT_EMISSION_MS: u64 = 42;
+    /// Gets the converted value of a signal and its numeric form if the signal's value was already converted with the same policy
+    ///
+    /// # Arguments
+    /// - `signal_id`: the id of the signal
+    /// - `raw_value`: the value before conversion
+    /// - `policy`: the signal's emission policy
+    fn get_converted_value(
+        &self,
+        signal_id: &str,
+        raw_value: &Arc<str>,
+        policy: &EmissionPolicy,
+    ) -> Option<(Arc<str>, Option<f64>)> {
+        let converted_values = self.converted_values.lock().unwrap();
+        converted_values
+            .get(signal_id)
+            .filter(|cached| {
+                cached.raw_value == *raw_value
+                    && cached.conversion == policy.conversion
+                    && cached.privacy == policy.privacy
+                    && cached.decimal_places == policy.decimal_places
+            })
+            .map(|cached| (cached.converted.clone(), cached.numeric_value))
+    }
 
-        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
-        mock_data_adapter_selector
+    /// Stores the converted value of a signal so that it can be reused until the signal's value or policy changes
+    ///
+    /// # Arguments
+    /// - `signal_id`: the id of the signal
+    /// - `raw_value`: the value before conversion
+    /// - `policy`: the signal's emission policy
+    /// - `converted`: the converted value
+    /// - `numeric_value`: the converted value as a number, if it's emitted as one
+    fn set_converted_value(
+        &self,
+        signal_id: &str,
+        raw_value: &Arc<str>,
+        policy: &EmissionPolicy,
+        converted: &Arc<str>,
+        numeric_value: Option<f64>,
+    ) {
+        self.converted_values.lock().unwrap().insert(
+            signal_id.to_string(),
+            ConvertedValue {
+                raw_value: raw_value.clone(),
+                conversion: policy.conversion.clone(),
+                privacy: policy.privacy.clone(),
+                decimal_places: policy.decimal_places,
+                converted: converted.clone(),
+                numeric_value,
+            },
+        );
+    }
+
+    /// Gets the privacy noise to add to a signal's value.
+    /// A new sample is only drawn when the raw value differs from the one that the signal last emitted
+    ///
+    /// # Arguments
+    /// - `signal_id`: the id of the signal
+    /// - `raw_value`: the value before conversion
+    /// - `noise`: the noise to sample
+    fn get_privacy_noise(&self, signal_id: &str, raw_value: f64, noise: &Noise) -> f64 {
+        let mut privacy_noise = self.privacy_noise.lock().unwrap();
+        match privacy_noise.get(signal_id) {
+            Some((value_bits, noise_sample)) if *value_bits == raw_value.to_bits() => *noise_sample,
+            _ => {
+                let noise_sample = noise.sample(&mut rand::thread_rng());
+                privacy_noise.insert(signal_id.to_string(), (raw_value.to_bits(), noise_sample));
+                noise_sample
+            }
+        }
+    }
+}
+
+/// Logs what the cloud reported about a signal's message, so that the emission can be matched with the backend's ingestion logs
+///
+/// # Arguments
+/// - `signal_id`: the id of the signal
+/// - `response`: the response to the signal's message
+fn log_cloud_response(signal_id: &str, response: &CloudMessageResponse) {
+    match (response.message_id.as_ref(), response.accepted_at) {
+        (Some(message_id), Some(accepted_at)) => info!(
+            "Signal {signal_id} was accepted by the cloud as message {message_id} at {accepted_at}"
+        ),
+        (Some(message_id), None) => {
+            info!("Signal {signal_id} was sent to the cloud as message {message_id}")
+        }
+        _ => {}
+    }
+
+    for warning in response.warnings.iter() {
+        warn!("The cloud accepted signal {signal_id} with a warning: {warning}");
+    }
+}
+
+/// Records that a signal's value was emitted, which adapts the signal's interval if it has an adaptive interval,
+/// sets its last emitted value, and schedules its next emission
+///
+/// # Arguments
+/// - `signals`: the signal store
+/// - `signal`: the signal, as it was before the emission
+/// - `value`: the emitted value, before it was converted
+fn record_emission(signals: &SignalStore, signal: &Signal, value: Arc<str>) {
+    adapt_interval(signals, signal);
+
+    // We don't set the last emitted value to the converted value so that we can meaningfully compare
+    // this value with the value coming directly from the signal.
+    signals.set_last_emitted_value(signal.id.clone(), value);
+}
+
+/// Adapts the interval of a signal with an adaptive interval to how often its value changes
+/// and schedules its next emission after the new interval.
+/// The interval is halved if the value changed since the last emission and doubled otherwise,
+/// within the bounds from the signal's policy. Signals without an adaptive interval are unaffected.
+///
+/// # Arguments
+/// - `signals`: the signal store
+/// - `signal`: the signal, as it was before the emission
+fn adapt_interval(signals: &SignalStore, signal: &Signal) {
+    let bounds = match signal.emission.policy.adaptive_interval {
+        Some(bounds) => bounds,
+        None => return,
+    };
+
+    // The first emission counts as a change since there's no previous value to compare it with
+    let current_ms = signal.emission.interval_ms();
+    let interval_ms = if signal.value != signal.emission.last_emitted_value {
+        current_ms / 2
+    } else {
+        current_ms.saturating_mul(2)
+    };
+    let interval_ms = interval_ms
+        .min(bounds.max_interval_ms)
+        .max(bounds.min_interval_ms);
+
+    signals.set_adaptive_interval(signal.id.clone(), interval_ms);
+}
+
+proc_macros::error! {
+    EmitterError {
+        SignalValueEmpty,
+        DataAdapterError,
+        CloudError,
+        ConversionFailed,
+    }
+}
+
+#[cfg(test)]
+mod emitter_tests {
+    use super::*;
+
+    use std::num::NonZeroU64;
+
+    use crate::{
+        config::{
+            Coordinates, FlightRecorderConfig, GeofenceArea, GeofenceConfig, GeofencesConfig,
+            StatusSignalsConfig,
+        },
+        status_reporter::SIGNALS_ACTIVE_STATUS_SIGNAL,
+    };
+
+    use freyja_common::{
+        clock::{ManualClock, SystemClock},
+        cloud_adapter::{
+            Acknowledger, BackfillRequest, CloudAdapterErrorKind, STATUS_SIGNAL_METADATA_KEY,
+            TENANT_METADATA_KEY,
+        },
+        entity::{Entity, EntityEndpoint},
+        privacy::PrivacyPolicy,
+        signal::{AdaptiveInterval, Emission, EmissionPolicy, SignalPatch, Target},
+    };
+    use freyja_test_common::{
+        mockall::Sequence,
+        mocks::{MockCloudAdapter, MockDataAdapterSelector},
+        simulated_cloud_adapter::{
+            LatencyDistribution, SimulatedCloudAdapter, SimulatedCloudAdapterConfig,
+        },
+    };
+
+    #[tokio::test]
+    async fn emit_data_returns_default_on_empty_input() {
+        let uut = Emitter {
+            signals: Arc::new(SignalStore::new()),
+            cloud_adapter: MockCloudAdapter::new(),
+            data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        };
+
+        let result = uut.emit_data(vec![]).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), DEFAULT_SLEEP_INTERVAL_MS);
+    }
+
+    #[tokio::test]
+    async fn emit_data_handles_nonzero_next_emission_time() {
+        const NEXT_EMISSION_MS: u64 = 42;
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
             .expect_request_entity_value()
             .never();
         let data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
@@ -239,6 +1319,20 @@ mod emitter_tests {
             signals: Arc::new(SignalStore::new()),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
         let test_signal = Signal {
@@ -249,7 +1343,8 @@ mod emitter_tests {
             ..Default::default()
         };
 
-        let result = uut.emit_data(vec![test_signal]).await;
+        let descriptors = add_signals(&uut.signals, vec![test_signal]);
+        let result = uut.emit_data(descriptors).await;
 
         uut.cloud_adapter.checkpoint();
         uut.data_adapter_selector.lock().await.checkpoint();
@@ -273,16 +1368,30 @@ mod emitter_tests {
         mock_cloud_adapter
             .expect_send_to_cloud()
             .once()
-            .returning(|_| Ok(CloudMessageResponse {}));
+            .returning(|_| Ok(CloudMessageResponse::default()));
 
         let mut uut = Emitter {
             signals: Arc::new(SignalStore::new()),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
         let test_signal = Signal {
-            value: Some("foo".to_string()),
+            value: Some("foo".into()),
             emission: Emission {
                 next_emission_ms: 0,
                 policy: EmissionPolicy {
@@ -294,7 +1403,8 @@ mod emitter_tests {
             ..Default::default()
         };
 
-        let result = uut.emit_data(vec![test_signal]).await;
+        let descriptors = add_signals(&uut.signals, vec![test_signal]);
+        let result = uut.emit_data(descriptors).await;
 
         uut.cloud_adapter.checkpoint();
         uut.data_adapter_selector.lock().await.checkpoint();
@@ -303,6 +1413,72 @@ mod emitter_tests {
         assert_eq!(result.unwrap(), INTERVAL);
     }
 
+    #[tokio::test]
+    async fn emit_data_paces_overdue_signals() {
+        const INTERVAL: u64 = 1000;
+        const RAMP_UP_MS: u64 = 100;
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .once()
+            .returning(|_| Ok(()));
+        let data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = Emitter {
+            signals: Arc::new(SignalStore::new()),
+            cloud_adapter: mock_cloud_adapter,
+            data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: Some(StdMutex::new(EmissionPacer::new(RAMP_UP_MS))),
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        };
+
+        let signals = ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| Signal {
+                id: id.to_string(),
+                value: Some("foo".into()),
+                emission: Emission {
+                    next_emission_ms: 0,
+                    policy: EmissionPolicy {
+                        interval_ms: INTERVAL,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect();
+        let descriptors = add_signals(&uut.signals, signals);
+
+        // Only the first of the four overdue signals is emitted, and the emitter wakes up to emit the next one
+        let result = uut.emit_data(descriptors).await;
+
+        uut.cloud_adapter.checkpoint();
+        uut.data_adapter_selector.lock().await.checkpoint();
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), RAMP_UP_MS / 4);
+    }
+
     #[tokio::test]
     async fn emit_data_doesnt_emit_when_value_empty() {
         const INTERVAL: u64 = 42;
@@ -321,6 +1497,20 @@ mod emitter_tests {
             signals: Arc::new(SignalStore::new()),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
         let test_signal = Signal {
@@ -336,7 +1526,8 @@ mod emitter_tests {
             ..Default::default()
         };
 
-        let result = uut.emit_data(vec![test_signal]).await;
+        let descriptors = add_signals(&uut.signals, vec![test_signal]);
+        let result = uut.emit_data(descriptors).await;
 
         uut.cloud_adapter.checkpoint();
         uut.data_adapter_selector.lock().await.checkpoint();
@@ -363,9 +1554,23 @@ mod emitter_tests {
             signals: Arc::new(SignalStore::new()),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
-        let value = Some("foo".to_string());
+        let value: Option<Arc<str>> = Some("foo".into());
         let test_signal = Signal {
             value: value.clone(),
             emission: Emission {
@@ -376,11 +1581,13 @@ mod emitter_tests {
                     emit_only_if_changed: true,
                     ..Default::default()
                 },
+                ..Default::default()
             },
             ..Default::default()
         };
 
-        let result = uut.emit_data(vec![test_signal]).await;
+        let descriptors = add_signals(&uut.signals, vec![test_signal]);
+        let result = uut.emit_data(descriptors).await;
 
         uut.cloud_adapter.checkpoint();
         uut.data_adapter_selector.lock().await.checkpoint();
@@ -404,29 +1611,45 @@ mod emitter_tests {
         mock_cloud_adapter
             .expect_send_to_cloud()
             .once()
-            .returning(|_| Ok(CloudMessageResponse {}));
+            .returning(|_| Ok(CloudMessageResponse::default()));
 
         let mut uut = Emitter {
             signals: Arc::new(SignalStore::new()),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
         let test_signal = Signal {
-            value: Some("foo".to_string()),
+            value: Some("foo".into()),
             emission: Emission {
                 next_emission_ms: 0,
-                last_emitted_value: Some("bar".to_string()),
+                last_emitted_value: Some("bar".into()),
                 policy: EmissionPolicy {
                     interval_ms: INTERVAL,
                     emit_only_if_changed: true,
                     ..Default::default()
                 },
+                ..Default::default()
             },
             ..Default::default()
         };
 
-        let result = uut.emit_data(vec![test_signal]).await;
+        let descriptors = add_signals(&uut.signals, vec![test_signal]);
+        let result = uut.emit_data(descriptors).await;
 
         uut.cloud_adapter.checkpoint();
         uut.data_adapter_selector.lock().await.checkpoint();
@@ -435,6 +1658,330 @@ mod emitter_tests {
         assert_eq!(result.unwrap(), INTERVAL);
     }
 
+    #[tokio::test]
+    async fn emit_data_only_emits_when_trigger_is_satisfied() {
+        const SPEED_ID: &str = "speed";
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .times(2)
+            .returning(|_| Ok(()));
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+        uut.signals.add(
+            [SignalPatch {
+                id: SPEED_ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        let test_signal = Signal {
+            id: "suspension".to_string(),
+            value: Some("42".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    trigger: Some(format!("{{{SPEED_ID}}} > 100")),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let descriptors = add_signals(&uut.signals, vec![test_signal]);
+
+        uut.signals
+            .set_value(SPEED_ID.to_string(), "50".to_string());
+        assert!(uut.emit_data(descriptors.clone()).await.is_ok());
+
+        uut.signals
+            .set_value(SPEED_ID.to_string(), "120".to_string());
+        assert!(uut.emit_data(descriptors).await.is_ok());
+
+        uut.cloud_adapter.checkpoint();
+        uut.data_adapter_selector.lock().await.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn emit_data_skips_signals_suppressed_by_geofence() {
+        const LATITUDE_ID: &str = "latitude";
+        const LONGITUDE_ID: &str = "longitude";
+        const SUPPRESSED_ID: &str = "suppressed";
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .times(6)
+            .returning(|_| Ok(()));
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| &*request.signal_value == "42")
+            .times(4)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+        uut.geofences = Some(GeofenceConditionProvider::new(GeofencesConfig {
+            latitude_signal_id: LATITUDE_ID.to_string(),
+            longitude_signal_id: LONGITUDE_ID.to_string(),
+            zones: vec![GeofenceConfig {
+                name: String::from("home"),
+                area: GeofenceArea::Circle {
+                    center: Coordinates {
+                        latitude: 47.64,
+                        longitude: -122.13,
+                    },
+                    radius_m: 200.0,
+                },
+                suppressed_signal_ids: HashSet::from([SUPPRESSED_ID.to_string()]),
+            }],
+            emit_when_location_unknown: false,
+        }));
+        uut.signals.add(
+            [LATITUDE_ID, LONGITUDE_ID]
+                .into_iter()
+                .map(|id| SignalPatch {
+                    id: id.to_string(),
+                    ..Default::default()
+                }),
+        );
+
+        let signals = ["emitted", SUPPRESSED_ID]
+            .into_iter()
+            .map(|id| Signal {
+                id: id.to_string(),
+                value: Some("42".into()),
+                ..Default::default()
+            })
+            .collect();
+        let descriptors = add_signals(&uut.signals, signals);
+
+        // The suppressed signal isn't emitted while the location is unknown or inside the geofence
+        assert!(uut.emit_data(descriptors.clone()).await.is_ok());
+
+        uut.signals
+            .set_value(LATITUDE_ID.to_string(), "47.64".to_string());
+        uut.signals
+            .set_value(LONGITUDE_ID.to_string(), "-122.13".to_string());
+        assert!(uut.emit_data(descriptors.clone()).await.is_ok());
+
+        uut.signals
+            .set_value(LATITUDE_ID.to_string(), "47.0".to_string());
+        assert!(uut.emit_data(descriptors).await.is_ok());
+
+        uut.cloud_adapter.checkpoint();
+        uut.data_adapter_selector.lock().await.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn emit_data_skips_signals_removed_during_emission() {
+        const KEPT_ID: &str = "kept";
+        const REMOVED_ID: &str = "removed";
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| request.metadata.get("id") == Some(&KEPT_ID.to_string()))
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        // The first value request syncs a mapping without the removed signal, whichever signal is emitted first
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        let signals = uut.signals.clone();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .times(2)
+            .returning(move |_| {
+                signals.sync(
+                    [SignalPatch {
+                        id: KEPT_ID.to_string(),
+                        target: Target {
+                            metadata: HashMap::from([(String::from("id"), KEPT_ID.to_string())]),
+                            tenant: None,
+                        },
+                        ..Default::default()
+                    }]
+                    .into_iter(),
+                );
+                Ok(())
+            });
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+
+        let signals = [KEPT_ID, REMOVED_ID]
+            .into_iter()
+            .map(|id| Signal {
+                id: id.to_string(),
+                value: Some("42".into()),
+                target: Target {
+                    metadata: HashMap::from([(String::from("id"), id.to_string())]),
+                    tenant: None,
+                },
+                ..Default::default()
+            })
+            .collect();
+        let descriptors = add_signals(&uut.signals, signals);
+
+        assert!(uut.emit_data(descriptors).await.is_ok());
+
+        uut.cloud_adapter.checkpoint();
+        uut.data_adapter_selector.lock().await.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn emit_data_records_emission_when_acknowledged() {
+        const ID: &str = "testid";
+
+        let acknowledgers = Arc::new(StdMutex::new(Vec::new()));
+        let acknowledgers_clone = acknowledgers.clone();
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .times(2)
+            .returning(move |_| {
+                let (acknowledger, acknowledgement) = Acknowledgement::pending();
+                acknowledgers_clone.lock().unwrap().push(acknowledger);
+                Ok(CloudMessageResponse {
+                    acknowledgement: Some(acknowledgement),
+                    ..Default::default()
+                })
+            });
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .returning(|_| Ok(()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+        // The manual clock would let the acknowledgement time out immediately
+        uut.clock = Arc::new(SystemClock);
+        uut.emission_acknowledgement_mode =
+            EmissionAcknowledgementMode::AtAcknowledgement { timeout_ms: 60000 };
+
+        let mut signal = Signal {
+            id: ID.to_string(),
+            value: Some("42".into()),
+            ..Default::default()
+        };
+        signal.emission.policy.emit_only_if_changed = true;
+        let descriptors = add_signals(&uut.signals, vec![signal]);
+
+        async fn acknowledge(
+            acknowledgers: &StdMutex<Vec<Acknowledger>>,
+            awaiting_acknowledgement: &StdMutex<HashSet<String>>,
+            accepted: bool,
+        ) {
+            let acknowledger = acknowledgers.lock().unwrap().pop().unwrap();
+            acknowledger.acknowledge(accepted);
+
+            // Let the task waiting for the acknowledgement record the outcome
+            while !awaiting_acknowledgement.lock().unwrap().is_empty() {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        // The signal isn't emitted again while it's waiting for the acknowledgement
+        assert!(uut.emit_data(descriptors.clone()).await.is_ok());
+        assert!(uut.emit_data(descriptors.clone()).await.is_ok());
+
+        // A rejected value isn't recorded, so it's emitted again even though it didn't change
+        acknowledge(&acknowledgers, &uut.awaiting_acknowledgement, false).await;
+        assert_eq!(
+            uut.signals
+                .get(&ID.to_string())
+                .unwrap()
+                .emission
+                .last_emitted_value,
+            None
+        );
+        assert!(uut.emit_data(descriptors.clone()).await.is_ok());
+
+        acknowledge(&acknowledgers, &uut.awaiting_acknowledgement, true).await;
+        assert_eq!(
+            uut.signals
+                .get(&ID.to_string())
+                .unwrap()
+                .emission
+                .last_emitted_value,
+            Some("42".into())
+        );
+        assert!(uut.emit_data(descriptors).await.is_ok());
+
+        uut.cloud_adapter.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn emit_data_does_not_record_emission_when_acknowledgement_times_out() {
+        const ID: &str = "testid";
+
+        let acknowledgers = Arc::new(StdMutex::new(Vec::new()));
+        let acknowledgers_clone = acknowledgers.clone();
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .once()
+            .returning(move |_| {
+                let (acknowledger, acknowledgement) = Acknowledgement::pending();
+                acknowledgers_clone.lock().unwrap().push(acknowledger);
+                Ok(CloudMessageResponse {
+                    acknowledgement: Some(acknowledgement),
+                    ..Default::default()
+                })
+            });
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .returning(|_| Ok(()));
+
+        let clock = Arc::new(ManualClock::default());
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+        uut.clock = clock.clone();
+        uut.emission_acknowledgement_mode =
+            EmissionAcknowledgementMode::AtAcknowledgement { timeout_ms: 60000 };
+
+        let signal = Signal {
+            id: ID.to_string(),
+            value: Some("42".into()),
+            ..Default::default()
+        };
+        let descriptors = add_signals(&uut.signals, vec![signal]);
+        let start = clock.now();
+
+        assert!(uut.emit_data(descriptors).await.is_ok());
+
+        // The timeout elapses on the emitter's clock, which the manual clock advances without waiting
+        while !uut.awaiting_acknowledgement.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(clock.now() >= start + Duration::from_millis(60000));
+        assert_eq!(
+            uut.signals
+                .get(&ID.to_string())
+                .unwrap()
+                .emission
+                .last_emitted_value,
+            None
+        );
+
+        uut.cloud_adapter.checkpoint();
+    }
+
     #[tokio::test]
     async fn emit_data_emits_when_last_value_empty() {
         const INTERVAL: u64 = 42;
@@ -450,16 +1997,30 @@ mod emitter_tests {
         mock_cloud_adapter
             .expect_send_to_cloud()
             .once()
-            .returning(|_| Ok(CloudMessageResponse {}));
+            .returning(|_| Ok(CloudMessageResponse::default()));
 
         let mut uut = Emitter {
             signals: Arc::new(SignalStore::new()),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
         let test_signal = Signal {
-            value: Some("foo".to_string()),
+            value: Some("foo".into()),
             emission: Emission {
                 next_emission_ms: 0,
                 last_emitted_value: None,
@@ -468,11 +2029,13 @@ mod emitter_tests {
                     emit_only_if_changed: true,
                     ..Default::default()
                 },
+                ..Default::default()
             },
             ..Default::default()
         };
 
-        let result = uut.emit_data(vec![test_signal]).await;
+        let descriptors = add_signals(&uut.signals, vec![test_signal]);
+        let result = uut.emit_data(descriptors).await;
 
         uut.cloud_adapter.checkpoint();
         uut.data_adapter_selector.lock().await.checkpoint();
@@ -500,14 +2063,29 @@ mod emitter_tests {
             signals: Arc::new(SignalStore::new()),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector,
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
         let test_signal = Signal {
-            value: Some("foo".to_string()),
+            value: Some("foo".into()),
             ..Default::default()
         };
 
-        let result = uut.emit_data(vec![test_signal.clone(), test_signal]).await;
+        let descriptors = add_signals(&uut.signals, vec![test_signal.clone(), test_signal]);
+        let result = uut.emit_data(descriptors).await;
 
         uut.cloud_adapter.checkpoint();
         uut.data_adapter_selector.lock().await.checkpoint();
@@ -515,39 +2093,399 @@ mod emitter_tests {
         assert!(result.is_ok());
     }
 
-    #[tokio::test]
-    async fn send_to_cloud_updates_signal_store() {
-        const ID: &str = "testid";
-        const INTERVAL: u64 = 42;
+    /// Creates an emitter which emits signals with a simulated cloud adapter
+    fn create_simulated_emitter(
+        cloud_adapter: SimulatedCloudAdapter,
+    ) -> Emitter<SimulatedCloudAdapter, MockDataAdapterSelector> {
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .returning(|_| Ok(()));
 
-        let mut mock_cloud_adapter = MockCloudAdapter::new();
-        mock_cloud_adapter
-            .expect_send_to_cloud()
-            .returning(|_| Ok(CloudMessageResponse {}));
+        Emitter {
+            signals: Arc::new(SignalStore::new()),
+            cloud_adapter,
+            data_adapter_selector: Arc::new(Mutex::new(mock_data_adapter_selector)),
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        }
+    }
 
-        let test_signal = Signal {
-            id: ID.to_string(),
-            value: Some("foo".to_string()),
-            emission: Emission {
-                policy: EmissionPolicy {
-                    interval_ms: INTERVAL,
+    /// Creates signals with values whose target metadata identifies them
+    fn create_identified_signals(count: usize) -> Vec<Signal> {
+        (0..count)
+            .map(|i| Signal {
+                id: format!("signal{i}"),
+                value: Some(i.to_string().into()),
+                target: Target {
+                    metadata: HashMap::from([(String::from("id"), format!("signal{i}"))]),
                     ..Default::default()
                 },
                 ..Default::default()
-            },
+            })
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn emit_data_waits_for_cloud_latency_of_each_signal() {
+        let cloud_adapter = SimulatedCloudAdapter::new(SimulatedCloudAdapterConfig {
+            latency: LatencyDistribution::Fixed(Duration::from_millis(50)),
             ..Default::default()
-        };
+        })
+        .unwrap();
+        let uut = create_simulated_emitter(cloud_adapter.clone());
 
-        let signals = SignalStore::new();
-        signals.sync([test_signal.clone()].into_iter());
+        let descriptors = add_signals(&uut.signals, create_identified_signals(10));
+        let start = tokio::time::Instant::now();
+        let result = uut.emit_data(descriptors).await;
+
+        assert!(result.is_ok());
+        assert_eq!(cloud_adapter.stats().delivered.len(), 10);
+
+        // Signals are sent one at a time, so an emission takes as long as all of its messages
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_emits_on_schedule_despite_cloud_latency() {
+        let cloud_adapter = SimulatedCloudAdapter::new(SimulatedCloudAdapterConfig {
+            latency: LatencyDistribution::Fixed(Duration::from_millis(30)),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut uut = create_simulated_emitter(cloud_adapter.clone());
+        uut.clock = Arc::new(SystemClock);
+
+        let mut signal = create_get_signal("signal", 1000);
+        signal.value = Some("42".into());
+        add_signals(&uut.signals, vec![signal]);
+
+        // If the time spent sending each message delayed the next emission,
+        // the tenth emission would happen after 9270ms instead of 9000ms
+        let cancellation_token = CancellationToken::new();
+        let stop = async {
+            tokio::time::sleep(Duration::from_millis(9100)).await;
+            cancellation_token.cancel();
+        };
+        let (result, _) = tokio::join!(uut.run_until_cancelled(cancellation_token.clone()), stop);
+
+        assert!(result.is_ok());
+        assert_eq!(cloud_adapter.stats().delivered.len(), 10);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn emit_data_only_records_emissions_which_reach_cloud() {
+        let cloud_adapter = SimulatedCloudAdapter::new(SimulatedCloudAdapterConfig {
+            latency: LatencyDistribution::Exponential {
+                mean: Duration::from_millis(100),
+            },
+            error_rate: 0.5,
+            seed: 7,
+            ..Default::default()
+        })
+        .unwrap();
+        let uut = create_simulated_emitter(cloud_adapter.clone());
+
+        let descriptors = add_signals(&uut.signals, create_identified_signals(20));
+        let result = uut.emit_data(descriptors).await;
+
+        assert!(result.is_ok());
+        let stats = cloud_adapter.stats();
+        assert!(stats.failed > 0);
+        assert_eq!(stats.delivered.len() as u64 + stats.failed, 20);
+
+        let delivered_ids: HashSet<String> = stats
+            .delivered
+            .iter()
+            .map(|message| message.metadata["id"].clone())
+            .collect();
+        for signal in uut.signals.get_all() {
+            assert_eq!(
+                signal.emission.last_emitted_value.is_some(),
+                delivered_ids.contains(&signal.id)
+            );
+        }
+    }
+
+    /// Adds signals to a store with the values and last emitted values of the provided signals,
+    /// and gets emission descriptors with the signals' next emission times
+    fn add_signals(store: &SignalStore, signals: Vec<Signal>) -> Vec<EmissionDescriptor> {
+        signals
+            .into_iter()
+            .map(|signal| {
+                store.add([signal.clone()].into_iter());
+                if let Some(value) = signal.emission.last_emitted_value.clone() {
+                    store.set_last_emitted_value(signal.id.clone(), value);
+                }
+                if let Some(value) = signal.value.clone() {
+                    store.set_value(signal.id.clone(), value);
+                }
+
+                EmissionDescriptor::from(&signal)
+            })
+            .collect()
+    }
+
+    fn create_get_signal(id: &str, interval_ms: u64) -> Signal {
+        Signal {
+            id: id.to_string(),
+            source: Entity {
+                id: id.to_string(),
+                endpoints: vec![EntityEndpoint {
+                    protocol: "protocol".to_string(),
+                    operations: vec![Operation::Get.to_string()],
+                    uri: "uri".to_string(),
+                    context: "context".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            emission: Emission {
+                policy: EmissionPolicy {
+                    interval_ms,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn emit_data_awaits_fresh_values() {
+        const ID: &str = "testid";
+        const NEW_VALUE: &str = "new";
+
+        let clock = Arc::new(ManualClock::default());
+        let signals = Arc::new(SignalStore::with_clock(clock.clone()));
+        signals.sync([create_get_signal(ID, 42)].into_iter());
+        signals.set_value(ID.to_string(), "old".to_string());
+        clock.advance(Duration::from_millis(1));
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        let signals_clone = signals.clone();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .once()
+            .returning(move |id| {
+                signals_clone.set_value(id.to_string(), NEW_VALUE.to_string());
+                Ok(())
+            });
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| &*request.signal_value == NEW_VALUE)
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = Emitter {
+            signals: signals.clone(),
+            cloud_adapter: mock_cloud_adapter,
+            data_adapter_selector: Arc::new(Mutex::new(mock_data_adapter_selector)),
+            clock,
+            value_request_mode: ValueRequestMode::AwaitFresh { timeout_ms: 100 },
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        };
+
+        let result = uut.emit_data(signals.update_emission_times(0, 0)).await;
+
+        uut.cloud_adapter.checkpoint();
+        uut.data_adapter_selector.lock().await.checkpoint();
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn emit_data_uses_cached_value_when_awaiting_fresh_value_times_out() {
+        const ID: &str = "testid";
+        const OLD_VALUE: &str = "old";
+        const TIMEOUT_MS: u64 = 100;
+
+        let clock = Arc::new(ManualClock::default());
+        let signals = Arc::new(SignalStore::with_clock(clock.clone()));
+        signals.sync([create_get_signal(ID, 42)].into_iter());
+        signals.set_value(ID.to_string(), OLD_VALUE.to_string());
+        clock.advance(Duration::from_millis(1));
+        let start = clock.now();
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .once()
+            .returning(|_| Ok(()));
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| &*request.signal_value == OLD_VALUE)
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = Emitter {
+            signals: signals.clone(),
+            cloud_adapter: mock_cloud_adapter,
+            data_adapter_selector: Arc::new(Mutex::new(mock_data_adapter_selector)),
+            clock: clock.clone(),
+            value_request_mode: ValueRequestMode::AwaitFresh {
+                timeout_ms: TIMEOUT_MS,
+            },
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        };
+
+        let result = uut.emit_data(signals.update_emission_times(0, 0)).await;
+
+        uut.cloud_adapter.checkpoint();
+        uut.data_adapter_selector.lock().await.checkpoint();
+
+        assert!(result.is_ok());
+        assert_eq!(clock.now() - start, Duration::from_millis(TIMEOUT_MS));
+    }
+
+    #[tokio::test]
+    async fn emit_data_prefetches_values_before_emission() {
+        const ID: &str = "testid";
+        const INTERVAL: u64 = 100;
+        const LEAD_TIME: u64 = 20;
+        const NEXT_EMISSION_MS: u64 = 10;
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .once()
+            .returning(|_| Ok(()));
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = Emitter {
+            signals: Arc::new(SignalStore::new()),
+            cloud_adapter: mock_cloud_adapter,
+            data_adapter_selector: Arc::new(Mutex::new(mock_data_adapter_selector)),
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::Prefetch {
+                lead_time_ms: LEAD_TIME,
+            },
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        };
+
+        let mut test_signal = create_get_signal(ID, INTERVAL);
+        test_signal.value = Some("foo".into());
+        test_signal.emission.next_emission_ms = NEXT_EMISSION_MS;
+
+        let mut descriptors = add_signals(&uut.signals, vec![test_signal]);
+
+        // The value is requested ahead of the emission
+        let result = uut.emit_data(descriptors.clone()).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), NEXT_EMISSION_MS);
+
+        // The signal is emitted without requesting the value again,
+        // and the next iteration is scheduled early enough to prefetch the next value
+        descriptors[0].next_emission_ms = 0;
+        let result = uut.emit_data(descriptors).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), INTERVAL - LEAD_TIME);
+
+        uut.cloud_adapter.checkpoint();
+        uut.data_adapter_selector.lock().await.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_updates_signal_store() {
+        const ID: &str = "testid";
+        const INTERVAL: u64 = 42;
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let test_signal = Signal {
+            id: ID.to_string(),
+            value: Some("foo".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    interval_ms: INTERVAL,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let signals = SignalStore::new();
+        signals.sync([test_signal.clone()].into_iter());
 
         let uut = Emitter {
             signals: Arc::new(signals),
             cloud_adapter: mock_cloud_adapter,
             data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
         };
 
-        let result = uut.send_to_cloud(test_signal).await;
+        let result = uut.send_to_cloud(test_signal, None).await;
 
         assert!(result.is_ok());
 
@@ -558,4 +2496,961 @@ mod emitter_tests {
         assert!(signal.emission.last_emitted_value.is_some());
         assert_eq!(signal.emission.next_emission_ms, INTERVAL);
     }
+
+    #[tokio::test]
+    async fn send_to_cloud_adapts_interval_to_value_volatility() {
+        const ID: &str = "testid";
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let uut = create_emitter(mock_cloud_adapter);
+        uut.signals.sync(
+            [SignalPatch {
+                id: ID.to_string(),
+                emission_policy: EmissionPolicy {
+                    interval_ms: 400,
+                    adaptive_interval: Some(AdaptiveInterval {
+                        min_interval_ms: 100,
+                        max_interval_ms: 1000,
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        /// Emits a value and returns the signal's interval afterwards
+        async fn emit(
+            uut: &Emitter<MockCloudAdapter, MockDataAdapterSelector>,
+            value: &str,
+        ) -> u64 {
+            uut.signals.set_value(ID.to_string(), value.to_string());
+            let signal = uut.signals.get(&ID.to_string()).unwrap();
+            uut.send_to_cloud(signal, None).await.unwrap();
+
+            let emission = uut.signals.get(&ID.to_string()).unwrap().emission;
+            assert_eq!(emission.next_emission_ms, emission.interval_ms());
+            emission.interval_ms()
+        }
+
+        // Changing values shrink the interval down to the minimum
+        assert_eq!(emit(&uut, "1").await, 200);
+        assert_eq!(emit(&uut, "2").await, 100);
+        assert_eq!(emit(&uut, "3").await, 100);
+
+        // Stable values grow the interval up to the maximum
+        for expected in [200, 400, 800, 1000, 1000] {
+            assert_eq!(emit(&uut, "3").await, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn emit_data_adapts_interval_of_unchanged_skipped_signal() {
+        const ID: &str = "testid";
+
+        let mut mock_data_adapter_selector = MockDataAdapterSelector::new();
+        mock_data_adapter_selector
+            .expect_request_entity_value()
+            .returning(|_| Ok(()));
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter.expect_send_to_cloud().never();
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.data_adapter_selector = Arc::new(Mutex::new(mock_data_adapter_selector));
+        uut.signals.sync(
+            [SignalPatch {
+                id: ID.to_string(),
+                emission_policy: EmissionPolicy {
+                    interval_ms: 400,
+                    emit_only_if_changed: true,
+                    adaptive_interval: Some(AdaptiveInterval {
+                        min_interval_ms: 100,
+                        max_interval_ms: 1000,
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+        uut.signals.set_value(ID.to_string(), "foo".to_string());
+        uut.signals
+            .set_last_emitted_value(ID.to_string(), "foo".to_string());
+        let descriptors = uut.signals.update_emission_times(400, 0);
+
+        let result = uut.emit_data(descriptors).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        // The emitter wakes up early enough for the interval to have been halved instead
+        assert_eq!(result.unwrap(), 200);
+
+        let emission = uut.signals.get(&ID.to_string()).unwrap().emission;
+        assert_eq!(emission.interval_ms(), 800);
+        assert_eq!(emission.next_emission_ms, 800);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_uses_clock_for_timestamp() {
+        let clock = Arc::new(ManualClock::default());
+        clock.advance(Duration::from_secs(42));
+        let expected_timestamp = clock.now();
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(move |request| request.signal_timestamp == expected_timestamp)
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = Emitter {
+            signals: Arc::new(SignalStore::new()),
+            cloud_adapter: mock_cloud_adapter,
+            data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
+            clock,
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        };
+
+        let test_signal = Signal {
+            value: Some("foo".into()),
+            ..Default::default()
+        };
+
+        let result = uut.send_to_cloud(test_signal, None).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok());
+    }
+
+    fn create_unconvertible_signal(policy: ConversionFailurePolicy) -> Signal {
+        Signal {
+            id: "testid".to_string(),
+            value: Some("foo".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    conversion: Conversion::c_to_f(),
+                    conversion_failure_policy: policy,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn create_emitter(
+        mock_cloud_adapter: MockCloudAdapter,
+    ) -> Emitter<MockCloudAdapter, MockDataAdapterSelector> {
+        Emitter {
+            signals: Arc::new(SignalStore::new()),
+            cloud_adapter: mock_cloud_adapter,
+            data_adapter_selector: Arc::new(Mutex::new(MockDataAdapterSelector::new())),
+            clock: Arc::new(ManualClock::default()),
+            value_request_mode: ValueRequestMode::AfterEmission,
+            prefetched: StdMutex::new(HashSet::new()),
+            conversion_failures: StdMutex::new(HashMap::new()),
+            privacy_noise: StdMutex::new(HashMap::new()),
+            converted_values: StdMutex::new(HashMap::new()),
+            backfill_poll_interval_ms: None,
+            flight_recorder: None,
+            pacer: None,
+            status_reporter: None,
+            geofences: None,
+            emission_acknowledgement_mode: EmissionAcknowledgementMode::AtSend,
+            awaiting_acknowledgement: Arc::new(StdMutex::new(HashSet::new())),
+            heartbeat: Arc::new(Heartbeat::default()),
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_emits_raw_value_when_conversion_fails() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| {
+                &*request.signal_value == "foo"
+                    && !request.metadata.contains_key(QUALITY_METADATA_KEY)
+            })
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let result = uut
+            .send_to_cloud(
+                create_unconvertible_signal(ConversionFailurePolicy::EmitRaw),
+                None,
+            )
+            .await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+        assert_eq!(uut.conversion_failures.lock().unwrap()["testid"], 1);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_skips_value_when_conversion_fails() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter.expect_send_to_cloud().never();
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        for _ in 0..2 {
+            let result = uut
+                .send_to_cloud(
+                    create_unconvertible_signal(ConversionFailurePolicy::SkipAndWarn),
+                    None,
+                )
+                .await;
+            assert!(result.is_ok_and(|r| r.is_none()));
+        }
+
+        uut.cloud_adapter.checkpoint();
+
+        assert_eq!(uut.conversion_failures.lock().unwrap()["testid"], 2);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_flags_value_when_conversion_fails() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| {
+                &*request.signal_value == "foo"
+                    && request.metadata.get(QUALITY_METADATA_KEY)
+                        == Some(&CONVERSION_FAILED_QUALITY.to_string())
+            })
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let result = uut
+            .send_to_cloud(
+                create_unconvertible_signal(ConversionFailurePolicy::EmitWithQualityFlag),
+                None,
+            )
+            .await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_returns_err_when_conversion_fails() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter.expect_send_to_cloud().never();
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let result = uut
+            .send_to_cloud(
+                create_unconvertible_signal(ConversionFailurePolicy::Error),
+                None,
+            )
+            .await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().kind(),
+            EmitterErrorKind::ConversionFailed
+        );
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_skips_private_value_when_conversion_fails() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter.expect_send_to_cloud().never();
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        for policy in [
+            ConversionFailurePolicy::EmitRaw,
+            ConversionFailurePolicy::EmitWithQualityFlag,
+        ] {
+            let mut signal = create_unconvertible_signal(policy);
+            signal.emission.policy.privacy = Some(PrivacyPolicy {
+                noise: Some(Noise::Laplace { scale: 1.0 }),
+                quantization_step: None,
+            });
+
+            let result = uut.send_to_cloud(signal, None).await;
+            assert!(result.is_ok_and(|r| r.is_none()));
+        }
+
+        uut.cloud_adapter.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_does_not_count_values_without_conversion() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let mut signal = create_unconvertible_signal(ConversionFailurePolicy::Error);
+        signal.emission.policy.conversion = Conversion::None;
+        let result = uut.send_to_cloud(signal, None).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+        assert!(uut.conversion_failures.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn format_number_rounds_to_decimal_places() {
+        type TestEmitter = Emitter<MockCloudAdapter, MockDataAdapterSelector>;
+
+        assert_eq!(
+            TestEmitter::format_number(123456.789, None),
+            ("123456.789".to_string(), 123456.789)
+        );
+        assert_eq!(
+            TestEmitter::format_number(123456.789, Some(2)),
+            ("123456.79".to_string(), 123456.79)
+        );
+        assert_eq!(
+            TestEmitter::format_number(1.5, Some(0)),
+            ("2".to_string(), 2.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_emits_numeric_value() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| {
+                &*request.signal_value == "98765432.1" && request.numeric_value == Some(98765432.1)
+            })
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            value: Some("98765432.123".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    decimal_places: Some(1),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = uut.send_to_cloud(signal, None).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_reuses_converted_value_until_policy_changes() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        for expected in ["98765432.1", "cached", "98765432.12"] {
+            mock_cloud_adapter
+                .expect_send_to_cloud()
+                .withf(move |request| &*request.signal_value == expected)
+                .once()
+                .in_sequence(&mut sequence)
+                .returning(|_| Ok(CloudMessageResponse::default()));
+        }
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let mut signal = Signal {
+            id: String::from("signal"),
+            value: Some("98765432.123".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    decimal_places: Some(1),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(uut.send_to_cloud(signal.clone(), None).await.is_ok());
+
+        // The unchanged value isn't converted again, so the cached result is emitted
+        uut.converted_values
+            .lock()
+            .unwrap()
+            .get_mut("signal")
+            .unwrap()
+            .converted = "cached".into();
+        assert!(uut.send_to_cloud(signal.clone(), None).await.is_ok());
+
+        signal.emission.policy.decimal_places = Some(2);
+        assert!(uut.send_to_cloud(signal, None).await.is_ok());
+
+        uut.cloud_adapter.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_applies_privacy_policy_after_conversion() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| {
+                &*request.signal_value == "47.5" && request.numeric_value == Some(47.5)
+            })
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            value: Some("476.394".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    conversion: Conversion::Linear {
+                        mul: 0.1,
+                        offset: 0.0,
+                    },
+                    decimal_places: Some(1),
+                    privacy: Some(PrivacyPolicy {
+                        noise: None,
+                        quantization_step: Some(0.5),
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = uut.send_to_cloud(signal, None).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_shares_value_without_conversion() {
+        let value: Arc<str> = Arc::from("42.5");
+        let expected_value = value.clone();
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(move |request| {
+                Arc::ptr_eq(&request.signal_value, &expected_value)
+                    && request.numeric_value == Some(42.5)
+            })
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            value: Some(value),
+            numeric_value: Some(42.5),
+            ..Default::default()
+        };
+
+        let result = uut.send_to_cloud(signal, None).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_formats_numbers_without_conversion() {
+        let signal_values = Arc::new(StdMutex::new(Vec::new()));
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        let sent_values = signal_values.clone();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .times(2)
+            .returning(move |request| {
+                sent_values.lock().unwrap().push(request.signal_value);
+                Ok(CloudMessageResponse::default())
+            });
+
+        let uut = create_emitter(mock_cloud_adapter);
+
+        for value in ["42.50", "1e3"] {
+            let signal = Signal {
+                value: Some(value.into()),
+                ..Default::default()
+            };
+
+            let result = uut.send_to_cloud(signal, None).await;
+            assert!(result.is_ok_and(|r| r.is_some()));
+        }
+
+        let signal_values = signal_values.lock().unwrap();
+        assert_eq!(&*signal_values[0], "42.5");
+        assert_eq!(&*signal_values[1], "1000");
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_reuses_privacy_noise_for_repeated_values() {
+        let signal_values = Arc::new(StdMutex::new(Vec::new()));
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        let sent_values = signal_values.clone();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .times(4)
+            .returning(move |request| {
+                sent_values.lock().unwrap().push(request.signal_value);
+                Ok(CloudMessageResponse::default())
+            });
+
+        let uut = create_emitter(mock_cloud_adapter);
+
+        let create_signal = |value: &str| Signal {
+            id: String::from("location"),
+            value: Some(value.into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    privacy: Some(PrivacyPolicy {
+                        noise: Some(Noise::Laplace { scale: 1000.0 }),
+                        quantization_step: None,
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for value in ["42", "42", "42", "43"] {
+            let result = uut.send_to_cloud(create_signal(value), None).await;
+            assert!(result.is_ok_and(|r| r.is_some()));
+        }
+
+        let signal_values = signal_values.lock().unwrap();
+        assert_ne!(&*signal_values[0], "42");
+        assert_eq!(signal_values[0], signal_values[1]);
+        assert_eq!(signal_values[1], signal_values[2]);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_includes_tenant_in_metadata() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| {
+                request.metadata.get(TENANT_METADATA_KEY) == Some(&String::from("contoso"))
+                    && request.metadata.get("property") == Some(&String::from("speed"))
+            })
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            value: Some("42".into()),
+            target: Target {
+                metadata: HashMap::from([(String::from("property"), String::from("speed"))]),
+                tenant: Some(String::from("contoso")),
+            },
+            ..Default::default()
+        };
+
+        let result = uut.send_to_cloud(signal, None).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_sets_idempotency_key_from_sample() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| request.idempotency_key == Some(String::from("speed@1000000000")))
+            .times(2)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| request.idempotency_key.is_none())
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            id: String::from("speed"),
+            value: Some("42".into()),
+            value_timestamp: Some(time::OffsetDateTime::from_unix_timestamp(1).unwrap()),
+            ..Default::default()
+        };
+
+        // Sending the same sample again, such as after a failure, uses the same key
+        for _ in 0..2 {
+            assert!(uut.send_to_cloud(signal.clone(), None).await.is_ok());
+        }
+
+        let signal = Signal {
+            value_timestamp: None,
+            ..signal
+        };
+        assert!(uut.send_to_cloud(signal, None).await.is_ok());
+
+        uut.cloud_adapter.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_emits_templated_value() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| &*request.signal_value == "0x00FF" && request.numeric_value.is_none())
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            value: Some("255".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    conversion: Conversion::Template {
+                        template: "0x{value:04X}".to_string(),
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = uut.send_to_cloud(signal, None).await;
+
+        uut.cloud_adapter.checkpoint();
+
+        assert!(result.is_ok_and(|r| r.is_some()));
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_applies_failure_policy_when_template_fails() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter.expect_send_to_cloud().never();
+
+        let uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            id: "testid".to_string(),
+            value: Some("1.5".into()),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    conversion: Conversion::Template {
+                        template: "{value:x}".to_string(),
+                    },
+                    conversion_failure_policy: ConversionFailurePolicy::SkipAndWarn,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = uut.send_to_cloud(signal, None).await;
+
+        assert!(result.is_ok_and(|r| r.is_none()));
+        assert_eq!(uut.conversion_failures.lock().unwrap()["testid"], 1);
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_applies_threshold_with_hysteresis() {
+        const ID: &str = "testid";
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        let mut sequence = Sequence::new();
+        for expected in ["true", "true", "false"] {
+            mock_cloud_adapter
+                .expect_send_to_cloud()
+                .withf(move |request| {
+                    &*request.signal_value == expected && request.numeric_value.is_none()
+                })
+                .once()
+                .in_sequence(&mut sequence)
+                .returning(|_| Ok(CloudMessageResponse::default()));
+        }
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+
+        let signal = Signal {
+            id: ID.to_string(),
+            emission: Emission {
+                policy: EmissionPolicy {
+                    conversion: Conversion::Threshold {
+                        on: 10.0,
+                        off: 12.0,
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        uut.signals.add([signal].into_iter());
+
+        for value in ["9", "11", "13"] {
+            uut.signals.set_value(ID.to_string(), value.to_string());
+            let signal = uut.signals.get(&ID.to_string()).unwrap();
+            let result = uut.send_to_cloud(signal, None).await;
+            assert!(result.is_ok_and(|r| r.is_some()));
+        }
+
+        uut.cloud_adapter.checkpoint();
+
+        assert_eq!(
+            uut.signals
+                .get(&ID.to_string())
+                .unwrap()
+                .emission
+                .threshold_state,
+            Some(false)
+        );
+    }
+
+    #[tokio::test]
+    async fn run_stops_when_cancelled() {
+        let uut = Arc::new(create_emitter(MockCloudAdapter::new()));
+
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), uut.run(cancellation_token)).await;
+        assert!(result.is_ok_and(|r| r.is_ok_and(|r| r.is_ok())));
+    }
+
+    #[tokio::test]
+    async fn backfill_emits_requested_signals_even_if_unchanged() {
+        const REQUESTED_ID: &str = "requested";
+        const VALUE: &str = "42";
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_check_for_backfill()
+            .once()
+            .returning(|| {
+                Ok(BackfillRequest {
+                    signal_ids: [REQUESTED_ID.to_string()].into_iter().collect(),
+                    all_signals: false,
+                    ..Default::default()
+                })
+            });
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| {
+                // The key differs from the one that the value was first emitted with
+                &*request.signal_value == VALUE
+                    && request
+                        .idempotency_key
+                        .as_ref()
+                        .is_some_and(|key| key.starts_with(&format!("backfill-0/{REQUESTED_ID}@")))
+            })
+            .once()
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        for id in [REQUESTED_ID, "other"] {
+            uut.signals.add(
+                [SignalPatch {
+                    id: id.to_string(),
+                    emission_policy: EmissionPolicy {
+                        emit_only_if_changed: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }]
+                .into_iter(),
+            );
+            uut.signals.set_value(id.to_string(), VALUE.to_string());
+            uut.signals
+                .set_last_emitted_value(id.to_string(), VALUE.to_string());
+        }
+
+        uut.backfill().await;
+
+        uut.cloud_adapter.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn emit_status_sends_each_status_signal() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_send_to_cloud()
+            .withf(|request| {
+                match request
+                    .metadata
+                    .get(STATUS_SIGNAL_METADATA_KEY)
+                    .map(String::as_str)
+                {
+                    Some(SIGNALS_ACTIVE_STATUS_SIGNAL) => &*request.signal_value == "1",
+                    Some(_) => true,
+                    None => false,
+                }
+            })
+            .times(5)
+            .returning(|_| Ok(CloudMessageResponse::default()));
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.status_reporter = Some(StatusReporter::new(
+            StatusSignalsConfig {
+                interval_ms: NonZeroU64::new(1000).unwrap(),
+                metadata: HashMap::new(),
+            },
+            uut.clock.clone(),
+        ));
+        uut.signals.add(
+            [SignalPatch {
+                id: String::from("testid"),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        uut.emit_status().await;
+
+        uut.cloud_adapter.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn backfill_does_not_emit_when_check_fails() {
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_check_for_backfill()
+            .once()
+            .returning(|| Err(CloudAdapterErrorKind::Communication.into()));
+        mock_cloud_adapter.expect_send_to_cloud().never();
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.signals.add(
+            [SignalPatch {
+                id: String::from("testid"),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+        uut.signals
+            .set_value(String::from("testid"), String::from("42"));
+
+        uut.backfill().await;
+
+        uut.cloud_adapter.checkpoint();
+    }
+
+    #[tokio::test]
+    async fn flight_recorder_capture_is_emitted_once_ready() {
+        const ID: &str = "suspension";
+
+        let mut mock_cloud_adapter = MockCloudAdapter::new();
+        mock_cloud_adapter
+            .expect_check_for_backfill()
+            .once()
+            .returning(|| {
+                Ok(BackfillRequest {
+                    flight_recorder: true,
+                    ..Default::default()
+                })
+            });
+
+        let clock = Arc::new(ManualClock::default());
+        let signals = Arc::new(SignalStore::with_clock(clock.clone()));
+        let recorder = Arc::new(FlightRecorder::new(
+            FlightRecorderConfig {
+                signal_ids: [ID.to_string()].into_iter().collect(),
+                capacity: 10,
+                pre_trigger_ms: 1000,
+                post_trigger_ms: 1000,
+            },
+            clock.clone(),
+        ));
+        signals.add_value_observer(recorder.clone());
+        signals.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                target: Target {
+                    metadata: [("property".to_string(), ID.to_string())]
+                        .into_iter()
+                        .collect(),
+                    tenant: None,
+                },
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        let mut uut = create_emitter(mock_cloud_adapter);
+        uut.signals = signals.clone();
+        uut.clock = clock.clone();
+        uut.flight_recorder = Some(recorder);
+
+        signals.set_value(ID.to_string(), "1".to_string());
+        clock.advance(Duration::from_millis(500));
+        uut.backfill().await;
+        let triggered_at = clock.now();
+
+        clock.advance(Duration::from_millis(500));
+        signals.set_value(ID.to_string(), "2".to_string());
+
+        // The capture isn't emitted until the post-trigger window has elapsed
+        uut.flush_flight_recorder().await;
+        uut.cloud_adapter.checkpoint();
+
+        let capture_id = (triggered_at.unix_timestamp_nanos() / 1_000_000).to_string();
+        let mut sequence = Sequence::new();
+        for (value, offset_ms) in [("1", -500), ("2", 500)] {
+            let capture_id = capture_id.clone();
+            let expected_timestamp = triggered_at + time::Duration::milliseconds(offset_ms);
+            uut.cloud_adapter
+                .expect_send_to_cloud()
+                .withf(move |request| {
+                    &*request.signal_value == value
+                        && request.signal_timestamp == expected_timestamp
+                        && request.metadata.get("property").is_some_and(|p| p == ID)
+                        && request
+                            .metadata
+                            .get(FLIGHT_RECORDER_CAPTURE_METADATA_KEY)
+                            .is_some_and(|id| *id == capture_id)
+                })
+                .once()
+                .in_sequence(&mut sequence)
+                .returning(|_| Ok(CloudMessageResponse::default()));
+        }
+
+        clock.advance(Duration::from_millis(500));
+        uut.flush_flight_recorder().await;
+        uut.cloud_adapter.checkpoint();
+    }
 }
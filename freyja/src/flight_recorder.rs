@@ -0,0 +1,217 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use time::OffsetDateTime;
+
+use freyja_common::{clock::Clock, signal::EmissionRecord, signal_store::SignalValueObserver};
+
+use crate::config::FlightRecorderConfig;
+
+/// The values captured by the flight recorder around a trigger
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Capture {
+    /// The time at which the capture was triggered
+    pub triggered_at: OffsetDateTime,
+
+    /// The captured values and the ids of their signals, oldest first
+    pub records: Vec<(String, EmissionRecord)>,
+}
+
+/// Keeps a circular buffer of the recent values of designated signals so that the values around an incident
+/// can be emitted when a capture is triggered, even if they would otherwise not be emitted.
+/// A capture includes the values received during the pre-trigger window before the trigger
+/// and the post-trigger window after it, and is ready once the post-trigger window has elapsed.
+pub(crate) struct FlightRecorder {
+    /// The flight recorder config
+    config: FlightRecorderConfig,
+
+    /// The clock used to time captures
+    clock: Arc<dyn Clock>,
+
+    /// The recent values of each designated signal, oldest first
+    buffers: Mutex<HashMap<String, VecDeque<EmissionRecord>>>,
+
+    /// The time at which the capture in progress was triggered, if any
+    triggered_at: Mutex<Option<OffsetDateTime>>,
+}
+
+impl FlightRecorder {
+    /// Creates a new FlightRecorder
+    ///
+    /// # Arguments
+    /// - `config`: the flight recorder config
+    /// - `clock`: the clock used to time captures, which should be the signal store's clock
+    pub fn new(config: FlightRecorderConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            buffers: Mutex::new(HashMap::new()),
+            triggered_at: Mutex::new(None),
+        }
+    }
+
+    /// Starts a capture at the current time.
+    /// Returns false if a capture is already in progress, in which case that capture is kept.
+    pub fn trigger(&self) -> bool {
+        let mut triggered_at = self.triggered_at.lock().unwrap();
+        if triggered_at.is_some() {
+            return false;
+        }
+
+        *triggered_at = Some(self.clock.now());
+        true
+    }
+
+    /// Gets the time until the capture in progress is ready, or `None` if there is no capture in progress
+    pub fn time_until_capture_ready(&self) -> Option<Duration> {
+        let triggered_at = (*self.triggered_at.lock().unwrap())?;
+        let ready_at = triggered_at + Duration::from_millis(self.config.post_trigger_ms);
+
+        Some(
+            (ready_at - self.clock.now())
+                .try_into()
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+
+    /// Takes the capture in progress if its post-trigger window has elapsed.
+    /// Returns `None` if there is no capture in progress or it isn't ready yet.
+    pub fn take_capture(&self) -> Option<Capture> {
+        let mut triggered_at = self.triggered_at.lock().unwrap();
+        let start = (*triggered_at)? - Duration::from_millis(self.config.pre_trigger_ms);
+        let end = (*triggered_at)? + Duration::from_millis(self.config.post_trigger_ms);
+        if self.clock.now() < end {
+            return None;
+        }
+
+        let mut records: Vec<(String, EmissionRecord)> = self
+            .buffers
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|(id, buffer)| {
+                buffer
+                    .iter()
+                    .filter(|record| start <= record.timestamp && record.timestamp <= end)
+                    .map(|record| (id.clone(), record.clone()))
+            })
+            .collect();
+        records.sort_by_key(|(_, record)| record.timestamp);
+
+        Some(Capture {
+            triggered_at: triggered_at.take()?,
+            records,
+        })
+    }
+}
+
+impl SignalValueObserver for FlightRecorder {
+    /// Buffers a value if it belongs to one of the designated signals
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `value`: the new value of the signal
+    /// - `timestamp`: the time at which the value was set
+    fn on_value_set(&self, id: &str, value: &Arc<str>, timestamp: OffsetDateTime) {
+        if !self.config.signal_ids.contains(id) {
+            return;
+        }
+
+        let mut buffers = self.buffers.lock().unwrap();
+        let buffer = buffers.entry(id.to_string()).or_default();
+        buffer.push_back(EmissionRecord {
+            timestamp,
+            value: value.clone(),
+        });
+
+        while buffer.len() > self.config.capacity {
+            buffer.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod flight_recorder_tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    use freyja_common::clock::ManualClock;
+
+    const ID: &str = "suspension";
+
+    fn create_flight_recorder(clock: Arc<ManualClock>, capacity: usize) -> FlightRecorder {
+        FlightRecorder::new(
+            FlightRecorderConfig {
+                signal_ids: HashSet::from([ID.to_string()]),
+                capacity,
+                pre_trigger_ms: 1000,
+                post_trigger_ms: 500,
+            },
+            clock,
+        )
+    }
+
+    /// Sets a value on the recorder at the current time of the clock, then advances the clock
+    fn record(uut: &FlightRecorder, clock: &ManualClock, id: &str, value: &str, advance_ms: u64) {
+        uut.on_value_set(id, &Arc::from(value), clock.now());
+        clock.advance(Duration::from_millis(advance_ms));
+    }
+
+    #[test]
+    fn buffer_keeps_most_recent_values_of_designated_signals() {
+        let clock = Arc::new(ManualClock::default());
+        let uut = create_flight_recorder(clock.clone(), 2);
+
+        for value in ["1", "2", "3"] {
+            record(&uut, &clock, ID, value, 10);
+        }
+        record(&uut, &clock, "other", "4", 10);
+
+        let buffers = uut.buffers.lock().unwrap();
+        let values: Vec<&str> = buffers[ID].iter().map(|r| &*r.value).collect();
+        assert_eq!(values, vec!["2", "3"]);
+        assert!(!buffers.contains_key("other"));
+    }
+
+    #[test]
+    fn take_capture_returns_pre_and_post_trigger_windows_once_ready() {
+        let clock = Arc::new(ManualClock::default());
+        let uut = create_flight_recorder(clock.clone(), 100);
+
+        // Too old for the pre-trigger window
+        record(&uut, &clock, ID, "old", 600);
+        record(&uut, &clock, ID, "pre", 900);
+
+        assert!(uut.trigger());
+        assert!(!uut.trigger());
+        let triggered_at = clock.now();
+
+        record(&uut, &clock, ID, "post", 300);
+        assert_eq!(uut.take_capture(), None);
+        assert_eq!(
+            uut.time_until_capture_ready(),
+            Some(Duration::from_millis(200))
+        );
+
+        clock.advance(Duration::from_millis(200));
+        record(&uut, &clock, ID, "late", 0);
+
+        let capture = uut.take_capture().unwrap();
+        assert_eq!(capture.triggered_at, triggered_at);
+        let values: Vec<&str> = capture.records.iter().map(|(_, r)| &*r.value).collect();
+        assert_eq!(values, vec!["pre", "post", "late"]);
+
+        // The capture has been taken, so a new one can be triggered
+        assert_eq!(uut.take_capture(), None);
+        assert_eq!(uut.time_until_capture_ready(), None);
+        assert!(uut.trigger());
+    }
+}
@@ -0,0 +1,641 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::error::Error;
+
+use freyja_common::shutdown_signal;
+use log::{info, warn};
+use tokio::{
+    task::{JoinError, JoinHandle},
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    config::StallAction,
+    crash_report::{self, LastGasp},
+    data_adapter_registry::DataAdapterRegistry,
+    restart_policy::RestartPolicy,
+    watchdog::{Watchdog, STALLED_EXIT_CODE},
+};
+
+/// The result of running a Freyja component
+pub type ComponentResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// A function which starts a new run of a component with the provided cancellation token
+pub(crate) type ComponentStarter =
+    Box<dyn Fn(CancellationToken) -> JoinHandle<ComponentResult> + Send + Sync>;
+
+/// The components of Freyja which run continuously and can be restarted individually
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FreyjaComponent {
+    /// The cartographer, which manages mappings and data adapters
+    Cartographer,
+
+    /// The emitter, which emits signals to the cloud
+    Emitter,
+}
+
+/// The state of a component's current run
+enum Run {
+    /// The component is running
+    Running(JoinHandle<ComponentResult>),
+
+    /// The component failed and is waiting until the provided time to start again
+    Restarting(Instant),
+}
+
+/// Something that happened to a component while waiting on it
+enum ComponentEvent {
+    /// The run of the component finished with the provided result
+    Finished(ComponentResult),
+
+    /// The component is due to start again after failing
+    RestartDue,
+}
+
+/// A running Freyja component
+struct ComponentTask {
+    /// The component
+    component: FreyjaComponent,
+
+    /// Starts a new run of the component
+    start: ComponentStarter,
+
+    /// The token for cancelling the current run
+    cancellation_token: CancellationToken,
+
+    /// The state of the current run
+    run: Run,
+
+    /// The time at which the current run started
+    started_at: Instant,
+
+    /// The number of times in a row that the component has been restarted after failing
+    restarts: u32,
+}
+
+impl ComponentTask {
+    /// Starts a component
+    ///
+    /// # Arguments
+    /// - `component`: the component
+    /// - `parent_token`: the token for cancelling every component
+    /// - `start`: starts a new run of the component
+    fn new(
+        component: FreyjaComponent,
+        parent_token: &CancellationToken,
+        start: ComponentStarter,
+    ) -> Self {
+        // A panic from before this run must not be reported as the reason that this run stopped
+        crash_report::take_panic_report(component);
+        let cancellation_token = parent_token.child_token();
+        let join_handle = start(cancellation_token.clone());
+
+        Self {
+            component,
+            start,
+            cancellation_token,
+            run: Run::Running(join_handle),
+            started_at: Instant::now(),
+            restarts: 0,
+        }
+    }
+
+    /// Starts a new run of the component
+    ///
+    /// # Arguments
+    /// - `parent_token`: the token for cancelling every component
+    fn start_run(&mut self, parent_token: &CancellationToken) {
+        crash_report::take_panic_report(self.component);
+        self.cancellation_token = parent_token.child_token();
+        self.run = Run::Running((self.start)(self.cancellation_token.clone()));
+        self.started_at = Instant::now();
+    }
+
+    /// Stops the current run of the component and waits for it to finish.
+    /// Returns the result of the run.
+    async fn stop(&mut self) -> ComponentResult {
+        self.cancellation_token.cancel();
+        match &mut self.run {
+            Run::Running(join_handle) => Self::join(join_handle).await,
+            Run::Restarting(_) => Ok(()),
+        }
+    }
+
+    /// Stops the current run of the component and starts a new one.
+    /// Returns the result of the stopped run.
+    ///
+    /// # Arguments
+    /// - `parent_token`: the token for cancelling every component
+    async fn restart(&mut self, parent_token: &CancellationToken) -> ComponentResult {
+        let result = self.stop().await;
+        self.start_run(parent_token);
+
+        result
+    }
+
+    /// Aborts the current run of the component without waiting for it to observe cancellation, then starts a new one.
+    /// This is used for runs which have stalled and might never check their cancellation token.
+    /// Returns the result of the aborted run.
+    ///
+    /// # Arguments
+    /// - `parent_token`: the token for cancelling every component
+    async fn abort_and_restart(&mut self, parent_token: &CancellationToken) -> ComponentResult {
+        if let Run::Running(join_handle) = &self.run {
+            join_handle.abort();
+        }
+
+        self.restart(parent_token).await
+    }
+
+    /// Schedules a new run of the component after it failed.
+    /// Returns false if the restart policy escalates the failure instead.
+    ///
+    /// # Arguments
+    /// - `restart_policy`: the restart policy
+    fn schedule_restart(&mut self, restart_policy: &RestartPolicy) -> bool {
+        if restart_policy.is_stable(self.started_at.elapsed()) {
+            self.restarts = 0;
+        }
+
+        match restart_policy.backoff(self.restarts) {
+            Some(backoff) => {
+                self.restarts += 1;
+                self.run = Run::Restarting(Instant::now() + backoff);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Waits until the current run finishes or a scheduled restart is due.
+    /// If the component is stopped while waiting to restart, this finishes successfully.
+    async fn next_event(&mut self) -> ComponentEvent {
+        match &mut self.run {
+            Run::Running(join_handle) => ComponentEvent::Finished(Self::join(join_handle).await),
+            Run::Restarting(restart_at) => tokio::select! {
+                _ = self.cancellation_token.cancelled() => ComponentEvent::Finished(Ok(())),
+                _ = tokio::time::sleep_until(*restart_at) => ComponentEvent::RestartDue,
+            },
+        }
+    }
+
+    /// Waits for a run of a component to finish, converting panics into errors
+    ///
+    /// # Arguments
+    /// - `join_handle`: the handle of the run
+    async fn join(join_handle: &mut JoinHandle<ComponentResult>) -> ComponentResult {
+        join_handle.await.unwrap_or_else(|e| Err(e.into()))
+    }
+}
+
+/// A handle for managing the lifecycle of a running Freyja instance.
+/// This allows host applications to stop Freyja or restart its components programmatically.
+pub struct FreyjaHandle {
+    /// The token for cancelling every component
+    cancellation_token: CancellationToken,
+
+    /// The registry of data adapter factories
+    data_adapter_registry: DataAdapterRegistry,
+
+    /// The running cartographer
+    cartographer: ComponentTask,
+
+    /// The running emitter
+    emitter: ComponentTask,
+
+    /// The watchdog which detects stalled components, if enabled
+    watchdog: Option<Watchdog>,
+
+    /// The policy for restarting components which fail. If this is `None`, Freyja stops when a component fails
+    restart_policy: Option<RestartPolicy>,
+
+    /// Notifies the cloud when Freyja stops because of a panic, if enabled
+    last_gasp: Option<LastGasp>,
+}
+
+impl FreyjaHandle {
+    /// Creates a new handle and starts the components
+    ///
+    /// # Arguments
+    /// - `cancellation_token`: the token for cancelling every component
+    /// - `data_adapter_registry`: the registry of data adapter factories
+    /// - `start_cartographer`: starts a new run of the cartographer
+    /// - `start_emitter`: starts a new run of the emitter
+    /// - `watchdog`: the watchdog which detects stalled components, if enabled
+    /// - `restart_policy`: the policy for restarting components which fail, if any
+    /// - `last_gasp`: notifies the cloud when Freyja stops because of a panic, if enabled
+    pub(crate) fn new(
+        cancellation_token: CancellationToken,
+        data_adapter_registry: DataAdapterRegistry,
+        start_cartographer: ComponentStarter,
+        start_emitter: ComponentStarter,
+        watchdog: Option<Watchdog>,
+        restart_policy: Option<RestartPolicy>,
+        last_gasp: Option<LastGasp>,
+    ) -> Self {
+        Self {
+            cartographer: ComponentTask::new(
+                FreyjaComponent::Cartographer,
+                &cancellation_token,
+                start_cartographer,
+            ),
+            emitter: ComponentTask::new(
+                FreyjaComponent::Emitter,
+                &cancellation_token,
+                start_emitter,
+            ),
+            cancellation_token,
+            data_adapter_registry,
+            watchdog,
+            restart_policy,
+            last_gasp,
+        }
+    }
+
+    /// Gets a token which stops every component when cancelled.
+    /// This can be used to stop Freyja from another task while waiting on this handle.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation_token.clone()
+    }
+
+    /// Gets a handle for registering and deregistering data adapter factories.
+    /// This can be used to change which protocols Freyja supports from another task while waiting on this handle.
+    pub fn data_adapter_registry(&self) -> DataAdapterRegistry {
+        self.data_adapter_registry.clone()
+    }
+
+    /// Stops every component and waits for them to finish.
+    /// Returns the first error reported by a component, if any.
+    pub async fn stop(mut self) -> ComponentResult {
+        self.cancellation_token.cancel();
+
+        let cartographer_result = self.cartographer.stop().await;
+        let emitter_result = self.emitter.stop().await;
+
+        cartographer_result.and(emitter_result)
+    }
+
+    /// Stops a component and starts it again.
+    /// Returns the result of the run that was stopped.
+    ///
+    /// # Arguments
+    /// - `component`: the component to restart
+    pub async fn restart_component(&mut self, component: FreyjaComponent) -> ComponentResult {
+        info!("Restarting the {component:?}");
+
+        match component {
+            FreyjaComponent::Cartographer => {
+                self.cartographer.restart(&self.cancellation_token).await
+            }
+            FreyjaComponent::Emitter => self.emitter.restart(&self.cancellation_token).await,
+        }
+    }
+
+    /// Handles a component which the watchdog detected as stalled by restarting it or exiting the process
+    ///
+    /// # Arguments
+    /// - `component`: the stalled component
+    async fn handle_stall(&mut self, component: FreyjaComponent) {
+        let watchdog = match self.watchdog.as_ref() {
+            Some(watchdog) => watchdog,
+            None => return,
+        };
+
+        match watchdog.action() {
+            StallAction::Restart => {
+                warn!("The {component:?} has stalled, restarting it");
+
+                // The heartbeat is reset first so that the new run isn't considered stalled before it starts
+                watchdog.heartbeat(component).idle();
+                let result = match component {
+                    FreyjaComponent::Cartographer => {
+                        self.cartographer
+                            .abort_and_restart(&self.cancellation_token)
+                            .await
+                    }
+                    FreyjaComponent::Emitter => {
+                        self.emitter
+                            .abort_and_restart(&self.cancellation_token)
+                            .await
+                    }
+                };
+
+                if let Err(e) = result {
+                    warn!("The stalled {component:?} was stopped with error {e:?}");
+                }
+            }
+            StallAction::Exit => {
+                log::error!("The {component:?} has stalled, exiting with code {STALLED_EXIT_CODE}");
+                self.cancellation_token.cancel();
+                std::process::exit(STALLED_EXIT_CODE);
+            }
+        }
+    }
+
+    /// Handles a component which failed by scheduling a restart according to the restart policy.
+    /// Returns false if the failure should stop Freyja instead.
+    ///
+    /// # Arguments
+    /// - `component`: the failed component
+    /// - `error`: the error that the component failed with
+    fn handle_failure(
+        &mut self,
+        component: FreyjaComponent,
+        error: &(dyn Error + Send + Sync),
+    ) -> bool {
+        let restart_policy = match self.restart_policy.as_ref() {
+            Some(restart_policy) if !self.cancellation_token.is_cancelled() => restart_policy,
+            _ => return false,
+        };
+
+        let task = match component {
+            FreyjaComponent::Cartographer => &mut self.cartographer,
+            FreyjaComponent::Emitter => &mut self.emitter,
+        };
+
+        if task.schedule_restart(restart_policy) {
+            // A failed run may not have finished its iteration, so the watchdog shouldn't consider it stalled
+            if let Some(watchdog) = self.watchdog.as_ref() {
+                watchdog.heartbeat(component).idle();
+            }
+
+            // A panic which the component recovers from by restarting isn't reported to the cloud
+            crash_report::take_panic_report(component);
+
+            warn!(
+                "The {component:?} failed with error {error:?}, restarting it (restart {})",
+                task.restarts
+            );
+            true
+        } else {
+            log::error!(
+                "The {component:?} failed {} times in a row, giving up",
+                task.restarts + 1
+            );
+            false
+        }
+    }
+
+    /// Waits until a component terminates or the cancellation token is cancelled, then stops every component.
+    /// If the watchdog is enabled, components which stall are handled while waiting,
+    /// and if a restart policy is set, components which fail are restarted until the policy gives up.
+    /// Returns the first error reported by a component, if any.
+    pub async fn wait(mut self) -> ComponentResult {
+        let (result, finished_component) = loop {
+            let (component, event) = tokio::select! {
+                event = self.cartographer.next_event() => (FreyjaComponent::Cartographer, Some(event)),
+                event = self.emitter.next_event() => (FreyjaComponent::Emitter, Some(event)),
+                component = Watchdog::wait_for_stall(self.watchdog.as_ref()) => (component, None),
+            };
+
+            match event {
+                None => self.handle_stall(component).await,
+                Some(ComponentEvent::RestartDue) => {
+                    info!("Restarting the {component:?}");
+                    match component {
+                        FreyjaComponent::Cartographer => {
+                            self.cartographer.start_run(&self.cancellation_token)
+                        }
+                        FreyjaComponent::Emitter => {
+                            self.emitter.start_run(&self.cancellation_token)
+                        }
+                    }
+                }
+                Some(ComponentEvent::Finished(result)) => match result {
+                    Err(e) if self.handle_failure(component, e.as_ref()) => {}
+                    result => break (result, component),
+                },
+            }
+        };
+
+        if let Err(e) = &result {
+            log::error!("The {finished_component:?} terminated with error {e:?}");
+
+            // If the component panicked, the cloud is notified before the other component is stopped
+            let report = e
+                .downcast_ref::<JoinError>()
+                .is_some_and(|e| e.is_panic())
+                .then(|| crash_report::take_panic_report(finished_component))
+                .flatten();
+            if let (Some(last_gasp), Some(report)) = (self.last_gasp.as_ref(), report) {
+                last_gasp.send(&report).await;
+            }
+        }
+
+        // The finished component's handle can't be joined again, so only the other component is stopped
+        self.cancellation_token.cancel();
+        let other_result = match finished_component {
+            FreyjaComponent::Cartographer => self.emitter.stop().await,
+            FreyjaComponent::Emitter => self.cartographer.stop().await,
+        };
+
+        result.and(other_result)
+    }
+
+    /// Waits until a component terminates or the operating system asks the process to shut down,
+    /// then stops every component.
+    /// Returns the first error reported by a component, if any.
+    pub async fn wait_for_shutdown(self) -> ComponentResult {
+        let cancellation_token = self.cancellation_token();
+        let shutdown_listener = tokio::spawn(async move {
+            match shutdown_signal::wait_for_shutdown_signal().await {
+                Ok(()) => {
+                    info!("Shutdown requested, stopping Freyja");
+                    cancellation_token.cancel();
+                }
+                Err(e) => warn!("Could not listen for shutdown signals: {e}"),
+            }
+        });
+
+        let result = self.wait().await;
+        shutdown_listener.abort();
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod freyja_handle_tests {
+    use super::*;
+
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use freyja_common::signal_store::SignalStore;
+    use tokio::sync::Mutex;
+
+    use crate::{
+        cli::FREYJA_COMMAND,
+        config::{ProviderRequestBudgets, WatchdogConfig},
+        data_adapter_selector_impl::DataAdapterSelectorImpl,
+        watchdog::Heartbeat,
+    };
+
+    fn create_data_adapter_registry() -> DataAdapterRegistry {
+        DataAdapterRegistry::new(Arc::new(Mutex::new(DataAdapterSelectorImpl::new(
+            Arc::new(SignalStore::new()),
+            ProviderRequestBudgets::default(),
+            None,
+        ))))
+    }
+
+    async fn run_until_cancelled(cancellation_token: CancellationToken) -> ComponentResult {
+        cancellation_token.cancelled().await;
+        Ok(())
+    }
+
+    async fn fail() -> ComponentResult {
+        Err("component failed".into())
+    }
+
+    /// Creates a starter for a component which runs until it's cancelled and counts how many times it was started
+    fn create_starter(start_count: Arc<AtomicUsize>) -> ComponentStarter {
+        Box::new(move |cancellation_token: CancellationToken| {
+            start_count.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(run_until_cancelled(cancellation_token))
+        })
+    }
+
+    /// Creates a starter for a component which starts an iteration and then never makes progress or checks for cancellation
+    fn create_stalling_starter(
+        start_count: Arc<AtomicUsize>,
+        heartbeat: Arc<Heartbeat>,
+    ) -> ComponentStarter {
+        Box::new(move |_: CancellationToken| {
+            start_count.fetch_add(1, Ordering::SeqCst);
+            heartbeat.beat();
+            tokio::spawn(std::future::pending())
+        })
+    }
+
+    /// Creates a starter for a component which fails immediately and counts how many times it was started
+    fn create_failing_starter(start_count: Arc<AtomicUsize>) -> ComponentStarter {
+        Box::new(move |_: CancellationToken| {
+            start_count.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(fail())
+        })
+    }
+
+    #[tokio::test]
+    async fn stop_stops_every_component() {
+        let uut = FreyjaHandle::new(
+            CancellationToken::new(),
+            create_data_adapter_registry(),
+            create_starter(Arc::new(AtomicUsize::new(0))),
+            create_starter(Arc::new(AtomicUsize::new(0))),
+            None,
+            None,
+            None,
+        );
+
+        let result = tokio::time::timeout(Duration::from_secs(5), uut.stop()).await;
+        assert!(result.is_ok_and(|r| r.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn restart_component_restarts_only_that_component() {
+        let cartographer_starts = Arc::new(AtomicUsize::new(0));
+        let emitter_starts = Arc::new(AtomicUsize::new(0));
+        let mut uut = FreyjaHandle::new(
+            CancellationToken::new(),
+            create_data_adapter_registry(),
+            create_starter(cartographer_starts.clone()),
+            create_starter(emitter_starts.clone()),
+            None,
+            None,
+            None,
+        );
+
+        assert!(uut
+            .restart_component(FreyjaComponent::Emitter)
+            .await
+            .is_ok());
+        assert_eq!(cartographer_starts.load(Ordering::SeqCst), 1);
+        assert_eq!(emitter_starts.load(Ordering::SeqCst), 2);
+
+        // The restarted component can still be stopped with the handle's token
+        uut.cancellation_token().cancel();
+        let result = tokio::time::timeout(Duration::from_secs(5), uut.wait()).await;
+        assert!(result.is_ok_and(|r| r.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn wait_returns_err_and_stops_other_components_when_component_fails() {
+        let uut = FreyjaHandle::new(
+            CancellationToken::new(),
+            create_data_adapter_registry(),
+            create_starter(Arc::new(AtomicUsize::new(0))),
+            create_failing_starter(Arc::new(AtomicUsize::new(0))),
+            None,
+            None,
+            None,
+        );
+
+        let result = tokio::time::timeout(Duration::from_secs(5), uut.wait()).await;
+        assert!(result.is_ok_and(|r| r.is_err()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_restarts_stalled_components() {
+        let cartographer_heartbeat = Arc::new(Heartbeat::default());
+        let emitter_heartbeat = Arc::new(Heartbeat::default());
+        let emitter_starts = Arc::new(AtomicUsize::new(0));
+        let watchdog = Watchdog::new(
+            WatchdogConfig {
+                stall_timeout_ms: 1000,
+                check_interval_ms: 100,
+                action: StallAction::Restart,
+            },
+            cartographer_heartbeat,
+            emitter_heartbeat.clone(),
+        );
+        let uut = FreyjaHandle::new(
+            CancellationToken::new(),
+            create_data_adapter_registry(),
+            create_starter(Arc::new(AtomicUsize::new(0))),
+            create_stalling_starter(emitter_starts.clone(), emitter_heartbeat),
+            Some(watchdog),
+            None,
+            None,
+        );
+        let wait = tokio::spawn(uut.wait());
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(emitter_starts.load(Ordering::SeqCst), 2);
+
+        wait.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_restarts_failed_components_until_policy_gives_up() {
+        let emitter_starts = Arc::new(AtomicUsize::new(0));
+        let args = FREYJA_COMMAND
+            .parse(
+                ["freyja", "--restart-max=2", "--restart-backoff-ms=100"]
+                    .into_iter()
+                    .map(String::from),
+            )
+            .unwrap();
+        let uut = FreyjaHandle::new(
+            CancellationToken::new(),
+            create_data_adapter_registry(),
+            create_starter(Arc::new(AtomicUsize::new(0))),
+            create_failing_starter(emitter_starts.clone()),
+            None,
+            RestartPolicy::from_args(&args).unwrap(),
+            None,
+        );
+
+        let result = tokio::time::timeout(Duration::from_secs(5), uut.wait()).await;
+        assert!(result.is_ok_and(|r| r.is_err()));
+        assert_eq!(emitter_starts.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashSet, sync::Arc};
+
+use log::debug;
+
+use crate::config::{Coordinates, GeofenceArea, GeofenceConfig, GeofencesConfig};
+
+/// The mean radius of the Earth in meters
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// Evaluates the vehicle's location against private zones to determine which signals must not be emitted
+pub(crate) struct GeofenceConditionProvider {
+    /// The geofences config
+    config: GeofencesConfig,
+}
+
+impl GeofenceConditionProvider {
+    /// Creates a new GeofenceConditionProvider
+    ///
+    /// # Arguments
+    /// - `config`: the geofences config
+    pub fn new(config: GeofencesConfig) -> Self {
+        Self { config }
+    }
+
+    /// Gets the ids of the signals which must not be emitted at the vehicle's current location.
+    /// If the location is unknown, every designated signal is suppressed unless the config allows emitting them.
+    ///
+    /// # Arguments
+    /// - `get_value`: gets the current value of a signal by its id
+    pub fn suppressed_signal_ids<F>(&self, get_value: F) -> HashSet<String>
+    where
+        F: Fn(&str) -> Option<Arc<str>>,
+    {
+        let parse = |id: &str| get_value(id).and_then(|value| value.parse::<f64>().ok());
+        let location = match (
+            parse(&self.config.latitude_signal_id),
+            parse(&self.config.longitude_signal_id),
+        ) {
+            (Some(latitude), Some(longitude)) => Some(Coordinates {
+                latitude,
+                longitude,
+            }),
+            _ => None,
+        };
+
+        let zones: Vec<&GeofenceConfig> = match location {
+            Some(location) => self
+                .config
+                .zones
+                .iter()
+                .filter(|zone| contains(&zone.area, &location))
+                .inspect(|zone| debug!("The vehicle is inside the {} geofence", zone.name))
+                .collect(),
+            None if self.config.emit_when_location_unknown => Vec::new(),
+            None => {
+                debug!(
+                    "The vehicle's location is unknown. Suppressing the signals of every geofence."
+                );
+                self.config.zones.iter().collect()
+            }
+        };
+
+        zones
+            .into_iter()
+            .flat_map(|zone| zone.suppressed_signal_ids.iter().cloned())
+            .collect()
+    }
+}
+
+/// Checks whether an area contains a location
+///
+/// # Arguments
+/// - `area`: the area
+/// - `location`: the location to check
+fn contains(area: &GeofenceArea, location: &Coordinates) -> bool {
+    match area {
+        GeofenceArea::Circle { center, radius_m } => distance_m(center, location) <= *radius_m,
+        GeofenceArea::Polygon { vertices } => {
+            // Count the edges crossed by a ray from the location towards increasing longitude
+            let mut inside = false;
+            for (i, a) in vertices.iter().enumerate() {
+                let b = &vertices[(i + 1) % vertices.len()];
+                if (a.latitude > location.latitude) != (b.latitude > location.latitude) {
+                    let crossing_longitude = a.longitude
+                        + (location.latitude - a.latitude) / (b.latitude - a.latitude)
+                            * (b.longitude - a.longitude);
+                    if location.longitude < crossing_longitude {
+                        inside = !inside;
+                    }
+                }
+            }
+
+            inside
+        }
+    }
+}
+
+/// Gets the great-circle distance between two locations in meters with the haversine formula
+///
+/// # Arguments
+/// - `a`: the first location
+/// - `b`: the second location
+fn distance_m(a: &Coordinates, b: &Coordinates) -> f64 {
+    let delta_latitude = (b.latitude - a.latitude).to_radians();
+    let delta_longitude = (b.longitude - a.longitude).to_radians();
+    let h = (delta_latitude / 2.0).sin().powi(2)
+        + a.latitude.to_radians().cos()
+            * b.latitude.to_radians().cos()
+            * (delta_longitude / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_M * h.sqrt().asin()
+}
+
+#[cfg(test)]
+mod geofence_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    const LATITUDE_ID: &str = "latitude";
+    const LONGITUDE_ID: &str = "longitude";
+
+    fn coordinates(latitude: f64, longitude: f64) -> Coordinates {
+        Coordinates {
+            latitude,
+            longitude,
+        }
+    }
+
+    fn create_provider(emit_when_location_unknown: bool) -> GeofenceConditionProvider {
+        GeofenceConditionProvider::new(GeofencesConfig {
+            latitude_signal_id: LATITUDE_ID.to_string(),
+            longitude_signal_id: LONGITUDE_ID.to_string(),
+            zones: vec![
+                GeofenceConfig {
+                    name: String::from("home"),
+                    area: GeofenceArea::Circle {
+                        center: coordinates(47.6400, -122.1300),
+                        radius_m: 200.0,
+                    },
+                    suppressed_signal_ids: HashSet::from([
+                        LATITUDE_ID.to_string(),
+                        LONGITUDE_ID.to_string(),
+                    ]),
+                },
+                GeofenceConfig {
+                    name: String::from("clinic"),
+                    area: GeofenceArea::Polygon {
+                        vertices: vec![
+                            coordinates(47.60, -122.34),
+                            coordinates(47.60, -122.32),
+                            coordinates(47.62, -122.32),
+                            coordinates(47.62, -122.34),
+                        ],
+                    },
+                    suppressed_signal_ids: HashSet::from([String::from("cabin_temperature")]),
+                },
+            ],
+            emit_when_location_unknown,
+        })
+    }
+
+    fn suppressed_at(uut: &GeofenceConditionProvider, values: &[(&str, &str)]) -> HashSet<String> {
+        let values: HashMap<&str, &str> = values.iter().cloned().collect();
+        uut.suppressed_signal_ids(|id| values.get(id).map(|value| Arc::from(*value)))
+    }
+
+    #[test]
+    fn distance_m_matches_known_distance() {
+        // One degree of latitude is about 111.2 km
+        let distance = distance_m(&coordinates(47.0, -122.0), &coordinates(48.0, -122.0));
+        assert!((distance - 111_195.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn contains_checks_circle() {
+        let area = GeofenceArea::Circle {
+            center: coordinates(47.6400, -122.1300),
+            radius_m: 200.0,
+        };
+
+        // 0.001 degrees of latitude is about 111 m
+        assert!(contains(&area, &coordinates(47.6410, -122.1300)));
+        assert!(!contains(&area, &coordinates(47.6420, -122.1300)));
+    }
+
+    #[test]
+    fn contains_checks_polygon() {
+        // A concave polygon shaped like an L
+        let area = GeofenceArea::Polygon {
+            vertices: vec![
+                coordinates(0.0, 0.0),
+                coordinates(0.0, 2.0),
+                coordinates(1.0, 2.0),
+                coordinates(1.0, 1.0),
+                coordinates(2.0, 1.0),
+                coordinates(2.0, 0.0),
+            ],
+        };
+
+        assert!(contains(&area, &coordinates(0.5, 1.5)));
+        assert!(contains(&area, &coordinates(1.5, 0.5)));
+        assert!(!contains(&area, &coordinates(1.5, 1.5)));
+        assert!(!contains(&area, &coordinates(-0.5, 0.5)));
+    }
+
+    #[test]
+    fn suppressed_signal_ids_includes_signals_of_zones_containing_location() {
+        let uut = create_provider(false);
+
+        assert_eq!(
+            suppressed_at(
+                &uut,
+                &[(LATITUDE_ID, "47.6401"), (LONGITUDE_ID, "-122.1301")]
+            ),
+            HashSet::from([LATITUDE_ID.to_string(), LONGITUDE_ID.to_string()])
+        );
+        assert_eq!(
+            suppressed_at(&uut, &[(LATITUDE_ID, "47.61"), (LONGITUDE_ID, "-122.33")]),
+            HashSet::from([String::from("cabin_temperature")])
+        );
+        assert!(suppressed_at(&uut, &[(LATITUDE_ID, "47.0"), (LONGITUDE_ID, "-122.0")]).is_empty());
+    }
+
+    #[test]
+    fn suppressed_signal_ids_handles_unknown_location() {
+        let unparsable_location = [(LATITUDE_ID, "north"), (LONGITUDE_ID, "-122.0")];
+
+        assert_eq!(
+            suppressed_at(&create_provider(false), &unparsable_location),
+            HashSet::from([
+                LATITUDE_ID.to_string(),
+                LONGITUDE_ID.to_string(),
+                String::from("cabin_temperature")
+            ])
+        );
+        assert!(suppressed_at(&create_provider(true), &unparsable_location).is_empty());
+        assert!(suppressed_at(&create_provider(true), &[]).is_empty());
+    }
+}
@@ -6,97 +6,509 @@
 pub use freyja_common;
 pub use proc_macros::freyja_main;
 
+mod adapter_timeout;
 mod cartographer;
+mod cli;
+mod cloud_adapter_router;
+mod config;
+mod crash_report;
+mod data_adapter_registry;
 mod data_adapter_selector_impl;
+mod emission_pacer;
 mod emitter;
+mod flight_recorder;
+mod freyja_handle;
+mod geofence;
+mod log_file;
+mod management_api;
+mod mapping_diff;
+mod registration_metrics;
+mod request_budget;
+mod restart_policy;
+mod self_test;
 mod service_discovery_adapter_selector_impl;
+mod service_uri_overrides;
+mod signal_service;
+mod static_registrar;
+mod status_reporter;
+mod watchdog;
 
-use std::{env, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
 
 use env_logger::Target;
 use log::LevelFilter;
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
-use cartographer::Cartographer;
+use cartographer::{Cartographer, ResolutionStates};
 use emitter::Emitter;
+use freyja_build_common::config_file_stem;
 use freyja_common::{
-    cloud_adapter::CloudAdapter,
-    cmd_utils::{get_log_level, parse_args},
+    clock::{Clock, SimulatedClock, SystemClock},
+    cloud_adapter::CloudAdapterConstructor,
+    cmd_utils::{get_log_level, Args},
+    config_utils,
     data_adapter::DataAdapterFactory,
     data_adapter_selector::DataAdapterSelector,
     digital_twin_adapter::DigitalTwinAdapter,
-    mapping_adapter::MappingAdapter,
+    duplicate_filter::DuplicateFilter,
+    mapping_adapter::MappingAdapterConstructor,
+    out_dir,
     service_discovery_adapter::ServiceDiscoveryAdapter,
     service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
     signal_store::SignalStore,
 };
 
 use crate::{
+    cloud_adapter_router::{CloudAdapterRouter, TenantRouter},
+    config::Config,
+    crash_report::LastGasp,
     data_adapter_selector_impl::DataAdapterSelectorImpl,
+    flight_recorder::FlightRecorder,
+    freyja_handle::ComponentStarter,
+    geofence::GeofenceConditionProvider,
+    log_file::{RotatingLogFile, StdoutAndFileWriter},
+    mapping_diff::MappingChangeLog,
+    restart_policy::RestartPolicy,
+    self_test::LoopbackDataAdapterFactory,
     service_discovery_adapter_selector_impl::ServiceDiscoveryAdapterSelectorImpl,
+    service_uri_overrides::ServiceUriOverrides,
+    static_registrar::StaticRegistrar,
+    status_reporter::StatusReporter,
+    watchdog::Watchdog,
 };
 
-pub async fn freyja_main<
-    TDigitalTwinAdapter: DigitalTwinAdapter,
-    TCloudAdapter: CloudAdapter,
-    TMappingAdapter: MappingAdapter,
->(
+pub use cli::FREYJA_COMMAND;
+pub use data_adapter_registry::DataAdapterRegistry;
+pub use freyja_handle::{ComponentResult, FreyjaComponent, FreyjaHandle};
+pub use watchdog::STALLED_EXIT_CODE;
+
+/// Runs the subcommand selected by the command-line arguments.
+/// By default, this runs Freyja until one of its components terminates or the operating system asks the process to shut down.
+///
+/// # Arguments
+/// - `args`: the command-line arguments, parsed with [`FREYJA_COMMAND`]
+/// - `cloud_adapter_constructors`: the constructors for the cloud adapters
+/// - `mapping_adapter_constructors`: the constructors for the mapping adapters, in order
+/// - `data_adapter_factories`: the data adapter factories
+/// - `service_discovery_adapters`: the service discovery adapters, in order
+pub async fn freyja_main<TDigitalTwinAdapter>(
+    args: Args,
+    cloud_adapter_constructors: Vec<CloudAdapterConstructor>,
+    mapping_adapter_constructors: Vec<MappingAdapterConstructor>,
     data_adapter_factories: Vec<Box<dyn DataAdapterFactory + Send + Sync>>,
     service_discovery_adapters: Vec<Box<dyn ServiceDiscoveryAdapter + Send + Sync>>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let args = parse_args(env::args()).expect("Failed to parse args");
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    TDigitalTwinAdapter: DigitalTwinAdapter + Send + Sync + 'static,
+{
+    crash_report::install_panic_hook();
 
-    // Setup logging
-    let log_level = get_log_level(&args, LevelFilter::Info).expect("Could not parse log level");
-    env_logger::Builder::new()
-        .filter(None, log_level)
-        .target(Target::Stdout)
-        .init();
+    match args.subcommand() {
+        // These subcommands print their results to stdout, so they log to stderr
+        Some(cli::VALIDATE_CONFIG) => {
+            init_logging(&args, Target::Stderr);
+            validate_config()
+        }
+        Some(cli::PRINT_MAPPING) => {
+            init_logging(&args, Target::Stderr);
+            print_mapping(mapping_adapter_constructors, service_discovery_adapters).await
+        }
+        _ => {
+            start_with_args::<TDigitalTwinAdapter>(
+                &args,
+                cloud_adapter_constructors,
+                mapping_adapter_constructors,
+                data_adapter_factories,
+                service_discovery_adapters,
+            )
+            .await?
+            .wait_for_shutdown()
+            .await
+        }
+    }
+}
+
+/// Starts Freyja and returns a handle for managing its lifecycle.
+/// This allows host applications to stop Freyja or restart its components programmatically.
+/// Returns an error if the command-line arguments select a subcommand other than `run`.
+///
+/// # Arguments
+/// - `args`: the command-line arguments, parsed with [`FREYJA_COMMAND`]
+/// - `cloud_adapter_constructors`: the constructors for the cloud adapters
+/// - `mapping_adapter_constructors`: the constructors for the mapping adapters, in order
+/// - `data_adapter_factories`: the data adapter factories
+/// - `service_discovery_adapters`: the service discovery adapters, in order
+pub async fn start_freyja<TDigitalTwinAdapter>(
+    args: Args,
+    cloud_adapter_constructors: Vec<CloudAdapterConstructor>,
+    mapping_adapter_constructors: Vec<MappingAdapterConstructor>,
+    data_adapter_factories: Vec<Box<dyn DataAdapterFactory + Send + Sync>>,
+    service_discovery_adapters: Vec<Box<dyn ServiceDiscoveryAdapter + Send + Sync>>,
+) -> Result<FreyjaHandle, Box<dyn std::error::Error + Send + Sync>>
+where
+    TDigitalTwinAdapter: DigitalTwinAdapter + Send + Sync + 'static,
+{
+    if args.subcommand() != Some(cli::RUN) {
+        return Err(format!(
+            "Freyja can only be started with the {} subcommand",
+            cli::RUN
+        )
+        .into());
+    }
+
+    start_with_args::<TDigitalTwinAdapter>(
+        &args,
+        cloud_adapter_constructors,
+        mapping_adapter_constructors,
+        data_adapter_factories,
+        service_discovery_adapters,
+    )
+    .await
+}
+
+/// Starts Freyja with parsed command-line arguments and returns a handle for managing its lifecycle
+///
+/// # Arguments
+/// - `args`: the parsed command-line arguments
+/// - `cloud_adapter_constructors`: the constructors for the cloud adapters
+/// - `mapping_adapter_constructors`: the constructors for the mapping adapters, in order
+/// - `data_adapter_factories`: the data adapter factories
+/// - `service_discovery_adapters`: the service discovery adapters, in order
+async fn start_with_args<TDigitalTwinAdapter>(
+    args: &Args,
+    cloud_adapter_constructors: Vec<CloudAdapterConstructor>,
+    mapping_adapter_constructors: Vec<MappingAdapterConstructor>,
+    data_adapter_factories: Vec<Box<dyn DataAdapterFactory + Send + Sync>>,
+    service_discovery_adapters: Vec<Box<dyn ServiceDiscoveryAdapter + Send + Sync>>,
+) -> Result<FreyjaHandle, Box<dyn std::error::Error + Send + Sync>>
+where
+    TDigitalTwinAdapter: DigitalTwinAdapter + Send + Sync + 'static,
+{
+    // The config is read first since it chooses where the logs are written
+    let config = read_config().expect("Could not read config");
+
+    let log_target = match config.log_file.as_ref() {
+        Some(log_file) => {
+            let file = RotatingLogFile::open(log_file).expect("Could not open log file");
+            Target::Pipe(Box::new(StdoutAndFileWriter::new(file)))
+        }
+        None => Target::Stdout,
+    };
+    init_logging(args, log_target);
+
+    let restart_policy = RestartPolicy::from_args(args).expect("Could not parse restart policy");
+
+    let clock: Arc<dyn Clock> = match config.simulation {
+        Some(simulation) => {
+            log::info!(
+                "Running in simulation mode with a speedup of {}",
+                simulation.speedup
+            );
+            Arc::new(SimulatedClock::new(SystemClock.now(), simulation.speedup))
+        }
+        None => Arc::new(SystemClock),
+    };
+    let signal_store = Arc::new(SignalStore::with_clock(clock.clone()));
 
-    let signal_store = Arc::new(SignalStore::new());
+    if let Some(duplicate_suppression) = config.duplicate_suppression {
+        signal_store.set_duplicate_filter(Arc::new(DuplicateFilter::new(
+            Duration::from_millis(duplicate_suppression.window_ms),
+            duplicate_suppression.content_signal_ids,
+        )));
+    }
+
+    let flight_recorder = config.flight_recorder.map(|flight_recorder_config| {
+        let recorder = Arc::new(FlightRecorder::new(flight_recorder_config, clock.clone()));
+        signal_store.add_value_observer(recorder.clone());
+        recorder
+    });
+
+    let mut data_adapter_selector = DataAdapterSelectorImpl::new(
+        signal_store.clone(),
+        config.provider_request_budgets,
+        config.adapter_timeouts.data_ms,
+    );
+    if let Some(path) = config.traffic_recording_path.as_ref() {
+        data_adapter_selector
+            .record_traffic(path)
+            .expect("Could not start recording provider traffic");
+    }
 
-    let mut data_adapter_selector = DataAdapterSelectorImpl::new(signal_store.clone());
     for factory in data_adapter_factories.into_iter() {
         data_adapter_selector
             .register(factory)
             .expect("Could not register data adapter factory");
     }
 
+    if config.self_test.is_some() {
+        data_adapter_selector
+            .register(Box::new(LoopbackDataAdapterFactory {}))
+            .expect("Could not register loopback data adapter factory");
+    }
+
+    let registration_metrics = data_adapter_selector.registration_metrics();
     let data_adapter_selector = Arc::new(Mutex::new(data_adapter_selector));
 
+    if let Some(self_test) = config.self_test.as_ref() {
+        self_test::register(self_test, &signal_store, &data_adapter_selector)
+            .await
+            .expect("Could not register self-test signal");
+    }
+
     let mut service_discovery_adapter_selector = ServiceDiscoveryAdapterSelectorImpl::new();
+    service_discovery_adapter_selector.set_overrides(ServiceUriOverrides::from_env(
+        config.service_uri_overrides_path.as_deref(),
+        clock.clone(),
+    ));
     for adapter in service_discovery_adapters.into_iter() {
         service_discovery_adapter_selector
             .register(adapter)
             .expect("Could not register service discovery adapter")
     }
 
+    if !config.prefetch_service_ids.is_empty() {
+        let ids: Vec<&str> = config
+            .prefetch_service_ids
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let uris = service_discovery_adapter_selector
+            .get_service_uris(&ids)
+            .await;
+        for id in ids.iter().filter(|id| !uris.contains_key(**id)) {
+            log::warn!("Could not discover service {id} at startup");
+        }
+    }
+
     let service_discovery_adapter_selector =
         Arc::new(Mutex::new(service_discovery_adapter_selector));
 
-    // Setup cartographer
+    // Setup cartographer, or register the static signals from config instead
     let cartographer_poll_interval = Duration::from_secs(5);
-    let cartographer = Cartographer::new(
-        signal_store.clone(),
-        TMappingAdapter::create_new(service_discovery_adapter_selector.clone())
-            .expect("Could not create mapping adapter"),
-        TDigitalTwinAdapter::create_new(service_discovery_adapter_selector.clone())
-            .expect("Could not create digital twin adapter"),
-        data_adapter_selector.clone(),
-        cartographer_poll_interval,
-    );
+    let mapping_changes = Arc::new(MappingChangeLog::default());
+    let resolution_states = Arc::new(ResolutionStates::default());
+    let (start_cartographer, cartographer_heartbeat) = match config.static_signals {
+        Some(static_signals) => {
+            log::info!(
+                "Registering {} static signals without the mapping and digital twin adapters",
+                static_signals.len()
+            );
+            let registrar = Arc::new(StaticRegistrar::new(
+                signal_store.clone(),
+                static_signals,
+                data_adapter_selector.clone(),
+                cartographer_poll_interval,
+            ));
+
+            let heartbeat = registrar.heartbeat();
+            let start: ComponentStarter = Box::new(move |cancellation_token: CancellationToken| {
+                registrar.clone().run(cancellation_token)
+            });
 
-    // Setup emitter
-    let emitter = Emitter::new(
+            (start, heartbeat)
+        }
+        None => {
+            let mapping_adapters = mapping_adapter_constructors
+                .into_iter()
+                .map(|constructor| {
+                    constructor(service_discovery_adapter_selector.clone())
+                        .expect("Could not create mapping adapter")
+                })
+                .collect();
+
+            let cartographer = Arc::new(Cartographer::new(
+                signal_store.clone(),
+                mapping_adapters,
+                config.mapping_merge_policy,
+                TDigitalTwinAdapter::create_new(service_discovery_adapter_selector.clone())
+                    .expect("Could not create digital twin adapter"),
+                data_adapter_selector.clone(),
+                cartographer_poll_interval,
+                config.adapter_timeouts,
+                mapping_changes.clone(),
+                resolution_states.clone(),
+            ));
+
+            let heartbeat = cartographer.heartbeat();
+            let start: ComponentStarter = Box::new(move |cancellation_token: CancellationToken| {
+                cartographer.clone().run(cancellation_token)
+            });
+
+            (start, heartbeat)
+        }
+    };
+
+    // Setup emitter.
+    // Signals without a tenant use the default cloud adapters, and each tenant gets its own instance of every adapter
+    let create_cloud_adapter_router = |tenant: Option<&str>| -> Result<
+        CloudAdapterRouter,
+        Box<dyn std::error::Error + Send + Sync>,
+    > {
+        let cloud_adapters = cloud_adapter_constructors
+            .iter()
+            .map(|constructor| constructor(service_discovery_adapter_selector.clone(), tenant))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CloudAdapterRouter::new(
+            cloud_adapters,
+            config.cloud_routes.clone(),
+            config.adapter_timeouts.cloud_ms,
+        )?)
+    };
+
+    let tenant_cloud_adapter_routers = config
+        .tenants
+        .iter()
+        .map(|tenant| {
+            create_cloud_adapter_router(Some(tenant.as_str()))
+                .map(|router| (tenant.clone(), router))
+                .map_err(|e| format!("Could not create cloud adapters for tenant {tenant}: {e}"))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    let emitter = Arc::new(Emitter::new(
         signal_store.clone(),
-        TCloudAdapter::create_new(service_discovery_adapter_selector.clone())
-            .expect("Could not create cloud adapter"),
+        TenantRouter::new(
+            create_cloud_adapter_router(None)?,
+            tenant_cloud_adapter_routers,
+        ),
         data_adapter_selector.clone(),
-    );
+        clock.clone(),
+        config.value_request_mode,
+        config.backfill_poll_interval_ms,
+        flight_recorder.clone(),
+        config.emission_ramp_up_ms,
+        config
+            .status_signals
+            .map(|status_signals| StatusReporter::new(status_signals, clock.clone())),
+        config.geofences.map(GeofenceConditionProvider::new),
+        config.emission_acknowledgement_mode,
+    ));
+
+    // Setup the APIs for in-vehicle consumers
+    let cancellation_token = CancellationToken::new();
+    if let Some(address) = config.management_api_address.as_ref() {
+        management_api::start(
+            address,
+            signal_store.clone(),
+            flight_recorder,
+            mapping_changes,
+            resolution_states,
+            registration_metrics,
+            data_adapter_selector.clone(),
+            cancellation_token.clone(),
+        )?;
+    }
+
+    if let Some(address) = config.signal_service_address.as_ref() {
+        signal_service::start(address, signal_store.clone(), cancellation_token.clone())?;
+    }
+
+    let watchdog = config.watchdog.map(|watchdog_config| {
+        Watchdog::new(watchdog_config, cartographer_heartbeat, emitter.heartbeat())
+    });
+
+    let last_gasp = config.last_gasp.map(|last_gasp_config| {
+        let emitter = emitter.clone();
+        LastGasp::new(
+            last_gasp_config,
+            Box::new(move |message| {
+                let emitter = emitter.clone();
+                tokio::spawn(async move { emitter.send_status(message).await })
+            }),
+        )
+    });
+
+    Ok(FreyjaHandle::new(
+        cancellation_token,
+        DataAdapterRegistry::new(data_adapter_selector),
+        start_cartographer,
+        Box::new(move |cancellation_token: CancellationToken| {
+            emitter.clone().run(cancellation_token)
+        }),
+        watchdog,
+        restart_policy,
+        last_gasp,
+    ))
+}
+
+/// Sets up logging at the level passed on the command line.
+/// Host applications may start Freyja more than once, so the logger might already be initialized
+///
+/// # Arguments
+/// - `args`: the parsed command-line arguments
+/// - `target`: where to write the logs
+fn init_logging(args: &Args, target: Target) {
+    let log_level = get_log_level(args, LevelFilter::Info).expect("Could not parse log level");
+    let _ = env_logger::Builder::new()
+        .filter(None, log_level)
+        .target(target)
+        .try_init();
+}
+
+/// Reads Freyja's config, including its overrides
+fn read_config() -> Result<Config, Box<dyn std::error::Error + Send + Sync>> {
+    config_utils::read_from_files(
+        config_file_stem!(),
+        config_utils::JSON_EXT,
+        out_dir!(),
+        |e| e.into(),
+        |e| e.into(),
+    )
+}
+
+/// Reads Freyja's config and prints it as JSON,
+/// so that the result of applying the config overrides and profile can be checked without starting Freyja
+fn validate_config() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let config = read_config()?;
+    println!("{}", serde_json::to_string_pretty(&config)?);
+
+    Ok(())
+}
+
+/// Gets the mapping from the mapping adapters, merges it according to the config, and prints it as JSON
+///
+/// # Arguments
+/// - `mapping_adapter_constructors`: the constructors for the mapping adapters, in order
+/// - `service_discovery_adapters`: the service discovery adapters, in order
+async fn print_mapping(
+    mapping_adapter_constructors: Vec<MappingAdapterConstructor>,
+    service_discovery_adapters: Vec<Box<dyn ServiceDiscoveryAdapter + Send + Sync>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let config = read_config()?;
 
-    tokio::select! {
-        Err(e) = cartographer.run() => { println!("[main] cartographer terminated with error {e:?}"); Err(e) },
-        Err(e) = emitter.run() => { println!("[main] emitter terminated with error {e:?}"); Err(e) },
-        else => { println!("[main] all operations terminated successfully"); Ok(()) },
+    let mut service_discovery_adapter_selector = ServiceDiscoveryAdapterSelectorImpl::new();
+    service_discovery_adapter_selector.set_overrides(ServiceUriOverrides::from_env(
+        config.service_uri_overrides_path.as_deref(),
+        Arc::new(SystemClock),
+    ));
+    for adapter in service_discovery_adapters.into_iter() {
+        service_discovery_adapter_selector.register(adapter)?;
     }
+
+    let service_discovery_adapter_selector =
+        Arc::new(Mutex::new(service_discovery_adapter_selector));
+
+    let mapping_adapters = mapping_adapter_constructors
+        .iter()
+        .map(|constructor| constructor(service_discovery_adapter_selector.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mapping = cartographer::merge_mappings(
+        &mapping_adapters,
+        config.mapping_merge_policy,
+        config.adapter_timeouts.mapping_ms,
+    )
+    .await?;
+
+    // Sort the entries so that the output is stable
+    let mapping: BTreeMap<_, _> = mapping.into_iter().collect();
+    println!("{}", serde_json::to_string_pretty(&mapping)?);
+
+    Ok(())
 }
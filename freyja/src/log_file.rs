@@ -0,0 +1,285 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, ErrorKind, Stdout, Write},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::config::LogFileConfig;
+
+/// A log file which is rotated when it grows too large or has been written to for too long.
+/// Rotated files are kept next to the log file with a numeric suffix, where `.1` is the most recent,
+/// and the oldest ones are deleted once there are more than the configured number.
+pub(crate) struct RotatingLogFile {
+    /// The path of the log file
+    path: PathBuf,
+
+    /// The size in bytes after which the log file is rotated
+    max_size_bytes: Option<u64>,
+
+    /// The time after which the log file is rotated
+    rotation_interval: Option<Duration>,
+
+    /// The number of rotated files to keep
+    max_rotated_files: usize,
+
+    /// The current log file
+    file: File,
+
+    /// The size of the current log file
+    size: u64,
+
+    /// When the current log file was opened
+    opened_at: Instant,
+}
+
+impl RotatingLogFile {
+    /// Opens the log file, appending to it if it already exists
+    ///
+    /// # Arguments
+    /// - `config`: the log file config
+    pub fn open(config: &LogFileConfig) -> io::Result<Self> {
+        let path = PathBuf::from(&config.path);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_size_bytes: config.max_size_bytes,
+            rotation_interval: config.rotation_interval_ms.map(Duration::from_millis),
+            max_rotated_files: config.max_rotated_files,
+            file,
+            size,
+            opened_at: Instant::now(),
+        })
+    }
+
+    /// Gets the path of a rotated file
+    ///
+    /// # Arguments
+    /// - `index`: the index of the rotated file, where 1 is the most recent
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        PathBuf::from(path)
+    }
+
+    /// Checks whether the log file should be rotated before writing to it
+    ///
+    /// # Arguments
+    /// - `len`: the number of bytes about to be written
+    fn should_rotate(&self, len: usize) -> bool {
+        // An empty file is never rotated, so a record larger than the limit still gets written
+        let too_large = self
+            .max_size_bytes
+            .is_some_and(|max| self.size > 0 && self.size + len as u64 > max);
+        let too_old = self
+            .rotation_interval
+            .is_some_and(|interval| self.opened_at.elapsed() >= interval);
+
+        too_large || too_old
+    }
+
+    /// Shifts the rotated files by one, moves the log file to the most recent rotated file, and starts a new log file
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_rotated_files > 0 {
+            for index in (1..self.max_rotated_files).rev() {
+                match fs::rename(self.rotated_path(index), self.rotated_path(index + 1)) {
+                    Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+                    _ => {}
+                }
+            }
+
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        self.opened_at = Instant::now();
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingLogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.should_rotate(buf.len()) {
+            self.rotate()?;
+        }
+
+        // Records are written whole so that rotation never splits one across files
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Writes logs to stdout and to a rotating log file
+pub(crate) struct StdoutAndFileWriter {
+    /// The standard output
+    stdout: Stdout,
+
+    /// The log file
+    file: RotatingLogFile,
+}
+
+impl StdoutAndFileWriter {
+    /// Creates a new writer
+    ///
+    /// # Arguments
+    /// - `file`: the log file
+    pub fn new(file: RotatingLogFile) -> Self {
+        Self {
+            stdout: io::stdout(),
+            file,
+        }
+    }
+}
+
+impl Write for StdoutAndFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdout.write_all(buf)?;
+        self.file.write_all(buf)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()?;
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod log_file_tests {
+    use super::*;
+
+    use freyja_test_common::fixtures::TempDirFixture;
+
+    /// A directory in the temp directory which is deleted when dropped
+    struct LogDir {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl LogDir {
+        fn new(name: &str) -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join(name);
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+
+        fn config(
+            &self,
+            max_size_bytes: Option<u64>,
+            rotation_interval_ms: Option<u64>,
+            max_rotated_files: usize,
+        ) -> LogFileConfig {
+            LogFileConfig {
+                path: self.path.join("freyja.log").to_string_lossy().into_owned(),
+                max_size_bytes,
+                rotation_interval_ms,
+                max_rotated_files,
+            }
+        }
+
+        fn read(&self, name: &str) -> Option<String> {
+            fs::read_to_string(self.path.join(name)).ok()
+        }
+    }
+
+    #[test]
+    fn open_appends_to_existing_file() {
+        let dir = LogDir::new("freyja_log_file_append");
+        let config = dir.config(None, None, 1);
+
+        RotatingLogFile::open(&config)
+            .unwrap()
+            .write_all(b"first\n")
+            .unwrap();
+        RotatingLogFile::open(&config)
+            .unwrap()
+            .write_all(b"second\n")
+            .unwrap();
+
+        assert_eq!(dir.read("freyja.log").unwrap(), "first\nsecond\n");
+    }
+
+    #[test]
+    fn write_rotates_file_when_it_would_exceed_max_size() {
+        let dir = LogDir::new("freyja_log_file_size");
+        let mut uut = RotatingLogFile::open(&dir.config(Some(10), None, 2)).unwrap();
+
+        uut.write_all(b"one\n").unwrap();
+        uut.write_all(b"two\n").unwrap();
+        uut.write_all(b"three\n").unwrap();
+        uut.write_all(b"four\n").unwrap();
+
+        assert_eq!(dir.read("freyja.log").unwrap(), "four\n");
+        assert_eq!(dir.read("freyja.log.1").unwrap(), "three\n");
+        assert_eq!(dir.read("freyja.log.2").unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn write_deletes_oldest_rotated_file() {
+        let dir = LogDir::new("freyja_log_file_max_files");
+        let mut uut = RotatingLogFile::open(&dir.config(Some(1), None, 2)).unwrap();
+
+        for record in ["one\n", "two\n", "three\n", "four\n"] {
+            uut.write_all(record.as_bytes()).unwrap();
+        }
+
+        assert_eq!(dir.read("freyja.log").unwrap(), "four\n");
+        assert_eq!(dir.read("freyja.log.1").unwrap(), "three\n");
+        assert_eq!(dir.read("freyja.log.2").unwrap(), "two\n");
+        assert!(dir.read("freyja.log.3").is_none());
+    }
+
+    #[test]
+    fn write_truncates_file_when_no_rotated_files_are_kept() {
+        let dir = LogDir::new("freyja_log_file_no_rotated_files");
+        let mut uut = RotatingLogFile::open(&dir.config(Some(1), None, 0)).unwrap();
+
+        uut.write_all(b"one\n").unwrap();
+        uut.write_all(b"two\n").unwrap();
+
+        assert_eq!(dir.read("freyja.log").unwrap(), "two\n");
+        assert!(dir.read("freyja.log.1").is_none());
+    }
+
+    #[test]
+    fn write_rotates_file_after_rotation_interval() {
+        let dir = LogDir::new("freyja_log_file_interval");
+        let mut uut = RotatingLogFile::open(&dir.config(None, Some(60_000), 1)).unwrap();
+
+        uut.write_all(b"one\n").unwrap();
+        uut.write_all(b"two\n").unwrap();
+        uut.opened_at = Instant::now()
+            .checked_sub(Duration::from_secs(120))
+            .unwrap();
+        uut.write_all(b"three\n").unwrap();
+
+        assert_eq!(dir.read("freyja.log").unwrap(), "three\n");
+        assert_eq!(dir.read("freyja.log.1").unwrap(), "one\ntwo\n");
+    }
+}
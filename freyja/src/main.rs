@@ -2,19 +2,33 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use dtc_data_adapter::dtc_data_adapter_factory::DtcDataAdapterFactory;
 use file_service_discovery_adapter::file_service_discovery_adapter::FileServiceDiscoveryAdapter;
 use grpc_cloud_adapter::grpc_cloud_adapter::GRPCCloudAdapter;
 use grpc_digital_twin_adapter::grpc_digital_twin_adapter::GRPCDigitalTwinAdapter;
 use grpc_mapping_adapter::grpc_mapping_adapter::GRPCMappingAdapter;
 use grpc_service_discovery_adapter::grpc_service_discovery_adapter::GRPCServiceDiscoveryAdapter;
+use grpc_v2_data_adapter::grpc_v2_data_adapter_factory::GRPCV2DataAdapterFactory;
+use http_data_adapter::http_data_adapter_factory::HttpDataAdapterFactory;
 use managed_subscribe_data_adapter::managed_subscribe_data_adapter_factory::ManagedSubscribeDataAdapterFactory;
 use mqtt_data_adapter::mqtt_data_adapter_factory::MqttDataAdapterFactory;
 use sample_grpc_data_adapter::sample_grpc_data_adapter_factory::SampleGRPCDataAdapterFactory;
+use sovd_data_adapter::sovd_data_adapter_factory::SovdDataAdapterFactory;
+use uprotocol_data_adapter::uprotocol_data_adapter_factory::UProtocolDataAdapterFactory;
 
 freyja::freyja_main! {
     GRPCDigitalTwinAdapter,
     GRPCCloudAdapter,
     GRPCMappingAdapter,
-    [SampleGRPCDataAdapterFactory, MqttDataAdapterFactory, ManagedSubscribeDataAdapterFactory],
+    [
+        SampleGRPCDataAdapterFactory,
+        MqttDataAdapterFactory,
+        ManagedSubscribeDataAdapterFactory,
+        HttpDataAdapterFactory,
+        GRPCV2DataAdapterFactory,
+        UProtocolDataAdapterFactory,
+        DtcDataAdapterFactory,
+        SovdDataAdapterFactory,
+    ],
     [GRPCServiceDiscoveryAdapter, FileServiceDiscoveryAdapter],
 }
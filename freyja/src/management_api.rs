@@ -0,0 +1,542 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::BTreeMap, convert::Infallible, sync::Arc};
+
+use axum::{
+    extract::{FromRef, Path, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Json, Router,
+};
+use log::{info, warn};
+use serde_json::Value;
+use tokio::sync::{broadcast::error::RecvError, mpsc, Mutex};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+
+use freyja_common::{
+    data_adapter_selector::{DataAdapterInfo, DataAdapterSelector, DataAdapterSelectorErrorKind},
+    signal_store::SignalStore,
+    uri,
+};
+
+use crate::{
+    cartographer::{ResolutionState, ResolutionStates},
+    flight_recorder::FlightRecorder,
+    mapping_diff::{MappingChangeLog, MappingDiff},
+    registration_metrics::{RegistrationMetrics, RegistrationMetricsSnapshot},
+};
+
+/// The path of the endpoint which exports the current signal values as a VSS-style JSON tree
+const VSS_EXPORT_PATH: &str = "/signals/vss";
+
+/// The path of the endpoint which exports the emission timeline of every signal
+const EMISSION_TIMELINE_PATH: &str = "/emissions/timeline";
+
+/// The path of the endpoint which triggers a flight recorder capture
+const FLIGHT_RECORDER_TRIGGER_PATH: &str = "/flight_recorder/trigger";
+
+/// The path of the endpoint which reports the resolution state of each signal in the current mapping
+const SIGNAL_RESOLUTION_PATH: &str = "/signals/resolution";
+
+/// The path of the endpoint which lists the recent changes to the mapping
+const MAPPING_CHANGES_PATH: &str = "/mapping/changes";
+
+/// The path of the endpoint which streams changes to the mapping as server-sent events
+const MAPPING_CHANGE_EVENTS_PATH: &str = "/mapping/changes/events";
+
+/// The path of the endpoint which reports the outcomes of registering entities with data adapters
+const DATA_ADAPTER_REGISTRATIONS_PATH: &str = "/data_adapters/registrations";
+
+/// The path of the endpoint which reports which data adapter serves an entity
+const DATA_ADAPTER_INFO_PATH: &str = "/data_adapters/:entity_id";
+
+/// The name of the server-sent events for changes to the mapping
+const MAPPING_CHANGE_EVENT: &str = "mapping_changed";
+
+/// The number of events that are buffered for each subscriber while the subscriber receives them
+const EVENT_BUFFER_SIZE: usize = 16;
+
+/// The data adapter selector, shared with the cartographer and the emitter
+type SharedDataAdapterSelector = Arc<Mutex<dyn DataAdapterSelector + Send + Sync>>;
+
+/// The state shared by the management API's handlers
+#[derive(Clone)]
+struct ManagementState {
+    /// The shared signal store
+    signals: Arc<SignalStore>,
+
+    /// The flight recorder, if enabled
+    flight_recorder: Option<Arc<FlightRecorder>>,
+
+    /// The log of changes to the mapping
+    mapping_changes: Arc<MappingChangeLog>,
+
+    /// The resolution state of each signal in the current mapping
+    resolution_states: Arc<ResolutionStates>,
+
+    /// The outcomes of registering entities with data adapters
+    registration_metrics: Arc<RegistrationMetrics>,
+
+    /// The data adapter selector
+    data_adapter_selector: SharedDataAdapterSelector,
+
+    /// The token for stopping the API, which also ends the event streams
+    cancellation_token: CancellationToken,
+}
+
+impl FromRef<ManagementState> for Arc<SignalStore> {
+    fn from_ref(state: &ManagementState) -> Self {
+        state.signals.clone()
+    }
+}
+
+impl FromRef<ManagementState> for Option<Arc<FlightRecorder>> {
+    fn from_ref(state: &ManagementState) -> Self {
+        state.flight_recorder.clone()
+    }
+}
+
+impl FromRef<ManagementState> for Arc<MappingChangeLog> {
+    fn from_ref(state: &ManagementState) -> Self {
+        state.mapping_changes.clone()
+    }
+}
+
+impl FromRef<ManagementState> for Arc<ResolutionStates> {
+    fn from_ref(state: &ManagementState) -> Self {
+        state.resolution_states.clone()
+    }
+}
+
+impl FromRef<ManagementState> for Arc<RegistrationMetrics> {
+    fn from_ref(state: &ManagementState) -> Self {
+        state.registration_metrics.clone()
+    }
+}
+
+impl FromRef<ManagementState> for SharedDataAdapterSelector {
+    fn from_ref(state: &ManagementState) -> Self {
+        state.data_adapter_selector.clone()
+    }
+}
+
+impl FromRef<ManagementState> for CancellationToken {
+    fn from_ref(state: &ManagementState) -> Self {
+        state.cancellation_token.clone()
+    }
+}
+
+/// Starts the management API, which lets in-vehicle consumers such as HMIs and diagnostics tools
+/// read Freyja's signal values without going through the cloud.
+/// The API runs until the cancellation token is cancelled.
+///
+/// # Arguments
+/// - `address`: the hosting address
+/// - `signals`: the shared signal store
+/// - `flight_recorder`: the flight recorder, if enabled
+/// - `mapping_changes`: the log of changes to the mapping
+/// - `resolution_states`: the resolution state of each signal in the current mapping
+/// - `registration_metrics`: the outcomes of registering entities with data adapters
+/// - `data_adapter_selector`: the data adapter selector
+/// - `cancellation_token`: the token for stopping the API
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn start(
+    address: &str,
+    signals: Arc<SignalStore>,
+    flight_recorder: Option<Arc<FlightRecorder>>,
+    mapping_changes: Arc<MappingChangeLog>,
+    resolution_states: Arc<ResolutionStates>,
+    registration_metrics: Arc<RegistrationMetrics>,
+    data_adapter_selector: SharedDataAdapterSelector,
+    cancellation_token: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let addr = uri::parse_socket_address(address)?;
+
+    let app = Router::new()
+        .route(VSS_EXPORT_PATH, get(export_vss_json))
+        .route(EMISSION_TIMELINE_PATH, get(export_emission_timeline))
+        .route(SIGNAL_RESOLUTION_PATH, get(get_signal_resolution))
+        .route(FLIGHT_RECORDER_TRIGGER_PATH, post(trigger_flight_recorder))
+        .route(MAPPING_CHANGES_PATH, get(list_mapping_changes))
+        .route(MAPPING_CHANGE_EVENTS_PATH, get(stream_mapping_changes))
+        .route(
+            DATA_ADAPTER_REGISTRATIONS_PATH,
+            get(get_data_adapter_registrations),
+        )
+        .route(DATA_ADAPTER_INFO_PATH, get(get_data_adapter_info))
+        .with_state(ManagementState {
+            signals,
+            flight_recorder,
+            mapping_changes,
+            resolution_states,
+            registration_metrics,
+            data_adapter_selector,
+            cancellation_token: cancellation_token.clone(),
+        });
+
+    let server = axum::Server::try_bind(&addr)?
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(async move { cancellation_token.cancelled().await });
+
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            warn!("Management API terminated with error: {e}");
+        }
+    });
+
+    info!("Started the management API on {addr}");
+
+    Ok(())
+}
+
+/// Exports the current signal values as a VSS-style JSON tree
+///
+/// # Arguments
+/// - `signals`: the shared signal store
+async fn export_vss_json(State(signals): State<Arc<SignalStore>>) -> Json<Value> {
+    Json(signals.export_vss_json())
+}
+
+/// Exports the next scheduled emission and recent emission history of every signal,
+/// which tools can render as a timeline for debugging emission intervals
+///
+/// # Arguments
+/// - `signals`: the shared signal store
+async fn export_emission_timeline(State(signals): State<Arc<SignalStore>>) -> Json<Value> {
+    Json(signals.export_emission_timeline())
+}
+
+/// Reports whether the source of each signal in the current mapping has been resolved,
+/// along with the reason and the time until the next attempt for signals which failed to resolve
+///
+/// # Arguments
+/// - `resolution_states`: the resolution state of each signal in the current mapping
+async fn get_signal_resolution(
+    State(resolution_states): State<Arc<ResolutionStates>>,
+) -> Json<BTreeMap<String, ResolutionState>> {
+    let resolution_states = resolution_states.read().unwrap();
+    Json(
+        resolution_states
+            .iter()
+            .map(|(id, state)| (id.clone(), state.clone()))
+            .collect(),
+    )
+}
+
+/// Triggers a flight recorder capture, which is emitted to the cloud once its post-trigger window has elapsed.
+/// Responds with `409 Conflict` if a capture is already in progress
+/// and `404 Not Found` if the flight recorder is not configured.
+///
+/// # Arguments
+/// - `flight_recorder`: the flight recorder, if enabled
+async fn trigger_flight_recorder(
+    State(flight_recorder): State<Option<Arc<FlightRecorder>>>,
+) -> StatusCode {
+    match flight_recorder {
+        Some(recorder) if recorder.trigger() => {
+            info!("Flight recorder capture triggered by the management API");
+            StatusCode::ACCEPTED
+        }
+        Some(_) => StatusCode::CONFLICT,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+/// Lists the recent changes to the mapping, from oldest to newest
+///
+/// # Arguments
+/// - `mapping_changes`: the log of changes to the mapping
+async fn list_mapping_changes(
+    State(mapping_changes): State<Arc<MappingChangeLog>>,
+) -> Json<Vec<MappingDiff>> {
+    Json(mapping_changes.recent())
+}
+
+/// Streams each new change to the mapping as a server-sent event until the API is stopped
+///
+/// # Arguments
+/// - `mapping_changes`: the log of changes to the mapping
+/// - `cancellation_token`: the token for stopping the API
+async fn stream_mapping_changes(
+    State(mapping_changes): State<Arc<MappingChangeLog>>,
+    State(cancellation_token): State<CancellationToken>,
+) -> Sse<ReceiverStream<Result<Event, Infallible>>> {
+    Sse::new(mapping_change_events(&mapping_changes, cancellation_token))
+        .keep_alive(KeepAlive::default())
+}
+
+/// Reports how many entities each kind of data adapter registered and how long it took,
+/// along with the endpoints and entities which no data adapter could serve
+///
+/// # Arguments
+/// - `registration_metrics`: the outcomes of registering entities with data adapters
+async fn get_data_adapter_registrations(
+    State(registration_metrics): State<Arc<RegistrationMetrics>>,
+) -> Json<RegistrationMetricsSnapshot> {
+    Json(registration_metrics.snapshot())
+}
+
+/// Reports which data adapter serves an entity, along with its provider endpoint and when the entity was registered.
+/// Responds with `404 Not Found` if no data adapter serves the entity.
+///
+/// # Arguments
+/// - `data_adapter_selector`: the data adapter selector
+/// - `entity_id`: the id of the entity
+async fn get_data_adapter_info(
+    State(data_adapter_selector): State<SharedDataAdapterSelector>,
+    Path(entity_id): Path<String>,
+) -> Result<Json<DataAdapterInfo>, StatusCode> {
+    let result = data_adapter_selector
+        .lock()
+        .await
+        .get_adapter_info(&entity_id)
+        .await;
+
+    match result {
+        Ok(info) => Ok(Json(info)),
+        Err(e) if e.kind() == DataAdapterSelectorErrorKind::EntityNotFound => {
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            warn!("Could not get the data adapter serving {entity_id}: {e}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Subscribes to the changes to the mapping and forwards each one as an event
+/// until the subscriber disconnects or the cancellation token is cancelled
+///
+/// # Arguments
+/// - `mapping_changes`: the log of changes to the mapping
+/// - `cancellation_token`: the token for ending the stream
+fn mapping_change_events(
+    mapping_changes: &MappingChangeLog,
+    cancellation_token: CancellationToken,
+) -> ReceiverStream<Result<Event, Infallible>> {
+    let mut changes = mapping_changes.subscribe();
+    let (sender, receiver) = mpsc::channel(EVENT_BUFFER_SIZE);
+
+    tokio::spawn(async move {
+        loop {
+            let diff = tokio::select! {
+                _ = cancellation_token.cancelled() => break,
+                _ = sender.closed() => break,
+                result = changes.recv() => match result {
+                    Ok(diff) => diff,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Mapping change subscriber missed {skipped} changes");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            let event = match Event::default()
+                .event(MAPPING_CHANGE_EVENT)
+                .id(diff.sequence.to_string())
+                .json_data(&diff)
+            {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Could not serialize mapping change {}: {e}", diff.sequence);
+                    continue;
+                }
+            };
+
+            if sender.send(Ok(event)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+#[cfg(test)]
+mod management_api_tests {
+    use super::*;
+
+    use std::collections::{HashMap, HashSet};
+
+    use time::OffsetDateTime;
+    use tokio_stream::StreamExt;
+
+    use freyja_common::{
+        clock::ManualClock, digital_twin_map_entry::DigitalTwinMapEntry, entity::Entity,
+        signal::SignalPatch,
+    };
+
+    use freyja_test_common::mocks::MockDataAdapterSelector;
+
+    use crate::{cartographer::ResolutionFailure, config::FlightRecorderConfig};
+
+    const ID: &str = "Vehicle.Speed";
+
+    fn create_mapping_diff() -> MappingDiff {
+        MappingDiff::between(
+            &HashMap::new(),
+            &HashMap::from([(ID.to_string(), DigitalTwinMapEntry::default())]),
+            OffsetDateTime::UNIX_EPOCH,
+        )
+    }
+
+    #[tokio::test]
+    async fn export_vss_json_returns_signal_values() {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                source: Entity {
+                    id: ID.to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+        signals.set_value(ID.to_string(), String::from("42"));
+
+        let Json(result) = export_vss_json(State(signals)).await;
+        assert_eq!(result["Vehicle"]["Speed"]["value"], "42");
+    }
+
+    #[tokio::test]
+    async fn export_emission_timeline_returns_emission_history() {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+        signals.set_last_emitted_value(ID.to_string(), String::from("42"));
+
+        let Json(result) = export_emission_timeline(State(signals)).await;
+        assert_eq!(result["signals"][0]["id"], ID);
+        assert_eq!(result["signals"][0]["emissions"][0]["value"], "42");
+    }
+
+    #[tokio::test]
+    async fn get_signal_resolution_returns_resolution_states() {
+        let resolution_states = Arc::new(ResolutionStates::new(HashMap::from([
+            (ID.to_string(), ResolutionState::Resolved),
+            (
+                String::from("Vehicle.Cabin.Temperature"),
+                ResolutionState::Failed {
+                    reason: ResolutionFailure::EntityNotFound,
+                    attempts: 2,
+                    next_attempt: tokio::time::Instant::now(),
+                },
+            ),
+        ])));
+
+        let Json(result) = get_signal_resolution(State(resolution_states)).await;
+        let result = serde_json::to_value(result).unwrap();
+        assert_eq!(result[ID]["state"], "resolved");
+        assert_eq!(result["Vehicle.Cabin.Temperature"]["state"], "failed");
+        assert_eq!(
+            result["Vehicle.Cabin.Temperature"]["reason"]["kind"],
+            "entity_not_found"
+        );
+        assert_eq!(result["Vehicle.Cabin.Temperature"]["attempts"], 2);
+        assert_eq!(result["Vehicle.Cabin.Temperature"]["retry_in_ms"], 0);
+    }
+
+    #[tokio::test]
+    async fn trigger_flight_recorder_starts_one_capture_at_a_time() {
+        let recorder = Arc::new(FlightRecorder::new(
+            FlightRecorderConfig {
+                signal_ids: HashSet::from([ID.to_string()]),
+                capacity: 10,
+                pre_trigger_ms: 1000,
+                post_trigger_ms: 1000,
+            },
+            Arc::new(ManualClock::default()),
+        ));
+
+        let result = trigger_flight_recorder(State(Some(recorder.clone()))).await;
+        assert_eq!(result, StatusCode::ACCEPTED);
+
+        let result = trigger_flight_recorder(State(Some(recorder))).await;
+        assert_eq!(result, StatusCode::CONFLICT);
+
+        let result = trigger_flight_recorder(State(None)).await;
+        assert_eq!(result, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn list_mapping_changes_returns_recorded_changes() {
+        let mapping_changes = Arc::new(MappingChangeLog::default());
+        mapping_changes.record(create_mapping_diff());
+
+        let Json(result) = list_mapping_changes(State(mapping_changes)).await;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].added, vec![ID.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn get_data_adapter_info_returns_info_or_not_found() {
+        let info = DataAdapterInfo {
+            adapter_kind: String::from("HttpDataAdapter"),
+            endpoint: String::from("http://provider"), // Devskim: ignore DS137138
+            protocol: String::from("http"),
+            registered_at: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        let mut data_adapter_selector = MockDataAdapterSelector::new();
+        let expected = info.clone();
+        data_adapter_selector.expect_get_adapter_info().returning(
+            move |entity_id| match entity_id {
+                ID => Ok(expected.clone()),
+                _ => Err(DataAdapterSelectorErrorKind::EntityNotFound.into()),
+            },
+        );
+        let data_adapter_selector: SharedDataAdapterSelector =
+            Arc::new(Mutex::new(data_adapter_selector));
+
+        let Json(result) =
+            get_data_adapter_info(State(data_adapter_selector.clone()), Path(ID.to_string()))
+                .await
+                .unwrap();
+        assert_eq!(result, info);
+
+        let result =
+            get_data_adapter_info(State(data_adapter_selector), Path(String::from("unknown")))
+                .await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn mapping_change_events_forwards_changes_until_cancelled() {
+        let mapping_changes = MappingChangeLog::default();
+        let cancellation_token = CancellationToken::new();
+        let mut events = mapping_change_events(&mapping_changes, cancellation_token.clone());
+
+        mapping_changes.record(create_mapping_diff());
+        assert!(matches!(events.next().await, Some(Ok(_))));
+
+        cancellation_token.cancel();
+        assert!(events.next().await.is_none());
+    }
+
+    #[test]
+    fn start_returns_err_for_invalid_address() {
+        let result = start(
+            "not an address",
+            Arc::new(SignalStore::new()),
+            None,
+            Arc::new(MappingChangeLog::default()),
+            Arc::new(ResolutionStates::default()),
+            Arc::new(RegistrationMetrics::default()),
+            Arc::new(Mutex::new(MockDataAdapterSelector::new())),
+            CancellationToken::new(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use log::info;
+use serde::Serialize;
+use serde_json::Value;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+
+use freyja_common::digital_twin_map_entry::DigitalTwinMapEntry;
+
+/// The number of recent mapping changes which are kept
+const MAPPING_CHANGE_HISTORY_SIZE: usize = 32;
+
+/// The number of mapping changes which are buffered for subscribers before the slowest ones start missing changes
+const MAPPING_CHANGE_BUFFER_SIZE: usize = 16;
+
+/// The differences between two mappings.
+/// Entries are listed in order of their ids, so that the same change is always reported the same way
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MappingDiff {
+    /// The sequence number of the change, which increases by one with each recorded change.
+    /// This is 0 until the change is recorded
+    pub sequence: u64,
+
+    /// The time at which the new mapping was applied
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+
+    /// The ids of the entries which are only in the new mapping
+    pub added: Vec<String>,
+
+    /// The ids of the entries which are only in the previous mapping
+    pub removed: Vec<String>,
+
+    /// The entries which are in both mappings but differ
+    pub changed: Vec<MappingEntryChange>,
+}
+
+/// The differences between two versions of a mapping entry
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MappingEntryChange {
+    /// The id of the entry
+    pub id: String,
+
+    /// The names of the fields which differ, in alphabetical order, or `*` if the fields couldn't be compared
+    pub fields: Vec<String>,
+
+    /// The previous and new emission interval, if it differs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_ms: Option<IntervalChange>,
+}
+
+/// A change to an entry's emission interval
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct IntervalChange {
+    /// The previous interval
+    pub from: u64,
+
+    /// The new interval
+    pub to: u64,
+}
+
+impl MappingDiff {
+    /// Computes the differences between two mappings
+    ///
+    /// # Arguments
+    /// - `previous`: the previous mapping
+    /// - `current`: the new mapping
+    /// - `timestamp`: the time at which the new mapping was applied
+    pub fn between(
+        previous: &HashMap<String, DigitalTwinMapEntry>,
+        current: &HashMap<String, DigitalTwinMapEntry>,
+        timestamp: OffsetDateTime,
+    ) -> Self {
+        let ids: BTreeSet<&String> = previous.keys().chain(current.keys()).collect();
+        let mut diff = Self {
+            sequence: 0,
+            timestamp,
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+
+        for id in ids {
+            match (previous.get(id), current.get(id)) {
+                (None, Some(_)) => diff.added.push(id.clone()),
+                (Some(_), None) => diff.removed.push(id.clone()),
+                (Some(previous_entry), Some(current_entry)) => {
+                    let fields = changed_fields(previous_entry, current_entry);
+                    if !fields.is_empty() {
+                        diff.changed.push(MappingEntryChange {
+                            id: id.clone(),
+                            fields,
+                            interval_ms: (previous_entry.interval_ms != current_entry.interval_ms)
+                                .then_some(IntervalChange {
+                                    from: previous_entry.interval_ms,
+                                    to: current_entry.interval_ms,
+                                }),
+                        });
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        diff
+    }
+
+    /// Returns true if the mappings are the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Logs a summary of the differences followed by each added, removed, and changed entry
+    pub fn log(&self) {
+        info!(
+            "Mapping changed: {} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        );
+
+        for id in self.added.iter() {
+            info!("Mapping entry {id} was added");
+        }
+
+        for id in self.removed.iter() {
+            info!("Mapping entry {id} was removed");
+        }
+
+        for change in self.changed.iter() {
+            match change.interval_ms {
+                Some(IntervalChange { from, to }) => info!(
+                    "Mapping entry {} changed {:?}, with its interval changing from {from}ms to {to}ms",
+                    change.id, change.fields
+                ),
+                None => info!("Mapping entry {} changed {:?}", change.id, change.fields),
+            }
+        }
+    }
+}
+
+/// Gets the names of the fields which differ between two versions of a mapping entry, in alphabetical order
+///
+/// # Arguments
+/// - `previous`: the previous version of the entry
+/// - `current`: the new version of the entry
+fn changed_fields(previous: &DigitalTwinMapEntry, current: &DigitalTwinMapEntry) -> Vec<String> {
+    // Comparing the serialized entries field by field covers every field without listing them here
+    match (
+        serde_json::to_value(previous),
+        serde_json::to_value(current),
+    ) {
+        (Ok(Value::Object(previous)), Ok(Value::Object(current))) => {
+            let names: BTreeSet<&String> = previous.keys().chain(current.keys()).collect();
+            names
+                .into_iter()
+                .filter(|name| previous.get(*name) != current.get(*name))
+                .cloned()
+                .collect()
+        }
+        // Entries which can't be serialized, such as ones with out-of-range timestamps, are compared as a whole
+        _ if format!("{previous:?}") != format!("{current:?}") => vec![String::from("*")],
+        _ => Vec::new(),
+    }
+}
+
+/// Keeps the recent changes to the mapping and notifies subscribers of new ones
+pub(crate) struct MappingChangeLog {
+    /// The recent changes, from oldest to newest
+    history: Mutex<VecDeque<MappingDiff>>,
+
+    /// The sender for new changes, which is used to subscribe to them
+    changes: broadcast::Sender<MappingDiff>,
+}
+
+impl Default for MappingChangeLog {
+    fn default() -> Self {
+        let (changes, _) = broadcast::channel(MAPPING_CHANGE_BUFFER_SIZE);
+        Self {
+            history: Mutex::new(VecDeque::with_capacity(MAPPING_CHANGE_HISTORY_SIZE)),
+            changes,
+        }
+    }
+}
+
+impl MappingChangeLog {
+    /// Assigns the next sequence number to a change, records it, and notifies the subscribers.
+    /// The oldest change is dropped once the history is full
+    ///
+    /// # Arguments
+    /// - `diff`: the change to record
+    pub fn record(&self, mut diff: MappingDiff) {
+        let mut history = self.history.lock().unwrap();
+        diff.sequence = history.back().map_or(1, |last| last.sequence + 1);
+
+        if history.len() == MAPPING_CHANGE_HISTORY_SIZE {
+            history.pop_front();
+        }
+        history.push_back(diff.clone());
+
+        // Sending only fails if there are no subscribers, in which case there's nobody to notify
+        let _ = self.changes.send(diff);
+    }
+
+    /// Gets the recent changes, from oldest to newest
+    pub fn recent(&self) -> Vec<MappingDiff> {
+        self.history.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Subscribes to the changes recorded from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<MappingDiff> {
+        self.changes.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod mapping_diff_tests {
+    use super::*;
+
+    fn create_mapping(entries: &[(&str, u64)]) -> HashMap<String, DigitalTwinMapEntry> {
+        entries
+            .iter()
+            .map(|(id, interval_ms)| {
+                (
+                    id.to_string(),
+                    DigitalTwinMapEntry {
+                        source: id.to_string(),
+                        interval_ms: *interval_ms,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn between_lists_added_removed_and_changed_entries_in_order() {
+        let previous = create_mapping(&[("b", 1000), ("d", 1000), ("e", 1000), ("c", 1000)]);
+        let mut current = create_mapping(&[("a", 1000), ("f", 1000), ("d", 500), ("e", 1000)]);
+        current.get_mut("e").unwrap().emit_on_change = true;
+
+        let diff = MappingDiff::between(&previous, &current, OffsetDateTime::UNIX_EPOCH);
+
+        assert_eq!(diff.added, vec![String::from("a"), String::from("f")]);
+        assert_eq!(diff.removed, vec![String::from("b"), String::from("c")]);
+        assert_eq!(
+            diff.changed,
+            vec![
+                MappingEntryChange {
+                    id: String::from("d"),
+                    fields: vec![String::from("interval_ms")],
+                    interval_ms: Some(IntervalChange {
+                        from: 1000,
+                        to: 500
+                    }),
+                },
+                MappingEntryChange {
+                    id: String::from("e"),
+                    fields: vec![String::from("emit_on_change")],
+                    interval_ms: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn between_is_empty_for_same_mapping() {
+        let mapping = create_mapping(&[("a", 1000), ("b", 500)]);
+        assert!(MappingDiff::between(&mapping, &mapping, OffsetDateTime::UNIX_EPOCH).is_empty());
+    }
+
+    #[tokio::test]
+    async fn record_assigns_sequence_and_notifies_subscribers() {
+        let uut = MappingChangeLog::default();
+        let mut subscription = uut.subscribe();
+
+        for i in 0..MAPPING_CHANGE_HISTORY_SIZE + 1 {
+            let current = create_mapping(&[("a", i as u64)]);
+            uut.record(MappingDiff::between(
+                &HashMap::new(),
+                &current,
+                OffsetDateTime::UNIX_EPOCH,
+            ));
+        }
+
+        let recent = uut.recent();
+        assert_eq!(recent.len(), MAPPING_CHANGE_HISTORY_SIZE);
+        assert_eq!(recent.first().unwrap().sequence, 2);
+        assert_eq!(
+            recent.last().unwrap().sequence,
+            MAPPING_CHANGE_HISTORY_SIZE as u64 + 1
+        );
+
+        // The subscription lags behind, so it only receives the most recent changes
+        let mut received = Vec::new();
+        loop {
+            match subscription.try_recv() {
+                Ok(diff) => received.push(diff.sequence),
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+        assert_eq!(received.len(), MAPPING_CHANGE_BUFFER_SIZE);
+        assert_eq!(
+            received.last(),
+            Some(&(MAPPING_CHANGE_HISTORY_SIZE as u64 + 1))
+        );
+    }
+}
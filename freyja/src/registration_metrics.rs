@@ -0,0 +1,153 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::Serialize;
+
+/// The outcomes of registering entities with data adapters
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RegistrationMetricsSnapshot {
+    /// The registrations of each kind of data adapter, keyed by adapter kind
+    pub adapters: BTreeMap<String, AdapterRegistrationMetrics>,
+
+    /// The number of entity endpoints which no data adapter factory supports, keyed by protocol.
+    /// This includes endpoints whose protocol is supported but whose operations are not.
+    /// Each endpoint of an entity is only counted once, even if the entity is registered again
+    pub rejected_endpoints: BTreeMap<String, u64>,
+
+    /// The number of entities which could not be registered with any data adapter
+    pub failed_registrations: u64,
+}
+
+/// The registrations of one kind of data adapter
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AdapterRegistrationMetrics {
+    /// The number of entities registered with adapters of this kind
+    pub registered: u64,
+
+    /// The average time taken to register an entity, including creating and starting the adapter if needed
+    pub average_latency_ms: f64,
+
+    /// The longest time taken to register an entity
+    pub max_latency_ms: u64,
+
+    /// The total time taken to register every entity, which the average is computed from
+    #[serde(skip)]
+    total_latency: Duration,
+}
+
+/// Records the outcomes of registering entities with data adapters,
+/// so that mismatches between the mapping and the available providers are visible
+#[derive(Default)]
+pub(crate) struct RegistrationMetrics {
+    /// The metrics recorded so far
+    snapshot: Mutex<RegistrationMetricsSnapshot>,
+
+    /// The entity ids and endpoint uris and protocols of the rejected endpoints which have been counted
+    rejected_endpoints: Mutex<HashSet<(String, String, String)>>,
+}
+
+impl RegistrationMetrics {
+    /// Records an entity which was registered
+    ///
+    /// # Arguments
+    /// - `adapter_kind`: the kind of adapter which registered the entity
+    /// - `latency`: the time taken to register the entity
+    pub fn record_registered(&self, adapter_kind: &str, latency: Duration) {
+        let mut snapshot = self.snapshot.lock().unwrap();
+        let metrics = snapshot
+            .adapters
+            .entry(adapter_kind.to_string())
+            .or_default();
+
+        metrics.registered += 1;
+        metrics.total_latency += latency;
+        metrics.average_latency_ms =
+            metrics.total_latency.as_secs_f64() * 1000.0 / metrics.registered as f64;
+        metrics.max_latency_ms = metrics.max_latency_ms.max(latency.as_millis() as u64);
+    }
+
+    /// Records an entity endpoint which no data adapter factory supports.
+    /// Endpoints which were already recorded for the entity aren't counted again,
+    /// since entities are registered again each time the mapping is synced.
+    ///
+    /// # Arguments
+    /// - `entity_id`: the id of the entity
+    /// - `protocol`: the endpoint's protocol
+    /// - `uri`: the endpoint's uri
+    pub fn record_rejected_endpoint(&self, entity_id: &str, protocol: &str, uri: &str) {
+        let is_new = self.rejected_endpoints.lock().unwrap().insert((
+            entity_id.to_string(),
+            protocol.to_string(),
+            uri.to_string(),
+        ));
+        if !is_new {
+            return;
+        }
+
+        *self
+            .snapshot
+            .lock()
+            .unwrap()
+            .rejected_endpoints
+            .entry(protocol.to_string())
+            .or_default() += 1;
+    }
+
+    /// Records an entity which could not be registered
+    pub fn record_failed_registration(&self) {
+        self.snapshot.lock().unwrap().failed_registrations += 1;
+    }
+
+    /// Gets the metrics recorded so far
+    pub fn snapshot(&self) -> RegistrationMetricsSnapshot {
+        self.snapshot.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod registration_metrics_tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_includes_recorded_outcomes() {
+        let uut = RegistrationMetrics::default();
+        uut.record_registered("Grpc", Duration::from_millis(10));
+        uut.record_registered("Grpc", Duration::from_millis(30));
+        uut.record_registered("Mqtt", Duration::from_millis(5));
+        uut.record_rejected_endpoint("a", "http", "http://a"); // Devskim: ignore DS137138
+        uut.record_rejected_endpoint("b", "http", "http://b"); // Devskim: ignore DS137138
+        uut.record_failed_registration();
+
+        let snapshot = uut.snapshot();
+        let grpc = &snapshot.adapters["Grpc"];
+        assert_eq!(grpc.registered, 2);
+        assert!((grpc.average_latency_ms - 20.0).abs() < 1e-9);
+        assert_eq!(grpc.max_latency_ms, 30);
+        assert_eq!(snapshot.adapters["Mqtt"].registered, 1);
+        assert_eq!(
+            snapshot.rejected_endpoints,
+            BTreeMap::from([(String::from("http"), 2)])
+        );
+        assert_eq!(snapshot.failed_registrations, 1);
+    }
+    #[test]
+    fn record_rejected_endpoint_counts_each_entity_endpoint_once() {
+        let uut = RegistrationMetrics::default();
+        for _ in 0..3 {
+            uut.record_rejected_endpoint("a", "http", "http://a"); // Devskim: ignore DS137138
+        }
+        uut.record_rejected_endpoint("a", "http", "http://other"); // Devskim: ignore DS137138
+
+        assert_eq!(
+            uut.snapshot().rejected_endpoints,
+            BTreeMap::from([(String::from("http"), 2)])
+        );
+    }
+}
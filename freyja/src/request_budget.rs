@@ -0,0 +1,146 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use crate::config::RequestBudgetConfig;
+
+/// A token bucket which limits how often requests can be sent to a provider.
+/// Tokens are refilled continuously, so admitted requests are spread out over time
+/// rather than being released all at once when a window resets.
+/// Requests can reserve tokens which haven't been refilled yet, so that they're sent in order once their tokens are available.
+pub struct RequestBudget {
+    /// The maximum number of tokens
+    capacity: f64,
+
+    /// The number of tokens currently available.
+    /// This is negative while tokens are reserved ahead of their refill
+    tokens: f64,
+
+    /// The number of tokens added per second
+    refill_per_second: f64,
+
+    /// The monotonic time of the clock when tokens were last refilled
+    last_refill: Duration,
+}
+
+impl RequestBudget {
+    /// Creates a new budget with a full bucket
+    ///
+    /// # Arguments
+    /// - `config`: the budget config
+    /// - `now`: the current monotonic time of the clock
+    pub fn new(config: &RequestBudgetConfig, now: Duration) -> Self {
+        let capacity = config.burst.max(1) as f64;
+
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_second: config.max_requests_per_second.max(0.0),
+            last_refill: now,
+        }
+    }
+
+    /// Takes the next token from the budget, even if it hasn't been refilled yet.
+    /// Returns how long the request must wait until its token is available, which is zero if a token is available now.
+    /// Returns `None` without taking a token if none is available and the budget never refills.
+    ///
+    /// # Arguments
+    /// - `now`: the current monotonic time of the clock
+    pub fn reserve(&mut self, now: Duration) -> Option<Duration> {
+        let elapsed = now.saturating_sub(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Some(Duration::ZERO)
+        } else if self.refill_per_second > 0.0 {
+            let wait = Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_second);
+            self.tokens -= 1.0;
+            Some(wait)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod request_budget_tests {
+    use super::*;
+
+    fn create_budget(max_requests_per_second: f64, burst: u32, now: Duration) -> RequestBudget {
+        RequestBudget::new(
+            &RequestBudgetConfig {
+                max_requests_per_second,
+                burst,
+            },
+            now,
+        )
+    }
+
+    #[test]
+    fn reserve_allows_burst_then_waits() {
+        let now = Duration::ZERO;
+        let mut uut = create_budget(1.0, 3, now);
+
+        for _ in 0..3 {
+            assert_eq!(uut.reserve(now), Some(Duration::ZERO));
+        }
+
+        assert_eq!(uut.reserve(now), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn reserve_refills_over_time() {
+        let now = Duration::ZERO;
+        let mut uut = create_budget(2.0, 1, now);
+
+        assert_eq!(uut.reserve(now), Some(Duration::ZERO));
+        assert_eq!(
+            uut.reserve(now + Duration::from_millis(250)),
+            Some(Duration::from_millis(250))
+        );
+
+        // The reserved token is only available to the request that reserved it
+        assert_eq!(
+            uut.reserve(now + Duration::from_millis(500)),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            uut.reserve(now + Duration::from_millis(1500)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn reserve_does_not_exceed_capacity() {
+        let now = Duration::ZERO;
+        let mut uut = create_budget(100.0, 2, now);
+
+        let later = now + Duration::from_secs(60);
+        assert_eq!(uut.reserve(later), Some(Duration::ZERO));
+        assert_eq!(uut.reserve(later), Some(Duration::ZERO));
+        assert_eq!(uut.reserve(later), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn reserve_returns_none_if_budget_never_refills() {
+        let now = Duration::ZERO;
+        let mut uut = create_budget(0.0, 1, now);
+
+        assert_eq!(uut.reserve(now), Some(Duration::ZERO));
+        assert_eq!(uut.reserve(now), None);
+        assert_eq!(uut.reserve(now + Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn zero_burst_allows_one_request() {
+        let now = Duration::ZERO;
+        let mut uut = create_budget(1.0, 0, now);
+
+        assert_eq!(uut.reserve(now), Some(Duration::ZERO));
+        assert_eq!(uut.reserve(now), Some(Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use freyja_common::cmd_utils::{ArgSpec, Args, ParseArgsError};
+
+/// The default time to wait before the first restart
+const DEFAULT_BACKOFF_MS: u64 = 1000;
+
+/// The default maximum time to wait before a restart
+const DEFAULT_MAX_BACKOFF_MS: u64 = 60000;
+
+/// The argument which enables the restart policy and sets the maximum number of restarts in a row
+pub(crate) const MAX_RESTARTS_ARG: ArgSpec = ArgSpec {
+    name: "restart-max",
+    value_name: Some("COUNT"),
+    help: "Restarts components which terminate with an error, at most this many times in a row. \
+        Without this, Freyja stops when a component terminates with an error",
+};
+
+/// The argument for the time to wait before the first restart
+pub(crate) const BACKOFF_ARG: ArgSpec = ArgSpec {
+    name: "restart-backoff-ms",
+    value_name: Some("MS"),
+    help: "The time to wait before the first restart. Defaults to 1000",
+};
+
+/// The argument for the maximum time to wait before a restart
+pub(crate) const MAX_BACKOFF_ARG: ArgSpec = ArgSpec {
+    name: "restart-max-backoff-ms",
+    value_name: Some("MS"),
+    help: "The maximum time to wait before a restart. Defaults to 60000",
+};
+
+/// Describes how components which terminate with an error are restarted.
+/// The time to wait before each restart doubles up to a maximum.
+/// When a component fails more than the maximum number of times in a row, the failure is escalated and Freyja stops.
+/// A run which lasts longer than the maximum backoff is considered successful, so the next failure starts a new series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// The maximum number of times in a row that a component is restarted
+    max_restarts: u32,
+
+    /// The time to wait before the first restart
+    initial_backoff: Duration,
+
+    /// The maximum time to wait before a restart
+    max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    /// Gets the restart policy from the command line arguments.
+    /// Returns `None` if the `--restart-max` argument is not present,
+    /// in which case Freyja stops when a component terminates with an error.
+    ///
+    /// # Arguments
+    /// - `args`: the parsed command line arguments
+    pub fn from_args(args: &Args) -> Result<Option<Self>, ParseArgsError> {
+        let max_restarts = match args.parse_value(MAX_RESTARTS_ARG.name)? {
+            Some(max_restarts) => max_restarts,
+            None => return Ok(None),
+        };
+
+        let initial_backoff = args
+            .parse_value(BACKOFF_ARG.name)?
+            .unwrap_or(DEFAULT_BACKOFF_MS);
+        let max_backoff = args
+            .parse_value(MAX_BACKOFF_ARG.name)?
+            .unwrap_or(DEFAULT_MAX_BACKOFF_MS);
+
+        Ok(Some(Self {
+            max_restarts,
+            initial_backoff: Duration::from_millis(initial_backoff),
+            max_backoff: Duration::from_millis(max_backoff.max(initial_backoff)),
+        }))
+    }
+
+    /// Gets the time to wait before restarting a component which failed,
+    /// or `None` if the failure should be escalated instead.
+    ///
+    /// # Arguments
+    /// - `restarts`: the number of times in a row that the component has already been restarted
+    pub fn backoff(&self, restarts: u32) -> Option<Duration> {
+        if restarts >= self.max_restarts {
+            return None;
+        }
+
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(restarts));
+
+        Some(backoff.min(self.max_backoff))
+    }
+
+    /// Returns true if a run lasted long enough that its failure starts a new series of restarts
+    ///
+    /// # Arguments
+    /// - `run_duration`: how long the run lasted
+    pub fn is_stable(&self, run_duration: Duration) -> bool {
+        run_duration > self.max_backoff
+    }
+}
+
+#[cfg(test)]
+mod restart_policy_tests {
+    use super::*;
+
+    use freyja_common::cmd_utils::ParseArgsErrorKind;
+
+    use crate::cli::FREYJA_COMMAND;
+
+    fn create_args(args: &[(ArgSpec, &str)]) -> Args {
+        let args: Vec<String> = ["freyja".to_string()]
+            .into_iter()
+            .chain(
+                args.iter()
+                    .map(|(arg, val)| format!("--{}={val}", arg.name)),
+            )
+            .collect();
+
+        FREYJA_COMMAND.parse(args.into_iter()).unwrap()
+    }
+
+    #[test]
+    fn from_args_returns_none_without_max_restarts() {
+        let args = create_args(&[(BACKOFF_ARG, "100")]);
+
+        let result = RestartPolicy::from_args(&args);
+
+        assert!(result.is_ok_and(|p| p.is_none()));
+    }
+
+    #[test]
+    fn from_args_uses_defaults() {
+        let args = create_args(&[(MAX_RESTARTS_ARG, "3")]);
+
+        let result = RestartPolicy::from_args(&args).unwrap();
+
+        assert_eq!(
+            result,
+            Some(RestartPolicy {
+                max_restarts: 3,
+                initial_backoff: Duration::from_millis(DEFAULT_BACKOFF_MS),
+                max_backoff: Duration::from_millis(DEFAULT_MAX_BACKOFF_MS),
+            })
+        );
+    }
+
+    #[test]
+    fn from_args_returns_err_for_invalid_values() {
+        let args = create_args(&[(MAX_RESTARTS_ARG, "3"), (BACKOFF_ARG, "soon")]);
+        let result = RestartPolicy::from_args(&args);
+        assert!(result.is_err_and(|e| matches!(e.kind(), ParseArgsErrorKind::ParseError { .. })));
+
+        let args = create_args(&[(MAX_RESTARTS_ARG, "-1")]);
+        let result = RestartPolicy::from_args(&args);
+        assert!(result.is_err_and(|e| matches!(e.kind(), ParseArgsErrorKind::ParseError { .. })));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max_then_escalates() {
+        let uut = RestartPolicy {
+            max_restarts: 4,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        };
+
+        assert_eq!(uut.backoff(0), Some(Duration::from_millis(100)));
+        assert_eq!(uut.backoff(1), Some(Duration::from_millis(200)));
+        assert_eq!(uut.backoff(2), Some(Duration::from_millis(300)));
+        assert_eq!(uut.backoff(3), Some(Duration::from_millis(300)));
+        assert_eq!(uut.backoff(4), None);
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex as StdMutex},
+};
+
+use async_trait::async_trait;
+use log::info;
+use tokio::sync::Mutex;
+
+use freyja_common::{
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterFactory, EntityRegistration},
+    data_adapter_selector::{DataAdapterSelector, DataAdapterSelectorError},
+    entity::{Entity, EntityEndpoint, Operation, Protocol},
+    signal::{EmissionPolicy, SignalPatch, Target},
+    signal_store::SignalStore,
+};
+
+use crate::config::SelfTestConfig;
+
+/// The id of the self-test signal
+pub const PIPELINE_OK_SIGNAL_ID: &str = "pipeline_ok";
+
+/// The uri of the loopback provider, which only exists inside Freyja
+const LOOPBACK_PROVIDER_URI: &str = "loopback://freyja";
+
+/// A data adapter which serves entities from inside Freyja rather than from a provider.
+/// Every requested value is the current time in seconds since the Unix epoch according to the signal store's clock,
+/// so each emission of a loopback signal shows that the store, the emitter, and the cloud adapters are working.
+pub struct LoopbackDataAdapter {
+    /// The shared signal store
+    signals: Arc<SignalStore>,
+
+    /// The ids of the entities registered with this adapter
+    entity_ids: StdMutex<HashSet<String>>,
+}
+
+#[async_trait]
+impl DataAdapter for LoopbackDataAdapter {
+    /// Creates a data adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri, which is ignored since there's no provider
+    /// - `signals`: the shared signal store
+    fn create_new(_provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            signals,
+            entity_ids: StdMutex::new(HashSet::new()),
+        })
+    }
+
+    /// Starts a data adapter
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        info!("Started a LoopbackDataAdapter!");
+        Ok(())
+    }
+
+    /// Publishes the current time as the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        if !self.entity_ids.lock().unwrap().contains(entity_id) {
+            return Err(DataAdapterError::entity_not_found(format!(
+                "Entity {entity_id} is not registered with the loopback adapter"
+            )));
+        }
+
+        let value = self.signals.clock().now().unix_timestamp().to_string();
+        self.signals
+            .set_value(entity_id.to_string(), value)
+            .map(|_| ())
+            .ok_or_else(|| {
+                DataAdapterError::entity_not_found(format!(
+                    "Signal {entity_id} is not in the signal store"
+                ))
+            })
+    }
+
+    /// Registers an entity id with this adapter
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that this entity supports
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        if !endpoint.supports_operation(&Operation::Get) {
+            return Err(DataAdapterError::operation_not_supported(format!(
+                "The loopback adapter can't serve entity {entity_id} without the Get operation"
+            )));
+        }
+
+        self.entity_ids
+            .lock()
+            .unwrap()
+            .insert(entity_id.to_string());
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+/// Factory for creating LoopbackDataAdapters
+#[derive(DataAdapterFactory)]
+#[data_adapter_factory(
+    adapter = LoopbackDataAdapter,
+    protocols = [Protocol::Loopback],
+    operations = [Operation::Get]
+)]
+pub struct LoopbackDataAdapterFactory {}
+
+/// Creates the mock entity which the loopback adapter serves for the self-test signal
+fn create_entity() -> Entity {
+    Entity {
+        id: PIPELINE_OK_SIGNAL_ID.to_string(),
+        name: Some(PIPELINE_OK_SIGNAL_ID.to_string()),
+        description: Some(String::from(
+            "The time at which Freyja last requested its self-test signal",
+        )),
+        endpoints: vec![EntityEndpoint {
+            protocol: Protocol::Loopback.to_string(),
+            operations: vec![Operation::Get.to_string()],
+            uri: LOOPBACK_PROVIDER_URI.to_string(),
+            context: PIPELINE_OK_SIGNAL_ID.to_string(),
+            ..Default::default()
+        }],
+    }
+}
+
+/// Registers the self-test signal with the loopback adapter and adds it to the signal store.
+/// The signal is pinned in the store so that it's kept when the mapping changes,
+/// and its first value is requested immediately so that it's emitted without waiting for the emitter.
+/// The data adapter selector must have a `LoopbackDataAdapterFactory`.
+///
+/// # Arguments
+/// - `config`: the self-test config
+/// - `signals`: the shared signal store
+/// - `data_adapter_selector`: the data adapter selector
+pub(crate) async fn register<TDataAdapterSelector: DataAdapterSelector>(
+    config: &SelfTestConfig,
+    signals: &SignalStore,
+    data_adapter_selector: &Mutex<TDataAdapterSelector>,
+) -> Result<(), DataAdapterSelectorError> {
+    let patch = SignalPatch {
+        id: PIPELINE_OK_SIGNAL_ID.to_string(),
+        source: create_entity(),
+        target: Target {
+            metadata: config.metadata.clone(),
+            tenant: None,
+        },
+        emission_policy: EmissionPolicy {
+            interval_ms: config.interval_ms,
+            ..Default::default()
+        },
+    };
+
+    let data_adapter_selector = data_adapter_selector.lock().await;
+    data_adapter_selector
+        .create_or_update_adapter(&patch.source, &patch.emission_policy)
+        .await?;
+
+    signals.add_pinned([patch].into_iter());
+    data_adapter_selector
+        .request_entity_value(PIPELINE_OK_SIGNAL_ID)
+        .await?;
+
+    info!(
+        "Registered self-test signal {PIPELINE_OK_SIGNAL_ID}, which is emitted every {}ms",
+        config.interval_ms
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod self_test_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use freyja_common::clock::{Clock, ManualClock};
+    use time::OffsetDateTime;
+
+    use crate::{
+        config::ProviderRequestBudgets, data_adapter_selector_impl::DataAdapterSelectorImpl,
+    };
+
+    #[test]
+    fn factory_supports_self_test_entity() {
+        let uut = LoopbackDataAdapterFactory::create_new().unwrap();
+        assert!(uut.is_supported(&create_entity()).is_some());
+
+        let mut entity = create_entity();
+        entity.endpoints[0].protocol = Protocol::InMemory.to_string();
+        assert!(uut.is_supported(&entity).is_none());
+    }
+
+    #[tokio::test]
+    async fn register_adds_pinned_signal_with_value() {
+        let now = OffsetDateTime::from_unix_timestamp(1_704_164_645).unwrap();
+        let clock: Arc<dyn Clock> = Arc::new(ManualClock::new(now));
+        let signals = Arc::new(SignalStore::with_clock(clock));
+        let mut data_adapter_selector =
+            DataAdapterSelectorImpl::new(signals.clone(), ProviderRequestBudgets::default(), None);
+        data_adapter_selector
+            .register(Box::new(LoopbackDataAdapterFactory::create_new().unwrap()))
+            .unwrap();
+        let data_adapter_selector = Mutex::new(data_adapter_selector);
+
+        let config = SelfTestConfig {
+            interval_ms: 1000,
+            metadata: HashMap::from([(String::from("name"), String::from("self-test"))]),
+        };
+        register(&config, &signals, &data_adapter_selector)
+            .await
+            .unwrap();
+
+        // The signal survives mapping changes which don't include it
+        signals.sync(std::iter::empty::<SignalPatch>());
+
+        let signal = signals.get(&PIPELINE_OK_SIGNAL_ID.to_string()).unwrap();
+        assert_eq!(signal.target.metadata, config.metadata);
+        assert_eq!(signal.emission.policy.interval_ms, 1000);
+        assert_eq!(signal.value, Some(now.unix_timestamp().to_string().into()));
+    }
+}
@@ -2,6 +2,8 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 
 use freyja_common::{
@@ -11,9 +13,14 @@ use freyja_common::{
     service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
 };
 
+use crate::service_uri_overrides::ServiceUriOverrides;
+
 /// Selector for `ServiceDiscoveryAdapters`
 pub struct ServiceDiscoveryAdapterSelectorImpl {
     adapters: Vec<Box<dyn ServiceDiscoveryAdapter + Send + Sync>>,
+
+    /// The URIs which services are pinned to ahead of every adapter, if any
+    overrides: Option<ServiceUriOverrides>,
 }
 
 impl ServiceDiscoveryAdapterSelectorImpl {
@@ -21,8 +28,28 @@ impl ServiceDiscoveryAdapterSelectorImpl {
     pub fn new() -> Self {
         Self {
             adapters: Vec::new(),
+            overrides: None,
         }
     }
+
+    /// Pins services to URIs which are returned instead of consulting the registered adapters
+    ///
+    /// # Arguments
+    /// - `overrides`: the overrides to use
+    pub(crate) fn set_overrides(&mut self, overrides: ServiceUriOverrides) {
+        self.overrides = Some(overrides);
+    }
+
+    /// Gets the URI that a service is pinned to, if any
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    fn get_override(&self, id: &str) -> Option<String> {
+        let uri = self.overrides.as_ref()?.get(id)?;
+        log::info!("Using override uri {uri} for service {id}");
+
+        Some(uri)
+    }
 }
 
 #[async_trait]
@@ -41,7 +68,8 @@ impl ServiceDiscoveryAdapterSelector for ServiceDiscoveryAdapterSelectorImpl {
     }
 
     /// Gets the URI for the requested service.
-    /// Adapters will be checked in registration order, and the first successful result will be returned.
+    /// If the service is pinned to a URI by the overrides, that URI is returned without checking the adapters.
+    /// Otherwise, adapters will be checked in registration order, and the first successful result will be returned.
     /// If no adapters can successfully retrieve the URI, a `NotFound` error will be returned.
     ///
     /// # Arguments
@@ -50,6 +78,10 @@ impl ServiceDiscoveryAdapterSelector for ServiceDiscoveryAdapterSelectorImpl {
         &self,
         id: &'a str,
     ) -> Result<String, ServiceDiscoveryAdapterError> {
+        if let Some(uri) = self.get_override(id) {
+            return Ok(uri);
+        }
+
         for adapter in self.adapters.iter() {
             log::debug!(
                 "Attempting to discover uri for service {id} from adapter {}...",
@@ -68,4 +100,214 @@ impl ServiceDiscoveryAdapterSelector for ServiceDiscoveryAdapterSelectorImpl {
 
         Err(ServiceDiscoveryAdapterErrorKind::NotFound.into())
     }
+
+    /// Gets every URI that the registered adapters can discover for the requested service.
+    /// URIs are returned in adapter registration order without duplicates.
+    /// If the service is pinned to a URI by the overrides, only that URI is returned.
+    /// If no adapters can successfully retrieve a URI, a `NotFound` error will be returned.
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    async fn get_service_uri_candidates<'a>(
+        &self,
+        id: &'a str,
+    ) -> Result<Vec<String>, ServiceDiscoveryAdapterError> {
+        if let Some(uri) = self.get_override(id) {
+            return Ok(vec![uri]);
+        }
+
+        let mut uris = Vec::new();
+        for adapter in self.adapters.iter() {
+            match adapter.get_service_uri(id).await {
+                Ok(uri) if !uris.contains(&uri) => uris.push(uri),
+                Ok(_) => {}
+                Err(e) => log::debug!(
+                    "Adapter {} failed to discover service uri: {e:?}",
+                    adapter.get_adapter_name()
+                ),
+            }
+        }
+
+        if uris.is_empty() {
+            Err(ServiceDiscoveryAdapterErrorKind::NotFound.into())
+        } else {
+            Ok(uris)
+        }
+    }
+
+    /// Gets the URIs for several services at once, keyed by service identifier.
+    /// Services which are pinned to URIs by the overrides aren't discovered.
+    /// Adapters will be checked in registration order, and each adapter is only asked for
+    /// the services that earlier adapters could not discover.
+    /// Services which can't be discovered by any adapter are omitted from the result.
+    ///
+    /// # Arguments
+    /// - `ids`: the service identifiers
+    async fn get_service_uris<'a>(&self, ids: &[&'a str]) -> HashMap<String, String> {
+        let mut uris: HashMap<String, String> = ids
+            .iter()
+            .filter_map(|id| Some((id.to_string(), self.get_override(id)?)))
+            .collect();
+        let mut remaining_ids = ids.to_vec();
+        remaining_ids.retain(|id| !uris.contains_key(*id));
+        for adapter in self.adapters.iter() {
+            if remaining_ids.is_empty() {
+                break;
+            }
+
+            log::debug!(
+                "Attempting to discover uris for {} services from adapter {}...",
+                remaining_ids.len(),
+                adapter.get_adapter_name()
+            );
+            uris.extend(adapter.get_service_uris(&remaining_ids).await);
+            remaining_ids.retain(|id| !uris.contains_key(*id));
+        }
+
+        uris
+    }
+}
+
+#[cfg(test)]
+mod service_discovery_adapter_selector_impl_tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use freyja_common::clock::SystemClock;
+
+    /// A service discovery adapter which returns a fixed result.
+    /// If `known_ids` is set, only those ids can be discovered.
+    struct FixedServiceDiscoveryAdapter {
+        uri: Option<&'static str>,
+        known_ids: Option<Vec<&'static str>>,
+    }
+
+    #[async_trait]
+    impl ServiceDiscoveryAdapter for FixedServiceDiscoveryAdapter {
+        fn create_new() -> Result<Self, ServiceDiscoveryAdapterError> {
+            Ok(Self {
+                uri: None,
+                known_ids: None,
+            })
+        }
+
+        fn get_adapter_name(&self) -> String {
+            String::from("FixedServiceDiscoveryAdapter")
+        }
+
+        async fn get_service_uri<'a>(
+            &self,
+            id: &'a str,
+        ) -> Result<String, ServiceDiscoveryAdapterError> {
+            if self
+                .known_ids
+                .as_ref()
+                .is_some_and(|known_ids| known_ids.iter().all(|known_id| *known_id != id))
+            {
+                return Err(ServiceDiscoveryAdapterErrorKind::NotFound.into());
+            }
+
+            self.uri
+                .map(String::from)
+                .ok_or(ServiceDiscoveryAdapterErrorKind::NotFound.into())
+        }
+    }
+
+    fn create_selector(uris: &[Option<&'static str>]) -> ServiceDiscoveryAdapterSelectorImpl {
+        let mut uut = ServiceDiscoveryAdapterSelectorImpl::new();
+        for uri in uris {
+            uut.register(Box::new(FixedServiceDiscoveryAdapter {
+                uri: *uri,
+                known_ids: None,
+            }))
+            .unwrap();
+        }
+
+        uut
+    }
+
+    #[tokio::test]
+    async fn get_service_uri_returns_first_discovered_uri() {
+        let uut = create_selector(&[None, Some("http://a"), Some("http://b")]); // Devskim: ignore DS137138
+        assert_eq!(uut.get_service_uri("id").await.unwrap(), "http://a"); // Devskim: ignore DS137138
+    }
+
+    #[tokio::test]
+    async fn get_service_uri_candidates_returns_every_discovered_uri_in_order() {
+        let uut = create_selector(&[
+            Some("http://a"), // Devskim: ignore DS137138
+            None,
+            Some("http://b"), // Devskim: ignore DS137138
+            Some("http://a"), // Devskim: ignore DS137138
+        ]);
+
+        assert_eq!(
+            uut.get_service_uri_candidates("id").await.unwrap(),
+            vec!["http://a", "http://b"] // Devskim: ignore DS137138
+        );
+    }
+
+    #[tokio::test]
+    async fn get_service_uri_candidates_returns_err_when_nothing_is_discovered() {
+        let uut = create_selector(&[None]);
+        let result = uut.get_service_uri_candidates("id").await;
+        assert!(result.is_err_and(|e| e.kind() == ServiceDiscoveryAdapterErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn get_service_uris_asks_later_adapters_for_remaining_services() {
+        let mut uut = ServiceDiscoveryAdapterSelectorImpl::new();
+        uut.register(Box::new(FixedServiceDiscoveryAdapter {
+            uri: Some("http://a"), // Devskim: ignore DS137138
+            known_ids: Some(vec!["first"]),
+        }))
+        .unwrap();
+        uut.register(Box::new(FixedServiceDiscoveryAdapter {
+            uri: Some("http://b"), // Devskim: ignore DS137138
+            known_ids: None,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            uut.get_service_uris(&["first", "second"]).await,
+            HashMap::from([
+                (String::from("first"), String::from("http://a")), // Devskim: ignore DS137138
+                (String::from("second"), String::from("http://b")), // Devskim: ignore DS137138
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn overrides_take_precedence_over_adapters() {
+        let mut uut = create_selector(&[Some("http://a")]); // Devskim: ignore DS137138
+        uut.set_overrides(ServiceUriOverrides::new(
+            None,
+            Some(String::from("pinned=http://pinned")), // Devskim: ignore DS137138
+            Arc::new(SystemClock),
+        ));
+
+        assert_eq!(
+            uut.get_service_uri("pinned").await.unwrap(),
+            "http://pinned"
+        ); // Devskim: ignore DS137138
+        assert_eq!(uut.get_service_uri("other").await.unwrap(), "http://a"); // Devskim: ignore DS137138
+        assert_eq!(
+            uut.get_service_uri_candidates("pinned").await.unwrap(),
+            vec!["http://pinned"] // Devskim: ignore DS137138
+        );
+        assert_eq!(
+            uut.get_service_uris(&["pinned", "other"]).await,
+            HashMap::from([
+                (String::from("pinned"), String::from("http://pinned")), // Devskim: ignore DS137138
+                (String::from("other"), String::from("http://a")),       // Devskim: ignore DS137138
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn get_service_uris_omits_undiscovered_services() {
+        let uut = create_selector(&[None]);
+        assert!(uut.get_service_uris(&["id"]).await.is_empty());
+    }
 }
@@ -0,0 +1,282 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use log::{info, warn};
+
+use freyja_common::clock::Clock;
+
+/// The environment variable which pins services to URIs, as comma-separated `id=uri` pairs
+pub const SERVICE_URI_OVERRIDES_ENV: &str = "FREYJA_SERVICE_URI_OVERRIDES";
+
+/// The minimum interval between checks of the override file for changes
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The overrides loaded from the override file
+#[derive(Default)]
+struct LoadedOverrides {
+    /// A map of service ids to uris
+    uris: HashMap<String, String>,
+
+    /// When the override file was last modified and its size, or `None` if it doesn't exist
+    version: Option<(SystemTime, u64)>,
+
+    /// The monotonic time at which the override file was last checked for changes, or `None` if it never was
+    checked_at: Option<Duration>,
+}
+
+/// Pins services to URIs ahead of every service discovery adapter,
+/// so that a misbehaving discovery system can be bypassed without restarting Freyja.
+/// The override file is a JSON object which maps service ids to URIs.
+/// It's checked for changes at most once per `RELOAD_CHECK_INTERVAL` and reloaded whenever it changes,
+/// and its entries take precedence over the environment variable's.
+pub(crate) struct ServiceUriOverrides {
+    /// The path of the override file, if any
+    path: Option<PathBuf>,
+
+    /// The overrides from the environment variable
+    env_uris: HashMap<String, String>,
+
+    /// The overrides from the override file
+    loaded: RwLock<LoadedOverrides>,
+
+    /// The clock which is used to decide when to check the override file for changes
+    clock: Arc<dyn Clock>,
+}
+
+impl ServiceUriOverrides {
+    /// Creates a new ServiceUriOverrides
+    ///
+    /// # Arguments
+    /// - `path`: the path of the override file, if any. The file doesn't need to exist yet
+    /// - `env_value`: the value of the environment variable, if it's set
+    /// - `clock`: the clock which is used to decide when to check the override file for changes
+    pub fn new(path: Option<PathBuf>, env_value: Option<String>, clock: Arc<dyn Clock>) -> Self {
+        let env_uris = env_value
+            .as_deref()
+            .map(Self::parse_env_value)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            env_uris,
+            loaded: RwLock::new(LoadedOverrides::default()),
+            clock,
+        }
+    }
+
+    /// Creates a new ServiceUriOverrides which uses the overrides from the `FREYJA_SERVICE_URI_OVERRIDES` environment variable
+    ///
+    /// # Arguments
+    /// - `path`: the path of the override file from Freyja's config, if any
+    /// - `clock`: the clock which is used to decide when to check the override file for changes
+    pub fn from_env(path: Option<&str>, clock: Arc<dyn Clock>) -> Self {
+        Self::new(
+            path.map(PathBuf::from),
+            env::var(SERVICE_URI_OVERRIDES_ENV).ok(),
+            clock,
+        )
+    }
+
+    /// Parses comma-separated `id=uri` pairs. Pairs without a `=` are ignored
+    ///
+    /// # Arguments
+    /// - `value`: the value to parse
+    fn parse_env_value(value: &str) -> HashMap<String, String> {
+        value
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .filter_map(|pair| match pair.split_once('=') {
+                Some((id, uri)) => Some((id.trim().to_string(), uri.trim().to_string())),
+                None => {
+                    warn!("Ignoring invalid service uri override {pair:?} in {SERVICE_URI_OVERRIDES_ENV}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Reloads the override file if it was created, removed, or modified since it was last loaded.
+    /// The file is only checked if `RELOAD_CHECK_INTERVAL` has passed since the last check,
+    /// so that lookups don't access the file system each time.
+    /// If the file can't be read or parsed, the previous overrides are kept
+    fn reload_if_changed(&self) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let now = self.clock.monotonic_now();
+        {
+            let mut loaded = self.loaded.write().unwrap();
+            if loaded
+                .checked_at
+                .is_some_and(|checked_at| now.saturating_sub(checked_at) < RELOAD_CHECK_INTERVAL)
+            {
+                return;
+            }
+
+            loaded.checked_at = Some(now);
+        }
+
+        let version = fs::metadata(path)
+            .and_then(|m| Ok((m.modified()?, m.len())))
+            .ok();
+        if version == self.loaded.read().unwrap().version {
+            return;
+        }
+
+        let uris = match version {
+            None => HashMap::new(),
+            Some(_) => {
+                let result = fs::read_to_string(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|contents| {
+                        serde_json::from_str(&contents).map_err(|e| e.to_string())
+                    });
+                match result {
+                    Ok(uris) => uris,
+                    Err(e) => {
+                        warn!(
+                            "Failed to load service uri override file {}, using previous overrides: {e}",
+                            path.display()
+                        );
+                        return;
+                    }
+                }
+            }
+        };
+
+        info!(
+            "Reloaded service uri override file {}. Override count is now {}",
+            path.display(),
+            uris.len()
+        );
+        *self.loaded.write().unwrap() = LoadedOverrides {
+            uris,
+            version,
+            checked_at: Some(now),
+        };
+    }
+
+    /// Gets the URI that a service is pinned to, if any
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    pub fn get(&self, id: &str) -> Option<String> {
+        self.reload_if_changed();
+
+        self.loaded
+            .read()
+            .unwrap()
+            .uris
+            .get(id)
+            .or_else(|| self.env_uris.get(id))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod service_uri_overrides_tests {
+    use super::*;
+
+    use freyja_common::clock::ManualClock;
+    use freyja_test_common::fixtures::TempDirFixture;
+
+    /// An override file in a temp directory which is deleted when dropped
+    struct OverrideFile {
+        _directory: TempDirFixture,
+        path: PathBuf,
+    }
+
+    impl OverrideFile {
+        fn new(name: &str) -> Self {
+            let directory = TempDirFixture::new();
+            let path = directory.path.join(format!("{name}.json"));
+            Self {
+                _directory: directory,
+                path,
+            }
+        }
+
+        fn write(&self, contents: &str) {
+            fs::write(&self.path, contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn parse_env_value_ignores_invalid_pairs() {
+        assert_eq!(
+            ServiceUriOverrides::parse_env_value(" a = http://a ,b,, c=http://c=1"), // Devskim: ignore DS137138
+            HashMap::from([
+                (String::from("a"), String::from("http://a")), // Devskim: ignore DS137138
+                (String::from("c"), String::from("http://c=1")), // Devskim: ignore DS137138
+            ])
+        );
+    }
+
+    #[test]
+    fn get_prefers_file_over_env() {
+        let file = OverrideFile::new("service_uri_overrides_precedence");
+        file.write(r#"{"a": "http://file"}"#); // Devskim: ignore DS137138
+
+        let uut = ServiceUriOverrides::new(
+            Some(file.path.clone()),
+            Some(String::from("a=http://env,b=http://env")), // Devskim: ignore DS137138
+            Arc::new(ManualClock::default()),
+        );
+
+        assert_eq!(uut.get("a"), Some(String::from("http://file"))); // Devskim: ignore DS137138
+        assert_eq!(uut.get("b"), Some(String::from("http://env"))); // Devskim: ignore DS137138
+        assert_eq!(uut.get("c"), None);
+    }
+
+    #[test]
+    fn get_reloads_changed_file() {
+        let file = OverrideFile::new("service_uri_overrides_reload");
+        let clock = Arc::new(ManualClock::default());
+        let uut = ServiceUriOverrides::new(Some(file.path.clone()), None, clock.clone());
+        assert_eq!(uut.get("a"), None);
+
+        file.write(r#"{"a": "http://a"}"#); // Devskim: ignore DS137138
+        clock.advance(RELOAD_CHECK_INTERVAL);
+        assert_eq!(uut.get("a"), Some(String::from("http://a"))); // Devskim: ignore DS137138
+
+        // Invalid files are ignored until they're fixed.
+        // Each version of the file has a different size so that changes are detected even if the modification time is coarse
+        file.write("{");
+        clock.advance(RELOAD_CHECK_INTERVAL);
+        assert_eq!(uut.get("a"), Some(String::from("http://a"))); // Devskim: ignore DS137138
+
+        file.write(r#"{"a": "http://a2"}"#); // Devskim: ignore DS137138
+        clock.advance(RELOAD_CHECK_INTERVAL);
+        assert_eq!(uut.get("a"), Some(String::from("http://a2"))); // Devskim: ignore DS137138
+
+        fs::remove_file(&file.path).unwrap();
+        clock.advance(RELOAD_CHECK_INTERVAL);
+        assert_eq!(uut.get("a"), None);
+    }
+
+    #[test]
+    fn get_checks_file_at_most_once_per_interval() {
+        let file = OverrideFile::new("service_uri_overrides_interval");
+        let clock = Arc::new(ManualClock::default());
+        let uut = ServiceUriOverrides::new(Some(file.path.clone()), None, clock.clone());
+        assert_eq!(uut.get("a"), None);
+
+        file.write(r#"{"a": "http://a"}"#); // Devskim: ignore DS137138
+        clock.advance(RELOAD_CHECK_INTERVAL / 2);
+        assert_eq!(uut.get("a"), None);
+
+        clock.advance(RELOAD_CHECK_INTERVAL / 2);
+        assert_eq!(uut.get("a"), Some(String::from("http://a"))); // Devskim: ignore DS137138
+    }
+}
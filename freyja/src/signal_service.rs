@@ -0,0 +1,299 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashSet, sync::Arc};
+
+use log::{info, warn};
+use time::OffsetDateTime;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+use tonic::{transport::Server, Request, Response, Status};
+
+use freyja_common::{
+    signal_store::{SignalStore, SignalValueObserver},
+    uri,
+};
+use signal_service_proto::v1::{
+    signal_service_server::{SignalService, SignalServiceServer},
+    GetSignalRequest, GetSignalResponse, SignalValue, SubscribeSignalRequest,
+    SubscribeSignalResponse,
+};
+
+/// The number of value updates that are buffered for subscribers before the slowest ones start missing updates
+const UPDATE_BUFFER_SIZE: usize = 1024;
+
+/// The number of values that are buffered for each subscription while the subscriber receives them
+const SUBSCRIPTION_BUFFER_SIZE: usize = 64;
+
+/// Starts the signal service, which lets other on-board applications read the values that Freyja has already
+/// aggregated instead of each talking to the providers directly.
+/// The service runs until the cancellation token is cancelled.
+///
+/// # Arguments
+/// - `address`: the hosting address
+/// - `signals`: the shared signal store
+/// - `cancellation_token`: the token for stopping the service
+pub(crate) fn start(
+    address: &str,
+    signals: Arc<SignalStore>,
+    cancellation_token: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let addr = uri::parse_socket_address(address)?;
+
+    let service = SignalServiceImpl::new(signals, cancellation_token.clone());
+    let server_future = Server::builder()
+        .add_service(SignalServiceServer::new(service))
+        .serve_with_shutdown(addr, async move { cancellation_token.cancelled().await });
+
+    tokio::spawn(async move {
+        if let Err(e) = server_future.await {
+            warn!("Signal service terminated with error: {e}");
+        }
+    });
+
+    info!("Started the signal service on {addr}");
+
+    Ok(())
+}
+
+/// Publishes the values set in the signal store to the signal service's subscriptions
+struct SignalValuePublisher {
+    /// The sender for value updates
+    updates: broadcast::Sender<SignalValue>,
+}
+
+impl SignalValueObserver for SignalValuePublisher {
+    /// Publishes a value to the subscriptions
+    ///
+    /// # Arguments
+    /// - `id`: the id of the signal
+    /// - `value`: the new value of the signal
+    /// - `timestamp`: the time at which the value was set
+    fn on_value_set(&self, id: &str, value: &Arc<str>, timestamp: OffsetDateTime) {
+        // Sending only fails if there are no subscriptions, in which case there's nobody to notify
+        let _ = self.updates.send(SignalValue {
+            id: id.to_string(),
+            value: Some(value.to_string()),
+            timestamp: Some(signal_service_proto::prost_types::Timestamp {
+                seconds: timestamp.unix_timestamp(),
+                nanos: timestamp.nanosecond() as i32,
+            }),
+        });
+    }
+}
+
+/// Serves the current signal values from the signal store
+struct SignalServiceImpl {
+    /// The shared signal store
+    signals: Arc<SignalStore>,
+
+    /// The sender for value updates, which is used to subscribe to them
+    updates: broadcast::Sender<SignalValue>,
+
+    /// The token for stopping the service, which also ends the subscriptions
+    cancellation_token: CancellationToken,
+}
+
+impl SignalServiceImpl {
+    /// Creates a new SignalServiceImpl and starts observing the values set in the signal store
+    ///
+    /// # Arguments
+    /// - `signals`: the shared signal store
+    /// - `cancellation_token`: the token for stopping the service
+    fn new(signals: Arc<SignalStore>, cancellation_token: CancellationToken) -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_BUFFER_SIZE);
+        signals.add_value_observer(Arc::new(SignalValuePublisher {
+            updates: updates.clone(),
+        }));
+
+        Self {
+            signals,
+            updates,
+            cancellation_token,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl SignalService for SignalServiceImpl {
+    type SubscribeSignalStream = ReceiverStream<Result<SubscribeSignalResponse, Status>>;
+
+    /// Gets the current value of a signal
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn get_signal(
+        &self,
+        request: Request<GetSignalRequest>,
+    ) -> Result<Response<GetSignalResponse>, Status> {
+        let id = request.into_inner().id;
+        match self.signals.get(&id) {
+            Some(signal) => Ok(Response::new(GetSignalResponse {
+                signal: Some(signal.into()),
+            })),
+            None => Err(Status::not_found(format!("Signal {id} is not tracked"))),
+        }
+    }
+
+    /// Subscribes to the values of signals.
+    /// The current values of the signals are sent first, followed by every new value.
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn subscribe_signal(
+        &self,
+        request: Request<SubscribeSignalRequest>,
+    ) -> Result<Response<Self::SubscribeSignalStream>, Status> {
+        let ids: HashSet<String> = request.into_inner().ids.into_iter().collect();
+        let is_subscribed = move |signals: &SignalStore, id: &String| {
+            if ids.is_empty() {
+                signals.get(id).is_some()
+            } else {
+                ids.contains(id)
+            }
+        };
+
+        // Subscribe to updates before reading the current values so that no updates are missed in between
+        let mut updates = self.updates.subscribe();
+        let current_values: Vec<SignalValue> = self
+            .signals
+            .get_all()
+            .into_iter()
+            .filter(|signal| {
+                signal.value.is_some() && is_subscribed(self.signals.as_ref(), &signal.id)
+            })
+            .map(SignalValue::from)
+            .collect();
+
+        let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER_SIZE);
+        let signals = self.signals.clone();
+        let cancellation_token = self.cancellation_token.clone();
+        tokio::spawn(async move {
+            for signal in current_values {
+                let response = SubscribeSignalResponse {
+                    signal: Some(signal),
+                };
+                if sender.send(Ok(response)).await.is_err() {
+                    return;
+                }
+            }
+
+            loop {
+                let signal = tokio::select! {
+                    _ = cancellation_token.cancelled() => return,
+                    _ = sender.closed() => return,
+                    update = updates.recv() => match update {
+                        Ok(signal) => signal,
+                        Err(broadcast::error::RecvError::Lagged(count)) => {
+                            warn!("A signal service subscriber missed {count} updates");
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
+                };
+
+                if !is_subscribed(signals.as_ref(), &signal.id) {
+                    continue;
+                }
+
+                let response = SubscribeSignalResponse {
+                    signal: Some(signal),
+                };
+                if sender.send(Ok(response)).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+#[cfg(test)]
+mod signal_service_tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use tokio_stream::StreamExt;
+
+    use freyja_common::signal::SignalPatch;
+
+    const ID: &str = "vehicle.speed";
+
+    fn create_service() -> SignalServiceImpl {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        SignalServiceImpl::new(signals, CancellationToken::new())
+    }
+
+    #[tokio::test]
+    async fn get_signal_returns_current_value() {
+        let uut = create_service();
+        uut.signals.set_value(ID.to_string(), String::from("42"));
+
+        let response = uut
+            .get_signal(Request::new(GetSignalRequest { id: ID.to_string() }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let signal = response.signal.unwrap();
+        assert_eq!(signal.id, ID);
+        assert_eq!(signal.value, Some(String::from("42")));
+        assert!(signal.timestamp.is_some());
+    }
+
+    #[tokio::test]
+    async fn get_signal_returns_not_found_for_unknown_signal() {
+        let uut = create_service();
+
+        let result = uut
+            .get_signal(Request::new(GetSignalRequest {
+                id: String::from("unknown"),
+            }))
+            .await;
+
+        assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn subscribe_signal_sends_current_and_new_values() {
+        let uut = create_service();
+        uut.signals.set_value(ID.to_string(), String::from("42"));
+
+        let mut stream = uut
+            .subscribe_signal(Request::new(SubscribeSignalRequest {
+                ids: vec![ID.to_string()],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Values for other signals are not sent
+        uut.signals
+            .set_value(String::from("other"), String::from("1"));
+        uut.signals.set_value(ID.to_string(), String::from("43"));
+
+        for expected_value in ["42", "43"] {
+            let response = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                response.signal.unwrap().value,
+                Some(expected_value.to_string())
+            );
+        }
+    }
+}
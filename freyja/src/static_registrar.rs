@@ -0,0 +1,309 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{sync::Arc, time::Duration};
+
+use log::{info, warn};
+use tokio::{sync::Mutex, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use freyja_common::{
+    data_adapter_selector::DataAdapterSelector,
+    entity::Operation,
+    signal::{EmissionPolicy, SignalPatch, Target},
+    signal_store::SignalStore,
+};
+
+use crate::{
+    config::StaticSignal, crash_report, freyja_handle::FreyjaComponent, watchdog::Heartbeat,
+};
+
+impl From<StaticSignal> for SignalPatch {
+    fn from(value: StaticSignal) -> Self {
+        Self {
+            id: value.entity.id.clone(),
+            source: value.entity,
+            target: Target {
+                metadata: value.target,
+                tenant: value.tenant,
+            },
+            emission_policy: EmissionPolicy {
+                interval_ms: value.interval_ms,
+                emit_only_if_changed: value.emit_on_change,
+                conversion: value.conversion,
+                conversion_failure_policy: value.conversion_failure_policy,
+                decimal_places: value.decimal_places,
+                trigger: value.trigger,
+                adaptive_interval: value.adaptive_interval,
+                privacy: value.privacy,
+            },
+        }
+    }
+}
+
+/// Registers signals from Freyja's config directly, without the mapping and digital twin services.
+/// This takes the place of the cartographer for small fixed deployments and demos.
+pub struct StaticRegistrar<TDataAdapterSelector> {
+    /// The shared signal store
+    signals: Arc<SignalStore>,
+
+    /// The signals to register
+    patches: Vec<SignalPatch>,
+
+    /// The data adapter selector
+    data_adapter_selector: Arc<Mutex<TDataAdapterSelector>>,
+
+    /// The interval at which signals whose data adapters could not be created are retried
+    retry_interval: Duration,
+
+    /// The heartbeat which tracks the progress of registering signals
+    heartbeat: Arc<Heartbeat>,
+}
+
+impl<TDataAdapterSelector: DataAdapterSelector> StaticRegistrar<TDataAdapterSelector> {
+    /// Creates a new StaticRegistrar
+    ///
+    /// # Arguments
+    /// - `signals`: the shared signal store
+    /// - `static_signals`: the signals to register
+    /// - `data_adapter_selector`: the data adapter selector
+    /// - `retry_interval`: the interval at which failed signals are retried
+    pub fn new(
+        signals: Arc<SignalStore>,
+        static_signals: Vec<StaticSignal>,
+        data_adapter_selector: Arc<Mutex<TDataAdapterSelector>>,
+        retry_interval: Duration,
+    ) -> Self {
+        Self {
+            signals,
+            patches: static_signals.into_iter().map(SignalPatch::from).collect(),
+            data_adapter_selector,
+            retry_interval,
+            heartbeat: Arc::new(Heartbeat::default()),
+        }
+    }
+
+    /// Gets the heartbeat which tracks the progress of registering signals
+    pub fn heartbeat(&self) -> Arc<Heartbeat> {
+        self.heartbeat.clone()
+    }
+
+    /// Runs the registrar in a new task.
+    /// Signals are added to the signal store once a data adapter has been created for them,
+    /// and signals whose data adapters could not be created are retried until they succeed.
+    /// The task finishes when the cancellation token is cancelled.
+    ///
+    /// # Arguments
+    /// - `cancellation_token`: the token for stopping the registrar
+    pub fn run(
+        self: Arc<Self>,
+        cancellation_token: CancellationToken,
+    ) -> JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>
+    where
+        TDataAdapterSelector: Send + Sync + 'static,
+    {
+        // The registrar takes the place of the cartographer
+        tokio::spawn(crash_report::in_component(
+            FreyjaComponent::Cartographer,
+            async move { self.run_until_cancelled(cancellation_token).await },
+        ))
+    }
+
+    /// Registers the signals until they have all succeeded, then waits for the cancellation token to be cancelled
+    ///
+    /// # Arguments
+    /// - `cancellation_token`: the token for stopping the registrar
+    async fn run_until_cancelled(
+        &self,
+        cancellation_token: CancellationToken,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut pending = self.patches.clone();
+        loop {
+            self.heartbeat.beat();
+            pending = self.register(pending).await;
+            self.heartbeat.idle();
+
+            if pending.is_empty() {
+                info!("Registered {} static signals", self.patches.len());
+                cancellation_token.cancelled().await;
+                break;
+            }
+
+            tokio::select! {
+                biased;
+                _ = cancellation_token.cancelled() => break,
+                _ = tokio::time::sleep(self.retry_interval) => {}
+            }
+        }
+
+        info!("Static registrar stopped");
+        Ok(())
+    }
+
+    /// Creates or updates data adapters for the provided signals and adds the successful signals to the signal store.
+    /// Returns the signals which failed.
+    ///
+    /// # Arguments
+    /// - `patches`: the signals to register
+    async fn register(&self, patches: Vec<SignalPatch>) -> Vec<SignalPatch> {
+        let mut successes = Vec::new();
+        let mut failures = Vec::new();
+
+        {
+            let data_adapter_selector = self.data_adapter_selector.lock().await;
+            for patch in patches {
+                match data_adapter_selector
+                    .create_or_update_adapter(&patch.source, &patch.emission_policy)
+                    .await
+                {
+                    Ok(_) => successes.push(patch),
+                    Err(e) => {
+                        warn!(
+                            "Error creating data adapter for static signal {}: {e}",
+                            patch.id
+                        );
+                        failures.push(patch);
+                    }
+                }
+            }
+
+            // Request values for new signals so that they can be emitted without waiting for the emitter to request them
+            for patch in successes
+                .iter()
+                .filter(|patch| patch.source.supports_operation(&Operation::Get))
+            {
+                if let Err(e) = data_adapter_selector.request_entity_value(&patch.id).await {
+                    warn!(
+                        "Failed to request initial value for signal {}: {e}",
+                        patch.id
+                    );
+                }
+            }
+        }
+
+        self.signals.add(successes.into_iter());
+
+        failures
+    }
+}
+
+#[cfg(test)]
+mod static_registrar_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use freyja_common::{
+        data_adapter_selector::{DataAdapterSelectorError, DataAdapterSelectorErrorKind},
+        entity::{Entity, EntityEndpoint},
+    };
+    use freyja_test_common::{mockall::Sequence, mocks::MockDataAdapterSelector};
+
+    const ID: &str = "vehicle.speed";
+
+    fn create_static_signal() -> StaticSignal {
+        StaticSignal {
+            entity: Entity {
+                id: ID.to_string(),
+                endpoints: vec![EntityEndpoint {
+                    protocol: String::from("in-memory"),
+                    operations: vec![Operation::Get.to_string()],
+                    uri: String::from("uri"),
+                    context: String::new(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            target: HashMap::from([(String::from("path"), String::from("Speed"))]),
+            interval_ms: 1000,
+            ..Default::default()
+        }
+    }
+
+    fn create_registrar(
+        data_adapter_selector: MockDataAdapterSelector,
+    ) -> Arc<StaticRegistrar<MockDataAdapterSelector>> {
+        Arc::new(StaticRegistrar::new(
+            Arc::new(SignalStore::new()),
+            vec![create_static_signal()],
+            Arc::new(Mutex::new(data_adapter_selector)),
+            Duration::from_millis(10),
+        ))
+    }
+
+    #[test]
+    fn static_signal_converts_to_signal_patch() {
+        let patch = SignalPatch::from(create_static_signal());
+
+        assert_eq!(patch.id, ID);
+        assert_eq!(patch.source, create_static_signal().entity);
+        assert_eq!(patch.target.metadata["path"], "Speed");
+        assert_eq!(patch.emission_policy.interval_ms, 1000);
+    }
+
+    #[tokio::test]
+    async fn run_adds_signals_and_requests_initial_values() {
+        let mut data_adapter_selector = MockDataAdapterSelector::new();
+        data_adapter_selector
+            .expect_create_or_update_adapter()
+            .once()
+            .returning(|_, _| Ok(()));
+        data_adapter_selector
+            .expect_request_entity_value()
+            .once()
+            .returning(|_| Ok(()));
+
+        let uut = create_registrar(data_adapter_selector);
+        let cancellation_token = CancellationToken::new();
+        let handle = uut.clone().run(cancellation_token.clone());
+
+        for _ in 0..100 {
+            if uut.signals.get(&ID.to_string()).is_some() {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let signal = uut.signals.get(&ID.to_string()).unwrap();
+        assert_eq!(signal.emission.policy.interval_ms, 1000);
+
+        cancellation_token.cancel();
+        assert!(handle.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn register_returns_failed_signals() {
+        let mut data_adapter_selector = MockDataAdapterSelector::new();
+        let mut sequence = Sequence::new();
+        data_adapter_selector
+            .expect_create_or_update_adapter()
+            .once()
+            .in_sequence(&mut sequence)
+            .returning(|_, _| {
+                Err(DataAdapterSelectorError::from(
+                    DataAdapterSelectorErrorKind::ProtocolNotSupported,
+                ))
+            });
+        data_adapter_selector
+            .expect_create_or_update_adapter()
+            .once()
+            .in_sequence(&mut sequence)
+            .returning(|_, _| Ok(()));
+        data_adapter_selector
+            .expect_request_entity_value()
+            .once()
+            .returning(|_| Ok(()));
+
+        let uut = create_registrar(data_adapter_selector);
+
+        let failures = uut.register(uut.patches.clone()).await;
+        assert_eq!(failures.len(), 1);
+        assert!(uut.signals.get(&ID.to_string()).is_none());
+
+        let failures = uut.register(failures).await;
+        assert!(failures.is_empty());
+        assert!(uut.signals.get(&ID.to_string()).is_some());
+    }
+}
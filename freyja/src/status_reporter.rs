@@ -0,0 +1,201 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use time::OffsetDateTime;
+
+use freyja_common::{
+    clock::Clock,
+    cloud_adapter::{CloudMessageRequest, STATUS_SIGNAL_METADATA_KEY},
+};
+
+use crate::config::StatusSignalsConfig;
+
+/// The status signal for the number of seconds since Freyja started
+pub const UPTIME_STATUS_SIGNAL: &str = "uptime_s";
+
+/// The status signal for the number of signals in the signal store
+pub const SIGNALS_ACTIVE_STATUS_SIGNAL: &str = "signals_active";
+
+/// The status signal for the number of signals which could not be emitted to the cloud since Freyja started
+pub const EMISSION_ERRORS_STATUS_SIGNAL: &str = "emission_errors";
+
+/// The status signal for the number of values which could not be converted since Freyja started
+pub const CONVERSION_FAILURES_STATUS_SIGNAL: &str = "conversion_failures";
+
+/// The status signal for the number of due signals which were held back by the emission pacer on the last emission
+pub const PENDING_EMISSIONS_STATUS_SIGNAL: &str = "pending_emissions";
+
+/// Tracks Freyja's operational status and creates the status signals which report it to the cloud
+pub(crate) struct StatusReporter {
+    /// The status signals config
+    config: StatusSignalsConfig,
+
+    /// The clock which measures the uptime
+    clock: Arc<dyn Clock>,
+
+    /// The clock's monotonic time at which the reporter was created
+    started_at: Duration,
+
+    /// The number of signals which could not be emitted to the cloud
+    emission_errors: AtomicU64,
+
+    /// The number of due signals which were held back on the last emission
+    pending_emissions: AtomicUsize,
+}
+
+impl StatusReporter {
+    /// Creates a new StatusReporter
+    ///
+    /// # Arguments
+    /// - `config`: the status signals config
+    /// - `clock`: the clock which measures the uptime
+    pub fn new(config: StatusSignalsConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            started_at: clock.monotonic_now(),
+            clock,
+            emission_errors: AtomicU64::new(0),
+            pending_emissions: AtomicUsize::new(0),
+        }
+    }
+
+    /// Gets the interval at which the status signals are emitted
+    pub fn interval_ms(&self) -> u64 {
+        self.config.interval_ms.get()
+    }
+
+    /// Records a signal which could not be emitted to the cloud
+    pub fn record_emission_error(&self) {
+        self.emission_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the number of due signals which were held back on the last emission
+    ///
+    /// # Arguments
+    /// - `pending_emissions`: the number of signals which were held back
+    pub fn set_pending_emissions(&self, pending_emissions: usize) {
+        self.pending_emissions
+            .store(pending_emissions, Ordering::Relaxed);
+    }
+
+    /// Creates a message for each status signal.
+    /// Each message has the configured metadata and identifies its status signal with the `freyja_status` metadata key
+    ///
+    /// # Arguments
+    /// - `signals_active`: the number of signals in the signal store
+    /// - `conversion_failures`: the number of values which could not be converted
+    /// - `timestamp`: the timestamp of the messages
+    pub fn messages(
+        &self,
+        signals_active: usize,
+        conversion_failures: u64,
+        timestamp: OffsetDateTime,
+    ) -> Vec<CloudMessageRequest> {
+        [
+            (
+                UPTIME_STATUS_SIGNAL,
+                self.clock
+                    .monotonic_now()
+                    .saturating_sub(self.started_at)
+                    .as_secs(),
+            ),
+            (SIGNALS_ACTIVE_STATUS_SIGNAL, signals_active as u64),
+            (
+                EMISSION_ERRORS_STATUS_SIGNAL,
+                self.emission_errors.load(Ordering::Relaxed),
+            ),
+            (CONVERSION_FAILURES_STATUS_SIGNAL, conversion_failures),
+            (
+                PENDING_EMISSIONS_STATUS_SIGNAL,
+                self.pending_emissions.load(Ordering::Relaxed) as u64,
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| {
+            let mut metadata = self.config.metadata.clone();
+            metadata.insert(STATUS_SIGNAL_METADATA_KEY.to_string(), name.to_string());
+
+            CloudMessageRequest {
+                metadata,
+                signal_value: value.to_string().into(),
+                numeric_value: Some(value as f64),
+                signal_timestamp: timestamp,
+                idempotency_key: None,
+            }
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod status_reporter_tests {
+    use super::*;
+
+    use std::{collections::HashMap, num::NonZeroU64};
+
+    use freyja_common::clock::ManualClock;
+
+    fn get_value(messages: &[CloudMessageRequest], name: &str) -> Option<String> {
+        messages
+            .iter()
+            .find(|m| {
+                m.metadata
+                    .get(STATUS_SIGNAL_METADATA_KEY)
+                    .map(String::as_str)
+                    == Some(name)
+            })
+            .map(|m| m.signal_value.to_string())
+    }
+
+    #[test]
+    fn messages_report_each_status_signal() {
+        let clock = Arc::new(ManualClock::default());
+        let uut = StatusReporter::new(
+            StatusSignalsConfig {
+                interval_ms: NonZeroU64::new(1000).unwrap(),
+                metadata: HashMap::from([(String::from("vehicle"), String::from("vin"))]),
+            },
+            clock.clone(),
+        );
+        clock.advance(Duration::from_secs(90));
+        uut.record_emission_error();
+        uut.record_emission_error();
+        uut.set_pending_emissions(3);
+
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_704_164_645).unwrap();
+        let messages = uut.messages(7, 4, timestamp);
+
+        assert_eq!(messages.len(), 5);
+        assert!(messages.iter().all(|m| m.signal_timestamp == timestamp
+            && m.metadata.get("vehicle") == Some(&String::from("vin"))));
+        assert_eq!(
+            get_value(&messages, UPTIME_STATUS_SIGNAL),
+            Some(String::from("90"))
+        );
+        assert_eq!(
+            get_value(&messages, SIGNALS_ACTIVE_STATUS_SIGNAL),
+            Some(String::from("7"))
+        );
+        assert_eq!(
+            get_value(&messages, EMISSION_ERRORS_STATUS_SIGNAL),
+            Some(String::from("2"))
+        );
+        assert_eq!(
+            get_value(&messages, CONVERSION_FAILURES_STATUS_SIGNAL),
+            Some(String::from("4"))
+        );
+        assert_eq!(
+            get_value(&messages, PENDING_EMISSIONS_STATUS_SIGNAL),
+            Some(String::from("3"))
+        );
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{
+    config::{StallAction, WatchdogConfig},
+    freyja_handle::FreyjaComponent,
+};
+
+/// The exit code of the process when the watchdog detects a stalled component and is configured to exit.
+/// This lets a supervisor such as systemd tell stalls apart from other failures
+pub const STALLED_EXIT_CODE: i32 = 70;
+
+/// Tracks the progress of a component's loop so that the watchdog can detect when it stalls.
+/// A component is only considered stalled while it's busy with an iteration, so long waits between iterations are fine.
+#[derive(Debug, Default)]
+pub struct Heartbeat {
+    /// The time at which the current iteration started, or `None` if the component is waiting for the next iteration
+    busy_since: Mutex<Option<Instant>>,
+}
+
+impl Heartbeat {
+    /// Records that the component started an iteration of its loop
+    pub fn beat(&self) {
+        *self.busy_since.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Records that the component finished an iteration and is waiting for the next one
+    pub fn idle(&self) {
+        *self.busy_since.lock().unwrap() = None;
+    }
+
+    /// Returns true if the component has been busy with the current iteration for longer than the timeout
+    ///
+    /// # Arguments
+    /// - `stall_timeout`: the maximum time that an iteration can take
+    pub fn is_stalled(&self, stall_timeout: Duration) -> bool {
+        self.busy_since
+            .lock()
+            .unwrap()
+            .is_some_and(|busy_since| busy_since.elapsed() > stall_timeout)
+    }
+}
+
+/// Monitors the heartbeats of the cartographer and emitter to detect when one of them stops making progress
+pub(crate) struct Watchdog {
+    /// The watchdog config
+    config: WatchdogConfig,
+
+    /// The heartbeat of the cartographer
+    cartographer: Arc<Heartbeat>,
+
+    /// The heartbeat of the emitter
+    emitter: Arc<Heartbeat>,
+}
+
+impl Watchdog {
+    /// Creates a new Watchdog
+    ///
+    /// # Arguments
+    /// - `config`: the watchdog config
+    /// - `cartographer`: the heartbeat of the cartographer
+    /// - `emitter`: the heartbeat of the emitter
+    pub fn new(
+        config: WatchdogConfig,
+        cartographer: Arc<Heartbeat>,
+        emitter: Arc<Heartbeat>,
+    ) -> Self {
+        Self {
+            config,
+            cartographer,
+            emitter,
+        }
+    }
+
+    /// Gets what to do when a component stalls
+    pub fn action(&self) -> StallAction {
+        self.config.action
+    }
+
+    /// Gets the heartbeat of a component
+    ///
+    /// # Arguments
+    /// - `component`: the component
+    pub fn heartbeat(&self, component: FreyjaComponent) -> &Heartbeat {
+        match component {
+            FreyjaComponent::Cartographer => &self.cartographer,
+            FreyjaComponent::Emitter => &self.emitter,
+        }
+    }
+
+    /// Checks the components at the configured interval and returns the first one that has stalled.
+    /// If there is no watchdog, this never returns.
+    ///
+    /// # Arguments
+    /// - `watchdog`: the watchdog, if enabled
+    pub async fn wait_for_stall(watchdog: Option<&Watchdog>) -> FreyjaComponent {
+        let watchdog = match watchdog {
+            Some(watchdog) => watchdog,
+            None => return std::future::pending().await,
+        };
+
+        let stall_timeout = Duration::from_millis(watchdog.config.stall_timeout_ms);
+        loop {
+            tokio::time::sleep(Duration::from_millis(watchdog.config.check_interval_ms)).await;
+
+            for component in [FreyjaComponent::Cartographer, FreyjaComponent::Emitter] {
+                if watchdog.heartbeat(component).is_stalled(stall_timeout) {
+                    return component;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod watchdog_tests {
+    use super::*;
+
+    fn create_watchdog() -> Watchdog {
+        Watchdog::new(
+            WatchdogConfig {
+                stall_timeout_ms: 1000,
+                check_interval_ms: 100,
+                action: StallAction::Restart,
+            },
+            Arc::new(Heartbeat::default()),
+            Arc::new(Heartbeat::default()),
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn heartbeat_is_stalled_only_while_busy() {
+        let uut = Heartbeat::default();
+        let stall_timeout = Duration::from_secs(1);
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert!(!uut.is_stalled(stall_timeout));
+
+        uut.beat();
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert!(!uut.is_stalled(stall_timeout));
+
+        tokio::time::advance(Duration::from_millis(600)).await;
+        assert!(uut.is_stalled(stall_timeout));
+
+        uut.idle();
+        assert!(!uut.is_stalled(stall_timeout));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_for_stall_returns_stalled_component() {
+        let uut = create_watchdog();
+        uut.heartbeat(FreyjaComponent::Emitter).beat();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), Watchdog::wait_for_stall(Some(&uut)))
+                .await;
+
+        assert_eq!(result, Ok(FreyjaComponent::Emitter));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_for_stall_ignores_idle_components() {
+        let uut = create_watchdog();
+        uut.heartbeat(FreyjaComponent::Cartographer).beat();
+        uut.heartbeat(FreyjaComponent::Cartographer).idle();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), Watchdog::wait_for_stall(Some(&uut)))
+                .await;
+
+        assert!(result.is_err());
+    }
+}
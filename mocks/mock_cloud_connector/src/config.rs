@@ -2,6 +2,9 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::{num::NonZeroU32, time::Duration};
+
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 /// Config for the mock cloud connector
@@ -9,4 +12,123 @@ use serde::{Deserialize, Serialize};
 pub struct Config {
     /// The server authority for hosting a gRPC server
     pub server_authority: String,
+
+    /// The latency to simulate before responding to each update
+    #[serde(default)]
+    pub latency: LatencyProfile,
+
+    /// The throttling to simulate for updates. If this is `None`, updates are never throttled
+    #[serde(default)]
+    pub throttling: Option<ThrottlingConfig>,
+}
+
+/// The distribution of the simulated response latency
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LatencyProfile {
+    /// Responses are sent immediately
+    #[default]
+    None,
+
+    /// Every response is delayed by the same latency
+    Fixed { latency_ms: u64 },
+
+    /// Responses are delayed by a latency chosen uniformly between two bounds, inclusive
+    Uniform { min_ms: u64, max_ms: u64 },
+
+    /// Responses are delayed by an exponentially distributed latency,
+    /// which is usually short but occasionally much longer than the mean
+    Exponential { mean_ms: u64 },
+}
+
+impl LatencyProfile {
+    /// Gets the latency for a response
+    ///
+    /// # Arguments
+    /// - `rng`: the random number generator to sample the latency with
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Duration {
+        match self {
+            Self::None => Duration::ZERO,
+            Self::Fixed { latency_ms } => Duration::from_millis(*latency_ms),
+            Self::Uniform { min_ms, max_ms } => {
+                Duration::from_millis(rng.gen_range(*min_ms.min(max_ms)..=*min_ms.max(max_ms)))
+            }
+            Self::Exponential { mean_ms } => {
+                // Inverse transform sampling. 1 - u is in (0, 1], so the logarithm is finite
+                let u: f64 = rng.gen();
+                Duration::from_secs_f64(-(*mean_ms as f64 / 1000.0) * (1.0 - u).ln())
+            }
+        }
+    }
+}
+
+/// Config for simulating a cloud backend which throttles clients that send too many requests
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThrottlingConfig {
+    /// The number of updates accepted in each one second window.
+    /// This can't be 0, since delayed updates would never fit in a window
+    pub max_requests_per_second: NonZeroU32,
+
+    /// What happens to updates beyond the limit
+    #[serde(default)]
+    pub behavior: ThrottleBehavior,
+}
+
+/// What happens to updates which exceed the throttling limit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThrottleBehavior {
+    /// The update is rejected with `RESOURCE_EXHAUSTED`, the gRPC equivalent of HTTP 429
+    #[default]
+    Reject,
+
+    /// The response is held until the update fits in a later window, which applies backpressure to the client
+    Delay,
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn sample_returns_latency_within_profile() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        assert_eq!(LatencyProfile::None.sample(&mut rng), Duration::ZERO);
+        assert_eq!(
+            LatencyProfile::Fixed { latency_ms: 100 }.sample(&mut rng),
+            Duration::from_millis(100)
+        );
+
+        let uniform = LatencyProfile::Uniform {
+            min_ms: 50,
+            max_ms: 60,
+        };
+        for _ in 0..100 {
+            let latency = uniform.sample(&mut rng);
+            assert!(Duration::from_millis(50) <= latency && latency <= Duration::from_millis(60));
+        }
+    }
+
+    #[test]
+    fn sample_exponential_latency_has_configured_mean() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let profile = LatencyProfile::Exponential { mean_ms: 100 };
+
+        let samples = 10_000;
+        let total: Duration = (0..samples).map(|_| profile.sample(&mut rng)).sum();
+        let mean_ms = total.as_millis() / samples;
+        assert!((90..=110).contains(&mean_ms), "mean was {mean_ms}ms");
+    }
+    #[test]
+    fn throttling_config_rejects_zero_limit() {
+        let result = serde_json::from_str::<ThrottlingConfig>(
+            r#"{ "max_requests_per_second": 0, "behavior": "Delay" }"#,
+        );
+        assert!(result.is_err());
+
+        let result =
+            serde_json::from_str::<ThrottlingConfig>(r#"{ "max_requests_per_second": 1 }"#);
+        assert_eq!(result.unwrap().max_requests_per_second.get(), 1);
+    }
 }
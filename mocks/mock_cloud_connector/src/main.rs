@@ -4,19 +4,32 @@
 
 mod config;
 mod mock_cloud_connector_impl;
+mod throttle;
 
-use std::env;
+use std::sync::{atomic::AtomicU64, Mutex};
 
 use cloud_connector_proto::v1::cloud_connector_server::CloudConnectorServer;
 use env_logger::Target;
 use log::{info, LevelFilter};
+use tokio::time::Instant;
 use tonic::transport::Server;
 
-use crate::{config::Config, mock_cloud_connector_impl::MockCloudConnectorImpl};
+use crate::{
+    config::Config, mock_cloud_connector_impl::MockCloudConnectorImpl, throttle::Throttle,
+};
 use freyja_build_common::config_file_stem;
 use freyja_common::{
-    cmd_utils::{get_log_level, parse_args},
-    config_utils, out_dir,
+    cmd_utils::{get_log_level, CommandSpec, LOG_LEVEL_ARG},
+    config_utils::{self, PROFILE_ARG},
+    out_dir,
+};
+
+/// The command-line interface of the mock cloud connector
+const COMMAND: CommandSpec = CommandSpec {
+    name: "mock-cloud-connector",
+    about: "Mocks a cloud connector by printing the requests that it receives from Freyja",
+    args: &[LOG_LEVEL_ARG, PROFILE_ARG],
+    subcommands: &[],
 };
 
 /// Starts the following threads and tasks:
@@ -26,7 +39,7 @@ use freyja_common::{
 /// - A gRPC server to accept incoming requests
 #[tokio::main]
 async fn main() {
-    let args = parse_args(env::args()).expect("Failed to parse args");
+    let args = COMMAND.parse_env_or_exit();
 
     // Setup logging
     let log_level = get_log_level(&args, LevelFilter::Info).expect("Could not parse log level");
@@ -55,7 +68,19 @@ async fn main() {
         .parse()
         .expect("Unable to parse server address");
 
-    let mock_cloud_connector = MockCloudConnectorImpl {};
+    let mock_cloud_connector = MockCloudConnectorImpl {
+        latency: config.latency,
+        throttle: config.throttling.map(|throttling| {
+            (
+                Mutex::new(Throttle::new(
+                    throttling.max_requests_per_second,
+                    Instant::now(),
+                )),
+                throttling.behavior,
+            )
+        }),
+        next_message_number: AtomicU64::new(0),
+    };
 
     Server::builder()
         .add_service(CloudConnectorServer::new(mock_cloud_connector))
@@ -2,15 +2,70 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::SystemTime,
+};
+
 use async_trait::async_trait;
-use cloud_connector_proto::v1::{
-    cloud_connector_server::CloudConnector, UpdateDigitalTwinRequest, UpdateDigitalTwinResponse,
+use cloud_connector_proto::{
+    prost_types::Timestamp,
+    v1::{
+        cloud_connector_server::CloudConnector, CheckForBackfillRequest, CheckForBackfillResponse,
+        UpdateDigitalTwinRequest, UpdateDigitalTwinResponse,
+    },
 };
-use log::info;
+use log::{info, warn};
+use tokio::time::Instant;
 use tonic::{Request, Response, Status};
 
+use crate::{
+    config::{LatencyProfile, ThrottleBehavior},
+    throttle::Throttle,
+};
+
 /// Implements a Mock Cloud Connector
-pub struct MockCloudConnectorImpl {}
+pub struct MockCloudConnectorImpl {
+    /// The latency to simulate before responding to each update
+    pub(crate) latency: LatencyProfile,
+
+    /// The throttle for updates and what happens to updates beyond its limit, if throttling is enabled
+    pub(crate) throttle: Option<(Mutex<Throttle>, ThrottleBehavior)>,
+
+    /// The number of the next update, which is used to assign its message id
+    pub(crate) next_message_number: AtomicU64,
+}
+
+impl MockCloudConnectorImpl {
+    /// Applies the throttle to an update, waiting for room in a later window if the behavior is `Delay`.
+    /// Returns `RESOURCE_EXHAUSTED` if the update is throttled and the behavior is `Reject`.
+    async fn throttle(&self) -> Result<(), Status> {
+        let (throttle, behavior) = match &self.throttle {
+            Some(throttle) => throttle,
+            None => return Ok(()),
+        };
+
+        loop {
+            let result = throttle.lock().unwrap().try_acquire(Instant::now());
+            match (result, behavior) {
+                (Ok(()), _) => return Ok(()),
+                (Err(_), ThrottleBehavior::Reject) => {
+                    warn!("Rejecting an update because the request rate is too high");
+                    return Err(Status::resource_exhausted(
+                        "Too many requests, try again later",
+                    ));
+                }
+                (Err(wait), ThrottleBehavior::Delay) => {
+                    info!("Delaying an update by {wait:?} because the request rate is too high");
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+}
 
 #[async_trait]
 impl CloudConnector for MockCloudConnectorImpl {
@@ -22,11 +77,91 @@ impl CloudConnector for MockCloudConnectorImpl {
         &self,
         request: Request<UpdateDigitalTwinRequest>,
     ) -> Result<Response<UpdateDigitalTwinResponse>, Status> {
+        self.throttle().await?;
+
+        let latency = self.latency.sample(&mut rand::thread_rng());
+        tokio::time::sleep(latency).await;
+
         let message_json = serde_json::to_string_pretty(&request.into_inner())
             .map_err(|_| Status::invalid_argument("Could not parse request"))?;
 
-        info!("Mock Cloud Connector received a message!\n{message_json}");
+        let message_id = format!(
+            "mock-{}",
+            self.next_message_number.fetch_add(1, Ordering::Relaxed)
+        );
+        info!("Mock Cloud Connector received message {message_id}!\n{message_json}");
+
+        Ok(Response::new(UpdateDigitalTwinResponse {
+            message_id,
+            accepted_timestamp: Some(Timestamp::from(SystemTime::now())),
+            warnings: Vec::new(),
+        }))
+    }
+    /// Checks for backfill requests.
+    /// The mock cloud connector never requests a backfill.
+    ///
+    /// # Arguments
+    /// - `_request`: the request
+    async fn check_for_backfill(
+        &self,
+        _request: Request<CheckForBackfillRequest>,
+    ) -> Result<Response<CheckForBackfillResponse>, Status> {
+        Ok(Response::new(CheckForBackfillResponse::default()))
+    }
+}
+
+#[cfg(test)]
+mod mock_cloud_connector_impl_tests {
+    use super::*;
+
+    use std::num::NonZeroU32;
+
+    use tonic::Code;
+
+    fn create_mock_cloud_connector(
+        max_requests_per_second: u32,
+        behavior: ThrottleBehavior,
+    ) -> MockCloudConnectorImpl {
+        MockCloudConnectorImpl {
+            latency: LatencyProfile::None,
+            throttle: Some((
+                Mutex::new(Throttle::new(
+                    NonZeroU32::new(max_requests_per_second).unwrap(),
+                    Instant::now(),
+                )),
+                behavior,
+            )),
+            next_message_number: AtomicU64::new(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn update_digital_twin_rejects_requests_beyond_limit() {
+        let uut = create_mock_cloud_connector(1, ThrottleBehavior::Reject);
+
+        let result = uut
+            .update_digital_twin(Request::new(UpdateDigitalTwinRequest::default()))
+            .await;
+        assert_eq!(result.unwrap().into_inner().message_id, "mock-0");
+
+        let result = uut
+            .update_digital_twin(Request::new(UpdateDigitalTwinRequest::default()))
+            .await;
+        assert_eq!(result.unwrap_err().code(), Code::ResourceExhausted);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn update_digital_twin_delays_requests_beyond_limit() {
+        let uut = create_mock_cloud_connector(1, ThrottleBehavior::Delay);
+        let start = Instant::now();
+
+        for _ in 0..2 {
+            let result = uut
+                .update_digital_twin(Request::new(UpdateDigitalTwinRequest::default()))
+                .await;
+            assert!(result.is_ok());
+        }
 
-        Ok(Response::new(UpdateDigitalTwinResponse {}))
+        assert!(start.elapsed() >= std::time::Duration::from_secs(1));
     }
 }
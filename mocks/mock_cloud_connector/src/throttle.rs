@@ -0,0 +1,81 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{num::NonZeroU32, time::Duration};
+
+use tokio::time::Instant;
+
+/// The length of a throttling window
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Limits the number of requests accepted in each one second window
+pub(crate) struct Throttle {
+    /// The maximum number of requests accepted in a window
+    max_requests: u32,
+
+    /// The start of the current window
+    window_start: Instant,
+
+    /// The number of requests accepted in the current window
+    accepted: u32,
+}
+
+impl Throttle {
+    /// Creates a new Throttle
+    ///
+    /// # Arguments
+    /// - `max_requests_per_second`: the maximum number of requests accepted in a window
+    /// - `now`: the current time, which starts the first window
+    pub fn new(max_requests_per_second: NonZeroU32, now: Instant) -> Self {
+        Self {
+            max_requests: max_requests_per_second.get(),
+            window_start: now,
+            accepted: 0,
+        }
+    }
+
+    /// Accepts a request if the current window has room for it.
+    /// Otherwise, returns the time until the next window starts.
+    ///
+    /// # Arguments
+    /// - `now`: the current time
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed >= WINDOW {
+            self.window_start = now;
+            self.accepted = 0;
+        }
+
+        if self.accepted < self.max_requests {
+            self.accepted += 1;
+            Ok(())
+        } else {
+            Err((self.window_start + WINDOW).saturating_duration_since(now))
+        }
+    }
+}
+
+#[cfg(test)]
+mod throttle_tests {
+    use super::*;
+
+    #[test]
+    fn try_acquire_limits_requests_per_window() {
+        let start = Instant::now();
+        let mut uut = Throttle::new(NonZeroU32::new(2).unwrap(), start);
+
+        assert_eq!(uut.try_acquire(start), Ok(()));
+        assert_eq!(uut.try_acquire(start + Duration::from_millis(100)), Ok(()));
+        assert_eq!(
+            uut.try_acquire(start + Duration::from_millis(400)),
+            Err(Duration::from_millis(600))
+        );
+
+        // A new window starts once the current one has elapsed
+        let next_window = start + Duration::from_millis(1000);
+        assert_eq!(uut.try_acquire(next_window), Ok(()));
+        assert_eq!(uut.try_acquire(next_window), Ok(()));
+        assert_eq!(uut.try_acquire(next_window), Err(WINDOW));
+    }
+}
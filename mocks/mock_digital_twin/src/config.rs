@@ -14,6 +14,11 @@ pub struct Config {
 
     /// The list of entities
     pub entities: Vec<EntityConfig>,
+
+    /// The address for hosting the management API, which lets tests control the mock over HTTP.
+    /// If this is `None`, the management API is disabled
+    #[serde(default)]
+    pub management_api_address: Option<String>,
 }
 
 /// A config entry for the MockDigitalTwinAdapter
@@ -3,12 +3,13 @@
 // SPDX-License-Identifier: MIT
 
 mod config;
+mod management_api;
 mod mock_digital_twin_impl;
 mod mock_provider;
 
 use std::{
     collections::{HashMap, HashSet},
-    env, io,
+    io,
     sync::{Arc, Mutex},
     thread,
     time::Duration,
@@ -24,7 +25,7 @@ use samples_protobuf_data_access::sample_grpc::v1::{
     digital_twin_provider::digital_twin_provider_server::DigitalTwinProviderServer,
 };
 use tokio::sync::{mpsc, mpsc::UnboundedSender};
-use tonic::{transport::Server, Request};
+use tonic::{transport::Server, Request, Status};
 
 use crate::{
     config::{Config, EntityConfig},
@@ -33,8 +34,24 @@ use crate::{
 };
 use freyja_build_common::config_file_stem;
 use freyja_common::{
-    cmd_utils::{get_log_level, parse_args},
-    config_utils, out_dir,
+    cmd_utils::{get_log_level, ArgSpec, CommandSpec, LOG_LEVEL_ARG},
+    config_utils::{self, PROFILE_ARG},
+    out_dir,
+};
+
+/// The argument which enables interactive mode
+const INTERACTIVE_ARG: ArgSpec = ArgSpec {
+    name: "interactive",
+    value_name: None,
+    help: "Only enables the entities for the current count, which is incremented by pressing Enter",
+};
+
+/// The command-line interface of the mock digital twin
+const COMMAND: CommandSpec = CommandSpec {
+    name: "mock-digital-twin",
+    about: "Serves the configured entities through the in-vehicle digital twin and provider APIs",
+    args: &[LOG_LEVEL_ARG, PROFILE_ARG, INTERACTIVE_ARG],
+    subcommands: &[],
 };
 
 /// Stores the state of active entities, subscribers, and relays responses
@@ -54,6 +71,9 @@ pub(crate) struct DigitalTwinAdapterState {
 
     /// Whether or not the application is in interactive mode
     interactive: bool,
+
+    /// Whether or not the mock responds to requests
+    available: bool,
 }
 
 /// Starts the following threads and tasks:
@@ -61,9 +81,10 @@ pub(crate) struct DigitalTwinAdapterState {
 /// - A task which handles async get responses
 /// - A task which handles publishing to subscribers
 /// - A gRPC server to accept incoming requests
+/// - A management API server, if configured
 #[tokio::main]
 async fn main() {
-    let args = parse_args(env::args()).expect("Failed to parse args");
+    let args = COMMAND.parse_env_or_exit();
 
     // Setup logging
     let log_level = get_log_level(&args, LevelFilter::Info).expect("Could not parse log level");
@@ -72,7 +93,7 @@ async fn main() {
         .target(Target::Stdout)
         .init();
 
-    let interactive = args.get("interactive").is_some();
+    let interactive = args.contains(INTERACTIVE_ARG.name);
 
     let config: Config = config_utils::read_from_files(
         config_file_stem!(),
@@ -95,6 +116,7 @@ async fn main() {
             .collect(),
         response_channel_sender: sender,
         interactive,
+        available: true,
     }));
 
     let console_listener_state = state.clone();
@@ -116,12 +138,7 @@ async fn main() {
                 io::stdin().read_line(&mut buffer)?;
 
                 let mut state = console_listener_state.lock().unwrap();
-                state.count += 1;
-                info!(
-                    "New count: {}. Active entities {:?}",
-                    state.count,
-                    get_active_entity_names(&state)
-                );
+                advance_count(&mut state);
             }
         });
     }
@@ -161,7 +178,11 @@ async fn main() {
 
             let subscriptions = {
                 let state = subscribe_loop_state.lock().unwrap();
-                state.subscriptions.clone()
+                if state.available {
+                    state.subscriptions.clone()
+                } else {
+                    HashMap::new()
+                }
             };
 
             for (entity_id, subscribers) in subscriptions {
@@ -201,6 +222,10 @@ async fn main() {
         }
     });
 
+    if let Some(address) = &config.management_api_address {
+        management_api::start(address, state.clone()).expect("Unable to start the management API");
+    }
+
     // Server setup
     info!(
         "Mock Digital Twin Server starting at {}",
@@ -243,6 +268,34 @@ fn within_bounds(value: u8, begin: u8, end: Option<u8>, interactive: bool) -> bo
         }
 }
 
+/// Increments the internal count which dictates which entities are enabled in interactive mode.
+/// The count stops at its maximum of 255 rather than overflowing. Returns the new count.
+///
+/// # Arguments
+/// - `state`: the state of the DigitalTwinAdapter which consists of active entities
+fn advance_count(state: &mut DigitalTwinAdapterState) -> u8 {
+    state.count = state.count.saturating_add(1);
+    info!(
+        "New count: {}. Active entities {:?}",
+        state.count,
+        get_active_entity_names(state)
+    );
+
+    state.count
+}
+
+/// Returns an error if the mock has been made unavailable with the management API
+///
+/// # Arguments
+/// - `state`: the state of the DigitalTwinAdapter
+fn check_available(state: &DigitalTwinAdapterState) -> Result<(), Status> {
+    if state.available {
+        Ok(())
+    } else {
+        Err(Status::unavailable("The mock digital twin is unavailable"))
+    }
+}
+
 /// Gets active entity names for this mock provider
 ///
 /// # Arguments
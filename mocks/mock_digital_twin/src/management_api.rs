@@ -0,0 +1,331 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    advance_count,
+    config::{EntityConfig, SensorValueConfig},
+    DigitalTwinAdapterState,
+};
+
+/// The path of the endpoint which lists and adds entities
+const ENTITIES_PATH: &str = "/entities";
+
+/// The path of the endpoint which removes an entity
+const ENTITY_PATH: &str = "/entities/:id";
+
+/// The path of the endpoint which sets the values of an entity
+const ENTITY_VALUES_PATH: &str = "/entities/:id/values";
+
+/// The path of the endpoint which toggles the availability of the mock
+const AVAILABILITY_PATH: &str = "/availability";
+
+/// The path of the endpoint which advances the internal count, like pressing Enter in interactive mode
+const ADVANCE_PATH: &str = "/advance";
+
+/// The body of a request to change the availability of the mock
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct AvailabilityRequest {
+    /// Whether the mock should respond to requests
+    pub available: bool,
+}
+
+/// Starts the management API, which lets test code control the mock's entities and availability
+/// without using interactive mode
+///
+/// # Arguments
+/// - `address`: the hosting address
+/// - `state`: the server state
+pub(crate) fn start(
+    address: &str,
+    state: Arc<Mutex<DigitalTwinAdapterState>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let addr: SocketAddr = address.parse()?;
+
+    let app = Router::new()
+        .route(ENTITIES_PATH, get(list_entities).post(add_entity))
+        .route(ENTITY_PATH, delete(remove_entity))
+        .route(ENTITY_VALUES_PATH, put(set_entity_values))
+        .route(AVAILABILITY_PATH, put(set_availability))
+        .route(ADVANCE_PATH, post(advance))
+        .with_state(state);
+
+    let server = axum::Server::try_bind(&addr)?.serve(app.into_make_service());
+
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            warn!("Management API terminated with error: {e}");
+        }
+    });
+
+    info!("Mock Digital Twin management API starting at {addr}");
+
+    Ok(())
+}
+
+/// Lists the configured entities, including the ones which aren't currently active
+///
+/// # Arguments
+/// - `state`: the server state
+async fn list_entities(
+    State(state): State<Arc<Mutex<DigitalTwinAdapterState>>>,
+) -> Json<Vec<EntityConfig>> {
+    let state = state.lock().unwrap();
+    Json(state.entities.iter().map(|(c, _)| c.clone()).collect())
+}
+
+/// Adds an entity.
+/// Responds with `409 Conflict` if an entity with the same id already exists.
+///
+/// # Arguments
+/// - `state`: the server state
+/// - `entity`: the entity to add
+async fn add_entity(
+    State(state): State<Arc<Mutex<DigitalTwinAdapterState>>>,
+    Json(entity): Json<EntityConfig>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let id = entity.entity.id.clone();
+    if state.entities.iter().any(|(c, _)| c.entity.id == id) {
+        return StatusCode::CONFLICT;
+    }
+
+    info!("Adding entity {id}");
+    state.entities.push((entity, 0));
+    state.subscriptions.insert(id, HashSet::new());
+
+    StatusCode::CREATED
+}
+
+/// Removes an entity and its subscriptions.
+/// Responds with `404 Not Found` if the entity doesn't exist.
+///
+/// # Arguments
+/// - `state`: the server state
+/// - `id`: the id of the entity to remove
+async fn remove_entity(
+    State(state): State<Arc<Mutex<DigitalTwinAdapterState>>>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let original_len = state.entities.len();
+    state.entities.retain(|(c, _)| c.entity.id != id);
+    if state.entities.len() == original_len {
+        return StatusCode::NOT_FOUND;
+    }
+
+    info!("Removed entity {id}");
+    state.subscriptions.remove(&id);
+
+    StatusCode::NO_CONTENT
+}
+
+/// Sets the values that an entity takes and restarts them from the first value.
+/// Responds with `404 Not Found` if the entity doesn't exist.
+///
+/// # Arguments
+/// - `state`: the server state
+/// - `id`: the id of the entity
+/// - `values`: the new values of the entity
+async fn set_entity_values(
+    State(state): State<Arc<Mutex<DigitalTwinAdapterState>>>,
+    Path(id): Path<String>,
+    Json(values): Json<SensorValueConfig>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    match state.entities.iter_mut().find(|(c, _)| c.entity.id == id) {
+        Some((config_item, count)) => {
+            info!("Setting values of entity {id} to {values:?}");
+            config_item.values = values;
+            *count = 0;
+            StatusCode::NO_CONTENT
+        }
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+/// Makes the mock available or unavailable.
+/// While unavailable, the mock's gRPC APIs respond with `UNAVAILABLE` and nothing is published to subscribers.
+///
+/// # Arguments
+/// - `state`: the server state
+/// - `request`: the requested availability
+async fn set_availability(
+    State(state): State<Arc<Mutex<DigitalTwinAdapterState>>>,
+    Json(request): Json<AvailabilityRequest>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    info!("Setting availability to {}", request.available);
+    state.available = request.available;
+
+    StatusCode::NO_CONTENT
+}
+
+/// Advances the internal count which controls the active entities in interactive mode,
+/// and responds with the new count
+///
+/// # Arguments
+/// - `state`: the server state
+async fn advance(State(state): State<Arc<Mutex<DigitalTwinAdapterState>>>) -> Json<u8> {
+    let mut state = state.lock().unwrap();
+    Json(advance_count(&mut state))
+}
+
+#[cfg(test)]
+mod management_api_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use tokio::sync::mpsc;
+
+    use freyja_common::entity::Entity;
+
+    use crate::{find_entity, get_entity_value};
+
+    const ID: &str = "dtmi:sdv:HVAC:AmbientAirTemperature;1";
+
+    fn create_state(interactive: bool) -> Arc<Mutex<DigitalTwinAdapterState>> {
+        let (sender, _) = mpsc::unbounded_channel();
+        Arc::new(Mutex::new(DigitalTwinAdapterState {
+            count: 0,
+            entities: Vec::new(),
+            subscriptions: HashMap::new(),
+            response_channel_sender: sender,
+            interactive,
+            available: true,
+        }))
+    }
+
+    fn create_entity(begin: u8, value: &str) -> EntityConfig {
+        EntityConfig {
+            begin,
+            end: None,
+            entity: Entity {
+                id: ID.to_string(),
+                ..Default::default()
+            },
+            values: SensorValueConfig::Static(value.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn add_entity_rejects_duplicate_ids() {
+        let state = create_state(false);
+
+        let result = add_entity(State(state.clone()), Json(create_entity(0, "42"))).await;
+        assert_eq!(result, StatusCode::CREATED);
+
+        let result = add_entity(State(state.clone()), Json(create_entity(0, "43"))).await;
+        assert_eq!(result, StatusCode::CONFLICT);
+
+        let Json(entities) = list_entities(State(state.clone())).await;
+        assert_eq!(entities.len(), 1);
+        assert!(state.lock().unwrap().subscriptions.contains_key(ID));
+    }
+
+    #[tokio::test]
+    async fn remove_entity_removes_entity_and_subscriptions() {
+        let state = create_state(false);
+        add_entity(State(state.clone()), Json(create_entity(0, "42"))).await;
+
+        let result = remove_entity(State(state.clone()), Path(ID.to_string())).await;
+        assert_eq!(result, StatusCode::NO_CONTENT);
+        {
+            let state = state.lock().unwrap();
+            assert!(find_entity(&state, &ID.to_string()).is_none());
+            assert!(!state.subscriptions.contains_key(ID));
+        }
+
+        let result = remove_entity(State(state), Path(ID.to_string())).await;
+        assert_eq!(result, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn set_entity_values_changes_value() {
+        let state = create_state(false);
+        add_entity(State(state.clone()), Json(create_entity(0, "42"))).await;
+
+        let result = set_entity_values(
+            State(state.clone()),
+            Path(ID.to_string()),
+            Json(SensorValueConfig::Static(String::from("43"))),
+        )
+        .await;
+        assert_eq!(result, StatusCode::NO_CONTENT);
+        assert_eq!(
+            get_entity_value(&mut state.lock().unwrap(), ID),
+            Some(String::from("43"))
+        );
+
+        let result = set_entity_values(
+            State(state),
+            Path(String::from("unknown")),
+            Json(SensorValueConfig::Static(String::from("43"))),
+        )
+        .await;
+        assert_eq!(result, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn set_availability_toggles_availability() {
+        let state = create_state(false);
+
+        set_availability(
+            State(state.clone()),
+            Json(AvailabilityRequest { available: false }),
+        )
+        .await;
+        assert!(!state.lock().unwrap().available);
+
+        set_availability(
+            State(state.clone()),
+            Json(AvailabilityRequest { available: true }),
+        )
+        .await;
+        assert!(state.lock().unwrap().available);
+    }
+
+    #[tokio::test]
+    async fn advance_activates_entities_in_interactive_mode() {
+        let state = create_state(true);
+        add_entity(State(state.clone()), Json(create_entity(1, "42"))).await;
+        assert!(find_entity(&state.lock().unwrap(), &ID.to_string()).is_none());
+
+        let Json(count) = advance(State(state.clone())).await;
+        assert_eq!(count, 1);
+        assert!(find_entity(&state.lock().unwrap(), &ID.to_string()).is_some());
+    }
+
+    #[tokio::test]
+    async fn advance_stops_at_max_count() {
+        let state = create_state(true);
+        state.lock().unwrap().count = u8::MAX - 1;
+
+        for _ in 0..2 {
+            let Json(count) = advance(State(state.clone())).await;
+            assert_eq!(count, u8::MAX);
+        }
+    }
+
+    #[test]
+    fn start_returns_err_for_invalid_address() {
+        let result = start("not an address", create_state(false));
+        assert!(result.is_err());
+    }
+}
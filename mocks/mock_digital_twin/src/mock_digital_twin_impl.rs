@@ -12,7 +12,7 @@ use core_protobuf_data_access::invehicle_digital_twin::v1::{
 use log::info;
 use tonic::{Request, Response, Status};
 
-use crate::{find_entity, DigitalTwinAdapterState};
+use crate::{check_available, find_entity, DigitalTwinAdapterState};
 
 /// Implements an In-Vehicle Digital Twin Server
 pub struct MockDigitalTwinImpl {
@@ -33,6 +33,7 @@ impl InvehicleDigitalTwin for MockDigitalTwinImpl {
         let request = request.into_inner();
         info!("Received request to get entity: {}", request.id);
         let state = self.state.lock().unwrap();
+        check_available(&state)?;
         find_entity(&state, &request.id)
             .map(|(config_item, _)| {
                 let endpoint_info_list = config_item
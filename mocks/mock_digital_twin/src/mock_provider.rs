@@ -20,7 +20,7 @@ use samples_protobuf_data_access::sample_grpc::v1::{
 use tokio_stream::Stream;
 use tonic::{Request, Response, Status};
 
-use crate::{find_entity, get_entity_value, DigitalTwinAdapterState};
+use crate::{check_available, find_entity, get_entity_value, DigitalTwinAdapterState};
 
 /// Implements a digital twin provider
 pub struct MockProvider {
@@ -43,6 +43,7 @@ impl DigitalTwinProvider for MockProvider {
         let request = request.into_inner();
         info!("Received subscribe request: {request:?}");
         let mut state = self.state.lock().unwrap();
+        check_available(&state)?;
 
         match find_entity(&state, &request.entity_id) {
             Some(_) => {
@@ -66,6 +67,7 @@ impl DigitalTwinProvider for MockProvider {
         let request = request.into_inner();
         info!("Received request to get value: {request:?}");
         let mut state = self.state.lock().unwrap();
+        check_available(&state)?;
         match get_entity_value(&mut state, &request.entity_id) {
             Some(value) => {
                 let publish_request = PublishRequest {
@@ -3,27 +3,53 @@
 // SPDX-License-Identifier: MIT
 
 mod config;
+mod mock_mapping_management_impl;
 mod mock_mapping_service_impl;
 
 use std::{
-    env, io,
+    io,
     sync::{Arc, Mutex},
     thread,
 };
 
 use env_logger::Target;
 use log::{info, LevelFilter};
-use mapping_service_proto::v1::mapping_service_server::MappingServiceServer;
+use mapping_service_proto::{
+    v1::{
+        mapping_management_server::MappingManagementServer,
+        mapping_service_server::MappingServiceServer,
+    },
+    v2::mapping_service_server::MappingServiceServer as MappingServiceV2Server,
+};
 use tonic::transport::Server;
 
-use config::Config;
+use config::{Config, ConfigItem};
 use freyja_build_common::config_file_stem;
 use freyja_common::{
-    cmd_utils::{get_log_level, parse_args},
-    config_utils, out_dir,
+    cmd_utils::{get_log_level, ArgSpec, CommandSpec, LOG_LEVEL_ARG},
+    config_utils::{self, PROFILE_ARG},
+    out_dir,
 };
 
-use crate::mock_mapping_service_impl::MockMappingServiceImpl;
+/// The argument which enables interactive mode
+const INTERACTIVE_ARG: ArgSpec = ArgSpec {
+    name: "interactive",
+    value_name: None,
+    help: "Only returns the mappings for the current count, which is incremented by pressing Enter",
+};
+
+/// The command-line interface of the mock mapping service
+const COMMAND: CommandSpec = CommandSpec {
+    name: "mock-mapping-service",
+    about: "Serves the configured mappings through the mapping service API",
+    args: &[LOG_LEVEL_ARG, PROFILE_ARG, INTERACTIVE_ARG],
+    subcommands: &[],
+};
+
+use crate::{
+    mock_mapping_management_impl::MockMappingManagementImpl,
+    mock_mapping_service_impl::MockMappingServiceImpl,
+};
 
 /// Stores the state of the mapping service
 struct MappingState {
@@ -42,10 +68,10 @@ struct MappingState {
 
 /// Starts the following threads and tasks:
 /// - A thread which listens for input from the command window
-/// - A GRPC server to accept incoming requests
+/// - A GRPC server to accept incoming requests, including requests to change the mapping
 #[tokio::main]
 async fn main() {
-    let args = parse_args(env::args()).expect("Failed to parse args");
+    let args = COMMAND.parse_env_or_exit();
 
     // Setup logging
     let log_level = get_log_level(&args, LevelFilter::Info).expect("Could not parse log level");
@@ -54,7 +80,7 @@ async fn main() {
         .target(Target::Stdout)
         .init();
 
-    let interactive = args.get("interactive").is_some();
+    let interactive = args.contains(INTERACTIVE_ARG.name);
 
     let config: Config = config_utils::read_from_files(
         config_file_stem!(),
@@ -70,7 +96,7 @@ async fn main() {
     let state = Arc::new(Mutex::new(MappingState {
         count: 0,
         pending_work: check_for_work(&config, 0, interactive),
-        config: config.clone(),
+        config,
         interactive,
     }));
 
@@ -90,7 +116,7 @@ async fn main() {
 
                 let mut state = state_clone.lock().unwrap();
                 state.count += 1;
-                let new_work = check_for_work(&config, state.count, state.interactive);
+                let new_work = check_for_work(&state.config, state.count, state.interactive);
 
                 state.pending_work |= new_work;
                 info!(
@@ -124,8 +150,18 @@ async fn main() {
         state: state.clone(),
     };
 
+    let mock_mapping_service_v2 = MockMappingServiceImpl {
+        state: state.clone(),
+    };
+
+    let mock_mapping_management = MockMappingManagementImpl {
+        state: state.clone(),
+    };
+
     Server::builder()
         .add_service(MappingServiceServer::new(mock_mapping_service))
+        .add_service(MappingServiceV2Server::new(mock_mapping_service_v2))
+        .add_service(MappingManagementServer::new(mock_mapping_management))
         .serve(addr)
         .await
         .unwrap();
@@ -152,3 +188,17 @@ fn check_for_work(config: &Config, n: u8, interactive: bool) -> bool {
             }
     })
 }
+
+/// Checks whether a mapping is currently served
+///
+/// # Arguments
+/// - `item`: the config item for the mapping
+/// - `n`: the current count
+/// - `interactive`: whether or not the service is running in interactive mode
+fn is_active(item: &ConfigItem, n: u8, interactive: bool) -> bool {
+    !interactive
+        || match item.end {
+            Some(end) => n >= item.begin && n < end,
+            None => n >= item.begin,
+        }
+}
@@ -0,0 +1,275 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use log::info;
+use mapping_service_proto::v1::{
+    mapping_management_server::MappingManagement, AddMappingRequest, AddMappingResponse,
+    DeleteMappingRequest, DeleteMappingResponse, MapEntry, UpdateMappingRequest,
+    UpdateMappingResponse,
+};
+use tonic::{Request, Response, Status};
+
+use freyja_common::digital_twin_map_entry::DigitalTwinMapEntry;
+
+use crate::{config::ConfigItem, is_active, MappingState};
+
+/// Implements the Mapping Management API, which changes the mapping served by the mock at runtime.
+/// Only the mappings which are currently served can be updated or deleted.
+pub struct MockMappingManagementImpl {
+    /// The server state
+    pub(crate) state: Arc<Mutex<MappingState>>,
+}
+
+/// Gets the mapping entry of a request
+///
+/// # Arguments
+/// - `entry`: the entry of the request, if any
+fn require_entry(entry: Option<MapEntry>) -> Result<DigitalTwinMapEntry, Status> {
+    entry
+        .map(Into::into)
+        .ok_or_else(|| Status::invalid_argument("The request has no mapping entry"))
+}
+
+#[async_trait]
+impl MappingManagement for MockMappingManagementImpl {
+    /// Adds a mapping entry, which is served immediately.
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn add_mapping(
+        &self,
+        request: Request<AddMappingRequest>,
+    ) -> Result<Response<AddMappingResponse>, Status> {
+        let entry = require_entry(request.into_inner().entry)?;
+        let mut state = self.state.lock().unwrap();
+        let (count, interactive) = (state.count, state.interactive);
+
+        if state
+            .config
+            .values
+            .iter()
+            .any(|c| c.value.source == entry.source && is_active(c, count, interactive))
+        {
+            return Err(Status::already_exists(format!(
+                "There is already a mapping for {}",
+                entry.source
+            )));
+        }
+
+        info!("Adding mapping for {}", entry.source);
+        state.config.values.push(ConfigItem {
+            begin: count,
+            end: None,
+            value: entry,
+        });
+        state.pending_work = true;
+
+        Ok(Response::new(AddMappingResponse {}))
+    }
+
+    /// Replaces the mapping entry with the same source.
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn update_mapping(
+        &self,
+        request: Request<UpdateMappingRequest>,
+    ) -> Result<Response<UpdateMappingResponse>, Status> {
+        let entry = require_entry(request.into_inner().entry)?;
+        let mut state = self.state.lock().unwrap();
+        let (count, interactive) = (state.count, state.interactive);
+
+        let mut updated = false;
+        for item in state
+            .config
+            .values
+            .iter_mut()
+            .filter(|c| c.value.source == entry.source && is_active(c, count, interactive))
+        {
+            item.value = entry.clone();
+            updated = true;
+        }
+
+        if !updated {
+            return Err(Status::not_found(format!(
+                "There is no mapping for {}",
+                entry.source
+            )));
+        }
+
+        info!("Updated mapping for {}", entry.source);
+        state.pending_work = true;
+
+        Ok(Response::new(UpdateMappingResponse {}))
+    }
+
+    /// Deletes the mapping entry for a source.
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn delete_mapping(
+        &self,
+        request: Request<DeleteMappingRequest>,
+    ) -> Result<Response<DeleteMappingResponse>, Status> {
+        let source = request.into_inner().source;
+        let mut state = self.state.lock().unwrap();
+        let (count, interactive) = (state.count, state.interactive);
+
+        let original_len = state.config.values.len();
+        state
+            .config
+            .values
+            .retain(|c| c.value.source != source || !is_active(c, count, interactive));
+
+        if state.config.values.len() == original_len {
+            return Err(Status::not_found(format!(
+                "There is no mapping for {source}"
+            )));
+        }
+
+        info!("Deleted mapping for {source}");
+        state.pending_work = true;
+
+        Ok(Response::new(DeleteMappingResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod mock_mapping_management_impl_tests {
+    use super::*;
+
+    use mapping_service_proto::v1::{
+        mapping_service_server::MappingService, CheckForWorkRequest, GetMappingRequest,
+    };
+
+    use crate::{config::Config, mock_mapping_service_impl::MockMappingServiceImpl};
+
+    const SOURCE: &str = "dtmi:sdv:HVAC:AmbientAirTemperature;1";
+
+    fn create_state(values: Vec<ConfigItem>, interactive: bool) -> Arc<Mutex<MappingState>> {
+        Arc::new(Mutex::new(MappingState {
+            count: 0,
+            pending_work: false,
+            config: Config {
+                mapping_server_authority: String::from("0.0.0.0:8888"),
+                values,
+            },
+            interactive,
+        }))
+    }
+
+    fn create_entry(interval_ms: u64) -> MapEntry {
+        DigitalTwinMapEntry {
+            source: SOURCE.to_string(),
+            interval_ms,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[tokio::test]
+    async fn add_mapping_is_served_and_reported_as_work() {
+        let state = create_state(Vec::new(), false);
+        let uut = MockMappingManagementImpl {
+            state: state.clone(),
+        };
+        let service = MockMappingServiceImpl {
+            state: state.clone(),
+        };
+
+        let request = AddMappingRequest {
+            entry: Some(create_entry(1000)),
+        };
+        assert!(uut.add_mapping(Request::new(request.clone())).await.is_ok());
+
+        let result = uut.add_mapping(Request::new(request)).await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::AlreadyExists);
+
+        let has_work = service
+            .check_for_work(Request::new(CheckForWorkRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .has_work;
+        assert!(has_work);
+
+        let mapping = service
+            .get_mapping(Request::new(GetMappingRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .mapping;
+        assert_eq!(mapping[SOURCE].interval_ms, 1000);
+    }
+
+    #[tokio::test]
+    async fn update_mapping_replaces_served_entry() {
+        let state = create_state(Vec::new(), false);
+        let uut = MockMappingManagementImpl {
+            state: state.clone(),
+        };
+
+        let request = UpdateMappingRequest {
+            entry: Some(create_entry(2000)),
+        };
+        let result = uut.update_mapping(Request::new(request.clone())).await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+
+        let add_request = AddMappingRequest {
+            entry: Some(create_entry(1000)),
+        };
+        uut.add_mapping(Request::new(add_request)).await.unwrap();
+        state.lock().unwrap().pending_work = false;
+
+        assert!(uut.update_mapping(Request::new(request)).await.is_ok());
+
+        let state = state.lock().unwrap();
+        assert!(state.pending_work);
+        assert_eq!(state.config.values[0].value.interval_ms, 2000);
+    }
+
+    #[tokio::test]
+    async fn delete_mapping_only_deletes_served_entries() {
+        let entry: DigitalTwinMapEntry = create_entry(1000).into();
+        let state = create_state(
+            vec![
+                ConfigItem {
+                    begin: 0,
+                    end: Some(1),
+                    value: entry.clone(),
+                },
+                ConfigItem {
+                    begin: 2,
+                    end: None,
+                    value: entry,
+                },
+            ],
+            true,
+        );
+        let uut = MockMappingManagementImpl {
+            state: state.clone(),
+        };
+
+        let request = DeleteMappingRequest {
+            source: SOURCE.to_string(),
+        };
+        assert!(uut
+            .delete_mapping(Request::new(request.clone()))
+            .await
+            .is_ok());
+
+        {
+            let state = state.lock().unwrap();
+            assert!(state.pending_work);
+            assert_eq!(state.config.values.len(), 1);
+            assert_eq!(state.config.values[0].begin, 2);
+        }
+
+        let result = uut.delete_mapping(Request::new(request)).await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+    }
+}
@@ -2,34 +2,35 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use async_trait::async_trait;
 use log::info;
-use mapping_service_proto::v1::{
-    mapping_service_server::MappingService, CheckForWorkRequest, CheckForWorkResponse,
-    GetMappingRequest, GetMappingResponse,
+use mapping_service_proto::{
+    v1::{
+        mapping_service_server::MappingService, CheckForWorkRequest, CheckForWorkResponse,
+        GetMappingRequest, GetMappingResponse,
+    },
+    v2,
 };
 use tonic::{Request, Response, Status};
 
-use crate::MappingState;
+use freyja_common::digital_twin_map_entry::DigitalTwinMapEntry;
+
+use crate::{is_active, MappingState};
 
-/// Implements a Mapping Service
+/// Implements the v1 and v2 Mapping Services
 pub struct MockMappingServiceImpl {
     /// The server state
     pub(crate) state: Arc<Mutex<MappingState>>,
 }
 
-#[async_trait]
-impl MappingService for MockMappingServiceImpl {
-    /// Check for any updates to the mapping.
-    ///
-    /// # Arguments
-    /// - `request`: the request
-    async fn check_for_work(
-        &self,
-        _request: Request<CheckForWorkRequest>,
-    ) -> Result<Response<CheckForWorkResponse>, Status> {
+impl MockMappingServiceImpl {
+    /// Consumes any pending work
+    fn consume_work(&self) -> CheckForWorkResponse {
         info!("Check for work called");
         let mut state = self.state.lock().unwrap();
         let has_work = state.pending_work;
@@ -40,7 +41,34 @@ impl MappingService for MockMappingServiceImpl {
 
         state.pending_work = false;
 
-        Ok(Response::new(CheckForWorkResponse { has_work }))
+        CheckForWorkResponse { has_work }
+    }
+
+    /// Gets the mappings which are currently served, converted to the entries of a mapping service version
+    fn active_mapping<T: From<DigitalTwinMapEntry>>(&self) -> HashMap<String, T> {
+        info!("Get mapping called");
+        let state = self.state.lock().unwrap();
+        state
+            .config
+            .values
+            .iter()
+            .filter(|c| is_active(c, state.count, state.interactive))
+            .map(|c| (c.value.source.clone(), c.value.clone().into()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl MappingService for MockMappingServiceImpl {
+    /// Check for any updates to the mapping.
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn check_for_work(
+        &self,
+        _request: Request<CheckForWorkRequest>,
+    ) -> Result<Response<CheckForWorkResponse>, Status> {
+        Ok(Response::new(self.consume_work()))
     }
 
     /// Get the latest mapping.
@@ -51,31 +79,35 @@ impl MappingService for MockMappingServiceImpl {
         &self,
         _request: Request<GetMappingRequest>,
     ) -> Result<Response<GetMappingResponse>, Status> {
-        info!("Get mapping called");
-        let state = self.state.lock().unwrap();
-        let response = GetMappingResponse {
-            mapping: state
-                .config
-                .values
-                .iter()
-                .filter_map(|c| {
-                    if !state.interactive {
-                        Some((c.value.source.clone(), c.value.clone().into()))
-                    } else {
-                        match c.end {
-                            Some(end) if state.count >= c.begin && state.count < end => {
-                                Some((c.value.source.clone(), c.value.clone().into()))
-                            }
-                            None if state.count >= c.begin => {
-                                Some((c.value.source.clone(), c.value.clone().into()))
-                            }
-                            _ => None,
-                        }
-                    }
-                })
-                .collect(),
-        };
+        Ok(Response::new(GetMappingResponse {
+            mapping: self.active_mapping(),
+        }))
+    }
+}
 
-        Ok(Response::new(response))
+#[async_trait]
+impl v2::mapping_service_server::MappingService for MockMappingServiceImpl {
+    /// Check for any updates to the mapping.
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn check_for_work(
+        &self,
+        _request: Request<CheckForWorkRequest>,
+    ) -> Result<Response<CheckForWorkResponse>, Status> {
+        Ok(Response::new(self.consume_work()))
+    }
+
+    /// Get the latest mapping, including the metadata of each entry.
+    ///
+    /// # Arguments
+    /// - `request`: the request
+    async fn get_mapping(
+        &self,
+        _request: Request<GetMappingRequest>,
+    ) -> Result<Response<v2::GetMappingResponse>, Status> {
+        Ok(Response::new(v2::GetMappingResponse {
+            mapping: self.active_mapping(),
+        }))
     }
 }
@@ -0,0 +1,71 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::process::DataAdapterFactoryOutput;
+use crate::data_adapter_factory::parse::DataAdapterFactoryArgs;
+
+/// Generate code for the DataAdapterFactory derive macro
+///
+/// # Arguments
+///
+/// - `ir`: the intermediate representation of the output
+pub(crate) fn generate(ir: DataAdapterFactoryOutput) -> TokenStream {
+    let DataAdapterFactoryOutput {
+        args:
+            DataAdapterFactoryArgs {
+                name,
+                generics,
+                adapter,
+                protocols,
+                operations,
+            },
+    } = ir;
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics freyja_common::data_adapter::DataAdapterFactory for #name #ty_generics #where_clause {
+            /// Creates a new instance of this factory
+            fn create_new() -> Result<Self, freyja_common::data_adapter::DataAdapterError> {
+                Ok(Self {})
+            }
+
+            /// Check to see whether this factory can create a data adapter for the requested entity.
+            /// Returns the first endpoint found that is supported by this factory.
+            ///
+            /// # Arguments
+            /// - `entity`: the entity to check for compatibility
+            fn is_supported(
+                &self,
+                entity: &freyja_common::entity::Entity,
+            ) -> Option<freyja_common::entity::EntityEndpoint> {
+                entity.is_supported(&[#(#protocols),*], &[#(#operations),*])
+            }
+
+            /// Create a new data adapter
+            ///
+            /// # Arguments
+            /// - `provider_uri`: the provider URI to associate with this adapter
+            /// - `signals`: the shared signal store
+            fn create_adapter(
+                &self,
+                provider_uri: &str,
+                signals: std::sync::Arc<freyja_common::signal_store::SignalStore>,
+            ) -> Result<
+                std::sync::Arc<dyn freyja_common::data_adapter::DataAdapter + Send + Sync>,
+                freyja_common::data_adapter::DataAdapterError,
+            > {
+                let adapter = <#adapter as freyja_common::data_adapter::DataAdapter>::create_new(
+                    provider_uri,
+                    signals,
+                )?;
+
+                Ok(std::sync::Arc::new(adapter))
+            }
+        }
+    }
+}
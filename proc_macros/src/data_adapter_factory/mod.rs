@@ -0,0 +1,24 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+mod generate;
+mod parse;
+mod process;
+
+use proc_macro2::TokenStream;
+
+use generate::generate;
+use parse::parse;
+use process::process;
+
+/// Implements the DataAdapterFactory derive macro
+///
+/// # Arguments:
+///
+/// - `ts`: The token stream input
+pub fn data_adapter_factory(ts: TokenStream) -> TokenStream {
+    let args = parse(ts);
+    let ir = process(args);
+    generate(ir)
+}
@@ -0,0 +1,197 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use proc_macro2::TokenStream;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    bracketed, punctuated::Punctuated, Data, DeriveInput, Error, Expr, Generics, Ident, Path, Token,
+};
+
+/// The name of the attribute which declares the factory's adapter, protocols, and operations
+const ATTRIBUTE_NAME: &str = "data_adapter_factory";
+
+/// Parse input for the DataAdapterFactory derive macro
+///
+/// # Arguments
+///
+/// - `ts`: the input token stream
+pub(crate) fn parse(ts: TokenStream) -> DataAdapterFactoryArgs {
+    syn::parse2::<DataAdapterFactoryArgs>(ts).unwrap()
+}
+
+/// Arguments to the DataAdapterFactory derive macro
+#[derive(Debug)]
+pub(crate) struct DataAdapterFactoryArgs {
+    /// The name of the factory type
+    pub name: Ident,
+    /// The generics of the factory type
+    pub generics: Generics,
+    /// The type of the data adapter that the factory creates
+    pub adapter: Path,
+    /// The protocols that the factory supports
+    pub protocols: Vec<Expr>,
+    /// The operations that the factory supports
+    pub operations: Vec<Expr>,
+}
+
+impl Parse for DataAdapterFactoryArgs {
+    /// Parses the input stream into `DataAdapterFactoryArgs`
+    ///
+    /// # Arguments
+    ///
+    /// - `input`: the input stream
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let input = input.parse::<DeriveInput>()?;
+
+        // The factory is created without any state, so it can't have any fields
+        let has_fields = match &input.data {
+            Data::Struct(data) => !data.fields.is_empty(),
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "DataAdapterFactory can only be derived for structs",
+                ))
+            }
+        };
+
+        if has_fields {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "DataAdapterFactory can only be derived for structs without fields",
+            ));
+        }
+
+        let attribute = input
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident(ATTRIBUTE_NAME))
+            .ok_or_else(|| {
+                Error::new_spanned(
+                    &input.ident,
+                    format!("Missing #[{ATTRIBUTE_NAME}(adapter = ..., protocols = [...], operations = [...])] attribute"),
+                )
+            })?;
+
+        let mut adapter = None;
+        let mut protocols = None;
+        let mut operations = None;
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("adapter") {
+                adapter = Some(meta.value()?.parse::<Path>()?);
+            } else if meta.path.is_ident("protocols") {
+                protocols = Some(parse_list(meta.value()?)?);
+            } else if meta.path.is_ident("operations") {
+                operations = Some(parse_list(meta.value()?)?);
+            } else {
+                return Err(meta.error("Expected `adapter`, `protocols`, or `operations`"));
+            }
+
+            Ok(())
+        })?;
+
+        let missing = |key: &str| Error::new_spanned(attribute, format!("Missing `{key}`"));
+
+        Ok(DataAdapterFactoryArgs {
+            name: input.ident,
+            generics: input.generics,
+            adapter: adapter.ok_or_else(|| missing("adapter"))?,
+            protocols: protocols.ok_or_else(|| missing("protocols"))?,
+            operations: operations.ok_or_else(|| missing("operations"))?,
+        })
+    }
+}
+
+/// Parses a bracketed list of expressions such as `[A, B]`
+///
+/// # Arguments
+///
+/// - `input`: the input stream
+fn parse_list(input: ParseStream) -> syn::Result<Vec<Expr>> {
+    let content;
+    let _ = bracketed!(content in input);
+    let list = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+    Ok(list.into_iter().collect())
+}
+
+#[cfg(test)]
+mod data_adapter_factory_parse_tests {
+    use quote::{format_ident, quote};
+    use std::panic::catch_unwind;
+
+    use super::*;
+
+    #[test]
+    fn can_parse_attribute() {
+        let input = quote! {
+            #[data_adapter_factory(
+                adapter = my_crate::MyDataAdapter,
+                protocols = [MY_PROTOCOL, "other"],
+                operations = [GET_OPERATION]
+            )]
+            pub struct MyDataAdapterFactory {}
+        };
+
+        let output = parse(input);
+
+        assert_eq!(output.name, format_ident!("MyDataAdapterFactory"));
+        assert_eq!(
+            output.adapter,
+            syn::parse2::<Path>(quote! { my_crate::MyDataAdapter }).unwrap()
+        );
+        assert_eq!(
+            output.protocols,
+            vec![
+                syn::parse2::<Expr>(quote! { MY_PROTOCOL }).unwrap(),
+                syn::parse2::<Expr>(quote! { "other" }).unwrap(),
+            ]
+        );
+        assert_eq!(
+            output.operations,
+            vec![syn::parse2::<Expr>(quote! { GET_OPERATION }).unwrap()]
+        );
+    }
+
+    #[test]
+    fn can_parse_unit_struct() {
+        let input = quote! {
+            #[data_adapter_factory(adapter = MyDataAdapter, protocols = [MY_PROTOCOL], operations = [])]
+            pub struct MyDataAdapterFactory;
+        };
+
+        let output = parse(input);
+
+        assert_eq!(output.name, format_ident!("MyDataAdapterFactory"));
+        assert!(output.operations.is_empty());
+    }
+
+    #[test]
+    fn parse_panics_with_invalid_input() {
+        let missing_attribute = quote! { pub struct MyDataAdapterFactory {} };
+        assert!(catch_unwind(|| parse(missing_attribute)).is_err());
+
+        let missing_operations = quote! {
+            #[data_adapter_factory(adapter = MyDataAdapter, protocols = [MY_PROTOCOL])]
+            pub struct MyDataAdapterFactory {}
+        };
+        assert!(catch_unwind(|| parse(missing_operations)).is_err());
+
+        let unknown_key = quote! {
+            #[data_adapter_factory(adapter = MyDataAdapter, protocols = [], operations = [], foo = [])]
+            pub struct MyDataAdapterFactory {}
+        };
+        assert!(catch_unwind(|| parse(unknown_key)).is_err());
+
+        let with_fields = quote! {
+            #[data_adapter_factory(adapter = MyDataAdapter, protocols = [], operations = [])]
+            pub struct MyDataAdapterFactory { config: Config }
+        };
+        assert!(catch_unwind(|| parse(with_fields)).is_err());
+
+        let not_a_struct = quote! {
+            #[data_adapter_factory(adapter = MyDataAdapter, protocols = [], operations = [])]
+            pub enum MyDataAdapterFactory {}
+        };
+        assert!(catch_unwind(|| parse(not_a_struct)).is_err());
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use super::parse::DataAdapterFactoryArgs;
+
+/// Process data for the DataAdapterFactory derive macro
+/// No additional processing is currently necessary for this macro, so the args are just being repackaged.
+///
+/// # Arguments
+///
+/// - `args`: the input arguments
+pub(crate) fn process(args: DataAdapterFactoryArgs) -> DataAdapterFactoryOutput {
+    DataAdapterFactoryOutput { args }
+}
+
+/// An intermediate representation of the DataAdapterFactory derive macro output
+#[derive(Debug)]
+pub(crate) struct DataAdapterFactoryOutput {
+    pub args: DataAdapterFactoryArgs,
+}
@@ -2,8 +2,8 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use proc_macro2::TokenStream;
-use quote::quote;
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, quote_spanned};
 
 use super::process::FreyjaMainOutput;
 use crate::freyja_main::parse::FreyjaMainArgs;
@@ -18,38 +18,164 @@ pub(crate) fn generate(ir: FreyjaMainOutput) -> TokenStream {
         args:
             FreyjaMainArgs {
                 dt_adapter_type,
-                cloud_adapter_type,
-                mapping_adapter_type,
+                cloud_adapter_types,
+                mapping_adapter_types,
                 data_adapter_factory_types,
                 service_discovery_adapter_types,
             },
     } = ir;
 
+    let trait_assertions = [
+        generate_trait_assertions(
+            quote! { freyja::freyja_common::digital_twin_adapter::DigitalTwinAdapter },
+            std::slice::from_ref(&dt_adapter_type),
+        ),
+        generate_trait_assertions(
+            quote! { freyja::freyja_common::cloud_adapter::CloudAdapter },
+            &cloud_adapter_types,
+        ),
+        generate_trait_assertions(
+            quote! { freyja::freyja_common::mapping_adapter::MappingAdapter },
+            &mapping_adapter_types,
+        ),
+        generate_trait_assertions(
+            quote! { freyja::freyja_common::data_adapter::DataAdapterFactory },
+            &data_adapter_factory_types,
+        ),
+        generate_trait_assertions(
+            quote! { freyja::freyja_common::service_discovery_adapter::ServiceDiscoveryAdapter },
+            &service_discovery_adapter_types,
+        ),
+    ];
+
     quote! {
-        #[tokio::main]
-        async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        /// Creates the adapters provided to `freyja_main!`
+        #[allow(clippy::type_complexity)]
+        fn create_freyja_adapters() -> (
+            Vec<freyja::freyja_common::cloud_adapter::CloudAdapterConstructor>,
+            Vec<freyja::freyja_common::mapping_adapter::MappingAdapterConstructor>,
+            Vec<Box<dyn freyja::freyja_common::data_adapter::DataAdapterFactory + Send + Sync>>,
+            Vec<Box<dyn freyja::freyja_common::service_discovery_adapter::ServiceDiscoveryAdapter + Send + Sync>>,
+        ) {
+            use freyja::freyja_common::cloud_adapter::{self, CloudAdapterConstructor};
             use freyja::freyja_common::data_adapter::DataAdapterFactory;
+            use freyja::freyja_common::mapping_adapter::{self, MappingAdapterConstructor};
             use freyja::freyja_common::service_discovery_adapter::ServiceDiscoveryAdapter;
+
+            #(#trait_assertions)*
+
+            let cloud_adapter_constructors: Vec<CloudAdapterConstructor> = vec![
+                #(cloud_adapter::create_boxed::<#cloud_adapter_types> as CloudAdapterConstructor),*
+            ];
+
+            let mapping_adapter_constructors: Vec<MappingAdapterConstructor> = vec![
+                #(mapping_adapter::create_boxed::<#mapping_adapter_types> as MappingAdapterConstructor),*
+            ];
+
             let data_adapter_factories: Vec<Box<dyn DataAdapterFactory + Send + Sync>> = vec![
                 #(Box::new(
-                    #data_adapter_factory_types::create_new()
+                    <#data_adapter_factory_types as DataAdapterFactory>::create_new()
                         .expect(concat!("Could not create ", stringify!(#data_adapter_factory_types)))
                 )),*
             ];
 
             let service_discovery_adapters: Vec<Box<dyn ServiceDiscoveryAdapter + Send + Sync>> = vec![
                 #(Box::new(
-                    #service_discovery_adapter_types::create_new()
+                    <#service_discovery_adapter_types as ServiceDiscoveryAdapter>::create_new()
                         .expect(concat!("Could not create ", stringify!(#service_discovery_adapter_types)))
                 )),*
             ];
 
-            freyja::freyja_main::<
-                #dt_adapter_type,
-                #cloud_adapter_type,
-                #mapping_adapter_type
-            >(data_adapter_factories, service_discovery_adapters)
+            (
+                cloud_adapter_constructors,
+                mapping_adapter_constructors,
+                data_adapter_factories,
+                service_discovery_adapters,
+            )
+        }
+
+        /// Starts Freyja with the adapters provided to `freyja_main!` and returns a handle for managing its lifecycle
+        ///
+        /// # Arguments
+        /// - `args`: the command-line arguments, parsed with `freyja::FREYJA_COMMAND`
+        // The generated main dispatches subcommands with freyja_main instead, so this is only used by host code in the same crate
+        #[allow(dead_code)]
+        async fn start_freyja(
+            args: freyja::freyja_common::cmd_utils::Args,
+        ) -> Result<freyja::FreyjaHandle, Box<dyn std::error::Error + Send + Sync>> {
+            let (cloud_adapter_constructors, mapping_adapter_constructors, data_adapter_factories, service_discovery_adapters) =
+                create_freyja_adapters();
+
+            freyja::start_freyja::<#dt_adapter_type>(
+                args,
+                cloud_adapter_constructors,
+                mapping_adapter_constructors,
+                data_adapter_factories,
+                service_discovery_adapters,
+            )
+            .await
+        }
+
+        #[tokio::main]
+        async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            let args = freyja::FREYJA_COMMAND.parse_env_or_exit();
+            let (cloud_adapter_constructors, mapping_adapter_constructors, data_adapter_factories, service_discovery_adapters) =
+                create_freyja_adapters();
+
+            freyja::freyja_main::<#dt_adapter_type>(
+                args,
+                cloud_adapter_constructors,
+                mapping_adapter_constructors,
+                data_adapter_factories,
+                service_discovery_adapters,
+            )
             .await
         }
     }
 }
+
+/// Generates assertions that each adapter type implements the trait that it's provided as.
+/// An adapter type which doesn't implement its trait then fails to compile with an error which points at the type
+/// in the macro input and names the missing trait, rather than an error about a missing `create_new` function.
+///
+/// # Arguments
+///
+/// - `trait_path`: the path of the adapter trait
+/// - `types`: the adapter types
+fn generate_trait_assertions(trait_path: TokenStream, types: &[Ident]) -> TokenStream {
+    let assertions = types.iter().map(|adapter_type| {
+        quote_spanned! {adapter_type.span()=>
+            assert_implements_adapter_trait::<#adapter_type>();
+        }
+    });
+
+    quote! {
+        {
+            fn assert_implements_adapter_trait<T: #trait_path + Send + Sync + 'static>() {}
+            #(#assertions)*
+        }
+    }
+}
+
+#[cfg(test)]
+mod freyja_main_generate_tests {
+    use quote::format_ident;
+
+    use super::*;
+
+    #[test]
+    fn generate_trait_assertions_asserts_each_type() {
+        let types = vec![format_ident!("Foo"), format_ident!("Bar")];
+
+        let output = generate_trait_assertions(quote! { MyTrait }, &types).to_string();
+
+        assert!(output.contains(
+            "fn assert_implements_adapter_trait < T : MyTrait + Send + Sync + 'static >"
+        ));
+        for adapter_type in types {
+            assert!(output.contains(&format!(
+                "assert_implements_adapter_trait :: < {adapter_type} > ()"
+            )));
+        }
+    }
+}
@@ -3,8 +3,8 @@
 // SPDX-License-Identifier: MIT
 
 use proc_macro2::TokenStream;
-use syn::bracketed;
 use syn::parse::{Parse, ParseStream};
+use syn::{bracketed, token};
 use syn::{punctuated::Punctuated, Ident, Token};
 
 /// Parse input for the freyja_main! macro
@@ -20,8 +20,8 @@ pub(crate) fn parse(ts: TokenStream) -> FreyjaMainArgs {
 #[derive(Debug)]
 pub(crate) struct FreyjaMainArgs {
     pub dt_adapter_type: Ident,
-    pub cloud_adapter_type: Ident,
-    pub mapping_adapter_type: Ident,
+    pub cloud_adapter_types: Vec<Ident>,
+    pub mapping_adapter_types: Vec<Ident>,
     pub data_adapter_factory_types: Vec<Ident>,
     pub service_discovery_adapter_types: Vec<Ident>,
 }
@@ -35,9 +35,21 @@ impl Parse for FreyjaMainArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let dt_adapter_type = input.parse::<Ident>().unwrap();
         let _ = input.parse::<Token![,]>().unwrap();
-        let cloud_adapter_type = input.parse::<Ident>().unwrap();
+
+        // The cloud adapters can be either a single type or a list of types
+        let cloud_adapter_types = parse_type_or_list(input)?;
+        if cloud_adapter_types.is_empty() {
+            panic!("At least one CloudAdapter is required");
+        }
+
         let _ = input.parse::<Token![,]>().unwrap();
-        let mapping_adapter_type = input.parse::<Ident>().unwrap();
+
+        // The mapping adapters can be either a single type or a list of types
+        let mapping_adapter_types = parse_type_or_list(input)?;
+        if mapping_adapter_types.is_empty() {
+            panic!("At least one MappingAdapter is required");
+        }
+
         let _ = input.parse::<Token![,]>().unwrap();
 
         let data_adapter_content;
@@ -78,14 +90,32 @@ impl Parse for FreyjaMainArgs {
 
         Ok(FreyjaMainArgs {
             dt_adapter_type,
-            cloud_adapter_type,
-            mapping_adapter_type,
+            cloud_adapter_types,
+            mapping_adapter_types,
             data_adapter_factory_types,
             service_discovery_adapter_types,
         })
     }
 }
 
+/// Parses either a single type or a bracketed list of types
+///
+/// # Arguments
+///
+/// - `input`: the input stream
+fn parse_type_or_list(input: ParseStream) -> syn::Result<Vec<Ident>> {
+    if input.peek(token::Bracket) {
+        let content;
+        let _ = bracketed!(content in input);
+        Ok(Punctuated::<Ident, Token![,]>::parse_terminated(&content)
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>())
+    } else {
+        Ok(vec![input.parse::<Ident>().unwrap()])
+    }
+}
+
 #[cfg(test)]
 mod freyja_main_parse_tests {
     use quote::{format_ident, quote};
@@ -105,8 +135,8 @@ mod freyja_main_parse_tests {
         let output = parse(input);
 
         assert_eq!(output.dt_adapter_type, foo_ident);
-        assert_eq!(output.cloud_adapter_type, bar_ident);
-        assert_eq!(output.mapping_adapter_type, baz_ident);
+        assert_eq!(output.cloud_adapter_types, vec![bar_ident.clone()]);
+        assert_eq!(output.mapping_adapter_types, vec![baz_ident.clone()]);
         for ident in factory_idents.iter() {
             assert!(output.data_adapter_factory_types.contains(ident));
         }
@@ -128,8 +158,8 @@ mod freyja_main_parse_tests {
         let output = parse(input);
 
         assert_eq!(output.dt_adapter_type, baz_ident);
-        assert_eq!(output.cloud_adapter_type, foo_ident);
-        assert_eq!(output.mapping_adapter_type, bar_ident);
+        assert_eq!(output.cloud_adapter_types, vec![foo_ident.clone()]);
+        assert_eq!(output.mapping_adapter_types, vec![bar_ident.clone()]);
 
         // Note that this case switched the data adapter factory and service discovery adapter idents
         for ident in service_discovery_idents {
@@ -146,6 +176,63 @@ mod freyja_main_parse_tests {
         }
     }
 
+    #[test]
+    fn can_parse_mapping_adapter_list() {
+        let foo_ident = format_ident!("Foo");
+        let bar_ident = format_ident!("Bar");
+        let mapping_idents = vec![format_ident!("MA1"), format_ident!("MA2")];
+        let factory_idents = vec![format_ident!("DA1"), format_ident!("DA2")];
+        let service_discovery_idents = vec![format_ident!("SDA1"), format_ident!("SDA2")];
+
+        let input = quote! { #foo_ident, #bar_ident, [#(#mapping_idents),*], [#(#factory_idents),*], [#(#service_discovery_idents),*] };
+        let output = parse(input);
+
+        // Order matters for mapping adapters, so we compare the whole list
+        assert_eq!(output.mapping_adapter_types, mapping_idents);
+        for ident in factory_idents.iter() {
+            assert!(output.data_adapter_factory_types.contains(ident));
+        }
+    }
+
+    #[test]
+    fn can_parse_cloud_adapter_list() {
+        let foo_ident = format_ident!("Foo");
+        let baz_ident = format_ident!("Baz");
+        let cloud_idents = vec![format_ident!("CA1"), format_ident!("CA2")];
+        let factory_idents = vec![format_ident!("DA1"), format_ident!("DA2")];
+        let service_discovery_idents = vec![format_ident!("SDA1"), format_ident!("SDA2")];
+
+        let input = quote! { #foo_ident, [#(#cloud_idents),*], #baz_ident, [#(#factory_idents),*], [#(#service_discovery_idents),*] };
+        let output = parse(input);
+
+        assert_eq!(output.cloud_adapter_types, cloud_idents);
+        assert_eq!(output.mapping_adapter_types, vec![baz_ident]);
+    }
+
+    #[test]
+    fn parse_panics_with_empty_cloud_adapter_list() {
+        let foo_ident = format_ident!("Foo");
+        let baz_ident = format_ident!("Baz");
+        let factory_idents = vec![format_ident!("DA1"), format_ident!("DA2")];
+        let service_discovery_idents = vec![format_ident!("SDA1"), format_ident!("SDA2")];
+
+        let input = quote! { #foo_ident, [], #baz_ident, [#(#factory_idents),*], [#(#service_discovery_idents),*] };
+        let result = catch_unwind(|| parse(input));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_panics_with_empty_mapping_adapter_list() {
+        let foo_ident = format_ident!("Foo");
+        let bar_ident = format_ident!("Bar");
+        let factory_idents = vec![format_ident!("DA1"), format_ident!("DA2")];
+        let service_discovery_idents = vec![format_ident!("SDA1"), format_ident!("SDA2")];
+
+        let input = quote! { #foo_ident, #bar_ident, [], [#(#factory_idents),*], [#(#service_discovery_idents),*] };
+        let result = catch_unwind(|| parse(input));
+        assert!(result.is_err());
+    }
+
     #[test]
     fn parse_panics_with_invalid_input() {
         let foo_ident = format_ident!("Foo");
@@ -2,6 +2,7 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+mod data_adapter_factory;
 mod error;
 mod freyja_main;
 mod use_env;
@@ -83,16 +84,32 @@ pub fn error(ts: TokenStream) -> TokenStream {
 ///
 /// *FreyjaMainPredicate*:
 ///
-/// &nbsp;&nbsp;&nbsp;&nbsp;*DigitalTwinAdapterType* `,` *CloudAdapterType* `,` *MappingAdapterType* `, [` *DataAdapterFactoryTypeList* `] , [` *ServiceDiscoveryAdapterTypeList* `]` (`,`)
+/// &nbsp;&nbsp;&nbsp;&nbsp;*DigitalTwinAdapterType* `,` *CloudAdapterTypes* `,` *MappingAdapterTypes* `, [` *DataAdapterFactoryTypeList* `] , [` *ServiceDiscoveryAdapterTypeList* `]` (`,`)
 ///
 /// *DigitalTwinAdapterType*:
 ///
 /// &nbsp;&nbsp;&nbsp;&nbsp;IDENTIFIER
 ///
+/// *CloudAdapterTypes*:
+///
+/// &nbsp;&nbsp;&nbsp;&nbsp;*CloudAdapterType* | `[` *CloudAdapterTypeList* `]`
+///
+/// *CloudAdapterTypeList*:
+///
+/// &nbsp;&nbsp;&nbsp;&nbsp;*CloudAdapterType* (`,` *CloudAdapterTypeList*)
+///
 /// *CloudAdapterType*:
 ///
 /// &nbsp;&nbsp;&nbsp;&nbsp;IDENTIFIER
 ///
+/// *MappingAdapterTypes*:
+///
+/// &nbsp;&nbsp;&nbsp;&nbsp;*MappingAdapterType* | `[` *MappingAdapterTypeList* `]`
+///
+/// *MappingAdapterTypeList*:
+///
+/// &nbsp;&nbsp;&nbsp;&nbsp;*MappingAdapterType* (`,` *MappingAdapterTypeList*)
+///
 /// *MappingAdapterType*:
 ///
 /// &nbsp;&nbsp;&nbsp;&nbsp;IDENTIFIER
@@ -120,7 +137,51 @@ pub fn error(ts: TokenStream) -> TokenStream {
 ///
 /// Order matters for service discovery adapters. They will be registered in the order provided,
 /// so earlier adapters in the list will take precedence over later ones.
+///
+/// Order also matters for mapping adapters. When multiple mapping adapters are provided,
+/// their mappings are merged according to the configured merge policy, which depends on this order.
+///
+/// When multiple cloud adapters are provided, each signal is emitted with the adapters selected by
+/// the `cloud_routes` in Freyja's config, or with every adapter if no route matches.
+///
+/// The generated `main` calls `freyja::freyja_main`, so the resulting binary supports Freyja's subcommands and `--help`.
+/// In addition to `main`, this generates an `async fn start_freyja(args)` which starts Freyja with the provided adapters
+/// and command-line arguments, and returns a `FreyjaHandle`.
 #[proc_macro]
 pub fn freyja_main(ts: TokenStream) -> TokenStream {
     freyja_main::freyja_main(ts.into()).into()
 }
+
+/// Derives an implementation of `DataAdapterFactory` for a struct without fields.
+/// The derived factory supports entities with an endpoint which has one of the declared protocols
+/// and one of the declared operations, and creates adapters with the adapter type's `create_new` function.
+///
+/// # Arguments
+/// - `ts`: a token stream with a struct declaration which has the following attribute:
+///
+/// *DataAdapterFactoryAttribute*:
+///
+/// &nbsp;&nbsp;&nbsp;&nbsp;`#[data_adapter_factory(adapter =` *DataAdapterType* `, protocols = [` *ExpressionList* `], operations = [` *ExpressionList* `])]`
+///
+/// *DataAdapterType*:
+///
+/// &nbsp;&nbsp;&nbsp;&nbsp;[*TypePath*](https://doc.rust-lang.org/reference/paths.html#paths-in-types)
+///
+/// *ExpressionList*:
+///
+/// &nbsp;&nbsp;&nbsp;&nbsp;[*Expression*](https://doc.rust-lang.org/reference/expressions.html) (`,` *ExpressionList*)
+///
+/// The expressions in the lists must evaluate to `freyja_common::entity::Protocol` and `freyja_common::entity::Operation` values, respectively.
+/// The generated code refers to the `freyja_common` crate, so the crate using this macro must depend on it.
+///
+/// # Examples
+///
+/// `#[derive(DataAdapterFactory)]`
+///
+/// `#[data_adapter_factory(adapter = MyDataAdapter, protocols = [Protocol::Http], operations = [Operation::Get, Operation::Subscribe])]`
+///
+/// `pub struct MyDataAdapterFactory {}`
+#[proc_macro_derive(DataAdapterFactory, attributes(data_adapter_factory))]
+pub fn data_adapter_factory(ts: TokenStream) -> TokenStream {
+    data_adapter_factory::data_adapter_factory(ts.into()).into()
+}
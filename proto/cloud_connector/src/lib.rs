@@ -8,6 +8,10 @@ pub use prost_types;
 pub mod v1 {
     use std::collections::HashMap;
 
+    use freyja_common::{
+        clock::{Clock, SystemClock},
+        cloud_adapter::{BackfillRequest, CloudMessageResponse},
+    };
     use prost_types::{value::Kind, Timestamp, Value};
     use serde::ser::{Serialize, SerializeStruct, Serializer};
     use time::OffsetDateTime;
@@ -26,7 +30,7 @@ pub mod v1 {
                 state.serialize_field(key, &None::<()>)
             };
 
-            let mut state = serializer.serialize_struct("UpdateDigitalTwinRequest", 3)?;
+            let mut state = serializer.serialize_struct("UpdateDigitalTwinRequest", 4)?;
 
             // Serialize value
             const VALUE_FIELD: &str = "value";
@@ -50,14 +54,25 @@ pub mod v1 {
             match self.timestamp {
                 None => serialize_null_field(&mut state, TIMESTAMP_FIELD)?,
                 Some(Timestamp { seconds, .. }) => {
-                    let timestamp = OffsetDateTime::from_unix_timestamp(seconds).unwrap();
-                    state.serialize_field(TIMESTAMP_FIELD, &timestamp)?;
+                    match OffsetDateTime::from_unix_timestamp(seconds) {
+                        Ok(timestamp) => state.serialize_field(TIMESTAMP_FIELD, &timestamp)?,
+                        // Timestamps which OffsetDateTime can't represent are treated as missing
+                        Err(_) => serialize_null_field(&mut state, TIMESTAMP_FIELD)?,
+                    }
                 }
             }
 
             // Serialize metadata
             state.serialize_field("metadata", &self.metadata)?;
 
+            // Serialize the idempotency key, which is empty if the request doesn't have one
+            const IDEMPOTENCY_KEY_FIELD: &str = "idempotency_key";
+            if self.idempotency_key.is_empty() {
+                serialize_null_field(&mut state, IDEMPOTENCY_KEY_FIELD)?;
+            } else {
+                state.serialize_field(IDEMPOTENCY_KEY_FIELD, &self.idempotency_key)?;
+            }
+
             // End serialization
             state.end()
         }
@@ -155,12 +170,17 @@ pub mod v1 {
             self
         }
 
-        /// Set the request timestamp to the current time
-        pub fn timestamp_now(mut self) -> Self {
-            let timestamp = OffsetDateTime::now_utc();
-            self = self.timestamp_offset(timestamp);
+        /// Set the request timestamp to the current system time
+        pub fn timestamp_now(self) -> Self {
+            self.timestamp_from_clock(&SystemClock)
+        }
 
-            self
+        /// Set the request timestamp to the current time of the provided clock
+        ///
+        /// # Arguments
+        /// - `clock`: the clock to get the time from
+        pub fn timestamp_from_clock(self, clock: &dyn Clock) -> Self {
+            self.timestamp_offset(clock.now())
         }
 
         /// Set the request metadata. This overwrites any previously set metadata
@@ -172,6 +192,15 @@ pub mod v1 {
             self
         }
 
+        /// Set the request idempotency key
+        ///
+        /// # Arguments
+        /// - `key`: the key to set
+        pub fn idempotency_key(mut self, key: String) -> Self {
+            self.request.idempotency_key = key;
+            self
+        }
+
         /// Add an entry to the request metadata
         ///
         /// # Arguments
@@ -182,13 +211,61 @@ pub mod v1 {
             self
         }
     }
+
+    impl From<UpdateDigitalTwinResponse> for CloudMessageResponse {
+        fn from(value: UpdateDigitalTwinResponse) -> Self {
+            Self {
+                acknowledgement: None,
+                message_id: Some(value.message_id).filter(|id| !id.is_empty()),
+                // Timestamps which OffsetDateTime can't represent are treated as missing
+                accepted_at: value.accepted_timestamp.and_then(|timestamp| {
+                    OffsetDateTime::from_unix_timestamp_nanos(
+                        i128::from(timestamp.seconds) * 1_000_000_000 + i128::from(timestamp.nanos),
+                    )
+                    .ok()
+                }),
+                warnings: value.warnings,
+            }
+        }
+    }
+
+    impl From<CheckForBackfillResponse> for BackfillRequest {
+        fn from(value: CheckForBackfillResponse) -> Self {
+            Self {
+                signal_ids: value.signal_ids.into_iter().collect(),
+                all_signals: value.all_signals,
+                flight_recorder: value.flight_recorder,
+            }
+        }
+    }
+
+    impl From<BackfillRequest> for CheckForBackfillResponse {
+        fn from(value: BackfillRequest) -> Self {
+            Self {
+                signal_ids: value.signal_ids.into_iter().collect(),
+                all_signals: value.all_signals,
+                flight_recorder: value.flight_recorder,
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod cloud_connector_tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use freyja_common::clock::ManualClock;
+    use proptest::prelude::*;
+    use prost::Message;
+    use prost_types::{value::Kind, ListValue, Timestamp};
     use serde_json::{json, Map, Value};
+    use time::OffsetDateTime;
+
+    use freyja_common::cloud_adapter::CloudMessageResponse;
 
-    use crate::v1::{UpdateDigitalTwinRequest, UpdateDigitalTwinRequestBuilder};
+    use crate::v1::{
+        UpdateDigitalTwinRequest, UpdateDigitalTwinRequestBuilder, UpdateDigitalTwinResponse,
+    };
 
     fn serialize_round_trip(request: &UpdateDigitalTwinRequest) -> Value {
         let serialize_result = serde_json::to_string(&request);
@@ -252,6 +329,20 @@ mod cloud_connector_tests {
         assert_eq!(result["value"], Value::String(s.into()));
     }
 
+    #[test]
+    fn test_serialize_out_of_range_timestamp() {
+        let request = UpdateDigitalTwinRequestBuilder::new()
+            .timestamp(Timestamp {
+                seconds: i64::MAX,
+                nanos: 0,
+            })
+            .build();
+
+        let result = serialize_round_trip(&request);
+
+        assert_eq!(result["timestamp"], Value::Null);
+    }
+
     #[test]
     fn test_serialize_no_timestamp() {
         let request = UpdateDigitalTwinRequestBuilder::new().build();
@@ -272,6 +363,16 @@ mod cloud_connector_tests {
         assert_ne!(result["timestamp"], Value::Null);
     }
 
+    #[test]
+    fn test_timestamp_from_clock() {
+        let clock = ManualClock::new(OffsetDateTime::UNIX_EPOCH + Duration::from_secs(42));
+        let request = UpdateDigitalTwinRequestBuilder::new()
+            .timestamp_from_clock(&clock)
+            .build();
+
+        assert_eq!(request.timestamp.unwrap().seconds, 42);
+    }
+
     #[test]
     fn test_serialize_no_metadata() {
         let request = UpdateDigitalTwinRequestBuilder::new().build();
@@ -294,4 +395,127 @@ mod cloud_connector_tests {
         map.insert(metadata.0.into(), Value::String(metadata.1.into()));
         assert_eq!(result["metadata"], Value::Object(map));
     }
+
+    #[test]
+    fn test_serialize_idempotency_key() {
+        let request = UpdateDigitalTwinRequestBuilder::new().build();
+        let result = serialize_round_trip(&request);
+        assert_eq!(result["idempotency_key"], Value::Null);
+
+        let request = UpdateDigitalTwinRequestBuilder::new()
+            .idempotency_key(String::from("speed@0"))
+            .build();
+        let result = serialize_round_trip(&request);
+        assert_eq!(result["idempotency_key"], json!("speed@0"));
+    }
+
+    #[test]
+    fn test_convert_response() {
+        let response: CloudMessageResponse = UpdateDigitalTwinResponse {
+            message_id: String::from("msg-1"),
+            accepted_timestamp: Some(Timestamp {
+                seconds: 1_704_164_645,
+                nanos: 500_000_000,
+            }),
+            warnings: vec![String::from("unknown metadata key")],
+        }
+        .into();
+
+        assert_eq!(response.message_id, Some(String::from("msg-1")));
+        assert_eq!(
+            response.accepted_at,
+            Some(
+                OffsetDateTime::from_unix_timestamp(1_704_164_645).unwrap()
+                    + Duration::from_millis(500)
+            )
+        );
+        assert_eq!(
+            response.warnings,
+            vec![String::from("unknown metadata key")]
+        );
+
+        let response: CloudMessageResponse = UpdateDigitalTwinResponse::default().into();
+        assert_eq!(response.message_id, None);
+        assert_eq!(response.accepted_at, None);
+    }
+
+    #[test]
+    fn test_decode_non_utf8_metadata() {
+        let mut encoded = UpdateDigitalTwinRequestBuilder::new()
+            .add_metadata(String::from("vin"), String::from("1"))
+            .build()
+            .encode_to_vec();
+        let position = encoded.iter().position(|b| *b == b'v').unwrap();
+        encoded[position] = 0xFF;
+
+        assert!(UpdateDigitalTwinRequest::decode(encoded.as_slice()).is_err());
+    }
+
+    /// Generates any request that Freyja could send,
+    /// including ones with NaN or infinite numbers, unsupported kinds of values, and out-of-range timestamps
+    fn any_request() -> impl Strategy<Value = UpdateDigitalTwinRequest> {
+        let kind = prop_oneof![
+            Just(None),
+            Just(Some(Kind::NullValue(0))),
+            prop::num::f64::ANY.prop_map(|n| Some(Kind::NumberValue(n))),
+            any::<String>().prop_map(|s| Some(Kind::StringValue(s))),
+            any::<bool>().prop_map(|b| Some(Kind::BoolValue(b))),
+            Just(Some(Kind::ListValue(ListValue::default()))),
+        ];
+
+        (
+            prop::option::of(kind),
+            prop::option::of((any::<i64>(), any::<i32>())),
+            any::<HashMap<String, String>>(),
+            any::<String>(),
+        )
+            .prop_map(|(kind, timestamp, metadata, idempotency_key)| {
+                UpdateDigitalTwinRequest {
+                    value: kind.map(|kind| prost_types::Value { kind }),
+                    timestamp: timestamp.map(|(seconds, nanos)| Timestamp { seconds, nanos }),
+                    metadata,
+                    idempotency_key,
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_serialize_any_request(request in any_request()) {
+            let result = serialize_round_trip(&request);
+
+            match request.value.as_ref().and_then(|v| v.kind.as_ref()) {
+                Some(Kind::NumberValue(n)) if n.is_finite() => {
+                    prop_assert!(result["value"].is_number())
+                }
+                Some(Kind::StringValue(s)) => prop_assert_eq!(&result["value"], &json!(s)),
+                Some(Kind::BoolValue(b)) => prop_assert_eq!(&result["value"], &json!(b)),
+                // JSON can't represent NaN or infinite numbers, and other kinds of values aren't supported
+                _ => prop_assert_eq!(&result["value"], &Value::Null),
+            }
+
+            match request.timestamp {
+                Some(Timestamp { seconds, .. }) => prop_assert_eq!(
+                    result["timestamp"].is_null(),
+                    OffsetDateTime::from_unix_timestamp(seconds).is_err()
+                ),
+                None => prop_assert_eq!(&result["timestamp"], &Value::Null),
+            }
+
+            prop_assert_eq!(&result["metadata"], &json!(request.metadata));
+
+            if request.idempotency_key.is_empty() {
+                prop_assert_eq!(&result["idempotency_key"], &Value::Null);
+            } else {
+                prop_assert_eq!(&result["idempotency_key"], &json!(request.idempotency_key));
+            }
+        }
+
+        #[test]
+        fn test_decode_and_serialize_any_bytes(bytes in any::<Vec<u8>>()) {
+            if let Ok(request) = UpdateDigitalTwinRequest::decode(bytes.as_slice()) {
+                prop_assert!(serde_json::to_string(&request).is_ok());
+            }
+        }
+    }
 }
@@ -9,9 +9,16 @@
 pub const GITHUB_BASE_URL: &str = "https://raw.githubusercontent.com";
 
 pub mod ibeji {
+    pub const NAME: &str = "ibeji";
     pub const REPO_NAME: &str = "eclipse-ibeji/ibeji";
+
+    /// The default interface version, which is used by Freyja's adapters
     pub const VERSION: &str = "0.1.1";
 
+    /// The interface versions which can also be generated side by side with the default version.
+    /// Each version is selected with a cargo feature of the proto crates, such as `ibeji-0_1_0`
+    pub const SUPPORTED_VERSIONS: &[&str] = &["0.1.0", "0.1.1"];
+
     pub mod interfaces {
         pub const INVEHICLE_DIGITAL_TWIN_INTERFACE: &str =
             "interfaces/invehicle_digital_twin/v1/invehicle_digital_twin.proto";
@@ -25,9 +32,16 @@ pub mod ibeji {
 }
 
 pub mod chariott {
+    pub const NAME: &str = "chariott";
     pub const REPO_NAME: &str = "eclipse-chariott/chariott";
+
+    /// The default interface version, which is used by Freyja's adapters
     pub const VERSION: &str = "0.2.1";
 
+    /// The interface versions which can also be generated side by side with the default version.
+    /// Each version is selected with a cargo feature of the proto crates, such as `chariott-0_2_1`
+    pub const SUPPORTED_VERSIONS: &[&str] = &["0.2.1"];
+
     pub mod interfaces {
         pub const SERVICE_REGISTRY_INTERFACE: &str =
             "service_discovery/proto/core/v1/service_registry.proto";
@@ -39,15 +53,39 @@ pub mod chariott {
 /// # Arguments
 /// - `service`: the service name. Corresponds to one of the submodules of `proto_common`.
 /// - `interface`: the interface name. Corresponds to one of the constants in the `interfaces` sub-module of the `service` module.
+/// - `version`: the interface version. This is optional and defaults to the `VERSION` constant of the `service` module.
 #[macro_export]
 macro_rules! interface_url {
     ($service:ident, $interface:ident) => {
+        proto_common::interface_url!($service, $interface, proto_common::$service::VERSION)
+    };
+    ($service:ident, $interface:ident, $version:expr) => {
         format!(
             "{}/{}/{}/{}",
             proto_common::GITHUB_BASE_URL,
             proto_common::$service::REPO_NAME,
-            proto_common::$service::VERSION,
+            $version,
             proto_common::$service::interfaces::$interface
         )
     };
 }
+
+/// Macro that includes the code generated for a specific interface version with `compile_versioned_remote_proto`.
+///
+/// # Arguments
+/// - `version_module`: the name of the module for the interface version, such as `ibeji_v0_1_0`.
+/// This must be a literal and match the name returned by `version_module_name` in the build script.
+/// - `package`: the proto package name.
+#[macro_export]
+macro_rules! include_versioned_proto {
+    ($version_module:literal, $package:literal) => {
+        include!(concat!(
+            env!("OUT_DIR"),
+            "/",
+            $version_module,
+            "/",
+            $package,
+            ".rs"
+        ));
+    };
+}
@@ -2,25 +2,46 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use freyja_build_common::{compile_remote_proto, SERDE_DERIVE_ATTRIBUTE};
-use proto_common::interface_url;
+use freyja_build_common::{
+    compile_remote_proto, compile_versioned_remote_proto, selected_versions, version_module_name,
+    SERDE_DERIVE_ATTRIBUTE,
+};
+use proto_common::{ibeji, interface_url};
+
+const INVEHICLE_DIGITAL_TWIN_ATTRIBUTES: &[(&str, &str)] = &[
+    ("EndpointInfo", SERDE_DERIVE_ATTRIBUTE),
+    ("EntityAccessInfo", SERDE_DERIVE_ATTRIBUTE),
+];
+
+const MANAGED_SUBSCRIBE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("Constraint", SERDE_DERIVE_ATTRIBUTE),
+    ("CallbackPayload", SERDE_DERIVE_ATTRIBUTE),
+    ("SubscriptionInfo", SERDE_DERIVE_ATTRIBUTE),
+];
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     compile_remote_proto(
         interface_url!(ibeji, INVEHICLE_DIGITAL_TWIN_INTERFACE),
-        &[
-            ("EndpointInfo", SERDE_DERIVE_ATTRIBUTE),
-            ("EntityAccessInfo", SERDE_DERIVE_ATTRIBUTE),
-        ],
+        INVEHICLE_DIGITAL_TWIN_ATTRIBUTES,
     )?;
     compile_remote_proto(
         interface_url!(ibeji, MANAGED_SUBSCRIBE_INTERFACE),
-        &[
-            ("Constraint", SERDE_DERIVE_ATTRIBUTE),
-            ("CallbackPayload", SERDE_DERIVE_ATTRIBUTE),
-            ("SubscriptionInfo", SERDE_DERIVE_ATTRIBUTE),
-        ],
+        MANAGED_SUBSCRIBE_ATTRIBUTES,
     )?;
 
+    for version in selected_versions(ibeji::NAME, ibeji::SUPPORTED_VERSIONS) {
+        let version_module = version_module_name(ibeji::NAME, version);
+        compile_versioned_remote_proto(
+            interface_url!(ibeji, INVEHICLE_DIGITAL_TWIN_INTERFACE, version),
+            &version_module,
+            INVEHICLE_DIGITAL_TWIN_ATTRIBUTES,
+        )?;
+        compile_versioned_remote_proto(
+            interface_url!(ibeji, MANAGED_SUBSCRIBE_INTERFACE, version),
+            &version_module,
+            MANAGED_SUBSCRIBE_ATTRIBUTES,
+        )?;
+    }
+
     Ok(())
 }
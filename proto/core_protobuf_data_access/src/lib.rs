@@ -18,3 +18,35 @@ pub mod module {
         }
     }
 }
+
+/// Declares the modules for the interfaces of a specific Ibeji version,
+/// with the same layout as the modules for the default version
+macro_rules! ibeji_interfaces {
+    ($version_module:literal) => {
+        pub mod invehicle_digital_twin {
+            pub mod v1 {
+                proto_common::include_versioned_proto!($version_module, "invehicle_digital_twin");
+            }
+        }
+
+        pub mod module {
+            pub mod managed_subscribe {
+                pub mod v1 {
+                    proto_common::include_versioned_proto!($version_module, "managed_subscribe");
+                }
+            }
+        }
+    };
+}
+
+/// The interfaces of Ibeji 0.1.0
+#[cfg(feature = "ibeji-0_1_0")]
+pub mod ibeji_v0_1_0 {
+    ibeji_interfaces!("ibeji_v0_1_0");
+}
+
+/// The interfaces of Ibeji 0.1.1
+#[cfg(feature = "ibeji-0_1_1")]
+pub mod ibeji_v0_1_1 {
+    ibeji_interfaces!("ibeji_v0_1_1");
+}
@@ -0,0 +1,12 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure().compile(
+        &["../../interfaces/digital_twin_provider/v2/digital_twin_provider.proto"],
+        &["../../interfaces/digital_twin_provider/v2/"],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+// Re-export this library so consumers have access to the types used in generation
+pub use prost_types;
+
+pub mod v2 {
+    tonic::include_proto!("digital_twin_provider.v2");
+}
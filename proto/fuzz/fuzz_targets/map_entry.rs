@@ -0,0 +1,21 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+#![no_main]
+
+use freyja_common::{digital_twin_map_entry::DigitalTwinMapEntry, mapping_adapter};
+use libfuzzer_sys::fuzz_target;
+use mapping_service_proto::v1::{GetMappingResponse, MapEntry};
+use prost::Message;
+
+fuzz_target!(|data: &[u8]| {
+    // Anything the mapping service sends must either be rejected by the decoder or convert without panicking
+    if let Ok(response) = GetMappingResponse::decode(data) {
+        let mapping = mapping_adapter::GetMappingResponse::from(response);
+        for entry in mapping.map.into_values() {
+            let encoded = MapEntry::from(entry).encode_to_vec();
+            let _ = DigitalTwinMapEntry::from(MapEntry::decode(encoded.as_slice()).unwrap());
+        }
+    }
+});
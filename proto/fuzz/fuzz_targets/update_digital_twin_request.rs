@@ -0,0 +1,16 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+#![no_main]
+
+use cloud_connector_proto::v1::UpdateDigitalTwinRequest;
+use libfuzzer_sys::fuzz_target;
+use prost::Message;
+
+fuzz_target!(|data: &[u8]| {
+    // Every request which can be decoded must also be serializable to JSON
+    if let Ok(request) = UpdateDigitalTwinRequest::decode(data) {
+        serde_json::to_string(&request).unwrap();
+    }
+});
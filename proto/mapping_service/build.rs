@@ -2,11 +2,46 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
+use std::{env, fs, path::PathBuf};
+
+/// The v1 messages which are shared with v2
+const SHARED_V1_MESSAGES: &[&str] = &[
+    "CheckForWorkRequest",
+    "CheckForWorkResponse",
+    "GetMappingRequest",
+    "LinearConversion",
+    "ThresholdConversion",
+    "AdaptiveInterval",
+    "PrivacyPolicy",
+    "ConversionFailurePolicy",
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure().compile(
-        &["../../interfaces/mapping_service/v1/mapping_service.proto"],
+        &[
+            "../../interfaces/mapping_service/v1/mapping_service.proto",
+            "../../interfaces/mapping_service/v1/mapping_management.proto",
+        ],
         &["../../interfaces/mapping_service/v1/"],
     )?;
 
+    // v2 imports v1, which would be generated again without the v1 management service,
+    // so v2 is generated in its own directory and refers to the v1 module for the shared messages
+    let v2_out_dir = PathBuf::from(env::var("OUT_DIR")?).join("v2");
+    fs::create_dir_all(&v2_out_dir)?;
+
+    let mut v2_builder = tonic_build::configure().out_dir(v2_out_dir);
+    for message in SHARED_V1_MESSAGES {
+        v2_builder = v2_builder.extern_path(
+            format!(".mapping_service.{message}"),
+            format!("crate::v1::{message}"),
+        );
+    }
+
+    v2_builder.compile(
+        &["../../interfaces/mapping_service/v2/mapping_service.proto"],
+        &["../../interfaces/mapping_service/"],
+    )?;
+
     Ok(())
 }
@@ -6,7 +6,16 @@
 pub use prost_types;
 
 pub mod v1 {
-    use freyja_common::{conversion::Conversion, digital_twin_map_entry::DigitalTwinMapEntry};
+    use std::collections::HashMap;
+
+    use freyja_common::{
+        conversion::{Conversion, ConversionFailurePolicy as FreyjaConversionFailurePolicy},
+        digital_twin_map_entry::DigitalTwinMapEntry,
+        privacy::{Noise, PrivacyPolicy as FreyjaPrivacyPolicy},
+        signal::AdaptiveInterval as FreyjaAdaptiveInterval,
+    };
+    use prost_types::Timestamp;
+    use time::OffsetDateTime;
 
     tonic::include_proto!("mapping_service");
 
@@ -72,40 +81,690 @@ pub mod v1 {
 
     impl From<MapEntry> for DigitalTwinMapEntry {
         fn from(value: MapEntry) -> Self {
+            let conversion_failure_policy = value.conversion_failure_policy().into();
+
             Self {
                 source: value.source,
                 target: value.target,
                 interval_ms: value.interval_ms,
                 emit_on_change: value.emit_on_change,
-                conversion: value
-                    .conversion
-                    .map(|c| c.into())
-                    .unwrap_or(Conversion::None),
+                conversion: match (
+                    value.conversion_template,
+                    value.threshold_conversion,
+                    value.conversion,
+                ) {
+                    (Some(template), _, _) => Conversion::Template { template },
+                    (None, Some(c), _) => c.into(),
+                    (None, None, Some(c)) => c.into(),
+                    (None, None, None) => Conversion::None,
+                },
+                conversion_failure_policy,
+                decimal_places: value.decimal_places,
+                namespace: value.namespace,
+                tenant: value.tenant,
+                trigger: value.trigger,
+                adaptive_interval: value.adaptive_interval.map(Into::into),
+                privacy: value.privacy.map(Into::into),
+                not_before: value.not_before.and_then(to_offset_date_time),
+                not_after: value.not_after.and_then(to_offset_date_time),
+                metadata: HashMap::new(),
             }
         }
     }
 
     impl From<DigitalTwinMapEntry> for MapEntry {
+        // The deprecated single precision factors are still set for older versions of Freyja which only read those
+        #[allow(deprecated)]
         fn from(value: DigitalTwinMapEntry) -> Self {
+            let (conversion, conversion_template, threshold_conversion) = match value.conversion {
+                Conversion::None => (None, None, None),
+                Conversion::Linear { mul, offset } => (
+                    Some(LinearConversion {
+                        mul: mul as f32,
+                        offset: offset as f32,
+                        mul_f64: mul,
+                        offset_f64: offset,
+                    }),
+                    None,
+                    None,
+                ),
+                Conversion::Template { template } => (None, Some(template), None),
+                Conversion::Threshold { on, off } => {
+                    (None, None, Some(ThresholdConversion { on, off }))
+                }
+            };
+
             Self {
                 source: value.source,
                 target: value.target,
                 interval_ms: value.interval_ms,
                 emit_on_change: value.emit_on_change,
-                conversion: match value.conversion {
-                    Conversion::None => None,
-                    Conversion::Linear { mul, offset } => Some(LinearConversion { mul, offset }),
-                },
+                conversion,
+                conversion_template,
+                threshold_conversion,
+                conversion_failure_policy: ConversionFailurePolicy::from(
+                    value.conversion_failure_policy,
+                ) as i32,
+                decimal_places: value.decimal_places,
+                namespace: value.namespace,
+                tenant: value.tenant,
+                trigger: value.trigger,
+                adaptive_interval: value.adaptive_interval.map(Into::into),
+                privacy: value.privacy.map(Into::into),
+                not_before: value.not_before.map(to_timestamp),
+                not_after: value.not_after.map(to_timestamp),
+            }
+        }
+    }
+
+    impl From<ConversionFailurePolicy> for FreyjaConversionFailurePolicy {
+        fn from(value: ConversionFailurePolicy) -> Self {
+            match value {
+                ConversionFailurePolicy::EmitRaw => Self::EmitRaw,
+                ConversionFailurePolicy::SkipAndWarn => Self::SkipAndWarn,
+                ConversionFailurePolicy::EmitWithQualityFlag => Self::EmitWithQualityFlag,
+                ConversionFailurePolicy::Error => Self::Error,
+            }
+        }
+    }
+
+    impl From<FreyjaConversionFailurePolicy> for ConversionFailurePolicy {
+        fn from(value: FreyjaConversionFailurePolicy) -> Self {
+            match value {
+                FreyjaConversionFailurePolicy::EmitRaw => Self::EmitRaw,
+                FreyjaConversionFailurePolicy::SkipAndWarn => Self::SkipAndWarn,
+                FreyjaConversionFailurePolicy::EmitWithQualityFlag => Self::EmitWithQualityFlag,
+                FreyjaConversionFailurePolicy::Error => Self::Error,
             }
         }
     }
 
     impl From<LinearConversion> for Conversion {
+        /// Uses the double precision factors unless they're both zero,
+        /// which means that the mapping service only set the deprecated single precision factors
+        #[allow(deprecated)]
         fn from(value: LinearConversion) -> Self {
-            Self::Linear {
-                mul: value.mul,
-                offset: value.offset,
+            if value.mul_f64 != 0.0 || value.offset_f64 != 0.0 {
+                Self::Linear {
+                    mul: value.mul_f64,
+                    offset: value.offset_f64,
+                }
+            } else {
+                Self::Linear {
+                    mul: value.mul.into(),
+                    offset: value.offset.into(),
+                }
+            }
+        }
+    }
+
+    impl From<ThresholdConversion> for Conversion {
+        fn from(value: ThresholdConversion) -> Self {
+            Self::Threshold {
+                on: value.on,
+                off: value.off,
+            }
+        }
+    }
+
+    impl From<AdaptiveInterval> for FreyjaAdaptiveInterval {
+        fn from(value: AdaptiveInterval) -> Self {
+            Self {
+                min_interval_ms: value.min_interval_ms,
+                max_interval_ms: value.max_interval_ms,
+            }
+        }
+    }
+
+    impl From<FreyjaAdaptiveInterval> for AdaptiveInterval {
+        fn from(value: FreyjaAdaptiveInterval) -> Self {
+            Self {
+                min_interval_ms: value.min_interval_ms,
+                max_interval_ms: value.max_interval_ms,
+            }
+        }
+    }
+
+    impl From<PrivacyPolicy> for FreyjaPrivacyPolicy {
+        fn from(value: PrivacyPolicy) -> Self {
+            Self {
+                noise: match (value.laplace_noise_scale, value.uniform_noise_amplitude) {
+                    (Some(scale), _) => Some(Noise::Laplace { scale }),
+                    (None, Some(amplitude)) => Some(Noise::Uniform { amplitude }),
+                    (None, None) => None,
+                },
+                quantization_step: value.quantization_step,
+            }
+        }
+    }
+
+    impl From<FreyjaPrivacyPolicy> for PrivacyPolicy {
+        fn from(value: FreyjaPrivacyPolicy) -> Self {
+            let (laplace_noise_scale, uniform_noise_amplitude) = match value.noise {
+                Some(Noise::Laplace { scale }) => (Some(scale), None),
+                Some(Noise::Uniform { amplitude }) => (None, Some(amplitude)),
+                None => (None, None),
+            };
+
+            Self {
+                laplace_noise_scale,
+                uniform_noise_amplitude,
+                quantization_step: value.quantization_step,
+            }
+        }
+    }
+
+    /// Converts a protobuf timestamp to an `OffsetDateTime`.
+    /// Returns `None` if the timestamp is out of range.
+    ///
+    /// # Arguments
+    /// - `timestamp`: the timestamp to convert
+    fn to_offset_date_time(timestamp: Timestamp) -> Option<OffsetDateTime> {
+        let nanos = i128::from(timestamp.seconds) * 1_000_000_000 + i128::from(timestamp.nanos);
+        OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()
+    }
+
+    /// Converts an `OffsetDateTime` to a protobuf timestamp
+    ///
+    /// # Arguments
+    /// - `time`: the time to convert
+    fn to_timestamp(time: OffsetDateTime) -> Timestamp {
+        Timestamp {
+            seconds: time.unix_timestamp(),
+            nanos: time.nanosecond() as i32,
+        }
+    }
+}
+
+pub mod v2 {
+    use freyja_common::digital_twin_map_entry::DigitalTwinMapEntry;
+
+    use crate::v1;
+
+    // v2 is generated in its own directory since it imports v1
+    include!(concat!(env!("OUT_DIR"), "/v2/mapping_service.v2.rs"));
+
+    impl From<GetMappingResponse> for freyja_common::mapping_adapter::GetMappingResponse {
+        fn from(value: GetMappingResponse) -> Self {
+            Self {
+                map: value
+                    .mapping
+                    .into_iter()
+                    .map(|(k, v)| (k, v.into()))
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<freyja_common::mapping_adapter::GetMappingResponse> for GetMappingResponse {
+        fn from(value: freyja_common::mapping_adapter::GetMappingResponse) -> Self {
+            Self {
+                mapping: value.map.into_iter().map(|(k, v)| (k, v.into())).collect(),
+            }
+        }
+    }
+
+    impl From<MapEntry> for DigitalTwinMapEntry {
+        /// Converts the fields which are shared with v1 like a v1 entry and adds the metadata
+        fn from(value: MapEntry) -> Self {
+            let entry = v1::MapEntry {
+                source: value.source,
+                target: value.target,
+                interval_ms: value.interval_ms,
+                conversion: value.conversion,
+                emit_on_change: value.emit_on_change,
+                conversion_failure_policy: value.conversion_failure_policy,
+                decimal_places: value.decimal_places,
+                conversion_template: value.conversion_template,
+                threshold_conversion: value.threshold_conversion,
+                namespace: value.namespace,
+                not_before: value.not_before,
+                not_after: value.not_after,
+                trigger: value.trigger,
+                adaptive_interval: value.adaptive_interval,
+                tenant: value.tenant,
+                privacy: value.privacy,
+            };
+
+            Self {
+                metadata: value.metadata,
+                ..entry.into()
+            }
+        }
+    }
+
+    impl From<DigitalTwinMapEntry> for MapEntry {
+        /// Converts the fields which are shared with v1 like a v1 entry and adds the metadata
+        fn from(mut value: DigitalTwinMapEntry) -> Self {
+            let metadata = std::mem::take(&mut value.metadata);
+            let entry = v1::MapEntry::from(value);
+
+            Self {
+                source: entry.source,
+                target: entry.target,
+                interval_ms: entry.interval_ms,
+                conversion: entry.conversion,
+                emit_on_change: entry.emit_on_change,
+                conversion_failure_policy: entry.conversion_failure_policy,
+                decimal_places: entry.decimal_places,
+                conversion_template: entry.conversion_template,
+                threshold_conversion: entry.threshold_conversion,
+                namespace: entry.namespace,
+                not_before: entry.not_before,
+                not_after: entry.not_after,
+                trigger: entry.trigger,
+                adaptive_interval: entry.adaptive_interval,
+                tenant: entry.tenant,
+                privacy: entry.privacy,
+                metadata,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod mapping_service_tests {
+    use std::collections::HashMap;
+
+    use freyja_common::{
+        conversion::{Conversion, ConversionFailurePolicy},
+        digital_twin_map_entry::DigitalTwinMapEntry,
+        privacy::{Noise, PrivacyPolicy},
+        signal::AdaptiveInterval,
+    };
+    use proptest::{prelude::*, test_runner::TestCaseError};
+    use prost::Message;
+    use prost_types::Timestamp;
+    use time::{OffsetDateTime, PrimitiveDateTime};
+
+    use crate::{
+        v1::{self, GetMappingResponse, MapEntry},
+        v2,
+    };
+
+    /// Generates any conversion, including ones with NaN, infinite, and subnormal numbers
+    fn any_conversion() -> impl Strategy<Value = Conversion> {
+        prop_oneof![
+            Just(Conversion::None),
+            (prop::num::f64::ANY, prop::num::f64::ANY)
+                .prop_map(|(mul, offset)| Conversion::Linear { mul, offset }),
+            any::<String>().prop_map(|template| Conversion::Template { template }),
+            (prop::num::f64::ANY, prop::num::f64::ANY)
+                .prop_map(|(on, off)| Conversion::Threshold { on, off }),
+        ]
+    }
+
+    fn any_conversion_failure_policy() -> impl Strategy<Value = ConversionFailurePolicy> {
+        prop_oneof![
+            Just(ConversionFailurePolicy::EmitRaw),
+            Just(ConversionFailurePolicy::SkipAndWarn),
+            Just(ConversionFailurePolicy::EmitWithQualityFlag),
+            Just(ConversionFailurePolicy::Error),
+        ]
+    }
+
+    fn any_privacy_policy() -> impl Strategy<Value = PrivacyPolicy> {
+        let noise = prop_oneof![
+            prop::num::f64::ANY.prop_map(|scale| Noise::Laplace { scale }),
+            prop::num::f64::ANY.prop_map(|amplitude| Noise::Uniform { amplitude }),
+        ];
+
+        (
+            prop::option::of(noise),
+            prop::option::of(prop::num::f64::ANY),
+        )
+            .prop_map(|(noise, quantization_step)| PrivacyPolicy {
+                noise,
+                quantization_step,
+            })
+    }
+
+    /// Generates any UTC time that an `OffsetDateTime` can represent
+    fn any_time() -> impl Strategy<Value = OffsetDateTime> {
+        let min = PrimitiveDateTime::MIN.assume_utc().unix_timestamp_nanos();
+        let max = PrimitiveDateTime::MAX.assume_utc().unix_timestamp_nanos();
+        (min..=max).prop_map(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos).unwrap())
+    }
+
+    fn any_digital_twin_map_entry() -> impl Strategy<Value = DigitalTwinMapEntry> {
+        (
+            (
+                any::<String>(),
+                any::<HashMap<String, String>>(),
+                any::<u64>(),
+                any::<bool>(),
+                any_conversion(),
+                any_conversion_failure_policy(),
+                any::<Option<u32>>(),
+            ),
+            (
+                any::<Option<String>>(),
+                any::<Option<String>>(),
+                any::<Option<String>>(),
+                prop::option::of((any::<u64>(), any::<u64>()).prop_map(
+                    |(min_interval_ms, max_interval_ms)| AdaptiveInterval {
+                        min_interval_ms,
+                        max_interval_ms,
+                    },
+                )),
+                prop::option::of(any_privacy_policy()),
+                prop::option::of(any_time()),
+                prop::option::of(any_time()),
+                any::<HashMap<String, String>>(),
+            ),
+        )
+            .prop_map(
+                |(
+                    (
+                        source,
+                        target,
+                        interval_ms,
+                        emit_on_change,
+                        conversion,
+                        conversion_failure_policy,
+                        decimal_places,
+                    ),
+                    (
+                        namespace,
+                        tenant,
+                        trigger,
+                        adaptive_interval,
+                        privacy,
+                        not_before,
+                        not_after,
+                        metadata,
+                    ),
+                )| DigitalTwinMapEntry {
+                    source,
+                    target,
+                    interval_ms,
+                    conversion,
+                    emit_on_change,
+                    conversion_failure_policy,
+                    decimal_places,
+                    namespace,
+                    tenant,
+                    trigger,
+                    adaptive_interval,
+                    privacy,
+                    not_before,
+                    not_after,
+                    metadata,
+                },
+            )
+    }
+
+    /// Generates any timestamp, including ones which are out of range and ones whose nanos aren't normalized
+    fn any_timestamp() -> impl Strategy<Value = Timestamp> {
+        (any::<i64>(), any::<i32>()).prop_map(|(seconds, nanos)| Timestamp { seconds, nanos })
+    }
+
+    /// Generates any linear conversion that the mapping service could send,
+    /// including ones with only the deprecated single precision factors
+    #[allow(deprecated)]
+    fn any_linear_conversion() -> impl Strategy<Value = v1::LinearConversion> {
+        (
+            prop::num::f32::ANY,
+            prop::num::f32::ANY,
+            prop_oneof![Just((0.0, 0.0)), (prop::num::f64::ANY, prop::num::f64::ANY)],
+        )
+            .prop_map(
+                |(mul, offset, (mul_f64, offset_f64))| v1::LinearConversion {
+                    mul,
+                    offset,
+                    mul_f64,
+                    offset_f64,
+                },
+            )
+    }
+
+    /// Generates any map entry that the mapping service could send,
+    /// including ones with several conversions and unknown conversion failure policies
+    fn any_map_entry() -> impl Strategy<Value = MapEntry> {
+        (
+            (
+                any::<String>(),
+                any::<HashMap<String, String>>(),
+                any::<u64>(),
+                prop::option::of(any_linear_conversion()),
+                any::<bool>(),
+                any::<i32>(),
+                any::<Option<u32>>(),
+                any::<Option<String>>(),
+            ),
+            (
+                prop::option::of((prop::num::f64::ANY, prop::num::f64::ANY))
+                    .prop_map(|c| c.map(|(on, off)| v1::ThresholdConversion { on, off })),
+                any::<Option<String>>(),
+                prop::option::of(any_timestamp()),
+                prop::option::of(any_timestamp()),
+                any::<Option<String>>(),
+                prop::option::of((any::<u64>(), any::<u64>())).prop_map(|i| {
+                    i.map(|(min_interval_ms, max_interval_ms)| v1::AdaptiveInterval {
+                        min_interval_ms,
+                        max_interval_ms,
+                    })
+                }),
+                any::<Option<String>>(),
+                prop::option::of(any::<(Option<f64>, Option<f64>, Option<f64>)>()).prop_map(|p| {
+                    p.map(
+                        |(laplace_noise_scale, uniform_noise_amplitude, quantization_step)| {
+                            v1::PrivacyPolicy {
+                                laplace_noise_scale,
+                                uniform_noise_amplitude,
+                                quantization_step,
+                            }
+                        },
+                    )
+                }),
+            ),
+        )
+            .prop_map(
+                |(
+                    (
+                        source,
+                        target,
+                        interval_ms,
+                        conversion,
+                        emit_on_change,
+                        conversion_failure_policy,
+                        decimal_places,
+                        conversion_template,
+                    ),
+                    (
+                        threshold_conversion,
+                        namespace,
+                        not_before,
+                        not_after,
+                        trigger,
+                        adaptive_interval,
+                        tenant,
+                        privacy,
+                    ),
+                )| MapEntry {
+                    source,
+                    target,
+                    interval_ms,
+                    conversion,
+                    emit_on_change,
+                    conversion_failure_policy,
+                    decimal_places,
+                    conversion_template,
+                    threshold_conversion,
+                    namespace,
+                    not_before,
+                    not_after,
+                    trigger,
+                    adaptive_interval,
+                    tenant,
+                    privacy,
+                },
+            )
+    }
+
+    /// Checks that two entries are the same.
+    /// Entries are compared by their debug representations since NaN isn't equal to itself,
+    /// except for their targets because the order of a map's debug representation isn't stable
+    fn prop_assert_same(
+        actual: DigitalTwinMapEntry,
+        expected: DigitalTwinMapEntry,
+    ) -> Result<(), TestCaseError> {
+        prop_assert_eq!(&actual.target, &expected.target);
+        prop_assert_eq!(
+            format!(
+                "{:?}",
+                DigitalTwinMapEntry {
+                    target: HashMap::new(),
+                    ..actual
+                }
+            ),
+            format!(
+                "{:?}",
+                DigitalTwinMapEntry {
+                    target: HashMap::new(),
+                    ..expected
+                }
+            )
+        );
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn digital_twin_map_entry_round_trips(entry in any_digital_twin_map_entry()) {
+            let encoded = v2::MapEntry::from(entry.clone()).encode_to_vec();
+            let decoded = v2::MapEntry::decode(encoded.as_slice()).unwrap();
+
+            prop_assert_same(decoded.into(), entry)?;
+        }
+
+        #[test]
+        fn digital_twin_map_entry_round_trips_through_v1_without_metadata(entry in any_digital_twin_map_entry()) {
+            let encoded = MapEntry::from(entry.clone()).encode_to_vec();
+            let decoded = MapEntry::decode(encoded.as_slice()).unwrap();
+
+            prop_assert_same(
+                decoded.into(),
+                DigitalTwinMapEntry {
+                    metadata: HashMap::new(),
+                    ..entry
+                },
+            )?;
+        }
+
+        #[test]
+        fn map_entry_conversion_is_stable(entry in any_map_entry()) {
+            // Map entries with several conversions or unknown policies lose information when they're converted,
+            // but converting the result again must not change it
+            let converted = DigitalTwinMapEntry::from(entry);
+            let round_tripped = DigitalTwinMapEntry::from(MapEntry::from(converted.clone()));
+
+            prop_assert_same(round_tripped, converted)?;
+        }
+
+        #[test]
+        fn get_mapping_response_decoding_never_panics(bytes in any::<Vec<u8>>()) {
+            if let Ok(response) = GetMappingResponse::decode(bytes.as_slice()) {
+                let _ = freyja_common::mapping_adapter::GetMappingResponse::from(response);
             }
         }
+
+        #[test]
+        fn v2_get_mapping_response_decoding_never_panics(bytes in any::<Vec<u8>>()) {
+            if let Ok(response) = v2::GetMappingResponse::decode(bytes.as_slice()) {
+                let _ = freyja_common::mapping_adapter::GetMappingResponse::from(response);
+            }
+        }
+    }
+
+    #[test]
+    fn map_entry_conversion_drops_out_of_range_timestamps() {
+        let entry = DigitalTwinMapEntry::from(MapEntry {
+            not_before: Some(Timestamp {
+                seconds: i64::MAX,
+                nanos: i32::MAX,
+            }),
+            not_after: Some(Timestamp {
+                seconds: i64::MIN,
+                nanos: i32::MIN,
+            }),
+            ..Default::default()
+        });
+
+        assert!(entry.not_before.is_none());
+        assert!(entry.not_after.is_none());
+    }
+
+    #[test]
+    fn map_entry_conversion_normalizes_negative_nanos() {
+        let entry = DigitalTwinMapEntry::from(MapEntry {
+            not_before: Some(Timestamp {
+                seconds: 1,
+                nanos: -1,
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            entry.not_before,
+            Some(OffsetDateTime::from_unix_timestamp_nanos(999_999_999).unwrap())
+        );
+        assert_eq!(
+            MapEntry::from(entry).not_before,
+            Some(Timestamp {
+                seconds: 0,
+                nanos: 999_999_999
+            })
+        );
+    }
+
+    #[test]
+    fn map_entry_decoding_rejects_non_utf8_strings() {
+        let mut encoded = MapEntry {
+            source: String::from("speed"),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        let position = encoded.iter().position(|b| *b == b's').unwrap();
+        encoded[position] = 0xFF;
+
+        assert!(MapEntry::decode(encoded.as_slice()).is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn linear_conversion_falls_back_to_single_precision_factors() {
+        let conversion = Conversion::from(v1::LinearConversion {
+            mul: 0.5,
+            offset: -32.0,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            conversion,
+            Conversion::Linear {
+                mul: 0.5,
+                offset: -32.0
+            }
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn linear_conversion_sets_both_precisions() {
+        let entry = MapEntry::from(DigitalTwinMapEntry {
+            conversion: Conversion::Linear {
+                mul: 0.1,
+                offset: 1e40,
+            },
+            ..Default::default()
+        });
+        let conversion = entry.conversion.unwrap();
+
+        assert_eq!(conversion.mul, 0.1_f32);
+        assert_eq!(conversion.offset, f32::INFINITY);
+        assert_eq!(conversion.mul_f64, 0.1);
+        assert_eq!(conversion.offset_f64, 1e40);
     }
 }
@@ -2,12 +2,28 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use freyja_build_common::compile_remote_proto;
-use proto_common::interface_url;
+use freyja_build_common::{
+    compile_remote_proto, compile_versioned_remote_proto, selected_versions, version_module_name,
+};
+use proto_common::{ibeji, interface_url};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     compile_remote_proto(interface_url!(ibeji, SAMPLE_CONSUMER_INTERFACE), &[])?;
     compile_remote_proto(interface_url!(ibeji, SAMPLE_PROVIDER_INTERFACE), &[])?;
 
+    for version in selected_versions(ibeji::NAME, ibeji::SUPPORTED_VERSIONS) {
+        let version_module = version_module_name(ibeji::NAME, version);
+        compile_versioned_remote_proto(
+            interface_url!(ibeji, SAMPLE_CONSUMER_INTERFACE, version),
+            &version_module,
+            &[],
+        )?;
+        compile_versioned_remote_proto(
+            interface_url!(ibeji, SAMPLE_PROVIDER_INTERFACE, version),
+            &version_module,
+            &[],
+        )?;
+    }
+
     Ok(())
 }
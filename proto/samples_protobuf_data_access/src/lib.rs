@@ -16,3 +16,39 @@ pub mod sample_grpc {
         }
     }
 }
+
+/// Declares the modules for the sample interfaces of a specific Ibeji version,
+/// with the same layout as the modules for the default version
+macro_rules! ibeji_sample_interfaces {
+    ($version_module:literal) => {
+        pub mod sample_grpc {
+            pub mod v1 {
+                pub mod digital_twin_consumer {
+                    proto_common::include_versioned_proto!(
+                        $version_module,
+                        "digital_twin_consumer"
+                    );
+                }
+
+                pub mod digital_twin_provider {
+                    proto_common::include_versioned_proto!(
+                        $version_module,
+                        "digital_twin_provider"
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// The sample interfaces of Ibeji 0.1.0
+#[cfg(feature = "ibeji-0_1_0")]
+pub mod ibeji_v0_1_0 {
+    ibeji_sample_interfaces!("ibeji_v0_1_0");
+}
+
+/// The sample interfaces of Ibeji 0.1.1
+#[cfg(feature = "ibeji-0_1_1")]
+pub mod ibeji_v0_1_1 {
+    ibeji_sample_interfaces!("ibeji_v0_1_1");
+}
@@ -2,11 +2,21 @@
 // Licensed under the MIT license.
 // SPDX-License-Identifier: MIT
 
-use freyja_build_common::compile_remote_proto;
-use proto_common::interface_url;
+use freyja_build_common::{
+    compile_remote_proto, compile_versioned_remote_proto, selected_versions, version_module_name,
+};
+use proto_common::{chariott, interface_url};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     compile_remote_proto(interface_url!(chariott, SERVICE_REGISTRY_INTERFACE), &[])?;
 
+    for version in selected_versions(chariott::NAME, chariott::SUPPORTED_VERSIONS) {
+        compile_versioned_remote_proto(
+            interface_url!(chariott, SERVICE_REGISTRY_INTERFACE, version),
+            &version_module_name(chariott::NAME, version),
+            &[],
+        )?;
+    }
+
     Ok(())
 }
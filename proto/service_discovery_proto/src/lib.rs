@@ -10,3 +10,13 @@ pub mod service_registry {
         tonic::include_proto!("service_registry");
     }
 }
+
+/// The interfaces of Chariott 0.2.1
+#[cfg(feature = "chariott-0_2_1")]
+pub mod chariott_v0_2_1 {
+    pub mod service_registry {
+        pub mod v1 {
+            proto_common::include_versioned_proto!("chariott_v0_2_1", "service_registry");
+        }
+    }
+}
@@ -0,0 +1,12 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure().compile(
+        &["../../interfaces/signal_service/v1/signal_service.proto"],
+        &["../../interfaces/signal_service/v1/"],
+    )?;
+
+    Ok(())
+}
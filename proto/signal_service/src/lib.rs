@@ -0,0 +1,27 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+// Re-export this library so consumers have access to the types used in generation
+pub use prost_types;
+
+pub mod v1 {
+    use freyja_common::signal::Signal;
+
+    tonic::include_proto!("signal_service");
+
+    impl From<Signal> for SignalValue {
+        fn from(value: Signal) -> Self {
+            Self {
+                id: value.id,
+                value: value.value.map(|value| value.to_string()),
+                timestamp: value
+                    .value_timestamp
+                    .map(|timestamp| prost_types::Timestamp {
+                        seconds: timestamp.unix_timestamp(),
+                        nanos: timestamp.nanosecond() as i32,
+                    }),
+            }
+        }
+    }
+}
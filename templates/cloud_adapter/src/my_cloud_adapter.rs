@@ -0,0 +1,142 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::debug;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    cloud_adapter::{CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse},
+    config_utils, out_dir,
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A cloud adapter which sends signals to a cloud service
+pub struct MyCloudAdapter {
+    /// The adapter config
+    config: Config,
+
+    /// The service discovery adapter selector used to find the cloud service
+    selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+}
+
+impl MyCloudAdapter {
+    /// Creates a new MyCloudAdapter with the specified config
+    ///
+    /// # Arguments
+    /// - `config`: the config to use
+    /// - `selector`: the service discovery adapter selector to use
+    pub fn from_config(
+        config: Config,
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Ok(Self { config, selector })
+    }
+}
+
+#[async_trait]
+impl CloudAdapter for MyCloudAdapter {
+    /// Creates a new instance of a CloudAdapter with default settings
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            CloudAdapterError::io,
+            CloudAdapterError::deserialize,
+        )?;
+
+        Self::from_config(config, selector)
+    }
+
+    /// Sends the signal to the cloud
+    ///
+    /// # Arguments
+    /// - `cloud_message`: represents a message to send to the cloud canonical model
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let uri = self
+            .selector
+            .lock()
+            .await
+            .get_service_uri(&self.config.service_discovery_id)
+            .await
+            .map_err(CloudAdapterError::communication)?;
+
+        // Replace this with a request to the cloud service at `uri`
+        debug!(
+            "Sending value {} to the cloud service at {uri}",
+            cloud_message.signal_value
+        );
+
+        Ok(CloudMessageResponse::default())
+    }
+}
+
+#[cfg(test)]
+mod my_cloud_adapter_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use time::OffsetDateTime;
+
+    use freyja_common::service_discovery_adapter::ServiceDiscoveryAdapterError;
+    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+
+    const SERVICE_DISCOVERY_ID: &str = "cloud_service";
+
+    fn create_adapter(selector: MockServiceDiscoveryAdapterSelector) -> MyCloudAdapter {
+        MyCloudAdapter::from_config(
+            Config {
+                service_discovery_id: SERVICE_DISCOVERY_ID.to_string(),
+            },
+            Arc::new(Mutex::new(selector)),
+        )
+        .unwrap()
+    }
+
+    fn create_message() -> CloudMessageRequest {
+        CloudMessageRequest {
+            metadata: HashMap::new(),
+            signal_value: "72".into(),
+            numeric_value: Some(72.0),
+            signal_timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_resolves_service() {
+        let mut selector = MockServiceDiscoveryAdapterSelector::new();
+        selector
+            .expect_get_service_uri()
+            .returning(|_| Ok(String::from("http://0.0.0.0:8890")));
+        let uut = create_adapter(selector);
+
+        assert!(uut.send_to_cloud(create_message()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_cloud_returns_err_when_service_not_found() {
+        let mut selector = MockServiceDiscoveryAdapterSelector::new();
+        selector
+            .expect_get_service_uri()
+            .returning(|_| Err(ServiceDiscoveryAdapterError::not_found("not found")));
+        let uut = create_adapter(selector);
+
+        assert!(uut.send_to_cloud(create_message()).await.is_err());
+    }
+}
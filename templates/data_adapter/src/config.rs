@@ -0,0 +1,15 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the data adapter template
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The protocol of the entity endpoints that the adapter supports
+    pub protocol: String,
+
+    /// The operations of the entity endpoints that the adapter supports
+    pub operations: Vec<String>,
+}
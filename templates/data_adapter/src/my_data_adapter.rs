@@ -0,0 +1,191 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use log::debug;
+
+use crate::config::Config;
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, EntityRegistration},
+    entity::EntityEndpoint,
+    out_dir,
+    signal_store::SignalStore,
+};
+
+/// A data adapter which gets signal values from a provider
+pub struct MyDataAdapter {
+    /// The uri of the provider
+    provider_uri: String,
+
+    /// The shared signal store, which receives the values from the provider
+    signals: Arc<SignalStore>,
+
+    /// The endpoints of the entities registered with this adapter
+    entities: Mutex<HashMap<String, EntityEndpoint>>,
+}
+
+impl MyDataAdapter {
+    /// Creates a new MyDataAdapter with the specified config
+    ///
+    /// # Arguments
+    /// - `_config`: the config to use
+    /// - `provider_uri`: the provider uri to associate with this adapter
+    /// - `signals`: the shared signal store
+    pub fn from_config(
+        _config: Config,
+        provider_uri: &str,
+        signals: Arc<SignalStore>,
+    ) -> Result<Self, DataAdapterError> {
+        Ok(Self {
+            provider_uri: provider_uri.to_string(),
+            signals,
+            entities: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Sets a value received from the provider in the signal store
+    ///
+    /// # Arguments
+    /// - `entity_id`: the id of the entity that the value belongs to
+    /// - `value`: the value received from the provider
+    pub fn on_value_received(&self, entity_id: &str, value: String) {
+        self.signals.set_value(entity_id.to_string(), value);
+    }
+}
+
+#[async_trait]
+impl DataAdapter for MyDataAdapter {
+    /// Creates a new instance of a DataAdapter with default settings
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri to associate with this adapter
+    /// - `signals`: the shared signal store
+    fn create_new(provider_uri: &str, signals: Arc<SignalStore>) -> Result<Self, DataAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        Self::from_config(config, provider_uri, signals)
+    }
+
+    /// Starts a data adapter
+    async fn start(&self) -> Result<(), DataAdapterError> {
+        // Start any listeners or background tasks which receive values from the provider here
+        Ok(())
+    }
+
+    /// Sends a request to a provider for obtaining the value of an entity
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id that needs a value
+    async fn send_request_to_provider(&self, entity_id: &str) -> Result<(), DataAdapterError> {
+        let endpoint = self
+            .entities
+            .lock()
+            .unwrap()
+            .get(entity_id)
+            .cloned()
+            .ok_or_else(|| {
+                DataAdapterError::entity_not_found(format!("Entity {entity_id} is not registered"))
+            })?;
+
+        // Replace this with a request to the provider, and call `on_value_received` with its response
+        debug!(
+            "Requesting the value of {entity_id} from {} at {}",
+            self.provider_uri, endpoint.uri
+        );
+
+        Ok(())
+    }
+
+    /// Registers an entity id to a local cache inside a data adapter for keeping track of entities
+    ///
+    /// # Arguments
+    /// - `entity_id`: the entity id to add
+    /// - `endpoint`: the endpoint that the entity id is associated with
+    async fn register_entity(
+        &self,
+        entity_id: &str,
+        endpoint: &EntityEndpoint,
+    ) -> Result<EntityRegistration, DataAdapterError> {
+        self.entities
+            .lock()
+            .unwrap()
+            .insert(entity_id.to_string(), endpoint.clone());
+
+        Ok(EntityRegistration::Registered)
+    }
+}
+
+#[cfg(test)]
+mod my_data_adapter_tests {
+    use super::*;
+
+    use freyja_common::{data_adapter::DataAdapterErrorKind, signal::SignalPatch};
+
+    const ENTITY_ID: &str = "dtmi:sdv:HVAC:AmbientAirTemperature;1";
+
+    fn create_adapter() -> MyDataAdapter {
+        let signals = Arc::new(SignalStore::new());
+        signals.add(
+            [SignalPatch {
+                id: ENTITY_ID.to_string(),
+                ..Default::default()
+            }]
+            .into_iter(),
+        );
+
+        MyDataAdapter::from_config(
+            Config {
+                protocol: String::from("my-protocol"),
+                operations: vec![String::from("Get")],
+            },
+            "http://0.0.0.0:8800",
+            signals,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn send_request_to_provider_succeeds_for_registered_entity() {
+        let uut = create_adapter();
+
+        let registration = uut
+            .register_entity(ENTITY_ID, &EntityEndpoint::default())
+            .await;
+        assert_eq!(registration.unwrap(), EntityRegistration::Registered);
+
+        assert!(uut.send_request_to_provider(ENTITY_ID).await.is_ok());
+    }
+
+    #[test]
+    fn on_value_received_sets_value() {
+        let uut = create_adapter();
+
+        uut.on_value_received(ENTITY_ID, String::from("72"));
+
+        let signal = uut.signals.get(&ENTITY_ID.to_string()).unwrap();
+        assert_eq!(signal.value, Some(String::from("72")));
+    }
+
+    #[tokio::test]
+    async fn send_request_to_provider_returns_err_for_unregistered_entity() {
+        let uut = create_adapter();
+
+        let result = uut.send_request_to_provider(ENTITY_ID).await;
+
+        assert!(result.is_err_and(|e| e.kind() == DataAdapterErrorKind::EntityNotFound));
+    }
+}
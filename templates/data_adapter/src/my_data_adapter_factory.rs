@@ -0,0 +1,109 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use crate::{config::Config, my_data_adapter::MyDataAdapter};
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    data_adapter::{DataAdapter, DataAdapterError, DataAdapterFactory},
+    entity::{Entity, EntityEndpoint, Operation, Protocol},
+    out_dir,
+    signal_store::SignalStore,
+};
+
+/// Factory for creating MyDataAdapters
+pub struct MyDataAdapterFactory {
+    /// The adapter config
+    config: Config,
+}
+
+impl MyDataAdapterFactory {
+    /// Creates a new MyDataAdapterFactory with the specified config
+    ///
+    /// # Arguments
+    /// - `config`: the config to use
+    pub fn from_config(config: Config) -> Result<Self, DataAdapterError> {
+        Ok(Self { config })
+    }
+}
+
+impl DataAdapterFactory for MyDataAdapterFactory {
+    /// Creates a new instance of a DataAdapterFactory with default settings
+    fn create_new() -> Result<Self, DataAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DataAdapterError::io,
+            DataAdapterError::deserialize,
+        )?;
+
+        Self::from_config(config)
+    }
+
+    /// Check to see whether this factory can create a data adapter for the requested entity.
+    /// Returns the first endpoint found that is supported by this factory.
+    ///
+    /// # Arguments
+    /// - `entity`: the entity to check for compatibility
+    fn is_supported(&self, entity: &Entity) -> Option<EntityEndpoint> {
+        let operations: Vec<Operation> = self
+            .config
+            .operations
+            .iter()
+            .map(|operation| Operation::from(operation.as_str()))
+            .collect();
+        entity.is_supported(
+            &[Protocol::from(self.config.protocol.as_str())],
+            &operations,
+        )
+    }
+
+    /// Create a new data adapter
+    ///
+    /// # Arguments
+    /// - `provider_uri`: the provider uri to associate with this adapter
+    /// - `signals`: the shared signal store
+    fn create_adapter(
+        &self,
+        provider_uri: &str,
+        signals: Arc<SignalStore>,
+    ) -> Result<Arc<dyn DataAdapter + Send + Sync>, DataAdapterError> {
+        let adapter = MyDataAdapter::from_config(self.config.clone(), provider_uri, signals)?;
+        Ok(Arc::new(adapter))
+    }
+}
+
+#[cfg(test)]
+mod my_data_adapter_factory_tests {
+    use super::*;
+
+    fn create_entity(protocol: &str) -> Entity {
+        Entity {
+            id: String::from("dtmi:sdv:HVAC:AmbientAirTemperature;1"),
+            endpoints: vec![EntityEndpoint {
+                protocol: protocol.to_string(),
+                operations: vec![String::from("Get")],
+                uri: String::from("http://0.0.0.0:8800"),
+                context: String::new(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn is_supported_matches_configured_protocol() {
+        let uut = MyDataAdapterFactory::from_config(Config {
+            protocol: String::from("my-protocol"),
+            operations: vec![String::from("Get")],
+        })
+        .unwrap();
+
+        assert!(uut.is_supported(&create_entity("my-protocol")).is_some());
+        assert!(uut.is_supported(&create_entity("other-protocol")).is_none());
+    }
+}
@@ -0,0 +1,12 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the digital twin adapter template
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The service discovery id of the service that the adapter communicates with
+    pub service_discovery_id: String,
+}
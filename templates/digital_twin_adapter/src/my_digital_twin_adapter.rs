@@ -0,0 +1,145 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::debug;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    digital_twin_adapter::{
+        DigitalTwinAdapter, DigitalTwinAdapterError, FindByIdRequest, FindByIdResponse,
+    },
+    out_dir,
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A digital twin adapter which finds entities in a digital twin service
+pub struct MyDigitalTwinAdapter {
+    /// The adapter config
+    config: Config,
+
+    /// The service discovery adapter selector used to find the digital twin service
+    selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+}
+
+impl MyDigitalTwinAdapter {
+    /// Creates a new MyDigitalTwinAdapter with the specified config
+    ///
+    /// # Arguments
+    /// - `config`: the config to use
+    /// - `selector`: the service discovery adapter selector to use
+    pub fn from_config(
+        config: Config,
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, DigitalTwinAdapterError> {
+        Ok(Self { config, selector })
+    }
+}
+
+#[async_trait]
+impl DigitalTwinAdapter for MyDigitalTwinAdapter {
+    /// Creates a new instance of a DigitalTwinAdapter with default settings
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, DigitalTwinAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            DigitalTwinAdapterError::io,
+            DigitalTwinAdapterError::deserialize,
+        )?;
+
+        Self::from_config(config, selector)
+    }
+
+    /// Gets the entity information based on the request
+    ///
+    /// # Arguments
+    /// - `request`: the request to send
+    async fn find_by_id(
+        &self,
+        request: FindByIdRequest,
+    ) -> Result<FindByIdResponse, DigitalTwinAdapterError> {
+        let uri = self
+            .selector
+            .lock()
+            .await
+            .get_service_uri(&self.config.service_discovery_id)
+            .await
+            .map_err(DigitalTwinAdapterError::communication)?;
+
+        // Replace this with a request to the digital twin service at `uri`
+        debug!(
+            "Finding entity {} in the digital twin service at {uri}",
+            request.entity_id
+        );
+
+        Err(DigitalTwinAdapterError::entity_not_found(format!(
+            "Entity {} not found",
+            request.entity_id
+        )))
+    }
+}
+
+#[cfg(test)]
+mod my_digital_twin_adapter_tests {
+    use super::*;
+
+    use freyja_common::{
+        digital_twin_adapter::DigitalTwinAdapterErrorKind,
+        service_discovery_adapter::ServiceDiscoveryAdapterError,
+    };
+    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+
+    fn create_adapter(selector: MockServiceDiscoveryAdapterSelector) -> MyDigitalTwinAdapter {
+        MyDigitalTwinAdapter::from_config(
+            Config {
+                service_discovery_id: String::from("digital_twin_service"),
+            },
+            Arc::new(Mutex::new(selector)),
+        )
+        .unwrap()
+    }
+
+    fn create_request() -> FindByIdRequest {
+        FindByIdRequest {
+            entity_id: String::from("dtmi:sdv:HVAC:AmbientAirTemperature;1"),
+        }
+    }
+
+    #[tokio::test]
+    async fn find_by_id_returns_entity_not_found() {
+        let mut selector = MockServiceDiscoveryAdapterSelector::new();
+        selector
+            .expect_get_service_uri()
+            .returning(|_| Ok(String::from("http://0.0.0.0:5010")));
+        let uut = create_adapter(selector);
+
+        let result = uut.find_by_id(create_request()).await;
+
+        assert!(result.is_err_and(|e| e.kind() == DigitalTwinAdapterErrorKind::EntityNotFound));
+    }
+
+    #[tokio::test]
+    async fn find_by_id_returns_err_when_service_not_found() {
+        let mut selector = MockServiceDiscoveryAdapterSelector::new();
+        selector
+            .expect_get_service_uri()
+            .returning(|_| Err(ServiceDiscoveryAdapterError::not_found("not found")));
+        let uut = create_adapter(selector);
+
+        let result = uut.find_by_id(create_request()).await;
+
+        assert!(result.is_err_and(|e| e.kind() == DigitalTwinAdapterErrorKind::Communication));
+    }
+}
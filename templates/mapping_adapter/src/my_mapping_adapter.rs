@@ -0,0 +1,157 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use log::debug;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils,
+    mapping_adapter::{
+        CheckForWorkRequest, CheckForWorkResponse, GetMappingRequest, GetMappingResponse,
+        MappingAdapter, MappingAdapterError,
+    },
+    out_dir,
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// A mapping adapter which gets mappings from a mapping service
+pub struct MyMappingAdapter {
+    /// The adapter config
+    config: Config,
+
+    /// The service discovery adapter selector used to find the mapping service
+    selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+}
+
+impl MyMappingAdapter {
+    /// Creates a new MyMappingAdapter with the specified config
+    ///
+    /// # Arguments
+    /// - `config`: the config to use
+    /// - `selector`: the service discovery adapter selector to use
+    pub fn from_config(
+        config: Config,
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, MappingAdapterError> {
+        Ok(Self { config, selector })
+    }
+
+    /// Gets the uri of the mapping service
+    async fn get_service_uri(&self) -> Result<String, MappingAdapterError> {
+        self.selector
+            .lock()
+            .await
+            .get_service_uri(&self.config.service_discovery_id)
+            .await
+            .map_err(MappingAdapterError::communication)
+    }
+}
+
+#[async_trait]
+impl MappingAdapter for MyMappingAdapter {
+    /// Creates a new instance of a MappingAdapter with default settings
+    ///
+    /// # Arguments
+    /// - `selector`: the service discovery adapter selector to use
+    fn create_new(
+        selector: Arc<Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, MappingAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            MappingAdapterError::io,
+            MappingAdapterError::deserialize,
+        )?;
+
+        Self::from_config(config, selector)
+    }
+
+    /// Checks for any additional work that the mapping service requires
+    ///
+    /// # Arguments
+    /// - `_request`: the request to send
+    async fn check_for_work(
+        &self,
+        _request: CheckForWorkRequest,
+    ) -> Result<CheckForWorkResponse, MappingAdapterError> {
+        let uri = self.get_service_uri().await?;
+
+        // Replace this with a request to the mapping service at `uri`
+        debug!("Checking for work from the mapping service at {uri}");
+
+        Ok(CheckForWorkResponse { has_work: false })
+    }
+
+    /// Gets the mapping from the mapping service
+    ///
+    /// # Arguments
+    /// - `_request`: the request to send
+    async fn get_mapping(
+        &self,
+        _request: GetMappingRequest,
+    ) -> Result<GetMappingResponse, MappingAdapterError> {
+        let uri = self.get_service_uri().await?;
+
+        // Replace this with a request to the mapping service at `uri`
+        debug!("Getting the mapping from the mapping service at {uri}");
+
+        Ok(GetMappingResponse {
+            map: HashMap::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod my_mapping_adapter_tests {
+    use super::*;
+
+    use freyja_common::service_discovery_adapter::ServiceDiscoveryAdapterError;
+    use freyja_test_common::mocks::MockServiceDiscoveryAdapterSelector;
+
+    fn create_adapter(selector: MockServiceDiscoveryAdapterSelector) -> MyMappingAdapter {
+        MyMappingAdapter::from_config(
+            Config {
+                service_discovery_id: String::from("mapping_service"),
+            },
+            Arc::new(Mutex::new(selector)),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn check_for_work_and_get_mapping_resolve_service() {
+        let mut selector = MockServiceDiscoveryAdapterSelector::new();
+        selector
+            .expect_get_service_uri()
+            .times(2)
+            .returning(|_| Ok(String::from("http://0.0.0.0:8888")));
+        let uut = create_adapter(selector);
+
+        assert!(uut
+            .check_for_work(CheckForWorkRequest {})
+            .await
+            .is_ok_and(|r| !r.has_work));
+        assert!(uut
+            .get_mapping(GetMappingRequest {})
+            .await
+            .is_ok_and(|r| r.map.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn check_for_work_returns_err_when_service_not_found() {
+        let mut selector = MockServiceDiscoveryAdapterSelector::new();
+        selector
+            .expect_get_service_uri()
+            .returning(|_| Err(ServiceDiscoveryAdapterError::not_found("not found")));
+        let uut = create_adapter(selector);
+
+        assert!(uut.check_for_work(CheckForWorkRequest {}).await.is_err());
+    }
+}
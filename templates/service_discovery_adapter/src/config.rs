@@ -0,0 +1,14 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the service discovery adapter template
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// A map of service ids to uris
+    pub services: HashMap<String, String>,
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+
+use crate::config::Config;
+use freyja_build_common::config_file_stem;
+use freyja_common::{
+    config_utils, out_dir,
+    service_discovery_adapter::{
+        ServiceDiscoveryAdapter, ServiceDiscoveryAdapterError, ServiceDiscoveryAdapterErrorKind,
+    },
+};
+
+/// A service discovery adapter which looks up service uris
+pub struct MyServiceDiscoveryAdapter {
+    /// The adapter config
+    config: Config,
+}
+
+impl MyServiceDiscoveryAdapter {
+    /// Creates a new MyServiceDiscoveryAdapter with the specified config
+    ///
+    /// # Arguments
+    /// - `config`: the config to use
+    pub fn from_config(config: Config) -> Result<Self, ServiceDiscoveryAdapterError> {
+        Ok(Self { config })
+    }
+}
+
+#[async_trait]
+impl ServiceDiscoveryAdapter for MyServiceDiscoveryAdapter {
+    /// Creates a new instance of a ServiceDiscoveryAdapter with default settings
+    fn create_new() -> Result<Self, ServiceDiscoveryAdapterError> {
+        let config = config_utils::read_from_files(
+            config_file_stem!(),
+            config_utils::JSON_EXT,
+            out_dir!(),
+            ServiceDiscoveryAdapterError::io,
+            ServiceDiscoveryAdapterError::deserialize,
+        )?;
+
+        Self::from_config(config)
+    }
+
+    /// Gets the name of this adapter. Used for diagnostic purposes.
+    fn get_adapter_name(&self) -> String {
+        String::from("MyServiceDiscoveryAdapter")
+    }
+
+    /// Gets the URI for the requested service
+    ///
+    /// # Arguments
+    /// - `id`: the service identifier
+    async fn get_service_uri<'a>(
+        &self,
+        id: &'a str,
+    ) -> Result<String, ServiceDiscoveryAdapterError> {
+        // Replace this with a query to your service discovery system
+        self.config
+            .services
+            .get(id)
+            .cloned()
+            .ok_or(ServiceDiscoveryAdapterErrorKind::NotFound.into())
+    }
+}
+
+#[cfg(test)]
+mod my_service_discovery_adapter_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn get_service_uri_returns_configured_uri() {
+        let uut = MyServiceDiscoveryAdapter::from_config(Config {
+            services: HashMap::from([(
+                String::from("sdv.ibeji/invehicle_digital_twin/1.0"),
+                String::from("http://0.0.0.0:5010"),
+            )]),
+        })
+        .unwrap();
+
+        let result = uut
+            .get_service_uri("sdv.ibeji/invehicle_digital_twin/1.0")
+            .await;
+        assert_eq!(result.unwrap(), "http://0.0.0.0:5010");
+
+        let result = uut.get_service_uri("unknown").await;
+        assert!(result.is_err_and(|e| e.kind() == ServiceDiscoveryAdapterErrorKind::NotFound));
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use std::{
+    fs,
     io::{stderr, Write},
     path::PathBuf,
 };
@@ -47,3 +48,43 @@ impl Drop for GRPCTestFixture {
         }
     }
 }
+
+/// A test fixture which manages a unique directory for tests which read or write files
+pub struct TempDirFixture {
+    /// The path to a directory in the temp directory.
+    /// This fixture creates the directory and deletes it along with its contents during teardown.
+    pub path: PathBuf,
+}
+
+impl TempDirFixture {
+    /// Create a new `TempDirFixture` that creates a unique directory
+    pub fn new() -> Self {
+        let path = std::env::temp_dir()
+            .as_path()
+            .join(Uuid::new_v4().as_hyphenated().to_string());
+
+        fs::create_dir_all(&path).expect("Error creating `TempDirFixture` directory");
+
+        Self { path }
+    }
+}
+
+impl Default for TempDirFixture {
+    /// Create a new `TempDirFixture` that creates a unique directory
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TempDirFixture {
+    /// Cleans up the fixture by deleting the directory and its contents
+    fn drop(&mut self) {
+        match fs::remove_dir_all(&self.path) {
+            Ok(_) => {}
+            Err(e) => {
+                write!(stderr(), "Error cleaning up `TempDirFixture`: {e:?}")
+                    .expect("Error writing to stderr");
+            }
+        }
+    }
+}
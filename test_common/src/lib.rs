@@ -4,5 +4,6 @@
 
 pub mod fixtures;
 pub mod mocks;
+pub mod simulated_cloud_adapter;
 
 pub use mockall;
@@ -13,12 +13,16 @@ use mockall::*;
 use tonic::{Request, Response, Status};
 
 use cloud_connector_proto::v1::{
-    cloud_connector_server::CloudConnector, UpdateDigitalTwinRequest, UpdateDigitalTwinResponse,
+    cloud_connector_server::CloudConnector, CheckForBackfillRequest, CheckForBackfillResponse,
+    UpdateDigitalTwinRequest, UpdateDigitalTwinResponse,
 };
 use freyja_common::{
-    cloud_adapter::{CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse},
+    blob_sink::{BlobSink, BlobSinkError},
+    cloud_adapter::{
+        BackfillRequest, CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse,
+    },
     data_adapter::DataAdapterFactory,
-    data_adapter_selector::{DataAdapterSelector, DataAdapterSelectorError},
+    data_adapter_selector::{DataAdapterInfo, DataAdapterSelector, DataAdapterSelectorError},
     digital_twin_adapter::{
         DigitalTwinAdapter, DigitalTwinAdapterError, FindByIdRequest, FindByIdResponse,
     },
@@ -29,11 +33,15 @@ use freyja_common::{
     },
     service_discovery_adapter::{ServiceDiscoveryAdapter, ServiceDiscoveryAdapterError},
     service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+    signal::EmissionPolicy,
 };
-use mapping_service_proto::v1::{
-    mapping_service_server::MappingService, CheckForWorkRequest as ProtoCheckForWorkRequest,
-    CheckForWorkResponse as ProtoCheckForWorkResponse, GetMappingRequest as ProtoGetMappingRequest,
-    GetMappingResponse as ProtoGetMappingResponse,
+use mapping_service_proto::{
+    v1::{
+        CheckForWorkRequest as ProtoCheckForWorkRequest,
+        CheckForWorkResponse as ProtoCheckForWorkResponse,
+        GetMappingRequest as ProtoGetMappingRequest,
+    },
+    v2::{mapping_service_server::MappingService, GetMappingResponse as ProtoGetMappingResponse},
 };
 
 mock! {
@@ -51,6 +59,21 @@ mock! {
             &self,
             cloud_message: CloudMessageRequest,
         ) -> Result<CloudMessageResponse, CloudAdapterError>;
+
+        async fn check_for_backfill(&self) -> Result<BackfillRequest, CloudAdapterError>;
+    }
+}
+
+mock! {
+    pub BlobSink {}
+
+    #[async_trait]
+    impl BlobSink for BlobSink {
+        fn create_new() -> Result<Self, BlobSinkError>
+        where
+            Self: Sized;
+
+        async fn upload(&self, key: &str, content: &[u8]) -> Result<String, BlobSinkError>;
     }
 }
 
@@ -64,15 +87,28 @@ mock! {
             factory: Box<dyn DataAdapterFactory + Send + Sync>
         ) -> Result<(), DataAdapterSelectorError>;
 
+        fn deregister(
+            &mut self,
+            factory_kind: &str
+        ) -> Result<(), DataAdapterSelectorError>;
+
         async fn create_or_update_adapter(
             &self,
-            entity: &Entity
+            entity: &Entity,
+            emission_policy: &EmissionPolicy
         ) -> Result<(), DataAdapterSelectorError>;
 
         async fn request_entity_value(
             &self,
             entity_id: &str
         ) -> Result<(), DataAdapterSelectorError>;
+
+        async fn migrate_entities(&self) -> Result<(), DataAdapterSelectorError>;
+
+        async fn get_adapter_info(
+            &self,
+            entity_id: &str
+        ) -> Result<DataAdapterInfo, DataAdapterSelectorError>;
     }
 }
 
@@ -131,6 +167,11 @@ mock! {
             &self,
             id: &'a str
         ) -> Result<String, ServiceDiscoveryAdapterError>;
+
+        async fn get_service_uri_candidates<'a>(
+            &self,
+            id: &'a str
+        ) -> Result<Vec<String>, ServiceDiscoveryAdapterError>;
     }
 }
 
@@ -143,6 +184,11 @@ mock! {
             &self,
             _request: Request<UpdateDigitalTwinRequest>,
         ) -> Result<Response<UpdateDigitalTwinResponse>, Status>;
+
+        async fn check_for_backfill(
+            &self,
+            _request: Request<CheckForBackfillRequest>,
+        ) -> Result<Response<CheckForBackfillResponse>, Status>;
     }
 }
 
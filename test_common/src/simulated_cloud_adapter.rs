@@ -0,0 +1,227 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use freyja_common::{
+    cloud_adapter::{CloudAdapter, CloudAdapterError, CloudMessageRequest, CloudMessageResponse},
+    service_discovery_adapter_selector::ServiceDiscoveryAdapterSelector,
+};
+
+/// How long the simulated cloud takes to respond to each message
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LatencyDistribution {
+    /// Every message takes the same time
+    Fixed(Duration),
+    /// Each message takes a time drawn uniformly between `min` and `max`
+    Uniform { min: Duration, max: Duration },
+    /// Each message takes a time drawn from an exponential distribution with the given mean,
+    /// which produces the occasional long delay of a congested network
+    Exponential { mean: Duration },
+}
+
+impl Default for LatencyDistribution {
+    fn default() -> Self {
+        Self::Fixed(Duration::ZERO)
+    }
+}
+
+/// The network conditions which a `SimulatedCloudAdapter` imposes on messages
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimulatedCloudAdapterConfig {
+    /// How long the cloud takes to respond to each message
+    pub latency: LatencyDistribution,
+
+    /// The probability that a message fails with a communication error, between 0 and 1
+    pub error_rate: f64,
+
+    /// The maximum number of messages per second that the cloud accepts, which must be positive.
+    /// Messages beyond this rate are queued until the link is free, as on a link with limited bandwidth.
+    /// If this is `None`, throughput isn't limited
+    pub max_messages_per_second: Option<f64>,
+
+    /// The seed of the random number generator, so that the same conditions can be reproduced
+    pub seed: u64,
+}
+
+impl Default for SimulatedCloudAdapterConfig {
+    fn default() -> Self {
+        Self {
+            latency: LatencyDistribution::default(),
+            error_rate: 0.0,
+            max_messages_per_second: None,
+            seed: 0,
+        }
+    }
+}
+
+/// What a `SimulatedCloudAdapter` has observed so far
+#[derive(Clone, Debug, Default)]
+pub struct SimulatedCloudAdapterStats {
+    /// The messages which the cloud accepted, in the order they were accepted
+    pub delivered: Vec<CloudMessageRequest>,
+
+    /// The number of messages which failed with a simulated error
+    pub failed: u64,
+
+    /// The total time spent waiting for the link to be free because of the throughput cap
+    pub total_queueing_delay: Duration,
+
+    /// The total simulated latency of every message
+    pub total_latency: Duration,
+
+    /// The longest simulated latency of any message
+    pub max_latency: Duration,
+}
+
+/// The mutable state of a `SimulatedCloudAdapter`
+struct SimulatedCloudAdapterState {
+    /// The state of the random number generator
+    rng: u64,
+
+    /// The earliest time at which the next message can use the link
+    next_free_slot: Option<Instant>,
+
+    /// What the adapter has observed so far
+    stats: SimulatedCloudAdapterStats,
+}
+
+/// A cloud adapter which simulates network conditions instead of sending messages anywhere.
+/// It's used by tests to characterize the emitter with realistic latency, errors, and limited throughput. Delays use Tokio's clock, so tests can run them instantly with paused time.
+/// Clones share the same simulated cloud, so a clone can be kept to read the stats after the adapter is moved.
+#[derive(Clone)]
+pub struct SimulatedCloudAdapter {
+    /// The simulated network conditions
+    config: SimulatedCloudAdapterConfig,
+
+    /// The mutable state, which is shared with clones of this adapter
+    state: Arc<Mutex<SimulatedCloudAdapterState>>,
+}
+
+impl SimulatedCloudAdapter {
+    /// Creates a new SimulatedCloudAdapter.
+    /// Returns an error if the throughput cap isn't a positive number of messages per second
+    ///
+    /// # Arguments
+    /// - `config`: the simulated network conditions
+    pub fn new(config: SimulatedCloudAdapterConfig) -> Result<Self, CloudAdapterError> {
+        if let Some(max_messages_per_second) = config.max_messages_per_second {
+            if !(max_messages_per_second > 0.0 && max_messages_per_second.is_finite()) {
+                return Err(CloudAdapterError::deserialize(format!(
+                    "The simulated cloud's max_messages_per_second must be positive, but it's {max_messages_per_second}"
+                )));
+            }
+        }
+
+        Ok(Self {
+            config,
+            state: Arc::new(Mutex::new(SimulatedCloudAdapterState {
+                rng: config.seed,
+                next_free_slot: None,
+                stats: SimulatedCloudAdapterStats::default(),
+            })),
+        })
+    }
+
+    /// Gets what the adapter has observed so far
+    pub fn stats(&self) -> SimulatedCloudAdapterStats {
+        self.state.lock().unwrap().stats.clone()
+    }
+}
+
+impl SimulatedCloudAdapterState {
+    /// Gets the next random number between 0 (inclusive) and 1 (exclusive).
+    /// This uses SplitMix64, which is fast and good enough for simulating network conditions
+    fn next_f64(&mut self) -> f64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Draws a latency from a distribution
+    ///
+    /// # Arguments
+    /// - `distribution`: the latency distribution
+    fn next_latency(&mut self, distribution: LatencyDistribution) -> Duration {
+        match distribution {
+            LatencyDistribution::Fixed(latency) => latency,
+            LatencyDistribution::Uniform { min, max } => {
+                min + max.saturating_sub(min).mul_f64(self.next_f64())
+            }
+            LatencyDistribution::Exponential { mean } => {
+                mean.mul_f64(-(1.0 - self.next_f64()).ln())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CloudAdapter for SimulatedCloudAdapter {
+    /// Creates a SimulatedCloudAdapter with ideal network conditions
+    ///
+    /// # Arguments
+    /// - `_selector`: the service discovery adapter selector, which is unused
+    fn create_new(
+        _selector: Arc<tokio::sync::Mutex<dyn ServiceDiscoveryAdapterSelector>>,
+    ) -> Result<Self, CloudAdapterError> {
+        Self::new(SimulatedCloudAdapterConfig::default())
+    }
+
+    /// Waits for the simulated link and latency, then accepts the message or fails with a simulated error
+    ///
+    /// # Arguments
+    /// - `cloud_message`: the message to send
+    async fn send_to_cloud(
+        &self,
+        cloud_message: CloudMessageRequest,
+    ) -> Result<CloudMessageResponse, CloudAdapterError> {
+        let (queueing_delay, latency, fails) = {
+            let mut state = self.state.lock().unwrap();
+
+            // Each message occupies the link for an equal share of a second, so messages which arrive
+            // while the link is busy wait for the previous ones
+            let now = Instant::now();
+            let start = match (self.config.max_messages_per_second, state.next_free_slot) {
+                (Some(_), Some(next_free_slot)) => next_free_slot.max(now),
+                _ => now,
+            };
+            if let Some(max_messages_per_second) = self.config.max_messages_per_second {
+                state.next_free_slot =
+                    Some(start + Duration::from_secs_f64(1.0 / max_messages_per_second));
+            }
+
+            let latency = state.next_latency(self.config.latency);
+            let fails = state.next_f64() < self.config.error_rate;
+
+            (start - now, latency, fails)
+        };
+
+        tokio::time::sleep(queueing_delay + latency).await;
+
+        let mut state = self.state.lock().unwrap();
+        state.stats.total_queueing_delay += queueing_delay;
+        state.stats.total_latency += latency;
+        state.stats.max_latency = state.stats.max_latency.max(latency);
+
+        if fails {
+            state.stats.failed += 1;
+            return Err(CloudAdapterError::communication(
+                "Simulated failure to send message",
+            ));
+        }
+
+        state.stats.delivered.push(cloud_message);
+        Ok(CloudMessageResponse::default())
+    }
+}